@@ -14,5 +14,7 @@ async fn main() {
 
     // Start the optimized runtime
     // No middleware = pure throughput test
-    NucleusRuntime::start(Some(routes), None).await;
+    NucleusRuntime::start(Some(routes), None)
+        .await
+        .expect("Atom Reactor failed");
 }
@@ -0,0 +1,421 @@
+//! Atom Assets - prerendered routes with HTTP caching semantics
+//!
+//! Every route body is hashed once when the route table is built (at startup
+//! and on hot swap), so requests only compare headers:
+//!
+//! - strong `ETag` + `Last-Modified`, answered with `304` on
+//!   `If-None-Match` / `If-Modified-Since`
+//! - `HEAD` returns headers and `Content-Length` without a body
+//! - single byte ranges (`Range`, `If-Range`) with `206` / `416`
+//! - `app.js.br` / `app.js.gz` next to `app.js` are served as-is to clients
+//!   that accept them, and [`compress`] leaves those responses alone
+//! - bodies compressed on the fly get a weak `ETag`, since the bytes no
+//!   longer match the identity validator
+
+use ahash::AHashMap;
+use axum::body::{Body, Bytes};
+use axum::http::{header, Extensions, HeaderMap, HeaderValue, Method, StatusCode, Version};
+use axum::middleware::map_response;
+use axum::response::Response;
+use axum::Router;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
+use tower_http::compression::CompressionLayer;
+
+/// Route table shared through `ArcSwap`
+pub type AssetMap = AHashMap<String, Asset>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ASSET
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One encoding of an asset body with its validator
+#[derive(Clone, Debug)]
+pub struct Representation {
+    pub body: Bytes,
+    pub etag: HeaderValue,
+}
+
+impl Representation {
+    fn new(body: Bytes) -> Self {
+        let digest = Sha256::digest(&body);
+        let etag = format!("\"{}\"", hex::encode(&digest[..16]));
+        Self {
+            body,
+            etag: HeaderValue::from_str(&etag).expect("hex etag is a valid header"),
+        }
+    }
+}
+
+/// A prerendered route body plus its precompressed variants
+#[derive(Clone, Debug)]
+pub struct Asset {
+    pub identity: Representation,
+    pub brotli: Option<Representation>,
+    pub gzip: Option<Representation>,
+    pub last_modified: SystemTime,
+}
+
+impl Asset {
+    /// Hash `body` and stamp it as modified now
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        // HTTP dates have second precision; truncate so comparisons line up
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            identity: Representation::new(body.into()),
+            brotli: None,
+            gzip: None,
+            last_modified: UNIX_EPOCH + Duration::from_secs(secs),
+        }
+    }
+
+    /// Attach a precompressed `br` body
+    pub fn with_brotli(mut self, body: impl Into<Bytes>) -> Self {
+        self.brotli = Some(Representation::new(body.into()));
+        self
+    }
+
+    /// Attach a precompressed `gzip` body
+    pub fn with_gzip(mut self, body: impl Into<Bytes>) -> Self {
+        self.gzip = Some(Representation::new(body.into()));
+        self
+    }
+
+    /// Uncompressed body
+    pub fn body(&self) -> &Bytes {
+        &self.identity.body
+    }
+
+    /// Build a response for `method` honouring conditional, range and
+    /// encoding headers in `req`
+    pub fn respond(
+        &self,
+        method: &Method,
+        req: &HeaderMap,
+        content_type: &'static str,
+        cache_control: &'static str,
+    ) -> Response {
+        let last_modified = HeaderValue::from_str(&httpdate::fmt_http_date(self.last_modified))
+            .expect("http date is a valid header");
+        let has_variants = self.brotli.is_some() || self.gzip.is_some();
+
+        // Ranges are only served from the identity body
+        let range = req
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .filter(|_| *method == Method::GET && self.if_range_matches(req))
+            .and_then(|v| parse_range(v, self.identity.body.len() as u64));
+
+        let (repr, encoding) = match range {
+            Some(_) => (&self.identity, None),
+            None => self.negotiate(req),
+        };
+
+        let mut res = Response::builder()
+            .header(header::ETAG, repr.etag.clone())
+            .header(header::LAST_MODIFIED, last_modified)
+            .header(header::CACHE_CONTROL, cache_control);
+        if has_variants {
+            res = res.header(header::VARY, "accept-encoding");
+        }
+
+        if self.not_modified(req, &repr.etag) {
+            return res
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Body::empty())
+                .unwrap();
+        }
+
+        res = res
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::ACCEPT_RANGES, "bytes");
+        if let Some(encoding) = encoding {
+            res = res.header(header::CONTENT_ENCODING, encoding);
+        }
+
+        let total = repr.body.len() as u64;
+        let (status, body) = match range {
+            Some(Ok((start, end))) => {
+                res = res.header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, total),
+                );
+                (
+                    StatusCode::PARTIAL_CONTENT,
+                    repr.body.slice(start as usize..=end as usize),
+                )
+            }
+            Some(Err(())) => {
+                return res
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", total))
+                    .body(Body::empty())
+                    .unwrap();
+            }
+            None => (StatusCode::OK, repr.body.clone()),
+        };
+
+        res = res
+            .status(status)
+            .header(header::CONTENT_LENGTH, body.len());
+        let body = if *method == Method::HEAD {
+            Body::empty()
+        } else {
+            Body::from(body)
+        };
+        res.body(body).unwrap()
+    }
+
+    /// Pick the best representation the client accepts
+    fn negotiate(&self, req: &HeaderMap) -> (&Representation, Option<&'static str>) {
+        let accept = req
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if let Some(br) = &self.brotli {
+            if accepts(accept, "br") {
+                return (br, Some("br"));
+            }
+        }
+        if let Some(gz) = &self.gzip {
+            if accepts(accept, "gzip") {
+                return (gz, Some("gzip"));
+            }
+        }
+        (&self.identity, None)
+    }
+
+    /// `If-None-Match` wins over `If-Modified-Since` (RFC 9110 §13.2.2)
+    fn not_modified(&self, req: &HeaderMap, etag: &HeaderValue) -> bool {
+        if let Some(inm) = req.get(header::IF_NONE_MATCH) {
+            return inm
+                .to_str()
+                .map(|v| etag_list_matches(v, etag))
+                .unwrap_or(false);
+        }
+        req.get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| httpdate::parse_http_date(v).ok())
+            .is_some_and(|since| self.last_modified <= since)
+    }
+
+    /// A missing `If-Range` always matches; a stale one downgrades to 200
+    fn if_range_matches(&self, req: &HeaderMap) -> bool {
+        let Some(value) = req.get(header::IF_RANGE).and_then(|v| v.to_str().ok()) else {
+            return true;
+        };
+        let value = value.trim();
+        if value.starts_with('"') {
+            // Strong comparison: weak tags never match
+            return value.as_bytes() == self.identity.etag.as_bytes();
+        }
+        if value.starts_with("W/") {
+            return false;
+        }
+        httpdate::parse_http_date(value).is_ok_and(|date| date == self.last_modified)
+    }
+}
+
+/// Index a route table, folding `key.br` / `key.gz` into `key`
+pub fn build_assets(routes: impl IntoIterator<Item = (String, Bytes)>) -> AssetMap {
+    let mut plain = AHashMap::new();
+    let mut compressed = Vec::new();
+    for (key, body) in routes {
+        if key.ends_with(".br") || key.ends_with(".gz") {
+            compressed.push((key, body));
+        } else {
+            plain.insert(key, Asset::new(body));
+        }
+    }
+
+    for (key, body) in compressed {
+        let (base, ext) = key.split_at(key.len() - 3);
+        match plain.get_mut(base) {
+            Some(asset) if ext == ".br" => asset.brotli = Some(Representation::new(body)),
+            Some(asset) => asset.gzip = Some(Representation::new(body)),
+            // No uncompressed sibling: serve the file itself
+            None => {
+                plain.insert(key, Asset::new(body));
+            }
+        }
+    }
+    plain
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Compression
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Set on responses that already carried `Content-Encoding` before compression
+#[derive(Clone, Copy)]
+struct PreEncoded;
+
+/// Compress `router` responses on the fly with `br` / `gzip`
+///
+/// Precompressed variants pass through untouched with their own strong
+/// `ETag`; anything compressed here gets its `ETag` weakened so it is never
+/// mistaken for the identity bytes (e.g. by `If-Range`).
+pub fn compress(router: Router) -> Router {
+    let not_encoded = |_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions| {
+        !headers.contains_key(header::CONTENT_ENCODING)
+    };
+    router
+        .layer(map_response(mark_pre_encoded))
+        .layer(
+            CompressionLayer::new()
+                .br(true)
+                .gzip(true)
+                .compress_when(DefaultPredicate::new().and(not_encoded)),
+        )
+        .layer(map_response(weaken_compressed_etag))
+}
+
+async fn mark_pre_encoded(mut res: Response) -> Response {
+    if res.headers().contains_key(header::CONTENT_ENCODING) {
+        res.extensions_mut().insert(PreEncoded);
+    }
+    res
+}
+
+async fn weaken_compressed_etag(mut res: Response) -> Response {
+    if !res.headers().contains_key(header::CONTENT_ENCODING)
+        || res.extensions().get::<PreEncoded>().is_some()
+    {
+        return res;
+    }
+    let weak = res
+        .headers()
+        .get(header::ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .and_then(|etag| HeaderValue::from_bytes(&[b"W/", etag.as_bytes()].concat()).ok());
+    if let Some(weak) = weak {
+        res.headers_mut().insert(header::ETAG, weak);
+    }
+    res
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Header parsing
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Whether `Accept-Encoding` allows `coding` with a non-zero q-value
+fn accepts(accept: &str, coding: &str) -> bool {
+    let mut wildcard = false;
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or("").trim();
+        let q = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if name.eq_ignore_ascii_case(coding) {
+            return q > 0.0;
+        }
+        if name == "*" {
+            wildcard = q > 0.0;
+        }
+    }
+    wildcard
+}
+
+/// Weak comparison against a comma separated `If-None-Match` list
+fn etag_list_matches(list: &str, etag: &HeaderValue) -> bool {
+    let etag = etag.to_str().unwrap_or("");
+    list.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// Parse a single `bytes=` range against a body of `len` bytes.
+///
+/// `None` means the header should be ignored (malformed or multiple ranges),
+/// `Some(Err(()))` means it is unsatisfiable.
+fn parse_range(value: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.trim().split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+        // Suffix range: the last N bytes
+        let n: u64 = end.parse().ok()?;
+        if n == 0 || len == 0 {
+            return Some(Err(()));
+        }
+        (len.saturating_sub(n), len - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            end.parse().ok()?
+        };
+        if end < start {
+            return None;
+        }
+        if start >= len {
+            return Some(Err(()));
+        }
+        (start, end.min(len - 1))
+    };
+    Some(Ok(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some(Ok((0, 9))));
+        assert_eq!(parse_range("bytes=90-", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=-10", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=50-500", 100), Some(Ok((50, 99))));
+        assert_eq!(parse_range("bytes=100-", 100), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+        assert_eq!(parse_range("bytes=9-0", 100), None);
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(accepts("gzip, deflate, br", "br"));
+        assert!(accepts("br;q=0.5, gzip", "br"));
+        assert!(!accepts("br;q=0, gzip", "br"));
+        assert!(accepts("*", "gzip"));
+        assert!(!accepts("identity", "gzip"));
+    }
+
+    #[test]
+    fn test_build_assets_folds_variants() {
+        let assets = build_assets(vec![
+            ("app.js".to_string(), Bytes::from("console.log(1)")),
+            ("app.js.br".to_string(), Bytes::from_static(b"\x0b\x06")),
+            ("app.js.gz".to_string(), Bytes::from_static(b"\x1f\x8b")),
+            ("orphan.gz".to_string(), Bytes::from_static(b"\x1f\x8b")),
+        ]);
+        assert_eq!(assets.len(), 2);
+        let app = &assets["app.js"];
+        assert!(app.brotli.is_some() && app.gzip.is_some());
+        assert_ne!(app.identity.etag, app.brotli.as_ref().unwrap().etag);
+        assert!(assets.contains_key("orphan.gz"));
+    }
+
+    #[test]
+    fn test_etag_is_stable() {
+        let a = Asset::new("hello");
+        let b = Asset::new("hello");
+        assert_eq!(a.identity.etag, b.identity.etag);
+        assert_ne!(a.identity.etag, Asset::new("world").identity.etag);
+        assert!(etag_list_matches(
+            &format!("\"x\", W/{}", a.identity.etag.to_str().unwrap()),
+            &a.identity.etag
+        ));
+    }
+}
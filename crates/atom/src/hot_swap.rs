@@ -1,5 +1,4 @@
-use ahash::AHashMap;
-use axum::body::Bytes;
+use crate::assets::{Asset, AssetMap};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::Path;
 use std::sync::Arc;
@@ -8,15 +7,12 @@ use tokio::sync::mpsc;
 use arc_swap::ArcSwap;
 
 pub struct HotSwapListener {
-    routes: Arc<ArcSwap<AHashMap<String, Bytes>>>,
+    routes: Arc<ArcSwap<AssetMap>>,
     tx: tokio::sync::broadcast::Sender<String>,
 }
 
 impl HotSwapListener {
-    pub fn new(
-        routes: Arc<ArcSwap<AHashMap<String, Bytes>>>,
-        tx: tokio::sync::broadcast::Sender<String>,
-    ) -> Self {
+    pub fn new(routes: Arc<ArcSwap<AssetMap>>, tx: tokio::sync::broadcast::Sender<String>) -> Self {
         Self { routes, tx }
     }
 
@@ -78,8 +74,8 @@ impl HotSwapListener {
                 let current = self.routes.load();
                 // 2. Clone it (New allocation)
                 let mut new_map = (**current).clone();
-                // 3. Update new map
-                new_map.insert(stem, Bytes::from(html));
+                // 3. Update new map (re-hashes the ETag)
+                new_map.insert(stem, Asset::new(html));
                 // 4. Atomic Store
                 self.routes.store(Arc::new(new_map));
 
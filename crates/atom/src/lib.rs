@@ -1,21 +1,26 @@
+pub mod assets;
 #[cfg(feature = "hot-reload")]
 pub mod hot_swap;
 pub mod memory;
 pub mod middleware;
 pub mod runtime;
+pub mod server;
 
 pub use runtime::NucleusRuntime;
 
 use std::collections::HashMap;
 
+use axum::body::Bytes;
+use nucleus_std::config::GLOBAL_CONFIG;
 use nucleus_std::stream::StreamHandler;
 use std::sync::Arc;
 
+/// Start with raw route bodies, so binary assets and `.br`/`.gz` files survive
 pub async fn start_reactor(
-    routes: Option<HashMap<String, String>>,
+    routes: Option<HashMap<String, Bytes>>,
     handler: Option<Arc<dyn StreamHandler>>,
-) {
-    NucleusRuntime::start(routes, handler).await;
+) -> std::io::Result<()> {
+    NucleusRuntime::start_with_config(&GLOBAL_CONFIG.server, routes, handler, None).await
 }
 
 #[cfg(test)]
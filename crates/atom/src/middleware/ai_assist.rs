@@ -208,7 +208,7 @@ pub fn analyze_error_sync(error: &str, status: StatusCode) -> ErrorAnalysis {
                 "Table '{}' not found. Run migrations or check the table name.",
                 table
             ));
-            analysis.fix_command = Some("nucleus db migrate".to_string());
+            analysis.fix_command = Some("nucleus db up".to_string());
         }
     }
     // Connection errors
@@ -306,7 +306,7 @@ mod tests {
             analyze_error_sync("no such table: 'users'", StatusCode::INTERNAL_SERVER_ERROR);
         assert!(analysis.suggestion.is_some());
         assert!(analysis.fix_command.is_some());
-        assert_eq!(analysis.fix_command.unwrap(), "nucleus db migrate");
+        assert_eq!(analysis.fix_command.unwrap(), "nucleus db up");
     }
 
     #[test]
@@ -4,6 +4,7 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         State,
     },
+    http::{HeaderMap, Method},
     response::{Html, IntoResponse},
     routing::get,
     Router,
@@ -11,9 +12,8 @@ use axum::{
 use futures::{SinkExt, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tower_http::compression::CompressionLayer;
 
 #[cfg(feature = "hot-reload")]
 use crate::hot_swap::HotSwapListener;
@@ -21,14 +21,16 @@ use crate::hot_swap::HotSwapListener;
 use crate::memory::arena_middleware;
 #[cfg(feature = "middleware-fortress")]
 use crate::middleware::fortress; // Fortress
+use crate::server::{self, Listener};
+use nucleus_std::config::{ServerConfig, GLOBAL_CONFIG};
 use nucleus_std::stream::{SocketMessage, StreamHandler, StreamHub, WebSocket as NucleusWebSocket};
 
-use ahash::AHashMap;
+use crate::assets::{build_assets, compress, AssetMap};
 use arc_swap::ArcSwap;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub routes: Arc<ArcSwap<AHashMap<String, Bytes>>>,
+    pub routes: Arc<ArcSwap<AssetMap>>,
     pub stream_handler: Option<Arc<dyn StreamHandler>>,
     pub stream_hub: Arc<StreamHub>,
     pub tx: tokio::sync::broadcast::Sender<String>,
@@ -52,21 +54,32 @@ impl NucleusRuntime {
     pub async fn start(
         routes: Option<HashMap<String, String>>,
         stream_handler: Option<Arc<dyn StreamHandler>>,
-    ) {
-        Self::start_with_router(routes, stream_handler, None).await;
+    ) -> std::io::Result<()> {
+        Self::start_with_router(routes, stream_handler, None).await
     }
 
+    /// Start with `[server]` from `nucleus.config`
     pub async fn start_with_router(
         routes: Option<HashMap<String, String>>,
         stream_handler: Option<Arc<dyn StreamHandler>>,
         extra_router: Option<Router>,
-    ) {
-        // Optimize: Convert String -> Bytes for zero-copy cloning
-        let optimized_routes: AHashMap<String, Bytes> = routes
-            .unwrap_or_default()
-            .into_iter()
-            .map(|(k, v)| (k, Bytes::from(v)))
-            .collect();
+    ) -> std::io::Result<()> {
+        let routes = routes.map(|r| r.into_iter().map(|(k, v)| (k, Bytes::from(v))).collect());
+        Self::start_with_config(&GLOBAL_CONFIG.server, routes, stream_handler, extra_router).await
+    }
+
+    /// Bind per `config` and serve until SIGTERM/SIGINT, then shut down gracefully
+    pub async fn start_with_config(
+        config: &ServerConfig,
+        routes: Option<HashMap<String, Bytes>>,
+        stream_handler: Option<Arc<dyn StreamHandler>>,
+        extra_router: Option<Router>,
+    ) -> std::io::Result<()> {
+        // Bind first so a taken port fails before any background work starts
+        let listener = Listener::bind(config).await?;
+
+        // Bytes for zero-copy cloning; ETags and .br/.gz variants resolved once here
+        let optimized_routes = build_assets(routes.unwrap_or_default());
 
         // Use ArcSwap for Wait-Free Reads + HMR Support (Enhanced Safe Mode)
         let routes = Arc::new(ArcSwap::from_pointee(optimized_routes));
@@ -102,10 +115,11 @@ impl NucleusRuntime {
             });
         }
 
+        let stream_hub = Arc::new(StreamHub::new());
         let state = AppState {
             routes,
             stream_handler,
-            stream_hub: Arc::new(StreamHub::new()),
+            stream_hub: stream_hub.clone(),
             tx,
             is_dev,
             cached_date,
@@ -119,7 +133,7 @@ impl NucleusRuntime {
             app = app.merge(router);
         }
 
-        let app = app.layer(CompressionLayer::new().br(true).gzip(true));
+        let app = compress(app);
 
         #[cfg(feature = "middleware-fortress")]
         let app = app.layer(axum::middleware::from_fn(fortress::fortress));
@@ -136,13 +150,15 @@ impl NucleusRuntime {
         ));
 
         // Start Reactor
-        println!("Atom Reactor starting on 0.0.0.0:3000");
-        let listener = TcpListener::bind("0.0.0.0:3000")
-            .await
-            .expect("Failed to bind to port 3000. Is the port already in use?");
-        axum::serve(listener, app)
-            .await
-            .expect("Server failed to start");
+        println!("Atom Reactor starting on {}", listener.describe());
+        server::serve(
+            listener,
+            app,
+            stream_hub,
+            Duration::from_secs(config.shutdown_timeout),
+            server::shutdown_signal(),
+        )
+        .await
     }
 
     pub fn make_router(state: AppState) -> Router {
@@ -172,7 +188,12 @@ async fn plaintext_handler() -> impl IntoResponse {
     )
 }
 
-async fn dynamic_handler(State(state): State<AppState>, uri: axum::http::Uri) -> impl IntoResponse {
+async fn dynamic_handler(
+    State(state): State<AppState>,
+    method: Method,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let path = uri.path();
 
     // Optimization: ArcSwap load (Wait-Free)
@@ -180,10 +201,11 @@ async fn dynamic_handler(State(state): State<AppState>, uri: axum::http::Uri) ->
     let routes = state.routes.load();
 
     let key = if path == "/" { "home" } else { &path[1..] };
-    if let Some(content) = routes.get(key).or_else(|| routes.get(path)) {
+    if let Some(asset) = routes.get(key).or_else(|| routes.get(path)) {
         // HOT PATH OPTIMIZATION:
         // In Production, we avoid string allocation and utf8 validation.
-        // We simply clone the Bytes (cheap ref-count incr) and return.
+        // We simply clone the Bytes (cheap ref-count incr) and return,
+        // or answer 304/206 from validators computed at load time.
         if !state.is_dev {
             // Note: Middleware handles Date headers now.
             // Determine MIME type and Cache-Control based on extension
@@ -203,20 +225,10 @@ async fn dynamic_handler(State(state): State<AppState>, uri: axum::http::Uri) ->
             } else if key == "plaintext" {
                 ("text/plain; charset=utf-8", "no-cache") // Benchmark compliance
             } else {
-                (
-                    "text/html; charset=utf-8",
-                    "no-cache, no-store, must-revalidate",
-                ) // HTML should not be cached
+                ("text/html; charset=utf-8", "no-cache") // HTML is revalidated via ETag
             };
 
-            return (
-                [
-                    (axum::http::header::CONTENT_TYPE, content_type),
-                    (axum::http::header::CACHE_CONTROL, cache_control),
-                ],
-                content.clone(),
-            )
-                .into_response();
+            return asset.respond(&method, &headers, content_type, cache_control);
         }
 
         // DEVELOPMENT MODE SLOW PATH:
@@ -235,7 +247,7 @@ async fn dynamic_handler(State(state): State<AppState>, uri: axum::http::Uri) ->
             "text/html; charset=utf-8"
         };
 
-        let mut body = String::from_utf8_lossy(content).to_string();
+        let mut body = String::from_utf8_lossy(asset.body()).to_string();
 
         // Inject DevTools (Only for HTML)
         if content_type.starts_with("text/html") {
@@ -277,6 +289,9 @@ async fn handle_socket(
         let handler_clone = handler.clone();
         let hub_clone = hub.clone();
 
+        // Track the socket so graceful shutdown can close it
+        hub.register(n_socket.clone()).await;
+
         // Notify connect
         handler.on_connect(&hub, &n_socket).await;
 
@@ -334,6 +349,7 @@ async fn handle_socket(
         };
 
         handler.on_disconnect(&hub, &socket_id).await;
+        hub.unregister(&socket_id).await;
     } else {
         // Echo fallback if no handler (Still support HMR?)
         // Ideally checking for HMR here too, but for now focusing on app mode
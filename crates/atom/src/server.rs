@@ -0,0 +1,304 @@
+//! Atom Server - listeners, TLS and graceful shutdown
+//!
+//! Binds the address from `[server]` in `nucleus.config`:
+//!
+//! ```toml
+//! [server]
+//! host = "0.0.0.0"
+//! port = 443
+//! shutdown_timeout = 30
+//!
+//! [server.tls]
+//! cert = "certs/fullchain.pem"
+//! key = "certs/privkey.pem"
+//! ```
+//!
+//! Setting `unix_socket = "/run/app.sock"` listens on a Unix domain socket
+//! instead. On SIGTERM/SIGINT the listener stops accepting, in-flight requests
+//! finish and every WebSocket in the `StreamHub` is asked to close, up to
+//! `shutdown_timeout` seconds.
+
+use axum::extract::connect_info::ConnectInfo;
+use axum::http::Request;
+use axum::Router;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::server::graceful::{GracefulShutdown, Watcher};
+use hyper_util::service::TowerToHyperService;
+use nucleus_std::config::{ServerConfig, TlsConfig};
+use nucleus_std::stream::StreamHub;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+
+/// How long a client gets to finish the TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ═══════════════════════════════════════════════════════════════════════════
+// LISTENER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A bound socket the reactor accepts connections from
+pub enum Listener {
+    Tcp(TcpListener),
+    Tls(TcpListener, TlsAcceptor),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+impl Listener {
+    /// Bind according to `config`: Unix socket, TLS or plain TCP
+    pub async fn bind(config: &ServerConfig) -> io::Result<Self> {
+        if let Some(path) = &config.unix_socket {
+            return Self::bind_unix(path);
+        }
+
+        let addr = config.bind_address();
+        let tcp = TcpListener::bind(&addr)
+            .await
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to bind {}: {}", addr, e)))?;
+
+        match &config.tls {
+            Some(tls) => Ok(Listener::Tls(tcp, tls_acceptor(tls)?)),
+            None => Ok(Listener::Tcp(tcp)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(path: &str) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        // A socket file left behind by a previous run would make bind fail
+        if let Ok(meta) = std::fs::symlink_metadata(path) {
+            if meta.file_type().is_socket() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        let listener = tokio::net::UnixListener::bind(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to bind {}: {}", path, e)))?;
+        Ok(Listener::Unix(listener, path.into()))
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(_path: &str) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "unix_socket is only supported on Unix platforms",
+        ))
+    }
+
+    /// Local TCP address, if listening on one
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Listener::Tcp(tcp) | Listener::Tls(tcp, _) => tcp.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(..) => None,
+        }
+    }
+
+    /// Human readable address for the startup banner
+    pub fn describe(&self) -> String {
+        match self {
+            Listener::Tcp(tcp) => format!("http://{}", display_addr(tcp)),
+            Listener::Tls(tcp, _) => format!("https://{}", display_addr(tcp)),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => format!("unix:{}", path.display()),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn display_addr(tcp: &TcpListener) -> String {
+    tcp.local_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|_| "?".to_string())
+}
+
+/// Build a TLS acceptor from PEM certificate chain and private key files
+pub fn tls_acceptor(config: &TlsConfig) -> io::Result<TlsAcceptor> {
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+    let certs = CertificateDer::pem_file_iter(&config.cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalid(format!("TLS certificate {}: {}", config.cert, e)))?;
+    if certs.is_empty() {
+        return Err(invalid(format!(
+            "TLS certificate {}: no certificates found",
+            config.cert
+        )));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key)
+        .map_err(|e| invalid(format!("TLS key {}: {}", config.key, e)))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut server = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .and_then(|b| b.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| invalid(format!("TLS configuration: {}", e)))?;
+    server.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(server)))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SERVE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Serve `app` until `shutdown` resolves, then drain connections.
+///
+/// In-flight requests and sockets registered in `hub` get `timeout` to finish;
+/// anything still open after that is dropped.
+pub async fn serve<F>(
+    listener: Listener,
+    app: Router,
+    hub: Arc<StreamHub>,
+    timeout: Duration,
+    shutdown: F,
+) -> io::Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            accepted = accept(&listener) => match accepted {
+                Ok(Accepted::Plain(tcp, remote)) => {
+                    spawn_connection(tcp, app.clone(), Some(remote), graceful.watcher());
+                }
+                Ok(Accepted::Tls(tcp, remote, acceptor)) => {
+                    let app = app.clone();
+                    let watcher = graceful.watcher();
+                    tokio::spawn(async move {
+                        let handshake = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(tcp));
+                        if let Ok(Ok(stream)) = handshake.await {
+                            spawn_connection(stream, app, Some(remote), watcher);
+                        }
+                    });
+                }
+                #[cfg(unix)]
+                Ok(Accepted::Unix(stream)) => {
+                    spawn_connection(stream, app.clone(), None, graceful.watcher());
+                }
+                // Usually EMFILE; back off instead of spinning
+                Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+            },
+            _ = &mut shutdown => break,
+        }
+    }
+
+    drop(listener);
+    println!(
+        "Atom Reactor shutting down ({} connections, {}s deadline)",
+        graceful.count(),
+        timeout.as_secs()
+    );
+
+    let drained = tokio::time::timeout(timeout, async {
+        tokio::join!(graceful.shutdown(), hub.drain(timeout));
+    })
+    .await;
+    if drained.is_err() {
+        println!("Atom Reactor shutdown deadline reached, closing remaining connections");
+    }
+
+    Ok(())
+}
+
+/// Resolves on SIGINT (Ctrl+C) or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Connection plumbing
+// ─────────────────────────────────────────────────────────────────────────────
+
+enum Accepted {
+    Plain(tokio::net::TcpStream, SocketAddr),
+    Tls(tokio::net::TcpStream, SocketAddr, TlsAcceptor),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+async fn accept(listener: &Listener) -> io::Result<Accepted> {
+    match listener {
+        Listener::Tcp(tcp) => {
+            let (stream, remote) = tcp.accept().await?;
+            Ok(Accepted::Plain(stream, remote))
+        }
+        Listener::Tls(tcp, acceptor) => {
+            let (stream, remote) = tcp.accept().await?;
+            Ok(Accepted::Tls(stream, remote, acceptor.clone()))
+        }
+        #[cfg(unix)]
+        Listener::Unix(unix, _) => {
+            let (stream, _) = unix.accept().await?;
+            Ok(Accepted::Unix(stream))
+        }
+    }
+}
+
+fn spawn_connection<I>(io: I, app: Router, remote: Option<SocketAddr>, watcher: Watcher)
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // Same extension axum::serve provides, so ConnectInfo<SocketAddr> keeps working
+    let app = app.map_request(move |mut req: Request<Incoming>| {
+        if let Some(addr) = remote {
+            req.extensions_mut().insert(ConnectInfo(addr));
+        }
+        req
+    });
+
+    let conn = Builder::new(TokioExecutor::new())
+        .serve_connection_with_upgrades(TokioIo::new(io), TowerToHyperService::new(app))
+        .into_owned();
+    let conn = watcher.watch(conn);
+
+    tokio::spawn(async move {
+        // Client resets and protocol errors are not actionable here
+        let _ = conn.await;
+    });
+}
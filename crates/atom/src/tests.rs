@@ -1,3 +1,4 @@
+use crate::assets::Asset;
 use crate::runtime::{AppState, NucleusRuntime};
 use ahash::AHashMap;
 use arc_swap::ArcSwap;
@@ -42,10 +43,7 @@ async fn test_runtime_404_compliance() {
 async fn test_runtime_benchmark_route() {
     // Test "Hello World" benchmark path logic
     let mut routes = AHashMap::new();
-    routes.insert(
-        "plaintext".to_string(),
-        axum::body::Bytes::from("Hello World"),
-    );
+    routes.insert("plaintext".to_string(), Asset::new("Hello World"));
 
     let now = chrono::Utc::now().to_rfc2822().replace("+0000", "GMT");
     let state = AppState {
@@ -86,10 +84,7 @@ async fn test_runtime_benchmark_route() {
 async fn test_runtime_mime_types() {
     // Test CSS Content-Type
     let mut routes = AHashMap::new();
-    routes.insert(
-        "styles.css".to_string(),
-        axum::body::Bytes::from("body { color: red; }"),
-    );
+    routes.insert("styles.css".to_string(), Asset::new("body { color: red; }"));
 
     let now = chrono::Utc::now().to_rfc2822().replace("+0000", "GMT");
     let state = AppState {
@@ -117,3 +112,305 @@ async fn test_runtime_mime_types() {
     let headers = res.headers();
     assert_eq!(headers["content-type"], "text/css");
 }
+
+#[tokio::test]
+async fn test_server_graceful_shutdown_finishes_in_flight() {
+    use crate::server::{self, Listener};
+    use nucleus_std::config::ServerConfig;
+    use std::time::Duration;
+
+    let config = ServerConfig {
+        host: "127.0.0.1".to_string(),
+        port: 0,
+        ..Default::default()
+    };
+    let listener = Listener::bind(&config).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(listener.describe().starts_with("http://127.0.0.1:"));
+
+    let started = Arc::new(tokio::sync::Notify::new());
+    let handler_started = started.clone();
+    let app = axum::Router::new().route(
+        "/slow",
+        axum::routing::get(move || async move {
+            handler_started.notify_one();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "done"
+        }),
+    );
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(server::serve(
+        listener,
+        app,
+        Arc::new(StreamHub::new()),
+        Duration::from_secs(5),
+        async {
+            let _ = stop_rx.await;
+        },
+    ));
+
+    let request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+    started.notified().await;
+    stop_tx.send(()).unwrap();
+
+    let res = request.await.unwrap().unwrap();
+    assert_eq!(res.text().await.unwrap(), "done");
+    server.await.unwrap().unwrap();
+
+    // Listener is closed once shutdown begins
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_server_bind_errors() {
+    use crate::server::{tls_acceptor, Listener};
+    use nucleus_std::config::{ServerConfig, TlsConfig};
+
+    let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ServerConfig {
+        host: "127.0.0.1".to_string(),
+        port: taken.local_addr().unwrap().port(),
+        ..Default::default()
+    };
+    let err = Listener::bind(&config).await.err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    let err = tls_acceptor(&TlsConfig {
+        cert: "/nonexistent/cert.pem".to_string(),
+        key: "/nonexistent/key.pem".to_string(),
+    })
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("/nonexistent/cert.pem"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_server_unix_socket() {
+    use crate::server::Listener;
+    use nucleus_std::config::ServerConfig;
+
+    let path = std::env::temp_dir().join(format!("atom-test-{}.sock", std::process::id()));
+    let config = ServerConfig {
+        unix_socket: Some(path.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+
+    let listener = Listener::bind(&config).await.unwrap();
+    assert!(listener.local_addr().is_none());
+    assert!(tokio::net::UnixStream::connect(&path).await.is_ok());
+
+    // Dropping the listener removes the socket file; a stale one is replaced
+    drop(listener);
+    assert!(!path.exists());
+    let _stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    let listener = Listener::bind(&config).await.unwrap();
+    assert_eq!(listener.describe(), format!("unix:{}", path.display()));
+}
+
+fn asset_app(assets: crate::assets::AssetMap) -> axum::Router {
+    let now = chrono::Utc::now().to_rfc2822().replace("+0000", "GMT");
+    NucleusRuntime::make_router(AppState {
+        routes: Arc::new(ArcSwap::from_pointee(assets)),
+        stream_handler: None,
+        stream_hub: Arc::new(StreamHub::new()),
+        tx: tokio::sync::broadcast::channel(1).0,
+        is_dev: false,
+        cached_date: Arc::new(ArcSwap::from_pointee(now)),
+    })
+}
+
+#[tokio::test]
+async fn test_runtime_conditional_requests() {
+    let mut routes = AHashMap::new();
+    routes.insert("home".to_string(), Asset::new("<h1>Home</h1>"));
+    let app = asset_app(routes);
+
+    let res = app
+        .clone()
+        .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::OK);
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+    let etag = res.headers()["etag"].clone();
+    let last_modified = res.headers()["last-modified"].clone();
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header("if-none-match", etag.clone())
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()["etag"], etag);
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header("if-modified-since", last_modified)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::NOT_MODIFIED);
+
+    // A stale ETag takes precedence over a fresh date
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header("if-none-match", "\"stale\"")
+                .header("if-modified-since", "Fri, 01 Jan 2100 00:00:00 GMT")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_runtime_head_and_ranges() {
+    let mut routes = AHashMap::new();
+    routes.insert("video.js".to_string(), Asset::new("0123456789"));
+    let app = asset_app(routes);
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("HEAD")
+                .uri("/video.js")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::OK);
+    assert_eq!(res.headers()["content-length"], "10");
+    assert_eq!(res.headers()["accept-ranges"], "bytes");
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.is_empty());
+
+    let res = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/video.js")
+                .header("range", "bytes=2-5")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.headers()["content-range"], "bytes 2-5/10");
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(body, "2345");
+
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/video.js")
+                .header("range", "bytes=20-")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(res.headers()["content-range"], "bytes */10");
+}
+
+#[tokio::test]
+async fn test_runtime_precompressed_variants() {
+    let assets = crate::assets::build_assets(vec![
+        ("app.js".to_string(), "console.log('hi')".into()),
+        ("app.js.br".to_string(), "BROTLI".into()),
+        ("app.js.gz".to_string(), "GZIP".into()),
+    ]);
+    let app = crate::assets::compress(asset_app(assets));
+
+    for (accept, encoding, body) in [
+        ("gzip, br", Some("br"), "BROTLI"),
+        ("gzip", Some("gzip"), "GZIP"),
+        ("identity", None, "console.log('hi')"),
+    ] {
+        let res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/app.js")
+                    .header("accept-encoding", accept)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["vary"], "accept-encoding");
+        assert_eq!(
+            res.headers()
+                .get("content-encoding")
+                .map(|v| v.to_str().unwrap()),
+            encoding
+        );
+        assert_eq!(res.headers()["content-type"], "application/javascript");
+        // Variants are never recompressed and keep their own strong ETag
+        assert!(res.headers()["etag"].to_str().unwrap().starts_with('"'));
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(bytes, body);
+    }
+}
+
+#[tokio::test]
+async fn test_runtime_compressed_etag_is_weak() {
+    let html = format!("<main>{}</main>", "Hello, Atom! ".repeat(32));
+    let mut routes = AHashMap::new();
+    routes.insert("home".to_string(), Asset::new(html.clone()));
+    let app = crate::assets::compress(asset_app(routes));
+
+    let request = |accept: &str| {
+        Request::builder()
+            .uri("/")
+            .header("accept-encoding", accept)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let plain = app.clone().oneshot(request("identity")).await.unwrap();
+    let strong = plain.headers()["etag"].to_str().unwrap().to_string();
+    assert!(strong.starts_with('"'));
+
+    let res = app.clone().oneshot(request("gzip")).await.unwrap();
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.headers()["etag"], format!("W/{}", strong).as_str());
+
+    // The weak tag still revalidates
+    let res = app
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header("accept-encoding", "gzip")
+                .header("if-none-match", format!("W/{}", strong))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::NOT_MODIFIED);
+}
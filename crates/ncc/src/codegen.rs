@@ -101,7 +101,7 @@ pub fn generate_nodes_handler_body(nodes: &[Node], fn_name: &str) -> String {
         render_node_to_body(node, &mut func_body);
     }
     format!(
-        "#[allow(non_snake_case, unreachable_code, unused_variables)]\nasync fn {}(headers: axum::http::HeaderMap, Query(params): Query<std::collections::HashMap<String, String>>) -> impl axum::response::IntoResponse {{\n    {}\n    axum::response::Html(html_body).into_response()\n}}\n\n",
+        "#[allow(non_snake_case, unreachable_code, unused_variables)]\nasync fn {}(headers: axum::http::HeaderMap, csrf_token: nucleus_std::csrf::CsrfToken, Query(params): Query<std::collections::HashMap<String, String>>) -> impl axum::response::IntoResponse {{\n    {}\n    axum::response::Html(html_body).into_response()\n}}\n\n",
         fn_name, func_body
     )
 }
@@ -402,7 +402,7 @@ pub fn generate_view_handler_fn(el: &Element, fn_name: &str) -> String {
     };
 
     format!(
-        "{}#[allow(non_snake_case, unreachable_code, unused_variables)]\nasync fn {}(headers: axum::http::HeaderMap, Query(params): Query<std::collections::HashMap<String, String>>) -> impl axum::response::IntoResponse {{\n{}\n    {}\n    {}\n    axum::response::Html(html_body).into_response()\n}}\n\n",
+        "{}#[allow(non_snake_case, unreachable_code, unused_variables)]\nasync fn {}(headers: axum::http::HeaderMap, csrf_token: nucleus_std::csrf::CsrfToken, Query(params): Query<std::collections::HashMap<String, String>>) -> impl axum::response::IntoResponse {{\n{}\n    {}\n    {}\n    axum::response::Html(html_body).into_response()\n}}\n\n",
         form_struct, fn_name, guard_code, injected_code, func_body
     )
 }
@@ -642,6 +642,11 @@ fn generate_element_html(el: &Element, body: &mut String) {
 
     body.push_str("html_body.push_str(\">\");\n");
 
+    // POST forms get a CSRF token; `nucleus build` installs CsrfLayer and SessionLayer for them
+    if is_post_form(el) {
+        body.push_str("html_body.push_str(&csrf_token.hidden_field());\n");
+    }
+
     for child in &el.children {
         render_node_to_body(child, body);
     }
@@ -749,6 +754,14 @@ fn render_node_to_body(node: &Node, body: &mut String) {
     }
 }
 
+fn is_post_form(el: &Element) -> bool {
+    (el.tag_name == "form" || el.tag_name == "n:form")
+        && el
+            .attributes
+            .iter()
+            .any(|(k, v)| k.eq_ignore_ascii_case("method") && v.eq_ignore_ascii_case("post"))
+}
+
 fn find_form(el: &Element) -> Option<&Element> {
     for child in &el.children {
         if let Node::Element(child_el) = child {
@@ -849,4 +862,24 @@ mod tests {
         assert!(code.contains("pub email: String"));
         assert!(code.contains("#[derive(Deserialize)]"));
     }
+
+    #[test]
+    fn test_post_form_gets_csrf_field() {
+        let form = |method: &str| {
+            Node::Element(Element {
+                tag_name: "form".to_string(),
+                attributes: vec![("method".to_string(), method.to_string())],
+                children: vec![],
+            })
+        };
+        let view = Element {
+            tag_name: "n:view".to_string(),
+            attributes: vec![],
+            children: vec![form("POST"), form("get")],
+        };
+
+        let code = generate_view_handler_fn(&view, "handle_home");
+        assert!(code.contains("csrf_token: nucleus_std::csrf::CsrfToken"));
+        assert_eq!(code.matches("csrf_token.hidden_field()").count(), 1);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ast::Model;
+use crate::ast::{Model, Node};
 use sha2::{Digest, Sha256};
 
 pub fn calculate_schema_hash(model: &Model) -> String {
@@ -35,3 +35,253 @@ pub fn generate_sql(model: &Model) -> String {
     sql.push_str(");");
     sql
 }
+
+/// A column declared by a model, with its schema hints
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModelColumn {
+    pub name: String,
+    /// Rust type as written (e.g. `Option<String>`)
+    pub ty: String,
+    pub primary_key: bool,
+    pub unique: bool,
+    pub index: bool,
+    /// `(table, column)` this column references
+    pub references: Option<(String, String)>,
+}
+
+/// The table a model maps to, from `<n:model>` or `#[derive(Model)]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSchema {
+    /// Model (struct) name
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<ModelColumn>,
+}
+
+impl ModelSchema {
+    /// The table as an `ast::Model`, for use with `calculate_schema_hash`
+    pub fn to_model(&self) -> Model {
+        let mut fields: Vec<(String, String)> = self
+            .columns
+            .iter()
+            .map(|c| {
+                let mut ty = c.ty.clone();
+                if c.primary_key {
+                    ty.push_str(" primary_key");
+                }
+                if c.unique {
+                    ty.push_str(" unique");
+                }
+                if c.index {
+                    ty.push_str(" index");
+                }
+                if let Some((table, column)) = &c.references {
+                    ty.push_str(&format!(" references {}.{}", table, column));
+                }
+                (c.name.clone(), ty)
+            })
+            .collect();
+        fields.sort();
+
+        Model {
+            name: self.table.clone(),
+            fields,
+            methods: vec![],
+            attributes: vec![],
+        }
+    }
+
+    /// Hash of the declared table, used to detect schema drift
+    pub fn schema_hash(&self) -> String {
+        calculate_schema_hash(&self.to_model())
+    }
+}
+
+/// Collect table models from parsed `.ncl` nodes
+///
+/// The table name comes from a `#[photon(table = "...")]` attribute line,
+/// falling back to the lowercased model name as in `generate_sql`. A field
+/// named `id` is the primary key. Data-binding `<n:model>` tags without a
+/// name or fields are ignored.
+pub fn models_from_nodes(nodes: &[Node]) -> Vec<ModelSchema> {
+    let mut models = Vec::new();
+    collect_node_models(nodes, &mut models);
+    models
+}
+
+fn collect_node_models(nodes: &[Node], models: &mut Vec<ModelSchema>) {
+    for node in nodes {
+        match node {
+            Node::Model(model) if model.name != "Unknown" && !model.fields.is_empty() => {
+                let table = model
+                    .attributes
+                    .iter()
+                    .filter_map(|attr| syn::parse_str::<AttrList>(attr).ok())
+                    .flat_map(|list| list.0)
+                    .find_map(|attr| photon_table(&attr))
+                    .unwrap_or_else(|| model.name.to_lowercase());
+
+                models.push(ModelSchema {
+                    name: model.name.clone(),
+                    table,
+                    columns: model
+                        .fields
+                        .iter()
+                        .map(|(name, ty)| ModelColumn {
+                            name: name.clone(),
+                            ty: ty.clone(),
+                            primary_key: name == "id",
+                            ..Default::default()
+                        })
+                        .collect(),
+                });
+            }
+            Node::Element(el) => collect_node_models(&el.children, models),
+            Node::For { children, .. } | Node::If { children, .. } => {
+                collect_node_models(children, models)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Outer attributes parsed from a single `#[...]` line
+struct AttrList(Vec<syn::Attribute>);
+
+impl syn::parse::Parse for AttrList {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self(input.call(syn::Attribute::parse_outer)?))
+    }
+}
+
+fn photon_table(attr: &syn::Attribute) -> Option<String> {
+    if !attr.path().is_ident("photon") {
+        return None;
+    }
+    let mut table = None;
+    let _ = attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("table") {
+            table = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+        } else if meta.input.peek(syn::Token![=]) {
+            meta.value()?.parse::<syn::Expr>()?;
+        }
+        Ok(())
+    });
+    table
+}
+
+/// Collect `#[derive(Model)]` structs from Rust source
+///
+/// Mirrors the rules of the derive: `#[photon(table = "...")]` is required,
+/// the primary key defaults to a field named `id`, and `skip` fields are
+/// not columns.
+pub fn models_from_rust(source: &str) -> syn::Result<Vec<ModelSchema>> {
+    let file = syn::parse_file(source)?;
+    let mut models = Vec::new();
+    collect_rust_models(&file.items, &mut models)?;
+    Ok(models)
+}
+
+fn collect_rust_models(items: &[syn::Item], models: &mut Vec<ModelSchema>) -> syn::Result<()> {
+    for item in items {
+        match item {
+            syn::Item::Struct(item) if derives_model(&item.attrs) => {
+                if let Some(model) = rust_model(item)? {
+                    models.push(model);
+                }
+            }
+            syn::Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_rust_models(items, models)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn derives_model(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .any(|attr| {
+            let mut found = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "Model")
+                {
+                    found = true;
+                }
+                Ok(())
+            });
+            found
+        })
+}
+
+fn rust_model(item: &syn::ItemStruct) -> syn::Result<Option<ModelSchema>> {
+    let Some(table) = item.attrs.iter().find_map(photon_table) else {
+        return Ok(None);
+    };
+    let syn::Fields::Named(fields) = &item.fields else {
+        return Ok(None);
+    };
+
+    let mut columns = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let mut column = ModelColumn {
+            name: ident.to_string().trim_start_matches("r#").to_string(),
+            ty: quote::quote!(#ty).to_string().replace(' ', ""),
+            ..Default::default()
+        };
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("photon")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("primary_key") {
+                    column.primary_key = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("unique") {
+                    column.unique = true;
+                } else if meta.path.is_ident("index") {
+                    column.index = true;
+                } else if meta.path.is_ident("column") {
+                    column.name = meta.value()?.parse::<syn::LitStr>()?.value();
+                } else if meta.path.is_ident("references") {
+                    let target = meta.value()?.parse::<syn::LitStr>()?;
+                    let (table, col) = target
+                        .value()
+                        .split_once('.')
+                        .map(|(t, c)| (t.to_string(), c.to_string()))
+                        .ok_or_else(|| {
+                            syn::Error::new_spanned(&target, "expected \"table.column\"")
+                        })?;
+                    column.references = Some((table, col));
+                }
+                Ok(())
+            })?;
+        }
+
+        if !skip {
+            columns.push(column);
+        }
+    }
+
+    if !columns.iter().any(|c| c.primary_key) {
+        if let Some(id) = columns.iter_mut().find(|c| c.name == "id") {
+            id.primary_key = true;
+        }
+    }
+
+    Ok(Some(ModelSchema {
+        name: item.ident.to_string(),
+        table,
+        columns,
+    }))
+}
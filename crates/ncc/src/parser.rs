@@ -898,7 +898,7 @@ mod tests {
 mod db_tests {
     // use super::*;
     use crate::ast::Model;
-    use crate::db::generate_sql;
+    use crate::db::{calculate_schema_hash, generate_sql, models_from_nodes, models_from_rust};
 
     #[test]
     fn test_generate_sql() {
@@ -917,6 +917,82 @@ mod db_tests {
         assert!(sql.contains("username TEXT"));
         assert!(sql.contains("active BOOLEAN"));
     }
+
+    #[test]
+    fn test_models_from_nodes() {
+        let input = r#"
+            <n:view>
+                <n:model name="Subscriber">
+                    #[photon(table = "subscribers")]
+                    id: i64
+                    email: String
+                </n:model>
+                <n:model items="load_items().await" />
+            </n:view>
+        "#;
+        let (_, nodes) = crate::parser::parse_root(input).unwrap();
+        let models = models_from_nodes(&nodes);
+
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].table, "subscribers");
+        assert!(models[0].columns[0].primary_key);
+        assert_eq!(models[0].columns[1].ty, "String");
+    }
+
+    #[test]
+    fn test_models_from_rust() {
+        let source = r#"
+            #[derive(Debug, nucleus_std::photon::Model, sqlx::FromRow)]
+            #[photon(table = "posts")]
+            pub struct Post {
+                id: i64,
+                #[photon(references = "users.id", index)]
+                user_id: i64,
+                #[photon(unique, column = "url_slug")]
+                slug: String,
+                body: Option<String>,
+                #[photon(skip)]
+                cached: Vec<String>,
+            }
+
+            struct NotAModel { id: i64 }
+        "#;
+        let models = models_from_rust(source).unwrap();
+        assert_eq!(models.len(), 1);
+
+        let post = &models[0];
+        assert_eq!(post.name, "Post");
+        assert_eq!(post.table, "posts");
+        assert_eq!(post.columns.len(), 4);
+        assert!(post.columns[0].primary_key);
+        assert_eq!(
+            post.columns[1].references,
+            Some(("users".to_string(), "id".to_string()))
+        );
+        assert!(post.columns[1].index);
+        assert_eq!(post.columns[2].name, "url_slug");
+        assert!(post.columns[2].unique);
+        assert_eq!(post.columns[3].ty, "Option<String>");
+    }
+
+    #[test]
+    fn test_model_schema_hash_tracks_columns() {
+        let source = r#"
+            #[derive(Model)]
+            #[photon(table = "users")]
+            struct User { id: i64, email: String }
+        "#;
+        let before = models_from_rust(source).unwrap().remove(0);
+        let after = models_from_rust(&source.replace("email: String", "email: Option<String>"))
+            .unwrap()
+            .remove(0);
+
+        assert_eq!(
+            before.schema_hash(),
+            calculate_schema_hash(&before.to_model())
+        );
+        assert_ne!(before.schema_hash(), after.schema_hash());
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ pub mod deploy; // Deploy module with multi-platform support
 pub mod export; // Static export and publish module
 pub mod generate; // Register module
 pub mod pwa; // PWA generation (manifest, service worker)
+pub mod schema_diff; // `nucleus db diff` model/database comparison
 pub mod studio; // Database Studio web UI // CLI animations
 use miette::IntoDiagnostic;
 use rayon::prelude::*;
@@ -127,6 +128,12 @@ pub enum DbCommands {
         /// Number of migrations to apply (default: all)
         #[arg(short, long)]
         step: Option<usize>,
+        /// Print the SQL of pending migrations without executing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Also migrate every tenant with its own schema or database
+        #[arg(long)]
+        all_tenants: bool,
     },
     /// Rollback migrations
     Down {
@@ -136,6 +143,18 @@ pub enum DbCommands {
     },
     /// Show migration status
     Status,
+    /// Generate a migration from differences between models and the database
+    Diff {
+        /// Migration name
+        #[arg(default_value = "schema_diff")]
+        name: String,
+        /// Print the changes and SQL without writing a migration
+        #[arg(long)]
+        dry_run: bool,
+        /// Also drop tables that no longer have a model
+        #[arg(long)]
+        drop_tables: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -317,7 +336,7 @@ pub async fn run_cli() -> miette::Result<()> {
                                         route_key
                                     };
 
-                                    routes.insert(final_key, html);
+                                    routes.insert(final_key, html.into());
                                 }
                                 Err(e) => {
                                     return Err(e.into());
@@ -333,7 +352,7 @@ pub async fn run_cli() -> miette::Result<()> {
                     fn load_static_recursive(
                         base: &Path,
                         dir: &Path,
-                        routes: &mut std::collections::HashMap<String, String>,
+                        routes: &mut std::collections::HashMap<String, axum::body::Bytes>,
                     ) {
                         if let Ok(entries) = fs::read_dir(dir) {
                             for entry in entries.flatten() {
@@ -346,11 +365,8 @@ pub async fn run_cli() -> miette::Result<()> {
                                         // Route key: assets/home.js -> "assets/home.js"
                                         let key =
                                             rel.to_string_lossy().to_string().replace("\\", "/");
-                                        // Store as string (for binary files this will be lossy but OK for js/css)
-                                        routes.insert(
-                                            key,
-                                            String::from_utf8_lossy(&content).to_string(),
-                                        );
+                                        // Raw bytes: images and precompressed .br/.gz stay intact
+                                        routes.insert(key, content.into());
                                     }
                                 }
                             }
@@ -362,11 +378,13 @@ pub async fn run_cli() -> miette::Result<()> {
                 println!("ℹ️  No nucleus.config found, running in standalone mode.");
                 routes.insert(
                     "home".to_string(),
-                    "<h1>Hello from Nucleus Standalone</h1>".to_string(),
+                    "<h1>Hello from Nucleus Standalone</h1>".into(),
                 );
             }
 
-            atom::start_reactor(Some(routes), None).await;
+            atom::start_reactor(Some(routes), None)
+                .await
+                .into_diagnostic()?;
         }
         Some(Commands::Db { command }) => {
             handle_db_command(command).await?;
@@ -863,6 +881,35 @@ pub fn build_project() -> miette::Result<()> {
     } else {
         ""
     };
+    // Views with POST forms render a CSRF token, so the app needs both layers.
+    // Sessions go to the app database; memory is only a development fallback.
+    let forms_layer = if handlers.contains("csrf_token.hidden_field()") {
+        r#"let app = {
+                    use nucleus_std::session::{MemorySessionStore, SessionStore, SqlSessionStore};
+                    let production = config.server.environment == "production";
+                    let (csrf, session) = if production {
+                        (nucleus_std::csrf::CsrfConfig::default(), nucleus_std::session::SessionConfig::production())
+                    } else {
+                        (nucleus_std::csrf::CsrfConfig::development(), nucleus_std::session::SessionConfig::development())
+                    };
+                    let store: Box<dyn SessionStore> = if nucleus_std::photon::is_db_initialized() {
+                        let store = SqlSessionStore::new(nucleus_std::photon::db().clone());
+                        if let Err(e) = store.migrate().await {
+                            panic!("Could not create the sessions table: {}", e);
+                        }
+                        Box::new(store)
+                    } else if production {
+                        panic!("Views with POST forms need sessions; set [database] url in nucleus.config");
+                    } else {
+                        eprintln!("⚠️ No database configured; sessions are kept in memory");
+                        Box::new(MemorySessionStore::new())
+                    };
+                    app.layer(nucleus_std::csrf::CsrfLayer::new(csrf))
+                        .layer(nucleus_std::session::SessionLayer::new(store, session))
+                };"#
+    } else {
+        ""
+    };
     let has_logic = Path::new("src/logic").exists();
     let logic_mod = if has_logic {
         r#"#[path = "../logic/mod.rs"] pub mod logic;"#
@@ -915,7 +962,7 @@ pub fn build_project() -> miette::Result<()> {
             // Initialize Database
             // Only init if URL is set (simple check)
             if !config.database.url.is_empty() {{
-                 match nucleus_std::photon::init_db(&config.database.url).await {{
+                 match nucleus_std::photon::init_databases(&config.database).await {{
                      Ok(_) => {{
                          println!("✅ Database initialized on {{}}", config.database.url);
                          // Auto-run migrations
@@ -949,6 +996,9 @@ pub fn build_project() -> miette::Result<()> {
                     .layer(CompressionLayer::new().br(true).gzip(true))
                     {};
 
+                // CSRF + session layers when views have POST forms
+                {}
+
                 // Auto-Inject Middleware if `src/middleware.rs` exists
                 {}
 
@@ -962,6 +1012,7 @@ pub fn build_project() -> miette::Result<()> {
         models_mod,       // Generated mod
         router_match,     // .route() calls
         logic_routes,     // .merge() calls
+        forms_layer,      // CSRF + session layers
         middleware_layer  // Layer application
     );
 
@@ -1230,199 +1281,139 @@ async fn handle_db_command(cmd: &DbCommands) -> miette::Result<()> {
             .into_diagnostic()?;
             println!("✅ Created migration: {}", filename);
         }
-        DbCommands::Up { step } => {
-            println!("⚛️  Applying migrations...");
-
-            // Connect to DB using Config
+        DbCommands::Up {
+            step,
+            dry_run,
+            all_tenants,
+        } => {
             let config = nucleus_std::config::Config::load();
-            let db_url = config.database.url;
+            nucleus_std::photon::init_databases(&config.database)
+                .await
+                .into_diagnostic()?;
+            let pool = nucleus_std::photon::db();
 
-            // Ensure SQLite file exists if local
-            if db_url.starts_with("sqlite:") {
-                let path = db_url.trim_start_matches("sqlite:");
-                if !Path::new(path).exists() {
-                    tokio::fs::File::create(path).await.into_diagnostic()?;
-                }
+            if *dry_run && *all_tenants {
+                return Err(miette::miette!(
+                    "--dry-run only covers the default database; run it without --all-tenants"
+                ));
             }
-
-            let pool = sqlx::SqlitePool::connect(&db_url).await.into_diagnostic()?;
-
-            // Create migrations table
-            sqlx::query(
-                "CREATE TABLE IF NOT EXISTS _migrations (
-                    id INTEGER PRIMARY KEY,
-                    key TEXT UNIQUE NOT NULL,
-                    applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
-                )",
-            )
-            .execute(&pool)
-            .await
-            .into_diagnostic()?;
-
-            // Read migrations dir
-            let mut entries = fs::read_dir("migrations")
-                .into_diagnostic()?
-                .filter_map(|e| e.ok())
-                .map(|e| e.path())
-                .filter(|p| p.extension().is_some_and(|ext| ext == "sql"))
-                .collect::<Vec<_>>();
-            entries.sort();
-
-            let mut applied_count = 0;
-            for path in entries {
-                if let Some(max) = step {
-                    if applied_count >= *max {
-                        break;
-                    }
-                }
-
-                let key = path.file_name().unwrap().to_string_lossy().to_string();
-
-                // Check if applied
-                let applied: bool = sqlx::query("SELECT 1 FROM _migrations WHERE key = ?")
-                    .bind(&key)
-                    .fetch_optional(&pool)
+            if *dry_run {
+                println!("⚛️  Pending migrations (dry run, nothing is executed):");
+                let pending = nucleus_std::photon::pending_migrations_on(pool, "migrations")
                     .await
-                    .into_diagnostic()?
-                    .is_some();
-
-                if !applied {
-                    let sql = fs::read_to_string(&path).into_diagnostic()?;
-                    // Extract UP migration (everything before -- DOWN)
-                    let up_sql = if let Some(pos) = sql.find("-- DOWN") {
-                        sql[..pos].trim()
-                    } else {
-                        sql.trim()
-                    };
-
-                    // Execute
-                    sqlx::query(up_sql).execute(&pool).await.into_diagnostic()?;
-                    // Record
-                    sqlx::query("INSERT INTO _migrations (key) VALUES (?)")
-                        .bind(&key)
-                        .execute(&pool)
-                        .await
-                        .into_diagnostic()?;
-
-                    println!("🚀 Applied: {}", key);
-                    applied_count += 1;
+                    .into_diagnostic()?;
+                let pending: Vec<_> = pending
+                    .into_iter()
+                    .take(step.unwrap_or(usize::MAX))
+                    .collect();
+                for migration in &pending {
+                    println!("\n-- {}\n{}", migration.name, migration.up_sql);
+                }
+                if pending.is_empty() {
+                    println!("✨ No pending migrations.");
+                } else {
+                    println!("\n✨ {} migration(s) would be applied.", pending.len());
                 }
+                return Ok(());
             }
 
-            if applied_count == 0 {
+            println!("⚛️  Applying migrations...");
+            let applied = nucleus_std::photon::run_migrations_limit_on(pool, "migrations", *step)
+                .await
+                .into_diagnostic()?;
+            for name in &applied {
+                println!("🚀 Applied: {}", name);
+            }
+            if applied.is_empty() {
                 println!("✨ No pending migrations.");
             } else {
-                println!("✨ Applied {} migration(s).", applied_count);
+                println!("✨ Applied {} migration(s).", applied.len());
             }
-        }
-        DbCommands::Down { step } => {
-            println!("⏪ Rolling back {} migration(s)...", step);
 
-            let config = nucleus_std::config::Config::load();
-            let db_url = config.database.url;
-            let pool = sqlx::SqlitePool::connect(&db_url).await.into_diagnostic()?;
-
-            // Get last N applied migrations
-            let rows: Vec<(String,)> =
-                sqlx::query_as("SELECT key FROM _migrations ORDER BY applied_at DESC LIMIT ?")
-                    .bind(*step as i64)
-                    .fetch_all(&pool)
-                    .await
-                    .into_diagnostic()?;
+            if *all_tenants {
+                let results = nucleus_std::photon::run_tenant_migrations("migrations").await;
+                if results.is_empty() {
+                    println!(
+                        "📄 No tenants with their own schema or database in [database.tenants]."
+                    );
+                }
 
-            for (key,) in rows {
-                // Try to find migration file
-                let path = std::path::PathBuf::from(format!("migrations/{}", key));
-
-                if path.exists() {
-                    let sql = fs::read_to_string(&path).into_diagnostic()?;
-                    // Extract DOWN migration (everything after -- DOWN)
-                    if let Some(pos) = sql.find("-- DOWN") {
-                        let down_sql = sql[pos + 7..].trim();
-                        if !down_sql.is_empty() {
-                            sqlx::query(down_sql)
-                                .execute(&pool)
-                                .await
-                                .into_diagnostic()?;
+                let mut failed = 0;
+                for tenant in &results {
+                    match &tenant.result {
+                        Ok(applied) => println!(
+                            "✅ {}: {} migration(s) applied",
+                            tenant.tenant,
+                            applied.len()
+                        ),
+                        Err(e) => {
+                            failed += 1;
+                            println!("❌ {}: {}", tenant.tenant, e);
                         }
                     }
                 }
+                if failed > 0 {
+                    return Err(miette::miette!(
+                        "{} of {} tenant(s) failed to migrate",
+                        failed,
+                        results.len()
+                    ));
+                }
+            }
+        }
+        DbCommands::Down { step } => {
+            println!("⏪ Rolling back {} migration(s)...", step);
 
-                // Remove from tracking
-                sqlx::query("DELETE FROM _migrations WHERE key = ?")
-                    .bind(&key)
-                    .execute(&pool)
-                    .await
-                    .into_diagnostic()?;
+            let config = nucleus_std::config::Config::load();
+            nucleus_std::photon::init_databases(&config.database)
+                .await
+                .into_diagnostic()?;
 
-                println!("⏪ Rolled back: {}", key);
+            let rolled_back = nucleus_std::photon::rollback_in("migrations", *step)
+                .await
+                .into_diagnostic()?;
+            for name in rolled_back {
+                println!("⏪ Rolled back: {}", name);
             }
 
             println!("✨ Rollback complete.");
         }
         DbCommands::Status => {
             let config = nucleus_std::config::Config::load();
-            let db_url = config.database.url;
 
-            // Ensure SQLite file exists if local
-            if db_url.starts_with("sqlite:") {
-                let path = db_url.trim_start_matches("sqlite:");
-                if !Path::new(path).exists() {
+            if let Some(path) = config.database.url.strip_prefix("sqlite:") {
+                if !Path::new(path.trim_start_matches("//")).exists() {
                     println!("📄 No database file found. Run `nucleus db up` first.");
                     return Ok(());
                 }
             }
 
-            let pool = sqlx::SqlitePool::connect(&db_url).await.into_diagnostic()?;
-
-            // Create migrations table if not exists
-            sqlx::query(
-                "CREATE TABLE IF NOT EXISTS _migrations (
-                    id INTEGER PRIMARY KEY,
-                    key TEXT UNIQUE NOT NULL,
-                    applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
-                )",
-            )
-            .execute(&pool)
-            .await
-            .into_diagnostic()?;
-
-            // Get applied migrations
-            let applied: Vec<(String,)> = sqlx::query_as("SELECT key FROM _migrations")
-                .fetch_all(&pool)
+            nucleus_std::photon::init_databases(&config.database)
+                .await
+                .into_diagnostic()?;
+            let status = nucleus_std::photon::migration_status("migrations")
                 .await
                 .into_diagnostic()?;
-            let applied_set: std::collections::HashSet<_> =
-                applied.into_iter().map(|(k,)| k).collect();
-
-            // Read migrations dir
-            let mut entries: Vec<_> = fs::read_dir("migrations")
-                .into_diagnostic()?
-                .filter_map(|e| e.ok())
-                .map(|e| e.path())
-                .filter(|p| p.extension().is_some_and(|ext| ext == "sql"))
-                .collect();
-            entries.sort();
 
             println!("\n📊 Migration Status:");
             println!("{}", "-".repeat(50));
-
-            for path in entries {
-                let key = path.file_name().unwrap().to_string_lossy().to_string();
-                let status = if applied_set.contains(&key) {
-                    "✅"
-                } else {
-                    "⏳"
-                };
-                let label = if applied_set.contains(&key) {
-                    "applied"
+            for migration in status {
+                let (icon, label) = if migration.applied {
+                    ("✅", "applied")
                 } else {
-                    "pending"
+                    ("⏳", "pending")
                 };
-                println!("{} {} ({})", status, key, label);
+                println!("{} {} ({})", icon, migration.name, label);
             }
             println!("{}", "-".repeat(50));
         }
+        DbCommands::Diff {
+            name,
+            dry_run,
+            drop_tables,
+        } => {
+            schema_diff::run(name, *dry_run, *drop_tables).await?;
+        }
     }
     Ok(())
 }
@@ -0,0 +1,334 @@
+//! Schema Diff Module
+//!
+//! Backs `nucleus db diff`: collects `<n:model>` and `#[derive(Model)]`
+//! definitions, compares them with the live database and writes a
+//! migration for the difference.
+
+use miette::IntoDiagnostic;
+use ncc::db::{models_from_nodes, models_from_rust, ModelSchema};
+use nucleus_std::photon::schema::{
+    diff_schemas, introspect, migration_warnings, render_migration, write_migration, ColumnDef,
+    ForeignKeyDef, IndexDef, SchemaChange, SqlType, TableDef,
+};
+use nucleus_std::photon::DatabasePool;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Model hashes recorded when the last migration was generated
+pub const SCHEMA_LOCK: &str = "migrations/schema.lock.json";
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MODEL DISCOVERY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Collect model tables from `.ncl` and `.rs` files under `src_dir`
+///
+/// When both declare the same table, the Rust model wins since it carries
+/// the full set of schema hints.
+pub fn collect_models(src_dir: &Path) -> Vec<ModelSchema> {
+    let mut rust_models = Vec::new();
+    let mut ncl_models = Vec::new();
+
+    for entry in walkdir::WalkDir::new(src_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let Ok(source) = fs::read_to_string(path) else {
+            continue;
+        };
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rs") if source.contains("Model") => {
+                if let Ok(models) = models_from_rust(&source) {
+                    rust_models.extend(models);
+                }
+            }
+            Some("ncl") if source.contains("<n:model") => {
+                if let Ok((_, nodes)) = ncc::parse_root(&source) {
+                    ncl_models.extend(models_from_nodes(&nodes));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for model in ncl_models {
+        if !rust_models.iter().any(|m| m.table == model.table) {
+            rust_models.push(model);
+        }
+    }
+    rust_models.sort_by(|a, b| a.table.cmp(&b.table));
+    rust_models
+}
+
+/// Convert a declared model into the table it should produce
+pub fn table_def(model: &ModelSchema) -> TableDef {
+    let mut table = TableDef::new(&model.table);
+
+    for column in &model.columns {
+        let (sql_type, nullable) = SqlType::from_rust_type(&column.ty);
+        table.columns.push(ColumnDef {
+            name: column.name.clone(),
+            sql_type,
+            nullable,
+            primary_key: column.primary_key,
+            default: None,
+        });
+
+        if column.unique || column.index {
+            let suffix = if column.unique { "unique" } else { "idx" };
+            table.indexes.push(IndexDef {
+                name: format!("{}_{}_{}", model.table, column.name, suffix),
+                columns: vec![column.name.clone()],
+                unique: column.unique,
+                constraint: false,
+            });
+        }
+
+        if let Some((ref_table, ref_column)) = &column.references {
+            table.foreign_keys.push(ForeignKeyDef::new(
+                &model.table,
+                &column.name,
+                ref_table,
+                ref_column,
+            ));
+        }
+    }
+
+    table
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DRIFT DETECTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Load the table → schema hash map from the lock file
+pub fn load_lock(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Schema hashes for the given models, keyed by table
+pub fn model_hashes(models: &[ModelSchema]) -> BTreeMap<String, String> {
+    models
+        .iter()
+        .map(|m| (m.table.clone(), m.schema_hash()))
+        .collect()
+}
+
+/// Tables whose model definition changed since the lock was written
+pub fn drifted_tables(
+    lock: &BTreeMap<String, String>,
+    hashes: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut tables: Vec<String> = hashes
+        .iter()
+        .filter(|(table, hash)| lock.get(*table) != Some(hash))
+        .map(|(table, _)| table.clone())
+        .collect();
+    tables.extend(lock.keys().filter(|t| !hashes.contains_key(*t)).cloned());
+    tables
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// COMMAND
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Run `nucleus db diff`
+pub async fn run(name: &str, dry_run: bool, drop_tables: bool) -> miette::Result<()> {
+    println!("🔍 Comparing models with the database...");
+
+    let models = collect_models(Path::new("src"));
+    if models.is_empty() {
+        println!("📄 No models found in src/ (<n:model> or #[derive(Model)]).");
+        return Ok(());
+    }
+
+    let hashes = model_hashes(&models);
+    let drifted = drifted_tables(&load_lock(Path::new(SCHEMA_LOCK)), &hashes);
+    if !drifted.is_empty() {
+        println!("🧬 Models changed since last diff: {}", drifted.join(", "));
+    }
+
+    let config = nucleus_std::config::Config::load();
+    let pool = DatabasePool::connect(&config.database.url)
+        .await
+        .into_diagnostic()?;
+    let db_type = pool.db_type();
+
+    let current = introspect(&pool).await.into_diagnostic()?;
+    let desired: Vec<TableDef> = models.iter().map(table_def).collect();
+
+    let mut changes = diff_schemas(&current, &desired, db_type);
+    let skipped = changes
+        .iter()
+        .filter(|c| matches!(c, SchemaChange::DropTable(_)))
+        .count();
+    if !drop_tables {
+        changes.retain(|c| !matches!(c, SchemaChange::DropTable(_)));
+    }
+    pool.close().await;
+
+    if changes.is_empty() {
+        println!("✨ Database schema is in sync with your models.");
+    } else {
+        println!("\n📋 Changes ({}):", db_type.name());
+        for change in &changes {
+            println!("   {}", change.describe());
+        }
+    }
+    if skipped > 0 && !drop_tables {
+        println!(
+            "   ({} table(s) without a model kept; pass --drop-tables to drop them)",
+            skipped
+        );
+    }
+    for warning in migration_warnings(&changes) {
+        println!("\n⚠️  {}", warning);
+    }
+
+    if dry_run {
+        if !changes.is_empty() {
+            let (up, down) = render_migration(&changes, db_type);
+            println!("\n-- UP\n{}\n\n-- DOWN\n{}", up, down);
+        }
+        return Ok(());
+    }
+
+    if !changes.is_empty() {
+        let filename = write_migration(name, "migrations", &changes, db_type).into_diagnostic()?;
+        println!("\n✅ Created migration: migrations/{}", filename);
+        println!("   Review it, then run `nucleus db up`.");
+    }
+
+    fs::create_dir_all("migrations").into_diagnostic()?;
+    fs::write(
+        SCHEMA_LOCK,
+        serde_json::to_string_pretty(&hashes).into_diagnostic()?,
+    )
+    .into_diagnostic()?;
+
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nucleus_std::photon::DatabaseType;
+
+    #[test]
+    fn test_collect_models_prefers_rust() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("views")).unwrap();
+        fs::write(
+            dir.path().join("models.rs"),
+            r#"
+            #[derive(Model)]
+            #[photon(table = "subscriber")]
+            struct Subscriber { id: i64, #[photon(unique)] email: String }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("views/index.ncl"),
+            r#"<n:view><n:model name="Subscriber">
+                email: String
+            </n:model><n:model name="Tag">
+                id: i64
+                label: String
+            </n:model></n:view>"#,
+        )
+        .unwrap();
+
+        let models = collect_models(dir.path());
+        let tables: Vec<&str> = models.iter().map(|m| m.table.as_str()).collect();
+        assert_eq!(tables, vec!["subscriber", "tag"]);
+        assert!(models[0].columns[1].unique);
+    }
+
+    #[test]
+    fn test_table_def_from_model() {
+        let models = models_from_rust(
+            r#"
+            #[derive(Model)]
+            #[photon(table = "posts")]
+            struct Post {
+                id: i64,
+                #[photon(references = "users.id", index)]
+                user_id: i64,
+                title: Option<String>,
+            }
+            "#,
+        )
+        .unwrap();
+        let table = table_def(&models[0]);
+
+        assert!(table.columns[0].primary_key);
+        assert_eq!(table.columns[1].sql_type, SqlType::BigInt);
+        assert!(table.columns[2].nullable);
+        assert_eq!(table.indexes[0].name, "posts_user_id_idx");
+        assert_eq!(table.foreign_keys[0].ref_table, "users");
+
+        let changes = diff_schemas(&[], &[table], DatabaseType::Postgres);
+        let (up, _) = render_migration(&changes, DatabaseType::Postgres);
+        assert!(up.contains("REFERENCES users(id)"));
+    }
+
+    #[test]
+    fn test_drifted_tables() {
+        let models = models_from_rust(
+            r#"
+            #[derive(Model)]
+            #[photon(table = "users")]
+            struct User { id: i64, email: String }
+            "#,
+        )
+        .unwrap();
+        let hashes = model_hashes(&models);
+        assert_eq!(drifted_tables(&BTreeMap::new(), &hashes), vec!["users"]);
+        assert!(drifted_tables(&hashes, &hashes).is_empty());
+
+        let mut lock = hashes.clone();
+        lock.insert("legacy".into(), "abc".into());
+        assert_eq!(drifted_tables(&lock, &hashes), vec!["legacy"]);
+    }
+
+    #[tokio::test]
+    async fn test_diff_against_live_sqlite() {
+        let pool = DatabasePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .execute(pool.as_sqlite().unwrap())
+            .await
+            .unwrap();
+
+        let models = models_from_rust(
+            r#"
+            #[derive(Model)]
+            #[photon(table = "users")]
+            struct User { id: i64, name: String, #[photon(unique)] email: String }
+            "#,
+        )
+        .unwrap();
+        let current = introspect(&pool).await.unwrap();
+        let changes = diff_schemas(&current, &[table_def(&models[0])], DatabaseType::Sqlite);
+        let (up, down) = render_migration(&changes, DatabaseType::Sqlite);
+
+        assert!(up.contains("ALTER TABLE users ADD COLUMN email TEXT NOT NULL DEFAULT '';"));
+        assert!(up.contains("CREATE UNIQUE INDEX users_email_unique ON users (email);"));
+        assert!(down.starts_with("DROP INDEX users_email_unique;"));
+
+        // Every existing row gets '', so the unique index needs a backfill first
+        let warnings = migration_warnings(&changes);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("users.email"));
+        assert!(warnings[0].contains("users_email_unique"));
+    }
+}
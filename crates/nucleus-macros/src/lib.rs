@@ -123,3 +123,286 @@ pub fn store(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     output.into()
 }
+
+/// Derive the Photon `Model` and `Record` traits plus typed column constants.
+///
+/// ```rust,ignore
+/// #[derive(Model, sqlx::FromRow)]
+/// #[photon(table = "users")]
+/// struct User {
+///     #[photon(primary_key)]
+///     id: i64,
+///     email: String,
+///     #[photon(column = "display_name")]
+///     name: Option<String>,
+///     #[photon(skip)]
+///     cached: Vec<String>,
+/// }
+/// ```
+///
+/// Generates `User::ID`, `User::EMAIL`, `User::NAME` (`Column<User, T>`),
+/// `Model::table_name()` and `Record` (`insert`/`update`/`save`/`delete`).
+/// The primary key defaults to a field named `id`.
+///
+/// `unique`, `index` and `references = "table.column"` describe the schema
+/// for `nucleus db diff` and do not change the generated code.
+///
+/// Struct-level behaviors: `timestamps` maintains `created_at`/`updated_at`,
+/// `soft_delete` (or `soft_delete = "column"`) turns deletes into a
+/// `deleted_at` update hidden from `query()`, `tenant` (or
+/// `tenant = "column"`) scopes queries to the current tenant's `tenant_id`,
+/// and `hooks` means you write your own `impl Hooks` instead of getting the
+/// no-op one.
+#[proc_macro_derive(Model, attributes(photon))]
+pub fn derive_model(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    match expand_model(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_model(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut table = None;
+    let mut timestamps = false;
+    let mut soft_delete = None;
+    let mut tenant = None;
+    let mut custom_hooks = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("photon")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<syn::LitStr>()?);
+            } else if meta.path.is_ident("timestamps") {
+                timestamps = true;
+            } else if meta.path.is_ident("soft_delete") {
+                soft_delete = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::LitStr>()?.value()
+                } else {
+                    "deleted_at".to_string()
+                });
+            } else if meta.path.is_ident("tenant") {
+                tenant = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::LitStr>()?.value()
+                } else {
+                    "tenant_id".to_string()
+                });
+            } else if meta.path.is_ident("hooks") {
+                custom_hooks = true;
+            } else {
+                return Err(meta.error(
+                    "unknown photon attribute; expected `table = \"...\"`, `timestamps`, `soft_delete`, `tenant` or `hooks`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    let table = table.ok_or_else(|| {
+        syn::Error::new_spanned(
+            struct_name,
+            "#[derive(Model)] requires #[photon(table = \"...\")]",
+        )
+    })?;
+
+    let fields = match &input.data {
+        syn::Data::Struct(data) => match &data.fields {
+            syn::Fields::Named(named) => &named.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    struct_name,
+                    "#[derive(Model)] only supports structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                struct_name,
+                "#[derive(Model)] only supports structs",
+            ));
+        }
+    };
+
+    let mut columns = Vec::new();
+    let mut primary_key = None;
+    let mut values = Vec::new();
+    let mut timestamp_fields = Vec::new();
+    let mut tenant_ty = None;
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let mut column = ident.to_string().trim_start_matches("r#").to_string();
+        let mut is_primary = false;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("photon")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("primary_key") {
+                    is_primary = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("column") {
+                    column = meta.value()?.parse::<syn::LitStr>()?.value();
+                } else if meta.path.is_ident("unique") || meta.path.is_ident("index") {
+                    // Schema hints, read by `nucleus db diff`
+                } else if meta.path.is_ident("references") {
+                    let target = meta.value()?.parse::<syn::LitStr>()?;
+                    if target.value().split_once('.').is_none() {
+                        return Err(syn::Error::new_spanned(
+                            target,
+                            "expected `references = \"table.column\"`",
+                        ));
+                    }
+                } else {
+                    return Err(meta.error(
+                        "unknown photon attribute; expected `primary_key`, `column = \"...\"`, `skip`, `unique`, `index` or `references = \"...\"`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+
+        let const_name = syn::Ident::new(
+            &ident.to_string().trim_start_matches("r#").to_uppercase(),
+            ident.span(),
+        );
+        columns.push(quote! {
+            pub const #const_name: nucleus_std::photon::Column<Self, #ty> =
+                nucleus_std::photon::Column::new(#column);
+        });
+
+        if tenant.as_deref() == Some(column.as_str()) {
+            tenant_ty = Some(ty.clone());
+        }
+
+        let managed = (timestamps && (column == "created_at" || column == "updated_at"))
+            || soft_delete.as_deref() == Some(column.as_str());
+        if managed {
+            timestamp_fields.push((ident.clone(), column.clone()));
+        }
+
+        if is_primary {
+            if primary_key.is_some() {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "#[derive(Model)] supports a single #[photon(primary_key)] field",
+                ));
+            }
+            primary_key = Some((ident.clone(), column));
+        } else {
+            values.push((ident.clone(), column));
+        }
+    }
+
+    // Fall back to a field named `id` when no primary key is marked
+    if primary_key.is_none()
+        && let Some(pos) = values.iter().position(|(ident, _)| ident == "id")
+    {
+        primary_key = Some(values.remove(pos));
+    }
+    let (pk_ident, pk_column) = primary_key.ok_or_else(|| {
+        syn::Error::new_spanned(
+            struct_name,
+            "#[derive(Model)] requires a #[photon(primary_key)] field or a field named `id`",
+        )
+    })?;
+
+    let value_pairs = values.iter().map(|(ident, column)| {
+        quote! {
+            (#column, nucleus_std::photon::QueryValue::from(::std::clone::Clone::clone(&self.#ident)))
+        }
+    });
+
+    let soft_delete_column = match &soft_delete {
+        Some(column) => quote! { ::std::option::Option::Some(#column) },
+        None => quote! { ::std::option::Option::None },
+    };
+    let tenant_column = match &tenant {
+        Some(column) => quote! { ::std::option::Option::Some(#column) },
+        None => quote! { ::std::option::Option::None },
+    };
+    // Bind the tenant as the field's type; text if the column has no field
+    let tenant_key = tenant_ty.map(|ty| {
+        quote! {
+            fn tenant_key(tenant: &str) -> ::std::option::Option<nucleus_std::photon::QueryValue> {
+                <#ty as nucleus_std::photon::TenantKey>::tenant_key(tenant)
+            }
+        }
+    });
+    let timestamp_arms = timestamp_fields.iter().map(|(ident, column)| {
+        quote! {
+            #column => nucleus_std::photon::TimestampField::set_timestamp(&mut self.#ident, value),
+        }
+    });
+    let hooks_impl = if custom_hooks {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics nucleus_std::photon::Hooks for #struct_name #ty_generics #where_clause {}
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#columns)*
+        }
+
+        impl #impl_generics nucleus_std::photon::query::Model for #struct_name #ty_generics #where_clause {
+            fn table_name() -> &'static str {
+                #table
+            }
+
+            fn timestamps() -> bool {
+                #timestamps
+            }
+
+            fn soft_delete_column() -> ::std::option::Option<&'static str> {
+                #soft_delete_column
+            }
+
+            fn tenant_column() -> ::std::option::Option<&'static str> {
+                #tenant_column
+            }
+
+            #tenant_key
+        }
+
+        #hooks_impl
+
+        impl #impl_generics nucleus_std::photon::Record for #struct_name #ty_generics #where_clause {
+            fn primary_key() -> &'static str {
+                #pk_column
+            }
+
+            fn primary_key_value(&self) -> ::std::option::Option<nucleus_std::photon::QueryValue> {
+                nucleus_std::photon::PrimaryKey::key_value(&self.#pk_ident)
+            }
+
+            fn set_primary_key(&mut self, id: i64) {
+                nucleus_std::photon::PrimaryKey::set_generated(&mut self.#pk_ident, id);
+            }
+
+            fn column_values(&self) -> ::std::vec::Vec<(&'static str, nucleus_std::photon::QueryValue)> {
+                ::std::vec![#(#value_pairs),*]
+            }
+
+            #[allow(unused_variables)]
+            fn set_timestamp(
+                &mut self,
+                column: &str,
+                value: ::std::option::Option<nucleus_std::chrono::DateTime<nucleus_std::chrono::Utc>>,
+            ) {
+                match column {
+                    #(#timestamp_arms)*
+                    _ => {}
+                }
+            }
+        }
+    })
+}
@@ -1,6 +1,7 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 
@@ -17,6 +18,7 @@ pub struct Config {
 
     pub payments: Option<PaymentsConfig>,
     pub chain: Option<ChainConfig>,
+    pub storage: Option<StorageConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -29,6 +31,15 @@ pub struct ServerConfig {
     pub environment: String,
     #[serde(default)]
     pub omit_signature: bool,
+    /// Serve HTTPS in-process with these PEM files
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Listen on a Unix domain socket instead of `host:port`
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// Seconds to wait for in-flight requests and sockets on shutdown
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
 }
 
 impl Default for ServerConfig {
@@ -38,10 +49,32 @@ impl Default for ServerConfig {
             port: default_port(),
             environment: default_env(),
             omit_signature: false,
+            tls: None,
+            unix_socket: None,
+            shutdown_timeout: default_shutdown_timeout(),
         }
     }
 }
 
+impl ServerConfig {
+    /// `host:port` to bind the TCP listener to
+    pub fn bind_address(&self) -> String {
+        if self.host.contains(':') && !self.host.starts_with('[') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TlsConfig {
+    /// Path to the PEM certificate chain
+    pub cert: String,
+    /// Path to the PEM private key
+    pub key: String,
+}
+
 fn default_host() -> String {
     "0.0.0.0".to_string()
 }
@@ -51,6 +84,9 @@ fn default_port() -> u16 {
 fn default_env() -> String {
     "development".to_string()
 }
+fn default_shutdown_timeout() -> u64 {
+    30
+}
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseConfig {
@@ -58,6 +94,21 @@ pub struct DatabaseConfig {
     pub url: String,
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
+    /// Read replica URLs; SELECTs are spread across them
+    #[serde(default)]
+    pub replicas: Vec<String>,
+    /// Additional named databases, selected with `Builder::on(name)`
+    #[serde(default)]
+    pub connections: HashMap<String, NamedDatabaseConfig>,
+    /// Tenants with their own schema or database
+    #[serde(default)]
+    pub tenants: HashMap<String, TenantDatabaseConfig>,
+    /// Most tenant databases kept connected at once
+    #[serde(default = "default_tenant_pools")]
+    pub tenant_pools: usize,
+    /// Pool size for each tenant database
+    #[serde(default = "default_max_connections")]
+    pub tenant_max_connections: u32,
 }
 
 impl Default for DatabaseConfig {
@@ -65,16 +116,43 @@ impl Default for DatabaseConfig {
         Self {
             url: default_db_url(),
             max_connections: default_max_connections(),
+            replicas: Vec::new(),
+            connections: HashMap::new(),
+            tenants: HashMap::new(),
+            tenant_pools: default_tenant_pools(),
+            tenant_max_connections: default_max_connections(),
         }
     }
 }
 
+/// A named database under `[database.connections.<name>]`
+#[derive(Debug, Deserialize, Clone)]
+pub struct NamedDatabaseConfig {
+    pub url: String,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default)]
+    pub replicas: Vec<String>,
+}
+
+/// An isolated tenant under `[database.tenants.<id>]`; set one of the two
+#[derive(Debug, Deserialize, Clone)]
+pub struct TenantDatabaseConfig {
+    /// PostgreSQL schema in the default database
+    pub schema: Option<String>,
+    /// URL of the tenant's own database
+    pub url: Option<String>,
+}
+
 fn default_db_url() -> String {
     "sqlite:nucleus.db".to_string()
 }
 fn default_max_connections() -> u32 {
     5
 }
+fn default_tenant_pools() -> usize {
+    16
+}
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct AppConfig {
@@ -213,6 +291,42 @@ pub struct ChainConfig {
     pub chain_id: u64,
 }
 
+/// Object storage for uploads, selected by `driver`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "driver", rename_all = "lowercase")]
+pub enum StorageConfig {
+    Local {
+        #[serde(default = "default_storage_path")]
+        path: String,
+        /// URL prefix the directory is served at
+        #[serde(default = "default_storage_url")]
+        base_url: String,
+    },
+    S3 {
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_region")]
+        region: String,
+        access_key: String,
+        secret_key: String,
+        /// `{endpoint}/{bucket}` URLs instead of `{bucket}.{endpoint}`
+        #[serde(default)]
+        path_style: bool,
+        /// Base URL for public object URLs, e.g. a CDN
+        public_url: Option<String>,
+    },
+}
+
+fn default_storage_path() -> String {
+    "uploads".to_string()
+}
+fn default_storage_url() -> String {
+    "/uploads".to_string()
+}
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
 use crate::errors::{NucleusError, Result};
 
 impl Config {
@@ -306,4 +420,73 @@ mod tests {
         let inter = Config::interpolate_env(raw);
         assert!(inter.contains("host = \"127.0.0.1\""));
     }
+
+    #[test]
+    fn test_database_replicas_and_connections() {
+        let config: Config = toml::from_str(
+            "
+            [database]
+            url = \"postgres://primary/app\"
+            replicas = [\"postgres://replica1/app\", \"postgres://replica2/app\"]
+
+            [database.connections.analytics]
+            url = \"postgres://warehouse/analytics\"
+            ",
+        )
+        .unwrap();
+        assert_eq!(config.database.replicas.len(), 2);
+        let analytics = &config.database.connections["analytics"];
+        assert_eq!(analytics.url, "postgres://warehouse/analytics");
+        assert!(analytics.replicas.is_empty());
+        assert_eq!(analytics.max_connections, 5);
+    }
+
+    #[test]
+    fn test_database_tenants() {
+        let config: Config = toml::from_str(
+            "
+            [database]
+            url = \"postgres://primary/app\"
+            tenant_pools = 4
+
+            [database.tenants.acme]
+            schema = \"tenant_acme\"
+
+            [database.tenants.globex]
+            url = \"postgres://db2/globex\"
+            ",
+        )
+        .unwrap();
+        let db = &config.database;
+        assert_eq!(db.tenant_pools, 4);
+        assert_eq!(db.tenant_max_connections, 5);
+        assert_eq!(db.tenants["acme"].schema.as_deref(), Some("tenant_acme"));
+        assert_eq!(
+            db.tenants["globex"].url.as_deref(),
+            Some("postgres://db2/globex")
+        );
+        assert_eq!(DatabaseConfig::default().tenant_pools, 16);
+    }
+
+    #[test]
+    fn test_server_tls_and_unix_socket() {
+        let config: Config = toml::from_str(
+            "
+            [server]
+            host = \"::1\"
+            port = 8443
+            shutdown_timeout = 5
+
+            [server.tls]
+            cert = \"certs/server.pem\"
+            key = \"certs/server.key\"
+            ",
+        )
+        .unwrap();
+        assert_eq!(config.server.bind_address(), "[::1]:8443");
+        assert_eq!(config.server.tls.unwrap().key, "certs/server.key");
+        assert_eq!(config.server.shutdown_timeout, 5);
+        assert!(config.server.unix_socket.is_none());
+        assert_eq!(ServerConfig::default().bind_address(), "0.0.0.0:3000");
+    }
 }
@@ -0,0 +1,899 @@
+//! Nucleus CSRF Protection
+//!
+//! `CsrfLayer` rejects unsafe requests (POST, PUT, PATCH, DELETE) that do not
+//! carry a valid token. A token is accepted from:
+//! - the `_csrf` form field (urlencoded or multipart bodies)
+//! - the `X-CSRF-Token` header
+//!
+//! and is valid if it matches the session's token, or the signed
+//! `nucleus_csrf` double-submit cookie the layer hands out on safe requests
+//! (readable from JavaScript for SPA clients). The cookie's signature covers
+//! the stored session's ID, so a cookie planted from a sibling subdomain
+//! does not validate for someone else's session; it is reissued whenever the
+//! session is stored, replaced or destroyed.
+//!
+//! Bearer-authenticated requests, tus upload requests (`Tus-Resumable`
+//! header) and configured paths are exempt. Multipart bodies are only read up
+//! to the `_csrf` part, so put it before any file input (generated forms do).
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::csrf::{CsrfConfig, CsrfLayer, CsrfToken};
+//!
+//! let app = Router::new()
+//!     .route("/posts", get(new_post).post(create_post))
+//!     .layer(CsrfLayer::new(CsrfConfig::default().exempt("/webhooks/*")))
+//!     .layer(SessionLayer::new(store, SessionConfig::production()));
+//!
+//! async fn new_post(csrf: CsrfToken) -> Html<String> {
+//!     Html(format!("<form method=\"post\">{}</form>", csrf.hidden_field()))
+//! }
+//! ```
+//!
+//! `SessionLayer` must wrap `CsrfLayer` (be added after it) so the session is
+//! loaded when the check runs.
+
+use crate::session::{app_secret, read_cookie, SessionError, SessionSlot};
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::convert::Infallible;
+use std::sync::Arc;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CONFIG
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// CSRF middleware configuration
+#[derive(Debug, Clone)]
+pub struct CsrfConfig {
+    /// Form field carrying the token
+    pub field_name: String,
+    /// Header carrying the token
+    pub header_name: String,
+    /// Double-submit cookie name
+    pub cookie_name: String,
+    /// Mark the double-submit cookie `Secure`
+    pub cookie_secure: bool,
+    /// Paths that skip the check; a trailing `*` matches a prefix
+    pub exempt_paths: Vec<String>,
+    /// Skip the check for `Authorization: Bearer` requests
+    pub exempt_bearer: bool,
+    /// Skip the check for requests carrying any of these headers
+    ///
+    /// Browsers only send custom headers cross-origin after a CORS
+    /// preflight, so they cannot be forged by another site's form.
+    pub exempt_headers: Vec<String>,
+    /// Largest urlencoded body buffered while looking for the form field
+    pub max_body_size: usize,
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        Self {
+            field_name: "_csrf".to_string(),
+            header_name: "x-csrf-token".to_string(),
+            cookie_name: "nucleus_csrf".to_string(),
+            cookie_secure: true,
+            exempt_paths: Vec::new(),
+            exempt_bearer: true,
+            exempt_headers: vec!["tus-resumable".to_string()],
+            max_body_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl CsrfConfig {
+    /// Development config (cookie works over plain HTTP)
+    pub fn development() -> Self {
+        Self {
+            cookie_secure: false,
+            ..Default::default()
+        }
+    }
+
+    /// Exempt a path (`/webhooks/stripe`) or prefix (`/webhooks/*`)
+    pub fn exempt(mut self, path: &str) -> Self {
+        self.exempt_paths.push(path.to_string());
+        self
+    }
+
+    /// Check bearer-authenticated requests too
+    pub fn check_bearer(mut self) -> Self {
+        self.exempt_bearer = false;
+        self
+    }
+
+    /// Exempt requests carrying `header` (e.g. an API key header)
+    pub fn exempt_header(mut self, header: &str) -> Self {
+        self.exempt_headers.push(header.to_ascii_lowercase());
+        self
+    }
+
+    fn has_exempt_header(&self, headers: &HeaderMap) -> bool {
+        self.exempt_headers
+            .iter()
+            .any(|name| headers.contains_key(name.as_str()))
+    }
+
+    fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_paths.iter().any(|p| match p.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == p,
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// LAYER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tower layer enforcing CSRF tokens on unsafe methods
+#[derive(Clone)]
+pub struct CsrfLayer {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    config: Arc<CsrfConfig>,
+    key: Vec<u8>,
+}
+
+impl CsrfLayer {
+    /// Sign the double-submit cookie with `app.secret_key` / `SECRET_KEY`
+    pub fn new(config: CsrfConfig) -> Self {
+        let secret = app_secret().unwrap_or_else(|| {
+            tracing::warn!("no app.secret_key or SECRET_KEY set; using a random CSRF key");
+            Uuid::new_v4().as_bytes().to_vec()
+        });
+        Self::with_secret(config, secret)
+    }
+
+    /// Sign the double-submit cookie with an explicit secret
+    pub fn with_secret(config: CsrfConfig, secret: impl AsRef<[u8]>) -> Self {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_ref()).expect("HMAC accepts any key length");
+        mac.update(b"nucleus.csrf.cookie");
+        Self {
+            shared: Arc::new(Shared {
+                config: Arc::new(config),
+                key: mac.finalize().into_bytes().to_vec(),
+            }),
+        }
+    }
+}
+
+impl<I> tower::Layer<I> for CsrfLayer {
+    type Service = CsrfService<I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        CsrfService {
+            inner,
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+/// Service produced by `CsrfLayer`
+#[derive(Clone)]
+pub struct CsrfService<I> {
+    inner: I,
+    shared: Arc<Shared>,
+}
+
+impl<I> tower::Service<Request> for CsrfService<I>
+where
+    I: tower::Service<Request, Response = Response> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = I::Error;
+    type Future = futures_util::future::BoxFuture<'static, Result<Response, I::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let shared = Arc::clone(&self.shared);
+
+        Box::pin(async move {
+            let config = &shared.config;
+            req.extensions_mut().insert(Arc::clone(config));
+
+            let slot = req.extensions().get::<SessionSlot>().cloned();
+            let session_id = stored_session_id(slot.as_ref());
+            let cookie = read_cookie(req.headers(), &config.cookie_name)
+                .filter(|value| shared.verify_cookie(value, &session_id));
+
+            if is_safe(req.method()) {
+                // Handed to `CsrfToken` so forms can render it without a session
+                let token = cookie
+                    .clone()
+                    .unwrap_or_else(|| shared.new_cookie(&session_id));
+                req.extensions_mut().insert(CookieToken(token.clone()));
+                let mut response = inner.call(req).await?;
+                if !shared.rebind_cookie(&mut response, slot.as_ref(), &session_id)
+                    && cookie.is_none()
+                {
+                    shared.set_cookie(&mut response, &token);
+                }
+                return Ok(response);
+            }
+            if let Some(token) = &cookie {
+                req.extensions_mut().insert(CookieToken(token.clone()));
+            }
+
+            if config.is_exempt(req.uri().path())
+                || (config.exempt_bearer && is_bearer(req.headers()))
+                || config.has_exempt_header(req.headers())
+            {
+                return inner.call(req).await;
+            }
+
+            let (req, submitted) = match submitted_token(req, config).await {
+                Ok(found) => found,
+                Err(rejection) => return Ok(rejection),
+            };
+            let valid = submitted.is_some_and(|token| {
+                session_token_matches(&req, &token)
+                    || cookie.as_deref().is_some_and(|c| constant_eq(c, &token))
+            });
+            if !valid {
+                tracing::warn!(path = %req.uri().path(), "CSRF token missing or invalid");
+                return Ok(SessionError::InvalidCsrfToken.into_response());
+            }
+
+            let mut response = inner.call(req).await?;
+            shared.rebind_cookie(&mut response, slot.as_ref(), &session_id);
+            Ok(response)
+        })
+    }
+}
+
+impl Shared {
+    /// MAC over the nonce and the ID of the session it belongs to (empty
+    /// without a stored session)
+    fn sign(&self, nonce: &str, session_id: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(nonce.as_bytes());
+        mac.update(b"\0");
+        mac.update(session_id.as_bytes());
+        mac
+    }
+
+    fn verify_cookie(&self, value: &str, session_id: &str) -> bool {
+        let Some((nonce, signature)) = value.split_once('.') else {
+            return false;
+        };
+        URL_SAFE_NO_PAD
+            .decode(signature)
+            .is_ok_and(|sig| self.sign(nonce, session_id).verify_slice(&sig).is_ok())
+    }
+
+    fn new_cookie(&self, session_id: &str) -> String {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let signature =
+            URL_SAFE_NO_PAD.encode(self.sign(&nonce, session_id).finalize().into_bytes());
+        format!("{}.{}", nonce, signature)
+    }
+
+    /// Issue a new cookie if the request stored, replaced or destroyed the
+    /// session, so the old one stops validating; returns whether it did
+    fn rebind_cookie(
+        &self,
+        response: &mut Response,
+        slot: Option<&SessionSlot>,
+        session_id: &str,
+    ) -> bool {
+        let after = stored_session_id(slot);
+        if after == session_id {
+            return false;
+        }
+        self.set_cookie(response, &self.new_cookie(&after));
+        true
+    }
+
+    fn set_cookie(&self, response: &mut Response, value: &str) {
+        // Not HttpOnly: JavaScript echoes it back in the header
+        let mut cookie = format!(
+            "{}={}; Path=/; SameSite=Lax",
+            self.config.cookie_name, value
+        );
+        if self.config.cookie_secure {
+            cookie.push_str("; Secure");
+        }
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn is_bearer(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.len() > 7 && v[..7].eq_ignore_ascii_case("bearer "))
+}
+
+/// ID of the stored session in `slot`, or empty without one
+fn stored_session_id(slot: Option<&SessionSlot>) -> String {
+    slot.and_then(|slot| {
+        let session = slot.lock().ok()?;
+        Some(session.as_ref()?.stored_id()?.to_string())
+    })
+    .unwrap_or_default()
+}
+
+fn session_token_matches(req: &Request, token: &str) -> bool {
+    req.extensions()
+        .get::<SessionSlot>()
+        .and_then(|slot| {
+            let session = slot.lock().ok()?;
+            Some(session.as_ref()?.verify_csrf(token))
+        })
+        .unwrap_or(false)
+}
+
+fn constant_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Token lookup
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Largest multipart prefix read while looking for the token part
+const MULTIPART_PREFIX_LIMIT: usize = 64 * 1024;
+
+/// Token from the header, or from the form body
+///
+/// Urlencoded bodies are buffered; multipart bodies are read only until the
+/// token part, and the bytes read are put back in front of the rest.
+async fn submitted_token(
+    req: Request,
+    config: &CsrfConfig,
+) -> Result<(Request, Option<String>), Response> {
+    if let Some(token) = req
+        .headers()
+        .get(config.header_name.as_str())
+        .and_then(|v| v.to_str().ok())
+    {
+        let token = token.to_string();
+        return Ok((req, Some(token)));
+    }
+
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let is_form = content_type.starts_with("application/x-www-form-urlencoded");
+    let boundary = multer::parse_boundary(&content_type).ok();
+    if !is_form && boundary.is_none() {
+        return Ok((req, None));
+    }
+
+    let (parts, body) = req.into_parts();
+    let Some(boundary) = boundary else {
+        let bytes = axum::body::to_bytes(body, config.max_body_size)
+            .await
+            .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?;
+        let token = urlencoded_field(&bytes, &config.field_name);
+        return Ok((Request::from_parts(parts, Body::from(bytes)), token));
+    };
+
+    let mut rest = body.into_data_stream();
+    let mut read: Vec<Bytes> = Vec::new();
+    let mut read_len = 0;
+    let token = loop {
+        match multipart_field(&read, &boundary, &config.field_name).await {
+            Lookup::Found(token) => break Some(token),
+            Lookup::Absent => break None,
+            Lookup::Incomplete if read_len > MULTIPART_PREFIX_LIMIT => break None,
+            Lookup::Incomplete => match rest.next().await {
+                Some(Ok(chunk)) => {
+                    read_len += chunk.len();
+                    read.push(chunk);
+                }
+                Some(Err(_)) => return Err(StatusCode::BAD_REQUEST.into_response()),
+                None => break None,
+            },
+        }
+    };
+
+    let replay = futures_util::stream::iter(read.into_iter().map(Ok::<_, axum::Error>));
+    let body = Body::from_stream(replay.chain(rest));
+    Ok((Request::from_parts(parts, body), token))
+}
+
+fn urlencoded_field(body: &[u8], name: &str) -> Option<String> {
+    let body = std::str::from_utf8(body).ok()?;
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == name)
+        .and_then(|(_, v)| {
+            urlencoding::decode(&v.replace('+', " "))
+                .ok()
+                .map(|v| v.into_owned())
+        })
+}
+
+enum Lookup {
+    Found(String),
+    Absent,
+    /// The prefix read so far ends before the field does
+    Incomplete,
+}
+
+/// Look for the `name` part in the first bytes of a multipart body
+async fn multipart_field(prefix: &[Bytes], boundary: &str, name: &str) -> Lookup {
+    let stream = futures_util::stream::iter(prefix.iter().cloned().map(Ok::<_, Infallible>));
+    let mut multipart = multer::Multipart::new(stream, boundary);
+    loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some(name) => {
+                return match field.text().await {
+                    Ok(token) => Lookup::Found(token),
+                    Err(_) => Lookup::Incomplete,
+                };
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => return Lookup::Absent,
+            // Most likely the prefix stops mid-part
+            Err(_) => return Lookup::Incomplete,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// EXTRACTOR
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// The double-submit cookie value `CsrfLayer` set or received
+#[derive(Clone)]
+struct CookieToken(String);
+
+/// The CSRF token for rendering into forms
+///
+/// Visitors with a stored session get the session's token. Everyone else
+/// gets the `CsrfLayer` double-submit cookie, so rendering a form for an
+/// anonymous visitor does not create a session. Only without `CsrfLayer` is
+/// a brand-new session kept so its token validates; with neither layer the
+/// token is empty.
+pub struct CsrfToken {
+    slot: Option<SessionSlot>,
+    cookie: Option<String>,
+    field_name: String,
+}
+
+impl CsrfToken {
+    /// The token value
+    pub fn value(&self) -> String {
+        let mut guard = self.slot.as_ref().and_then(|slot| slot.lock().ok());
+        match (guard.as_mut().and_then(|s| s.as_mut()), &self.cookie) {
+            (Some(session), _) if session.is_stored() => session.csrf_token().to_string(),
+            (_, Some(cookie)) => cookie.clone(),
+            (Some(session), None) => {
+                session.retain();
+                session.csrf_token().to_string()
+            }
+            (None, None) => String::new(),
+        }
+    }
+
+    /// `<input type="hidden">` carrying the token
+    pub fn hidden_field(&self) -> String {
+        format!(
+            r#"<input type="hidden" name="{}" value="{}">"#,
+            self.field_name,
+            self.value()
+        )
+    }
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CsrfToken {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let field_name = parts
+            .extensions
+            .get::<Arc<CsrfConfig>>()
+            .map(|c| c.field_name.clone())
+            .unwrap_or_else(|| CsrfConfig::default().field_name);
+        Ok(Self {
+            slot: parts.extensions.get::<SessionSlot>().cloned(),
+            cookie: parts.extensions.get::<CookieToken>().map(|c| c.0.clone()),
+            field_name,
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{MemorySessionStore, Session, SessionConfig, SessionLayer};
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn app(config: CsrfConfig) -> Router {
+        Router::new()
+            .route(
+                "/form",
+                get(|csrf: CsrfToken| async move { csrf.hidden_field() })
+                    .post(|| async { "created" }),
+            )
+            .route("/webhooks/stripe", axum::routing::post(|| async { "hook" }))
+            .route(
+                "/upload",
+                axum::routing::post(|body: Bytes| async move { body.len().to_string() })
+                    .patch(|| async { "chunk" }),
+            )
+            .route(
+                "/whoami",
+                get(|session: Session| async move { session.csrf_token().to_string() }),
+            )
+            .route(
+                "/login",
+                axum::routing::post(|mut session: Session| async move {
+                    session.login("42", true);
+                    "ok"
+                }),
+            )
+            .layer(CsrfLayer::with_secret(config, "test-secret"))
+            .layer(SessionLayer::with_secret(
+                MemorySessionStore::new(),
+                SessionConfig::development(),
+                "test-secret",
+            ))
+    }
+
+    async fn send(app: &Router, req: axum::http::Request<Body>) -> (StatusCode, HeaderMap, String) {
+        let res = app.clone().oneshot(req).await.unwrap();
+        let (status, headers) = (res.status(), res.headers().clone());
+        let body = axum::body::to_bytes(res.into_body(), 4096).await.unwrap();
+        (status, headers, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn cookies(headers: &HeaderMap) -> String {
+        headers
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|v| v.to_str().unwrap().split(';').next().unwrap().to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn post(
+        path: &str,
+        cookie: &str,
+        content_type: &str,
+        body: String,
+    ) -> axum::http::Request<Body> {
+        axum::http::Request::post(path)
+            .header(header::COOKIE, cookie)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    /// Render the form and return (session + csrf cookies, token)
+    async fn render_form(app: &Router) -> (String, String) {
+        let (_, headers, html) = send(
+            app,
+            axum::http::Request::get("/form")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        let token = html
+            .split("value=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap()
+            .to_string();
+        assert!(html.contains("name=\"_csrf\""));
+        (cookies(&headers), token)
+    }
+
+    #[tokio::test]
+    async fn test_form_field_token() {
+        let app = app(CsrfConfig::development());
+        let (cookie, token) = render_form(&app).await;
+        assert!(!token.is_empty());
+
+        let form = "application/x-www-form-urlencoded";
+        let (status, _, body) = send(
+            &app,
+            post(
+                "/form",
+                &cookie,
+                form,
+                format!("title=Hi+there&_csrf={}", token),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "created");
+
+        let (status, ..) = send(&app, post("/form", &cookie, form, "_csrf=wrong".into())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, ..) = send(&app, post("/form", &cookie, form, "title=x".into())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_cookie_is_bound_to_session() {
+        let app = app(CsrfConfig::development());
+        let csrf_cookie = |headers: &HeaderMap| {
+            cookies(headers)
+                .split("; ")
+                .find_map(|c| c.strip_prefix("nucleus_csrf="))
+                .map(str::to_string)
+        };
+        let header_post = |path: &str, cookie: &str, token: &str| {
+            axum::http::Request::post(path)
+                .header(header::COOKIE, cookie)
+                .header("x-csrf-token", token)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // An attacker's anonymous cookie, planted from a sibling subdomain
+        let (attacker, _) = render_form(&app).await;
+        let planted = attacker.strip_prefix("nucleus_csrf=").unwrap().to_string();
+
+        // The victim logs in; the response rebinds the cookie to the new session
+        let (cookie, token) = render_form(&app).await;
+        let (status, headers, _) = send(&app, header_post("/login", &cookie, &token)).await;
+        assert_eq!(status, StatusCode::OK);
+        let bound = csrf_cookie(&headers).unwrap();
+        assert_ne!(bound, token);
+        let session = cookies(&headers)
+            .split("; ")
+            .find(|c| !c.starts_with("nucleus_csrf="))
+            .unwrap()
+            .to_string();
+
+        let tossed = format!("{}; nucleus_csrf={}", session, planted);
+        let (status, ..) = send(&app, header_post("/form", &tossed, &planted)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let own = format!("{}; nucleus_csrf={}", session, bound);
+        let (status, ..) = send(&app, header_post("/form", &own, &bound)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_and_header_token() {
+        let app = app(CsrfConfig::development());
+        let (cookie, token) = render_form(&app).await;
+
+        let body = format!(
+            "--XyZ\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\n{}\r\n--XyZ--\r\n",
+            token
+        );
+        let (status, ..) = send(
+            &app,
+            post("/form", &cookie, "multipart/form-data; boundary=XyZ", body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let req = axum::http::Request::post("/form")
+            .header(header::COOKIE, &cookie)
+            .header("x-csrf-token", &token)
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, req).await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_is_streamed_past_the_token() {
+        let config = CsrfConfig {
+            max_body_size: 1024,
+            ..CsrfConfig::development()
+        };
+        let app = app(config);
+        let (cookie, token) = render_form(&app).await;
+
+        // Sent in 8 KiB chunks, like a browser upload
+        let upload = |body: String| {
+            let chunks: Vec<Result<Bytes, Infallible>> = body
+                .into_bytes()
+                .chunks(8 * 1024)
+                .map(|c| Ok(Bytes::copy_from_slice(c)))
+                .collect();
+            axum::http::Request::post("/upload")
+                .header(header::COOKIE, &cookie)
+                .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XyZ")
+                .body(Body::from_stream(futures_util::stream::iter(chunks)))
+                .unwrap()
+        };
+        let token_part = format!(
+            "--XyZ\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\n{}\r\n",
+            token
+        );
+        let file_part = format!(
+            "--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\r\n{}\r\n",
+            "x".repeat(256 * 1024)
+        );
+
+        // Far larger than max_body_size; only the token part is read up front
+        let body = format!("{}{}--XyZ--\r\n", token_part, file_part);
+        let expected = body.len().to_string();
+        let (status, _, received) = send(&app, upload(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received, expected);
+
+        // A token after a large file is out of reach
+        let body = format!("{}{}--XyZ--\r\n", file_part, token_part);
+        assert_eq!(send(&app, upload(body)).await.0, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_double_submit_cookie() {
+        let app = app(CsrfConfig::development());
+        let (_, headers, _) = send(
+            &app,
+            axum::http::Request::get("/whoami")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        let cookie = cookies(&headers);
+        let csrf_cookie = cookie
+            .split("; ")
+            .find_map(|c| c.strip_prefix("nucleus_csrf="))
+            .expect("double-submit cookie issued")
+            .to_string();
+
+        let echo = |value: &str| {
+            axum::http::Request::post("/form")
+                .header(header::COOKIE, &cookie)
+                .header("x-csrf-token", value)
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(send(&app, echo(&csrf_cookie)).await.0, StatusCode::OK);
+
+        // A cookie the server did not sign is ignored
+        let forged = axum::http::Request::post("/form")
+            .header(header::COOKIE, "nucleus_csrf=abc.def")
+            .header("x-csrf-token", "abc.def")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, forged).await.0, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_exemptions() {
+        let app = app(CsrfConfig::development().exempt("/webhooks/*"));
+        let json = "application/json";
+
+        let (status, ..) = send(&app, post("/webhooks/stripe", "", json, "{}".into())).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let bearer = axum::http::Request::post("/form")
+            .header(header::AUTHORIZATION, "Bearer abc")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, bearer).await.0, StatusCode::OK);
+
+        let strict = self::app(CsrfConfig::development().check_bearer());
+        let bearer = axum::http::Request::post("/form")
+            .header(header::AUTHORIZATION, "Bearer abc")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&strict, bearer).await.0, StatusCode::FORBIDDEN);
+
+        // tus clients send Tus-Resumable, which needs a CORS preflight
+        let tus = axum::http::Request::patch("/upload")
+            .header("tus-resumable", "1.0.0")
+            .header(header::CONTENT_TYPE, "application/offset+octet-stream")
+            .body(Body::from("chunk"))
+            .unwrap();
+        assert_eq!(send(&app, tus).await.0, StatusCode::OK);
+        let (status, ..) = send(&app, post("/webhooks/stripe", "", json, "{}".into())).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_form_creates_no_session() {
+        let app = app(CsrfConfig::development());
+        let (cookie, token) = render_form(&app).await;
+        // Only the double-submit cookie; its value is the rendered token
+        assert_eq!(cookie, format!("nucleus_csrf={}", token));
+
+        // Once a session is stored, forms use its token instead
+        let store = MemorySessionStore::new();
+        let app = Router::new()
+            .route("/form", get(|csrf: CsrfToken| async move { csrf.value() }))
+            .route(
+                "/login",
+                get(|mut session: Session| async move {
+                    session.set("user", "ada");
+                    session.csrf_token().to_string()
+                }),
+            )
+            .layer(CsrfLayer::with_secret(CsrfConfig::development(), "k"))
+            .layer(SessionLayer::with_secret(
+                store,
+                SessionConfig::development(),
+                "k",
+            ));
+        let get = |path: &str, cookie: &str| {
+            axum::http::Request::get(path)
+                .header(header::COOKIE, cookie)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let (_, headers, session_token) = send(&app, get("/login", "")).await;
+        let (_, _, token) = send(&app, get("/form", &cookies(&headers))).await;
+        assert_eq!(token, session_token);
+    }
+
+    #[tokio::test]
+    async fn test_session_kept_without_csrf_layer() {
+        let app = Router::new()
+            .route("/", get(|csrf: CsrfToken| async move { csrf.value() }))
+            .layer(SessionLayer::with_secret(
+                MemorySessionStore::new(),
+                SessionConfig::development(),
+                "k",
+            ));
+        let (_, headers, token) = send(
+            &app,
+            axum::http::Request::get("/").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert!(!token.is_empty());
+        assert!(headers.contains_key(header::SET_COOKIE));
+    }
+
+    #[tokio::test]
+    async fn test_token_without_session_layer() {
+        let app = Router::new().route("/", get(|csrf: CsrfToken| async move { csrf.value() }));
+        let (status, _, body) = send(
+            &app,
+            axum::http::Request::get("/").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "");
+    }
+}
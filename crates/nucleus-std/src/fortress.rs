@@ -1,3 +1,4 @@
+use crate::tokens::{self, Claims, TokenError};
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
@@ -11,7 +12,7 @@ use axum::{
 };
 use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 
 type HmacSha256 = Hmac<Sha256>;
@@ -188,6 +189,8 @@ impl Fortress {
             .is_ok()
     }
 
+    /// Legacy `hex(user_id).hmac` token without expiry.
+    /// Prefer [`crate::tokens::TokenManager`] for anything new.
     pub fn generate_token(user_id: &str, secret: &str) -> String {
         let mut mac =
             HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take any size key");
@@ -213,10 +216,7 @@ impl Fortress {
             token
         };
 
-        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC error");
-        mac.update(user_id.as_bytes());
-        let expected_sig = hex::encode(mac.finalize().into_bytes());
-        token_sig == expected_sig
+        Self::check_signature(user_id, token_sig, secret)
     }
 
     pub fn validate_token(token: &str, secret: &str) -> Result<String, String> {
@@ -232,18 +232,23 @@ impl Fortress {
         let user_id =
             String::from_utf8(user_id_bytes).map_err(|_| "Invalid ID UTF-8".to_string())?;
 
-        // Recompute sig
-        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC error");
-        mac.update(user_id.as_bytes());
-        let expected_sig = hex::encode(mac.finalize().into_bytes());
-
-        if sig != expected_sig {
+        if !Self::check_signature(&user_id, sig, secret) {
             return Err("Invalid signature".to_string());
         }
 
         Ok(user_id)
     }
 
+    /// Constant-time check of a hex HMAC over `user_id`
+    fn check_signature(user_id: &str, sig_hex: &str, secret: &str) -> bool {
+        let Ok(sig) = hex::decode(sig_hex) else {
+            return false;
+        };
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC error");
+        mac.update(user_id.as_bytes());
+        mac.verify_slice(&sig).is_ok()
+    }
+
     pub fn check_permission(user: &User, required_perm: &Permission) -> bool {
         for role in &user.roles {
             if role.permissions.contains(required_perm) {
@@ -258,20 +263,45 @@ impl Fortress {
 // RATE LIMITING
 // ═══════════════════════════════════════════════════════════════════════════
 
-use std::sync::{Arc, RwLock};
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-/// Rate limit key type
+/// Rate limiting errors
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum RateLimitError {
+    #[error("Rate limit storage error: {0}")]
+    Storage(String),
+}
+
+/// What a request is counted against
 #[derive(Debug, Clone)]
 pub enum RateLimitKey {
-    /// Rate limit by IP address
+    /// Client IP address (see `RateLimitConfig::trusted_proxies`)
     Ip,
-    /// Rate limit by user ID
+    /// Authenticated user (`AuthUser`), falling back to IP
     UserId,
-    /// Custom key (e.g., API key, endpoint)
+    /// API key from the named header, falling back to IP
+    ApiKey(String),
+    /// Client IP per route
+    Route,
+    /// One shared bucket with this name
     Custom(String),
 }
 
+/// Rate limiting algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    /// `max_requests` per fixed window; cheapest, allows bursts at window edges
+    #[default]
+    FixedWindow,
+    /// Exact count over the trailing window; stores one entry per request
+    SlidingLog,
+    /// Bucket of `max_requests` tokens refilled evenly over the window
+    TokenBucket,
+}
+
 /// Rate limiter configuration
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -281,6 +311,10 @@ pub struct RateLimitConfig {
     pub window: Duration,
     /// Key type for rate limiting
     pub key_type: RateLimitKey,
+    /// Counting algorithm
+    pub algorithm: RateLimitAlgorithm,
+    /// Proxies whose `X-Forwarded-For` is trusted (addresses or CIDR ranges)
+    pub trusted_proxies: Vec<ipnet::IpNet>,
 }
 
 impl Default for RateLimitConfig {
@@ -289,6 +323,8 @@ impl Default for RateLimitConfig {
             max_requests: 100,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            algorithm: RateLimitAlgorithm::FixedWindow,
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -299,7 +335,7 @@ impl RateLimitConfig {
         Self {
             max_requests: 60,
             window: Duration::from_secs(60),
-            key_type: RateLimitKey::Ip,
+            ..Default::default()
         }
     }
 
@@ -308,7 +344,7 @@ impl RateLimitConfig {
         Self {
             max_requests: 5,
             window: Duration::from_secs(300), // 5 minutes
-            key_type: RateLimitKey::Ip,
+            ..Default::default()
         }
     }
 
@@ -317,16 +353,38 @@ impl RateLimitConfig {
         Self {
             max_requests: 1000,
             window: Duration::from_secs(60),
-            key_type: RateLimitKey::Ip,
+            ..Default::default()
         }
     }
-}
 
-/// Rate limit entry for tracking
-#[derive(Debug, Clone)]
-struct RateLimitEntry {
-    count: u32,
-    window_start: Instant,
+    /// Set the counting algorithm
+    pub fn algorithm(mut self, algorithm: RateLimitAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set what requests are counted against
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key_type = key;
+        self
+    }
+
+    /// Trust `X-Forwarded-For` from a proxy address or CIDR range
+    ///
+    /// # Panics
+    /// If `proxy` is not an IP address or CIDR range.
+    pub fn trust_proxy(mut self, proxy: &str) -> Self {
+        let net = proxy
+            .parse::<ipnet::IpNet>()
+            .or_else(|_| proxy.parse::<IpAddr>().map(ipnet::IpNet::from))
+            .unwrap_or_else(|_| panic!("invalid trusted proxy: {}", proxy));
+        self.trusted_proxies.push(net);
+        self
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
 }
 
 /// Result of rate limit check
@@ -429,18 +487,372 @@ impl RateLimitResult {
     }
 }
 
-/// Thread-safe rate limiter using sliding window algorithm
+impl RateLimitResult {
+    fn open(config: &RateLimitConfig) -> Self {
+        Self {
+            allowed: true,
+            remaining: config.max_requests,
+            limit: config.max_requests,
+            reset_at: Instant::now() + config.window,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Storage
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Where rate limit counters live
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Count a request against `key`, or only report its state when `consume` is false
+    async fn hit(
+        &self,
+        key: &str,
+        config: &RateLimitConfig,
+        consume: bool,
+    ) -> Result<RateLimitResult, RateLimitError>;
+
+    /// Forget one key
+    async fn reset(&self, key: &str) -> Result<(), RateLimitError>;
+
+    /// Forget every key
+    async fn clear(&self) -> Result<(), RateLimitError>;
+
+    /// Drop state that no longer affects any decision
+    async fn cleanup(&self, _config: &RateLimitConfig) -> Result<(), RateLimitError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum RateLimitEntry {
+    Window { count: u32, start: Instant },
+    Log(VecDeque<Instant>),
+    Bucket { tokens: f64, updated: Instant },
+}
+
+impl RateLimitEntry {
+    fn new(algorithm: RateLimitAlgorithm, max: u32, now: Instant) -> Self {
+        match algorithm {
+            RateLimitAlgorithm::FixedWindow => Self::Window {
+                count: 0,
+                start: now,
+            },
+            RateLimitAlgorithm::SlidingLog => Self::Log(VecDeque::new()),
+            RateLimitAlgorithm::TokenBucket => Self::Bucket {
+                tokens: max as f64,
+                updated: now,
+            },
+        }
+    }
+
+    fn matches(&self, algorithm: RateLimitAlgorithm) -> bool {
+        matches!(
+            (self, algorithm),
+            (Self::Window { .. }, RateLimitAlgorithm::FixedWindow)
+                | (Self::Log(_), RateLimitAlgorithm::SlidingLog)
+                | (Self::Bucket { .. }, RateLimitAlgorithm::TokenBucket)
+        )
+    }
+
+    fn hit(&mut self, config: &RateLimitConfig, now: Instant, consume: bool) -> RateLimitResult {
+        let max = config.max_requests;
+        let window = config.window;
+        let (allowed, remaining, reset_at) = match self {
+            Self::Window { count, start } => {
+                if now.duration_since(*start) >= window {
+                    *count = 0;
+                    *start = now;
+                }
+                let allowed = *count < max;
+                if allowed && consume {
+                    *count += 1;
+                }
+                (allowed, max.saturating_sub(*count), *start + window)
+            }
+            Self::Log(log) => {
+                while log
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= window)
+                {
+                    log.pop_front();
+                }
+                let allowed = (log.len() as u32) < max;
+                if allowed && consume {
+                    log.push_back(now);
+                }
+                let reset_at = log.front().map_or(now + window, |t| *t + window);
+                (allowed, max.saturating_sub(log.len() as u32), reset_at)
+            }
+            Self::Bucket { tokens, updated } => {
+                let rate = max as f64 / window.as_secs_f64();
+                *tokens =
+                    (*tokens + now.duration_since(*updated).as_secs_f64() * rate).min(max as f64);
+                *updated = now;
+                let allowed = *tokens >= 1.0;
+                if allowed && consume {
+                    *tokens -= 1.0;
+                }
+                // Blocked: until the next token; otherwise until the bucket is full
+                let wait = if allowed {
+                    (max as f64 - *tokens) / rate
+                } else {
+                    (1.0 - *tokens) / rate
+                };
+                (allowed, *tokens as u32, now + Duration::from_secs_f64(wait))
+            }
+        };
+        RateLimitResult {
+            allowed,
+            remaining,
+            limit: max,
+            reset_at,
+        }
+    }
+
+    fn is_stale(&self, config: &RateLimitConfig, now: Instant) -> bool {
+        match self {
+            Self::Window { start, .. } => now.duration_since(*start) >= config.window,
+            Self::Log(log) => log
+                .back()
+                .is_none_or(|t| now.duration_since(*t) >= config.window),
+            Self::Bucket { updated, .. } => now.duration_since(*updated) >= config.window,
+        }
+    }
+}
+
+/// Process-local store; counters are not shared between replicas
+#[derive(Clone, Default)]
+pub struct MemoryRateLimitStore {
+    entries: Arc<Mutex<HashMap<String, RateLimitEntry>>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn hit(
+        &self,
+        key: &str,
+        config: &RateLimitConfig,
+        consume: bool,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let entry = entries
+            .entry(key.to_string())
+            .or_insert_with(|| RateLimitEntry::new(config.algorithm, config.max_requests, now));
+        if !entry.matches(config.algorithm) {
+            *entry = RateLimitEntry::new(config.algorithm, config.max_requests, now);
+        }
+        Ok(entry.hit(config, now, consume))
+    }
+
+    async fn reset(&self, key: &str) -> Result<(), RateLimitError> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), RateLimitError> {
+        self.entries.lock().unwrap().clear();
+        Ok(())
+    }
+
+    async fn cleanup(&self, config: &RateLimitConfig) -> Result<(), RateLimitError> {
+        let now = Instant::now();
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| !entry.is_stale(config, now));
+        Ok(())
+    }
+}
+
+/// Redis store shared by every replica; each check is one atomic Lua script
+pub struct RedisRateLimitStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(url: &str) -> Result<Self, RateLimitError> {
+        let client =
+            redis::Client::open(url).map_err(|e| RateLimitError::Storage(e.to_string()))?;
+        Ok(Self {
+            client,
+            prefix: "nucleus:ratelimit:".to_string(),
+        })
+    }
+
+    /// Key prefix; give each limiter sharing a Redis its own
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    async fn connection(&self) -> Result<redis::aio::MultiplexedConnection, RateLimitError> {
+        self.client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| RateLimitError::Storage(e.to_string()))
+    }
+}
+
+// Each script takes KEYS[1] and ARGV = max, window_ms, consume (0/1), nonce and
+// returns {allowed, remaining, reset_ms}. Time comes from Redis so replicas agree.
+
+const FIXED_WINDOW_LUA: &str = r#"
+local max, window, consume = tonumber(ARGV[1]), tonumber(ARGV[2]), ARGV[3] == '1'
+local count = tonumber(redis.call('GET', KEYS[1]) or '0')
+local allowed = count < max
+if allowed and consume then
+  count = redis.call('INCR', KEYS[1])
+  if count == 1 then redis.call('PEXPIRE', KEYS[1], window) end
+end
+local ttl = redis.call('PTTL', KEYS[1])
+if ttl < 0 then ttl = window end
+return {allowed and 1 or 0, math.max(max - count, 0), ttl}
+"#;
+
+const SLIDING_LOG_LUA: &str = r#"
+redis.replicate_commands()
+local max, window, consume = tonumber(ARGV[1]), tonumber(ARGV[2]), ARGV[3] == '1'
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+local count = redis.call('ZCARD', KEYS[1])
+local allowed = count < max
+if allowed and consume then
+  redis.call('ZADD', KEYS[1], now, now .. ':' .. ARGV[4])
+  redis.call('PEXPIRE', KEYS[1], window)
+  count = count + 1
+end
+local reset = window
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+if oldest[2] then reset = tonumber(oldest[2]) + window - now end
+return {allowed and 1 or 0, math.max(max - count, 0), reset}
+"#;
+
+const TOKEN_BUCKET_LUA: &str = r#"
+redis.replicate_commands()
+local max, window, consume = tonumber(ARGV[1]), tonumber(ARGV[2]), ARGV[3] == '1'
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+local rate = max / window
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1]) or max
+local ts = tonumber(state[2]) or now
+tokens = math.min(max, tokens + (now - ts) * rate)
+local allowed = tokens >= 1
+if allowed and consume then tokens = tokens - 1 end
+if consume then
+  redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+  redis.call('PEXPIRE', KEYS[1], math.ceil((max - tokens) / rate) + 1000)
+end
+local wait
+if allowed then wait = (max - tokens) / rate else wait = (1 - tokens) / rate end
+return {allowed and 1 or 0, math.floor(tokens), math.ceil(wait)}
+"#;
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn hit(
+        &self,
+        key: &str,
+        config: &RateLimitConfig,
+        consume: bool,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let script = match config.algorithm {
+            RateLimitAlgorithm::FixedWindow => FIXED_WINDOW_LUA,
+            RateLimitAlgorithm::SlidingLog => SLIDING_LOG_LUA,
+            RateLimitAlgorithm::TokenBucket => TOKEN_BUCKET_LUA,
+        };
+        let mut con = self.connection().await?;
+        let (allowed, remaining, reset_ms): (i64, i64, i64) = redis::Script::new(script)
+            .key(format!("{}{}", self.prefix, key))
+            .arg(config.max_requests)
+            .arg(config.window.as_millis().max(1) as u64)
+            .arg(if consume { 1 } else { 0 })
+            .arg(uuid::Uuid::new_v4().simple().to_string())
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| RateLimitError::Storage(e.to_string()))?;
+        Ok(RateLimitResult {
+            allowed: allowed == 1,
+            remaining: remaining.max(0) as u32,
+            limit: config.max_requests,
+            reset_at: Instant::now() + Duration::from_millis(reset_ms.max(0) as u64),
+        })
+    }
+
+    async fn reset(&self, key: &str) -> Result<(), RateLimitError> {
+        let mut con = self.connection().await?;
+        let _: () = redis::cmd("DEL")
+            .arg(format!("{}{}", self.prefix, key))
+            .query_async(&mut con)
+            .await
+            .map_err(|e| RateLimitError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), RateLimitError> {
+        let mut con = self.connection().await?;
+        let pattern = format!("{}*", self.prefix);
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut con)
+                .await
+                .map_err(|e| RateLimitError::Storage(e.to_string()))?;
+            if !keys.is_empty() {
+                let _: () = redis::cmd("DEL")
+                    .arg(keys)
+                    .query_async(&mut con)
+                    .await
+                    .map_err(|e| RateLimitError::Storage(e.to_string()))?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Limiter
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Rate limiter over a pluggable store (in-memory by default)
+///
+/// Storage errors fail open: the request is allowed and the error logged.
+/// Use `try_check` to handle them yourself.
 pub struct RateLimiter {
     config: RateLimitConfig,
-    entries: Arc<RwLock<HashMap<String, RateLimitEntry>>>,
+    store: Arc<dyn RateLimitStore>,
 }
 
 impl RateLimiter {
-    /// Create a new rate limiter with the given config
+    /// Create a new in-memory rate limiter with the given config
     pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_store(config, MemoryRateLimitStore::new())
+    }
+
+    /// Create a rate limiter backed by `store`
+    pub fn with_store(config: RateLimitConfig, store: impl RateLimitStore + 'static) -> Self {
         Self {
             config,
-            entries: Arc::new(RwLock::new(HashMap::new())),
+            store: Arc::new(store),
         }
     }
 
@@ -449,102 +861,84 @@ impl RateLimiter {
         Self::new(RateLimitConfig::default())
     }
 
-    /// Check if a request is allowed and consume one request
-    pub fn check(&self, key: &str) -> RateLimitResult {
-        let mut entries = self.entries.write().unwrap();
-        let now = Instant::now();
-
-        let entry = entries.entry(key.to_string()).or_insert(RateLimitEntry {
-            count: 0,
-            window_start: now,
-        });
-
-        // Check if window has expired
-        if now.duration_since(entry.window_start) >= self.config.window {
-            // Reset window
-            entry.count = 0;
-            entry.window_start = now;
-        }
+    /// Get config
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
 
-        let reset_at = entry.window_start + self.config.window;
+    /// Check if a request is allowed and consume one request
+    pub async fn check(&self, key: &str) -> RateLimitResult {
+        self.try_check(key).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "rate limit store unavailable, allowing request");
+            RateLimitResult::open(&self.config)
+        })
+    }
 
-        if entry.count >= self.config.max_requests {
-            // Rate limited
-            RateLimitResult {
-                allowed: false,
-                remaining: 0,
-                limit: self.config.max_requests,
-                reset_at,
-            }
-        } else {
-            // Allow and increment
-            entry.count += 1;
-            RateLimitResult {
-                allowed: true,
-                remaining: self.config.max_requests - entry.count,
-                limit: self.config.max_requests,
-                reset_at,
-            }
-        }
+    /// Like `check`, but surfaces storage errors
+    pub async fn try_check(&self, key: &str) -> Result<RateLimitResult, RateLimitError> {
+        self.store.hit(key, &self.config, true).await
     }
 
     /// Check without consuming a request (peek)
-    pub fn peek(&self, key: &str) -> RateLimitResult {
-        let entries = self.entries.read().unwrap();
-        let now = Instant::now();
-
-        if let Some(entry) = entries.get(key) {
-            if now.duration_since(entry.window_start) >= self.config.window {
-                // Window expired, would reset
-                RateLimitResult {
-                    allowed: true,
-                    remaining: self.config.max_requests,
-                    limit: self.config.max_requests,
-                    reset_at: now + self.config.window,
-                }
-            } else {
-                let reset_at = entry.window_start + self.config.window;
-                RateLimitResult {
-                    allowed: entry.count < self.config.max_requests,
-                    remaining: self.config.max_requests.saturating_sub(entry.count),
-                    limit: self.config.max_requests,
-                    reset_at,
-                }
-            }
-        } else {
-            // No entry yet
-            RateLimitResult {
-                allowed: true,
-                remaining: self.config.max_requests,
-                limit: self.config.max_requests,
-                reset_at: now + self.config.window,
-            }
-        }
+    pub async fn peek(&self, key: &str) -> RateLimitResult {
+        self.store
+            .hit(key, &self.config, false)
+            .await
+            .unwrap_or_else(|_| RateLimitResult::open(&self.config))
     }
 
     /// Get remaining requests for a key
-    pub fn remaining(&self, key: &str) -> u32 {
-        self.peek(key).remaining
+    pub async fn remaining(&self, key: &str) -> u32 {
+        self.peek(key).await.remaining
     }
 
     /// Reset rate limit for a key
-    pub fn reset(&self, key: &str) {
-        let mut entries = self.entries.write().unwrap();
-        entries.remove(key);
+    pub async fn reset(&self, key: &str) {
+        if let Err(e) = self.store.reset(key).await {
+            tracing::warn!(error = %e, "rate limit reset failed");
+        }
     }
 
     /// Clear all rate limit entries
-    pub fn clear(&self) {
-        let mut entries = self.entries.write().unwrap();
-        entries.clear();
+    pub async fn clear(&self) {
+        if let Err(e) = self.store.clear().await {
+            tracing::warn!(error = %e, "rate limit clear failed");
+        }
     }
 
     /// Clean up expired entries (call periodically)
-    pub fn cleanup(&self) {
-        let mut entries = self.entries.write().unwrap();
-        let now = Instant::now();
+    pub async fn cleanup(&self) {
+        if let Err(e) = self.store.cleanup(&self.config).await {
+            tracing::warn!(error = %e, "rate limit cleanup failed");
+        }
+    }
 
-        entries.retain(|_, entry| now.duration_since(entry.window_start) < self.config.window);
+    /// The key a request is counted against, per `RateLimitConfig::key_type`
+    pub fn key_for<B>(&self, request: &axum::http::Request<B>) -> String {
+        let ip = || client_ip(request, &self.config);
+        match &self.config.key_type {
+            RateLimitKey::Ip => format!("ip:{}", ip()),
+            RateLimitKey::UserId => match request.extensions().get::<AuthUser>() {
+                Some(user) => format!("user:{}", user.user_id),
+                None => format!("ip:{}", ip()),
+            },
+            RateLimitKey::ApiKey(header) => {
+                match request.headers().get(header).and_then(|v| v.to_str().ok()) {
+                    // Hashed so raw keys never end up in the store
+                    Some(key) => format!("key:{}", &hex::encode(Sha256::digest(key))[..32]),
+                    None => format!("ip:{}", ip()),
+                }
+            }
+            RateLimitKey::Route => {
+                let route = request
+                    .extensions()
+                    .get::<axum::extract::MatchedPath>()
+                    .map(|p| p.as_str())
+                    .unwrap_or_else(|| request.uri().path());
+                format!("route:{}:{}", route, ip())
+            }
+            RateLimitKey::Custom(name) => name.clone(),
+        }
     }
 }
 
@@ -552,11 +946,48 @@ impl Clone for RateLimiter {
     fn clone(&self) -> Self {
         Self {
             config: self.config.clone(),
-            entries: Arc::clone(&self.entries),
+            store: Arc::clone(&self.store),
         }
     }
 }
 
+/// Client address: the socket peer, or when the peer is a trusted proxy the
+/// right-most `X-Forwarded-For` entry that is not itself a trusted proxy.
+///
+/// Requests without a peer address (Unix sockets, or no `ConnectInfo`) read
+/// `X-Forwarded-For` only when trusted proxies are configured, i.e. the app
+/// is declared to sit behind one. Otherwise they share a single `unknown`
+/// bucket, since any client could set the header.
+fn client_ip<B>(request: &axum::http::Request<B>, config: &RateLimitConfig) -> String {
+    const UNKNOWN: &str = "unknown";
+    let peer = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    match peer {
+        Some(peer) if !config.is_trusted(peer) => return peer.to_string(),
+        None if config.trusted_proxies.is_empty() => return UNKNOWN.to_string(),
+        _ => {}
+    }
+
+    let forwarded: Vec<IpAddr> = request
+        .headers()
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect();
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !config.is_trusted(**ip))
+        .or(forwarded.first())
+        .map(|ip| ip.to_string())
+        .or_else(|| peer.map(|p| p.to_string()))
+        .unwrap_or_else(|| UNKNOWN.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -717,142 +1148,149 @@ mod tests {
     // RATE LIMITER TESTS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    #[test]
-    fn test_rate_limiter_basic() {
+    #[tokio::test]
+    async fn test_rate_limiter_basic() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 5,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         // First 5 requests should be allowed
         for i in 0..5 {
-            let result = limiter.check("192.168.1.1");
+            let result = limiter.check("192.168.1.1").await;
             assert!(result.allowed, "Request {} should be allowed", i + 1);
             assert_eq!(result.remaining, 4 - i as u32);
         }
 
         // 6th request should be blocked
-        let result = limiter.check("192.168.1.1");
+        let result = limiter.check("192.168.1.1").await;
         assert!(!result.allowed);
         assert_eq!(result.remaining, 0);
     }
 
-    #[test]
-    fn test_rate_limiter_different_keys() {
+    #[tokio::test]
+    async fn test_rate_limiter_different_keys() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 2,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         // User A uses 2 requests
-        assert!(limiter.check("user_a").allowed);
-        assert!(limiter.check("user_a").allowed);
-        assert!(!limiter.check("user_a").allowed);
+        assert!(limiter.check("user_a").await.allowed);
+        assert!(limiter.check("user_a").await.allowed);
+        assert!(!limiter.check("user_a").await.allowed);
 
         // User B should still have full quota
-        assert!(limiter.check("user_b").allowed);
-        assert_eq!(limiter.remaining("user_b"), 1);
+        assert!(limiter.check("user_b").await.allowed);
+        assert_eq!(limiter.remaining("user_b").await, 1);
     }
 
-    #[test]
-    fn test_rate_limiter_window_expiration() {
+    #[tokio::test]
+    async fn test_rate_limiter_window_expiration() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 2,
             window: Duration::from_millis(50),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         // Exhaust quota
-        limiter.check("key");
-        limiter.check("key");
-        assert!(!limiter.check("key").allowed);
+        limiter.check("key").await;
+        limiter.check("key").await;
+        assert!(!limiter.check("key").await.allowed);
 
         // Wait for window to expire
-        std::thread::sleep(Duration::from_millis(60));
+        tokio::time::sleep(Duration::from_millis(60)).await;
 
         // Should be allowed again
-        let result = limiter.check("key");
+        let result = limiter.check("key").await;
         assert!(result.allowed);
         assert_eq!(result.remaining, 1);
     }
 
-    #[test]
-    fn test_rate_limiter_peek_vs_check() {
+    #[tokio::test]
+    async fn test_rate_limiter_peek_vs_check() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 3,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         // Peek should not consume
-        let peek1 = limiter.peek("key");
+        let peek1 = limiter.peek("key").await;
         assert!(peek1.allowed);
         assert_eq!(peek1.remaining, 3);
 
         // Peek again - still 3
-        let peek2 = limiter.peek("key");
+        let peek2 = limiter.peek("key").await;
         assert_eq!(peek2.remaining, 3);
 
         // Check should consume
-        let check1 = limiter.check("key");
+        let check1 = limiter.check("key").await;
         assert!(check1.allowed);
         assert_eq!(check1.remaining, 2);
 
         // Peek now shows 2
-        assert_eq!(limiter.peek("key").remaining, 2);
+        assert_eq!(limiter.peek("key").await.remaining, 2);
     }
 
-    #[test]
-    fn test_rate_limiter_reset() {
+    #[tokio::test]
+    async fn test_rate_limiter_reset() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 2,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        limiter.check("key");
-        limiter.check("key");
-        assert!(!limiter.check("key").allowed);
+        limiter.check("key").await;
+        limiter.check("key").await;
+        assert!(!limiter.check("key").await.allowed);
 
         // Reset the key
-        limiter.reset("key");
+        limiter.reset("key").await;
 
         // Should have full quota again
-        assert!(limiter.check("key").allowed);
-        assert_eq!(limiter.remaining("key"), 1);
+        assert!(limiter.check("key").await.allowed);
+        assert_eq!(limiter.remaining("key").await, 1);
     }
 
-    #[test]
-    fn test_rate_limiter_clear() {
+    #[tokio::test]
+    async fn test_rate_limiter_clear() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 1,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        limiter.check("key1");
-        limiter.check("key2");
-        assert!(!limiter.check("key1").allowed);
-        assert!(!limiter.check("key2").allowed);
+        limiter.check("key1").await;
+        limiter.check("key2").await;
+        assert!(!limiter.check("key1").await.allowed);
+        assert!(!limiter.check("key2").await.allowed);
 
         // Clear all
-        limiter.clear();
+        limiter.clear().await;
 
-        assert!(limiter.check("key1").allowed);
-        assert!(limiter.check("key2").allowed);
+        assert!(limiter.check("key1").await.allowed);
+        assert!(limiter.check("key2").await.allowed);
     }
 
-    #[test]
-    fn test_rate_limiter_headers() {
+    #[tokio::test]
+    async fn test_rate_limiter_headers() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 10,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        let result = limiter.check("key");
+        let result = limiter.check("key").await;
         let headers = result.headers();
 
         assert_eq!(headers.len(), 3);
@@ -881,29 +1319,30 @@ mod tests {
         assert_eq!(lenient.max_requests, 1000);
     }
 
-    #[test]
-    fn test_rate_limiter_clone_shares_state() {
+    #[tokio::test]
+    async fn test_rate_limiter_clone_shares_state() {
         let limiter1 = RateLimiter::new(RateLimitConfig {
             max_requests: 3,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
         let limiter2 = limiter1.clone();
 
         // Use some quota via limiter1
-        limiter1.check("key");
-        limiter1.check("key");
+        limiter1.check("key").await;
+        limiter1.check("key").await;
 
         // limiter2 should see the same state
-        assert_eq!(limiter2.remaining("key"), 1);
+        assert_eq!(limiter2.remaining("key").await, 1);
     }
 
-    #[test]
-    fn test_rate_limiter_empty_key() {
+    #[tokio::test]
+    async fn test_rate_limiter_empty_key() {
         let limiter = RateLimiter::default_limiter();
 
         // Empty string key should work
-        let result = limiter.check("");
+        let result = limiter.check("").await;
         assert!(result.allowed);
     }
 
@@ -911,15 +1350,16 @@ mod tests {
     // RATE LIMIT HEADERS TESTS
     // ═══════════════════════════════════════════════════════════════════════
 
-    #[test]
-    fn test_rate_limit_headers_when_allowed() {
+    #[tokio::test]
+    async fn test_rate_limit_headers_when_allowed() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 10,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        let result = limiter.check("test_key");
+        let result = limiter.check("test_key").await;
         let headers = result.headers();
 
         // Should have 3 headers when allowed
@@ -931,17 +1371,18 @@ mod tests {
         assert!(!headers.iter().any(|(k, _)| k == "Retry-After"));
     }
 
-    #[test]
-    fn test_rate_limit_headers_when_blocked() {
+    #[tokio::test]
+    async fn test_rate_limit_headers_when_blocked() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 1,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         // Exhaust quota
-        limiter.check("key");
-        let result = limiter.check("key");
+        limiter.check("key").await;
+        let result = limiter.check("key").await;
 
         assert!(!result.allowed);
         let headers = result.headers();
@@ -951,105 +1392,355 @@ mod tests {
         assert!(headers.iter().any(|(k, _)| k == "Retry-After"));
     }
 
-    #[test]
-    fn test_rate_limit_headers_map() {
+    #[tokio::test]
+    async fn test_rate_limit_headers_map() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 100,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        let result = limiter.check("key");
+        let result = limiter.check("key").await;
         let map = result.headers_map();
 
         assert_eq!(map.get("X-RateLimit-Limit"), Some(&"100".to_string()));
         assert_eq!(map.get("X-RateLimit-Remaining"), Some(&"99".to_string()));
     }
 
-    #[test]
-    fn test_rate_limit_status_code() {
+    #[tokio::test]
+    async fn test_rate_limit_status_code() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 1,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        let result1 = limiter.check("key");
+        let result1 = limiter.check("key").await;
         assert_eq!(result1.status_code(), 200);
         assert!(result1.is_allowed());
 
-        let result2 = limiter.check("key");
+        let result2 = limiter.check("key").await;
         assert_eq!(result2.status_code(), 429);
         assert!(!result2.is_allowed());
     }
 
-    #[test]
-    fn test_rate_limit_retry_after() {
+    #[tokio::test]
+    async fn test_rate_limit_retry_after() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 1,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        limiter.check("key");
-        let result = limiter.check("key");
+        limiter.check("key").await;
+        let result = limiter.check("key").await;
 
         let retry = result.retry_after_secs();
         assert!(retry > 0);
         assert!(retry <= 60);
     }
 
-    #[test]
-    fn test_rate_limit_remaining_decrements() {
+    #[tokio::test]
+    async fn test_rate_limit_remaining_decrements() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 5,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
         for i in (0..5).rev() {
-            let result = limiter.check("key");
+            let result = limiter.check("key").await;
             assert_eq!(result.remaining, i as u32);
         }
     }
 
-    #[test]
-    fn test_rate_limit_different_keys_independent() {
+    #[tokio::test]
+    async fn test_rate_limit_different_keys_independent() {
         let limiter = RateLimiter::new(RateLimitConfig {
             max_requests: 5,
             window: Duration::from_secs(60),
             key_type: RateLimitKey::Ip,
+            ..Default::default()
         });
 
-        limiter.check("user_1");
-        limiter.check("user_1");
+        limiter.check("user_1").await;
+        limiter.check("user_1").await;
 
-        let result_user1 = limiter.peek("user_1");
-        let result_user2 = limiter.peek("user_2");
+        let result_user1 = limiter.peek("user_1").await;
+        let result_user2 = limiter.peek("user_2").await;
 
         assert_eq!(result_user1.remaining, 3);
         assert_eq!(result_user2.remaining, 5); // Full quota
     }
 
-    #[test]
-    fn test_rate_limit_unicode_keys() {
+    #[tokio::test]
+    async fn test_rate_limit_unicode_keys() {
         let limiter = RateLimiter::default_limiter();
 
-        let result1 = limiter.check("用户123");
-        let result2 = limiter.check("🔒🔑");
+        let result1 = limiter.check("用户123").await;
+        let result2 = limiter.check("🔒🔑").await;
 
         assert!(result1.allowed);
         assert!(result2.allowed);
     }
+
+    #[tokio::test]
+    async fn test_rate_limit_sliding_log() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig {
+                max_requests: 2,
+                window: Duration::from_millis(100),
+                ..Default::default()
+            }
+            .algorithm(RateLimitAlgorithm::SlidingLog),
+        );
+
+        assert!(limiter.check("key").await.allowed);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(limiter.check("key").await.allowed);
+        assert!(!limiter.check("key").await.allowed);
+
+        // Only the first request has left the trailing window
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(limiter.check("key").await.allowed);
+        assert!(!limiter.check("key").await.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_token_bucket() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig {
+                max_requests: 10,
+                window: Duration::from_millis(200),
+                ..Default::default()
+            }
+            .algorithm(RateLimitAlgorithm::TokenBucket),
+        );
+
+        for _ in 0..10 {
+            assert!(limiter.check("key").await.allowed);
+        }
+        let blocked = limiter.check("key").await;
+        assert!(!blocked.allowed);
+        assert!(blocked.reset_at <= Instant::now() + Duration::from_millis(20));
+
+        // One token every 20ms
+        tokio::time::sleep(Duration::from_millis(45)).await;
+        assert!(limiter.check("key").await.allowed);
+        assert!(limiter.check("key").await.allowed);
+        assert!(!limiter.check("key").await.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_cleanup_drops_stale_entries() {
+        let store = MemoryRateLimitStore::new();
+        let limiter = RateLimiter::with_store(
+            RateLimitConfig {
+                max_requests: 1,
+                window: Duration::from_millis(30),
+                ..Default::default()
+            },
+            store.clone(),
+        );
+
+        limiter.check("key").await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        limiter.cleanup().await;
+        assert!(store.entries.lock().unwrap().is_empty());
+    }
+
+    fn request_from(peer: &str, forwarded: Option<&str>) -> axum::http::Request<()> {
+        let mut builder = axum::http::Request::builder().uri("/api/items");
+        if let Some(forwarded) = forwarded {
+            builder = builder.header("X-Forwarded-For", forwarded);
+        }
+        let mut request = builder.body(()).unwrap();
+        let addr: SocketAddr = format!("{}:5000", peer).parse().unwrap();
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(addr));
+        request
+    }
+
+    #[test]
+    fn test_rate_limit_key_ignores_untrusted_forwarded_for() {
+        let limiter = RateLimiter::default_limiter();
+        let request = request_from("203.0.113.9", Some("1.2.3.4"));
+        assert_eq!(limiter.key_for(&request), "ip:203.0.113.9");
+    }
+
+    #[test]
+    fn test_rate_limit_key_trusted_proxy_chain() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::default()
+                .trust_proxy("10.0.0.0/8")
+                .trust_proxy("192.168.1.1"),
+        );
+
+        // Spoofed left-most entry is skipped; the last untrusted hop wins
+        let request = request_from("10.0.0.2", Some("6.6.6.6, 198.51.100.7, 192.168.1.1"));
+        assert_eq!(limiter.key_for(&request), "ip:198.51.100.7");
+
+        let request = request_from("10.0.0.2", None);
+        assert_eq!(limiter.key_for(&request), "ip:10.0.0.2");
+    }
+
+    #[test]
+    fn test_rate_limit_key_without_peer() {
+        let request = || {
+            axum::http::Request::builder()
+                .header("X-Forwarded-For", "1.2.3.4")
+                .body(())
+                .unwrap()
+        };
+
+        // Nothing vouches for the header, so everyone shares one bucket
+        let limiter = RateLimiter::default_limiter();
+        assert_eq!(limiter.key_for(&request()), "ip:unknown");
+
+        // Behind a declared proxy (e.g. on a Unix socket) the header is used
+        let limiter = RateLimiter::new(RateLimitConfig::default().trust_proxy("10.0.0.0/8"));
+        assert_eq!(limiter.key_for(&request()), "ip:1.2.3.4");
+    }
+
+    #[test]
+    fn test_rate_limit_key_types() {
+        let request = request_from("203.0.113.9", None);
+
+        let limiter = RateLimiter::new(RateLimitConfig::default().key(RateLimitKey::UserId));
+        assert_eq!(limiter.key_for(&request), "ip:203.0.113.9");
+
+        let mut authed = request_from("203.0.113.9", None);
+        authed.extensions_mut().insert(AuthUser {
+            user_id: "42".to_string(),
+            claims: serde_json::from_value(serde_json::json!({ "sub": "42", "exp": 0 })).unwrap(),
+        });
+        assert_eq!(limiter.key_for(&authed), "user:42");
+
+        let limiter = RateLimiter::new(
+            RateLimitConfig::default().key(RateLimitKey::ApiKey("x-api-key".to_string())),
+        );
+        let mut keyed = request_from("203.0.113.9", None);
+        keyed
+            .headers_mut()
+            .insert("x-api-key", "secret".parse().unwrap());
+        let key = limiter.key_for(&keyed);
+        assert!(key.starts_with("key:"));
+        assert!(!key.contains("secret"));
+
+        let limiter = RateLimiter::new(RateLimitConfig::default().key(RateLimitKey::Route));
+        assert_eq!(limiter.key_for(&request), "route:/api/items:203.0.113.9");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_redis_store() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let prefix = format!("test:ratelimit:{}:", uuid::Uuid::new_v4().simple());
+        let store = RedisRateLimitStore::new(&url).unwrap().with_prefix(&prefix);
+
+        for algorithm in [
+            RateLimitAlgorithm::FixedWindow,
+            RateLimitAlgorithm::SlidingLog,
+            RateLimitAlgorithm::TokenBucket,
+        ] {
+            let config = RateLimitConfig {
+                max_requests: 2,
+                window: Duration::from_secs(60),
+                ..Default::default()
+            }
+            .algorithm(algorithm);
+            let key = format!("{:?}", algorithm);
+
+            assert!(store.hit(&key, &config, true).await.unwrap().allowed);
+            assert_eq!(store.hit(&key, &config, false).await.unwrap().remaining, 1);
+            assert!(store.hit(&key, &config, true).await.unwrap().allowed);
+            assert!(!store.hit(&key, &config, true).await.unwrap().allowed);
+
+            store.reset(&key).await.unwrap();
+            assert!(store.hit(&key, &config, true).await.unwrap().allowed);
+        }
+        store.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_auth_user_exposes_claims() {
+        use crate::tokens::{SigningKey, TokenManager};
+        use axum::http::Request;
+
+        // Whichever manager is global, tokens it issues must round-trip
+        let _ = tokens::init(TokenManager::new(SigningKey::hs256(
+            "fortress-test",
+            "fortress-test-secret",
+        )));
+        let manager = tokens::manager().unwrap();
+        let token = manager
+            .sign(&manager.claims("user_42").with_scope("admin"))
+            .unwrap();
+
+        let (mut parts, _) = Request::builder()
+            .header("Authorization", format!("Bearer {}", token))
+            .body(())
+            .unwrap()
+            .into_parts();
+        let user = AuthUser::from_request_parts(&mut parts, &()).await.unwrap();
+        assert_eq!(user.user_id, "user_42");
+        assert!(user.has_scope("admin"));
+
+        manager.revoke(&token).await.unwrap();
+        let err = AuthUser::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::UNAUTHORIZED);
+
+        let (mut parts, _) = Request::builder().body(()).unwrap().into_parts();
+        let optional = OptionalAuth::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert!(optional.user_id.is_none() && optional.claims.is_none());
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
 // AUTH EXTRACTORS & MIDDLEWARE
 // ═══════════════════════════════════════════════════════════════════════════
 
+/// Authenticated caller, verified through the global [`crate::tokens`] manager
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: String,
+    pub claims: Claims,
+}
+
+impl AuthUser {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.claims.has_scope(scope)
+    }
+}
+
+fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+async fn authenticate(token: &str) -> Result<AuthUser, (StatusCode, String)> {
+    let manager =
+        tokens::manager().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match manager.verify(token).await {
+        Ok(claims) => Ok(AuthUser {
+            user_id: claims.sub.clone(),
+            claims,
+        }),
+        Err(TokenError::Storage(e)) => Err((StatusCode::SERVICE_UNAVAILABLE, e)),
+        Err(_) => Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string())),
+    }
 }
 
 #[async_trait]
@@ -1060,39 +1751,29 @@ where
     type Rejection = (StatusCode, String);
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let auth_header = parts
-            .headers
-            .get("Authorization")
-            .and_then(|value| value.to_str().ok())
-            .ok_or((
-                StatusCode::UNAUTHORIZED,
-                "Missing Authorization header".to_string(),
-            ))?;
+        // Already verified by `require_auth`
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
 
-        if !auth_header.starts_with("Bearer ") {
+        if !parts.headers.contains_key("Authorization") {
             return Err((
                 StatusCode::UNAUTHORIZED,
-                "Invalid Authorization header".to_string(),
+                "Missing Authorization header".to_string(),
             ));
         }
+        let token = bearer_token(&parts.headers).ok_or((
+            StatusCode::UNAUTHORIZED,
+            "Invalid Authorization header".to_string(),
+        ))?;
 
-        let token = &auth_header[7..];
-        let secret = std::env::var("SECRET_KEY").map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "SECRET_KEY not set".to_string(),
-            )
-        })?;
-
-        match Fortress::validate_token(token, &secret) {
-            Ok(user_id) => Ok(AuthUser { user_id }),
-            Err(_) => Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string())),
-        }
+        authenticate(token).await
     }
 }
 
 pub struct OptionalAuth {
     pub user_id: Option<String>,
+    pub claims: Option<Claims>,
 }
 
 #[async_trait]
@@ -1102,47 +1783,25 @@ where
 {
     type Rejection = (StatusCode, String);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let auth_header = parts
-            .headers
-            .get("Authorization")
-            .and_then(|value| value.to_str().ok());
-
-        match auth_header {
-            Some(header) if header.starts_with("Bearer ") => {
-                let token = &header[7..];
-                if let Ok(secret) = std::env::var("SECRET_KEY") {
-                    if let Ok(user_id) = Fortress::validate_token(token, &secret) {
-                        return Ok(OptionalAuth {
-                            user_id: Some(user_id),
-                        });
-                    }
-                }
-            }
-            _ => {}
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match AuthUser::from_request_parts(parts, state).await {
+            Ok(user) => Ok(OptionalAuth {
+                user_id: Some(user.user_id),
+                claims: Some(user.claims),
+            }),
+            Err(_) => Ok(OptionalAuth {
+                user_id: None,
+                claims: None,
+            }),
         }
-
-        Ok(OptionalAuth { user_id: None })
     }
 }
 
-pub async fn require_auth(req: Request, next: Next) -> Result<Response, StatusCode> {
-    let auth_header = req
-        .headers()
-        .get("Authorization")
-        .and_then(|value| value.to_str().ok());
-
-    match auth_header {
-        Some(header) if header.starts_with("Bearer ") => {
-            let token = &header[7..];
-            if let Ok(secret) = std::env::var("SECRET_KEY") {
-                if Fortress::validate_token(token, &secret).is_ok() {
-                    return Ok(next.run(req).await);
-                }
-            }
-        }
-        _ => {}
-    }
-
-    Err(StatusCode::UNAUTHORIZED)
+/// Reject requests without a valid access token; the verified
+/// [`AuthUser`] is stored in the request extensions
+pub async fn require_auth(mut req: Request, next: Next) -> Result<Response, StatusCode> {
+    let token = bearer_token(req.headers()).ok_or(StatusCode::UNAUTHORIZED)?;
+    let user = authenticate(token).await.map_err(|(status, _)| status)?;
+    req.extensions_mut().insert(user);
+    Ok(next.run(req).await)
 }
@@ -27,6 +27,9 @@
 //! router.route("/graphql/playground", GraphQL::playground());
 //! ```
 
+use crate::fortress::Permission;
+use crate::photon::{Builder, Cursor, CursorPage, FromDbRow};
+use crate::policy::{self, Action, Gate, PolicyError, Principal, Resource};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -75,6 +78,16 @@ impl From<async_graphql::Error> for GraphError {
     }
 }
 
+impl From<PolicyError> for GraphError {
+    fn from(err: PolicyError) -> Self {
+        match err {
+            PolicyError::Unauthenticated => GraphError::Unauthenticated,
+            PolicyError::Forbidden(msg) => GraphError::PermissionDenied(msg),
+            other => GraphError::InternalError(other.to_string()),
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // GRAPHQL REQUEST/RESPONSE
 // ═══════════════════════════════════════════════════════════════════════════
@@ -200,27 +213,68 @@ impl GraphQL {
         let _ = schema; // Just to use the param
         |state: axum::extract::State<Schema<Q, M, S>>,
          axum::Json(req): axum::Json<GraphQLRequest>| {
-            Box::pin(async move {
-                let mut request = async_graphql::Request::new(&req.query);
-                if let Some(op) = req.operation_name {
-                    request = request.operation_name(op);
-                }
-                if let Some(vars) = req.variables {
-                    let variables = async_graphql::Variables::from_json(vars);
-                    request = request.variables(variables);
-                }
+            Box::pin(Self::execute(state.0, None, req))
+        }
+    }
 
-                let response = state.execute(request).await;
-                let data = response.data.into_json().ok();
-                let errors: Vec<GraphQLError> = response
-                    .errors
-                    .into_iter()
-                    .map(|e| GraphQLError::new(&e.message))
-                    .collect();
+    /// Like [`GraphQL::handler`], also adding the caller's [`Principal`] (if
+    /// authenticated) to the request data for [`AuthGuard`] and
+    /// [`PolicyGuard`]
+    #[allow(clippy::type_complexity)]
+    pub fn handler_with_principal<Q, M, S>(
+        schema: Schema<Q, M, S>,
+    ) -> impl Fn(
+        axum::extract::State<Schema<Q, M, S>>,
+        Option<Principal>,
+        axum::Json<GraphQLRequest>,
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = axum::Json<GraphQLResponse>> + Send>,
+    > + Clone
+           + Send
+    where
+        Q: async_graphql::ObjectType + 'static,
+        M: async_graphql::ObjectType + 'static,
+        S: async_graphql::SubscriptionType + 'static,
+    {
+        let _ = schema; // Just to use the param
+        |state: axum::extract::State<Schema<Q, M, S>>,
+         principal: Option<Principal>,
+         axum::Json(req): axum::Json<GraphQLRequest>| {
+            Box::pin(Self::execute(state.0, principal, req))
+        }
+    }
 
-                axum::Json(GraphQLResponse { data, errors })
-            })
+    async fn execute<Q, M, S>(
+        schema: Schema<Q, M, S>,
+        principal: Option<Principal>,
+        req: GraphQLRequest,
+    ) -> axum::Json<GraphQLResponse>
+    where
+        Q: async_graphql::ObjectType + 'static,
+        M: async_graphql::ObjectType + 'static,
+        S: async_graphql::SubscriptionType + 'static,
+    {
+        let mut request = async_graphql::Request::new(&req.query);
+        if let Some(principal) = principal {
+            request = request.data(principal);
+        }
+        if let Some(op) = req.operation_name {
+            request = request.operation_name(op);
         }
+        if let Some(vars) = req.variables {
+            let variables = async_graphql::Variables::from_json(vars);
+            request = request.variables(variables);
+        }
+
+        let response = schema.execute(request).await;
+        let data = response.data.into_json().ok();
+        let errors: Vec<GraphQLError> = response
+            .errors
+            .into_iter()
+            .map(|e| GraphQLError::new(&e.message))
+            .collect();
+
+        axum::Json(GraphQLResponse { data, errors })
     }
 
     /// Create a GraphQL playground HTML page
@@ -463,6 +517,104 @@ impl AuthGuard {
             )))
         }
     }
+
+    /// Evaluate the global [`policy::gate`] for `action` on `resource`
+    pub fn authorize<'a, R: Resource>(
+        user: &'a Option<Principal>,
+        action: Action,
+        resource: &R,
+    ) -> Result<&'a Principal, GraphError> {
+        Self::authorize_with(policy::gate(), user, action, resource)
+    }
+
+    /// Like [`authorize`](Self::authorize), against a specific gate
+    /// (e.g. one stored in the schema data)
+    pub fn authorize_with<'a, R: Resource>(
+        gate: &Gate,
+        user: &'a Option<Principal>,
+        action: Action,
+        resource: &R,
+    ) -> Result<&'a Principal, GraphError> {
+        let u = user.as_ref().ok_or(GraphError::Unauthenticated)?;
+        gate.authorize(u, action, resource)?;
+        Ok(u)
+    }
+
+    /// Evaluate the global gate for an action without an instance (`Create`, `ViewAny`)
+    pub fn authorize_any<R: Resource>(
+        user: &Option<Principal>,
+        action: Action,
+    ) -> Result<&Principal, GraphError> {
+        let u = user.as_ref().ok_or(GraphError::Unauthenticated)?;
+        policy::gate().authorize_any::<R>(u, action)?;
+        Ok(u)
+    }
+
+    /// Check a permission, including grants from inherited roles
+    pub fn require_permission<'a>(
+        user: &'a Option<Principal>,
+        permission: &Permission,
+    ) -> Result<&'a Principal, GraphError> {
+        let u = user.as_ref().ok_or(GraphError::Unauthenticated)?;
+        if policy::gate().has_permission(u, permission) {
+            Ok(u)
+        } else {
+            Err(GraphError::PermissionDenied(format!(
+                "Required permission: {:?}",
+                permission
+            )))
+        }
+    }
+}
+
+/// Field guard evaluating the same rules as the axum guards
+///
+/// Reads the [`Principal`] from the request data (added by
+/// [`GraphQL::handler_with_principal`]) and a [`Gate`] from the schema data, falling back to
+/// the global gate.
+///
+/// ```rust,ignore
+/// #[graphql(guard = "PolicyGuard::permission(Permission::Admin)")]
+/// async fn users(&self) -> Vec<User> { ... }
+/// ```
+pub enum PolicyGuard {
+    Role(String),
+    Permission(Permission),
+}
+
+impl PolicyGuard {
+    pub fn role(role: &str) -> Self {
+        PolicyGuard::Role(role.to_string())
+    }
+
+    pub fn permission(permission: Permission) -> Self {
+        PolicyGuard::Permission(permission)
+    }
+
+    fn allows(&self, gate: &Gate, user: &Principal) -> bool {
+        match self {
+            PolicyGuard::Role(role) => gate.has_role(user, role),
+            PolicyGuard::Permission(permission) => gate.has_permission(user, permission),
+        }
+    }
+}
+
+impl async_graphql::Guard for PolicyGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let user = ctx
+            .data_opt::<Principal>()
+            .ok_or_else(|| GraphError::Unauthenticated.to_string())?;
+        let gate = ctx.data_opt::<Gate>().unwrap_or_else(|| policy::gate());
+        if self.allows(gate, user) {
+            Ok(())
+        } else {
+            Err(
+                GraphError::PermissionDenied("insufficient privileges".to_string())
+                    .to_string()
+                    .into(),
+            )
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -482,6 +634,32 @@ impl PaginationInput {
     pub fn limit(&self) -> i32 {
         self.first.or(self.last).unwrap_or(10).min(100)
     }
+
+    /// Run a keyset-paginated query for this input
+    ///
+    /// `before`/`last` page backwards, otherwise pages forward from
+    /// `after`. Cursors are verified with `Cursor::decode`.
+    ///
+    /// ```rust,ignore
+    /// async fn posts(&self, page: PaginationInput) -> Result<Connection<Post>> {
+    ///     let query = Post::query().order_by("published_at", "DESC");
+    ///     let total = Post::query().count().await? as i32;
+    ///     Ok(Connection::from_cursor_page(page.paginate(query).await?, total))
+    /// }
+    /// ```
+    pub async fn paginate<T>(&self, query: Builder<'_>) -> Result<CursorPage<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        let limit = self.limit() as i64;
+        if self.before.is_some() || (self.last.is_some() && self.first.is_none()) {
+            let before = self.before.as_deref().map(Cursor::decode).transpose()?;
+            query.paginate_cursor_before(before, limit).await
+        } else {
+            let after = self.after.as_deref().map(Cursor::decode).transpose()?;
+            query.paginate_cursor(after, limit).await
+        }
+    }
 }
 
 /// Connection for cursor-based pagination
@@ -530,6 +708,36 @@ impl<T: async_graphql::OutputType> Connection<T> {
     }
 }
 
+impl<T: async_graphql::OutputType> Connection<T> {
+    /// Build a connection whose edges carry real keyset cursors
+    ///
+    /// Keyset pagination doesn't count rows, so `total_count` is supplied
+    /// by the caller.
+    pub fn from_cursor_page(page: CursorPage<T>, total_count: i32) -> Self {
+        let page_info = PageInfo {
+            has_next_page: page.has_next_page(),
+            has_previous_page: page.has_previous_page(),
+            start_cursor: page.cursors.first().map(|c| c.to_string()),
+            end_cursor: page.cursors.last().map(|c| c.to_string()),
+        };
+        let edges = page
+            .data
+            .into_iter()
+            .zip(page.cursors)
+            .map(|(node, cursor)| Edge {
+                node,
+                cursor: cursor.to_string(),
+            })
+            .collect();
+
+        Self {
+            edges,
+            page_info,
+            total_count,
+        }
+    }
+}
+
 fn base64_encode(s: &str) -> String {
     // Simple base64-like encoding for cursors
     s.as_bytes()
@@ -774,6 +982,121 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    struct Doc {
+        owner: String,
+    }
+
+    impl Resource for Doc {
+        fn owner_id(&self) -> Option<String> {
+            Some(self.owner.clone())
+        }
+    }
+
+    #[test]
+    fn test_authorize_with_policy() {
+        use crate::policy::{Rule, RulePolicy};
+
+        let gate = Gate::new().policy(RulePolicy::<Doc>::new().allow(Action::Update, Rule::Owner));
+        let doc = Doc {
+            owner: "ada".into(),
+        };
+
+        let ada = Some(Principal::new("ada"));
+        let bob = Some(Principal::new("bob"));
+        assert!(AuthGuard::authorize_with(&gate, &ada, Action::Update, &doc).is_ok());
+        assert!(matches!(
+            AuthGuard::authorize_with(&gate, &bob, Action::Update, &doc),
+            Err(GraphError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            AuthGuard::authorize_with(&gate, &None, Action::Update, &doc),
+            Err(GraphError::Unauthenticated)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_policy_guard() {
+        use crate::policy::RoleHierarchy;
+
+        struct Query;
+
+        #[Object]
+        impl Query {
+            #[graphql(guard = "PolicyGuard::role(\"admin\")")]
+            async fn secret(&self) -> &str {
+                "42"
+            }
+        }
+
+        let gate = Gate::new().roles(RoleHierarchy::new().inherit("owner", "admin"));
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .data(gate)
+            .finish();
+        let run = |principal: Option<Principal>| {
+            let mut request = async_graphql::Request::new("{ secret }");
+            if let Some(principal) = principal {
+                request = request.data(principal);
+            }
+            schema.execute(request)
+        };
+
+        // Inherited role passes
+        let response = run(Some(Principal::new("1").with_role("owner"))).await;
+        assert!(response.errors.is_empty());
+        let response = run(Some(Principal::new("2").with_role("viewer"))).await;
+        assert!(response.errors[0].message.contains("Permission denied"));
+        let response = run(None).await;
+        assert!(response.errors[0]
+            .message
+            .contains("Authentication required"));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_and_principal() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        struct Query;
+
+        #[Object]
+        impl Query {
+            async fn me(&self, ctx: &Context<'_>) -> Option<String> {
+                ctx.data_opt::<Principal>().map(|p| p.id.clone())
+            }
+        }
+
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription).finish();
+        let app = axum::Router::new()
+            .route(
+                "/plain",
+                axum::routing::post(GraphQL::handler(schema.clone())),
+            )
+            .route(
+                "/principal",
+                axum::routing::post(GraphQL::handler_with_principal(schema.clone())),
+            )
+            .with_state(schema)
+            .layer(axum::Extension(Principal::new("ada")));
+
+        let me = |path: &str| {
+            let request = Request::post(path)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"query":"{ me }"}"#))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"]["me"].clone()
+            }
+        };
+        assert_eq!(me("/plain").await, serde_json::Value::Null);
+        assert_eq!(me("/principal").await, "ada");
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // PAGINATION TESTS
     // ═══════════════════════════════════════════════════════════════════════
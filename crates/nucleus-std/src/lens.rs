@@ -15,24 +15,39 @@
 //!
 //! // Create thumbnail
 //! let thumb = Lens::thumbnail(&image_bytes, 150)?;
+//!
+//! // Declarative resize, as used by upload variants
+//! let transform: Transform = "150x150 cover webp".parse()?;
+//! let thumb = Lens::transform(&image_bytes, &transform)?;
 //! ```
-
-use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageFormat as ImgFormat};
+//!
+//! Images are decoded with their EXIF orientation applied, so photos taken
+//! on a rotated phone come out upright.
+
+use image::{
+    imageops::FilterType, DynamicImage, GenericImageView, ImageDecoder, ImageFormat as ImgFormat,
+    ImageReader,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::Cursor;
+use std::str::FromStr;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // TYPES
 // ═══════════════════════════════════════════════════════════════════════════
 
 /// Image output format with quality settings
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImageFormat {
     /// JPEG with quality (1-100)
     Jpeg(u8),
     /// PNG (lossless)
     Png,
-    /// WebP with quality (1-100)
+    /// WebP; encoded losslessly, the quality is ignored
     WebP(u8),
+    /// AVIF with quality (1-100)
+    Avif(u8),
     /// GIF
     Gif,
     /// BMP
@@ -46,6 +61,7 @@ impl ImageFormat {
             ImageFormat::Jpeg(_) => "jpg",
             ImageFormat::Png => "png",
             ImageFormat::WebP(_) => "webp",
+            ImageFormat::Avif(_) => "avif",
             ImageFormat::Gif => "gif",
             ImageFormat::Bmp => "bmp",
         }
@@ -57,10 +73,184 @@ impl ImageFormat {
             ImageFormat::Jpeg(_) => "image/jpeg",
             ImageFormat::Png => "image/png",
             ImageFormat::WebP(_) => "image/webp",
+            ImageFormat::Avif(_) => "image/avif",
             ImageFormat::Gif => "image/gif",
             ImageFormat::Bmp => "image/bmp",
         }
     }
+
+    /// Format of encoded image data, with default quality; `None` for
+    /// formats Lens can't write
+    pub fn of(data: &[u8]) -> Option<Self> {
+        match image::guess_format(data).ok()? {
+            ImgFormat::Jpeg => Some(ImageFormat::Jpeg(85)),
+            ImgFormat::Png => Some(ImageFormat::Png),
+            ImgFormat::WebP => Some(ImageFormat::WebP(80)),
+            ImgFormat::Avif => Some(ImageFormat::Avif(70)),
+            ImgFormat::Gif => Some(ImageFormat::Gif),
+            ImgFormat::Bmp => Some(ImageFormat::Bmp),
+            _ => None,
+        }
+    }
+
+    fn quality(&self) -> Option<u8> {
+        match self {
+            ImageFormat::Jpeg(q) | ImageFormat::WebP(q) | ImageFormat::Avif(q) => Some(*q),
+            _ => None,
+        }
+    }
+
+    fn with_quality(self, quality: u8) -> Option<Self> {
+        match self {
+            ImageFormat::Jpeg(_) => Some(ImageFormat::Jpeg(quality)),
+            ImageFormat::WebP(_) => Some(ImageFormat::WebP(quality)),
+            ImageFormat::Avif(_) => Some(ImageFormat::Avif(quality)),
+            _ => None,
+        }
+    }
+}
+
+/// How a resize fills its box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// Fill the box exactly, cropping the overflow around the center
+    Cover,
+    /// Fit inside the box, keeping the aspect ratio; never enlarges
+    Contain,
+    /// Stretch to the box
+    Fill,
+}
+
+/// A resize and re-encode, written as text like `150x150 cover webp` or
+/// `fit 1600 avif q60`
+///
+/// Tokens are separated by spaces or `-`, in any order:
+/// - `WxH`, or `N` for an `NxN` box (required)
+/// - `cover`, `fit` (default) or `fill`
+/// - `jpeg`, `png`, `webp`, `avif` or `gif`; otherwise the source format is kept
+/// - `qN` quality for jpeg, webp and avif
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    pub width: u32,
+    pub height: u32,
+    pub fit: Fit,
+    /// Output format; `None` keeps the source format
+    pub format: Option<ImageFormat>,
+}
+
+/// Largest width or height a `Transform` accepts
+pub const MAX_DIMENSION: u32 = 8192;
+
+impl Transform {
+    pub fn new(width: u32, height: u32, fit: Fit) -> Self {
+        Self {
+            width,
+            height,
+            fit,
+            format: None,
+        }
+    }
+
+    /// Encode the result as `format`
+    pub fn with_format(mut self, format: ImageFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Format the result of transforming `source` is encoded in
+    pub fn output_format(&self, source: &[u8]) -> ImageFormat {
+        self.format
+            .or_else(|| ImageFormat::of(source))
+            .unwrap_or(ImageFormat::Png)
+    }
+}
+
+impl FromStr for Transform {
+    type Err = LensError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = |why: &str| LensError(format!("Invalid transform '{}': {}", spec, why));
+        let mut size = None;
+        let mut fit = Fit::Contain;
+        let mut format = None;
+        let mut quality = None;
+
+        for token in spec.split([' ', '-']).filter(|t| !t.is_empty()) {
+            let token = token.to_ascii_lowercase();
+            match token.as_str() {
+                "cover" => fit = Fit::Cover,
+                "fit" | "contain" => fit = Fit::Contain,
+                "fill" => fit = Fit::Fill,
+                "jpeg" | "jpg" => format = Some(ImageFormat::Jpeg(85)),
+                "png" => format = Some(ImageFormat::Png),
+                "webp" => format = Some(ImageFormat::WebP(80)),
+                "avif" => format = Some(ImageFormat::Avif(70)),
+                "gif" => format = Some(ImageFormat::Gif),
+                _ => {
+                    if let Some(q) = token.strip_prefix('q') {
+                        match q.parse::<u8>() {
+                            Ok(q @ 1..=100) => quality = Some(q),
+                            _ => return Err(invalid("quality must be q1 to q100")),
+                        }
+                        continue;
+                    }
+                    let (w, h) = token.split_once('x').unwrap_or((&token, &token));
+                    match (w.parse::<u32>(), h.parse::<u32>()) {
+                        (Ok(w), Ok(h))
+                            if (1..=MAX_DIMENSION).contains(&w.max(h)) && w.min(h) > 0 =>
+                        {
+                            size = Some((w, h))
+                        }
+                        _ => return Err(invalid(&format!("unknown token '{}'", token))),
+                    }
+                }
+            }
+        }
+
+        let (width, height) = size.ok_or_else(|| invalid("missing size"))?;
+        if let Some(q) = quality {
+            format = Some(
+                format
+                    .and_then(|f| f.with_quality(q))
+                    .ok_or_else(|| invalid("quality needs jpeg, webp or avif"))?,
+            );
+        }
+        Ok(Self {
+            width,
+            height,
+            fit,
+            format,
+        })
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fit = match self.fit {
+            Fit::Cover => "cover",
+            Fit::Contain => "fit",
+            Fit::Fill => "fill",
+        };
+        write!(f, "{}x{} {}", self.width, self.height, fit)?;
+        if let Some(format) = self.format {
+            write!(f, " {}", format.extension())?;
+            if let Some(q) = format.quality() {
+                write!(f, " q{}", q)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Low-resolution stand-in shown while an image loads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Placeholder {
+    pub width: u32,
+    pub height: u32,
+    /// BlurHash (<https://blurha.sh>) with 4x3 components
+    pub blurhash: String,
+    /// Dominant color as `#rrggbb`
+    pub color: String,
 }
 
 /// Image dimensions
@@ -94,8 +284,7 @@ pub struct Lens;
 impl Lens {
     /// Get image dimensions
     pub fn dimensions(data: &[u8]) -> Result<Dimensions> {
-        let img = image::load_from_memory(data)
-            .map_err(|e| LensError(format!("Failed to load image: {}", e)))?;
+        let img = Self::load(data)?;
 
         Ok(Dimensions {
             width: img.width(),
@@ -141,6 +330,63 @@ impl Lens {
         Self::encode(&cropped, ImageFormat::Jpeg(85))
     }
 
+    /// Resize and re-encode as described by `transform`
+    pub fn transform(data: &[u8], transform: &Transform) -> Result<Vec<u8>> {
+        let img = Self::load(data)?;
+        let (width, height) = (transform.width, transform.height);
+        let resized = match transform.fit {
+            Fit::Cover => img.resize_to_fill(width, height, FilterType::Lanczos3),
+            Fit::Contain if img.width() <= width && img.height() <= height => img,
+            Fit::Contain => img.resize(width, height, FilterType::Lanczos3),
+            Fit::Fill => img.resize_exact(width, height, FilterType::Lanczos3),
+        };
+        Self::encode(&resized, transform.output_format(data))
+    }
+
+    /// Re-encode a JPEG or PNG with its EXIF orientation applied, dropping
+    /// EXIF (GPS position, camera details) and all other metadata
+    pub fn strip_metadata(data: &[u8]) -> Result<Vec<u8>> {
+        let format = match image::guess_format(data) {
+            Ok(ImgFormat::Jpeg) => ImageFormat::Jpeg(90),
+            Ok(ImgFormat::Png) => ImageFormat::Png,
+            _ => {
+                return Err(LensError(
+                    "Metadata can only be stripped from JPEG and PNG".to_string(),
+                ))
+            }
+        };
+        Self::encode(&Self::load(data)?, format)
+    }
+
+    /// Dimensions, BlurHash and dominant color of an image
+    pub fn placeholder(data: &[u8]) -> Result<Placeholder> {
+        let img = Self::load(data)?;
+        let small = img.thumbnail(64, 64);
+        Ok(Placeholder {
+            width: img.width(),
+            height: img.height(),
+            blurhash: blurhash::encode(&small, 4, 3),
+            color: dominant_color(&small),
+        })
+    }
+
+    /// BlurHash of an image with `x` by `y` components (1-9 each)
+    pub fn blurhash(data: &[u8], x: u32, y: u32) -> Result<String> {
+        if !(1..=9).contains(&x) || !(1..=9).contains(&y) {
+            return Err(LensError(format!(
+                "BlurHash components must be 1-9, got {}x{}",
+                x, y
+            )));
+        }
+        let img = Self::load(data)?;
+        Ok(blurhash::encode(&img.thumbnail(64, 64), x, y))
+    }
+
+    /// Most common color of an image as `#rrggbb`
+    pub fn dominant_color(data: &[u8]) -> Result<String> {
+        Ok(dominant_color(&Self::load(data)?.thumbnail(64, 64)))
+    }
+
     /// Convert image to specified format
     pub fn convert(data: &[u8], format: ImageFormat) -> Result<Vec<u8>> {
         let img = Self::load(data)?;
@@ -221,8 +467,18 @@ impl Lens {
     // INTERNAL
     // ─────────────────────────────────────────────────────────────────────────
 
+    /// Decode with the EXIF orientation applied
     fn load(data: &[u8]) -> Result<DynamicImage> {
-        image::load_from_memory(data).map_err(|e| LensError(format!("Failed to load image: {}", e)))
+        let fail = |e: image::ImageError| LensError(format!("Failed to load image: {}", e));
+        let mut decoder = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|e| LensError(format!("Failed to load image: {}", e)))?
+            .into_decoder()
+            .map_err(fail)?;
+        let orientation = decoder.orientation().map_err(fail)?;
+        let mut img = DynamicImage::from_decoder(decoder).map_err(fail)?;
+        img.apply_orientation(orientation);
+        Ok(img)
     }
 
     fn encode(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>> {
@@ -232,7 +488,9 @@ impl Lens {
             ImageFormat::Jpeg(quality) => {
                 let encoder =
                     image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
-                img.write_with_encoder(encoder)
+                // JPEG has no alpha channel
+                DynamicImage::ImageRgb8(img.to_rgb8())
+                    .write_with_encoder(encoder)
                     .map_err(|e| LensError(format!("Failed to encode JPEG: {}", e)))?;
             }
             ImageFormat::Png => {
@@ -248,9 +506,19 @@ impl Lens {
                     .map_err(|e| LensError(format!("Failed to encode BMP: {}", e)))?;
             }
             ImageFormat::WebP(_) => {
-                // WebP encoding requires the webp feature, fall back to PNG
-                img.write_to(&mut buffer, ImgFormat::Png)
-                    .map_err(|e| LensError(format!("Failed to encode (WebP fallback): {}", e)))?;
+                DynamicImage::ImageRgba8(img.to_rgba8())
+                    .write_to(&mut buffer, ImgFormat::WebP)
+                    .map_err(|e| LensError(format!("Failed to encode WebP: {}", e)))?;
+            }
+            ImageFormat::Avif(quality) => {
+                let encoder = image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                    &mut buffer,
+                    8,
+                    quality,
+                );
+                DynamicImage::ImageRgba8(img.to_rgba8())
+                    .write_with_encoder(encoder)
+                    .map_err(|e| LensError(format!("Failed to encode AVIF: {}", e)))?;
             }
         }
 
@@ -258,6 +526,128 @@ impl Lens {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// PLACEHOLDERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Average of the most populated color bucket, ignoring transparent pixels
+fn dominant_color(img: &DynamicImage) -> String {
+    let mut buckets = vec![(0u32, [0u32; 3]); 4096];
+    for pixel in img.to_rgba8().pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let bucket =
+            &mut buckets[(r as usize >> 4) << 8 | (g as usize >> 4) << 4 | b as usize >> 4];
+        bucket.0 += 1;
+        for (sum, value) in bucket.1.iter_mut().zip([r, g, b]) {
+            *sum += value as u32;
+        }
+    }
+    let (count, sums) = buckets
+        .into_iter()
+        .max_by_key(|(count, _)| *count)
+        .filter(|(count, _)| *count > 0)
+        .unwrap_or((1, [0; 3]));
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        sums[0] / count,
+        sums[1] / count,
+        sums[2] / count
+    )
+}
+
+/// BlurHash encoder, following the reference implementation
+mod blurhash {
+    use image::DynamicImage;
+    use std::f32::consts::PI;
+
+    const CHARACTERS: &[u8] =
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+    pub(super) fn encode(img: &DynamicImage, components_x: u32, components_y: u32) -> String {
+        let rgb = img.to_rgb8();
+        let (width, height) = rgb.dimensions();
+        let linear: Vec<[f32; 3]> = rgb.pixels().map(|p| p.0.map(srgb_to_linear)).collect();
+
+        let mut factors = Vec::with_capacity((components_x * components_y) as usize);
+        for j in 0..components_y {
+            for i in 0..components_x {
+                let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+                let mut factor = [0.0f32; 3];
+                for y in 0..height {
+                    let basis_y = (PI * j as f32 * y as f32 / height as f32).cos();
+                    for x in 0..width {
+                        let basis = basis_y * (PI * i as f32 * x as f32 / width as f32).cos();
+                        let pixel = linear[(y * width + x) as usize];
+                        for c in 0..3 {
+                            factor[c] += basis * pixel[c];
+                        }
+                    }
+                }
+                let scale = normalisation / (width * height) as f32;
+                factors.push(factor.map(|v| v * scale));
+            }
+        }
+
+        let mut hash = String::new();
+        base83(components_x - 1 + (components_y - 1) * 9, 1, &mut hash);
+
+        let (dc, ac) = factors.split_first().expect("at least one component");
+        let max_value = if ac.is_empty() {
+            base83(0, 1, &mut hash);
+            1.0
+        } else {
+            let actual_max = ac
+                .iter()
+                .flat_map(|f| f.iter())
+                .fold(0.0f32, |max, v| max.max(v.abs()));
+            let quantised = ((actual_max * 166.0 - 0.5).floor()).clamp(0.0, 82.0) as u32;
+            base83(quantised, 1, &mut hash);
+            (quantised + 1) as f32 / 166.0
+        };
+
+        let [r, g, b] = dc.map(linear_to_srgb);
+        base83((r << 16) + (g << 8) + b, 4, &mut hash);
+        for factor in ac {
+            let [r, g, b] = factor.map(|v| {
+                let v = v / max_value;
+                (v.signum() * v.abs().sqrt() * 9.0 + 9.5)
+                    .floor()
+                    .clamp(0.0, 18.0) as u32
+            });
+            base83(r * 19 * 19 + g * 19 + b, 2, &mut hash);
+        }
+        hash
+    }
+
+    fn base83(value: u32, length: u32, out: &mut String) {
+        for i in 1..=length {
+            let digit = (value / 83u32.pow(length - i)) % 83;
+            out.push(CHARACTERS[digit as usize] as char);
+        }
+    }
+
+    fn srgb_to_linear(value: u8) -> f32 {
+        let v = value as f32 / 255.0;
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    fn linear_to_srgb(value: f32) -> u32 {
+        let v = value.clamp(0.0, 1.0);
+        if v <= 0.003_130_8 {
+            (v * 12.92 * 255.0 + 0.5) as u32
+        } else {
+            ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u32
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // TESTS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -463,11 +853,151 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_webp_fallback() {
+    fn test_convert_webp() {
         let data = create_test_image();
-        // WebP falls back to PNG in our implementation
         let converted = Lens::convert(&data, ImageFormat::WebP(80)).unwrap();
-        assert!(!converted.is_empty());
+        assert_eq!(&converted[8..12], b"WEBP");
+        assert_eq!(Lens::dimensions(&converted).unwrap().width, 10);
+    }
+
+    #[test]
+    fn test_convert_avif() {
+        let data = create_test_image();
+        let converted = Lens::convert(&data, ImageFormat::Avif(60)).unwrap();
+        assert_eq!(&converted[4..12], b"ftypavif");
+        assert_eq!(ImageFormat::of(&converted), Some(ImageFormat::Avif(70)));
+    }
+
+    /// A JPEG whose EXIF says to rotate it 90° clockwise for display
+    fn create_rotated_jpeg(width: u32, height: u32) -> Vec<u8> {
+        let img: ImageBuffer<image::Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(width, height, |_, _| image::Rgb([0, 0, 255]));
+        let jpeg = Lens::encode(&DynamicImage::ImageRgb8(img), ImageFormat::Jpeg(90)).unwrap();
+
+        let mut exif = b"Exif\0\0MM\0*\0\0\0\x08\0\x01".to_vec();
+        exif.extend_from_slice(b"\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0");
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(b"\xFF\xE1");
+        out.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(&exif);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    #[test]
+    fn test_exif_orientation_applied() {
+        let data = create_rotated_jpeg(20, 10);
+        assert_eq!(
+            Lens::dimensions(&data).unwrap(),
+            Dimensions {
+                width: 10,
+                height: 20
+            }
+        );
+
+        let stripped = Lens::strip_metadata(&data).unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"Exif"));
+        assert_eq!(Lens::dimensions(&stripped).unwrap().height, 20);
+        assert!(Lens::strip_metadata(&Lens::convert(&data, ImageFormat::Gif).unwrap()).is_err());
+
+        let thumb = Lens::transform(&data, &"10x10 cover png".parse().unwrap()).unwrap();
+        assert_eq!(Lens::dimensions(&thumb).unwrap().width, 10);
+    }
+
+    #[test]
+    fn test_transform_parse() {
+        let thumb: Transform = "150x150 cover webp".parse().unwrap();
+        assert_eq!(
+            thumb,
+            Transform::new(150, 150, Fit::Cover).with_format(ImageFormat::WebP(80))
+        );
+        let large: Transform = "fit 1600 avif q60".parse().unwrap();
+        assert_eq!((large.width, large.height), (1600, 1600));
+        assert_eq!(large.fit, Fit::Contain);
+        assert_eq!(large.format, Some(ImageFormat::Avif(60)));
+        assert_eq!(large.to_string(), "1600x1600 fit avif q60");
+        assert_eq!(
+            large
+                .to_string()
+                .replace(' ', "-")
+                .parse::<Transform>()
+                .unwrap(),
+            large
+        );
+
+        let keep: Transform = "300x200".parse().unwrap();
+        assert_eq!(keep.format, None);
+        assert_eq!(keep.fit, Fit::Contain);
+
+        for invalid in [
+            "",
+            "cover webp",
+            "0x10",
+            "99999x1",
+            "10x10 q0",
+            "10x10 png q80",
+            "10x10 tiff",
+        ] {
+            assert!(invalid.parse::<Transform>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_transform_fit_modes() {
+        let wide: ImageBuffer<image::Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(40, 20, |_, _| image::Rgb([0, 255, 0]));
+        let mut buffer = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(wide)
+            .write_to(&mut buffer, ImgFormat::Png)
+            .unwrap();
+        let data = buffer.into_inner();
+
+        let size = |spec: &str| {
+            let out = Lens::transform(&data, &spec.parse().unwrap()).unwrap();
+            let dims = Lens::dimensions(&out).unwrap();
+            (dims.width, dims.height)
+        };
+        assert_eq!(size("10x10 cover"), (10, 10));
+        assert_eq!(size("10x10 fit"), (10, 5));
+        assert_eq!(size("10x10 fill"), (10, 10));
+        // Contain never enlarges
+        assert_eq!(size("100x100 fit"), (40, 20));
+
+        let out = Lens::transform(&data, &"10 cover jpeg".parse().unwrap()).unwrap();
+        assert_eq!(ImageFormat::of(&out), Some(ImageFormat::Jpeg(85)));
+        let out = Lens::transform(&data, &"10 cover".parse().unwrap()).unwrap();
+        assert_eq!(ImageFormat::of(&out), Some(ImageFormat::Png));
+    }
+
+    #[test]
+    fn test_placeholder() {
+        let data = create_test_image();
+        let placeholder = Lens::placeholder(&data).unwrap();
+        assert_eq!((placeholder.width, placeholder.height), (10, 10));
+        assert_eq!(placeholder.color, "#ff0000");
+        // 4x3 components ("L"), then the AC scale and a DC of pure red
+        assert_eq!(&placeholder.blurhash[..1], "L");
+        assert_eq!(&placeholder.blurhash[2..6], "TI:j");
+        assert_eq!(placeholder.blurhash.len(), 28);
+
+        assert_eq!(Lens::blurhash(&data, 1, 1).unwrap(), "00TI:j");
+        assert!(Lens::blurhash(&data, 0, 3).is_err());
+        assert_eq!(Lens::dominant_color(&data).unwrap(), "#ff0000");
+    }
+
+    #[test]
+    fn test_blurhash_reference() {
+        // Left half black, right half white: only horizontal components
+        let img: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(32, 32, |x, _| {
+            if x < 16 {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        });
+        let hash = blurhash::encode(&DynamicImage::ImageRgb8(img), 2, 1);
+        assert_eq!(hash.len(), 8);
+        assert_eq!(&hash[..1], "1");
     }
 
     #[test]
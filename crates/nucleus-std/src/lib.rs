@@ -7,6 +7,7 @@ pub mod browser;
 pub mod cache;
 pub mod chain;
 pub mod config;
+pub mod csrf;
 pub mod devtools;
 pub mod errors;
 pub mod federation;
@@ -18,12 +19,14 @@ pub mod graph;
 pub mod health;
 pub mod lens;
 pub mod logging;
+pub mod mfa;
 pub mod middleware;
 pub mod neural;
 pub mod neutron;
 pub mod oauth;
 pub mod payments;
 pub mod photon;
+pub mod policy;
 pub mod polyglot;
 pub mod pool_monitor;
 #[cfg(feature = "mail")]
@@ -36,9 +39,11 @@ pub mod scheduler;
 pub mod scout;
 pub mod session;
 pub mod sonar;
+pub mod storage;
 pub mod stream;
 pub mod tenant;
 pub mod testing;
+pub mod tokens;
 pub mod upload;
 pub mod utils;
 pub mod vault;
@@ -52,23 +57,36 @@ pub mod agent;
 pub mod mcp;
 
 // Re-exports
+pub use async_trait::async_trait;
 pub use axum;
 pub use beacon::Beacon;
 #[cfg(feature = "browser")]
 pub use browser::{Browser, BrowserError, BrowserOptions};
 pub use cache::{cached, cached_with_ttl, Cache, CacheKey};
 pub use chain::Chain;
+pub use chrono;
 pub use config::{Config, GLOBAL_CONFIG};
+pub use csrf::{CsrfConfig, CsrfLayer, CsrfToken};
 pub use fortress::Fortress;
 pub use fortress::{require_auth, AuthUser, OptionalAuth};
-pub use fortress::{RateLimitConfig, RateLimitResult, RateLimiter};
+pub use fortress::{
+    MemoryRateLimitStore, RateLimitAlgorithm, RateLimitConfig, RateLimitError, RateLimitKey,
+    RateLimitResult, RateLimitStore, RateLimiter, RedisRateLimitStore,
+};
 pub use health::{ComponentCheck, HealthChecker, HealthReport, HealthStatus};
 pub use lens::Lens;
 pub use logging::{init as init_logging, LogConfig, LogFormat, LogLevel};
+pub use mfa::{
+    MemoryMfaStore, Mfa, MfaError, MfaMethods, MfaStore, PasskeyCredential, SqlMfaStore, Totp,
+    WebAuthn,
+};
 pub use neural::{ChatMessage, Neural, NeuralError, Role, Usage};
 pub use neutron::Signal;
 pub use payments::Stripe;
 pub use photon::{db, init_db, Builder, Model, Op};
+pub use policy::{
+    Action, Authz, Gate, Policy, PolicyError, Principal, Resource, RoleHierarchy, Rule, RulePolicy,
+};
 pub use polyglot::Polyglot;
 pub use pool_monitor::{
     PoolDashboard, PoolHealth, PoolHealthStatus, PoolMonitor, PoolSizingRecommendation, PoolStats,
@@ -76,7 +94,9 @@ pub use pool_monitor::{
 };
 #[cfg(feature = "mail")]
 pub use postman::Postman;
-pub use pulse::{Job, JobConfig, JobPriority, JobStatus, Pulse, PulseError};
+pub use pulse::{
+    Backoff, BatchStatus, Job, JobConfig, JobPriority, JobStatus, JobStore, Pulse, PulseError,
+};
 pub use redis_cache::{
     memory_cache, redis_cache as create_redis_cache, CacheBackend, MemoryCacheBackend,
     RedisBackend, RedisCacheError, UnifiedCache,
@@ -87,15 +107,21 @@ pub use scout::{
     TaskStatus,
 };
 pub use session::{
-    MemorySessionStore, SameSite, Session, SessionConfig, SessionManager, SessionStore,
+    MemorySessionStore, RedisSessionStore, SameSite, Session, SessionConfig, SessionLayer,
+    SessionManager, SessionStore, SqlSessionStore,
 };
 pub use sonar::Sonar;
 pub use sqlx;
+pub use storage::{
+    LocalStorage, MemoryStorage, Object, ObjectMeta, S3Storage, Storage, StorageError, UrlSigner,
+};
 pub use stream::{Room, SocketMessage, StreamError, StreamHandler, StreamHub, WebSocket};
 pub use tenant::{
-    Tenant, TenantError, TenantExtractor, TenantGuard, TenantInfo, TenantQuery, TenantStrategy,
+    register_tenant, registered_tenants, tenant_info, Isolation, Tenant, TenantError,
+    TenantExtractor, TenantGuard, TenantInfo, TenantQuery, TenantStrategy,
 };
-pub use upload::{Upload, UploadConfig, UploadError, UploadedFile};
+pub use tokens::{Claims, SigningKey, TokenManager, TokenPair};
+pub use upload::{TusServer, Upload, UploadConfig, UploadError, UploadedFile};
 pub use vault::{Account, AccountType, Ledger, LedgerEntry, Money, Transaction, Vault};
 
 #[cfg(test)]
@@ -0,0 +1,2018 @@
+//! Nucleus MFA - second factors for Fortress
+//!
+//! - TOTP (RFC 6238) secrets, `otpauth://` provisioning URIs, verification
+//!   with a drift window and replay protection
+//! - One-time recovery codes, stored hashed
+//! - WebAuthn / passkeys: registration and assertion ceremonies
+//!
+//! Credentials are persisted through an [`MfaStore`]: [`MemoryMfaStore`] for
+//! tests, [`SqlMfaStore`] on a Photon pool.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::mfa::{Mfa, SqlMfaStore, WebAuthn};
+//!
+//! let store = SqlMfaStore::new(pool);
+//! store.migrate().await?;
+//! let mfa = Mfa::new(store, "Acme")
+//!     .with_webauthn(WebAuthn::new("acme.com", "Acme", "https://acme.com"));
+//!
+//! // Enrollment: show the QR code, then confirm with the first code
+//! let totp = mfa.enroll_totp(&user.id, &user.email).await?;
+//! let uri = totp.provisioning_uri();
+//! mfa.confirm_totp(&user.id, &code).await?;
+//!
+//! // Login
+//! mfa.verify_totp(&user.id, &code).await?;
+//! ```
+
+use crate::photon::{on_pool, DatabasePool, DatabaseType};
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ciborium::value::Value as Cbor;
+use data_encoding::BASE32_NOPAD;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ERRORS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// MFA errors
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MfaError {
+    #[error("Invalid code")]
+    InvalidCode,
+
+    #[error("Code was already used")]
+    CodeReused,
+
+    #[error("Invalid TOTP secret")]
+    InvalidSecret,
+
+    #[error("{0} is not enrolled")]
+    NotEnrolled(&'static str),
+
+    #[error("WebAuthn is not configured")]
+    WebAuthnDisabled,
+
+    #[error("WebAuthn verification failed: {0}")]
+    WebAuthn(String),
+
+    #[error("Unknown credential")]
+    UnknownCredential,
+
+    #[error("Signature counter went backwards; the authenticator may be cloned")]
+    CounterRegression,
+
+    #[error("MFA storage error: {0}")]
+    Storage(String),
+}
+
+impl From<sqlx::Error> for MfaError {
+    fn from(err: sqlx::Error) -> Self {
+        MfaError::Storage(err.to_string())
+    }
+}
+
+fn webauthn_error(msg: impl Into<String>) -> MfaError {
+    MfaError::WebAuthn(msg.into())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TOTP
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// HMAC used by a TOTP secret; authenticator apps mostly support only SHA1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            TotpAlgorithm::Sha1 => "SHA1",
+            TotpAlgorithm::Sha256 => "SHA256",
+            TotpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    fn parse(name: &str) -> Self {
+        match name {
+            "SHA256" => TotpAlgorithm::Sha256,
+            "SHA512" => TotpAlgorithm::Sha512,
+            _ => TotpAlgorithm::Sha1,
+        }
+    }
+
+    fn hmac(&self) -> ring::hmac::Algorithm {
+        match self {
+            TotpAlgorithm::Sha1 => ring::hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            TotpAlgorithm::Sha256 => ring::hmac::HMAC_SHA256,
+            TotpAlgorithm::Sha512 => ring::hmac::HMAC_SHA512,
+        }
+    }
+}
+
+/// A TOTP secret with its parameters (RFC 6238)
+#[derive(Clone)]
+pub struct Totp {
+    secret: Vec<u8>,
+    issuer: String,
+    account: String,
+    algorithm: TotpAlgorithm,
+    digits: u32,
+    step: u64,
+    skew: u64,
+}
+
+impl std::fmt::Debug for Totp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Totp")
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Totp {
+    /// New random 160-bit secret; 6 digits, 30 second steps, ±1 step of drift
+    pub fn generate(issuer: &str, account: &str) -> Self {
+        let secret: [u8; 20] = rand::thread_rng().gen();
+        Self::from_secret(secret.to_vec(), issuer, account)
+    }
+
+    /// Load a base32 secret (as shown to users and stored by `MfaStore`)
+    pub fn from_base32(secret: &str, issuer: &str, account: &str) -> Result<Self, MfaError> {
+        let normalized: String = secret
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '=')
+            .collect::<String>()
+            .to_ascii_uppercase();
+        let secret = BASE32_NOPAD
+            .decode(normalized.as_bytes())
+            .map_err(|_| MfaError::InvalidSecret)?;
+        if secret.len() < 10 {
+            return Err(MfaError::InvalidSecret);
+        }
+        Ok(Self::from_secret(secret, issuer, account))
+    }
+
+    fn from_secret(secret: Vec<u8>, issuer: &str, account: &str) -> Self {
+        Self {
+            secret,
+            issuer: issuer.to_string(),
+            account: account.to_string(),
+            algorithm: TotpAlgorithm::Sha1,
+            digits: 6,
+            step: 30,
+            skew: 1,
+        }
+    }
+
+    /// Set the HMAC algorithm
+    pub fn algorithm(mut self, algorithm: TotpAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the code length (6-8)
+    pub fn digits(mut self, digits: u32) -> Self {
+        self.digits = digits.clamp(6, 8);
+        self
+    }
+
+    /// Set the time step
+    pub fn step(mut self, step: Duration) -> Self {
+        self.step = step.as_secs().max(1);
+        self
+    }
+
+    /// Accept codes up to `steps` steps before or after the current one
+    pub fn skew(mut self, steps: u64) -> Self {
+        self.skew = steps;
+        self
+    }
+
+    /// Secret in base32, for manual entry
+    pub fn secret_base32(&self) -> String {
+        BASE32_NOPAD.encode(&self.secret)
+    }
+
+    /// `otpauth://` URI to render as a QR code
+    pub fn provisioning_uri(&self) -> String {
+        let label = if self.issuer.is_empty() {
+            urlencoding::encode(&self.account).into_owned()
+        } else {
+            format!(
+                "{}:{}",
+                urlencoding::encode(&self.issuer),
+                urlencoding::encode(&self.account)
+            )
+        };
+        let mut uri = format!(
+            "otpauth://totp/{}?secret={}&algorithm={}&digits={}&period={}",
+            label,
+            self.secret_base32(),
+            self.algorithm.name(),
+            self.digits,
+            self.step
+        );
+        if !self.issuer.is_empty() {
+            uri.push_str(&format!("&issuer={}", urlencoding::encode(&self.issuer)));
+        }
+        uri
+    }
+
+    /// Code for a time step
+    fn code_for_step(&self, step: u64) -> String {
+        let key = ring::hmac::Key::new(self.algorithm.hmac(), &self.secret);
+        let tag = ring::hmac::sign(&key, &step.to_be_bytes());
+        let hash = tag.as_ref();
+
+        // Dynamic truncation (RFC 4226 section 5.3)
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(self.digits),
+            width = self.digits as usize
+        )
+    }
+
+    /// Code at a Unix time
+    pub fn code_at(&self, unix: u64) -> String {
+        self.code_for_step(unix / self.step)
+    }
+
+    /// Current code
+    pub fn now(&self) -> String {
+        self.code_at(unix_now() as u64)
+    }
+
+    /// Check `code` against the current time; returns the matched step
+    ///
+    /// Steps at or before `last_step` are rejected so a code cannot be
+    /// replayed; persist the returned step as the new `last_step`.
+    pub fn verify(&self, code: &str, last_step: Option<u64>) -> Result<u64, MfaError> {
+        self.verify_at(code, last_step, unix_now() as u64)
+    }
+
+    /// `verify` at a given Unix time
+    pub fn verify_at(
+        &self,
+        code: &str,
+        last_step: Option<u64>,
+        unix: u64,
+    ) -> Result<u64, MfaError> {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        if code.len() != self.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(MfaError::InvalidCode);
+        }
+
+        let current = unix / self.step;
+        let first = current.saturating_sub(self.skew);
+        let matched = (first..=current + self.skew)
+            .find(|step| constant_eq(self.code_for_step(*step).as_bytes(), code.as_bytes()))
+            .ok_or(MfaError::InvalidCode)?;
+
+        if last_step.is_some_and(|last| matched <= last) {
+            return Err(MfaError::CodeReused);
+        }
+        Ok(matched)
+    }
+}
+
+/// A stored TOTP enrollment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TotpRecord {
+    /// Base32 secret
+    pub secret: String,
+    pub algorithm: TotpAlgorithm,
+    pub digits: u32,
+    /// Step length in seconds
+    pub step: u64,
+    /// Set once the user entered a valid code
+    pub confirmed: bool,
+    /// Last accepted step, for replay protection
+    pub last_step: Option<u64>,
+}
+
+impl TotpRecord {
+    fn totp(&self, issuer: &str, account: &str) -> Result<Totp, MfaError> {
+        Ok(Totp::from_base32(&self.secret, issuer, account)?
+            .algorithm(self.algorithm)
+            .digits(self.digits)
+            .step(Duration::from_secs(self.step)))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// RECOVERY CODES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Alphabet without look-alike characters (no 0/o, 1/l/i)
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Generate `count` recovery codes like `k7xq-m2pd-9fhz-t3wa` (~79 bits each)
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            let chars: Vec<char> = (0..16)
+                .map(|_| RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+                .collect();
+            chars
+                .chunks(4)
+                .map(|c| c.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect()
+}
+
+/// Hash stored for a recovery code; case, spaces and dashes are ignored
+///
+/// The codes carry enough entropy that a fast hash is sufficient.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// WEBAUTHN
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// COSE algorithm identifiers offered at registration
+const COSE_ES256: i64 = -7;
+const COSE_EDDSA: i64 = -8;
+const COSE_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_DATA: u8 = 0x40;
+
+/// A registered passkey / security key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasskeyCredential {
+    /// Credential id, base64url
+    pub id: String,
+    pub user_id: String,
+    /// COSE public key, base64url
+    pub public_key: String,
+    pub sign_count: u32,
+    #[serde(default)]
+    pub transports: Vec<String>,
+    /// Label chosen by the user
+    pub name: Option<String>,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+/// Server side state of a registration ceremony; keep it in the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationState {
+    pub challenge: String,
+    pub user_id: String,
+}
+
+/// Server side state of an authentication ceremony; keep it in the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthenticationState {
+    pub challenge: String,
+    /// `None` for usernameless (discoverable credential) login
+    pub user_id: Option<String>,
+}
+
+/// `PublicKeyCredential.toJSON()` of `navigator.credentials.create()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationResponse {
+    pub id: String,
+    pub raw_id: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+/// `PublicKeyCredential.toJSON()` of `navigator.credentials.get()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationResponse {
+    pub id: String,
+    pub raw_id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/// Relying party settings and the two WebAuthn ceremonies
+///
+/// Options are returned in the JSON form accepted by
+/// `PublicKeyCredential.parseCreationOptionsFromJSON` / `parseRequestOptionsFromJSON`.
+/// Attestation is not requested, so any authenticator is accepted.
+#[derive(Debug, Clone)]
+pub struct WebAuthn {
+    rp_id: String,
+    rp_name: String,
+    origins: Vec<String>,
+    user_verification: bool,
+    timeout: Duration,
+}
+
+impl WebAuthn {
+    /// `rp_id` is the registrable domain (e.g. `acme.com`), `origin` the full
+    /// origin pages are served from (e.g. `https://acme.com`)
+    pub fn new(rp_id: &str, rp_name: &str, origin: &str) -> Self {
+        Self {
+            rp_id: rp_id.to_string(),
+            rp_name: rp_name.to_string(),
+            origins: vec![origin.trim_end_matches('/').to_string()],
+            user_verification: false,
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// Accept another origin (e.g. a subdomain)
+    pub fn origin(mut self, origin: &str) -> Self {
+        self.origins.push(origin.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Require PIN / biometrics, not just presence
+    pub fn require_user_verification(mut self) -> Self {
+        self.user_verification = true;
+        self
+    }
+
+    /// Ceremony timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.user_verification {
+            "required"
+        } else {
+            "preferred"
+        }
+    }
+
+    /// Options for `navigator.credentials.create()`
+    ///
+    /// `existing` credentials are excluded so one authenticator is not
+    /// registered twice.
+    pub fn start_registration(
+        &self,
+        user_id: &str,
+        user_name: &str,
+        display_name: &str,
+        existing: &[PasskeyCredential],
+    ) -> (serde_json::Value, RegistrationState) {
+        let challenge = random_challenge();
+        let params: Vec<_> = [COSE_ES256, COSE_EDDSA, COSE_RS256]
+            .iter()
+            .map(|alg| serde_json::json!({ "type": "public-key", "alg": alg }))
+            .collect();
+        let options = serde_json::json!({
+            "rp": { "id": self.rp_id, "name": self.rp_name },
+            "user": {
+                "id": URL_SAFE_NO_PAD.encode(user_id),
+                "name": user_name,
+                "displayName": display_name,
+            },
+            "challenge": challenge,
+            "pubKeyCredParams": params,
+            "timeout": self.timeout.as_millis() as u64,
+            "attestation": "none",
+            "excludeCredentials": credential_descriptors(existing),
+            "authenticatorSelection": {
+                "residentKey": "preferred",
+                "userVerification": self.user_verification(),
+            },
+        });
+        let state = RegistrationState {
+            challenge,
+            user_id: user_id.to_string(),
+        };
+        (options, state)
+    }
+
+    /// Verify the browser's response and return the new credential
+    pub fn finish_registration(
+        &self,
+        state: &RegistrationState,
+        response: &RegistrationResponse,
+    ) -> Result<PasskeyCredential, MfaError> {
+        let client_data = b64(&response.response.client_data_json)?;
+        self.check_client_data(&client_data, "webauthn.create", &state.challenge)?;
+
+        let attestation: Cbor =
+            ciborium::de::from_reader(&b64(&response.response.attestation_object)?[..])
+                .map_err(|_| webauthn_error("malformed attestation object"))?;
+        let auth_data = cbor_get(&attestation, "authData")
+            .and_then(|v| v.as_bytes())
+            .ok_or_else(|| webauthn_error("attestation object has no authData"))?;
+
+        let parsed = self.check_auth_data(auth_data)?;
+        if parsed.flags & FLAG_ATTESTED_DATA == 0 {
+            return Err(webauthn_error("no attested credential data"));
+        }
+
+        // aaguid(16) | id length(2) | id | COSE key
+        let rest = &auth_data[37..];
+        if rest.len() < 18 {
+            return Err(webauthn_error("truncated credential data"));
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_len {
+            return Err(webauthn_error("truncated credential id"));
+        }
+        let (credential_id, mut key_bytes) = rest.split_at(id_len);
+        let all_key_bytes = key_bytes;
+        let key: Cbor = ciborium::de::from_reader(&mut key_bytes)
+            .map_err(|_| webauthn_error("malformed credential public key"))?;
+        let cose_key = &all_key_bytes[..all_key_bytes.len() - key_bytes.len()];
+        CoseKey::parse(&key)?;
+
+        if b64(&response.raw_id)? != credential_id {
+            return Err(webauthn_error("credential id mismatch"));
+        }
+
+        Ok(PasskeyCredential {
+            id: URL_SAFE_NO_PAD.encode(credential_id),
+            user_id: state.user_id.clone(),
+            public_key: URL_SAFE_NO_PAD.encode(cose_key),
+            sign_count: parsed.sign_count,
+            transports: response.response.transports.clone(),
+            name: None,
+            created_at: unix_now(),
+            last_used_at: None,
+        })
+    }
+
+    /// Options for `navigator.credentials.get()`
+    ///
+    /// With no `user_id` the browser offers any discoverable passkey for this
+    /// site (usernameless login).
+    pub fn start_authentication(
+        &self,
+        user_id: Option<&str>,
+        credentials: &[PasskeyCredential],
+    ) -> (serde_json::Value, AuthenticationState) {
+        let challenge = random_challenge();
+        let options = serde_json::json!({
+            "challenge": challenge,
+            "timeout": self.timeout.as_millis() as u64,
+            "rpId": self.rp_id,
+            "allowCredentials": credential_descriptors(credentials),
+            "userVerification": self.user_verification(),
+        });
+        let state = AuthenticationState {
+            challenge,
+            user_id: user_id.map(String::from),
+        };
+        (options, state)
+    }
+
+    /// Verify an assertion made with `credential`; returns the new signature counter
+    pub fn finish_authentication(
+        &self,
+        state: &AuthenticationState,
+        response: &AuthenticationResponse,
+        credential: &PasskeyCredential,
+    ) -> Result<u32, MfaError> {
+        if b64(&response.raw_id)? != b64(&credential.id)? {
+            return Err(MfaError::UnknownCredential);
+        }
+        if state
+            .user_id
+            .as_ref()
+            .is_some_and(|user| *user != credential.user_id)
+        {
+            return Err(MfaError::UnknownCredential);
+        }
+        if let Some(handle) = &response.response.user_handle {
+            if b64(handle)? != credential.user_id.as_bytes() {
+                return Err(webauthn_error("user handle mismatch"));
+            }
+        }
+
+        let client_data = b64(&response.response.client_data_json)?;
+        self.check_client_data(&client_data, "webauthn.get", &state.challenge)?;
+
+        let auth_data = b64(&response.response.authenticator_data)?;
+        let parsed = self.check_auth_data(&auth_data)?;
+
+        let key: Cbor = ciborium::de::from_reader(&b64(&credential.public_key)?[..])
+            .map_err(|_| webauthn_error("malformed stored public key"))?;
+        let signed = [&auth_data[..], &Sha256::digest(&client_data)[..]].concat();
+        if !CoseKey::parse(&key)?.verify(&signed, &b64(&response.response.signature)?) {
+            return Err(webauthn_error("invalid signature"));
+        }
+
+        // Authenticators without a counter always report 0
+        if (parsed.sign_count != 0 || credential.sign_count != 0)
+            && parsed.sign_count <= credential.sign_count
+        {
+            return Err(MfaError::CounterRegression);
+        }
+        Ok(parsed.sign_count)
+    }
+
+    fn check_client_data(&self, json: &[u8], kind: &str, challenge: &str) -> Result<(), MfaError> {
+        let data: ClientData =
+            serde_json::from_slice(json).map_err(|_| webauthn_error("malformed client data"))?;
+        if data.kind != kind {
+            return Err(webauthn_error(format!("expected {}", kind)));
+        }
+        if !constant_eq(data.challenge.as_bytes(), challenge.as_bytes()) {
+            return Err(webauthn_error("challenge mismatch"));
+        }
+        if !self.origins.contains(&data.origin) {
+            return Err(webauthn_error(format!("unexpected origin {}", data.origin)));
+        }
+        Ok(())
+    }
+
+    fn check_auth_data(&self, auth_data: &[u8]) -> Result<AuthData, MfaError> {
+        // rpIdHash(32) | flags(1) | signCount(4)
+        if auth_data.len() < 37 {
+            return Err(webauthn_error("truncated authenticator data"));
+        }
+        if auth_data[..32] != Sha256::digest(self.rp_id.as_bytes())[..] {
+            return Err(webauthn_error("relying party id mismatch"));
+        }
+        let flags = auth_data[32];
+        if flags & FLAG_USER_PRESENT == 0 {
+            return Err(webauthn_error("user not present"));
+        }
+        if self.user_verification && flags & FLAG_USER_VERIFIED == 0 {
+            return Err(webauthn_error("user not verified"));
+        }
+        Ok(AuthData {
+            flags,
+            sign_count: u32::from_be_bytes([
+                auth_data[33],
+                auth_data[34],
+                auth_data[35],
+                auth_data[36],
+            ]),
+        })
+    }
+}
+
+struct AuthData {
+    flags: u8,
+    sign_count: u32,
+}
+
+/// Public key from a COSE_Key map (RFC 9053)
+enum CoseKey {
+    Es256 { point: Vec<u8> },
+    EdDsa { x: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CoseKey {
+    fn parse(key: &Cbor) -> Result<Self, MfaError> {
+        let param = |label: i64| -> Option<&Cbor> {
+            key.as_map()?
+                .iter()
+                .find(|(k, _)| {
+                    k.as_integer()
+                        .is_some_and(|i| i128::from(i) == label as i128)
+                })
+                .map(|(_, v)| v)
+        };
+        let int = |label| param(label).and_then(|v| v.as_integer()).map(i128::from);
+        let bytes = |label| {
+            param(label)
+                .and_then(|v| v.as_bytes())
+                .cloned()
+                .ok_or_else(|| webauthn_error("incomplete public key"))
+        };
+
+        // 1: kty, 3: alg, -1: crv / n, -2: x / e, -3: y
+        match (int(1), int(3)) {
+            (Some(2), Some(-7)) if int(-1) == Some(1) => {
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                Ok(CoseKey::Es256 {
+                    point: [&[4u8][..], &x, &y].concat(),
+                })
+            }
+            (Some(1), Some(-8)) if int(-1) == Some(6) => Ok(CoseKey::EdDsa { x: bytes(-2)? }),
+            (Some(3), Some(-257)) => Ok(CoseKey::Rs256 {
+                n: bytes(-1)?,
+                e: bytes(-2)?,
+            }),
+            _ => Err(webauthn_error("unsupported public key algorithm")),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        use ring::signature as sig;
+        match self {
+            CoseKey::Es256 { point } => {
+                sig::UnparsedPublicKey::new(&sig::ECDSA_P256_SHA256_ASN1, point)
+                    .verify(message, signature)
+                    .is_ok()
+            }
+            CoseKey::EdDsa { x } => sig::UnparsedPublicKey::new(&sig::ED25519, x)
+                .verify(message, signature)
+                .is_ok(),
+            CoseKey::Rs256 { n, e } => sig::RsaPublicKeyComponents { n, e }
+                .verify(&sig::RSA_PKCS1_2048_8192_SHA256, message, signature)
+                .is_ok(),
+        }
+    }
+}
+
+fn cbor_get<'a>(map: &'a Cbor, key: &str) -> Option<&'a Cbor> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn credential_descriptors(credentials: &[PasskeyCredential]) -> Vec<serde_json::Value> {
+    credentials
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "type": "public-key",
+                "id": c.id,
+                "transports": c.transports,
+            })
+        })
+        .collect()
+}
+
+fn random_challenge() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn b64(value: &str) -> Result<Vec<u8>, MfaError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| webauthn_error("invalid base64url"))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// STORAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Where second factors are persisted
+#[async_trait]
+pub trait MfaStore: Send + Sync {
+    async fn totp(&self, user_id: &str) -> Result<Option<TotpRecord>, MfaError>;
+
+    async fn save_totp(&self, user_id: &str, record: &TotpRecord) -> Result<(), MfaError>;
+
+    async fn delete_totp(&self, user_id: &str) -> Result<(), MfaError>;
+
+    /// Record `step` as used; false if it or a later step already was
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> Result<bool, MfaError>;
+
+    /// Replace all recovery code hashes of a user
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        hashes: &[String],
+    ) -> Result<(), MfaError>;
+
+    /// Remove `hash`; false if the user has no such code
+    async fn consume_recovery_code(&self, user_id: &str, hash: &str) -> Result<bool, MfaError>;
+
+    async fn recovery_codes_left(&self, user_id: &str) -> Result<usize, MfaError>;
+
+    async fn passkeys(&self, user_id: &str) -> Result<Vec<PasskeyCredential>, MfaError>;
+
+    async fn passkey(&self, id: &str) -> Result<Option<PasskeyCredential>, MfaError>;
+
+    async fn save_passkey(&self, credential: &PasskeyCredential) -> Result<(), MfaError>;
+
+    async fn delete_passkey(&self, id: &str) -> Result<(), MfaError>;
+
+    /// Store the counter and last use after a successful assertion, only if
+    /// the stored counter is still `previous`; false if another login with
+    /// the same credential got there first
+    async fn touch_passkey(
+        &self,
+        id: &str,
+        previous: u32,
+        sign_count: u32,
+        used_at: i64,
+    ) -> Result<bool, MfaError>;
+}
+
+#[derive(Default)]
+struct MemoryMfaState {
+    totp: HashMap<String, TotpRecord>,
+    recovery: HashMap<String, Vec<String>>,
+    passkeys: HashMap<String, PasskeyCredential>,
+}
+
+/// In-process store for tests and single-instance apps
+#[derive(Clone, Default)]
+pub struct MemoryMfaStore {
+    state: Arc<Mutex<MemoryMfaState>>,
+}
+
+impl MemoryMfaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MfaStore for MemoryMfaStore {
+    async fn totp(&self, user_id: &str) -> Result<Option<TotpRecord>, MfaError> {
+        Ok(self.state.lock().unwrap().totp.get(user_id).cloned())
+    }
+
+    async fn save_totp(&self, user_id: &str, record: &TotpRecord) -> Result<(), MfaError> {
+        self.state
+            .lock()
+            .unwrap()
+            .totp
+            .insert(user_id.to_string(), record.clone());
+        Ok(())
+    }
+
+    async fn delete_totp(&self, user_id: &str) -> Result<(), MfaError> {
+        self.state.lock().unwrap().totp.remove(user_id);
+        Ok(())
+    }
+
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> Result<bool, MfaError> {
+        let mut state = self.state.lock().unwrap();
+        match state.totp.get_mut(user_id) {
+            Some(record) if record.last_step.is_none_or(|last| last < step) => {
+                record.last_step = Some(step);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        hashes: &[String],
+    ) -> Result<(), MfaError> {
+        self.state
+            .lock()
+            .unwrap()
+            .recovery
+            .insert(user_id.to_string(), hashes.to_vec());
+        Ok(())
+    }
+
+    async fn consume_recovery_code(&self, user_id: &str, hash: &str) -> Result<bool, MfaError> {
+        let mut state = self.state.lock().unwrap();
+        let Some(codes) = state.recovery.get_mut(user_id) else {
+            return Ok(false);
+        };
+        let before = codes.len();
+        codes.retain(|c| c != hash);
+        Ok(codes.len() < before)
+    }
+
+    async fn recovery_codes_left(&self, user_id: &str) -> Result<usize, MfaError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .recovery
+            .get(user_id)
+            .map_or(0, Vec::len))
+    }
+
+    async fn passkeys(&self, user_id: &str) -> Result<Vec<PasskeyCredential>, MfaError> {
+        let state = self.state.lock().unwrap();
+        let mut keys: Vec<_> = state
+            .passkeys
+            .values()
+            .filter(|c| c.user_id == user_id)
+            .cloned()
+            .collect();
+        keys.sort_by_key(|c| c.created_at);
+        Ok(keys)
+    }
+
+    async fn passkey(&self, id: &str) -> Result<Option<PasskeyCredential>, MfaError> {
+        Ok(self.state.lock().unwrap().passkeys.get(id).cloned())
+    }
+
+    async fn save_passkey(&self, credential: &PasskeyCredential) -> Result<(), MfaError> {
+        self.state
+            .lock()
+            .unwrap()
+            .passkeys
+            .insert(credential.id.clone(), credential.clone());
+        Ok(())
+    }
+
+    async fn delete_passkey(&self, id: &str) -> Result<(), MfaError> {
+        self.state.lock().unwrap().passkeys.remove(id);
+        Ok(())
+    }
+
+    async fn touch_passkey(
+        &self,
+        id: &str,
+        previous: u32,
+        sign_count: u32,
+        used_at: i64,
+    ) -> Result<bool, MfaError> {
+        match self.state.lock().unwrap().passkeys.get_mut(id) {
+            Some(credential) if credential.sign_count == previous => {
+                credential.sign_count = sign_count;
+                credential.last_used_at = Some(used_at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// MFA store backed by a Photon `DatabasePool` (SQLite, Postgres or MySQL)
+///
+/// Uses the tables `mfa_totp`, `mfa_recovery_codes` and `mfa_passkeys`
+/// (prefix configurable). Call `migrate()` once at startup to create them.
+#[derive(Clone)]
+pub struct SqlMfaStore {
+    pool: DatabasePool,
+    prefix: String,
+}
+
+impl SqlMfaStore {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self {
+            pool,
+            prefix: "mfa_".to_string(),
+        }
+    }
+
+    /// Use a different table name prefix
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn table(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    fn ph(&self, i: usize) -> String {
+        self.pool.placeholder(i)
+    }
+
+    /// Create the MFA tables if they do not exist
+    pub async fn migrate(&self) -> Result<(), MfaError> {
+        let (totp, recovery, passkeys) = (
+            self.table("totp"),
+            self.table("recovery_codes"),
+            self.table("passkeys"),
+        );
+        let mut statements = vec![
+            format!(
+                "CREATE TABLE IF NOT EXISTS {totp} (user_id VARCHAR(128) PRIMARY KEY, \
+                 secret VARCHAR(128) NOT NULL, algorithm VARCHAR(8) NOT NULL, digits INTEGER NOT NULL, \
+                 step BIGINT NOT NULL, confirmed BOOLEAN NOT NULL, last_step BIGINT)"
+            ),
+            format!(
+                "CREATE TABLE IF NOT EXISTS {recovery} (user_id VARCHAR(128) NOT NULL, \
+                 code_hash VARCHAR(64) NOT NULL, PRIMARY KEY (user_id, code_hash))"
+            ),
+            format!(
+                "CREATE TABLE IF NOT EXISTS {passkeys} (id VARCHAR(512) PRIMARY KEY, \
+                 user_id VARCHAR(128) NOT NULL, public_key TEXT NOT NULL, sign_count BIGINT NOT NULL, \
+                 transports TEXT NOT NULL, name VARCHAR(255), created_at BIGINT NOT NULL, \
+                 last_used_at BIGINT)"
+            ),
+        ];
+        if self.pool.db_type() == DatabaseType::MySql {
+            statements.push(format!(
+                "CREATE INDEX idx_{passkeys}_user_id ON {passkeys} (user_id)"
+            ));
+        } else {
+            statements.push(format!(
+                "CREATE INDEX IF NOT EXISTS idx_{passkeys}_user_id ON {passkeys} (user_id)"
+            ));
+        }
+
+        let last = statements.len() - 1;
+        for (i, sql) in statements.into_iter().enumerate() {
+            let result = on_pool!(&self.pool, p => sqlx::query(&sql).execute(p).await.map(|r| r.rows_affected()));
+            match result {
+                Ok(_) => {}
+                // MySQL has no CREATE INDEX IF NOT EXISTS; a second run hits a duplicate
+                Err(_) if i == last && self.pool.db_type() == DatabaseType::MySql => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+type PasskeyRow = (
+    String,
+    String,
+    String,
+    i64,
+    String,
+    Option<String>,
+    i64,
+    Option<i64>,
+);
+
+fn passkey_from_row(row: PasskeyRow) -> PasskeyCredential {
+    let (id, user_id, public_key, sign_count, transports, name, created_at, last_used_at) = row;
+    PasskeyCredential {
+        id,
+        user_id,
+        public_key,
+        sign_count: sign_count as u32,
+        transports: serde_json::from_str(&transports).unwrap_or_default(),
+        name,
+        created_at,
+        last_used_at,
+    }
+}
+
+#[async_trait]
+impl MfaStore for SqlMfaStore {
+    async fn totp(&self, user_id: &str) -> Result<Option<TotpRecord>, MfaError> {
+        let sql = format!(
+            "SELECT secret, algorithm, digits, step, confirmed, last_step FROM {} WHERE user_id = {}",
+            self.table("totp"),
+            self.ph(1)
+        );
+        let row: Option<(String, String, i32, i64, bool, Option<i64>)> = on_pool!(&self.pool, p => {
+            sqlx::query_as(&sql).bind(user_id).fetch_optional(p).await
+        })?;
+        Ok(row.map(
+            |(secret, algorithm, digits, step, confirmed, last_step)| TotpRecord {
+                secret,
+                algorithm: TotpAlgorithm::parse(&algorithm),
+                digits: digits as u32,
+                step: step as u64,
+                confirmed,
+                last_step: last_step.map(|s| s as u64),
+            },
+        ))
+    }
+
+    async fn save_totp(&self, user_id: &str, record: &TotpRecord) -> Result<(), MfaError> {
+        let t = self.table("totp");
+        let values = (1..=7).map(|i| self.ph(i)).collect::<Vec<_>>().join(", ");
+        let sql = match self.pool.db_type() {
+            DatabaseType::MySql => format!(
+                "INSERT INTO {t} (user_id, secret, algorithm, digits, step, confirmed, last_step) \
+                 VALUES ({values}) ON DUPLICATE KEY UPDATE secret = VALUES(secret), \
+                 algorithm = VALUES(algorithm), digits = VALUES(digits), step = VALUES(step), \
+                 confirmed = VALUES(confirmed), last_step = VALUES(last_step)"
+            ),
+            _ => format!(
+                "INSERT INTO {t} (user_id, secret, algorithm, digits, step, confirmed, last_step) \
+                 VALUES ({values}) ON CONFLICT (user_id) DO UPDATE SET secret = excluded.secret, \
+                 algorithm = excluded.algorithm, digits = excluded.digits, step = excluded.step, \
+                 confirmed = excluded.confirmed, last_step = excluded.last_step"
+            ),
+        };
+        let last_step = record.last_step.map(|s| s as i64);
+        on_pool!(&self.pool, p => {
+            sqlx::query(&sql)
+                .bind(user_id)
+                .bind(&record.secret)
+                .bind(record.algorithm.name())
+                .bind(record.digits as i32)
+                .bind(record.step as i64)
+                .bind(record.confirmed)
+                .bind(last_step)
+                .execute(p)
+                .await
+                .map(|r| r.rows_affected())
+        })?;
+        Ok(())
+    }
+
+    async fn delete_totp(&self, user_id: &str) -> Result<(), MfaError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE user_id = {}",
+            self.table("totp"),
+            self.ph(1)
+        );
+        on_pool!(&self.pool, p => sqlx::query(&sql).bind(user_id).execute(p).await.map(|r| r.rows_affected()))?;
+        Ok(())
+    }
+
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> Result<bool, MfaError> {
+        // Conditional update so two concurrent logins cannot both use one code
+        let sql = format!(
+            "UPDATE {} SET last_step = {} WHERE user_id = {} AND (last_step IS NULL OR last_step < {})",
+            self.table("totp"),
+            self.ph(1),
+            self.ph(2),
+            self.ph(3)
+        );
+        let step = step as i64;
+        let updated = on_pool!(&self.pool, p => {
+            sqlx::query(&sql).bind(step).bind(user_id).bind(step).execute(p).await.map(|r| r.rows_affected())
+        })?;
+        Ok(updated == 1)
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        hashes: &[String],
+    ) -> Result<(), MfaError> {
+        let t = self.table("recovery_codes");
+        let delete = format!("DELETE FROM {t} WHERE user_id = {}", self.ph(1));
+        let insert = format!(
+            "INSERT INTO {t} (user_id, code_hash) VALUES ({}, {})",
+            self.ph(1),
+            self.ph(2)
+        );
+        on_pool!(&self.pool, p => {
+            async {
+                let mut tx = p.begin().await?;
+                sqlx::query(&delete).bind(user_id).execute(&mut *tx).await?;
+                for hash in hashes {
+                    sqlx::query(&insert).bind(user_id).bind(hash).execute(&mut *tx).await?;
+                }
+                tx.commit().await
+            }
+            .await
+        })?;
+        Ok(())
+    }
+
+    async fn consume_recovery_code(&self, user_id: &str, hash: &str) -> Result<bool, MfaError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE user_id = {} AND code_hash = {}",
+            self.table("recovery_codes"),
+            self.ph(1),
+            self.ph(2)
+        );
+        let deleted = on_pool!(&self.pool, p => {
+            sqlx::query(&sql).bind(user_id).bind(hash).execute(p).await.map(|r| r.rows_affected())
+        })?;
+        Ok(deleted == 1)
+    }
+
+    async fn recovery_codes_left(&self, user_id: &str) -> Result<usize, MfaError> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE user_id = {}",
+            self.table("recovery_codes"),
+            self.ph(1)
+        );
+        let (count,): (i64,) = on_pool!(&self.pool, p => {
+            sqlx::query_as(&sql).bind(user_id).fetch_one(p).await
+        })?;
+        Ok(count as usize)
+    }
+
+    async fn passkeys(&self, user_id: &str) -> Result<Vec<PasskeyCredential>, MfaError> {
+        let sql = format!(
+            "SELECT id, user_id, public_key, sign_count, transports, name, created_at, last_used_at \
+             FROM {} WHERE user_id = {} ORDER BY created_at",
+            self.table("passkeys"),
+            self.ph(1)
+        );
+        let rows: Vec<PasskeyRow> = on_pool!(&self.pool, p => {
+            sqlx::query_as(&sql).bind(user_id).fetch_all(p).await
+        })?;
+        Ok(rows.into_iter().map(passkey_from_row).collect())
+    }
+
+    async fn passkey(&self, id: &str) -> Result<Option<PasskeyCredential>, MfaError> {
+        let sql = format!(
+            "SELECT id, user_id, public_key, sign_count, transports, name, created_at, last_used_at \
+             FROM {} WHERE id = {}",
+            self.table("passkeys"),
+            self.ph(1)
+        );
+        let row: Option<PasskeyRow> = on_pool!(&self.pool, p => {
+            sqlx::query_as(&sql).bind(id).fetch_optional(p).await
+        })?;
+        Ok(row.map(passkey_from_row))
+    }
+
+    async fn save_passkey(&self, credential: &PasskeyCredential) -> Result<(), MfaError> {
+        let values = (1..=8).map(|i| self.ph(i)).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "INSERT INTO {} (id, user_id, public_key, sign_count, transports, name, created_at, last_used_at) \
+             VALUES ({values})",
+            self.table("passkeys")
+        );
+        let transports = serde_json::to_string(&credential.transports).unwrap_or_default();
+        on_pool!(&self.pool, p => {
+            sqlx::query(&sql)
+                .bind(&credential.id)
+                .bind(&credential.user_id)
+                .bind(&credential.public_key)
+                .bind(credential.sign_count as i64)
+                .bind(&transports)
+                .bind(&credential.name)
+                .bind(credential.created_at)
+                .bind(credential.last_used_at)
+                .execute(p)
+                .await
+                .map(|r| r.rows_affected())
+        })?;
+        Ok(())
+    }
+
+    async fn delete_passkey(&self, id: &str) -> Result<(), MfaError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE id = {}",
+            self.table("passkeys"),
+            self.ph(1)
+        );
+        on_pool!(&self.pool, p => sqlx::query(&sql).bind(id).execute(p).await.map(|r| r.rows_affected()))?;
+        Ok(())
+    }
+
+    async fn touch_passkey(
+        &self,
+        id: &str,
+        previous: u32,
+        sign_count: u32,
+        used_at: i64,
+    ) -> Result<bool, MfaError> {
+        let sql = format!(
+            "UPDATE {} SET sign_count = {}, last_used_at = {} WHERE id = {} AND sign_count = {}",
+            self.table("passkeys"),
+            self.ph(1),
+            self.ph(2),
+            self.ph(3),
+            self.ph(4)
+        );
+        let updated = on_pool!(&self.pool, p => {
+            sqlx::query(&sql)
+                .bind(sign_count as i64)
+                .bind(used_at)
+                .bind(id)
+                .bind(previous as i64)
+                .execute(p)
+                .await
+                .map(|r| r.rows_affected())
+        })?;
+        Ok(updated == 1)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MFA SERVICE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Second factors a user has set up
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MfaMethods {
+    pub totp: bool,
+    pub passkeys: usize,
+    pub recovery_codes: usize,
+}
+
+impl MfaMethods {
+    /// Whether login should ask for a second factor
+    pub fn enabled(&self) -> bool {
+        self.totp || self.passkeys > 0
+    }
+}
+
+/// Enrollment and verification of second factors over an `MfaStore`
+#[derive(Clone)]
+pub struct Mfa {
+    store: Arc<dyn MfaStore>,
+    issuer: String,
+    webauthn: Option<WebAuthn>,
+    recovery_code_count: usize,
+}
+
+impl Mfa {
+    /// `issuer` is the name authenticator apps show next to the code
+    pub fn new(store: impl MfaStore + 'static, issuer: &str) -> Self {
+        Self {
+            store: Arc::new(store),
+            issuer: issuer.to_string(),
+            webauthn: None,
+            recovery_code_count: 10,
+        }
+    }
+
+    /// Enable passkeys
+    pub fn with_webauthn(mut self, webauthn: WebAuthn) -> Self {
+        self.webauthn = Some(webauthn);
+        self
+    }
+
+    /// Number of codes `generate_recovery_codes` creates
+    pub fn recovery_code_count(mut self, count: usize) -> Self {
+        self.recovery_code_count = count;
+        self
+    }
+
+    /// The underlying store
+    pub fn store(&self) -> &dyn MfaStore {
+        self.store.as_ref()
+    }
+
+    /// What a user has set up
+    pub async fn methods(&self, user_id: &str) -> Result<MfaMethods, MfaError> {
+        Ok(MfaMethods {
+            totp: self
+                .store
+                .totp(user_id)
+                .await?
+                .is_some_and(|record| record.confirmed),
+            passkeys: self.store.passkeys(user_id).await?.len(),
+            recovery_codes: self.store.recovery_codes_left(user_id).await?,
+        })
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // TOTP
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Start TOTP enrollment with a fresh secret; replaces any previous one
+    ///
+    /// The secret is inactive until `confirm_totp` succeeds. `account` is the
+    /// label shown in the authenticator app, usually the email address.
+    pub async fn enroll_totp(&self, user_id: &str, account: &str) -> Result<Totp, MfaError> {
+        let totp = Totp::generate(&self.issuer, account);
+        let record = TotpRecord {
+            secret: totp.secret_base32(),
+            algorithm: totp.algorithm,
+            digits: totp.digits,
+            step: totp.step,
+            confirmed: false,
+            last_step: None,
+        };
+        self.store.save_totp(user_id, &record).await?;
+        Ok(totp)
+    }
+
+    /// Activate the enrolled secret once the user enters a valid code
+    pub async fn confirm_totp(&self, user_id: &str, code: &str) -> Result<(), MfaError> {
+        let mut record = self
+            .store
+            .totp(user_id)
+            .await?
+            .ok_or(MfaError::NotEnrolled("TOTP"))?;
+        let step = record
+            .totp(&self.issuer, user_id)?
+            .verify(code, record.last_step)?;
+        record.confirmed = true;
+        record.last_step = Some(step);
+        self.store.save_totp(user_id, &record).await
+    }
+
+    /// Check a login code; each code is accepted once
+    pub async fn verify_totp(&self, user_id: &str, code: &str) -> Result<(), MfaError> {
+        let record = self
+            .store
+            .totp(user_id)
+            .await?
+            .filter(|record| record.confirmed)
+            .ok_or(MfaError::NotEnrolled("TOTP"))?;
+        let step = record
+            .totp(&self.issuer, user_id)?
+            .verify(code, record.last_step)?;
+        if !self.store.advance_totp_step(user_id, step).await? {
+            return Err(MfaError::CodeReused);
+        }
+        Ok(())
+    }
+
+    /// Remove TOTP
+    pub async fn disable_totp(&self, user_id: &str) -> Result<(), MfaError> {
+        self.store.delete_totp(user_id).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Recovery codes
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Create a new set of recovery codes, invalidating the old ones
+    ///
+    /// Only hashes are stored; show the returned codes to the user once.
+    pub async fn generate_recovery_codes(&self, user_id: &str) -> Result<Vec<String>, MfaError> {
+        let codes = generate_recovery_codes(self.recovery_code_count);
+        let hashes: Vec<String> = codes.iter().map(|c| hash_recovery_code(c)).collect();
+        self.store.replace_recovery_codes(user_id, &hashes).await?;
+        Ok(codes)
+    }
+
+    /// Accept and burn a recovery code
+    pub async fn use_recovery_code(&self, user_id: &str, code: &str) -> Result<(), MfaError> {
+        if self
+            .store
+            .consume_recovery_code(user_id, &hash_recovery_code(code))
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(MfaError::InvalidCode)
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Passkeys
+    // ─────────────────────────────────────────────────────────────────────────
+
+    fn webauthn(&self) -> Result<&WebAuthn, MfaError> {
+        self.webauthn.as_ref().ok_or(MfaError::WebAuthnDisabled)
+    }
+
+    /// Begin registering a passkey; send the options to the browser
+    pub async fn start_passkey_registration(
+        &self,
+        user_id: &str,
+        user_name: &str,
+        display_name: &str,
+    ) -> Result<(serde_json::Value, RegistrationState), MfaError> {
+        let existing = self.store.passkeys(user_id).await?;
+        Ok(self
+            .webauthn()?
+            .start_registration(user_id, user_name, display_name, &existing))
+    }
+
+    /// Verify and save a new passkey
+    pub async fn finish_passkey_registration(
+        &self,
+        state: &RegistrationState,
+        response: &RegistrationResponse,
+        name: Option<&str>,
+    ) -> Result<PasskeyCredential, MfaError> {
+        let mut credential = self.webauthn()?.finish_registration(state, response)?;
+        if self.store.passkey(&credential.id).await?.is_some() {
+            return Err(webauthn_error("credential is already registered"));
+        }
+        credential.name = name.map(String::from);
+        self.store.save_passkey(&credential).await?;
+        Ok(credential)
+    }
+
+    /// Begin a passkey login; `None` allows usernameless login
+    pub async fn start_passkey_authentication(
+        &self,
+        user_id: Option<&str>,
+    ) -> Result<(serde_json::Value, AuthenticationState), MfaError> {
+        let credentials = match user_id {
+            Some(user_id) => self.store.passkeys(user_id).await?,
+            None => Vec::new(),
+        };
+        Ok(self.webauthn()?.start_authentication(user_id, &credentials))
+    }
+
+    /// Verify a passkey login; the returned credential names the user
+    pub async fn finish_passkey_authentication(
+        &self,
+        state: &AuthenticationState,
+        response: &AuthenticationResponse,
+    ) -> Result<PasskeyCredential, MfaError> {
+        let mut credential = self
+            .store
+            .passkey(&response.id)
+            .await?
+            .ok_or(MfaError::UnknownCredential)?;
+        let sign_count = self
+            .webauthn()?
+            .finish_authentication(state, response, &credential)?;
+        let now = unix_now();
+        // A concurrent login with the same assertion (or a clone) moved the
+        // counter since it was read
+        if !self
+            .store
+            .touch_passkey(&credential.id, credential.sign_count, sign_count, now)
+            .await?
+        {
+            return Err(MfaError::CounterRegression);
+        }
+        credential.sign_count = sign_count;
+        credential.last_used_at = Some(now);
+        Ok(credential)
+    }
+
+    /// Remove a passkey belonging to `user_id`
+    pub async fn remove_passkey(&self, user_id: &str, id: &str) -> Result<(), MfaError> {
+        match self.store.passkey(id).await? {
+            Some(credential) if credential.user_id == user_id => {
+                self.store.delete_passkey(id).await
+            }
+            _ => Err(MfaError::UnknownCredential),
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn constant_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // TOTP TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
+    fn rfc_totp(secret: &[u8], algorithm: TotpAlgorithm) -> Totp {
+        Totp::from_secret(secret.to_vec(), "", "test")
+            .algorithm(algorithm)
+            .digits(8)
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let sha1 = rfc_totp(b"12345678901234567890", TotpAlgorithm::Sha1);
+        let sha256 = rfc_totp(b"12345678901234567890123456789012", TotpAlgorithm::Sha256);
+        let sha512 = rfc_totp(
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+            TotpAlgorithm::Sha512,
+        );
+
+        assert_eq!(sha1.code_at(59), "94287082");
+        assert_eq!(sha1.code_at(1111111109), "07081804");
+        assert_eq!(sha1.code_at(20000000000), "65353130");
+        assert_eq!(sha256.code_at(59), "46119246");
+        assert_eq!(sha256.code_at(1234567890), "91819424");
+        assert_eq!(sha512.code_at(59), "90693936");
+        assert_eq!(sha512.code_at(2000000000), "38618901");
+    }
+
+    #[test]
+    fn test_totp_drift_window_and_replay() {
+        let totp = Totp::generate("Acme", "ada@example.com");
+        let now = 1_700_000_000u64;
+        let step = now / 30;
+
+        assert_eq!(totp.verify_at(&totp.code_at(now), None, now).unwrap(), step);
+        // One step late or early is accepted, two is not
+        assert!(totp.verify_at(&totp.code_at(now - 30), None, now).is_ok());
+        assert!(totp.verify_at(&totp.code_at(now + 30), None, now).is_ok());
+        assert!(matches!(
+            totp.verify_at(&totp.code_at(now - 60), None, now),
+            Err(MfaError::InvalidCode)
+        ));
+
+        // A code from an already used step is rejected
+        assert!(matches!(
+            totp.verify_at(&totp.code_at(now), Some(step), now),
+            Err(MfaError::CodeReused)
+        ));
+        assert!(totp.verify_at("12345", None, now).is_err());
+        assert!(totp.verify_at("abcdef", None, now).is_err());
+    }
+
+    #[test]
+    fn test_totp_provisioning_uri() {
+        let totp = Totp::generate("Acme Corp", "ada@example.com");
+        let uri = totp.provisioning_uri();
+
+        assert!(uri.starts_with("otpauth://totp/Acme%20Corp:ada%40example.com?secret="));
+        assert!(uri.contains(&format!("secret={}", totp.secret_base32())));
+        assert!(uri.contains("algorithm=SHA1&digits=6&period=30"));
+        assert!(uri.ends_with("&issuer=Acme%20Corp"));
+
+        let restored =
+            Totp::from_base32(&totp.secret_base32().to_lowercase(), "Acme Corp", "ada").unwrap();
+        assert_eq!(restored.code_at(1_700_000_000), totp.code_at(1_700_000_000));
+        assert!(Totp::from_base32("not base32!", "", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mfa_totp_enrollment() {
+        let mfa = Mfa::new(MemoryMfaStore::new(), "Acme");
+        let totp = mfa.enroll_totp("user_1", "ada@example.com").await.unwrap();
+
+        // Not usable before confirmation
+        assert!(matches!(
+            mfa.verify_totp("user_1", &totp.now()).await,
+            Err(MfaError::NotEnrolled(_))
+        ));
+        assert!(!mfa.methods("user_1").await.unwrap().enabled());
+
+        mfa.confirm_totp("user_1", &totp.now()).await.unwrap();
+        assert!(mfa.methods("user_1").await.unwrap().totp);
+
+        // The confirmation code cannot be replayed for login
+        assert!(matches!(
+            mfa.verify_totp("user_1", &totp.now()).await,
+            Err(MfaError::CodeReused)
+        ));
+        let next = totp.code_at(unix_now() as u64 + 30);
+        mfa.verify_totp("user_1", &next).await.unwrap();
+        assert!(mfa.verify_totp("user_1", &next).await.is_err());
+
+        mfa.disable_totp("user_1").await.unwrap();
+        assert!(!mfa.methods("user_1").await.unwrap().totp);
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // RECOVERY CODE TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_recovery_codes_single_use() {
+        let store = MemoryMfaStore::new();
+        let mfa = Mfa::new(store.clone(), "Acme");
+        let codes = mfa.generate_recovery_codes("user_1").await.unwrap();
+
+        assert_eq!(codes.len(), 10);
+        assert_eq!(codes[0].len(), 19);
+        // Only hashes are stored
+        let stored = store.state.lock().unwrap().recovery["user_1"].clone();
+        assert!(!stored.contains(&codes[0]));
+
+        // Case and separators do not matter
+        let typed = codes[0].to_uppercase().replace('-', " ");
+        mfa.use_recovery_code("user_1", &typed).await.unwrap();
+        assert!(mfa.use_recovery_code("user_1", &codes[0]).await.is_err());
+        assert!(mfa.use_recovery_code("user_2", &codes[1]).await.is_err());
+        assert_eq!(mfa.methods("user_1").await.unwrap().recovery_codes, 9);
+
+        // Regenerating invalidates the old set
+        mfa.generate_recovery_codes("user_1").await.unwrap();
+        assert!(mfa.use_recovery_code("user_1", &codes[1]).await.is_err());
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // WEBAUTHN TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
+    const ORIGIN: &str = "https://acme.test";
+
+    /// Software authenticator with a P-256 key
+    struct TestAuthenticator {
+        pair: ring::signature::EcdsaKeyPair,
+        credential_id: Vec<u8>,
+        counter: u32,
+    }
+
+    impl TestAuthenticator {
+        fn new() -> Self {
+            use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+            let rng = ring::rand::SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+            Self {
+                pair: EcdsaKeyPair::from_pkcs8(
+                    &ECDSA_P256_SHA256_ASN1_SIGNING,
+                    pkcs8.as_ref(),
+                    &rng,
+                )
+                .unwrap(),
+                credential_id: rand::thread_rng().gen::<[u8; 16]>().to_vec(),
+                counter: 0,
+            }
+        }
+
+        fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+            serde_json::json!({ "type": kind, "challenge": challenge, "origin": origin })
+                .to_string()
+                .into_bytes()
+        }
+
+        fn auth_data(&self, rp_id: &str, flags: u8) -> Vec<u8> {
+            let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+            data.push(flags);
+            data.extend_from_slice(&self.counter.to_be_bytes());
+            data
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            use ring::signature::KeyPair;
+            let point = self.pair.public_key().as_ref();
+            let int = |i: i64| Cbor::Integer(i.into());
+            let key = Cbor::Map(vec![
+                (int(1), int(2)),
+                (int(3), int(-7)),
+                (int(-1), int(1)),
+                (int(-2), Cbor::Bytes(point[1..33].to_vec())),
+                (int(-3), Cbor::Bytes(point[33..].to_vec())),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&key, &mut bytes).unwrap();
+            bytes
+        }
+
+        fn register(&mut self, options: &serde_json::Value, origin: &str) -> RegistrationResponse {
+            let challenge = options["challenge"].as_str().unwrap();
+            let rp_id = options["rp"]["id"].as_str().unwrap();
+
+            let mut auth_data = self.auth_data(rp_id, FLAG_USER_PRESENT | FLAG_ATTESTED_DATA);
+            auth_data.extend_from_slice(&[0u8; 16]);
+            auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            auth_data.extend_from_slice(&self.credential_id);
+            auth_data.extend_from_slice(&self.cose_key());
+
+            let attestation = Cbor::Map(vec![
+                (Cbor::Text("fmt".into()), Cbor::Text("none".into())),
+                (Cbor::Text("attStmt".into()), Cbor::Map(vec![])),
+                (Cbor::Text("authData".into()), Cbor::Bytes(auth_data)),
+            ]);
+            let mut attestation_object = Vec::new();
+            ciborium::ser::into_writer(&attestation, &mut attestation_object).unwrap();
+
+            let id = URL_SAFE_NO_PAD.encode(&self.credential_id);
+            RegistrationResponse {
+                id: id.clone(),
+                raw_id: id,
+                response: AttestationResponse {
+                    client_data_json: URL_SAFE_NO_PAD.encode(Self::client_data(
+                        "webauthn.create",
+                        challenge,
+                        origin,
+                    )),
+                    attestation_object: URL_SAFE_NO_PAD.encode(attestation_object),
+                    transports: vec!["internal".into()],
+                },
+            }
+        }
+
+        fn assert(&mut self, options: &serde_json::Value, user_id: &str) -> AuthenticationResponse {
+            self.counter += 1;
+            let challenge = options["challenge"].as_str().unwrap();
+            let rp_id = options["rpId"].as_str().unwrap();
+            let client_data = Self::client_data("webauthn.get", challenge, ORIGIN);
+            let auth_data = self.auth_data(rp_id, FLAG_USER_PRESENT | FLAG_USER_VERIFIED);
+
+            let signed = [&auth_data[..], &Sha256::digest(&client_data)[..]].concat();
+            let rng = ring::rand::SystemRandom::new();
+            let signature = self.pair.sign(&rng, &signed).unwrap();
+
+            let id = URL_SAFE_NO_PAD.encode(&self.credential_id);
+            AuthenticationResponse {
+                id: id.clone(),
+                raw_id: id,
+                response: AssertionResponse {
+                    client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                    authenticator_data: URL_SAFE_NO_PAD.encode(auth_data),
+                    signature: URL_SAFE_NO_PAD.encode(signature.as_ref()),
+                    user_handle: Some(URL_SAFE_NO_PAD.encode(user_id)),
+                },
+            }
+        }
+    }
+
+    fn passkey_mfa() -> Mfa {
+        Mfa::new(MemoryMfaStore::new(), "Acme").with_webauthn(WebAuthn::new(
+            "acme.test",
+            "Acme",
+            ORIGIN,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_passkey_registration_and_login() {
+        let mfa = passkey_mfa();
+        let mut authenticator = TestAuthenticator::new();
+
+        let (options, state) = mfa
+            .start_passkey_registration("user_1", "ada", "Ada Lovelace")
+            .await
+            .unwrap();
+        assert_eq!(options["rp"]["id"], "acme.test");
+        assert_eq!(options["pubKeyCredParams"][0]["alg"], -7);
+
+        let response = authenticator.register(&options, ORIGIN);
+        let credential = mfa
+            .finish_passkey_registration(&state, &response, Some("Laptop"))
+            .await
+            .unwrap();
+        assert_eq!(credential.user_id, "user_1");
+        assert_eq!(credential.name.as_deref(), Some("Laptop"));
+        assert_eq!(mfa.methods("user_1").await.unwrap().passkeys, 1);
+
+        // Registering the same authenticator twice is refused
+        assert!(mfa
+            .finish_passkey_registration(&state, &response, None)
+            .await
+            .is_err());
+
+        let (options, state) = mfa
+            .start_passkey_authentication(Some("user_1"))
+            .await
+            .unwrap();
+        assert_eq!(options["allowCredentials"][0]["id"], credential.id);
+        let assertion = authenticator.assert(&options, "user_1");
+        let used = mfa
+            .finish_passkey_authentication(&state, &assertion)
+            .await
+            .unwrap();
+        assert_eq!(used.user_id, "user_1");
+        assert_eq!(used.sign_count, 1);
+
+        // Replaying the same assertion fails on the counter
+        assert!(matches!(
+            mfa.finish_passkey_authentication(&state, &assertion).await,
+            Err(MfaError::CounterRegression)
+        ));
+
+        // Usernameless login finds the user from the credential
+        let (options, state) = mfa.start_passkey_authentication(None).await.unwrap();
+        assert_eq!(options["allowCredentials"], serde_json::json!([]));
+        let assertion = authenticator.assert(&options, "user_1");
+        let used = mfa
+            .finish_passkey_authentication(&state, &assertion)
+            .await
+            .unwrap();
+        assert_eq!(used.user_id, "user_1");
+    }
+
+    #[tokio::test]
+    async fn test_passkey_rejects_tampering() {
+        let mfa = passkey_mfa();
+        let mut authenticator = TestAuthenticator::new();
+
+        let (options, state) = mfa
+            .start_passkey_registration("user_1", "ada", "Ada")
+            .await
+            .unwrap();
+
+        // Phishing origin
+        let response = authenticator.register(&options, "https://acme.evil");
+        let err = mfa
+            .finish_passkey_registration(&state, &response, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("origin"));
+
+        // Response to another challenge
+        let (other, _) = mfa
+            .start_passkey_registration("user_1", "ada", "Ada")
+            .await
+            .unwrap();
+        let response = authenticator.register(&other, ORIGIN);
+        let err = mfa
+            .finish_passkey_registration(&state, &response, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("challenge"));
+
+        let response = authenticator.register(&options, ORIGIN);
+        mfa.finish_passkey_registration(&state, &response, None)
+            .await
+            .unwrap();
+
+        // Forged signature
+        let (options, state) = mfa
+            .start_passkey_authentication(Some("user_1"))
+            .await
+            .unwrap();
+        let mut assertion = authenticator.assert(&options, "user_1");
+        assertion.response.signature =
+            URL_SAFE_NO_PAD.encode([0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01]);
+        assert!(mfa
+            .finish_passkey_authentication(&state, &assertion)
+            .await
+            .is_err());
+
+        // Credential of another user
+        let state = AuthenticationState {
+            user_id: Some("user_2".into()),
+            ..state
+        };
+        let assertion = authenticator.assert(&options, "user_1");
+        assert!(matches!(
+            mfa.finish_passkey_authentication(&state, &assertion).await,
+            Err(MfaError::UnknownCredential)
+        ));
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // SQL STORE TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_sql_mfa_store() {
+        let pool = DatabasePool::connect_with_max("sqlite::memory:", 1)
+            .await
+            .unwrap();
+        let store = SqlMfaStore::new(pool);
+        store.migrate().await.unwrap();
+        store.migrate().await.unwrap();
+
+        let record = TotpRecord {
+            secret: Totp::generate("", "").secret_base32(),
+            algorithm: TotpAlgorithm::Sha256,
+            digits: 8,
+            step: 30,
+            confirmed: true,
+            last_step: None,
+        };
+        store.save_totp("user_1", &record).await.unwrap();
+        assert_eq!(store.totp("user_1").await.unwrap(), Some(record));
+        assert!(store.advance_totp_step("user_1", 10).await.unwrap());
+        assert!(!store.advance_totp_step("user_1", 10).await.unwrap());
+        assert!(store.advance_totp_step("user_1", 11).await.unwrap());
+        assert_eq!(
+            store.totp("user_1").await.unwrap().unwrap().last_step,
+            Some(11)
+        );
+
+        let hashes = vec![hash_recovery_code("a"), hash_recovery_code("b")];
+        store
+            .replace_recovery_codes("user_1", &hashes)
+            .await
+            .unwrap();
+        assert!(store
+            .consume_recovery_code("user_1", &hashes[0])
+            .await
+            .unwrap());
+        assert!(!store
+            .consume_recovery_code("user_1", &hashes[0])
+            .await
+            .unwrap());
+        assert_eq!(store.recovery_codes_left("user_1").await.unwrap(), 1);
+
+        let credential = PasskeyCredential {
+            id: "cred-1".into(),
+            user_id: "user_1".into(),
+            public_key: "key".into(),
+            sign_count: 0,
+            transports: vec!["usb".into(), "nfc".into()],
+            name: Some("YubiKey".into()),
+            created_at: 1,
+            last_used_at: None,
+        };
+        store.save_passkey(&credential).await.unwrap();
+        assert!(store.touch_passkey("cred-1", 0, 5, 100).await.unwrap());
+        // A second login that read the old counter loses
+        assert!(!store.touch_passkey("cred-1", 0, 6, 101).await.unwrap());
+        assert!(!store.touch_passkey("missing", 0, 1, 101).await.unwrap());
+        let loaded = store.passkey("cred-1").await.unwrap().unwrap();
+        assert_eq!(loaded.sign_count, 5);
+        assert_eq!(loaded.last_used_at, Some(100));
+        assert_eq!(loaded.transports, credential.transports);
+        assert_eq!(store.passkeys("user_1").await.unwrap().len(), 1);
+
+        store.delete_passkey("cred-1").await.unwrap();
+        store.delete_totp("user_1").await.unwrap();
+        assert!(store.passkeys("user_1").await.unwrap().is_empty());
+        assert!(store.totp("user_1").await.unwrap().is_none());
+    }
+}
@@ -227,22 +227,11 @@ pub fn rate_limit_middleware(
     move |request: NucleusRequest, next: NucleusNext| {
         let limiter = limiter.clone();
         Box::pin(async move {
-            // Extract client IP from request
-            let client_ip = request
-                .headers()
-                .get("X-Forwarded-For")
-                .and_then(|h| h.to_str().ok())
-                .map(|s| s.split(',').next().unwrap_or("unknown"))
-                .unwrap_or("unknown")
-                .to_string();
-
-            let result = limiter.check(&client_ip);
+            let key = limiter.key_for(&request);
+            let result = limiter.check(&key).await;
 
             if !result.allowed {
-                warn!(
-                    client_ip = %client_ip,
-                    "Rate limit exceeded"
-                );
+                warn!(key = %key, "Rate limit exceeded");
 
                 let mut response = Response::builder()
                     .status(StatusCode::TOO_MANY_REQUESTS)
@@ -283,4 +272,28 @@ mod tests {
             .contains(&"https://example.com".to_string()));
         assert!(config.allow_credentials);
     }
+
+    #[tokio::test]
+    async fn test_rate_limit_middleware_returns_429() {
+        use crate::fortress::RateLimitConfig;
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 1,
+            ..Default::default()
+        });
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(rate_limit_middleware(limiter)));
+
+        let request = || Request::get("/").body(Body::empty()).unwrap();
+        let first = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()["X-RateLimit-Remaining"], "0");
+
+        let second = app.oneshot(request()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(second.headers().contains_key("Retry-After"));
+    }
 }
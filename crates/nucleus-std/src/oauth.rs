@@ -9,6 +9,10 @@
 //! - Apple
 //! - Facebook
 //! - Microsoft
+//! - Custom (any OAuth2 / OpenID Connect server, e.g. Keycloak or Authentik)
+//!
+//! Every flow uses PKCE (S256). For OpenID Connect providers the `id_token`
+//! is verified against the provider's JWKS, including nonce and audience.
 //!
 //! # Example
 //!
@@ -18,15 +22,30 @@
 //! let config = OAuthConfig::from_env();
 //! let oauth = OAuth::new(config);
 //!
-//! // Generate login URL
-//! let (url, state) = oauth.authorize_url(OAuthProvider::Google);
+//! // Generate login URL; keep `request` in the session until the callback
+//! let request = oauth.authorize_url(OAuthProvider::Google).await?;
 //!
 //! // Handle callback
-//! let user = oauth.exchange_code(OAuthProvider::Google, &code, &state).await?;
+//! let user = oauth.exchange_code(OAuthProvider::Google, &code, &state, &request).await?;
 //! ```
 
+use crate::tokens::Audience;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Clock skew tolerated when checking `exp`, `nbf` and `iat`
+const ID_TOKEN_LEEWAY: i64 = 60;
+
+/// How long fetched signing keys are trusted before refetching
+const JWKS_TTL: Duration = Duration::from_secs(3600);
+
+/// Minimum gap between refetches triggered by an unknown `kid`
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 // ═══════════════════════════════════════════════════════════════════════════
 // PROVIDERS
@@ -42,10 +61,12 @@ pub enum OAuthProvider {
     Apple,
     Facebook,
     Microsoft,
+    /// Configured entirely from `OAuthConfig::custom`
+    Custom,
 }
 
 impl OAuthProvider {
-    /// Get the authorization endpoint URL
+    /// Get the authorization endpoint URL (empty for `Custom`)
     pub fn auth_url(&self) -> &'static str {
         match self {
             OAuthProvider::Google => "https://accounts.google.com/o/oauth2/v2/auth",
@@ -56,10 +77,11 @@ impl OAuthProvider {
             OAuthProvider::Microsoft => {
                 "https://login.microsoftonline.com/common/oauth2/v2.0/authorize"
             }
+            OAuthProvider::Custom => "",
         }
     }
 
-    /// Get the token exchange endpoint URL
+    /// Get the token exchange endpoint URL (empty for `Custom`)
     pub fn token_url(&self) -> &'static str {
         match self {
             OAuthProvider::Google => "https://oauth2.googleapis.com/token",
@@ -70,6 +92,7 @@ impl OAuthProvider {
             OAuthProvider::Microsoft => {
                 "https://login.microsoftonline.com/common/oauth2/v2.0/token"
             }
+            OAuthProvider::Custom => "",
         }
     }
 
@@ -82,6 +105,32 @@ impl OAuthProvider {
             OAuthProvider::Apple => "", // Apple returns user info in ID token
             OAuthProvider::Facebook => "https://graph.facebook.com/me?fields=id,name,email,picture",
             OAuthProvider::Microsoft => "https://graph.microsoft.com/v1.0/me",
+            OAuthProvider::Custom => "",
+        }
+    }
+
+    /// OpenID Connect issuer, for providers that return an `id_token`
+    ///
+    /// Microsoft's multi-tenant issuer contains a `{tenantid}` placeholder that
+    /// is filled from the token's `tid` claim.
+    pub fn issuer(&self) -> Option<&'static str> {
+        match self {
+            OAuthProvider::Google => Some("https://accounts.google.com"),
+            OAuthProvider::Apple => Some("https://appleid.apple.com"),
+            OAuthProvider::Microsoft => Some("https://login.microsoftonline.com/{tenantid}/v2.0"),
+            _ => None,
+        }
+    }
+
+    /// JWKS endpoint holding the keys that sign the provider's `id_token`
+    pub fn jwks_url(&self) -> Option<&'static str> {
+        match self {
+            OAuthProvider::Google => Some("https://www.googleapis.com/oauth2/v3/certs"),
+            OAuthProvider::Apple => Some("https://appleid.apple.com/auth/keys"),
+            OAuthProvider::Microsoft => {
+                Some("https://login.microsoftonline.com/common/discovery/v2.0/keys")
+            }
+            _ => None,
         }
     }
 
@@ -94,6 +143,7 @@ impl OAuthProvider {
             OAuthProvider::Apple => "name email",
             OAuthProvider::Facebook => "email public_profile",
             OAuthProvider::Microsoft => "openid email profile User.Read",
+            OAuthProvider::Custom => "openid email profile",
         }
     }
 
@@ -106,6 +156,7 @@ impl OAuthProvider {
             OAuthProvider::Apple => "Apple",
             OAuthProvider::Facebook => "Facebook",
             OAuthProvider::Microsoft => "Microsoft",
+            OAuthProvider::Custom => "SSO",
         }
     }
 
@@ -118,8 +169,14 @@ impl OAuthProvider {
             OAuthProvider::Apple => "🍎",
             OAuthProvider::Facebook => "📘",
             OAuthProvider::Microsoft => "🪟",
+            OAuthProvider::Custom => "🔐",
         }
     }
+
+    /// Lowercase name used in callback paths and `OAuthUser::provider`
+    fn slug(&self) -> String {
+        format!("{:?}", self).to_lowercase()
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -135,6 +192,30 @@ pub struct ProviderConfig {
     pub enabled: bool,
 }
 
+/// A provider not built into Nucleus
+///
+/// With `issuer` set, endpoints are discovered from
+/// `{issuer}/.well-known/openid-configuration`; explicit URLs override them.
+/// Without an issuer, `auth_url` and `token_url` are required and no
+/// `id_token` is verified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProviderConfig {
+    /// Label on the login button
+    pub name: String,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub auth_url: Option<String>,
+    #[serde(default)]
+    pub token_url: Option<String>,
+    #[serde(default)]
+    pub userinfo_url: Option<String>,
+    #[serde(default)]
+    pub jwks_url: Option<String>,
+    #[serde(flatten)]
+    pub client: ProviderConfig,
+}
+
 /// Complete OAuth configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OAuthConfig {
@@ -145,6 +226,8 @@ pub struct OAuthConfig {
     pub apple: Option<ProviderConfig>,
     pub facebook: Option<ProviderConfig>,
     pub microsoft: Option<ProviderConfig>,
+    #[serde(default)]
+    pub custom: Option<CustomProviderConfig>,
 }
 
 impl OAuthConfig {
@@ -158,6 +241,9 @@ impl OAuthConfig {
     /// - APPLE_CLIENT_ID, APPLE_CLIENT_SECRET
     /// - FACEBOOK_CLIENT_ID, FACEBOOK_CLIENT_SECRET
     /// - MICROSOFT_CLIENT_ID, MICROSOFT_CLIENT_SECRET
+    /// - CUSTOM_CLIENT_ID, CUSTOM_CLIENT_SECRET, plus CUSTOM_ISSUER or
+    ///   CUSTOM_AUTH_URL/CUSTOM_TOKEN_URL (optional: CUSTOM_NAME,
+    ///   CUSTOM_USERINFO_URL, CUSTOM_JWKS_URL)
     pub fn from_env() -> Self {
         let get_env = |key: &str| std::env::var(key).ok();
 
@@ -242,6 +328,26 @@ impl OAuthConfig {
             });
         }
 
+        // Custom (OIDC / OAuth2 server)
+        if let (Some(id), Some(secret)) =
+            (get_env("CUSTOM_CLIENT_ID"), get_env("CUSTOM_CLIENT_SECRET"))
+        {
+            config.custom = Some(CustomProviderConfig {
+                name: get_env("CUSTOM_NAME").unwrap_or_else(|| "SSO".to_string()),
+                issuer: get_env("CUSTOM_ISSUER"),
+                auth_url: get_env("CUSTOM_AUTH_URL"),
+                token_url: get_env("CUSTOM_TOKEN_URL"),
+                userinfo_url: get_env("CUSTOM_USERINFO_URL"),
+                jwks_url: get_env("CUSTOM_JWKS_URL"),
+                client: ProviderConfig {
+                    client_id: id,
+                    client_secret: secret,
+                    scopes: get_env("CUSTOM_SCOPES"),
+                    enabled: true,
+                },
+            });
+        }
+
         config
     }
 
@@ -254,6 +360,15 @@ impl OAuthConfig {
             OAuthProvider::Apple => self.apple.as_ref(),
             OAuthProvider::Facebook => self.facebook.as_ref(),
             OAuthProvider::Microsoft => self.microsoft.as_ref(),
+            OAuthProvider::Custom => self.custom.as_ref().map(|c| &c.client),
+        }
+    }
+
+    /// Button label for a provider; `Custom` uses its configured name
+    pub fn display_name(&self, provider: OAuthProvider) -> &str {
+        match (provider, &self.custom) {
+            (OAuthProvider::Custom, Some(custom)) => &custom.name,
+            _ => provider.display_name(),
         }
     }
 
@@ -278,6 +393,14 @@ impl OAuthConfig {
         if self.microsoft.as_ref().map(|p| p.enabled).unwrap_or(false) {
             providers.push(OAuthProvider::Microsoft);
         }
+        if self
+            .custom
+            .as_ref()
+            .map(|c| c.client.enabled)
+            .unwrap_or(false)
+        {
+            providers.push(OAuthProvider::Custom);
+        }
         providers
     }
 }
@@ -287,8 +410,14 @@ impl OAuthConfig {
 // ═══════════════════════════════════════════════════════════════════════════
 
 /// OAuth client for handling social login flows
+///
+/// Cheap to clone; discovery documents and signing keys are cached and shared
+/// between clones.
+#[derive(Clone)]
 pub struct OAuth {
     config: OAuthConfig,
+    http: reqwest::Client,
+    cache: Arc<Mutex<OidcCache>>,
 }
 
 /// User information returned from OAuth provider
@@ -313,10 +442,57 @@ pub struct TokenResponse {
     pub id_token: Option<String>,
 }
 
+/// A login in progress, returned by `OAuth::authorize_url`
+///
+/// Keep it server side (e.g. in the session) until the callback and pass it to
+/// `exchange_code`; the verifier and nonce must never reach the browser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationRequest {
+    /// Where to redirect the user
+    pub url: String,
+    pub state: String,
+    /// PKCE verifier whose S256 challenge was sent with `url`
+    pub code_verifier: String,
+    /// Expected `nonce` of the `id_token` (OpenID Connect providers only)
+    pub nonce: Option<String>,
+}
+
+/// Verified claims of an OpenID Connect `id_token`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: Audience,
+    pub exp: i64,
+    #[serde(default)]
+    pub iat: i64,
+    pub nbf: Option<i64>,
+    pub nonce: Option<String>,
+    pub azp: Option<String>,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Endpoints of a provider after discovery
+struct Endpoints {
+    auth: String,
+    token: String,
+    userinfo: Option<String>,
+    issuer: Option<String>,
+    jwks: Option<String>,
+}
+
 impl OAuth {
     /// Create a new OAuth client
     pub fn new(config: OAuthConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            cache: Arc::new(Mutex::new(OidcCache::default())),
+        }
     }
 
     /// Generate a secure random state parameter
@@ -327,30 +503,60 @@ impl OAuth {
         hex::encode(bytes)
     }
 
-    /// Generate authorization URL for a provider
-    ///
-    /// Returns (url, state) - store the state in session for verification
-    pub fn authorize_url(&self, provider: OAuthProvider) -> Result<(String, String), String> {
+    /// Generate a PKCE code verifier (43 URL-safe characters)
+    pub fn generate_code_verifier() -> String {
+        use rand::Rng;
+        let bytes: [u8; 32] = rand::thread_rng().gen();
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// S256 PKCE challenge for a code verifier (RFC 7636)
+    pub fn code_challenge(verifier: &str) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+    }
+
+    fn callback_uri(&self, provider: OAuthProvider) -> String {
+        format!(
+            "{}/{}",
+            self.config.redirect_uri.trim_end_matches('/'),
+            provider.slug()
+        )
+    }
+
+    fn enabled_provider(&self, provider: OAuthProvider) -> Result<&ProviderConfig, String> {
+        let name = self.config.display_name(provider);
         let provider_config = self
             .config
             .get_provider(provider)
-            .ok_or_else(|| format!("{} is not configured", provider.display_name()))?;
+            .ok_or_else(|| format!("{} is not configured", name))?;
 
         if !provider_config.enabled {
-            return Err(format!("{} is not enabled", provider.display_name()));
+            return Err(format!("{} is not enabled", name));
         }
+        Ok(provider_config)
+    }
+
+    /// Start a login with a provider
+    ///
+    /// Redirect the user to `url` and store the returned request (e.g. in the
+    /// session) for `exchange_code`.
+    pub async fn authorize_url(
+        &self,
+        provider: OAuthProvider,
+    ) -> Result<AuthorizationRequest, String> {
+        let provider_config = self.enabled_provider(provider)?;
+        let endpoints = self.endpoints(provider).await?;
 
         let state = Self::generate_state();
+        let code_verifier = Self::generate_code_verifier();
+        let code_challenge = Self::code_challenge(&code_verifier);
+        // Binds the id_token to this login
+        let nonce = endpoints.issuer.as_ref().map(|_| Self::generate_state());
         let scopes = provider_config
             .scopes
             .as_deref()
             .unwrap_or(provider.default_scopes());
-
-        let callback_uri = format!(
-            "{}/{}",
-            self.config.redirect_uri.trim_end_matches('/'),
-            format!("{:?}", provider).to_lowercase()
-        );
+        let callback_uri = self.callback_uri(provider);
 
         let mut params = vec![
             ("client_id", provider_config.client_id.as_str()),
@@ -358,7 +564,12 @@ impl OAuth {
             ("response_type", "code"),
             ("scope", scopes),
             ("state", &state),
+            ("code_challenge", &code_challenge),
+            ("code_challenge_method", "S256"),
         ];
+        if let Some(nonce) = &nonce {
+            params.push(("nonce", nonce));
+        }
 
         // Provider-specific params
         match provider {
@@ -381,40 +592,46 @@ impl OAuth {
             .collect::<Vec<_>>()
             .join("&");
 
-        let url = format!("{}?{}", provider.auth_url(), query);
-
-        Ok((url, state))
+        let separator = if endpoints.auth.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let url = format!("{}{}{}", endpoints.auth, separator, query);
+
+        Ok(AuthorizationRequest {
+            url,
+            state,
+            code_verifier,
+            nonce,
+        })
     }
 
     /// Exchange authorization code for tokens and user info
+    ///
+    /// `state` is the value the provider sent back; `request` is what
+    /// `authorize_url` returned for this login. OpenID Connect providers must
+    /// return an `id_token`, which is verified before any user info is trusted.
     pub async fn exchange_code(
         &self,
         provider: OAuthProvider,
         code: &str,
         state: &str,
-        expected_state: &str,
+        request: &AuthorizationRequest,
     ) -> Result<OAuthUser, String> {
         // Verify state
-        if state != expected_state {
+        if state != request.state {
             return Err("Invalid state parameter - possible CSRF attack".to_string());
         }
 
-        let provider_config = self
-            .config
-            .get_provider(provider)
-            .ok_or_else(|| format!("{} is not configured", provider.display_name()))?;
-
-        let callback_uri = format!(
-            "{}/{}",
-            self.config.redirect_uri.trim_end_matches('/'),
-            format!("{:?}", provider).to_lowercase()
-        );
+        let provider_config = self.enabled_provider(provider)?;
+        let endpoints = self.endpoints(provider).await?;
+        let callback_uri = self.callback_uri(provider);
 
         // Exchange code for token
-        let client = reqwest::Client::new();
-
-        let token_response = client
-            .post(provider.token_url())
+        let token_response = self
+            .http
+            .post(&endpoints.token)
             .header("Accept", "application/json")
             .form(&[
                 ("client_id", provider_config.client_id.as_str()),
@@ -422,6 +639,7 @@ impl OAuth {
                 ("code", code),
                 ("redirect_uri", &callback_uri),
                 ("grant_type", "authorization_code"),
+                ("code_verifier", &request.code_verifier),
             ])
             .send()
             .await
@@ -437,24 +655,224 @@ impl OAuth {
             .await
             .map_err(|e| format!("Failed to parse token response: {}", e))?;
 
-        // Get user info
-        self.get_user_info(provider, &tokens.access_token).await
+        let claims = match (&tokens.id_token, &endpoints.issuer) {
+            (Some(id_token), Some(_)) => Some(
+                self.verify_id_token(provider, id_token, request.nonce.as_deref())
+                    .await?,
+            ),
+            // Userinfo alone would skip the issuer, audience and nonce checks
+            (None, Some(_)) => return Err("Provider did not return an id_token".to_string()),
+            _ if request.nonce.is_some() => {
+                return Err("Login expected an id_token but the provider is not OIDC".to_string())
+            }
+            _ => None,
+        };
+
+        match (endpoints.userinfo.as_deref(), claims) {
+            (Some(userinfo_url), claims) => {
+                let user = self
+                    .get_user_info(provider, userinfo_url, &tokens.access_token)
+                    .await?;
+                // OIDC requires userinfo to describe the same subject as the id_token
+                let sub = user.raw.get("sub").and_then(|v| v.as_str());
+                if let (Some(sub), Some(claims)) = (sub, &claims) {
+                    if sub != claims.sub {
+                        return Err("User info subject does not match id_token".to_string());
+                    }
+                }
+                Ok(user)
+            }
+            (None, Some(claims)) => Ok(user_from_claims(provider, claims)),
+            (None, None) => Err("Provider returned neither id_token nor user info".to_string()),
+        }
+    }
+
+    /// Verify an OpenID Connect `id_token` from `provider`
+    ///
+    /// Checks the signature against the provider's JWKS, then issuer, audience,
+    /// expiry and, when given, the nonce from `AuthorizationRequest::nonce`.
+    pub async fn verify_id_token(
+        &self,
+        provider: OAuthProvider,
+        id_token: &str,
+        nonce: Option<&str>,
+    ) -> Result<IdTokenClaims, String> {
+        let client_id = self.enabled_provider(provider)?.client_id.clone();
+        let endpoints = self.endpoints(provider).await?;
+        let (Some(issuer), Some(jwks_url)) = (endpoints.issuer, endpoints.jwks) else {
+            return Err(format!(
+                "{} does not issue ID tokens",
+                self.config.display_name(provider)
+            ));
+        };
+
+        let mut segments = id_token.split('.');
+        let (Some(header_b64), Some(payload), Some(signature), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err("Malformed id_token".to_string());
+        };
+
+        let header: JwtHeader = decode_segment(header_b64)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "Malformed id_token signature".to_string())?;
+        let signed = &id_token[..header_b64.len() + 1 + payload.len()];
+
+        let key = self
+            .signing_key(&jwks_url, header.kid.as_deref(), &header.alg)
+            .await?;
+        if !key.verify(&header.alg, signed.as_bytes(), &signature) {
+            return Err("Invalid id_token signature".to_string());
+        }
+
+        let claims: IdTokenClaims = decode_segment(payload)?;
+        validate_claims(
+            &claims,
+            &issuer,
+            &client_id,
+            nonce,
+            chrono::Utc::now().timestamp(),
+        )?;
+        Ok(claims)
+    }
+
+    /// Resolve endpoints, running OIDC discovery for a custom issuer
+    async fn endpoints(&self, provider: OAuthProvider) -> Result<Endpoints, String> {
+        if provider != OAuthProvider::Custom {
+            return Ok(Endpoints {
+                auth: provider.auth_url().to_string(),
+                token: provider.token_url().to_string(),
+                userinfo: Some(provider.userinfo_url().to_string()).filter(|u| !u.is_empty()),
+                issuer: provider.issuer().map(String::from),
+                jwks: provider.jwks_url().map(String::from),
+            });
+        }
+
+        let custom = self
+            .config
+            .custom
+            .as_ref()
+            .ok_or_else(|| "Custom provider is not configured".to_string())?;
+        let discovered = match &custom.issuer {
+            Some(issuer) => Some(self.discover(issuer).await?),
+            None => None,
+        };
+        let pick = |explicit: &Option<String>, found: Option<&String>| {
+            explicit.clone().or_else(|| found.cloned())
+        };
+        let meta = discovered.as_deref();
+
+        Ok(Endpoints {
+            auth: pick(&custom.auth_url, meta.map(|m| &m.authorization_endpoint))
+                .ok_or_else(|| "Custom provider needs `issuer` or `auth_url`".to_string())?,
+            token: pick(&custom.token_url, meta.map(|m| &m.token_endpoint))
+                .ok_or_else(|| "Custom provider needs `issuer` or `token_url`".to_string())?,
+            userinfo: pick(
+                &custom.userinfo_url,
+                meta.and_then(|m| m.userinfo_endpoint.as_ref()),
+            ),
+            issuer: meta.map(|m| m.issuer.clone()),
+            jwks: pick(&custom.jwks_url, meta.map(|m| &m.jwks_uri)),
+        })
+    }
+
+    /// Fetch (once per client) `{issuer}/.well-known/openid-configuration`
+    async fn discover(&self, issuer: &str) -> Result<Arc<ProviderMetadata>, String> {
+        if let Some(meta) = self.cache.lock().unwrap().metadata.get(issuer) {
+            return Ok(Arc::clone(meta));
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+        let meta: ProviderMetadata = self.fetch_json(&url).await?;
+        // A document for another issuer could swap in its own keys
+        if meta.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(format!(
+                "Discovery issuer {} does not match {}",
+                meta.issuer, issuer
+            ));
+        }
+
+        let meta = Arc::new(meta);
+        self.cache
+            .lock()
+            .unwrap()
+            .metadata
+            .insert(issuer.to_string(), Arc::clone(&meta));
+        Ok(meta)
+    }
+
+    /// Find the key for `kid`, refetching the JWKS when it is stale or the
+    /// key is unknown (providers rotate keys without notice)
+    async fn signing_key(
+        &self,
+        jwks_url: &str,
+        kid: Option<&str>,
+        alg: &str,
+    ) -> Result<Jwk, String> {
+        let refetch = {
+            let cache = self.cache.lock().unwrap();
+            match cache.jwks.get(jwks_url) {
+                Some(cached) if cached.fetched_at.elapsed() < JWKS_TTL => {
+                    if let Some(key) = cached.find(kid, alg) {
+                        return Ok(key.clone());
+                    }
+                    cached.fetched_at.elapsed() >= JWKS_REFRESH_INTERVAL
+                }
+                _ => true,
+            }
+        };
+
+        if refetch {
+            let set: JwkSet = self.fetch_json(jwks_url).await?;
+            let cached = CachedJwks {
+                keys: set.keys,
+                fetched_at: Instant::now(),
+            };
+            let key = cached.find(kid, alg).cloned();
+            self.cache
+                .lock()
+                .unwrap()
+                .jwks
+                .insert(jwks_url.to_string(), cached);
+            if let Some(key) = key {
+                return Ok(key);
+            }
+        }
+        Err("No matching key for id_token".to_string())
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let response = self
+            .http
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Request to {} failed: {}", url, response.status()));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse {}: {}", url, e))
     }
 
     /// Get user information from the provider
     async fn get_user_info(
         &self,
         provider: OAuthProvider,
+        userinfo_url: &str,
         access_token: &str,
     ) -> Result<OAuthUser, String> {
-        let client = reqwest::Client::new();
-
-        let userinfo_url = provider.userinfo_url();
-        if userinfo_url.is_empty() {
-            return Err("Provider does not support userinfo endpoint".to_string());
-        }
-
-        let mut request = client.get(userinfo_url);
+        let mut request = self.http.get(userinfo_url);
 
         // Provider-specific auth headers
         match provider {
@@ -486,11 +904,7 @@ impl OAuth {
         let user = match provider {
             OAuthProvider::Google => OAuthUser {
                 provider: "google".to_string(),
-                provider_id: raw
-                    .get("sub")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
+                provider_id: provider_id(&raw, &["sub"])?,
                 email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
                 name: raw.get("name").and_then(|v| v.as_str()).map(String::from),
                 avatar: raw
@@ -501,11 +915,7 @@ impl OAuth {
             },
             OAuthProvider::GitHub => OAuthUser {
                 provider: "github".to_string(),
-                provider_id: raw
-                    .get("id")
-                    .and_then(|v| v.as_i64())
-                    .map(|v| v.to_string())
-                    .unwrap_or_default(),
+                provider_id: provider_id(&raw, &["id"])?,
                 email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
                 name: raw.get("name").and_then(|v| v.as_str()).map(String::from),
                 avatar: raw
@@ -516,11 +926,7 @@ impl OAuth {
             },
             OAuthProvider::Discord => OAuthUser {
                 provider: "discord".to_string(),
-                provider_id: raw
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
+                provider_id: provider_id(&raw, &["id"])?,
                 email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
                 name: raw
                     .get("username")
@@ -534,11 +940,7 @@ impl OAuth {
             },
             OAuthProvider::Facebook => OAuthUser {
                 provider: "facebook".to_string(),
-                provider_id: raw
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
+                provider_id: provider_id(&raw, &["id"])?,
                 email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
                 name: raw.get("name").and_then(|v| v.as_str()).map(String::from),
                 avatar: raw
@@ -551,11 +953,7 @@ impl OAuth {
             },
             OAuthProvider::Microsoft => OAuthUser {
                 provider: "microsoft".to_string(),
-                provider_id: raw
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
+                provider_id: provider_id(&raw, &["id"])?,
                 email: raw
                     .get("mail")
                     .or(raw.get("userPrincipalName"))
@@ -570,22 +968,243 @@ impl OAuth {
             },
             OAuthProvider::Apple => OAuthUser {
                 provider: "apple".to_string(),
-                provider_id: raw
-                    .get("sub")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string(),
+                provider_id: provider_id(&raw, &["sub"])?,
                 email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
                 name: None, // Apple provides name only on first login
                 avatar: None,
                 raw,
             },
+            OAuthProvider::Custom => OAuthUser {
+                provider: "custom".to_string(),
+                provider_id: provider_id(&raw, &["sub", "id"])?,
+                email: raw.get("email").and_then(|v| v.as_str()).map(String::from),
+                name: raw
+                    .get("name")
+                    .or(raw.get("preferred_username"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                avatar: raw
+                    .get("picture")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                raw,
+            },
         };
 
         Ok(user)
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// OpenID Connect
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// The parts of `.well-known/openid-configuration` Nucleus uses
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+/// A public key from a provider's JWKS
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    alg: Option<String>,
+    #[serde(rename = "use")]
+    key_use: Option<String>,
+    crv: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+impl Jwk {
+    fn supports(&self, alg: &str) -> bool {
+        let kty = match alg {
+            "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512" => "RSA",
+            "ES256" | "ES384" => "EC",
+            "EdDSA" => "OKP",
+            _ => return false,
+        };
+        self.kty == kty
+            && self.key_use.as_deref() != Some("enc")
+            && self.alg.as_deref().is_none_or(|a| a == alg)
+    }
+
+    fn verify(&self, alg: &str, message: &[u8], signature: &[u8]) -> bool {
+        use ring::signature as sig;
+
+        let decode =
+            |part: &Option<String>| part.as_ref().and_then(|p| URL_SAFE_NO_PAD.decode(p).ok());
+        match alg {
+            "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512" => {
+                let (Some(n), Some(e)) = (decode(&self.n), decode(&self.e)) else {
+                    return false;
+                };
+                let params: &sig::RsaParameters = match alg {
+                    "RS256" => &sig::RSA_PKCS1_2048_8192_SHA256,
+                    "RS384" => &sig::RSA_PKCS1_2048_8192_SHA384,
+                    "RS512" => &sig::RSA_PKCS1_2048_8192_SHA512,
+                    "PS256" => &sig::RSA_PSS_2048_8192_SHA256,
+                    "PS384" => &sig::RSA_PSS_2048_8192_SHA384,
+                    _ => &sig::RSA_PSS_2048_8192_SHA512,
+                };
+                sig::RsaPublicKeyComponents { n, e }
+                    .verify(params, message, signature)
+                    .is_ok()
+            }
+            "ES256" | "ES384" => {
+                let (crv, params) = if alg == "ES256" {
+                    ("P-256", &sig::ECDSA_P256_SHA256_FIXED)
+                } else {
+                    ("P-384", &sig::ECDSA_P384_SHA384_FIXED)
+                };
+                let (Some(x), Some(y)) = (decode(&self.x), decode(&self.y)) else {
+                    return false;
+                };
+                if self.crv.as_deref() != Some(crv) {
+                    return false;
+                }
+                // Uncompressed SEC1 point
+                let point = [&[4u8][..], &x, &y].concat();
+                sig::UnparsedPublicKey::new(params, point)
+                    .verify(message, signature)
+                    .is_ok()
+            }
+            "EdDSA" => match (self.crv.as_deref(), decode(&self.x)) {
+                (Some("Ed25519"), Some(x)) => sig::UnparsedPublicKey::new(&sig::ED25519, x)
+                    .verify(message, signature)
+                    .is_ok(),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+struct CachedJwks {
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+impl CachedJwks {
+    fn find(&self, kid: Option<&str>, alg: &str) -> Option<&Jwk> {
+        self.keys
+            .iter()
+            .filter(|key| key.supports(alg))
+            .find(|key| kid.is_none() || key.kid.as_deref() == kid)
+    }
+}
+
+/// Discovery documents by issuer and key sets by JWKS URL
+#[derive(Default)]
+struct OidcCache {
+    metadata: HashMap<String, Arc<ProviderMetadata>>,
+    jwks: HashMap<String, CachedJwks>,
+}
+
+/// The provider's user id under the first present `keys` entry
+///
+/// An account without an id must never log in: it would collide with every
+/// other user whose id is also missing.
+fn provider_id(raw: &HashMap<String, serde_json::Value>, keys: &[&str]) -> Result<String, String> {
+    keys.iter()
+        .filter_map(|key| raw.get(*key))
+        .find_map(|value| match value {
+            serde_json::Value::String(id) if !id.is_empty() => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| format!("User info has no `{}`", keys.join("` or `")))
+}
+
+fn decode_segment<T: serde::de::DeserializeOwned>(segment: &str) -> Result<T, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| "Malformed id_token".to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Malformed id_token: {}", e))
+}
+
+/// Check the registered claims of a signature-verified `id_token`
+fn validate_claims(
+    claims: &IdTokenClaims,
+    issuer: &str,
+    client_id: &str,
+    nonce: Option<&str>,
+    now: i64,
+) -> Result<(), String> {
+    // Microsoft's multi-tenant issuer names the user's tenant
+    let expected_issuer = match claims.extra.get("tid").and_then(|v| v.as_str()) {
+        Some(tid) => issuer.replace("{tenantid}", tid),
+        None => issuer.to_string(),
+    };
+    // Google may omit the scheme
+    let google_bare =
+        issuer == "https://accounts.google.com" && claims.iss == "accounts.google.com";
+    if claims.iss != expected_issuer && !google_bare {
+        return Err(format!(
+            "id_token issuer {} is not {}",
+            claims.iss, expected_issuer
+        ));
+    }
+
+    if !claims.aud.contains(client_id) {
+        return Err("id_token was issued for another client".to_string());
+    }
+    if claims.azp.as_deref().is_some_and(|azp| azp != client_id) {
+        return Err("id_token was issued for another client".to_string());
+    }
+
+    if claims.exp + ID_TOKEN_LEEWAY < now {
+        return Err("id_token has expired".to_string());
+    }
+    if claims.nbf.is_some_and(|nbf| nbf > now + ID_TOKEN_LEEWAY)
+        || claims.iat > now + ID_TOKEN_LEEWAY
+    {
+        return Err("id_token is not valid yet".to_string());
+    }
+
+    if let Some(expected) = nonce {
+        if claims.nonce.as_deref() != Some(expected) {
+            return Err("id_token nonce does not match".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Build the user from ID token claims when the provider has no userinfo
+fn user_from_claims(provider: OAuthProvider, claims: IdTokenClaims) -> OAuthUser {
+    let raw = match serde_json::to_value(&claims) {
+        Ok(serde_json::Value::Object(map)) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    OAuthUser {
+        provider: provider.slug(),
+        provider_id: claims.sub,
+        email: claims.email,
+        name: claims.name,
+        avatar: claims.picture,
+        raw,
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // HTML HELPERS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -608,7 +1227,7 @@ pub fn render_social_buttons(config: &OAuthConfig, class: Option<&str>) -> Strin
             button_class,
             format!("{:?}", provider).to_lowercase(),
             provider.icon(),
-            provider.display_name()
+            config.display_name(provider)
         ));
     }
 
@@ -655,6 +1274,8 @@ pub fn social_buttons_css() -> &'static str {
 .social-facebook:hover { background: #166fe5; }
 .social-microsoft { background: #00a4ef; color: #fff; border-color: #00a4ef; }
 .social-microsoft:hover { background: #0095d9; }
+.social-custom { background: #0f172a; color: #fff; border-color: #0f172a; }
+.social-custom:hover { background: #1e293b; }
 "#
 }
 
@@ -842,6 +1463,7 @@ mod tests {
             apple: Some(provider_config.clone()),
             facebook: Some(provider_config.clone()),
             microsoft: Some(provider_config),
+            custom: None,
         };
 
         assert_eq!(config.enabled_providers().len(), 6);
@@ -867,8 +1489,8 @@ mod tests {
         assert!(state1.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
-    #[test]
-    fn test_authorize_url_google() {
+    #[tokio::test]
+    async fn test_authorize_url_google() {
         let config = OAuthConfig {
             redirect_uri: "http://localhost:3000/auth/callback".to_string(),
             google: Some(ProviderConfig {
@@ -881,10 +1503,11 @@ mod tests {
         };
 
         let oauth = OAuth::new(config);
-        let result = oauth.authorize_url(OAuthProvider::Google);
+        let result = oauth.authorize_url(OAuthProvider::Google).await;
 
         assert!(result.is_ok());
-        let (url, state) = result.unwrap();
+        let request = result.unwrap();
+        let (url, state) = (request.url, request.state);
 
         assert!(url.contains("accounts.google.com"));
         assert!(url.contains("client_id=my-client-id"));
@@ -893,10 +1516,16 @@ mod tests {
         assert!(url.contains(&format!("state={}", state)));
         assert!(url.contains("access_type=offline"));
         assert!(url.contains("prompt=select_account"));
+        assert!(url.contains(&format!(
+            "code_challenge={}",
+            OAuth::code_challenge(&request.code_verifier)
+        )));
+        assert!(url.contains("code_challenge_method=S256"));
+        assert!(url.contains(&format!("nonce={}", request.nonce.unwrap())));
     }
 
-    #[test]
-    fn test_authorize_url_github() {
+    #[tokio::test]
+    async fn test_authorize_url_github() {
         let config = OAuthConfig {
             redirect_uri: "http://localhost:3000/auth/callback".to_string(),
             github: Some(ProviderConfig {
@@ -909,18 +1538,18 @@ mod tests {
         };
 
         let oauth = OAuth::new(config);
-        let result = oauth.authorize_url(OAuthProvider::GitHub);
+        let result = oauth.authorize_url(OAuthProvider::GitHub).await;
 
         assert!(result.is_ok());
-        let (url, _) = result.unwrap();
+        let url = result.unwrap().url;
 
         assert!(url.contains("github.com"));
         assert!(url.contains("client_id=github-client"));
         assert!(url.contains("scope=repo"));
     }
 
-    #[test]
-    fn test_authorize_url_discord() {
+    #[tokio::test]
+    async fn test_authorize_url_discord() {
         let config = OAuthConfig {
             redirect_uri: "http://localhost:3000/auth/callback".to_string(),
             discord: Some(ProviderConfig {
@@ -933,17 +1562,17 @@ mod tests {
         };
 
         let oauth = OAuth::new(config);
-        let result = oauth.authorize_url(OAuthProvider::Discord);
+        let result = oauth.authorize_url(OAuthProvider::Discord).await;
 
         assert!(result.is_ok());
-        let (url, _) = result.unwrap();
+        let url = result.unwrap().url;
 
         assert!(url.contains("discord.com"));
         assert!(url.contains("prompt=consent"));
     }
 
-    #[test]
-    fn test_authorize_url_apple() {
+    #[tokio::test]
+    async fn test_authorize_url_apple() {
         let config = OAuthConfig {
             redirect_uri: "http://localhost:3000/auth/callback".to_string(),
             apple: Some(ProviderConfig {
@@ -956,27 +1585,27 @@ mod tests {
         };
 
         let oauth = OAuth::new(config);
-        let result = oauth.authorize_url(OAuthProvider::Apple);
+        let result = oauth.authorize_url(OAuthProvider::Apple).await;
 
         assert!(result.is_ok());
-        let (url, _) = result.unwrap();
+        let url = result.unwrap().url;
 
         assert!(url.contains("appleid.apple.com"));
         assert!(url.contains("response_mode=form_post"));
     }
 
-    #[test]
-    fn test_authorize_url_not_configured() {
+    #[tokio::test]
+    async fn test_authorize_url_not_configured() {
         let config = OAuthConfig::default();
         let oauth = OAuth::new(config);
 
-        let result = oauth.authorize_url(OAuthProvider::Google);
+        let result = oauth.authorize_url(OAuthProvider::Google).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("not configured"));
     }
 
-    #[test]
-    fn test_authorize_url_disabled() {
+    #[tokio::test]
+    async fn test_authorize_url_disabled() {
         let config = OAuthConfig {
             redirect_uri: "http://localhost:3000/auth/callback".to_string(),
             google: Some(ProviderConfig {
@@ -989,7 +1618,7 @@ mod tests {
         };
 
         let oauth = OAuth::new(config);
-        let result = oauth.authorize_url(OAuthProvider::Google);
+        let result = oauth.authorize_url(OAuthProvider::Google).await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("not enabled"));
@@ -1132,4 +1761,389 @@ mod tests {
         assert!(deserialized.google.is_some());
         assert!(deserialized.github.is_none());
     }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // PKCE / OIDC TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636, Appendix B
+        assert_eq!(
+            OAuth::code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+
+        let verifier = OAuth::generate_code_verifier();
+        assert_eq!(verifier.len(), 43);
+        assert_ne!(verifier, OAuth::generate_code_verifier());
+    }
+
+    #[tokio::test]
+    async fn test_authorize_url_plain_oauth_has_no_nonce() {
+        let config = OAuthConfig {
+            redirect_uri: "http://localhost:3000/auth/callback".to_string(),
+            github: Some(ProviderConfig {
+                client_id: "github-client".to_string(),
+                client_secret: "github-secret".to_string(),
+                scopes: None,
+                enabled: true,
+            }),
+            ..Default::default()
+        };
+
+        let request = OAuth::new(config)
+            .authorize_url(OAuthProvider::GitHub)
+            .await
+            .unwrap();
+        assert!(request.nonce.is_none());
+        assert!(request.url.contains("code_challenge_method=S256"));
+    }
+
+    fn custom_config(issuer: Option<String>) -> OAuthConfig {
+        OAuthConfig {
+            redirect_uri: "http://localhost:3000/auth/callback".to_string(),
+            custom: Some(CustomProviderConfig {
+                name: "Keycloak".to_string(),
+                issuer,
+                auth_url: None,
+                token_url: None,
+                userinfo_url: None,
+                jwks_url: None,
+                client: ProviderConfig {
+                    client_id: "nucleus-app".to_string(),
+                    client_secret: "secret".to_string(),
+                    scopes: None,
+                    enabled: true,
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorize_url_custom_explicit_endpoints() {
+        let mut config = custom_config(None);
+        let custom = config.custom.as_mut().unwrap();
+        custom.auth_url = Some("https://sso.example.com/auth?realm=staff".to_string());
+        custom.token_url = Some("https://sso.example.com/token".to_string());
+
+        assert_eq!(config.enabled_providers(), vec![OAuthProvider::Custom]);
+        assert!(render_social_buttons(&config, None).contains("Continue with Keycloak"));
+
+        let request = OAuth::new(config)
+            .authorize_url(OAuthProvider::Custom)
+            .await
+            .unwrap();
+        assert!(request
+            .url
+            .starts_with("https://sso.example.com/auth?realm=staff&client_id=nucleus-app"));
+        assert!(request
+            .url
+            .contains("redirect_uri=http%3A%2F%2Flocalhost%3A3000%2Fauth%2Fcallback%2Fcustom"));
+        assert!(request.nonce.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_custom_provider_requires_endpoints() {
+        let oauth = OAuth::new(custom_config(None));
+        let err = oauth
+            .authorize_url(OAuthProvider::Custom)
+            .await
+            .unwrap_err();
+        assert!(err.contains("auth_url"));
+    }
+
+    fn id_claims(value: serde_json::Value) -> IdTokenClaims {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_id_token_claims() {
+        let now = 1_700_000_000;
+        let base = serde_json::json!({
+            "iss": "https://sso.example.com",
+            "sub": "user-1",
+            "aud": "nucleus-app",
+            "exp": now + 300,
+            "iat": now,
+            "nonce": "n1",
+        });
+        let with = |key: &str, value: serde_json::Value| {
+            let mut claims = base.clone();
+            claims[key] = value;
+            id_claims(claims)
+        };
+        let check = |claims: &IdTokenClaims| {
+            validate_claims(
+                claims,
+                "https://sso.example.com",
+                "nucleus-app",
+                Some("n1"),
+                now,
+            )
+        };
+
+        assert!(check(&id_claims(base.clone())).is_ok());
+        assert!(check(&with("iss", "https://evil.example.com".into())).is_err());
+        assert!(check(&with("aud", "other-app".into())).is_err());
+        assert!(check(&with(
+            "aud",
+            serde_json::json!(["other-app", "nucleus-app"])
+        ))
+        .is_ok());
+        assert!(check(&with("azp", "other-app".into())).is_err());
+        assert!(check(&with("exp", (now - 120).into())).is_err());
+        assert!(check(&with("exp", (now - 30).into())).is_ok()); // within leeway
+        assert!(check(&with("iat", (now + 600).into())).is_err());
+        assert!(check(&with("nonce", "replayed".into())).is_err());
+    }
+
+    #[test]
+    fn test_validate_id_token_issuer_variants() {
+        let now = 1_700_000_000;
+        let claims = id_claims(serde_json::json!({
+            "iss": "https://login.microsoftonline.com/tenant-1/v2.0",
+            "tid": "tenant-1",
+            "sub": "user-1",
+            "aud": "app",
+            "exp": now + 300,
+        }));
+        let microsoft = OAuthProvider::Microsoft.issuer().unwrap();
+        assert!(validate_claims(&claims, microsoft, "app", None, now).is_ok());
+
+        let claims = id_claims(serde_json::json!({
+            "iss": "accounts.google.com",
+            "sub": "user-1",
+            "aud": "app",
+            "exp": now + 300,
+        }));
+        let google = OAuthProvider::Google.issuer().unwrap();
+        assert!(validate_claims(&claims, google, "app", None, now).is_ok());
+    }
+
+    struct TestKey {
+        pair: ring::signature::EcdsaKeyPair,
+        kid: String,
+    }
+
+    impl TestKey {
+        fn generate(kid: &str) -> Self {
+            use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+            let rng = ring::rand::SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self {
+                pair,
+                kid: kid.to_string(),
+            }
+        }
+
+        fn jwk(&self) -> serde_json::Value {
+            use ring::signature::KeyPair;
+            let point = self.pair.public_key().as_ref();
+            serde_json::json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "kid": self.kid,
+                "alg": "ES256",
+                "use": "sig",
+                "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+            })
+        }
+
+        fn sign(&self, claims: &serde_json::Value) -> String {
+            let header = serde_json::json!({ "alg": "ES256", "kid": self.kid, "typ": "JWT" });
+            let message = format!(
+                "{}.{}",
+                URL_SAFE_NO_PAD.encode(header.to_string()),
+                URL_SAFE_NO_PAD.encode(claims.to_string())
+            );
+            let rng = ring::rand::SystemRandom::new();
+            let signature = self.pair.sign(&rng, message.as_bytes()).unwrap();
+            format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature.as_ref()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_info_requires_provider_id() {
+        use crate::testing::MockServer;
+
+        let server = MockServer::start().await.unwrap();
+        server
+            .expect("GET", "/numeric")
+            .respond_with_json(serde_json::json!({ "id": 42, "name": "Ada" }))
+            .mount()
+            .await;
+        server
+            .expect("GET", "/anonymous")
+            .respond_with_json(serde_json::json!({ "sub": "", "email": "ada@example.com" }))
+            .mount()
+            .await;
+
+        let oauth = OAuth::new(custom_config(None));
+        let user = oauth
+            .get_user_info(
+                OAuthProvider::Custom,
+                &format!("{}/numeric", server.url()),
+                "access",
+            )
+            .await
+            .unwrap();
+        assert_eq!(user.provider_id, "42");
+
+        let err = oauth
+            .get_user_info(
+                OAuthProvider::Custom,
+                &format!("{}/anonymous", server.url()),
+                "access",
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("`sub` or `id`"));
+    }
+
+    #[tokio::test]
+    async fn test_oidc_login_requires_id_token() {
+        use crate::testing::MockServer;
+
+        let server = MockServer::start().await.unwrap();
+        let issuer = server.url();
+        server
+            .expect("GET", "/.well-known/openid-configuration")
+            .respond_with_json(serde_json::json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/auth", issuer),
+                "token_endpoint": format!("{}/token", issuer),
+                "userinfo_endpoint": format!("{}/userinfo", issuer),
+                "jwks_uri": format!("{}/jwks", issuer),
+            }))
+            .mount()
+            .await;
+        server
+            .expect("POST", "/token")
+            .respond_with_json(serde_json::json!({
+                "access_token": "access",
+                "token_type": "Bearer",
+            }))
+            .mount()
+            .await;
+        server
+            .expect("GET", "/userinfo")
+            .respond_with_json(serde_json::json!({ "sub": "user-1" }))
+            .mount()
+            .await;
+
+        let oauth = OAuth::new(custom_config(Some(issuer.clone())));
+        let request = oauth.authorize_url(OAuthProvider::Custom).await.unwrap();
+        let err = oauth
+            .exchange_code(OAuthProvider::Custom, "code", &request.state, &request)
+            .await
+            .unwrap_err();
+        assert!(err.contains("id_token"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_oidc_login_flow() {
+        use crate::testing::MockServer;
+
+        let server = MockServer::start().await.unwrap();
+        let issuer = server.url();
+        let key = TestKey::generate("k1");
+        let now = chrono::Utc::now().timestamp();
+        let claims = serde_json::json!({
+            "iss": issuer,
+            "sub": "user-1",
+            "aud": "nucleus-app",
+            "exp": now + 300,
+            "iat": now,
+            "nonce": "n1",
+            "email": "ada@example.com",
+        });
+
+        server
+            .expect("GET", "/.well-known/openid-configuration")
+            .respond_with_json(serde_json::json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/auth", issuer),
+                "token_endpoint": format!("{}/token", issuer),
+                "userinfo_endpoint": format!("{}/userinfo", issuer),
+                "jwks_uri": format!("{}/jwks", issuer),
+            }))
+            .times(1)
+            .mount()
+            .await;
+        server
+            .expect("GET", "/jwks")
+            .respond_with_json(serde_json::json!({ "keys": [key.jwk()] }))
+            .mount()
+            .await;
+        server
+            .expect("POST", "/token")
+            .respond_with_json(serde_json::json!({
+                "access_token": "access",
+                "token_type": "Bearer",
+                "id_token": key.sign(&claims),
+            }))
+            .mount()
+            .await;
+        server
+            .expect("GET", "/userinfo")
+            .respond_with_json(serde_json::json!({
+                "sub": "user-1",
+                "email": "ada@example.com",
+                "preferred_username": "ada",
+            }))
+            .mount()
+            .await;
+
+        let oauth = OAuth::new(custom_config(Some(issuer.clone())));
+
+        let started = oauth.authorize_url(OAuthProvider::Custom).await.unwrap();
+        assert!(started.url.starts_with(&format!("{}/auth?", issuer)));
+        assert!(started.nonce.is_some());
+
+        let request = AuthorizationRequest {
+            nonce: Some("n1".to_string()),
+            ..started
+        };
+        let user = oauth
+            .exchange_code(OAuthProvider::Custom, "code", &request.state, &request)
+            .await
+            .unwrap();
+        assert_eq!(user.provider, "custom");
+        assert_eq!(user.provider_id, "user-1");
+        assert_eq!(user.name.as_deref(), Some("ada"));
+
+        // Wrong nonce
+        let id_token = key.sign(&claims);
+        let err = oauth
+            .verify_id_token(OAuthProvider::Custom, &id_token, Some("other"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("nonce"));
+
+        // Signature over different claims
+        let mut forged = id_token.rsplitn(2, '.').last().unwrap().to_string();
+        let other = key.sign(&serde_json::json!({ "sub": "admin" }));
+        forged.push('.');
+        forged.push_str(other.rsplit('.').next().unwrap());
+        let err = oauth
+            .verify_id_token(OAuthProvider::Custom, &forged, None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("signature"));
+
+        // Key the provider never published
+        let stranger = TestKey::generate("k2");
+        let err = oauth
+            .verify_id_token(OAuthProvider::Custom, &stranger.sign(&claims), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("No matching key"));
+    }
 }
@@ -0,0 +1,612 @@
+//! Photon Cursor Pagination
+//!
+//! Opaque, signed cursors for keyset pagination. A cursor records the
+//! values of the ORDER BY columns for one row, so the next page can be
+//! fetched with a `WHERE (a, b) > (?, ?)` style condition instead of an
+//! OFFSET scan.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::photon::Cursor;
+//!
+//! let page = Post::query()
+//!     .order_by("published_at", "DESC")
+//!     .paginate_cursor::<Post>(None, 20)
+//!     .await?;
+//!
+//! // Later, from the client's `?after=` parameter
+//! let after = Cursor::decode(&token)?;
+//! let next = Post::query()
+//!     .order_by("published_at", "DESC")
+//!     .paginate_cursor::<Post>(Some(after), 20)
+//!     .await?;
+//! ```
+
+use crate::config::GLOBAL_CONFIG;
+use crate::photon::db::{DatabaseType, QueryValue};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::Sha256;
+use sqlx::mysql::MySqlRow;
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, TypeInfo, ValueRef};
+use std::fmt;
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// PostgreSQL types a cursor value may be cast back to
+const PG_CASTS: &[&str] = &["TIMESTAMPTZ", "TIMESTAMP", "DATE", "TIME"];
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CURSOR
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One ORDER BY column value captured in a cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum CursorKey {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    /// Text form of a PostgreSQL value that needs a cast to compare
+    Cast(String, String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorPayload {
+    o: String,
+    k: Vec<CursorKey>,
+}
+
+/// Opaque position in a keyset-paginated result
+///
+/// The token is `hex(payload).hex(hmac)`, signed with `app.secret_key`
+/// (or the `SECRET_KEY` env var), so clients cannot forge positions.
+/// Without either, a random key is used and cursors stop verifying after a
+/// restart or on another replica.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    order: String,
+    keys: Vec<CursorKey>,
+    token: String,
+}
+
+impl Cursor {
+    /// Decode and verify a cursor token
+    pub fn decode(token: &str) -> Result<Self, sqlx::Error> {
+        Self::decode_with(token, cursor_secret())
+    }
+
+    fn decode_with(token: &str, secret: &[u8]) -> Result<Self, sqlx::Error> {
+        let invalid = || sqlx::Error::Configuration("Invalid cursor".into());
+
+        let (payload_hex, sig_hex) = token.split_once('.').ok_or_else(invalid)?;
+        let sig = hex::decode(sig_hex).map_err(|_| invalid())?;
+
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC error");
+        mac.update(payload_hex.as_bytes());
+        mac.verify_slice(&sig).map_err(|_| invalid())?;
+
+        let payload = hex::decode(payload_hex).map_err(|_| invalid())?;
+        let payload: CursorPayload = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+
+        Ok(Self {
+            order: payload.o,
+            keys: payload.k,
+            token: token.to_string(),
+        })
+    }
+
+    fn sign(order: &str, keys: Vec<CursorKey>, secret: &[u8]) -> Self {
+        let payload = CursorPayload {
+            o: order.to_string(),
+            k: keys,
+        };
+        let payload_hex = hex::encode(serde_json::to_vec(&payload).expect("cursor payload"));
+
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take any size key");
+        mac.update(payload_hex.as_bytes());
+        let sig = hex::encode(mac.finalize().into_bytes());
+
+        Self {
+            order: payload.o,
+            keys: payload.k,
+            token: format!("{}.{}", payload_hex, sig),
+        }
+    }
+
+    /// The encoded token to hand to clients
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.token)
+    }
+}
+
+/// Signing key for cursors: `app.secret_key`, then `SECRET_KEY`, then a
+/// random key for the life of the process
+fn cursor_secret() -> &'static [u8] {
+    static SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+    SECRET.get_or_init(|| {
+        if !GLOBAL_CONFIG.app.secret_key.is_empty() {
+            return GLOBAL_CONFIG.app.secret_key.clone().into_bytes();
+        }
+        if let Some(secret) = std::env::var("SECRET_KEY").ok().filter(|s| !s.is_empty()) {
+            return secret.into_bytes();
+        }
+        tracing::warn!("no app.secret_key or SECRET_KEY set; using a random cursor key");
+        let mut key = vec![0u8; 32];
+        ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut key)
+            .expect("system random cursor key");
+        key
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CURSOR PAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One page of keyset-paginated results
+///
+/// Returned by `Builder::paginate_cursor()` and
+/// `Builder::paginate_cursor_before()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CursorPage<T> {
+    /// The rows on this page
+    pub data: Vec<T>,
+    /// Cursor for each row in `data`
+    pub cursors: Vec<Cursor>,
+    /// Items per page
+    pub per_page: i64,
+    /// Pass to `paginate_cursor()` for the next page
+    pub next_cursor: Option<Cursor>,
+    /// Pass to `paginate_cursor_before()` for the previous page
+    pub prev_cursor: Option<Cursor>,
+}
+
+impl<T> CursorPage<T> {
+    /// Check if there is a next page
+    pub fn has_next_page(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Check if there is a previous page
+    pub fn has_previous_page(&self) -> bool {
+        self.prev_cursor.is_some()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// KEYSET CONDITION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Resolved keyset pagination state for one query
+pub(crate) struct Keyset {
+    /// Ordering with the `id` tiebreaker appended
+    order_by: Vec<(String, String)>,
+    /// Canonical ordering string stored in cursors
+    spec: String,
+    /// Position to continue from, as `(value, cast)` per column
+    values: Vec<(QueryValue, Option<String>)>,
+    /// Paging towards the start of the result
+    backward: bool,
+}
+
+impl Keyset {
+    /// Resolve ordering and position, checking the cursor matches the query
+    pub(crate) fn new(
+        table: &str,
+        order_by: &[(String, String)],
+        cursor: Option<Cursor>,
+        backward: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let mut order_by = order_by.to_vec();
+        if !order_by.iter().any(|(col, _)| column_name(col) == "id") {
+            let dir = order_by
+                .last()
+                .map(|(_, dir)| dir.clone())
+                .unwrap_or_else(|| "ASC".to_string());
+            order_by.push((format!("{}.id", table), dir));
+        }
+        let spec = order_by
+            .iter()
+            .map(|(col, dir)| format!("{} {}", col, dir))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut values = Vec::new();
+        if let Some(cursor) = cursor {
+            if cursor.order != spec || cursor.keys.len() != order_by.len() {
+                return Err(sqlx::Error::Configuration(
+                    "Cursor does not match the query ordering".into(),
+                ));
+            }
+            for key in cursor.keys {
+                values.push(match key {
+                    CursorKey::Int(v) => (QueryValue::Int(v), None),
+                    CursorKey::Float(v) => (QueryValue::Float(v), None),
+                    CursorKey::Text(v) => (QueryValue::Text(v), None),
+                    CursorKey::Bool(v) => (QueryValue::Bool(v), None),
+                    CursorKey::Bytes(v) => (QueryValue::Bytes(v), None),
+                    CursorKey::Cast(v, ty) if PG_CASTS.contains(&ty.as_str()) => {
+                        (QueryValue::Text(v), Some(ty))
+                    }
+                    CursorKey::Cast(..) => {
+                        return Err(sqlx::Error::Configuration("Invalid cursor".into()))
+                    }
+                });
+            }
+        }
+
+        Ok(Self {
+            order_by,
+            spec,
+            values,
+            backward,
+        })
+    }
+
+    /// Whether a cursor position was given
+    pub(crate) fn has_position(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    /// ORDER BY clause for the query, reversed when paging backward
+    pub(crate) fn query_order(&self) -> Vec<(String, String)> {
+        self.order_by
+            .iter()
+            .map(|(col, dir)| {
+                let asc = dir != "DESC";
+                let dir = if asc != self.backward { "ASC" } else { "DESC" };
+                (col.clone(), dir.to_string())
+            })
+            .collect()
+    }
+
+    /// Write the row-comparison condition, if there is a position
+    ///
+    /// Expands to `(a > ?) OR (a = ? AND b > ?) ...` so mixed ASC/DESC
+    /// orderings work on every backend.
+    pub(crate) fn write_sql<'s>(
+        &'s self,
+        sql: &mut String,
+        bindings: &mut Vec<&'s QueryValue>,
+        param_index: &mut usize,
+        db_type: DatabaseType,
+    ) {
+        let mut placeholder = |value: &'s (QueryValue, Option<String>)| {
+            let p = db_type.placeholder(*param_index);
+            *param_index += 1;
+            bindings.push(&value.0);
+            match (&value.1, db_type) {
+                (Some(ty), DatabaseType::Postgres) => format!("CAST({} AS {})", p, ty),
+                _ => p,
+            }
+        };
+
+        sql.push('(');
+        for (i, (col, dir)) in self.order_by.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(" OR ");
+            }
+            sql.push('(');
+            for (j, (prev, _)) in self.order_by[..i].iter().enumerate() {
+                write!(sql, "{} = {} AND ", prev, placeholder(&self.values[j])).unwrap();
+            }
+            let asc = dir != "DESC";
+            let op = if asc != self.backward { ">" } else { "<" };
+            write!(sql, "{} {} {})", col, op, placeholder(&self.values[i])).unwrap();
+        }
+        sql.push(')');
+    }
+
+    fn cursor(&self, keys: Vec<CursorKey>) -> Cursor {
+        Cursor::sign(&self.spec, keys, cursor_secret())
+    }
+
+    /// Build the page from `per_page + 1` fetched rows
+    pub(crate) fn page<T>(
+        &self,
+        mut rows: Vec<(T, Vec<CursorKey>)>,
+        per_page: i64,
+    ) -> CursorPage<T> {
+        let has_more = rows.len() as i64 > per_page;
+        rows.truncate(per_page as usize);
+        if self.backward {
+            rows.reverse();
+        }
+
+        let (data, cursors): (Vec<T>, Vec<Cursor>) = rows
+            .into_iter()
+            .map(|(row, keys)| (row, self.cursor(keys)))
+            .unzip();
+
+        let (has_next, has_previous) = if self.backward {
+            (self.has_position(), has_more)
+        } else {
+            (has_more, self.has_position())
+        };
+
+        CursorPage {
+            next_cursor: cursors.last().cloned().filter(|_| has_next),
+            prev_cursor: cursors.first().cloned().filter(|_| has_previous),
+            data,
+            cursors,
+            per_page,
+        }
+    }
+
+    /// Column names to read cursor values from
+    pub(crate) fn columns(&self) -> Vec<&str> {
+        self.order_by
+            .iter()
+            .map(|(col, _)| column_name(col))
+            .collect()
+    }
+}
+
+/// Strip a `table.` qualifier from an ORDER BY column
+fn column_name(col: &str) -> &str {
+    col.rsplit('.').next().unwrap_or(col)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ROW VALUES
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn null_key(col: &str) -> sqlx::Error {
+    sqlx::Error::Configuration(format!("Cursor column '{}' is NULL", col).into())
+}
+
+fn unsupported_key(col: &str, ty: &str) -> sqlx::Error {
+    sqlx::Error::Configuration(
+        format!("Cursor column '{}' has unsupported type {}", col, ty).into(),
+    )
+}
+
+/// Read cursor values from a SQLite row
+pub(crate) fn sqlite_keys(row: &SqliteRow, cols: &[&str]) -> Result<Vec<CursorKey>, sqlx::Error> {
+    cols.iter()
+        .map(|&col| {
+            let raw = row.try_get_raw(col)?;
+            if raw.is_null() {
+                return Err(null_key(col));
+            }
+            let ty = raw.type_info().name().to_string();
+            Ok(match ty.as_str() {
+                "INTEGER" | "BOOLEAN" => CursorKey::Int(row.try_get_unchecked(col)?),
+                "REAL" => CursorKey::Float(row.try_get_unchecked(col)?),
+                "BLOB" => CursorKey::Bytes(row.try_get_unchecked(col)?),
+                _ => CursorKey::Text(row.try_get_unchecked(col)?),
+            })
+        })
+        .collect()
+}
+
+/// Read cursor values from a PostgreSQL row
+pub(crate) fn pg_keys(row: &PgRow, cols: &[&str]) -> Result<Vec<CursorKey>, sqlx::Error> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+    cols.iter()
+        .map(|&col| {
+            let raw = row.try_get_raw(col)?;
+            if raw.is_null() {
+                return Err(null_key(col));
+            }
+            let ty = raw.type_info().name().to_string();
+            Ok(match ty.as_str() {
+                "INT2" => CursorKey::Int(row.try_get::<i16, _>(col)? as i64),
+                "INT4" => CursorKey::Int(row.try_get::<i32, _>(col)? as i64),
+                "INT8" => CursorKey::Int(row.try_get(col)?),
+                "FLOAT4" => CursorKey::Float(row.try_get::<f32, _>(col)? as f64),
+                "FLOAT8" => CursorKey::Float(row.try_get(col)?),
+                "BOOL" => CursorKey::Bool(row.try_get(col)?),
+                "BYTEA" => CursorKey::Bytes(row.try_get(col)?),
+                "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" | "CITEXT" => {
+                    CursorKey::Text(row.try_get(col)?)
+                }
+                "TIMESTAMPTZ" => CursorKey::Cast(
+                    row.try_get::<DateTime<Utc>, _>(col)?.to_rfc3339(),
+                    ty.clone(),
+                ),
+                "TIMESTAMP" => CursorKey::Cast(
+                    row.try_get::<NaiveDateTime, _>(col)?
+                        .format("%Y-%m-%d %H:%M:%S%.f")
+                        .to_string(),
+                    ty.clone(),
+                ),
+                "DATE" => {
+                    CursorKey::Cast(row.try_get::<NaiveDate, _>(col)?.to_string(), ty.clone())
+                }
+                "TIME" => {
+                    CursorKey::Cast(row.try_get::<NaiveTime, _>(col)?.to_string(), ty.clone())
+                }
+                _ => return Err(unsupported_key(col, &ty)),
+            })
+        })
+        .collect()
+}
+
+/// Read cursor values from a MySQL row
+pub(crate) fn mysql_keys(row: &MySqlRow, cols: &[&str]) -> Result<Vec<CursorKey>, sqlx::Error> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    cols.iter()
+        .map(|&col| {
+            let raw = row.try_get_raw(col)?;
+            if raw.is_null() {
+                return Err(null_key(col));
+            }
+            let ty = raw.type_info().name().to_string();
+            Ok(match ty.as_str() {
+                "BOOLEAN" | "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT"
+                | "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED"
+                | "INT UNSIGNED" => CursorKey::Int(row.try_get_unchecked(col)?),
+                "BIGINT UNSIGNED" => CursorKey::Int(row.try_get::<u64, _>(col)? as i64),
+                "FLOAT" | "DOUBLE" => CursorKey::Float(row.try_get_unchecked(col)?),
+                "DATETIME" => CursorKey::Text(
+                    row.try_get::<NaiveDateTime, _>(col)?
+                        .format("%Y-%m-%d %H:%M:%S%.f")
+                        .to_string(),
+                ),
+                "TIMESTAMP" => CursorKey::Text(
+                    row.try_get::<DateTime<Utc>, _>(col)?
+                        .format("%Y-%m-%d %H:%M:%S%.f")
+                        .to_string(),
+                ),
+                "DATE" => CursorKey::Text(row.try_get::<NaiveDate, _>(col)?.to_string()),
+                "BINARY" | "VARBINARY" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+                    CursorKey::Bytes(row.try_get(col)?)
+                }
+                _ => CursorKey::Text(row.try_get_unchecked(col)?),
+            })
+        })
+        .collect()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photon::db::{db, test_db};
+    use crate::photon::query::Builder;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Entry {
+        id: i64,
+        score: i64,
+    }
+
+    async fn seed_cursor_table() -> tokio::sync::MutexGuard<'static, ()> {
+        let guard = test_db().await;
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS cursor_entries (id INTEGER PRIMARY KEY, score INTEGER NOT NULL);
+             INSERT OR IGNORE INTO cursor_entries VALUES
+                (1, 50), (2, 40), (3, 50), (4, 30), (5, 40), (6, 20), (7, 50);",
+        )
+        .execute(db().as_sqlite().unwrap())
+        .await
+        .unwrap();
+        guard
+    }
+
+    fn ids(page: &CursorPage<Entry>) -> Vec<i64> {
+        page.data.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_cursor_round_trip_and_tamper() {
+        let cursor = Cursor::sign("t.id ASC", vec![CursorKey::Int(7)], b"secret");
+        let decoded = Cursor::decode_with(cursor.as_str(), b"secret").unwrap();
+        assert_eq!(decoded, cursor);
+
+        assert!(Cursor::decode_with(cursor.as_str(), b"other").is_err());
+        let forged = Cursor::sign("t.id ASC", vec![CursorKey::Int(8)], b"secret");
+        let (payload, _) = forged.as_str().split_once('.').unwrap();
+        let (_, sig) = cursor.as_str().split_once('.').unwrap();
+        assert!(Cursor::decode_with(&format!("{}.{}", payload, sig), b"secret").is_err());
+        assert!(Cursor::decode_with("garbage", b"secret").is_err());
+    }
+
+    #[test]
+    fn test_keyset_sql_mixed_directions() {
+        let order = vec![("score".to_string(), "DESC".to_string())];
+        let cursor = Cursor::sign(
+            "score DESC,posts.id DESC",
+            vec![CursorKey::Int(40), CursorKey::Int(5)],
+            b"photon-cursor-tests",
+        );
+        let keyset = Keyset::new("posts", &order, Some(cursor), false).unwrap();
+
+        let mut sql = String::new();
+        let mut bindings = Vec::new();
+        let mut index = 1;
+        keyset.write_sql(&mut sql, &mut bindings, &mut index, DatabaseType::Postgres);
+        assert_eq!(sql, "((score < $1) OR (score = $2 AND posts.id < $3))");
+        assert_eq!(bindings.len(), 3);
+
+        let backward = Keyset::new("posts", &order, None, true).unwrap();
+        assert_eq!(
+            backward.query_order(),
+            vec![
+                ("score".to_string(), "ASC".to_string()),
+                ("posts.id".to_string(), "ASC".to_string())
+            ]
+        );
+
+        let other = Cursor::sign("id ASC", vec![CursorKey::Int(1)], b"photon-cursor-tests");
+        assert!(Keyset::new("posts", &order, Some(other), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_cursor_walks_forward_and_back() {
+        let _db = seed_cursor_table().await;
+        let query = || Builder::new("cursor_entries").order_by("score", "DESC");
+
+        let first = query().paginate_cursor::<Entry>(None, 3).await.unwrap();
+        assert_eq!(ids(&first), vec![7, 3, 1]);
+        assert!(first.data.iter().all(|e| e.score == 50));
+        assert!(!first.has_previous_page());
+
+        let after = Cursor::decode(first.next_cursor.as_ref().unwrap().as_str()).unwrap();
+        let second = query()
+            .paginate_cursor::<Entry>(Some(after), 3)
+            .await
+            .unwrap();
+        assert_eq!(ids(&second), vec![5, 2, 4]);
+        assert!(second.has_next_page());
+
+        let last = query()
+            .paginate_cursor::<Entry>(second.next_cursor.clone(), 3)
+            .await
+            .unwrap();
+        assert_eq!(ids(&last), vec![6]);
+        assert!(!last.has_next_page());
+
+        let back = query()
+            .paginate_cursor_before::<Entry>(second.prev_cursor.clone(), 3)
+            .await
+            .unwrap();
+        assert_eq!(ids(&back), ids(&first));
+        assert!(!back.has_previous_page());
+        assert!(back.has_next_page());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_cursor_keeps_filters_grouped() {
+        let _db = seed_cursor_table().await;
+        let query = || {
+            Builder::new("cursor_entries")
+                .r#where("score", 50)
+                .or_where("score", 20)
+                .order_by("id", "ASC")
+        };
+
+        let first = query().paginate_cursor::<Entry>(None, 2).await.unwrap();
+        assert_eq!(ids(&first), vec![1, 3]);
+        let rest = query()
+            .paginate_cursor::<Entry>(first.next_cursor, 2)
+            .await
+            .unwrap();
+        assert_eq!(ids(&rest), vec![6, 7]);
+        assert!(rest.next_cursor.is_none());
+    }
+}
@@ -13,7 +13,17 @@
 //! init_db("sqlite://./data.db").await?;
 //! ```
 
-use std::sync::OnceLock;
+use crate::config::DatabaseConfig;
+use crate::tenant::{register_tenant, schema_routing, tenant_info, Isolation, Tenant, TenantInfo};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use sqlx::pool::PoolConnection;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // DATABASE TYPE DETECTION
@@ -81,6 +91,18 @@ impl std::fmt::Debug for DatabasePool {
     }
 }
 
+/// Runs the same sqlx expression against whichever backend the pool holds
+macro_rules! on_pool {
+    ($pool:expr, $p:ident => $body:expr) => {
+        match $pool {
+            $crate::photon::DatabasePool::Sqlite($p) => $body,
+            $crate::photon::DatabasePool::Postgres($p) => $body,
+            $crate::photon::DatabasePool::MySql($p) => $body,
+        }
+    };
+}
+pub(crate) use on_pool;
+
 impl DatabasePool {
     /// Connect to a database using the URL
     ///
@@ -89,17 +111,37 @@ impl DatabasePool {
     /// - `mysql://` or `mariadb://` → MySQL
     /// - `sqlite://` or `sqlite:` → SQLite
     pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        Self::connect_with_max(url, 10).await
+    }
+
+    /// Connect with an explicit pool size
+    pub async fn connect_with_max(url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
         let db_type = DatabaseType::from_url(url).ok_or_else(|| {
             sqlx::Error::Configuration(format!("Unknown database URL format: {}", url).into())
         })?;
 
         match db_type {
             DatabaseType::Postgres => {
-                let pool = sqlx::PgPool::connect(url).await?;
+                let pool = sqlx::postgres::PgPoolOptions::new()
+                    .max_connections(max_connections)
+                    // A schema tenant's search_path must not outlive its checkout
+                    .after_release(|conn, _| {
+                        Box::pin(async move {
+                            if schema_routing() {
+                                sqlx::query("RESET search_path").execute(&mut *conn).await?;
+                            }
+                            Ok(true)
+                        })
+                    })
+                    .connect(url)
+                    .await?;
                 Ok(Self::Postgres(pool))
             }
             DatabaseType::MySql => {
-                let pool = sqlx::MySqlPool::connect(url).await?;
+                let pool = sqlx::mysql::MySqlPoolOptions::new()
+                    .max_connections(max_connections)
+                    .connect(url)
+                    .await?;
                 Ok(Self::MySql(pool))
             }
             DatabaseType::Sqlite => {
@@ -110,7 +152,10 @@ impl DatabasePool {
                         sqlx::Error::Configuration(format!("Invalid SQLite URL: {}", e).into())
                     })?
                     .create_if_missing(true);
-                let pool = sqlx::SqlitePool::connect_with(options).await?;
+                let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                    .max_connections(max_connections)
+                    .connect_with(options)
+                    .await?;
                 Ok(Self::Sqlite(pool))
             }
         }
@@ -162,13 +207,143 @@ impl DatabasePool {
             Self::Sqlite(pool) => pool.close().await,
         }
     }
+
+    /// Begin a transaction on this pool
+    ///
+    /// Ignores the current tenant; use `photon::begin()` for tenant-routed
+    /// transactions.
+    pub async fn begin(&self) -> Result<DatabaseTransaction, sqlx::Error> {
+        Ok(match self {
+            Self::Postgres(pool) => DatabaseTransaction::Postgres(pool.begin().await?),
+            Self::MySql(pool) => DatabaseTransaction::MySql(pool.begin().await?),
+            Self::Sqlite(pool) => DatabaseTransaction::Sqlite(pool.begin().await?),
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TRANSACTIONS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// An open transaction on any backend
+///
+/// Run builder queries in it with `Builder::execute_in`/`all_in`. Dropping
+/// it without `commit()` rolls back.
+pub enum DatabaseTransaction {
+    Postgres(sqlx::Transaction<'static, sqlx::Postgres>),
+    MySql(sqlx::Transaction<'static, sqlx::MySql>),
+    Sqlite(sqlx::Transaction<'static, sqlx::Sqlite>),
+}
+
+impl std::fmt::Debug for DatabaseTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DatabaseTransaction::{:?}", self.db_type())
+    }
+}
+
+impl DatabaseTransaction {
+    /// Get the database type
+    pub fn db_type(&self) -> DatabaseType {
+        match self {
+            Self::Postgres(_) => DatabaseType::Postgres,
+            Self::MySql(_) => DatabaseType::MySql,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+        }
+    }
+
+    /// Commit the transaction
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        match self {
+            Self::Postgres(tx) => tx.commit().await,
+            Self::MySql(tx) => tx.commit().await,
+            Self::Sqlite(tx) => tx.commit().await,
+        }
+    }
+
+    /// Roll the transaction back
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        match self {
+            Self::Postgres(tx) => tx.rollback().await,
+            Self::MySql(tx) => tx.rollback().await,
+            Self::Sqlite(tx) => tx.rollback().await,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CLUSTERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A primary pool plus its read replicas
+///
+/// Reads rotate across the replicas; writes, and reads that must see this
+/// request's writes (see `sticky`), go to the primary.
+#[derive(Debug)]
+pub struct DatabaseCluster {
+    primary: DatabasePool,
+    replicas: Vec<DatabasePool>,
+    next_replica: AtomicUsize,
+}
+
+impl DatabaseCluster {
+    /// Group an existing primary pool with its replicas
+    pub fn new(primary: DatabasePool, replicas: Vec<DatabasePool>) -> Self {
+        Self {
+            primary,
+            replicas,
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    /// Connect to a primary and its replicas
+    pub async fn connect(
+        url: &str,
+        replicas: &[String],
+        max_connections: u32,
+    ) -> Result<Self, sqlx::Error> {
+        let primary = DatabasePool::connect_with_max(url, max_connections).await?;
+        let mut replica_pools = Vec::with_capacity(replicas.len());
+        for replica in replicas {
+            let pool = DatabasePool::connect_with_max(replica, max_connections).await?;
+            if pool.db_type() != primary.db_type() {
+                return Err(sqlx::Error::Configuration(
+                    format!(
+                        "Replica {} is not a {} database",
+                        replica,
+                        primary.db_type().name()
+                    )
+                    .into(),
+                ));
+            }
+            replica_pools.push(pool);
+        }
+        Ok(Self::new(primary, replica_pools))
+    }
+
+    /// The pool that receives writes
+    pub fn primary(&self) -> &DatabasePool {
+        &self.primary
+    }
+
+    /// The pool for the next read
+    ///
+    /// Returns the primary when there are no replicas or the current
+    /// `sticky` scope has written.
+    pub fn reader(&self) -> &DatabasePool {
+        if self.replicas.is_empty() || has_written() {
+            return &self.primary;
+        }
+        let i = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        &self.replicas[i % self.replicas.len()]
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
 // GLOBAL POOL
 // ═══════════════════════════════════════════════════════════════════════════
 
-static GLOBAL_DB: OnceLock<DatabasePool> = OnceLock::new();
+static GLOBAL_DB: OnceLock<DatabaseCluster> = OnceLock::new();
+static NAMED_DBS: OnceLock<RwLock<HashMap<String, Arc<DatabaseCluster>>>> = OnceLock::new();
 
 /// Initialize the global database connection
 ///
@@ -181,21 +356,377 @@ static GLOBAL_DB: OnceLock<DatabasePool> = OnceLock::new();
 /// ```
 pub async fn init_db(url: &str) -> Result<(), sqlx::Error> {
     let pool = DatabasePool::connect(url).await?;
-    GLOBAL_DB
-        .set(pool)
-        .map_err(|_| sqlx::Error::Configuration("Database already initialized".into()))?;
+    set_default(DatabaseCluster::new(pool, Vec::new()))
+}
+
+/// Initialize the primary, its replicas and all named databases from config
+///
+/// # Example
+///
+/// ```toml
+/// [database]
+/// url = "postgres://primary/app"
+/// replicas = ["postgres://replica1/app"]
+///
+/// [database.connections.analytics]
+/// url = "postgres://warehouse/analytics"
+/// ```
+///
+/// ```rust,ignore
+/// init_databases(&Config::load().database).await?;
+/// ```
+pub async fn init_databases(config: &DatabaseConfig) -> Result<(), sqlx::Error> {
+    let default =
+        DatabaseCluster::connect(&config.url, &config.replicas, config.max_connections).await?;
+    set_default(default)?;
+
+    for (name, named) in &config.connections {
+        let cluster =
+            DatabaseCluster::connect(&named.url, &named.replicas, named.max_connections).await?;
+        register_database(name, cluster);
+    }
+
+    configure_tenant_pools(config.tenant_pools, config.tenant_max_connections);
+    for (id, tenant) in &config.tenants {
+        let isolation = match (&tenant.schema, &tenant.url) {
+            (Some(schema), None) => Isolation::Schema(schema.clone()),
+            (None, Some(url)) => Isolation::Database(url.clone()),
+            _ => {
+                return Err(sqlx::Error::Configuration(
+                    format!("Tenant {} needs exactly one of `schema` or `url`", id).into(),
+                ))
+            }
+        };
+        register_tenant(TenantInfo::new(id, id).with_isolation(isolation))
+            .map_err(|e| sqlx::Error::Configuration(e.to_string().into()))?;
+    }
     Ok(())
 }
 
+fn set_default(cluster: DatabaseCluster) -> Result<(), sqlx::Error> {
+    GLOBAL_DB
+        .set(cluster)
+        .map_err(|_| sqlx::Error::Configuration("Database already initialized".into()))
+}
+
+/// Register a named database for `Builder::on(name)`
+///
+/// Replaces any database previously registered under the same name; the
+/// old pools close once queries still holding them finish.
+pub fn register_database(name: &str, cluster: DatabaseCluster) {
+    let cluster = Arc::new(cluster);
+    NAMED_DBS
+        .get_or_init(Default::default)
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), cluster);
+}
+
+/// Get a named database registered with `init_databases` or `register_database`
+pub fn database(name: &str) -> Option<Arc<DatabaseCluster>> {
+    NAMED_DBS
+        .get()?
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+}
+
+fn default_cluster() -> &'static DatabaseCluster {
+    GLOBAL_DB
+        .get()
+        .expect("Database not initialized. Call init_db() first.")
+}
+
 /// Get reference to the global database pool
 ///
+/// This is the primary; use `db_read()` for replica-routed reads.
+///
 /// # Panics
 ///
 /// Panics if the database has not been initialized with `init_db()`.
 pub fn db() -> &'static DatabasePool {
-    GLOBAL_DB
-        .get()
-        .expect("Database not initialized. Call init_db() first.")
+    &default_cluster().primary
+}
+
+/// Get the global pool for a read, honoring replicas and stickiness
+///
+/// # Panics
+///
+/// Panics if the database has not been initialized with `init_db()`.
+pub fn db_read() -> &'static DatabasePool {
+    default_cluster().reader()
+}
+
+/// Begin a transaction on the default database's primary
+///
+/// Follows the current tenant like builder queries do: into its own
+/// database, or into its schema for the transaction's duration. Prefer this
+/// over `db().begin()`, which always uses the shared database.
+///
+/// # Panics
+///
+/// Panics if the database has not been initialized with `init_db()`.
+pub async fn begin() -> Result<DatabaseTransaction, sqlx::Error> {
+    route(None, true).await?.begin().await
+}
+
+/// Resolve the pool for a query on `connection` (default database if `None`)
+///
+/// Writes go to the primary and make the current `sticky` scope read from
+/// primaries from then on. Queries on the default database follow the
+/// current tenant's `Isolation` into its schema or its own database.
+pub(crate) async fn route(connection: Option<&str>, write: bool) -> Result<Routed, sqlx::Error> {
+    let cluster = match connection {
+        None => {
+            let isolation = Tenant::get()
+                .and_then(|id| tenant_info(&id))
+                .map(|info| (info.id, info.isolation));
+            match isolation {
+                Some((id, Isolation::Database(url))) => {
+                    if write {
+                        mark_written();
+                    }
+                    return Ok(Routed::new(tenant_pool(&id, &url).await?, None));
+                }
+                Some((id, Isolation::Schema(schema))) => {
+                    let pool = pick(default_cluster(), write);
+                    if pool.db_type() != DatabaseType::Postgres {
+                        return Err(sqlx::Error::Configuration(
+                            format!("Tenant {} uses a schema, which needs PostgreSQL", id).into(),
+                        ));
+                    }
+                    return Ok(Routed::new(pool.clone(), Some(schema)));
+                }
+                Some((_, Isolation::Shared)) | None => default_cluster(),
+            }
+        }
+        Some(name) => {
+            let cluster = database(name).ok_or_else(|| {
+                sqlx::Error::Configuration(format!("Unknown database connection: {}", name).into())
+            })?;
+            return Ok(Routed::new(pick(&cluster, write).clone(), None));
+        }
+    };
+    Ok(Routed::new(pick(cluster, write).clone(), None))
+}
+
+fn pick(cluster: &DatabaseCluster, write: bool) -> &DatabasePool {
+    if write {
+        mark_written();
+        &cluster.primary
+    } else {
+        cluster.reader()
+    }
+}
+
+/// The pool a query was routed to, and the tenant schema it runs in
+#[derive(Debug, Clone)]
+pub(crate) struct Routed {
+    pool: DatabasePool,
+    search_path: Option<String>,
+}
+
+impl Routed {
+    pub(crate) fn new(pool: DatabasePool, schema: Option<String>) -> Self {
+        Self {
+            pool,
+            // Only the tenant schema, so a missing table errors instead of
+            // silently reading the shared one in `public`
+            search_path: schema.map(|s| format!("\"{}\"", s.replace('"', "\"\""))),
+        }
+    }
+
+    pub(crate) fn pool(&self) -> &DatabasePool {
+        &self.pool
+    }
+
+    pub(crate) fn db_type(&self) -> DatabaseType {
+        self.pool.db_type()
+    }
+
+    /// Check out a PostgreSQL connection, switched to the tenant's schema
+    pub(crate) async fn checkout(
+        &self,
+        pool: &sqlx::PgPool,
+    ) -> Result<PoolConnection<sqlx::Postgres>, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        if let Some(path) = &self.search_path {
+            sqlx::query("SELECT set_config('search_path', $1, false)")
+                .bind(path)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(conn)
+    }
+
+    /// Begin a transaction, in the tenant's schema for its duration
+    pub(crate) async fn begin(&self) -> Result<DatabaseTransaction, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if let (Some(path), DatabaseTransaction::Postgres(t)) = (&self.search_path, &mut tx) {
+            sqlx::query("SELECT set_config('search_path', $1, true)")
+                .bind(path)
+                .execute(&mut **t)
+                .await?;
+        }
+        Ok(tx)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TENANT DATABASES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Pools for database-per-tenant isolation, least recently used first out
+///
+/// An evicted pool closes once the queries still holding it finish.
+struct TenantPools {
+    capacity: usize,
+    max_connections: u32,
+    clock: u64,
+    pools: HashMap<String, TenantPool>,
+}
+
+struct TenantPool {
+    url: String,
+    last_used: u64,
+    pool: Arc<tokio::sync::OnceCell<DatabasePool>>,
+}
+
+impl TenantPools {
+    fn new(capacity: usize, max_connections: u32) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_connections,
+            clock: 0,
+            pools: HashMap::new(),
+        }
+    }
+
+    /// The pool slot for `tenant`, marked as just used
+    fn slot(&mut self, tenant: &str, url: &str) -> Arc<tokio::sync::OnceCell<DatabasePool>> {
+        self.clock += 1;
+        let now = self.clock;
+        let entry = self
+            .pools
+            .entry(tenant.to_string())
+            .or_insert_with(|| TenantPool {
+                url: url.to_string(),
+                last_used: now,
+                pool: Arc::default(),
+            });
+        if entry.url != url {
+            // The tenant moved; stop handing out the old database
+            entry.url = url.to_string();
+            entry.pool = Arc::default();
+        }
+        entry.last_used = now;
+        let slot = Arc::clone(&entry.pool);
+
+        while self.pools.len() > self.capacity {
+            let oldest = self
+                .pools
+                .iter()
+                .min_by_key(|(_, p)| p.last_used)
+                .map(|(id, _)| id.clone())
+                .expect("pools is not empty");
+            self.pools.remove(&oldest);
+        }
+        slot
+    }
+}
+
+static TENANT_POOLS: OnceLock<Mutex<TenantPools>> = OnceLock::new();
+
+fn tenant_pools() -> &'static Mutex<TenantPools> {
+    TENANT_POOLS.get_or_init(|| Mutex::new(TenantPools::new(16, 5)))
+}
+
+/// Bound the pools kept open for database-per-tenant isolation
+///
+/// At most `capacity` tenant databases stay connected, each with up to
+/// `max_connections` connections; the least recently used is closed to make
+/// room. Set from `[database] tenant_pools`/`tenant_max_connections`.
+pub fn configure_tenant_pools(capacity: usize, max_connections: u32) {
+    let mut pools = tenant_pools().lock().unwrap_or_else(|e| e.into_inner());
+    pools.capacity = capacity.max(1);
+    pools.max_connections = max_connections;
+}
+
+/// Get (connecting on first use) the pool for a tenant's own database
+pub(crate) async fn tenant_pool(tenant: &str, url: &str) -> Result<DatabasePool, sqlx::Error> {
+    let (slot, max_connections) = {
+        let mut pools = tenant_pools().lock().unwrap_or_else(|e| e.into_inner());
+        (pools.slot(tenant, url), pools.max_connections)
+    };
+    slot.get_or_try_init(|| DatabasePool::connect_with_max(url, max_connections))
+        .await
+        .cloned()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// READ-YOUR-WRITES
+// ═══════════════════════════════════════════════════════════════════════════
+
+tokio::task_local! {
+    static WRITTEN: Cell<bool>;
+}
+
+/// Run `fut` in a read-your-writes scope
+///
+/// Once a write happens inside the scope, later reads in it go to the
+/// primary so they can't observe replica lag. Nested scopes share the
+/// outer one. Tasks spawned from inside the scope are not covered.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use nucleus_std::photon::db::sticky;
+///
+/// sticky(async {
+///     Post::create().value("title", "Hello").execute().await?;
+///     // Served by the primary, so the new post is visible
+///     Post::query().all::<Post>().await
+/// })
+/// .await?;
+/// ```
+pub async fn sticky<F: Future>(fut: F) -> F::Output {
+    if WRITTEN.try_with(|_| ()).is_ok() {
+        fut.await
+    } else {
+        WRITTEN.scope(Cell::new(false), fut).await
+    }
+}
+
+/// Run `fut` with every read going to the primary
+///
+/// Used around transactions, whose reads must see their own writes.
+pub(crate) async fn on_primary<F: Future>(fut: F) -> F::Output {
+    if WRITTEN.try_with(|w| w.set(true)).is_ok() {
+        fut.await
+    } else {
+        WRITTEN.scope(Cell::new(true), fut).await
+    }
+}
+
+/// Axum middleware giving each request a `sticky` scope
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/posts", post(create_post))
+///     .layer(axum::middleware::from_fn(sticky_reads));
+/// ```
+pub async fn sticky_reads(req: Request, next: Next) -> Response {
+    sticky(next.run(req)).await
+}
+
+/// Send the rest of the current `sticky` scope's reads to the primary
+pub fn mark_written() {
+    let _ = WRITTEN.try_with(|w| w.set(true));
+}
+
+fn has_written() -> bool {
+    WRITTEN.try_with(|w| w.get()).unwrap_or(false)
 }
 
 /// Check if the database has been initialized
@@ -216,6 +747,7 @@ pub enum QueryValue {
     Bool(bool),
     Null,
     Bytes(Vec<u8>),
+    Timestamp(chrono::DateTime<chrono::Utc>),
 }
 
 impl From<&str> for QueryValue {
@@ -266,6 +798,12 @@ impl From<bool> for QueryValue {
     }
 }
 
+impl From<chrono::DateTime<chrono::Utc>> for QueryValue {
+    fn from(v: chrono::DateTime<chrono::Utc>) -> Self {
+        QueryValue::Timestamp(v)
+    }
+}
+
 impl<T> From<Option<T>> for QueryValue
 where
     T: Into<QueryValue>,
@@ -278,6 +816,74 @@ where
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// QUERY RESULT (backend-neutral)
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Result of an INSERT/UPDATE/DELETE, independent of the database backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryResult {
+    rows_affected: u64,
+    last_insert_id: Option<i64>,
+}
+
+impl QueryResult {
+    /// Create a result from raw parts
+    pub fn new(rows_affected: u64, last_insert_id: Option<i64>) -> Self {
+        Self {
+            rows_affected,
+            last_insert_id,
+        }
+    }
+
+    /// Number of rows affected by the statement
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+
+    /// ID generated by the last INSERT
+    ///
+    /// Always `None` on PostgreSQL, which has no implicit last-insert ID;
+    /// use `Builder::insert_get_id()` there (it appends `RETURNING id`).
+    pub fn last_insert_id(&self) -> Option<i64> {
+        self.last_insert_id
+    }
+}
+
+impl From<sqlx::sqlite::SqliteQueryResult> for QueryResult {
+    fn from(r: sqlx::sqlite::SqliteQueryResult) -> Self {
+        Self::new(r.rows_affected(), Some(r.last_insert_rowid()))
+    }
+}
+
+impl From<sqlx::postgres::PgQueryResult> for QueryResult {
+    fn from(r: sqlx::postgres::PgQueryResult) -> Self {
+        Self::new(r.rows_affected(), None)
+    }
+}
+
+impl From<sqlx::mysql::MySqlQueryResult> for QueryResult {
+    fn from(r: sqlx::mysql::MySqlQueryResult) -> Self {
+        Self::new(r.rows_affected(), Some(r.last_insert_id() as i64))
+    }
+}
+
+/// Default database for tests, held exclusively while the guard lives
+///
+/// Model APIs always use the process-wide default database, so tests that
+/// touch it share one in-memory SQLite and run one at a time.
+#[cfg(test)]
+pub(crate) async fn test_db() -> tokio::sync::MutexGuard<'static, ()> {
+    static LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+    let guard = LOCK.lock().await;
+    if GLOBAL_DB.get().is_none() {
+        init_db("sqlite:file:photon_tests?mode=memory&cache=shared")
+            .await
+            .expect("test database");
+    }
+    guard
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // TESTS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -347,4 +953,247 @@ mod tests {
         ));
         assert!(matches!(QueryValue::from(Some(42i64)), QueryValue::Int(42)));
     }
+
+    #[test]
+    fn test_query_result_accessors() {
+        let result = QueryResult::new(3, Some(42));
+        assert_eq!(result.rows_affected(), 3);
+        assert_eq!(result.last_insert_id(), Some(42));
+
+        let pg = QueryResult::new(1, None);
+        assert_eq!(pg.last_insert_id(), None);
+        assert_eq!(QueryResult::default().rows_affected(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_replaced_database_is_released() {
+        let cluster = || async {
+            let pool = DatabasePool::connect("sqlite::memory:").await.unwrap();
+            DatabaseCluster::new(pool, Vec::new())
+        };
+        register_database("replaced_test", cluster().await);
+        let held = database("replaced_test").unwrap();
+        let old = Arc::downgrade(&held);
+
+        register_database("replaced_test", cluster().await);
+        assert!(!Arc::ptr_eq(&held, &database("replaced_test").unwrap()));
+        // In-flight users keep the old pools alive, then they are dropped
+        assert!(old.upgrade().is_some());
+        drop(held);
+        assert!(old.upgrade().is_none());
+    }
+
+    async fn routing_cluster() {
+        if database("routing_test").is_some() {
+            return;
+        }
+        let mut pools = Vec::new();
+        for name in ["primary", "replica"] {
+            let pool = DatabasePool::connect(&format!(
+                "sqlite:file:photon_routing_{}?mode=memory&cache=shared",
+                name
+            ))
+            .await
+            .unwrap();
+            sqlx::raw_sql(&format!(
+                "CREATE TABLE IF NOT EXISTS routing_items (id INTEGER PRIMARY KEY, src TEXT NOT NULL);
+                 INSERT OR IGNORE INTO routing_items VALUES (1, '{}');",
+                name
+            ))
+            .execute(pool.as_sqlite().unwrap())
+            .await
+            .unwrap();
+            pools.push(pool);
+        }
+        let replica = pools.pop().unwrap();
+        let primary = pools.pop().unwrap();
+        register_database("routing_test", DatabaseCluster::new(primary, vec![replica]));
+    }
+
+    async fn read_src() -> String {
+        use crate::photon::query::Builder;
+        let row: Option<(String,)> = Builder::new("routing_items")
+            .on("routing_test")
+            .select(&["src"])
+            .r#where("id", 1)
+            .first()
+            .await
+            .unwrap();
+        row.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_reads_use_replica_until_written() {
+        use crate::photon::query::Builder;
+        routing_cluster().await;
+
+        assert_eq!(read_src().await, "replica");
+
+        sticky(async {
+            assert_eq!(read_src().await, "replica");
+            Builder::new("routing_items")
+                .on("routing_test")
+                .update()
+                .value("src", "primary")
+                .r#where("id", 1)
+                .execute()
+                .await
+                .unwrap();
+            assert_eq!(read_src().await, "primary");
+            sticky(async { assert_eq!(read_src().await, "primary") }).await;
+        })
+        .await;
+
+        // Outside the scope reads go back to the replica
+        assert_eq!(read_src().await, "replica");
+    }
+
+    #[test]
+    fn test_tenant_pools_evict_least_recently_used() {
+        let mut pools = TenantPools::new(2, 1);
+        let a = pools.slot("a", "sqlite:a.db");
+        pools.slot("b", "sqlite:b.db");
+        assert!(Arc::ptr_eq(&a, &pools.slot("a", "sqlite:a.db")));
+
+        // "b" is now the least recently used
+        pools.slot("c", "sqlite:c.db");
+        let mut open: Vec<&String> = pools.pools.keys().collect();
+        open.sort();
+        assert_eq!(open, vec!["a", "c"]);
+
+        // A changed URL gets a fresh pool
+        assert!(!Arc::ptr_eq(&a, &pools.slot("a", "sqlite:moved.db")));
+    }
+
+    #[tokio::test]
+    async fn test_database_per_tenant_routing() {
+        use crate::photon::query::Builder;
+
+        let tenants = ["db_iso_a", "db_iso_b"];
+        for id in tenants {
+            let url = format!("sqlite:file:photon_{}?mode=memory&cache=shared", id);
+            register_tenant(TenantInfo::new(id, id).with_database(&url)).unwrap();
+            Tenant::scope(id, async {
+                let routed = route(None, true).await.unwrap();
+                sqlx::query("CREATE TABLE IF NOT EXISTS iso_items (name TEXT NOT NULL)")
+                    .execute(routed.pool().as_sqlite().unwrap())
+                    .await
+                    .unwrap();
+                Builder::new("iso_items")
+                    .insert()
+                    .value("name", id)
+                    .execute()
+                    .await
+                    .unwrap();
+            })
+            .await;
+        }
+
+        for id in tenants {
+            let names: Vec<(String,)> = Tenant::scope(id, async {
+                Builder::new("iso_items").select(&["name"]).all().await
+            })
+            .await
+            .unwrap();
+            assert_eq!(names, vec![(id.to_string(),)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transactions_follow_tenant() {
+        use crate::photon::query::{transaction_sqlite, Builder};
+
+        let id = "db_iso_tx";
+        let url = format!("sqlite:file:photon_{}?mode=memory&cache=shared", id);
+        register_tenant(TenantInfo::new(id, id).with_database(&url)).unwrap();
+        // Keeps the shared in-memory database alive between transactions
+        let tenant_db = DatabasePool::connect(&url).await.unwrap();
+        sqlx::query("CREATE TABLE IF NOT EXISTS tx_items (name TEXT NOT NULL)")
+            .execute(tenant_db.as_sqlite().unwrap())
+            .await
+            .unwrap();
+
+        Tenant::scope(id, async {
+            let mut tx = begin().await.unwrap();
+            Builder::new("tx_items")
+                .insert()
+                .value("name", "begin")
+                .execute_in(&mut tx)
+                .await
+                .unwrap();
+            tx.commit().await.unwrap();
+
+            transaction_sqlite(|tx| {
+                Box::pin(async move {
+                    sqlx::query("INSERT INTO tx_items (name) VALUES ('closure')")
+                        .execute(&mut **tx)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        })
+        .await;
+
+        let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM tx_items ORDER BY rowid")
+            .fetch_all(tenant_db.as_sqlite().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            names,
+            vec![("begin".to_string(),), ("closure".to_string(),)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_search_path_per_checkout() {
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        register_tenant(TenantInfo::new("schema_probe", "Probe").with_schema("photon_probe"))
+            .unwrap();
+        // One connection, so every checkout reuses it
+        let pool = DatabasePool::connect_with_max(&url, 1).await.unwrap();
+        let pg = pool.as_postgres().unwrap();
+        let tenant = Routed::new(pool.clone(), Some("photon_probe".to_string()));
+        let plain = Routed::new(pool.clone(), None);
+
+        async fn search_path(conn: &mut sqlx::PgConnection) -> String {
+            let (path,): (String,) = sqlx::query_as("SHOW search_path")
+                .fetch_one(conn)
+                .await
+                .unwrap();
+            path
+        }
+
+        let mut conn = tenant.checkout(pg).await.unwrap();
+        assert_eq!(search_path(&mut conn).await, "\"photon_probe\"");
+        drop(conn);
+
+        // Released connections come back with the default search_path
+        let mut conn = plain.checkout(pg).await.unwrap();
+        assert_eq!(search_path(&mut conn).await, "\"$user\", public");
+        drop(conn);
+
+        let mut tx = tenant.begin().await.unwrap();
+        let DatabaseTransaction::Postgres(t) = &mut tx else {
+            unreachable!()
+        };
+        assert_eq!(search_path(t).await, "\"photon_probe\"");
+        tx.rollback().await.unwrap();
+        let mut conn = plain.checkout(pg).await.unwrap();
+        assert_eq!(search_path(&mut conn).await, "\"$user\", public");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_connection_errors() {
+        use crate::photon::query::Builder;
+        let err = Builder::new("routing_items")
+            .on("missing")
+            .all::<(i64,)>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown database connection"));
+    }
 }
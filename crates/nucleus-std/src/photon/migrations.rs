@@ -3,15 +3,26 @@
 //! Version-controlled schema changes with support for applying, rolling back,
 //! and checking migration status.
 //!
+//! Applying is safe to run from several app instances at once: runs and
+//! rollbacks hold a database lock (`pg_advisory_lock`, `GET_LOCK`, or an exclusive SQLite
+//! transaction), each migration is applied in its own transaction where the
+//! backend supports transactional DDL, and the checksum of every applied
+//! migration's UP SQL is recorded so later edits are rejected. A run that is
+//! cancelled while holding the lock closes its connection rather than
+//! returning it to the pool.
+//!
 //! # Example
 //!
 //! ```rust,ignore
-//! use nucleus_std::photon::migrations::{run_migrations, migration_status};
+//! use nucleus_std::photon::migrations::{dry_run_migrations, migration_status, run_migrations};
 //!
 //! // Apply all pending migrations
 //! let applied = run_migrations("./migrations").await?;
 //! println!("Applied {} migrations", applied.len());
 //!
+//! // Print pending SQL without applying it
+//! let pending = dry_run_migrations("./migrations").await?;
+//!
 //! // Check status
 //! let status = migration_status("./migrations").await?;
 //! for m in status {
@@ -19,8 +30,13 @@
 //! }
 //! ```
 
-use crate::photon::db::{db, DatabasePool, DatabaseType};
+use crate::photon::db::{db, tenant_pool, DatabasePool};
+use crate::tenant::{registered_tenants, Isolation, TenantInfo};
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::pool::PoolConnection;
+use sqlx::Connection;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -36,6 +52,18 @@ pub struct MigrationInfo {
     pub applied_at: Option<DateTime<Utc>>,
 }
 
+/// A migration that has not been applied yet
+#[derive(Debug, Clone)]
+pub struct PendingMigration {
+    pub name: String,
+    /// SQL before the `-- DOWN` marker
+    pub up_sql: String,
+    /// SHA-256 of `up_sql`; editing the DOWN section does not change it
+    pub checksum: String,
+    /// SHA-256 of the whole file, recorded by earlier versions
+    pub(crate) file_checksum: String,
+}
+
 /// Migration error type
 #[derive(Debug)]
 pub enum MigrationError {
@@ -43,6 +71,14 @@ pub enum MigrationError {
     Sql(sqlx::Error),
     Parse(String),
     NotFound(String),
+    /// An applied migration file was edited after it ran
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// The migration lock could not be acquired
+    Lock(String),
 }
 
 impl std::fmt::Display for MigrationError {
@@ -52,6 +88,16 @@ impl std::fmt::Display for MigrationError {
             Self::Sql(e) => write!(f, "SQL error: {}", e),
             Self::Parse(msg) => write!(f, "Parse error: {}", msg),
             Self::NotFound(name) => write!(f, "Migration not found: {}", name),
+            Self::ChecksumMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Migration {} was modified after it was applied (checksum {} != recorded {})",
+                name, actual, expected
+            ),
+            Self::Lock(msg) => write!(f, "Migration lock error: {}", msg),
         }
     }
 }
@@ -78,6 +124,7 @@ const MIGRATIONS_TABLE_SQLITE: &str = r#"
 CREATE TABLE IF NOT EXISTS _migrations (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     name TEXT NOT NULL UNIQUE,
+    checksum TEXT,
     applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
 )
 "#;
@@ -86,6 +133,7 @@ const MIGRATIONS_TABLE_POSTGRES: &str = r#"
 CREATE TABLE IF NOT EXISTS _migrations (
     id SERIAL PRIMARY KEY,
     name TEXT NOT NULL UNIQUE,
+    checksum TEXT,
     applied_at TIMESTAMPTZ DEFAULT NOW()
 )
 "#;
@@ -94,30 +142,88 @@ const MIGRATIONS_TABLE_MYSQL: &str = r#"
 CREATE TABLE IF NOT EXISTS _migrations (
     id INT AUTO_INCREMENT PRIMARY KEY,
     name VARCHAR(255) NOT NULL UNIQUE,
+    checksum VARCHAR(64),
     applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 )
 "#;
 
-async fn ensure_migrations_table() -> Result<(), MigrationError> {
-    let pool = db();
-    let sql = match pool.db_type() {
-        DatabaseType::Sqlite => MIGRATIONS_TABLE_SQLITE,
-        DatabaseType::Postgres => MIGRATIONS_TABLE_POSTGRES,
-        DatabaseType::MySql => MIGRATIONS_TABLE_MYSQL,
-    };
+/// Key for `pg_advisory_lock` ("nucleus" in ASCII)
+const MIGRATION_LOCK_KEY: i64 = 0x6e75636c657573;
+
+/// Name for MySQL `GET_LOCK`
+const MIGRATION_LOCK_NAME: &str = "nucleus_migrations";
+
+/// Seconds to wait for another instance to finish migrating (MySQL)
+const MIGRATION_LOCK_TIMEOUT_SECS: i64 = 300;
 
+async fn ensure_migrations_table(pool: &DatabasePool) -> Result<(), MigrationError> {
     match pool {
-        DatabasePool::Sqlite(p) => {
-            sqlx::query(sql).execute(p).await?;
-        }
-        DatabasePool::Postgres(p) => {
-            sqlx::query(sql).execute(p).await?;
-        }
-        DatabasePool::MySql(p) => {
-            sqlx::query(sql).execute(p).await?;
-        }
+        DatabasePool::Sqlite(p) => ensure_sqlite_table(&mut *p.acquire().await?).await,
+        DatabasePool::Postgres(p) => ensure_postgres_table(&mut *p.acquire().await?).await,
+        DatabasePool::MySql(p) => ensure_mysql_table(&mut *p.acquire().await?).await,
     }
+}
 
+// Tables created before checksums existed gain the column on first use;
+// their rows are backfilled with the current checksum when next run, as are
+// rows holding an older whole-file checksum.
+
+async fn ensure_sqlite_table(conn: &mut sqlx::SqliteConnection) -> Result<(), MigrationError> {
+    sqlx::query(MIGRATIONS_TABLE_SQLITE)
+        .execute(&mut *conn)
+        .await?;
+    // Older `nucleus db up` tracked file names (with `.sql`) in a `key` column
+    let has_key: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM pragma_table_info('_migrations') WHERE name = 'key'")
+            .fetch_optional(&mut *conn)
+            .await?;
+    if has_key.is_some() {
+        sqlx::query("ALTER TABLE _migrations RENAME COLUMN key TO name")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "UPDATE _migrations SET name = substr(name, 1, length(name) - 4) WHERE name LIKE '%.sql'",
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    let has_checksum: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM pragma_table_info('_migrations') WHERE name = 'checksum'")
+            .fetch_optional(&mut *conn)
+            .await?;
+    if has_checksum.is_none() {
+        sqlx::query("ALTER TABLE _migrations ADD COLUMN checksum TEXT")
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn ensure_postgres_table(conn: &mut sqlx::PgConnection) -> Result<(), MigrationError> {
+    sqlx::query(MIGRATIONS_TABLE_POSTGRES)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("ALTER TABLE _migrations ADD COLUMN IF NOT EXISTS checksum TEXT")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn ensure_mysql_table(conn: &mut sqlx::MySqlConnection) -> Result<(), MigrationError> {
+    sqlx::query(MIGRATIONS_TABLE_MYSQL)
+        .execute(&mut *conn)
+        .await?;
+    let has_checksum: Option<(i64,)> = sqlx::query_as(
+        "SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = DATABASE() AND table_name = '_migrations' AND column_name = 'checksum'",
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if has_checksum.is_none() {
+        sqlx::query("ALTER TABLE _migrations ADD COLUMN checksum VARCHAR(64)")
+            .execute(&mut *conn)
+            .await?;
+    }
     Ok(())
 }
 
@@ -127,105 +233,606 @@ async fn ensure_migrations_table() -> Result<(), MigrationError> {
 
 /// Run all pending migrations in the specified directory
 ///
-/// Migrations are applied in alphabetical order by filename.
+/// Migrations are applied in alphabetical order by filename while holding
+/// the migration lock, so concurrent callers wait and then find nothing to
+/// do. Each migration runs in its own transaction on SQLite and PostgreSQL;
+/// MySQL commits DDL implicitly, so a failed MySQL migration may be partially
+/// applied. Fails with `ChecksumMismatch` if an applied file was edited.
 /// Returns a list of migration names that were applied.
 pub async fn run_migrations(dir: &str) -> Result<Vec<String>, MigrationError> {
-    ensure_migrations_table().await?;
+    run_migrations_on(db(), dir).await
+}
 
-    let pool = db();
-    let migrations = list_migration_files(dir)?;
-    let applied = get_applied_migrations().await?;
+/// Run all pending migrations against a specific pool
+///
+/// Same as `run_migrations`, for pools other than the global one.
+pub async fn run_migrations_on(
+    pool: &DatabasePool,
+    dir: &str,
+) -> Result<Vec<String>, MigrationError> {
+    run_migrations_limit_on(pool, dir, None).await
+}
 
-    let mut newly_applied = Vec::new();
+/// Run at most `limit` pending migrations against a specific pool
+///
+/// `None` applies every pending migration, like `run_migrations_on`.
+pub async fn run_migrations_limit_on(
+    pool: &DatabasePool,
+    dir: &str,
+    limit: Option<usize>,
+) -> Result<Vec<String>, MigrationError> {
+    let migrations = load_migrations(dir)?;
+    let limit = limit.unwrap_or(usize::MAX);
 
-    for (name, path) in migrations {
-        if applied.contains(&name) {
-            continue;
-        }
+    match pool {
+        DatabasePool::Sqlite(p) => apply_sqlite(p, &migrations, limit).await,
+        DatabasePool::Postgres(p) => apply_postgres(p, &migrations, limit).await,
+        DatabasePool::MySql(p) => apply_mysql(p, &migrations, limit).await,
+    }
+}
 
-        // Read and execute migration
-        let sql = fs::read_to_string(&path)?;
+/// Run all pending migrations inside a PostgreSQL schema
+///
+/// Creates the schema if needed and tracks applied migrations in its own
+/// `_migrations` table, so each schema-isolated tenant migrates separately.
+pub async fn run_migrations_in_schema(
+    pool: &DatabasePool,
+    schema: &str,
+    dir: &str,
+) -> Result<Vec<String>, MigrationError> {
+    let pool = pool.as_postgres().ok_or_else(|| {
+        sqlx::Error::Configuration(format!("schema {} needs a PostgreSQL database", schema).into())
+    })?;
+    let migrations = load_migrations(dir)?;
+    let mut conn = SessionConn::acquire(pool).await?;
+
+    let quoted = format!("\"{}\"", schema.replace('"', "\"\""));
+    sqlx::raw_sql(&format!("CREATE SCHEMA IF NOT EXISTS {}", quoted))
+        .execute(&mut *conn)
+        .await?;
+    conn.hold();
+    sqlx::query("SELECT set_config('search_path', $1, false)")
+        .bind(&quoted)
+        .execute(&mut *conn)
+        .await?;
+
+    let result = apply_postgres_on(&mut conn, &migrations, usize::MAX).await;
+    sqlx::query("RESET search_path").execute(&mut *conn).await?;
+    conn.release();
+    result
+}
 
-        // Extract UP migration (everything before -- DOWN)
-        let up_sql = extract_up_migration(&sql);
+/// Outcome of migrating one isolated tenant
+#[derive(Debug)]
+pub struct TenantMigration {
+    pub tenant: String,
+    pub result: Result<Vec<String>, MigrationError>,
+}
 
-        // Execute
-        match pool {
-            DatabasePool::Sqlite(p) => {
-                sqlx::query(&up_sql).execute(p).await?;
-            }
-            DatabasePool::Postgres(p) => {
-                sqlx::query(&up_sql).execute(p).await?;
-            }
-            DatabasePool::MySql(p) => {
-                sqlx::query(&up_sql).execute(p).await?;
-            }
-        }
+/// Run pending migrations for every registered tenant with its own schema
+/// or database
+///
+/// Shared-table tenants are skipped; `run_migrations` covers them. One
+/// tenant failing doesn't stop the others.
+pub async fn run_tenant_migrations(dir: &str) -> Vec<TenantMigration> {
+    run_tenant_migrations_for(&registered_tenants(), dir).await
+}
+
+/// Run pending migrations for the given tenants
+pub async fn run_tenant_migrations_for(tenants: &[TenantInfo], dir: &str) -> Vec<TenantMigration> {
+    let mut results = Vec::new();
+    for tenant in tenants {
+        let result = match &tenant.isolation {
+            Isolation::Shared => continue,
+            Isolation::Schema(schema) => run_migrations_in_schema(db(), schema, dir).await,
+            Isolation::Database(url) => match tenant_pool(&tenant.id, url).await {
+                Ok(pool) => run_migrations_on(&pool, dir).await,
+                Err(e) => Err(e.into()),
+            },
+        };
+        results.push(TenantMigration {
+            tenant: tenant.id.clone(),
+            result,
+        });
+    }
+    results
+}
+
+/// List the migrations `run_migrations` would apply, without applying them
+///
+/// Checksums of applied migrations are still verified.
+pub async fn pending_migrations(dir: &str) -> Result<Vec<PendingMigration>, MigrationError> {
+    pending_migrations_on(db(), dir).await
+}
+
+/// List pending migrations for a specific pool
+pub async fn pending_migrations_on(
+    pool: &DatabasePool,
+    dir: &str,
+) -> Result<Vec<PendingMigration>, MigrationError> {
+    ensure_migrations_table(pool).await?;
+
+    let migrations = load_migrations(dir)?;
+    let applied = get_applied_checksums(pool).await?;
+    Ok(select_pending(&migrations, &applied)?
+        .into_iter()
+        .cloned()
+        .collect())
+}
 
-        // Record migration
-        let record_sql = "INSERT INTO _migrations (name) VALUES (?)";
-        match pool {
-            DatabasePool::Sqlite(p) => {
-                sqlx::query(record_sql).bind(&name).execute(p).await?;
+/// Print the SQL of every pending migration without executing it
+///
+/// Returns the names of the migrations that would be applied.
+pub async fn dry_run_migrations(dir: &str) -> Result<Vec<String>, MigrationError> {
+    let pending = pending_migrations(dir).await?;
+    for migration in &pending {
+        println!("-- Migration: {}\n{}\n", migration.name, migration.up_sql);
+    }
+    Ok(pending.into_iter().map(|m| m.name).collect())
+}
+
+// ─────────────────────────────────────────────────────────────────────────
+// Locked appliers (one per backend)
+// ─────────────────────────────────────────────────────────────────────────
+
+async fn apply_sqlite(
+    pool: &sqlx::SqlitePool,
+    migrations: &[PendingMigration],
+    limit: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+
+    // An exclusive transaction blocks other writers for the whole run;
+    // each migration is isolated in a savepoint inside it
+    conn.hold();
+    sqlx::query("BEGIN EXCLUSIVE").execute(&mut *conn).await?;
+
+    let mut newly_applied = Vec::new();
+    let result = async {
+        ensure_sqlite_table(&mut conn).await?;
+        let applied: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, checksum FROM _migrations")
+                .fetch_all(&mut *conn)
+                .await?;
+        let applied: HashMap<_, _> = applied.into_iter().collect();
+
+        for migration in select_pending(migrations, &applied)?
+            .into_iter()
+            .take(limit)
+        {
+            sqlx::query("SAVEPOINT nucleus_migration")
+                .execute(&mut *conn)
+                .await?;
+            let step = async {
+                sqlx::raw_sql(&migration.up_sql).execute(&mut *conn).await?;
+                sqlx::query("INSERT INTO _migrations (name, checksum) VALUES (?, ?)")
+                    .bind(&migration.name)
+                    .bind(&migration.checksum)
+                    .execute(&mut *conn)
+                    .await
             }
-            DatabasePool::Postgres(p) => {
-                sqlx::query("INSERT INTO _migrations (name) VALUES ($1)")
-                    .bind(&name)
-                    .execute(p)
+            .await;
+            if let Err(e) = step {
+                sqlx::query("ROLLBACK TO nucleus_migration")
+                    .execute(&mut *conn)
                     .await?;
+                return Err(e.into());
             }
-            DatabasePool::MySql(p) => {
-                sqlx::query(record_sql).bind(&name).execute(p).await?;
-            }
+            sqlx::query("RELEASE nucleus_migration")
+                .execute(&mut *conn)
+                .await?;
+            newly_applied.push(migration.name.clone());
+        }
+
+        backfill_sqlite(&mut conn, migrations, &applied).await
+    }
+    .await;
+
+    // Migrations that succeeded before a failure are kept
+    sqlx::query("COMMIT").execute(&mut *conn).await?;
+    conn.release();
+    result.map(|_| newly_applied)
+}
+
+async fn apply_postgres(
+    pool: &sqlx::PgPool,
+    migrations: &[PendingMigration],
+    limit: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+    apply_postgres_on(&mut conn, migrations, limit).await
+}
+
+async fn apply_postgres_on(
+    conn: &mut SessionConn<sqlx::Postgres>,
+    migrations: &[PendingMigration],
+    limit: usize,
+) -> Result<Vec<String>, MigrationError> {
+    lock_postgres(conn).await?;
+
+    let result = async {
+        ensure_postgres_table(conn).await?;
+        let applied: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, checksum FROM _migrations")
+                .fetch_all(&mut **conn)
+                .await?;
+        let applied: HashMap<_, _> = applied.into_iter().collect();
+
+        let mut newly_applied = Vec::new();
+        for migration in select_pending(migrations, &applied)?
+            .into_iter()
+            .take(limit)
+        {
+            let mut tx = conn.begin().await?;
+            sqlx::raw_sql(&migration.up_sql).execute(&mut *tx).await?;
+            sqlx::query("INSERT INTO _migrations (name, checksum) VALUES ($1, $2)")
+                .bind(&migration.name)
+                .bind(&migration.checksum)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            newly_applied.push(migration.name.clone());
+        }
+
+        for (name, checksum) in missing_checksums(migrations, &applied) {
+            sqlx::query("UPDATE _migrations SET checksum = $1 WHERE name = $2")
+                .bind(checksum)
+                .bind(name)
+                .execute(&mut **conn)
+                .await?;
+        }
+        Ok(newly_applied)
+    }
+    .await;
+
+    unlock_postgres(conn).await?;
+    result
+}
+
+async fn apply_mysql(
+    pool: &sqlx::MySqlPool,
+    migrations: &[PendingMigration],
+    limit: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+    lock_mysql(&mut conn).await?;
+
+    let result = async {
+        ensure_mysql_table(&mut conn).await?;
+        let applied: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, checksum FROM _migrations")
+                .fetch_all(&mut *conn)
+                .await?;
+        let applied: HashMap<_, _> = applied.into_iter().collect();
+
+        // DDL commits implicitly in MySQL, so there is no transaction to use
+        let mut newly_applied = Vec::new();
+        for migration in select_pending(migrations, &applied)?
+            .into_iter()
+            .take(limit)
+        {
+            sqlx::raw_sql(&migration.up_sql).execute(&mut *conn).await?;
+            sqlx::query("INSERT INTO _migrations (name, checksum) VALUES (?, ?)")
+                .bind(&migration.name)
+                .bind(&migration.checksum)
+                .execute(&mut *conn)
+                .await?;
+            newly_applied.push(migration.name.clone());
+        }
+
+        for (name, checksum) in missing_checksums(migrations, &applied) {
+            sqlx::query("UPDATE _migrations SET checksum = ? WHERE name = ?")
+                .bind(checksum)
+                .bind(name)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(newly_applied)
+    }
+    .await;
+
+    unlock_mysql(&mut conn).await?;
+    result
+}
+
+/// Pooled connection carrying session state: a lock, an open transaction,
+/// a `search_path`
+///
+/// If it is dropped while that state is still held (the future was
+/// cancelled, or cleaning up failed), the connection is closed instead of
+/// returned to the pool, so the database ends the session and drops the
+/// state with it.
+struct SessionConn<DB: sqlx::Database> {
+    conn: PoolConnection<DB>,
+    held: usize,
+}
+
+impl<DB: sqlx::Database> SessionConn<DB> {
+    async fn acquire(pool: &sqlx::Pool<DB>) -> Result<Self, MigrationError> {
+        Ok(Self {
+            conn: pool.acquire().await?,
+            held: 0,
+        })
+    }
+
+    /// Call before the statement that sets up session state
+    fn hold(&mut self) {
+        self.held += 1;
+    }
+
+    /// Call once that state has been cleared
+    fn release(&mut self) {
+        self.held -= 1;
+    }
+}
+
+impl<DB: sqlx::Database> std::ops::Deref for SessionConn<DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<DB: sqlx::Database> std::ops::DerefMut for SessionConn<DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+impl<DB: sqlx::Database> Drop for SessionConn<DB> {
+    fn drop(&mut self) {
+        if self.held > 0 {
+            self.conn.close_on_drop();
         }
+    }
+}
+
+// Session-level locks; they wait until other instances finish
 
-        newly_applied.push(name);
+async fn lock_postgres(conn: &mut SessionConn<sqlx::Postgres>) -> Result<(), MigrationError> {
+    conn.hold();
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut **conn)
+        .await?;
+    Ok(())
+}
+
+async fn unlock_postgres(conn: &mut SessionConn<sqlx::Postgres>) -> Result<(), MigrationError> {
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut **conn)
+        .await?;
+    conn.release();
+    Ok(())
+}
+
+async fn lock_mysql(conn: &mut SessionConn<sqlx::MySql>) -> Result<(), MigrationError> {
+    conn.hold();
+    let (locked,): (Option<i64>,) = sqlx::query_as("SELECT GET_LOCK(?, ?)")
+        .bind(MIGRATION_LOCK_NAME)
+        .bind(MIGRATION_LOCK_TIMEOUT_SECS)
+        .fetch_one(&mut **conn)
+        .await?;
+    if locked != Some(1) {
+        conn.release();
+        return Err(MigrationError::Lock(format!(
+            "timed out after {}s waiting for {}",
+            MIGRATION_LOCK_TIMEOUT_SECS, MIGRATION_LOCK_NAME
+        )));
     }
+    Ok(())
+}
+
+async fn unlock_mysql(conn: &mut SessionConn<sqlx::MySql>) -> Result<(), MigrationError> {
+    sqlx::query("SELECT RELEASE_LOCK(?)")
+        .bind(MIGRATION_LOCK_NAME)
+        .execute(&mut **conn)
+        .await?;
+    conn.release();
+    Ok(())
+}
 
-    Ok(newly_applied)
+async fn backfill_sqlite(
+    conn: &mut sqlx::SqliteConnection,
+    migrations: &[PendingMigration],
+    applied: &HashMap<String, Option<String>>,
+) -> Result<(), MigrationError> {
+    for (name, checksum) in missing_checksums(migrations, applied) {
+        sqlx::query("UPDATE _migrations SET checksum = ? WHERE name = ?")
+            .bind(checksum)
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
 }
 
 /// Rollback the last N migrations
+///
+/// Only removes the tracking rows; use `rollback_in` to also run the
+/// `-- DOWN` section of each file.
 pub async fn rollback(n: usize) -> Result<Vec<String>, MigrationError> {
-    ensure_migrations_table().await?;
+    rollback_with(db(), None, n).await
+}
 
-    let pool = db();
-    let applied = get_applied_migrations_ordered().await?;
+/// Rollback the last N migrations, running the `-- DOWN` SQL found in `dir`
+///
+/// Migrations whose file is missing or has no DOWN section are only removed
+/// from tracking.
+pub async fn rollback_in(dir: &str, n: usize) -> Result<Vec<String>, MigrationError> {
+    rollback_with(db(), Some(dir), n).await
+}
 
-    let to_rollback: Vec<_> = applied.into_iter().rev().take(n).collect();
-    let mut rolled_back = Vec::new();
+async fn rollback_with(
+    pool: &DatabasePool,
+    dir: Option<&str>,
+    n: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let files: HashMap<_, _> = match dir {
+        Some(dir) => list_migration_files(dir)?.into_iter().collect(),
+        None => HashMap::new(),
+    };
 
-    for (name, _applied_at) in to_rollback {
-        // Try to find the migration file to get DOWN migration
-        // For now, just remove from tracking (users should handle DOWN manually)
-        let delete_sql = "DELETE FROM _migrations WHERE name = ?";
-        match pool {
-            DatabasePool::Sqlite(p) => {
-                sqlx::query(delete_sql).bind(&name).execute(p).await?;
-            }
-            DatabasePool::Postgres(p) => {
-                sqlx::query("DELETE FROM _migrations WHERE name = $1")
+    // Same lock as applying, so a rollback never interleaves with a migrate
+    match pool {
+        DatabasePool::Sqlite(p) => rollback_sqlite(p, &files, n).await,
+        DatabasePool::Postgres(p) => rollback_postgres(p, &files, n).await,
+        DatabasePool::MySql(p) => rollback_mysql(p, &files, n).await,
+    }
+}
+
+async fn rollback_sqlite(
+    pool: &sqlx::SqlitePool,
+    files: &HashMap<String, std::path::PathBuf>,
+    n: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+    conn.hold();
+    sqlx::query("BEGIN EXCLUSIVE").execute(&mut *conn).await?;
+
+    let mut rolled_back = Vec::new();
+    let result = async {
+        ensure_sqlite_table(&mut conn).await?;
+        let newest: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM _migrations ORDER BY id DESC LIMIT ?")
+                .bind(limit_arg(n))
+                .fetch_all(&mut *conn)
+                .await?;
+
+        for (name,) in newest {
+            let down_sql = down_sql_for(files, &name)?;
+            sqlx::query("SAVEPOINT nucleus_rollback")
+                .execute(&mut *conn)
+                .await?;
+            let step = async {
+                if !down_sql.is_empty() {
+                    sqlx::raw_sql(&down_sql).execute(&mut *conn).await?;
+                }
+                sqlx::query("DELETE FROM _migrations WHERE name = ?")
                     .bind(&name)
-                    .execute(p)
+                    .execute(&mut *conn)
+                    .await
+            }
+            .await;
+            if let Err(e) = step {
+                sqlx::query("ROLLBACK TO nucleus_rollback")
+                    .execute(&mut *conn)
                     .await?;
+                return Err(e.into());
             }
-            DatabasePool::MySql(p) => {
-                sqlx::query(delete_sql).bind(&name).execute(p).await?;
+            sqlx::query("RELEASE nucleus_rollback")
+                .execute(&mut *conn)
+                .await?;
+            rolled_back.push(name);
+        }
+        Ok(())
+    }
+    .await;
+
+    // Rollbacks that succeeded before a failure are kept
+    sqlx::query("COMMIT").execute(&mut *conn).await?;
+    conn.release();
+    result.map(|_| rolled_back)
+}
+
+async fn rollback_postgres(
+    pool: &sqlx::PgPool,
+    files: &HashMap<String, std::path::PathBuf>,
+    n: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+    lock_postgres(&mut conn).await?;
+
+    let result = async {
+        ensure_postgres_table(&mut conn).await?;
+        let newest: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM _migrations ORDER BY id DESC LIMIT $1")
+                .bind(limit_arg(n))
+                .fetch_all(&mut *conn)
+                .await?;
+
+        let mut rolled_back = Vec::new();
+        for (name,) in newest {
+            let down_sql = down_sql_for(files, &name)?;
+            let mut tx = conn.begin().await?;
+            if !down_sql.is_empty() {
+                sqlx::raw_sql(&down_sql).execute(&mut *tx).await?;
             }
+            sqlx::query("DELETE FROM _migrations WHERE name = $1")
+                .bind(&name)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            rolled_back.push(name);
         }
+        Ok(rolled_back)
+    }
+    .await;
 
-        rolled_back.push(name);
+    unlock_postgres(&mut conn).await?;
+    result
+}
+
+async fn rollback_mysql(
+    pool: &sqlx::MySqlPool,
+    files: &HashMap<String, std::path::PathBuf>,
+    n: usize,
+) -> Result<Vec<String>, MigrationError> {
+    let mut conn = SessionConn::acquire(pool).await?;
+    lock_mysql(&mut conn).await?;
+
+    let result = async {
+        ensure_mysql_table(&mut conn).await?;
+        let newest: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM _migrations ORDER BY id DESC LIMIT ?")
+                .bind(limit_arg(n))
+                .fetch_all(&mut *conn)
+                .await?;
+
+        let mut rolled_back = Vec::new();
+        for (name,) in newest {
+            let down_sql = down_sql_for(files, &name)?;
+            if !down_sql.is_empty() {
+                sqlx::raw_sql(&down_sql).execute(&mut *conn).await?;
+            }
+            sqlx::query("DELETE FROM _migrations WHERE name = ?")
+                .bind(&name)
+                .execute(&mut *conn)
+                .await?;
+            rolled_back.push(name);
+        }
+        Ok(rolled_back)
     }
+    .await;
+
+    unlock_mysql(&mut conn).await?;
+    result
+}
 
-    Ok(rolled_back)
+/// `-- DOWN` SQL of an applied migration, empty if its file is gone
+fn down_sql_for(
+    files: &HashMap<String, std::path::PathBuf>,
+    name: &str,
+) -> Result<String, MigrationError> {
+    Ok(match files.get(name) {
+        Some(path) => extract_down_migration(&fs::read_to_string(path)?),
+        None => String::new(),
+    })
+}
+
+/// Row count for `LIMIT`, saturating
+fn limit_arg(n: usize) -> i64 {
+    i64::try_from(n).unwrap_or(i64::MAX)
 }
 
 /// Get status of all migrations
 pub async fn migration_status(dir: &str) -> Result<Vec<MigrationInfo>, MigrationError> {
-    ensure_migrations_table().await?;
+    ensure_migrations_table(db()).await?;
 
     let migrations = list_migration_files(dir)?;
-    let applied_map = get_applied_migrations_map().await?;
+    let applied_map = get_applied_migrations_map(db()).await?;
 
     let mut status = Vec::new();
 
@@ -287,6 +894,79 @@ fn list_migration_files(dir: &str) -> Result<Vec<(String, std::path::PathBuf)>,
     Ok(migrations)
 }
 
+/// Read every migration file with its UP section and checksum
+fn load_migrations(dir: &str) -> Result<Vec<PendingMigration>, MigrationError> {
+    list_migration_files(dir)?
+        .into_iter()
+        .map(|(name, path)| {
+            let sql = fs::read_to_string(&path)?;
+            let up_sql = extract_up_migration(&sql);
+            Ok(PendingMigration {
+                name,
+                checksum: migration_checksum(&up_sql),
+                file_checksum: migration_checksum(&sql),
+                up_sql,
+            })
+        })
+        .collect()
+}
+
+/// SHA-256 of migration SQL, hex-encoded
+fn migration_checksum(contents: &str) -> String {
+    hex::encode(Sha256::digest(contents.as_bytes()))
+}
+
+/// Verify applied checksums and return the migrations still to apply
+///
+/// Rows without a checksum predate checksum tracking and are accepted.
+fn select_pending<'a>(
+    migrations: &'a [PendingMigration],
+    applied: &HashMap<String, Option<String>>,
+) -> Result<Vec<&'a PendingMigration>, MigrationError> {
+    let mut pending = Vec::new();
+    for migration in migrations {
+        match applied.get(&migration.name) {
+            None => pending.push(migration),
+            Some(Some(recorded))
+                if *recorded != migration.checksum && *recorded != migration.file_checksum =>
+            {
+                return Err(MigrationError::ChecksumMismatch {
+                    name: migration.name.clone(),
+                    expected: recorded.clone(),
+                    actual: migration.checksum.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(pending)
+}
+
+/// Applied migrations recorded without a current checksum, with the value
+/// to backfill
+fn missing_checksums<'a>(
+    migrations: &'a [PendingMigration],
+    applied: &HashMap<String, Option<String>>,
+) -> Vec<(&'a str, &'a str)> {
+    migrations
+        .iter()
+        .filter(|m| match applied.get(&m.name) {
+            Some(None) => true,
+            Some(Some(recorded)) => *recorded != m.checksum && *recorded == m.file_checksum,
+            None => false,
+        })
+        .map(|m| (m.name.as_str(), m.checksum.as_str()))
+        .collect()
+}
+
+fn extract_down_migration(sql: &str) -> String {
+    // Everything after the "-- DOWN" marker, or nothing without one
+    match sql.find("-- DOWN") {
+        Some(pos) => sql[pos + "-- DOWN".len()..].trim().to_string(),
+        None => String::new(),
+    }
+}
+
 fn extract_up_migration(sql: &str) -> String {
     // Find "-- DOWN" marker and take everything before it
     if let Some(pos) = sql.find("-- DOWN") {
@@ -296,22 +976,25 @@ fn extract_up_migration(sql: &str) -> String {
     }
 }
 
-async fn get_applied_migrations() -> Result<Vec<String>, MigrationError> {
-    let pool = db();
-    let sql = "SELECT name FROM _migrations ORDER BY name";
+async fn get_applied_checksums(
+    pool: &DatabasePool,
+) -> Result<HashMap<String, Option<String>>, MigrationError> {
+    let sql = "SELECT name, checksum FROM _migrations";
 
-    let names: Vec<(String,)> = match pool {
+    let rows: Vec<(String, Option<String>)> = match pool {
         DatabasePool::Sqlite(p) => sqlx::query_as(sql).fetch_all(p).await?,
         DatabasePool::Postgres(p) => sqlx::query_as(sql).fetch_all(p).await?,
         DatabasePool::MySql(p) => sqlx::query_as(sql).fetch_all(p).await?,
     };
 
-    Ok(names.into_iter().map(|(n,)| n).collect())
+    Ok(rows.into_iter().collect())
 }
 
-async fn get_applied_migrations_ordered() -> Result<Vec<(String, DateTime<Utc>)>, MigrationError> {
-    let pool = db();
-    let sql = "SELECT name, applied_at FROM _migrations ORDER BY applied_at DESC";
+async fn get_applied_migrations_ordered(
+    pool: &DatabasePool,
+) -> Result<Vec<(String, DateTime<Utc>)>, MigrationError> {
+    // Insertion order; applied_at has one-second resolution on SQLite
+    let sql = "SELECT name, applied_at FROM _migrations ORDER BY id";
 
     #[derive(sqlx::FromRow)]
     struct Row {
@@ -329,8 +1012,9 @@ async fn get_applied_migrations_ordered() -> Result<Vec<(String, DateTime<Utc>)>
 }
 
 async fn get_applied_migrations_map(
-) -> Result<std::collections::HashMap<String, DateTime<Utc>>, MigrationError> {
-    let applied = get_applied_migrations_ordered().await?;
+    pool: &DatabasePool,
+) -> Result<HashMap<String, DateTime<Utc>>, MigrationError> {
+    let applied = get_applied_migrations_ordered(pool).await?;
     Ok(applied.into_iter().collect())
 }
 
@@ -484,6 +1168,429 @@ mod tests {
         let _ = fs::remove_dir_all(dir);
     }
 
+    #[test]
+    fn test_migration_error_checksum_display() {
+        let err = MigrationError::ChecksumMismatch {
+            name: "001_users".to_string(),
+            expected: "aaa".to_string(),
+            actual: "bbb".to_string(),
+        };
+        assert!(err.to_string().contains("001_users was modified"));
+    }
+
+    fn pending(name: &str, sql: &str) -> PendingMigration {
+        PendingMigration {
+            name: name.to_string(),
+            up_sql: extract_up_migration(sql),
+            checksum: migration_checksum(&extract_up_migration(sql)),
+            file_checksum: migration_checksum(sql),
+        }
+    }
+
+    #[test]
+    fn test_select_pending_verifies_checksums() {
+        let migrations = vec![
+            pending("001_a", "CREATE TABLE a (id INT);"),
+            pending("002_b", "CREATE TABLE b (id INT);"),
+        ];
+        assert_eq!(migrations[0].checksum.len(), 64);
+
+        let mut applied = HashMap::new();
+        applied.insert("001_a".to_string(), Some(migrations[0].checksum.clone()));
+        let todo = select_pending(&migrations, &applied).unwrap();
+        assert_eq!(todo.len(), 1);
+        assert_eq!(todo[0].name, "002_b");
+
+        // Legacy rows without a checksum are accepted and backfilled
+        applied.insert("001_a".to_string(), None);
+        assert_eq!(select_pending(&migrations, &applied).unwrap().len(), 1);
+        assert_eq!(
+            missing_checksums(&migrations, &applied),
+            vec![("001_a", migrations[0].checksum.as_str())]
+        );
+
+        applied.insert("001_a".to_string(), Some("edited".to_string()));
+        assert!(matches!(
+            select_pending(&migrations, &applied),
+            Err(MigrationError::ChecksumMismatch { .. })
+        ));
+    }
+
+    async fn temp_sqlite(name: &str) -> (DatabasePool, String) {
+        let dir = format!("/tmp/test_mig_{}", name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let pool = DatabasePool::connect(&format!("sqlite:{}/app.db", dir))
+            .await
+            .unwrap();
+        (pool, dir)
+    }
+
+    async fn table_exists(pool: &DatabasePool, table: &str) -> bool {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(table)
+                .fetch_optional(pool.as_sqlite().unwrap())
+                .await
+                .unwrap();
+        row.is_some()
+    }
+
+    #[tokio::test]
+    async fn test_run_migrations_on_sqlite() {
+        let (pool, dir) = temp_sqlite("run").await;
+        fs::write(
+            format!("{}/001_users.sql", dir),
+            "-- UP\nCREATE TABLE users (id INTEGER PRIMARY KEY);\nCREATE INDEX users_id ON users (id);\n-- DOWN\nDROP TABLE users;",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/002_posts.sql", dir),
+            "CREATE TABLE posts (id INTEGER);",
+        )
+        .unwrap();
+
+        let pending = pending_migrations_on(&pool, &dir).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending[0].up_sql.contains("CREATE INDEX"));
+
+        let applied = run_migrations_on(&pool, &dir).await.unwrap();
+        assert_eq!(applied, vec!["001_users", "002_posts"]);
+        assert!(table_exists(&pool, "users").await);
+        assert!(run_migrations_on(&pool, &dir).await.unwrap().is_empty());
+
+        // A failing migration is rolled back as a unit; earlier ones stay applied
+        fs::write(
+            format!("{}/003_broken.sql", dir),
+            "CREATE TABLE half (id INTEGER);\nINSERT INTO missing VALUES (1);",
+        )
+        .unwrap();
+        assert!(matches!(
+            run_migrations_on(&pool, &dir).await,
+            Err(MigrationError::Sql(_))
+        ));
+        assert!(!table_exists(&pool, "half").await);
+        fs::remove_file(format!("{}/003_broken.sql", dir)).unwrap();
+
+        // Editing an applied migration is a hard error
+        fs::write(
+            format!("{}/002_posts.sql", dir),
+            "CREATE TABLE posts (id TEXT);",
+        )
+        .unwrap();
+        assert!(matches!(
+            run_migrations_on(&pool, &dir).await,
+            Err(MigrationError::ChecksumMismatch { name, .. }) if name == "002_posts"
+        ));
+
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_limit_and_rollback_with_down_sql() {
+        let (pool, dir) = temp_sqlite("limit").await;
+        for (i, table) in ["a", "b", "c"].iter().enumerate() {
+            fs::write(
+                format!("{}/00{}_{}.sql", dir, i + 1, table),
+                format!(
+                    "CREATE TABLE {0} (id INTEGER);\n-- DOWN\nDROP TABLE {0};",
+                    table
+                ),
+            )
+            .unwrap();
+        }
+
+        let applied = run_migrations_limit_on(&pool, &dir, Some(2)).await.unwrap();
+        assert_eq!(applied, vec!["001_a", "002_b"]);
+        assert!(!table_exists(&pool, "c").await);
+        assert_eq!(pending_migrations_on(&pool, &dir).await.unwrap().len(), 1);
+
+        // Newest first, running each DOWN section
+        let rolled_back = rollback_with(&pool, Some(&dir), 1).await.unwrap();
+        assert_eq!(rolled_back, vec!["002_b"]);
+        assert!(!table_exists(&pool, "b").await);
+        assert!(table_exists(&pool, "a").await);
+
+        let applied = run_migrations_limit_on(&pool, &dir, None).await.unwrap();
+        assert_eq!(applied, vec!["002_b", "003_c"]);
+
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_migrations_per_database() {
+        let (_, dir) = temp_sqlite("tenants").await;
+        let migrations = format!("{}/migrations", dir);
+        fs::create_dir_all(&migrations).unwrap();
+        fs::write(
+            format!("{}/001_items.sql", migrations),
+            "CREATE TABLE items (id INTEGER);",
+        )
+        .unwrap();
+
+        let url = |name: &str| format!("sqlite:{}/{}.db", dir, name);
+        let tenants = vec![
+            TenantInfo::new("mig_a", "A").with_database(&url("a")),
+            TenantInfo::new("mig_shared", "Shared"),
+            TenantInfo::new("mig_b", "B").with_database(&url("b")),
+            TenantInfo::new("mig_bad", "Bad").with_database("unknown://nowhere"),
+        ];
+        let results = run_tenant_migrations_for(&tenants, &migrations).await;
+
+        let ids: Vec<&str> = results.iter().map(|r| r.tenant.as_str()).collect();
+        assert_eq!(ids, vec!["mig_a", "mig_b", "mig_bad"]);
+        assert_eq!(results[0].result.as_ref().unwrap(), &vec!["001_items"]);
+        assert_eq!(results[1].result.as_ref().unwrap(), &vec!["001_items"]);
+        assert!(results[2].result.is_err());
+
+        let pool = tenant_pool("mig_b", &url("b")).await.unwrap();
+        assert!(table_exists(&pool, "items").await);
+        let again = run_tenant_migrations_for(&tenants[..1], &migrations).await;
+        assert!(again[0].result.as_ref().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_in_schema_postgres() {
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        let pool = DatabasePool::connect(&url).await.unwrap();
+        let (_, dir) = temp_sqlite("schema").await;
+        fs::write(
+            format!("{}/001_items.sql", dir),
+            "CREATE TABLE items (id INTEGER);",
+        )
+        .unwrap();
+
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let schemas = [
+            format!("mig_a_{}", &suffix[..8]),
+            format!("mig_b_{}", &suffix[..8]),
+        ];
+        for schema in &schemas {
+            let applied = run_migrations_in_schema(&pool, schema, &dir).await.unwrap();
+            assert_eq!(applied, vec!["001_items"]);
+            assert!(run_migrations_in_schema(&pool, schema, &dir)
+                .await
+                .unwrap()
+                .is_empty());
+
+            let (tables,): (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = $1 AND table_name IN ('items', '_migrations')",
+            )
+            .bind(schema)
+            .fetch_one(pool.as_postgres().unwrap())
+            .await
+            .unwrap();
+            assert_eq!(tables, 2);
+        }
+
+        for schema in &schemas {
+            sqlx::raw_sql(&format!("DROP SCHEMA {} CASCADE", schema))
+                .execute(pool.as_postgres().unwrap())
+                .await
+                .unwrap();
+        }
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_waits_for_migration_lock_postgres() {
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        let holder = DatabasePool::connect(&url).await.unwrap();
+        let mut held = SessionConn::acquire(holder.as_postgres().unwrap())
+            .await
+            .unwrap();
+        lock_postgres(&mut held).await.unwrap();
+
+        // A rollback must queue behind a migration run holding the lock
+        let pool = DatabasePool::connect(&url).await.unwrap();
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(300),
+            rollback_with(&pool, None, 0),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        pool.close().await;
+        unlock_postgres(&mut held).await.unwrap();
+        drop(held);
+        holder.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_does_not_pool_a_locked_connection() {
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        let holder = DatabasePool::connect(&url).await.unwrap();
+        let mut held = SessionConn::acquire(holder.as_postgres().unwrap())
+            .await
+            .unwrap();
+        lock_postgres(&mut held).await.unwrap();
+
+        // Cancel a run while it waits for the lock, then let the lock go
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .unwrap();
+        let cancelled = tokio::time::timeout(
+            std::time::Duration::from_millis(200),
+            apply_postgres(&pool, &[], usize::MAX),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        unlock_postgres(&mut held).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        // The pool's next connection holds no advisory lock
+        let (locks,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pg_locks WHERE locktype = 'advisory' AND pid = pg_backend_pid()",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(locks, 0);
+
+        pool.close().await;
+        drop(held);
+        holder.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_apply_once() {
+        let (pool, dir) = temp_sqlite("concurrent").await;
+        let other = DatabasePool::connect(&format!("sqlite:{}/app.db", dir))
+            .await
+            .unwrap();
+        for i in 0..5 {
+            fs::write(
+                format!("{}/{:03}_t.sql", dir, i),
+                format!("CREATE TABLE t{} (id INTEGER);", i),
+            )
+            .unwrap();
+        }
+
+        let (a, b) = tokio::join!(
+            run_migrations_on(&pool, &dir),
+            run_migrations_on(&other, &dir)
+        );
+        assert_eq!(a.unwrap().len() + b.unwrap().len(), 5);
+
+        pool.close().await;
+        other.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_cli_table_is_upgraded() {
+        let (pool, dir) = temp_sqlite("legacy_cli").await;
+        let sqlite = pool.as_sqlite().unwrap();
+        sqlx::query(
+            "CREATE TABLE _migrations (id INTEGER PRIMARY KEY, key TEXT UNIQUE NOT NULL, \
+             applied_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        )
+        .execute(sqlite)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO _migrations (key) VALUES ('001_users.sql')")
+            .execute(sqlite)
+            .await
+            .unwrap();
+        fs::write(
+            format!("{}/001_users.sql", dir),
+            "CREATE TABLE users (id INTEGER);",
+        )
+        .unwrap();
+
+        // Already applied under its old key, so nothing runs
+        assert!(run_migrations_on(&pool, &dir).await.unwrap().is_empty());
+        assert!(!table_exists(&pool, "users").await);
+
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_table_gains_checksums() {
+        let (pool, dir) = temp_sqlite("legacy").await;
+        let sqlite = pool.as_sqlite().unwrap();
+        sqlx::query("CREATE TABLE _migrations (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP)")
+            .execute(sqlite)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO _migrations (name) VALUES ('001_old')")
+            .execute(sqlite)
+            .await
+            .unwrap();
+        fs::write(
+            format!("{}/001_old.sql", dir),
+            "CREATE TABLE old (id INTEGER);",
+        )
+        .unwrap();
+
+        assert!(run_migrations_on(&pool, &dir).await.unwrap().is_empty());
+        let (checksum,): (Option<String>,) =
+            sqlx::query_as("SELECT checksum FROM _migrations WHERE name = '001_old'")
+                .fetch_one(sqlite)
+                .await
+                .unwrap();
+        assert_eq!(
+            checksum,
+            Some(migration_checksum("CREATE TABLE old (id INTEGER);"))
+        );
+
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_covers_only_up_sql() {
+        let (pool, dir) = temp_sqlite("checksum_up").await;
+        let file = format!("{}/001_notes.sql", dir);
+        let original = "CREATE TABLE notes (id INTEGER);\n-- DOWN\nDROP TABLE notes;";
+        fs::write(&file, original).unwrap();
+        run_migrations_on(&pool, &dir).await.unwrap();
+
+        // Fixing a DOWN section after deploying is not an edit of the migration
+        fs::write(
+            &file,
+            "CREATE TABLE notes (id INTEGER);\n-- DOWN\nDROP TABLE IF EXISTS notes;",
+        )
+        .unwrap();
+        assert!(run_migrations_on(&pool, &dir).await.unwrap().is_empty());
+
+        // Rows recorded with the old whole-file checksum are accepted and rewritten
+        let sqlite = pool.as_sqlite().unwrap();
+        sqlx::query("UPDATE _migrations SET checksum = ? WHERE name = '001_notes'")
+            .bind(migration_checksum(original))
+            .execute(sqlite)
+            .await
+            .unwrap();
+        fs::write(&file, original).unwrap();
+        assert!(run_migrations_on(&pool, &dir).await.unwrap().is_empty());
+        let (checksum,): (Option<String>,) =
+            sqlx::query_as("SELECT checksum FROM _migrations WHERE name = '001_notes'")
+                .fetch_one(sqlite)
+                .await
+                .unwrap();
+        assert_eq!(
+            checksum,
+            Some(migration_checksum("CREATE TABLE notes (id INTEGER);"))
+        );
+
+        pool.close().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_migration_files_sorted_alphabetically() {
         let dir = "/tmp/test_mig_sort";
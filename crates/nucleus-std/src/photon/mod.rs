@@ -5,10 +5,14 @@
 //! # Features
 //!
 //! - **Multi-Database**: PostgreSQL, MySQL, SQLite from one API
+//! - **Routing**: Read replicas, named databases and read-your-writes stickiness
 //! - **Query Builder**: Fluent, type-safe SQL generation
+//! - **Pagination**: Offset pages or signed keyset cursors
 //! - **Transactions**: ACID-compliant with automatic rollback
-//! - **Migrations**: Version-controlled schema changes
-//! - **Relationships**: HasMany, BelongsTo, HasOne with eager loading
+//! - **Migrations**: Checksummed, locked schema changes, generated by schema diff
+//! - **Relationships**: HasOne, HasMany, BelongsTo, BelongsToMany with nested eager loading
+//! - **Derive**: `#[derive(Model)]` with typed columns and active-record methods
+//! - **Behaviors**: Timestamps, soft deletes and transactional lifecycle hooks
 //!
 //! # Quick Start
 //!
@@ -34,18 +38,36 @@
 //!     .await?;
 //! ```
 
+pub mod cursor;
 pub mod db;
 pub mod migrations;
 pub mod query;
+pub mod record;
 pub mod relations;
+pub mod schema;
 
 // Re-export main types
-pub use db::{db, init_db, is_db_initialized, DatabasePool, DatabaseType, QueryValue};
+pub use cursor::{Cursor, CursorPage};
+pub(crate) use db::on_pool;
+pub use db::{
+    begin, configure_tenant_pools, database, db, db_read, init_databases, init_db,
+    is_db_initialized, register_database, sticky, sticky_reads, DatabaseCluster, DatabasePool,
+    DatabaseTransaction, DatabaseType, QueryResult, QueryValue,
+};
 pub use migrations::{
-    create_migration, migration_status, rollback, run_migrations, MigrationError, MigrationInfo,
+    create_migration, dry_run_migrations, migration_status, pending_migrations,
+    pending_migrations_on, rollback, rollback_in, run_migrations, run_migrations_in_schema,
+    run_migrations_limit_on, run_migrations_on, run_tenant_migrations, run_tenant_migrations_for,
+    MigrationError, MigrationInfo, PendingMigration, TenantMigration,
+};
+pub use query::{
+    transaction_mysql, transaction_postgres, transaction_sqlite, Builder, FromDbRow, Model, Op,
+    Paginated,
 };
-pub use query::{transaction_mysql, transaction_postgres, transaction_sqlite, Builder, Model, Op, Paginated};
-pub use relations::{BelongsTo, HasMany, HasOne};
+pub use record::{Column, Hooks, PrimaryKey, Record, TenantKey, TimestampField};
+pub use relations::{eager_load, BelongsTo, BelongsToMany, HasMany, HasOne, Relation, Relations};
+pub use schema::{diff_schemas, introspect, SchemaChange, TableDef};
 
-// Re-export macro
+// Re-export macros
 pub use crate::impl_model;
+pub use nucleus_macros::Model;
@@ -17,8 +17,18 @@
 //!     .await?;
 //! ```
 
-use crate::photon::db::{db, DatabaseType, QueryValue};
+use crate::photon::cursor::{mysql_keys, pg_keys, sqlite_keys, Cursor, CursorPage, Keyset};
+use crate::photon::db::{
+    begin, on_primary, route, DatabasePool, DatabaseTransaction, DatabaseType, QueryResult,
+    QueryValue, Routed,
+};
+use crate::photon::record::Column;
+use crate::photon::relations::{load_relations, Relations};
+use crate::tenant::Tenant;
 use serde::Serialize;
+use sqlx::mysql::MySqlRow;
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
 use sqlx::{FromRow, Row};
 use std::fmt::Write;
 use std::future::Future;
@@ -157,6 +167,53 @@ impl Op {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// ROW DECODING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A type that can be decoded from a row of any supported backend
+///
+/// Implemented automatically for anything implementing `sqlx::FromRow` for
+/// SQLite, PostgreSQL and MySQL rows, which `#[derive(sqlx::FromRow)]` does.
+pub trait FromDbRow:
+    for<'r> FromRow<'r, SqliteRow>
+    + for<'r> FromRow<'r, PgRow>
+    + for<'r> FromRow<'r, MySqlRow>
+    + Send
+    + Unpin
+{
+}
+
+impl<T> FromDbRow for T where
+    T: for<'r> FromRow<'r, SqliteRow>
+        + for<'r> FromRow<'r, PgRow>
+        + for<'r> FromRow<'r, MySqlRow>
+        + Send
+        + Unpin
+{
+}
+
+/// Bind a list of `QueryValue`s onto a `sqlx::query`/`query_as` for any backend
+macro_rules! bind_values {
+    ($query:expr, $values:expr) => {{
+        let mut query = $query;
+        for val in $values {
+            query = match val {
+                QueryValue::Text(v) => query.bind(v),
+                QueryValue::Int(v) => query.bind(v),
+                QueryValue::Float(v) => query.bind(v),
+                QueryValue::Bool(v) => query.bind(v),
+                QueryValue::Null => query.bind(Option::<String>::None),
+                QueryValue::Bytes(v) => query.bind(v),
+                QueryValue::Timestamp(v) => query.bind(v),
+            };
+        }
+        query
+    }};
+}
+
+pub(crate) use bind_values;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MODEL TRAIT
 // ═══════════════════════════════════════════════════════════════════════════
@@ -167,27 +224,71 @@ pub trait Model: Sized + Send + Unpin {
     /// Get the table name for this model
     fn table_name() -> &'static str;
 
+    /// Maintain `created_at`/`updated_at` when records are saved
+    fn timestamps() -> bool {
+        false
+    }
+
+    /// Column marking soft-deleted rows, if this model soft deletes
+    ///
+    /// Queries from `query()` then skip rows where it is set; use
+    /// `with_trashed()`/`only_trashed()` to see them.
+    fn soft_delete_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Column holding the owning tenant, if this model is tenant-scoped
+    ///
+    /// Builders from `query()` and `create()` then only read and write rows
+    /// of the current `Tenant`; use `without_tenant_scope()` to opt out.
+    fn tenant_column() -> Option<&'static str> {
+        None
+    }
+
+    /// The current tenant ID as a value of the tenant column's type
+    ///
+    /// Text by default; `#[derive(Model)]` binds through the field's
+    /// `TenantKey` impl, so integer columns get integers.
+    fn tenant_key(tenant: &str) -> Option<QueryValue> {
+        Some(QueryValue::from(tenant))
+    }
+
     /// Start a query builder for this model
     fn query() -> Builder<'static> {
-        Builder::new(Self::table_name())
+        let mut builder = Builder::new(Self::table_name());
+        if let Some(column) = Self::tenant_column() {
+            builder = builder.tenant_scoped_by(column, Self::tenant_key);
+        }
+        match Self::soft_delete_column() {
+            Some(column) => builder.soft_deletes(column),
+            None => builder,
+        }
     }
 
     /// Start an INSERT query builder
     fn create() -> Builder<'static> {
-        Builder::new(Self::table_name()).insert()
+        let builder = Builder::new(Self::table_name()).insert();
+        match Self::tenant_column() {
+            Some(column) => builder.tenant_scoped_by(column, Self::tenant_key),
+            None => builder,
+        }
     }
 
     /// Find a record by ID
     async fn find<T>(id: i64) -> Result<Option<T>, sqlx::Error>
     where
-        T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+        T: FromDbRow,
     {
         Self::query().r#where("id", id).first::<T>().await
     }
 
-    /// Delete a record by ID
+    /// Delete a record by ID (soft delete for soft-deleting models)
     async fn delete_by_id(id: i64) -> Result<u64, sqlx::Error> {
-        let result = Self::query().delete().r#where("id", id).execute().await?;
+        let builder = match Self::soft_delete_column() {
+            Some(column) => Self::query().update().value(column, chrono::Utc::now()),
+            None => Self::query().delete(),
+        };
+        let result = builder.r#where("id", id).execute().await?;
         Ok(result.rows_affected())
     }
 }
@@ -217,7 +318,26 @@ enum Operation {
     Delete,
 }
 
+/// Which rows a soft-deleting query sees
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Trashed {
+    Without,
+    With,
+    Only,
+}
+
+/// Tenant restriction of a tenant-scoped query
+#[derive(Clone)]
+struct TenantScope {
+    column: String,
+    /// Tenant current when the scope was applied
+    tenant: Option<String>,
+    /// `tenant` as a value of the column's type
+    value: Option<QueryValue>,
+}
+
 /// A WHERE clause
+#[derive(Clone)]
 struct WhereClause {
     column: String,
     operator: Op,
@@ -232,6 +352,7 @@ enum Conjunction {
 }
 
 /// A JOIN clause
+#[derive(Clone)]
 struct JoinClause {
     table: String,
     on_left: String,
@@ -268,6 +389,10 @@ pub struct Builder<'a> {
     operation: Operation,
     values: Vec<(String, QueryValue)>,
     includes: Vec<String>,
+    keyset: Option<Keyset>,
+    connection: Option<String>,
+    soft_delete: Option<(String, Trashed)>,
+    tenant: Option<TenantScope>,
 }
 
 impl<'a> Builder<'a> {
@@ -284,6 +409,10 @@ impl<'a> Builder<'a> {
             operation: Operation::Select,
             values: vec![],
             includes: vec![],
+            keyset: None,
+            connection: None,
+            soft_delete: None,
+            tenant: None,
         }
     }
 
@@ -309,6 +438,34 @@ impl<'a> Builder<'a> {
         self
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // CONNECTION
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Run on a named database from `[database.connections]`
+    ///
+    /// ```rust,ignore
+    /// let events = Builder::new("events")
+    ///     .on("analytics")
+    ///     .all::<Event>()
+    ///     .await?;
+    /// ```
+    pub fn on(mut self, connection: &str) -> Self {
+        self.connection = Some(connection.to_string());
+        self
+    }
+
+    /// Pool for this query: a replica for SELECTs, the primary otherwise,
+    /// in the current tenant's schema or database
+    async fn route(&self) -> Result<Routed, sqlx::Error> {
+        self.check_tenant()?;
+        route(
+            self.connection.as_deref(),
+            self.operation != Operation::Select,
+        )
+        .await
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // SELECT CLAUSES
     // ─────────────────────────────────────────────────────────────────────────
@@ -373,6 +530,134 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Add a WHERE clause on a typed column with equality operator
+    ///
+    /// The value must convert into the column's type, so mismatches are
+    /// caught at compile time.
+    pub fn where_col<M, T, V>(self, column: Column<M, T>, value: V) -> Self
+    where
+        V: Into<T>,
+        QueryValue: From<T>,
+    {
+        self.filter_op(column.name(), Op::Eq, value.into())
+    }
+
+    /// Add a WHERE clause on a typed column with a custom operator
+    pub fn filter_col<M, T, V>(self, column: Column<M, T>, op: Op, value: V) -> Self
+    where
+        V: Into<T>,
+        QueryValue: From<T>,
+    {
+        self.filter_op(column.name(), op, value.into())
+    }
+
+    /// Add an OR WHERE clause on a typed column
+    pub fn or_where_col<M, T, V>(self, column: Column<M, T>, value: V) -> Self
+    where
+        V: Into<T>,
+        QueryValue: From<T>,
+    {
+        self.or_where(column.name(), value.into())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // SOFT DELETES
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Hide rows whose `column` is set (applied by `Model::query()` for
+    /// soft-deleting models)
+    pub fn soft_deletes(mut self, column: &str) -> Self {
+        self.soft_delete = Some((column.to_string(), Trashed::Without));
+        self
+    }
+
+    /// Include soft-deleted rows
+    pub fn with_trashed(mut self) -> Self {
+        if let Some((_, trashed)) = &mut self.soft_delete {
+            *trashed = Trashed::With;
+        }
+        self
+    }
+
+    /// Return only soft-deleted rows
+    pub fn only_trashed(mut self) -> Self {
+        if let Some((_, trashed)) = &mut self.soft_delete {
+            *trashed = Trashed::Only;
+        }
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // TENANT SCOPE
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Restrict to rows whose `column` is the current tenant, and set it on
+    /// INSERT (applied by `Model::query()`/`create()` for tenant-scoped models)
+    ///
+    /// The tenant is read from `Tenant` when this is called and bound as
+    /// text. Running the query with no tenant set is an error, never an
+    /// unscoped query.
+    pub fn tenant_scoped(self, column: &str) -> Self {
+        self.tenant_scoped_by(column, |tenant| Some(QueryValue::from(tenant)))
+    }
+
+    /// `tenant_scoped`, converting the tenant ID with `key` (see `TenantKey`)
+    ///
+    /// A tenant ID that `key` rejects makes the query fail.
+    pub fn tenant_scoped_by(mut self, column: &str, key: fn(&str) -> Option<QueryValue>) -> Self {
+        let tenant = Tenant::get();
+        self.tenant = Some(TenantScope {
+            column: column.to_string(),
+            value: tenant.as_deref().and_then(key),
+            tenant,
+        });
+        self
+    }
+
+    /// Drop the tenant scope to read or write rows of any tenant
+    ///
+    /// ```rust,ignore
+    /// let total = Invoice::query().without_tenant_scope().count().await?;
+    /// ```
+    pub fn without_tenant_scope(mut self) -> Self {
+        self.tenant = None;
+        self
+    }
+
+    /// Refuse to run a tenant-scoped query outside a tenant context
+    fn check_tenant(&self) -> Result<(), sqlx::Error> {
+        match &self.tenant {
+            Some(TenantScope { tenant: None, .. }) => Err(sqlx::Error::Protocol(format!(
+                "{} is tenant-scoped but no tenant is set; use without_tenant_scope() to query across tenants",
+                self.table
+            ))),
+            Some(TenantScope {
+                column,
+                tenant: Some(tenant),
+                value: None,
+            }) => Err(sqlx::Error::Protocol(format!(
+                "tenant {} is not a valid value for {}.{}",
+                tenant, self.table, column
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// The scoped column and current tenant, if both are known
+    fn tenant_value(&self) -> Option<(&str, &QueryValue)> {
+        let scope = self.tenant.as_ref()?;
+        Some((scope.column.as_str(), scope.value.as_ref()?))
+    }
+
+    /// INSERT/UPDATE values, minus the tenant column a scope controls
+    fn scoped_values(&self) -> impl Iterator<Item = (&str, &QueryValue)> {
+        let scoped = self.tenant.as_ref().map(|s| s.column.as_str());
+        self.values
+            .iter()
+            .filter(move |(column, _)| Some(column.as_str()) != scoped)
+            .map(|(column, value)| (column.as_str(), value))
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // JOIN CLAUSES
     // ─────────────────────────────────────────────────────────────────────────
@@ -450,11 +735,29 @@ impl<'a> Builder<'a> {
     // ─────────────────────────────────────────────────────────────────────────
 
     /// Include a relationship for eager loading
+    ///
+    /// Use dots for nested relations (`"posts.comments"`). Includes are
+    /// loaded by `load()`, `load_first()` and `load_paginate()`; other
+    /// fetches return an error rather than silently dropping them.
     pub fn include(mut self, relation: &str) -> Self {
         self.includes.push(relation.to_string());
         self
     }
 
+    /// Reject includes on a fetch that cannot eager load them
+    fn check_includes(&self) -> Result<(), sqlx::Error> {
+        if self.includes.is_empty() {
+            return Ok(());
+        }
+        Err(sqlx::Error::Configuration(
+            format!(
+                "include({}) requires load(), load_first() or load_paginate()",
+                self.includes.join(", ")
+            )
+            .into(),
+        ))
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // SQL GENERATION
     // ─────────────────────────────────────────────────────────────────────────
@@ -482,33 +785,7 @@ impl<'a> Builder<'a> {
                     .unwrap();
                 }
 
-                // Where
-                if !self.wheres.is_empty() {
-                    sql.push_str(" WHERE ");
-                    for (i, w) in self.wheres.iter().enumerate() {
-                        if i > 0 {
-                            match w.conjunction {
-                                Conjunction::And => sql.push_str(" AND "),
-                                Conjunction::Or => sql.push_str(" OR "),
-                            }
-                        }
-
-                        if w.operator.requires_value() {
-                            write!(
-                                sql,
-                                "{} {} {}",
-                                w.column,
-                                w.operator.to_sql(db_type),
-                                db_type.placeholder(param_index)
-                            )
-                            .unwrap();
-                            bindings.push(&w.value);
-                            param_index += 1;
-                        } else {
-                            write!(sql, "{} {}", w.column, w.operator.to_sql(db_type)).unwrap();
-                        }
-                    }
-                }
+                self.write_where(&mut sql, &mut bindings, &mut param_index, db_type);
 
                 // Order
                 if !self.order_by.is_empty() {
@@ -531,24 +808,23 @@ impl<'a> Builder<'a> {
             }
 
             Operation::Insert => {
-                let cols: Vec<&String> = self.values.iter().map(|(c, _)| c).collect();
-                let placeholders: Vec<String> = (1..=self.values.len())
-                    .map(|i| db_type.placeholder(i))
-                    .collect();
+                // A scoped insert always belongs to the current tenant
+                let values: Vec<(&str, &QueryValue)> =
+                    self.scoped_values().chain(self.tenant_value()).collect();
+                let cols: Vec<&str> = values.iter().map(|(c, _)| *c).collect();
+                let placeholders: Vec<String> =
+                    (1..=values.len()).map(|i| db_type.placeholder(i)).collect();
 
                 write!(
                     sql,
                     "INSERT INTO {} ({}) VALUES ({})",
                     self.table,
-                    cols.iter()
-                        .map(|s| s.as_str())
-                        .collect::<Vec<_>>()
-                        .join(", "),
+                    cols.join(", "),
                     placeholders.join(", ")
                 )
                 .unwrap();
 
-                for (_, v) in &self.values {
+                for (_, v) in values {
                     bindings.push(v);
                 }
             }
@@ -556,71 +832,123 @@ impl<'a> Builder<'a> {
             Operation::Update => {
                 write!(sql, "UPDATE {} SET ", self.table).unwrap();
 
-                let sets: Vec<String> = self
-                    .values
+                // Rows can't be moved out of the tenant they're scoped to
+                let values: Vec<(&str, &QueryValue)> = self.scoped_values().collect();
+                let sets: Vec<String> = values
                     .iter()
                     .enumerate()
                     .map(|(i, (col, _))| format!("{} = {}", col, db_type.placeholder(i + 1)))
                     .collect();
                 sql.push_str(&sets.join(", "));
 
-                for (_, v) in &self.values {
+                for (_, v) in values {
                     bindings.push(v);
                     param_index += 1;
                 }
 
-                // Where
-                if !self.wheres.is_empty() {
-                    sql.push_str(" WHERE ");
-                    for (i, w) in self.wheres.iter().enumerate() {
-                        if i > 0 {
-                            match w.conjunction {
-                                Conjunction::And => sql.push_str(" AND "),
-                                Conjunction::Or => sql.push_str(" OR "),
-                            }
-                        }
-                        write!(
-                            sql,
-                            "{} {} {}",
-                            w.column,
-                            w.operator.to_sql(db_type),
-                            db_type.placeholder(param_index)
-                        )
-                        .unwrap();
-                        bindings.push(&w.value);
-                        param_index += 1;
-                    }
-                }
+                self.write_where(&mut sql, &mut bindings, &mut param_index, db_type);
             }
 
             Operation::Delete => {
                 write!(sql, "DELETE FROM {}", self.table).unwrap();
+                self.write_where(&mut sql, &mut bindings, &mut param_index, db_type);
+            }
+        }
+
+        (sql, bindings)
+    }
+
+    /// Write the WHERE clause: user filters, then tenant and soft-delete
+    /// scopes and keyset
+    ///
+    /// User filters are parenthesized when scopes follow, so their OR
+    /// conditions can't escape the scope.
+    fn write_where<'s>(
+        &'s self,
+        sql: &mut String,
+        bindings: &mut Vec<&'s QueryValue>,
+        param_index: &mut usize,
+        db_type: DatabaseType,
+    ) {
+        let scope = self
+            .soft_delete
+            .as_ref()
+            .and_then(|(col, trashed)| match trashed {
+                Trashed::Without => Some(format!("{}.{} IS NULL", self.table, col)),
+                Trashed::Only => Some(format!("{}.{} IS NOT NULL", self.table, col)),
+                Trashed::With => None,
+            });
+        let tenant = self.tenant_value();
+        let keyset = self.keyset.as_ref().filter(|k| k.has_position());
+        let scoped = tenant.is_some() || scope.is_some() || keyset.is_some();
+
+        if self.wheres.is_empty() && !scoped {
+            return;
+        }
+        sql.push_str(" WHERE ");
 
-                if !self.wheres.is_empty() {
-                    sql.push_str(" WHERE ");
-                    for (i, w) in self.wheres.iter().enumerate() {
-                        if i > 0 {
-                            match w.conjunction {
-                                Conjunction::And => sql.push_str(" AND "),
-                                Conjunction::Or => sql.push_str(" OR "),
-                            }
-                        }
-                        write!(
-                            sql,
-                            "{} {} {}",
-                            w.column,
-                            w.operator.to_sql(db_type),
-                            db_type.placeholder(param_index)
-                        )
-                        .unwrap();
-                        bindings.push(&w.value);
-                        param_index += 1;
+        if !self.wheres.is_empty() {
+            if scoped {
+                sql.push('(');
+            }
+            for (i, w) in self.wheres.iter().enumerate() {
+                if i > 0 {
+                    match w.conjunction {
+                        Conjunction::And => sql.push_str(" AND "),
+                        Conjunction::Or => sql.push_str(" OR "),
                     }
                 }
+
+                if w.operator.requires_value() {
+                    write!(
+                        sql,
+                        "{} {} {}",
+                        w.column,
+                        w.operator.to_sql(db_type),
+                        db_type.placeholder(*param_index)
+                    )
+                    .unwrap();
+                    bindings.push(&w.value);
+                    *param_index += 1;
+                } else {
+                    write!(sql, "{} {}", w.column, w.operator.to_sql(db_type)).unwrap();
+                }
+            }
+            if scoped {
+                sql.push(')');
             }
         }
 
-        (sql, bindings)
+        let mut first = self.wheres.is_empty();
+        if let Some((column, tenant)) = tenant {
+            if !first {
+                sql.push_str(" AND ");
+            }
+            write!(
+                sql,
+                "{}.{} = {}",
+                self.table,
+                column,
+                db_type.placeholder(*param_index)
+            )
+            .unwrap();
+            bindings.push(tenant);
+            *param_index += 1;
+            first = false;
+        }
+        if let Some(scope) = scope {
+            if !first {
+                sql.push_str(" AND ");
+            }
+            sql.push_str(&scope);
+            first = false;
+        }
+        if let Some(keyset) = keyset {
+            if !first {
+                sql.push_str(" AND ");
+            }
+            keyset.write_sql(sql, bindings, param_index, db_type);
+        }
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -630,76 +958,110 @@ impl<'a> Builder<'a> {
     /// Fetch all matching rows
     pub async fn all<T>(self) -> Result<Vec<T>, sqlx::Error>
     where
-        T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+        T: FromDbRow,
     {
-        let pool = db();
-        let (sql, values) = self.to_sql(pool.db_type());
-
-        // For SQLite (primary support)
-        if let Some(sqlite_pool) = pool.as_sqlite() {
-            let mut query = sqlx::query_as::<_, T>(&sql);
-
-            for val in values {
-                query = match val {
-                    QueryValue::Text(v) => query.bind(v),
-                    QueryValue::Int(v) => query.bind(v),
-                    QueryValue::Float(v) => query.bind(v),
-                    QueryValue::Bool(v) => query.bind(v),
-                    QueryValue::Null => query.bind(Option::<String>::None),
-                    QueryValue::Bytes(v) => query.bind(v),
-                };
-            }
+        self.check_includes()?;
+        let routed = self.route().await?;
+        self.all_on(&routed).await
+    }
 
-            return query.fetch_all(sqlite_pool).await;
+    /// Fetch all matching rows on an already-routed connection
+    async fn all_on<T>(&self, routed: &Routed) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        let (sql, values) = self.to_sql(routed.db_type());
+
+        match routed.pool() {
+            DatabasePool::Sqlite(p) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(p)
+                    .await
+            }
+            DatabasePool::Postgres(p) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(&mut *routed.checkout(p).await?)
+                    .await
+            }
+            DatabasePool::MySql(p) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(p)
+                    .await
+            }
         }
+    }
 
-        Err(sqlx::Error::Configuration(
-            "Unsupported database type for this query".into(),
-        ))
+    /// Fetch all matching rows and eager load the included relations
+    ///
+    /// Runs the main query, then one batched `IN (...)` query per included
+    /// relation and nesting level, attaching results to their parents.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let users = User::query()
+    ///     .include("posts.comments")
+    ///     .include("roles")
+    ///     .load::<User>()
+    ///     .await?;
+    /// ```
+    pub async fn load<T>(mut self) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: FromDbRow + Relations,
+    {
+        let includes = std::mem::take(&mut self.includes);
+        let routed = self.route().await?;
+        let mut models = self.all_on::<T>(&routed).await?;
+        load_relations(&mut models, &includes, &routed).await?;
+        Ok(models)
     }
 
     /// Fetch the first matching row
     pub async fn first<T>(self) -> Result<Option<T>, sqlx::Error>
     where
-        T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+        T: FromDbRow,
     {
         let results = self.limit(1).all::<T>().await?;
         Ok(results.into_iter().next())
     }
 
+    /// Fetch the first matching row and eager load the included relations
+    pub async fn load_first<T>(self) -> Result<Option<T>, sqlx::Error>
+    where
+        T: FromDbRow + Relations,
+    {
+        let results = self.limit(1).load::<T>().await?;
+        Ok(results.into_iter().next())
+    }
+
     /// Execute an INSERT/UPDATE/DELETE and return query result
     ///
-    /// For INSERT, use `.last_insert_rowid()` to get the ID.
+    /// For INSERT, use `.last_insert_id()` to get the ID (SQLite/MySQL).
     /// For UPDATE/DELETE, use `.rows_affected()` to get count.
-    pub async fn execute(self) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-        let pool = db();
-        let (sql, values) = self.to_sql(pool.db_type());
-
-        if let Some(sqlite_pool) = pool.as_sqlite() {
-            let mut query = sqlx::query(&sql);
-
-            for val in values {
-                query = match val {
-                    QueryValue::Text(v) => query.bind(v),
-                    QueryValue::Int(v) => query.bind(v),
-                    QueryValue::Float(v) => query.bind(v),
-                    QueryValue::Bool(v) => query.bind(v),
-                    QueryValue::Null => query.bind(Option::<String>::None),
-                    QueryValue::Bytes(v) => query.bind(v),
-                };
-            }
-
-            return query.execute(sqlite_pool).await;
+    pub async fn execute(self) -> Result<QueryResult, sqlx::Error> {
+        let routed = self.route().await?;
+        let (sql, values) = self.to_sql(routed.db_type());
+
+        match routed.pool() {
+            DatabasePool::Sqlite(p) => bind_values!(sqlx::query(&sql), values)
+                .execute(p)
+                .await
+                .map(QueryResult::from),
+            DatabasePool::Postgres(p) => bind_values!(sqlx::query(&sql), values)
+                .execute(&mut *routed.checkout(p).await?)
+                .await
+                .map(QueryResult::from),
+            DatabasePool::MySql(p) => bind_values!(sqlx::query(&sql), values)
+                .execute(p)
+                .await
+                .map(QueryResult::from),
         }
-
-        Err(sqlx::Error::Configuration(
-            "Unsupported database type".into(),
-        ))
     }
 
     /// Execute an INSERT and return the new row's ID
     ///
-    /// Convenience method that calls `execute()` and extracts `last_insert_rowid()`.
+    /// On SQLite and MySQL this reads the driver's last-insert ID. PostgreSQL
+    /// has no equivalent, so `RETURNING id` is appended to the statement.
     ///
     /// # Example
     ///
@@ -710,8 +1072,21 @@ impl<'a> Builder<'a> {
     ///     .await?;
     /// ```
     pub async fn insert_get_id(self) -> Result<i64, sqlx::Error> {
+        let routed = self.route().await?;
+
+        if let DatabasePool::Postgres(p) = routed.pool() {
+            let (mut sql, values) = self.to_sql(routed.db_type());
+            sql.push_str(" RETURNING id");
+            let row = bind_values!(sqlx::query(&sql), values)
+                .fetch_one(&mut *routed.checkout(p).await?)
+                .await?;
+            return row.try_get::<i64, _>("id");
+        }
+
         let result = self.execute().await?;
-        Ok(result.last_insert_rowid())
+        result
+            .last_insert_id()
+            .ok_or_else(|| sqlx::Error::Protocol("No last insert ID returned".into()))
     }
 
     /// Execute an UPDATE/DELETE and return the number of affected rows
@@ -722,37 +1097,88 @@ impl<'a> Builder<'a> {
         Ok(result.rows_affected())
     }
 
-    /// Count matching rows
-    pub async fn count(self) -> Result<i64, sqlx::Error> {
-        let pool = db();
-        let mut builder = Builder::new(self.table);
-        builder.select = vec!["COUNT(*) as count".to_string()];
-        builder.wheres = self.wheres;
-        builder.joins = self.joins;
-
-        let (sql, values) = builder.to_sql(pool.db_type());
-
-        if let Some(sqlite_pool) = pool.as_sqlite() {
-            let mut query = sqlx::query(&sql);
-
-            for val in values {
-                query = match val {
-                    QueryValue::Text(v) => query.bind(v),
-                    QueryValue::Int(v) => query.bind(v),
-                    QueryValue::Float(v) => query.bind(v),
-                    QueryValue::Bool(v) => query.bind(v),
-                    QueryValue::Null => query.bind(Option::<String>::None),
-                    QueryValue::Bytes(v) => query.bind(v),
-                };
+    /// Fetch all matching rows inside a transaction
+    pub async fn all_in<T>(self, tx: &mut DatabaseTransaction) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        self.check_tenant()?;
+        self.check_includes()?;
+        let (sql, values) = self.to_sql(tx.db_type());
+
+        match tx {
+            DatabaseTransaction::Sqlite(t) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(&mut **t)
+                    .await
+            }
+            DatabaseTransaction::Postgres(t) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(&mut **t)
+                    .await
+            }
+            DatabaseTransaction::MySql(t) => {
+                bind_values!(sqlx::query_as::<_, T>(&sql), values)
+                    .fetch_all(&mut **t)
+                    .await
             }
+        }
+    }
 
-            let row = query.fetch_one(sqlite_pool).await?;
-            return Ok(row.get::<i64, _>("count"));
+    /// Fetch the first matching row inside a transaction
+    pub async fn first_in<T>(self, tx: &mut DatabaseTransaction) -> Result<Option<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        let results = self.limit(1).all_in::<T>(tx).await?;
+        Ok(results.into_iter().next())
+    }
+
+    /// Execute an INSERT/UPDATE/DELETE inside a transaction
+    pub async fn execute_in(
+        self,
+        tx: &mut DatabaseTransaction,
+    ) -> Result<QueryResult, sqlx::Error> {
+        self.check_tenant()?;
+        let (sql, values) = self.to_sql(tx.db_type());
+
+        match tx {
+            DatabaseTransaction::Sqlite(t) => bind_values!(sqlx::query(&sql), values)
+                .execute(&mut **t)
+                .await
+                .map(QueryResult::from),
+            DatabaseTransaction::Postgres(t) => bind_values!(sqlx::query(&sql), values)
+                .execute(&mut **t)
+                .await
+                .map(QueryResult::from),
+            DatabaseTransaction::MySql(t) => bind_values!(sqlx::query(&sql), values)
+                .execute(&mut **t)
+                .await
+                .map(QueryResult::from),
+        }
+    }
+
+    /// Execute an INSERT inside a transaction and return the new row's ID
+    pub async fn insert_get_id_in(self, tx: &mut DatabaseTransaction) -> Result<i64, sqlx::Error> {
+        if let DatabaseTransaction::Postgres(t) = tx {
+            self.check_tenant()?;
+            let (mut sql, values) = self.to_sql(DatabaseType::Postgres);
+            sql.push_str(" RETURNING id");
+            let row = bind_values!(sqlx::query(&sql), values)
+                .fetch_one(&mut **t)
+                .await?;
+            return row.try_get::<i64, _>("id");
         }
 
-        Err(sqlx::Error::Configuration(
-            "Unsupported database type".into(),
-        ))
+        let result = self.execute_in(tx).await?;
+        result
+            .last_insert_id()
+            .ok_or_else(|| sqlx::Error::Protocol("No last insert ID returned".into()))
+    }
+
+    /// Count matching rows
+    pub async fn count(self) -> Result<i64, sqlx::Error> {
+        self.count_builder().fetch_count().await
     }
 
     /// Check if any matching rows exist
@@ -782,58 +1208,14 @@ impl<'a> Builder<'a> {
     /// ```
     pub async fn paginate<T>(self, page: i64, per_page: i64) -> Result<Paginated<T>, sqlx::Error>
     where
-        T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+        T: FromDbRow,
     {
         let per_page = per_page.clamp(1, 100); // Enforce limits
         let page = page.max(1);
         let offset = (page - 1) * per_page;
 
-        // Clone the wheres and joins for count query
-        let count_builder = Builder {
-            table: self.table,
-            select: vec!["COUNT(*) as count".to_string()],
-            wheres: self.wheres.iter().map(|w| WhereClause {
-                column: w.column.clone(),
-                operator: w.operator,
-                value: w.value.clone(),
-                conjunction: w.conjunction,
-            }).collect(),
-            joins: self.joins.iter().map(|j| JoinClause {
-                table: j.table.clone(),
-                on_left: j.on_left.clone(),
-                on_right: j.on_right.clone(),
-                join_type: j.join_type,
-            }).collect(),
-            order_by: vec![],
-            limit: None,
-            offset: None,
-            operation: Operation::Select,
-            values: vec![],
-            includes: vec![],
-        };
-
-        let pool = db();
-
         // Get total count
-        let (count_sql, count_values) = count_builder.to_sql(pool.db_type());
-        let total: i64 = if let Some(sqlite_pool) = pool.as_sqlite() {
-            let mut query = sqlx::query(&count_sql);
-            for val in count_values {
-                query = match val {
-                    QueryValue::Text(v) => query.bind(v),
-                    QueryValue::Int(v) => query.bind(v),
-                    QueryValue::Float(v) => query.bind(v),
-                    QueryValue::Bool(v) => query.bind(v),
-                    QueryValue::Null => query.bind(Option::<String>::None),
-                    QueryValue::Bytes(v) => query.bind(v),
-                };
-            }
-            let row = query.fetch_one(sqlite_pool).await?;
-            row.get::<i64, _>("count")
-        } else {
-            return Err(sqlx::Error::Configuration("Unsupported database type".into()));
-        };
-
+        let total = self.count_builder().fetch_count().await?;
         let total_pages = ((total as f64) / (per_page as f64)).ceil() as i64;
 
         // Get paginated data
@@ -847,6 +1229,147 @@ impl<'a> Builder<'a> {
             total_pages,
         })
     }
+
+    /// Paginate like `paginate()` and eager load the included relations
+    /// onto the page's rows
+    pub async fn load_paginate<T>(
+        mut self,
+        page: i64,
+        per_page: i64,
+    ) -> Result<Paginated<T>, sqlx::Error>
+    where
+        T: FromDbRow + Relations,
+    {
+        let includes = std::mem::take(&mut self.includes);
+        let routed = self.route().await?;
+        let mut result = self.paginate::<T>(page, per_page).await?;
+        load_relations(&mut result.data, &includes, &routed).await?;
+        Ok(result)
+    }
+
+    /// Paginate with an opaque keyset cursor
+    ///
+    /// Pages by the ORDER BY column values of the last row seen instead of
+    /// an OFFSET, so deep pages stay fast and rows don't shift when data
+    /// changes between requests. `id` is appended as a tiebreaker unless
+    /// already ordered on; ordered columns must be non-NULL. No `COUNT(*)`
+    /// is run.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let after = params.after.as_deref().map(Cursor::decode).transpose()?;
+    /// let page = Post::query()
+    ///     .order_by("published_at", "DESC")
+    ///     .paginate_cursor::<Post>(after, 20)
+    ///     .await?;
+    ///
+    /// let next = page.next_cursor.map(|c| c.to_string());
+    /// ```
+    pub async fn paginate_cursor<T>(
+        self,
+        after: Option<Cursor>,
+        per_page: i64,
+    ) -> Result<CursorPage<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        self.fetch_cursor_page(after, per_page, false).await
+    }
+
+    /// Fetch the page that ends just before `before`
+    ///
+    /// Counterpart to `paginate_cursor()` for walking backwards with a
+    /// page's `prev_cursor`. Rows are returned in the query's ordering.
+    pub async fn paginate_cursor_before<T>(
+        self,
+        before: Option<Cursor>,
+        per_page: i64,
+    ) -> Result<CursorPage<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        self.fetch_cursor_page(before, per_page, true).await
+    }
+
+    async fn fetch_cursor_page<T>(
+        mut self,
+        cursor: Option<Cursor>,
+        per_page: i64,
+        backward: bool,
+    ) -> Result<CursorPage<T>, sqlx::Error>
+    where
+        T: FromDbRow,
+    {
+        self.check_includes()?;
+        let per_page = per_page.clamp(1, 100);
+        let keyset = Keyset::new(self.table, &self.order_by, cursor, backward)?;
+        self.order_by = keyset.query_order();
+        self.limit = Some(per_page + 1);
+        self.offset = None;
+        self.keyset = Some(keyset);
+
+        let routed = self.route().await?;
+        let (sql, values) = self.to_sql(routed.db_type());
+        let keyset = self.keyset.as_ref().expect("keyset set above");
+        let cols = keyset.columns();
+
+        let rows = match routed.pool() {
+            DatabasePool::Sqlite(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_all(p)
+                .await?
+                .iter()
+                .map(|row| Ok((T::from_row(row)?, sqlite_keys(row, &cols)?)))
+                .collect::<Result<Vec<_>, sqlx::Error>>()?,
+            DatabasePool::Postgres(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_all(&mut *routed.checkout(p).await?)
+                .await?
+                .iter()
+                .map(|row| Ok((T::from_row(row)?, pg_keys(row, &cols)?)))
+                .collect::<Result<Vec<_>, sqlx::Error>>()?,
+            DatabasePool::MySql(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_all(p)
+                .await?
+                .iter()
+                .map(|row| Ok((T::from_row(row)?, mysql_keys(row, &cols)?)))
+                .collect::<Result<Vec<_>, sqlx::Error>>()?,
+        };
+
+        Ok(keyset.page(rows, per_page))
+    }
+
+    /// Build a `COUNT(*)` query sharing this builder's joins and filters
+    fn count_builder(&self) -> Builder<'a> {
+        let mut builder = Builder::new(self.table);
+        builder.select = vec!["COUNT(*) as count".to_string()];
+        builder.wheres = self.wheres.clone();
+        builder.joins = self.joins.clone();
+        builder.connection = self.connection.clone();
+        builder.soft_delete = self.soft_delete.clone();
+        builder.tenant = self.tenant.clone();
+        builder
+    }
+
+    /// Run a `COUNT(*)` query built by `count_builder()`
+    async fn fetch_count(self) -> Result<i64, sqlx::Error> {
+        let routed = self.route().await?;
+        let (sql, values) = self.to_sql(routed.db_type());
+
+        match routed.pool() {
+            DatabasePool::Sqlite(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_one(p)
+                .await?
+                .try_get::<i64, _>("count"),
+            DatabasePool::Postgres(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_one(&mut *routed.checkout(p).await?)
+                .await?
+                .try_get::<i64, _>("count"),
+            DatabasePool::MySql(p) => bind_values!(sqlx::query(&sql), values)
+                .fetch_one(p)
+                .await?
+                .try_get::<i64, _>("count"),
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -856,7 +1379,8 @@ impl<'a> Builder<'a> {
 /// Execute a closure within a SQLite database transaction
 ///
 /// The transaction is automatically committed if the closure returns Ok,
-/// or rolled back if it returns Err or panics.
+/// or rolled back if it returns Err or panics. Like `photon::begin()`, it
+/// runs on the current tenant's database or schema.
 ///
 /// # Example
 ///
@@ -877,15 +1401,11 @@ where
         &'c mut sqlx::Transaction<'_, sqlx::Sqlite>,
     ) -> Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'c>>,
 {
-    let pool = db();
-
-    let sqlite_pool = pool
-        .as_sqlite()
-        .ok_or_else(|| sqlx::Error::Configuration("Not a SQLite database".into()))?;
-
-    let mut tx = sqlite_pool.begin().await?;
+    let DatabaseTransaction::Sqlite(mut tx) = begin().await? else {
+        return Err(sqlx::Error::Configuration("Not a SQLite database".into()));
+    };
 
-    match f(&mut tx).await {
+    match on_primary(f(&mut tx)).await {
         Ok(result) => {
             tx.commit().await?;
             Ok(result)
@@ -918,15 +1438,13 @@ where
         &'c mut sqlx::Transaction<'_, sqlx::Postgres>,
     ) -> Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'c>>,
 {
-    let pool = db();
-
-    let pg_pool = pool
-        .as_postgres()
-        .ok_or_else(|| sqlx::Error::Configuration("Not a PostgreSQL database".into()))?;
-
-    let mut tx = pg_pool.begin().await?;
+    let DatabaseTransaction::Postgres(mut tx) = begin().await? else {
+        return Err(sqlx::Error::Configuration(
+            "Not a PostgreSQL database".into(),
+        ));
+    };
 
-    match f(&mut tx).await {
+    match on_primary(f(&mut tx)).await {
         Ok(result) => {
             tx.commit().await?;
             Ok(result)
@@ -959,15 +1477,11 @@ where
         &'c mut sqlx::Transaction<'_, sqlx::MySql>,
     ) -> Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'c>>,
 {
-    let pool = db();
-
-    let mysql_pool = pool
-        .as_mysql()
-        .ok_or_else(|| sqlx::Error::Configuration("Not a MySQL database".into()))?;
-
-    let mut tx = mysql_pool.begin().await?;
+    let DatabaseTransaction::MySql(mut tx) = begin().await? else {
+        return Err(sqlx::Error::Configuration("Not a MySQL database".into()));
+    };
 
-    match f(&mut tx).await {
+    match on_primary(f(&mut tx)).await {
         Ok(result) => {
             tx.commit().await?;
             Ok(result)
@@ -1295,6 +1809,94 @@ mod tests {
         assert_eq!(bindings.len(), 3); // 2 values + 1 where
     }
 
+    fn texts(bindings: &[&QueryValue]) -> Vec<String> {
+        bindings
+            .iter()
+            .map(|v| match v {
+                QueryValue::Text(s) => s.clone(),
+                QueryValue::Int(i) => i.to_string(),
+                _ => "?".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tenant_scope_select() {
+        let builder = Tenant::with("acme", || {
+            Builder::new("projects")
+                .tenant_scoped("tenant_id")
+                .r#where("status", "open")
+                .or_where("status", "draft")
+        });
+
+        let (sql, bindings) = builder.to_sql(DatabaseType::Postgres);
+        assert_eq!(
+            sql,
+            "SELECT * FROM projects WHERE (status = $1 OR status = $2) AND projects.tenant_id = $3"
+        );
+        assert_eq!(texts(&bindings), vec!["open", "draft", "acme"]);
+    }
+
+    #[test]
+    fn test_tenant_scope_writes() {
+        let insert = Tenant::with("acme", || {
+            Builder::new("projects")
+                .insert()
+                .tenant_scoped("tenant_id")
+                .value("name", "Apollo")
+                .value("tenant_id", "globex")
+        });
+        let (sql, bindings) = insert.to_sql(DatabaseType::Sqlite);
+        assert_eq!(sql, "INSERT INTO projects (name, tenant_id) VALUES (?, ?)");
+        assert_eq!(texts(&bindings), vec!["Apollo", "acme"]);
+
+        let update = Tenant::with("acme", || {
+            Builder::new("projects")
+                .tenant_scoped("tenant_id")
+                .update()
+                .value("name", "Gemini")
+                .value("tenant_id", "globex")
+                .r#where("id", 7i64)
+        });
+        let (sql, bindings) = update.to_sql(DatabaseType::Postgres);
+        assert_eq!(
+            sql,
+            "UPDATE projects SET name = $1 WHERE (id = $2) AND projects.tenant_id = $3"
+        );
+        assert_eq!(texts(&bindings), vec!["Gemini", "7", "acme"]);
+
+        let delete = Tenant::with("acme", || {
+            Builder::new("projects").tenant_scoped("tenant_id").delete()
+        });
+        let (sql, _) = delete.to_sql(DatabaseType::Sqlite);
+        assert_eq!(sql, "DELETE FROM projects WHERE projects.tenant_id = ?");
+    }
+
+    #[test]
+    fn test_without_tenant_scope() {
+        let builder = Tenant::with("acme", || {
+            Builder::new("projects")
+                .tenant_scoped("tenant_id")
+                .without_tenant_scope()
+        });
+        let (sql, bindings) = builder.to_sql(DatabaseType::Sqlite);
+        assert_eq!(sql, "SELECT * FROM projects");
+        assert!(bindings.is_empty());
+        assert!(builder.check_tenant().is_ok());
+    }
+
+    #[test]
+    fn test_tenant_scope_requires_tenant() {
+        let builder = Builder::new("projects").tenant_scoped("tenant_id");
+        let err = builder.check_tenant().unwrap_err();
+        assert!(err.to_string().contains("projects is tenant-scoped"));
+
+        let scoped = Tenant::with("acme", || {
+            Builder::new("projects").tenant_scoped("tenant_id")
+        });
+        assert!(scoped.check_tenant().is_ok());
+    }
+
     #[test]
     fn test_paginated_has_next_page() {
         let paginated: Paginated<i32> = Paginated {
@@ -1410,4 +2012,3 @@ mod tests {
         assert!(json.contains("\"total_pages\":1"));
     }
 }
-
@@ -0,0 +1,879 @@
+//! Typed Columns and Active Records
+//!
+//! Support types for `#[derive(Model)]`. The derive reads a struct's fields
+//! and generates typed column constants plus a `Record` implementation, so
+//! instances can persist themselves without string column names.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::photon::{Model, Op, Record};
+//!
+//! #[derive(Model, sqlx::FromRow)]
+//! #[photon(table = "users")]
+//! struct User {
+//!     #[photon(primary_key)]
+//!     id: i64,
+//!     email: String,
+//!     age: i64,
+//! }
+//!
+//! let mut user = User { id: 0, email: "a@b.c".into(), age: 30 };
+//! user.save().await?; // INSERT, sets `user.id`
+//!
+//! let adults = User::query()
+//!     .filter_col(User::AGE, Op::Gte, 18)   // i64 column, i64 value
+//!     .where_col(User::EMAIL, "a@b.c")       // String column, &str value
+//!     .all::<User>()
+//!     .await?;
+//! ```
+
+use crate::photon::db::{route, DatabaseTransaction, QueryValue};
+use crate::photon::query::Model;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fmt;
+use std::marker::PhantomData;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TYPED COLUMNS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A column of model `M` holding values of type `T`
+///
+/// Used with `Builder::where_col`/`filter_col` so that filter values are
+/// checked against the column type at compile time.
+pub struct Column<M, T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (M, T)>,
+}
+
+impl<M, T> Column<M, T> {
+    /// Create a column reference (used by `#[derive(Model)]`)
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the SQL column name
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<M, T> Clone for Column<M, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M, T> Copy for Column<M, T> {}
+
+impl<M, T> fmt::Debug for Column<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Column").field(&self.name).finish()
+    }
+}
+
+impl<M, T> fmt::Display for Column<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PRIMARY KEYS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A field type usable as a model primary key
+///
+/// A key is considered unset (not yet persisted) when it is `0`, empty or
+/// `None`; unset keys are left to the database to generate on INSERT.
+pub trait PrimaryKey {
+    /// The key value, or `None` if the record has not been persisted
+    fn key_value(&self) -> Option<QueryValue>;
+
+    /// Store a database-generated ID
+    fn set_generated(&mut self, id: i64);
+}
+
+impl PrimaryKey for i64 {
+    fn key_value(&self) -> Option<QueryValue> {
+        (*self != 0).then_some(QueryValue::Int(*self))
+    }
+
+    fn set_generated(&mut self, id: i64) {
+        *self = id;
+    }
+}
+
+impl PrimaryKey for i32 {
+    fn key_value(&self) -> Option<QueryValue> {
+        (*self != 0).then_some(QueryValue::Int(*self as i64))
+    }
+
+    fn set_generated(&mut self, id: i64) {
+        *self = id as i32;
+    }
+}
+
+impl PrimaryKey for String {
+    fn key_value(&self) -> Option<QueryValue> {
+        (!self.is_empty()).then(|| QueryValue::Text(self.clone()))
+    }
+
+    fn set_generated(&mut self, id: i64) {
+        *self = id.to_string();
+    }
+}
+
+impl<K: PrimaryKey + Default> PrimaryKey for Option<K> {
+    fn key_value(&self) -> Option<QueryValue> {
+        self.as_ref().and_then(PrimaryKey::key_value)
+    }
+
+    fn set_generated(&mut self, id: i64) {
+        self.get_or_insert_with(K::default).set_generated(id);
+    }
+}
+
+/// A field type usable as a model's tenant column
+///
+/// Converts the current `Tenant` ID into a value of the column's type, so
+/// integer tenant columns are bound as integers. `None` means the ID is not
+/// valid for the column (e.g. `"acme"` for an integer column).
+pub trait TenantKey {
+    fn tenant_key(tenant: &str) -> Option<QueryValue>;
+}
+
+impl TenantKey for String {
+    fn tenant_key(tenant: &str) -> Option<QueryValue> {
+        Some(QueryValue::Text(tenant.to_string()))
+    }
+}
+
+macro_rules! int_tenant_key {
+    ($($ty:ty),*) => {$(
+        impl TenantKey for $ty {
+            fn tenant_key(tenant: &str) -> Option<QueryValue> {
+                tenant.parse::<$ty>().ok().map(|id| QueryValue::Int(id as i64))
+            }
+        }
+    )*};
+}
+
+int_tenant_key!(i16, i32, i64, u16, u32);
+
+impl<K: TenantKey> TenantKey for Option<K> {
+    fn tenant_key(tenant: &str) -> Option<QueryValue> {
+        K::tenant_key(tenant)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TIMESTAMPS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Column set on INSERT for models with timestamps
+pub const CREATED_AT: &str = "created_at";
+
+/// Column set on INSERT and UPDATE for models with timestamps
+pub const UPDATED_AT: &str = "updated_at";
+
+/// A field type that can hold a managed timestamp
+///
+/// Non-optional fields ignore `None` (a restored record keeps its old
+/// `deleted_at` value in memory).
+pub trait TimestampField {
+    /// Store `value` in the field
+    fn set_timestamp(&mut self, value: Option<DateTime<Utc>>);
+}
+
+impl TimestampField for DateTime<Utc> {
+    fn set_timestamp(&mut self, value: Option<DateTime<Utc>>) {
+        if let Some(value) = value {
+            *self = value;
+        }
+    }
+}
+
+impl TimestampField for NaiveDateTime {
+    fn set_timestamp(&mut self, value: Option<DateTime<Utc>>) {
+        if let Some(value) = value {
+            *self = value.naive_utc();
+        }
+    }
+}
+
+impl TimestampField for String {
+    fn set_timestamp(&mut self, value: Option<DateTime<Utc>>) {
+        if let Some(value) = value {
+            *self = value.to_rfc3339();
+        }
+    }
+}
+
+impl<T: TimestampField + Default> TimestampField for Option<T> {
+    fn set_timestamp(&mut self, value: Option<DateTime<Utc>>) {
+        match value {
+            Some(_) => self.get_or_insert_with(T::default).set_timestamp(value),
+            None => *self = None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// LIFECYCLE HOOKS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Callbacks run around record writes
+///
+/// Hooks run inside the record's transaction, so queries made through
+/// `tx` (`Builder::execute_in`, `all_in`, ...) commit or roll back with the
+/// write. Returning an error aborts and rolls back.
+///
+/// `#[derive(Model)]` implements this with no-op hooks unless the struct
+/// has `#[photon(hooks)]`, in which case implement it yourself:
+///
+/// ```rust,ignore
+/// #[derive(Model, sqlx::FromRow)]
+/// #[photon(table = "posts", hooks)]
+/// struct Post { id: i64, title: String, slug: String }
+///
+/// #[async_trait]
+/// impl Hooks for Post {
+///     async fn before_save(&mut self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+///         self.slug = slugify(&self.title);
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait Hooks: Send {
+    /// Runs before every INSERT or UPDATE
+    async fn before_save(&mut self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    /// Runs after an INSERT, once the primary key is set
+    async fn after_create(&mut self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    /// Runs before a soft or hard delete
+    async fn before_delete(&self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// RECORD TRAIT
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A model instance that can insert, update and delete itself
+///
+/// Implemented by `#[derive(Model)]`; the persistence methods are provided.
+/// Each write runs in its own transaction on the primary together with the
+/// `Hooks`; the `*_in` variants join a transaction you already hold.
+#[async_trait::async_trait]
+pub trait Record: Model + Hooks + Sync {
+    /// Primary key column name
+    fn primary_key() -> &'static str;
+
+    /// Primary key value, or `None` if the record has not been persisted
+    fn primary_key_value(&self) -> Option<QueryValue>;
+
+    /// Store the database-generated primary key after an INSERT
+    fn set_primary_key(&mut self, id: i64);
+
+    /// Column/value pairs written on INSERT and UPDATE (primary key excluded)
+    fn column_values(&self) -> Vec<(&'static str, QueryValue)>;
+
+    /// Write a managed timestamp into the field for `column`, if any
+    fn set_timestamp(&mut self, _column: &str, _value: Option<DateTime<Utc>>) {}
+
+    /// INSERT this record
+    ///
+    /// If the primary key is unset, the generated ID is written back.
+    async fn insert(&mut self) -> Result<(), sqlx::Error> {
+        let mut tx = route(None, true).await?.begin().await?;
+        self.insert_in(&mut tx).await?;
+        tx.commit().await
+    }
+
+    /// INSERT this record inside `tx`
+    async fn insert_in(&mut self, tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+        self.before_save(tx).await?;
+        write_insert(self, tx).await?;
+        self.after_create(tx).await
+    }
+
+    /// UPDATE this record by primary key, returning the affected row count
+    async fn update(&mut self) -> Result<u64, sqlx::Error> {
+        let mut tx = route(None, true).await?.begin().await?;
+        let affected = self.update_in(&mut tx).await?;
+        tx.commit().await?;
+        Ok(affected)
+    }
+
+    /// UPDATE this record inside `tx`
+    async fn update_in(&mut self, tx: &mut DatabaseTransaction) -> Result<u64, sqlx::Error> {
+        self.before_save(tx).await?;
+        write_update(self, tx).await
+    }
+
+    /// INSERT or UPDATE depending on whether the record has been persisted
+    ///
+    /// A record with a primary key that matches no row is inserted.
+    async fn save(&mut self) -> Result<(), sqlx::Error> {
+        let mut tx = route(None, true).await?.begin().await?;
+        self.save_in(&mut tx).await?;
+        tx.commit().await
+    }
+
+    /// INSERT or UPDATE this record inside `tx`
+    async fn save_in(&mut self, tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+        self.before_save(tx).await?;
+        if self.primary_key_value().is_some() && write_update(self, tx).await? > 0 {
+            return Ok(());
+        }
+        write_insert(self, tx).await?;
+        self.after_create(tx).await
+    }
+
+    /// Delete this record by primary key, returning the affected row count
+    ///
+    /// Soft-deleting models get their delete column set instead.
+    async fn delete(&self) -> Result<u64, sqlx::Error> {
+        let mut tx = route(None, true).await?.begin().await?;
+        let affected = self.delete_in(&mut tx).await?;
+        tx.commit().await?;
+        Ok(affected)
+    }
+
+    /// Delete this record inside `tx`
+    async fn delete_in(&self, tx: &mut DatabaseTransaction) -> Result<u64, sqlx::Error> {
+        let key = required_key(self, "delete")?;
+        self.before_delete(tx).await?;
+
+        let builder = match Self::soft_delete_column() {
+            Some(column) => Self::query().update().value(column, Utc::now()),
+            None => Self::query().delete(),
+        };
+        builder
+            .r#where(Self::primary_key(), key)
+            .execute_in(tx)
+            .await
+            .map(|r| r.rows_affected())
+    }
+
+    /// Permanently DELETE this record, even if it soft deletes
+    async fn force_delete(&self) -> Result<u64, sqlx::Error> {
+        let key = required_key(self, "delete")?;
+        let mut tx = route(None, true).await?.begin().await?;
+        self.before_delete(&mut tx).await?;
+
+        let affected = Self::query()
+            .with_trashed()
+            .delete()
+            .r#where(Self::primary_key(), key)
+            .execute_in(&mut tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(affected)
+    }
+
+    /// Clear the soft-delete column, returning the affected row count
+    async fn restore(&mut self) -> Result<u64, sqlx::Error> {
+        let key = required_key(self, "restore")?;
+        let column = Self::soft_delete_column().ok_or_else(|| {
+            sqlx::Error::Configuration(
+                format!("{} does not use soft deletes", Self::table_name()).into(),
+            )
+        })?;
+
+        let mut builder = Self::query()
+            .only_trashed()
+            .update()
+            .value(column, QueryValue::Null);
+        if Self::timestamps() {
+            let now = Utc::now();
+            builder = builder.value(UPDATED_AT, now);
+            self.set_timestamp(UPDATED_AT, Some(now));
+        }
+        self.set_timestamp(column, None);
+
+        builder.r#where(Self::primary_key(), key).run().await
+    }
+}
+
+fn required_key<T: Record>(record: &T, action: &str) -> Result<QueryValue, sqlx::Error> {
+    record.primary_key_value().ok_or_else(|| {
+        sqlx::Error::Protocol(format!("Cannot {} a record without a primary key", action))
+    })
+}
+
+/// Column values with the managed timestamp columns replaced by `stamps`
+fn stamped_values<T: Record>(
+    record: &T,
+    skip: &[&str],
+    stamps: &[(&'static str, DateTime<Utc>)],
+) -> Vec<(&'static str, QueryValue)> {
+    let mut values: Vec<_> = record
+        .column_values()
+        .into_iter()
+        .filter(|(column, _)| !skip.contains(column) && !stamps.iter().any(|(c, _)| c == column))
+        .collect();
+    values.extend(stamps.iter().map(|&(c, at)| (c, QueryValue::Timestamp(at))));
+    values
+}
+
+async fn write_insert<T: Record>(
+    record: &mut T,
+    tx: &mut DatabaseTransaction,
+) -> Result<(), sqlx::Error> {
+    let stamps = if T::timestamps() {
+        let now = Utc::now();
+        record.set_timestamp(CREATED_AT, Some(now));
+        record.set_timestamp(UPDATED_AT, Some(now));
+        vec![(CREATED_AT, now), (UPDATED_AT, now)]
+    } else {
+        Vec::new()
+    };
+
+    let mut builder = T::create();
+    for (column, value) in stamped_values(record, &[], &stamps) {
+        builder = builder.value(column, value);
+    }
+
+    match record.primary_key_value() {
+        Some(key) => {
+            builder.value(T::primary_key(), key).execute_in(tx).await?;
+        }
+        None => {
+            let id = builder.insert_get_id_in(tx).await?;
+            record.set_primary_key(id);
+        }
+    }
+    Ok(())
+}
+
+async fn write_update<T: Record>(
+    record: &mut T,
+    tx: &mut DatabaseTransaction,
+) -> Result<u64, sqlx::Error> {
+    let key = required_key(record, "update")?;
+
+    // The soft-delete column is left to delete/restore, and `created_at`
+    // is never rewritten
+    let mut skip: Vec<&str> = T::soft_delete_column().into_iter().collect();
+    let stamps = if T::timestamps() {
+        let now = Utc::now();
+        record.set_timestamp(UPDATED_AT, Some(now));
+        skip.push(CREATED_AT);
+        vec![(UPDATED_AT, now)]
+    } else {
+        Vec::new()
+    };
+
+    let mut builder = T::query().with_trashed().update();
+    for (column, value) in stamped_values(record, &skip, &stamps) {
+        builder = builder.value(column, value);
+    }
+    builder
+        .r#where(T::primary_key(), key)
+        .execute_in(tx)
+        .await
+        .map(|r| r.rows_affected())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photon::db::{db, test_db, DatabaseType};
+    use crate::photon::Op;
+
+    #[derive(Debug, Clone, PartialEq, crate::photon::Model, sqlx::FromRow)]
+    #[photon(table = "record_test_users")]
+    struct User {
+        #[photon(primary_key)]
+        id: i64,
+        email: String,
+        #[photon(column = "display_name")]
+        #[sqlx(rename = "display_name")]
+        name: Option<String>,
+        age: i64,
+    }
+
+    #[test]
+    fn test_column_constants() {
+        assert_eq!(User::ID.name(), "id");
+        assert_eq!(User::EMAIL.name(), "email");
+        assert_eq!(User::NAME.name(), "display_name");
+        assert_eq!(User::AGE.to_string(), "age");
+        assert_eq!(User::table_name(), "record_test_users");
+        assert_eq!(User::primary_key(), "id");
+    }
+
+    #[test]
+    fn test_column_values_exclude_primary_key() {
+        let user = User {
+            id: 7,
+            email: "a@example.com".into(),
+            name: None,
+            age: 30,
+        };
+        let columns: Vec<&str> = user.column_values().iter().map(|(c, _)| *c).collect();
+        assert_eq!(columns, vec!["email", "display_name", "age"]);
+        assert!(matches!(user.primary_key_value(), Some(QueryValue::Int(7))));
+    }
+
+    #[test]
+    fn test_typed_filters_sql() {
+        let builder = User::query()
+            .where_col(User::EMAIL, "a@example.com")
+            .filter_col(User::AGE, Op::Gte, 18)
+            .or_where_col(User::NAME, Some("Ann".to_string()));
+
+        let (sql, bindings) = builder.to_sql(DatabaseType::Postgres);
+        assert!(sql.contains("WHERE email = $1 AND age >= $2 OR display_name = $3"));
+        assert_eq!(bindings.len(), 3);
+    }
+
+    #[test]
+    fn test_primary_key_unset_values() {
+        assert!(0i64.key_value().is_none());
+        assert!(String::new().key_value().is_none());
+        assert!(Option::<i64>::None.key_value().is_none());
+
+        let mut key: Option<i64> = None;
+        key.set_generated(5);
+        assert_eq!(key, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_record_roundtrip_sqlite() {
+        let _db = test_db().await;
+        let pool = db().as_sqlite().unwrap();
+        sqlx::query(
+            "CREATE TABLE record_test_users (id INTEGER PRIMARY KEY AUTOINCREMENT, email TEXT NOT NULL, display_name TEXT, age INTEGER NOT NULL)",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let mut user = User {
+            id: 0,
+            email: "ann@example.com".into(),
+            name: Some("Ann".into()),
+            age: 41,
+        };
+        user.save().await.unwrap();
+        assert!(user.id > 0);
+
+        user.age = 42;
+        user.save().await.unwrap();
+
+        let loaded = User::find::<User>(user.id).await.unwrap().unwrap();
+        assert_eq!(loaded, user);
+
+        assert_eq!(user.delete().await.unwrap(), 1);
+        assert!(User::find::<User>(user.id).await.unwrap().is_none());
+    }
+
+    #[derive(Debug, Clone, PartialEq, crate::photon::Model, sqlx::FromRow)]
+    #[photon(table = "record_test_projects", tenant)]
+    struct Project {
+        id: i64,
+        tenant_id: String,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_tenant_scoped_records() {
+        use crate::tenant::Tenant;
+
+        let _db = test_db().await;
+        sqlx::query(
+            "CREATE TABLE record_test_projects (id INTEGER PRIMARY KEY AUTOINCREMENT, tenant_id TEXT NOT NULL, name TEXT NOT NULL)",
+        )
+        .execute(db().as_sqlite().unwrap())
+        .await
+        .unwrap();
+        assert_eq!(Project::tenant_column(), Some("tenant_id"));
+        assert_eq!(User::tenant_column(), None);
+
+        let mut apollo = Project {
+            id: 0,
+            tenant_id: String::new(),
+            name: "Apollo".into(),
+        };
+        Tenant::scope("acme", apollo.save()).await.unwrap();
+        Tenant::scope("globex", async {
+            Project::create()
+                .value("name", "Gemini")
+                .execute()
+                .await
+                .unwrap();
+        })
+        .await;
+
+        // Inserts take the context's tenant, and reads only see its rows
+        let acme = Tenant::scope("acme", async { Project::query().all::<Project>().await })
+            .await
+            .unwrap();
+        assert_eq!(acme.len(), 1);
+        assert_eq!(acme[0].tenant_id, "acme");
+        assert_eq!(acme[0].name, "Apollo");
+
+        let id = apollo.id;
+        let found = Tenant::scope("globex", Project::find::<Project>(id)).await;
+        assert!(found.unwrap().is_none());
+        let deleted = Tenant::scope("globex", Project::delete_by_id(id)).await;
+        assert_eq!(deleted.unwrap(), 0);
+
+        // Saving from another tenant neither updates the row nor moves it
+        apollo.name = "Hijacked".into();
+        Tenant::scope("globex", apollo.update()).await.unwrap();
+        let loaded = Tenant::scope("acme", Project::find::<Project>(id)).await;
+        assert_eq!(loaded.unwrap().unwrap().name, "Apollo");
+
+        // Outside a tenant, scoped queries fail instead of seeing everything
+        assert!(Project::query().all::<Project>().await.is_err());
+        assert!(Project::query().count().await.is_err());
+        let all = Project::query()
+            .without_tenant_scope()
+            .count()
+            .await
+            .unwrap();
+        assert_eq!(all, 2);
+    }
+
+    #[derive(Debug, Clone, PartialEq, crate::photon::Model, sqlx::FromRow)]
+    #[photon(table = "record_test_org_projects", tenant = "org_id")]
+    struct OrgProject {
+        id: i64,
+        org_id: i64,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_integer_tenant_column() {
+        use crate::photon::db::{register_database, DatabaseCluster, DatabasePool};
+        use crate::photon::Model;
+        use crate::tenant::Tenant;
+
+        assert!(matches!(
+            OrgProject::tenant_key("42"),
+            Some(QueryValue::Int(42))
+        ));
+        assert!(OrgProject::tenant_key("acme").is_none());
+        assert!(matches!(Project::tenant_key("acme"), Some(QueryValue::Text(t)) if t == "acme"));
+
+        // PostgreSQL rejects text compared with an integer column
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        let pool = DatabasePool::connect(&url).await.unwrap();
+        sqlx::raw_sql(
+            "DROP TABLE IF EXISTS record_test_org_projects;
+             CREATE TABLE record_test_org_projects (id BIGSERIAL PRIMARY KEY, org_id BIGINT NOT NULL, name TEXT NOT NULL)",
+        )
+        .execute(pool.as_postgres().unwrap())
+        .await
+        .unwrap();
+        register_database("record_org_tenants", DatabaseCluster::new(pool, vec![]));
+
+        for (org, name) in [("42", "Apollo"), ("7", "Gemini")] {
+            Tenant::scope(org, async {
+                OrgProject::create()
+                    .on("record_org_tenants")
+                    .value("name", name)
+                    .execute()
+                    .await
+                    .unwrap();
+            })
+            .await;
+        }
+        let projects = Tenant::scope("42", async {
+            OrgProject::query()
+                .on("record_org_tenants")
+                .all::<OrgProject>()
+                .await
+        })
+        .await
+        .unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(
+            (projects[0].org_id, projects[0].name.as_str()),
+            (42, "Apollo")
+        );
+
+        // An ID the column can't hold is an error, not an unscoped query
+        let invalid = Tenant::scope("acme", async {
+            OrgProject::query().on("record_org_tenants").count().await
+        })
+        .await;
+        assert!(invalid.is_err());
+    }
+
+    #[derive(Debug, Clone, crate::photon::Model, sqlx::FromRow)]
+    #[photon(table = "record_test_notes", timestamps, soft_delete, hooks)]
+    struct Note {
+        id: i64,
+        body: String,
+        created_at: Option<DateTime<Utc>>,
+        updated_at: Option<DateTime<Utc>>,
+        deleted_at: Option<DateTime<Utc>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Hooks for Note {
+        async fn before_save(&mut self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+            self.body = self.body.trim().to_string();
+            Ok(())
+        }
+
+        async fn after_create(&mut self, tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+            crate::photon::Builder::new("record_test_audit")
+                .insert()
+                .value("note_id", self.id)
+                .execute_in(tx)
+                .await?;
+            if self.body == "fail" {
+                return Err(sqlx::Error::Protocol("rejected".into()));
+            }
+            Ok(())
+        }
+
+        async fn before_delete(&self, _tx: &mut DatabaseTransaction) -> Result<(), sqlx::Error> {
+            if self.body == "pinned" {
+                return Err(sqlx::Error::Protocol("pinned notes stay".into()));
+            }
+            Ok(())
+        }
+    }
+
+    async fn seed_notes() -> tokio::sync::MutexGuard<'static, ()> {
+        let guard = test_db().await;
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS record_test_notes (id INTEGER PRIMARY KEY AUTOINCREMENT, body TEXT NOT NULL, created_at TEXT, updated_at TEXT, deleted_at TEXT);
+             CREATE TABLE IF NOT EXISTS record_test_audit (note_id INTEGER NOT NULL);",
+        )
+        .execute(db().as_sqlite().unwrap())
+        .await
+        .unwrap();
+        guard
+    }
+
+    async fn audit_count(id: i64) -> i64 {
+        crate::photon::Builder::new("record_test_audit")
+            .r#where("note_id", id)
+            .count()
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_soft_delete_scope_sql() {
+        let (sql, _) = Note::query()
+            .r#where("id", 1)
+            .or_where("id", 2)
+            .to_sql(DatabaseType::Sqlite);
+        assert_eq!(
+            sql,
+            "SELECT * FROM record_test_notes WHERE (id = ? OR id = ?) AND record_test_notes.deleted_at IS NULL"
+        );
+        let (sql, _) = Note::query().only_trashed().to_sql(DatabaseType::Sqlite);
+        assert!(sql.ends_with("WHERE record_test_notes.deleted_at IS NOT NULL"));
+        let (sql, _) = Note::query().with_trashed().to_sql(DatabaseType::Sqlite);
+        assert_eq!(sql, "SELECT * FROM record_test_notes");
+        assert!(Note::timestamps());
+        assert!(!User::timestamps());
+        assert_eq!(User::soft_delete_column(), None);
+    }
+
+    #[tokio::test]
+    async fn test_timestamps_and_soft_delete() {
+        let _db = seed_notes().await;
+
+        let mut note = Note {
+            id: 0,
+            body: "  hello ".into(),
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+        note.save().await.unwrap();
+        assert_eq!(note.body, "hello");
+        let created = note.created_at.unwrap();
+        assert_eq!(note.updated_at, Some(created));
+        assert_eq!(audit_count(note.id).await, 1);
+
+        note.body = "edited".into();
+        note.created_at = None;
+        note.save().await.unwrap();
+        assert!(note.updated_at.unwrap() >= created);
+        let loaded = Note::find::<Note>(note.id).await.unwrap().unwrap();
+        assert_eq!(loaded.created_at, Some(created));
+        assert_eq!(loaded.body, "edited");
+        assert_eq!(audit_count(note.id).await, 1);
+
+        assert_eq!(note.delete().await.unwrap(), 1);
+        assert!(Note::find::<Note>(note.id).await.unwrap().is_none());
+        let trashed = Note::query()
+            .only_trashed()
+            .r#where("id", note.id)
+            .first::<Note>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(trashed.deleted_at.is_some());
+
+        assert_eq!(note.restore().await.unwrap(), 1);
+        assert!(Note::find::<Note>(note.id).await.unwrap().is_some());
+
+        assert_eq!(note.force_delete().await.unwrap(), 1);
+        assert!(Note::query()
+            .with_trashed()
+            .r#where("id", note.id)
+            .first::<Note>()
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hooks_share_the_transaction() {
+        let _db = seed_notes().await;
+
+        let mut note = Note {
+            id: 0,
+            body: "fail".into(),
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+        assert!(note.save().await.is_err());
+        // The insert and the hook's audit row were rolled back together
+        assert!(Note::find::<Note>(note.id).await.unwrap().is_none());
+        assert_eq!(audit_count(note.id).await, 0);
+
+        let mut pinned = Note {
+            id: 0,
+            body: "pinned".into(),
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+        pinned.save().await.unwrap();
+        assert!(pinned.delete().await.is_err());
+        assert!(Note::find::<Note>(pinned.id).await.unwrap().is_some());
+    }
+}
@@ -1,25 +1,47 @@
 //! Relationships for Photon Models
 //!
-//! Provides trait-based relationship definitions for eager loading.
+//! Trait-based relationship definitions plus batched eager loading.
 //!
 //! # Example
 //!
 //! ```rust,ignore
-//! use nucleus_std::photon::{Model, HasMany, BelongsTo};
+//! use nucleus_std::photon::{BelongsTo, HasMany, Model, Relation, Relations};
 //!
-//! struct User { id: i64, name: String }
+//! #[derive(Clone, sqlx::FromRow)]
+//! struct User {
+//!     id: i64,
+//!     name: String,
+//!     #[sqlx(skip)]
+//!     posts: Vec<Post>,
+//! }
+//!
+//! #[derive(Clone, sqlx::FromRow)]
 //! struct Post { id: i64, user_id: i64, title: String }
 //!
 //! impl HasMany<Post> for User {
 //!     fn foreign_key() -> &'static str { "user_id" }
+//!     fn get_id(&self) -> i64 { self.id }
 //! }
 //!
-//! impl BelongsTo<User> for Post {
-//!     fn foreign_key() -> &'static str { "user_id" }
+//! impl Relations for User {
+//!     fn relations() -> Vec<Relation<Self>> {
+//!         vec![Relation::has_many::<Post>("posts", |user, posts| user.posts = posts)]
+//!     }
 //! }
+//! impl Relations for Post {}
+//!
+//! // One query for users, one batched `IN (...)` query for their posts
+//! let users = User::query().include("posts").load::<User>().await?;
 //! ```
 
-use crate::photon::query::Model;
+use crate::photon::db::{db_read, route, DatabasePool, DatabaseType, QueryValue, Routed};
+use crate::photon::query::{bind_values, FromDbRow, Model};
+use crate::tenant::Tenant;
+use sqlx::{FromRow, Row};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::pin::Pin;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // RELATIONSHIP TRAITS
@@ -45,6 +67,11 @@ pub trait BelongsTo<T: Model> {
 
     /// Get the foreign key value
     fn get_foreign_key_value(&self) -> i64;
+
+    /// The column on the related table the foreign key points at
+    fn owner_key() -> &'static str {
+        "id"
+    }
 }
 
 /// Trait for "has one" relationships (one-to-one)
@@ -58,11 +85,393 @@ pub trait HasOne<T: Model> {
     fn get_id(&self) -> i64;
 }
 
+/// Trait for "belongs to many" relationships (many-to-many through a pivot table)
+///
+/// Example: User belongs to many Roles through `role_user`
+pub trait BelongsToMany<T: Model> {
+    /// The pivot table joining both models
+    fn pivot_table() -> &'static str;
+
+    /// Pivot column referencing this model
+    fn foreign_pivot_key() -> &'static str;
+
+    /// Pivot column referencing the related model
+    fn related_pivot_key() -> &'static str;
+
+    /// The column on the related table the pivot points at
+    fn related_key() -> &'static str {
+        "id"
+    }
+
+    /// Get the ID of this record for the relationship
+    fn get_id(&self) -> i64;
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// EAGER LOADING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Keys per `IN (...)` query; larger sets are split into several queries
+pub const EAGER_BATCH_SIZE: usize = 500;
+
+/// Column alias carrying the parent key in eager loading queries
+const PARENT_KEY: &str = "__photon_parent_key";
+
+type LoadFuture<'a> = Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>>;
+type Loader<P> =
+    Box<dyn for<'a> Fn(&'a mut [P], &'a [String], &'a Routed) -> LoadFuture<'a> + Send + Sync>;
+
+/// A named relation that can be eager loaded onto a slice of `P`
+///
+/// Built with `has_one`, `has_many`, `belongs_to` or `belongs_to_many`,
+/// each taking a setter that attaches the loaded records to their parent.
+pub struct Relation<P> {
+    name: &'static str,
+    load: Loader<P>,
+}
+
+impl<P> std::fmt::Debug for Relation<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relation")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<P: Send + 'static> Relation<P> {
+    /// Relation name used in `include()`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// One-to-many via `HasMany<C>`; every parent receives a (possibly empty) `Vec`
+    pub fn has_many<C>(name: &'static str, attach: fn(&mut P, Vec<C>)) -> Self
+    where
+        P: HasMany<C>,
+        C: Relations + Model + FromDbRow + Clone + Sync,
+    {
+        Self::keyed(
+            name,
+            |parent| Some(<P as HasMany<C>>::get_id(parent)),
+            move |db_type, count| {
+                keyed_select(
+                    db_type,
+                    C::table_name(),
+                    &format!("{}.{}", C::table_name(), <P as HasMany<C>>::foreign_key()),
+                    "",
+                    count,
+                    C::tenant_column(),
+                    C::soft_delete_column(),
+                )
+            },
+            move |parent, children: Vec<C>| attach(parent, children),
+        )
+    }
+
+    /// One-to-one via `HasOne<C>`
+    pub fn has_one<C>(name: &'static str, attach: fn(&mut P, Option<C>)) -> Self
+    where
+        P: HasOne<C>,
+        C: Relations + Model + FromDbRow + Clone + Sync,
+    {
+        Self::keyed(
+            name,
+            |parent| Some(<P as HasOne<C>>::get_id(parent)),
+            move |db_type, count| {
+                keyed_select(
+                    db_type,
+                    C::table_name(),
+                    &format!("{}.{}", C::table_name(), <P as HasOne<C>>::foreign_key()),
+                    "",
+                    count,
+                    C::tenant_column(),
+                    C::soft_delete_column(),
+                )
+            },
+            move |parent, children: Vec<C>| attach(parent, children.into_iter().next()),
+        )
+    }
+
+    /// Inverse of `has_many`/`has_one` via `BelongsTo<C>`
+    pub fn belongs_to<C>(name: &'static str, attach: fn(&mut P, Option<C>)) -> Self
+    where
+        P: BelongsTo<C>,
+        C: Relations + Model + FromDbRow + Clone + Sync,
+    {
+        Self::keyed(
+            name,
+            |parent| Some(<P as BelongsTo<C>>::get_foreign_key_value(parent)),
+            move |db_type, count| {
+                keyed_select(
+                    db_type,
+                    C::table_name(),
+                    &format!("{}.{}", C::table_name(), <P as BelongsTo<C>>::owner_key()),
+                    "",
+                    count,
+                    C::tenant_column(),
+                    C::soft_delete_column(),
+                )
+            },
+            move |parent, children: Vec<C>| attach(parent, children.into_iter().next()),
+        )
+    }
+
+    /// Many-to-many via `BelongsToMany<C>` and its pivot table
+    pub fn belongs_to_many<C>(name: &'static str, attach: fn(&mut P, Vec<C>)) -> Self
+    where
+        P: BelongsToMany<C>,
+        C: Relations + Model + FromDbRow + Clone + Sync,
+    {
+        Self::keyed(
+            name,
+            |parent| Some(<P as BelongsToMany<C>>::get_id(parent)),
+            move |db_type, count| {
+                let pivot = <P as BelongsToMany<C>>::pivot_table();
+                let join = format!(
+                    " INNER JOIN {pivot} ON {pivot}.{} = {}.{}",
+                    <P as BelongsToMany<C>>::related_pivot_key(),
+                    C::table_name(),
+                    <P as BelongsToMany<C>>::related_key(),
+                );
+                keyed_select(
+                    db_type,
+                    C::table_name(),
+                    &format!("{}.{}", pivot, <P as BelongsToMany<C>>::foreign_pivot_key()),
+                    &join,
+                    count,
+                    C::tenant_column(),
+                    C::soft_delete_column(),
+                )
+            },
+            move |parent, children: Vec<C>| attach(parent, children),
+        )
+    }
+
+    /// Shared loader: fetch children keyed by parent key, load nested
+    /// relations on them, then hand each parent its group
+    fn keyed<C, K, S, A>(name: &'static str, key: K, sql: S, attach: A) -> Self
+    where
+        C: Relations + Model + FromDbRow + Clone + Sync,
+        K: Fn(&P) -> Option<i64> + Copy + Send + Sync + 'static,
+        S: Fn(DatabaseType, usize) -> String + Copy + Send + Sync + 'static,
+        A: Fn(&mut P, Vec<C>) + Copy + Send + Sync + 'static,
+    {
+        let load: Loader<P> = Box::new(move |parents, nested, routed| {
+            let mut keys: Vec<i64> = parents.iter().filter_map(key).collect();
+            keys.sort_unstable();
+            keys.dedup();
+
+            let nested = nested.to_vec();
+            Box::pin(async move {
+                let tenant = tenant_value::<C>()?;
+                let mut rows: Vec<(i64, C)> = Vec::new();
+                for chunk in keys.chunks(EAGER_BATCH_SIZE) {
+                    let sql = sql(routed.db_type(), chunk.len());
+                    let mut values: Vec<QueryValue> =
+                        chunk.iter().map(|k| QueryValue::Int(*k)).collect();
+                    values.extend(tenant.clone());
+                    rows.extend(fetch_keyed::<C>(routed, &sql, values).await?);
+                }
+
+                let (row_keys, mut children): (Vec<i64>, Vec<C>) = rows.into_iter().unzip();
+                if !nested.is_empty() {
+                    load_relations(&mut children, &nested, routed).await?;
+                }
+
+                let mut groups: HashMap<i64, Vec<C>> = HashMap::new();
+                for (k, child) in row_keys.into_iter().zip(children) {
+                    groups.entry(k).or_default().push(child);
+                }
+
+                for parent in parents.iter_mut() {
+                    let group = key(parent)
+                        .and_then(|k| groups.get(&k))
+                        .cloned()
+                        .unwrap_or_default();
+                    attach(parent, group);
+                }
+                Ok(())
+            })
+        });
+
+        Self { name, load }
+    }
+}
+
+/// Declares the named relations of a model for `Builder::include`
+///
+/// Models that are only ever loaded as leaves can use the empty default.
+pub trait Relations: Sized + Send + 'static {
+    /// All relations that can be included on this model
+    fn relations() -> Vec<Relation<Self>> {
+        Vec::new()
+    }
+}
+
+/// Eager load relations onto already-fetched models
+///
+/// Each include is a relation name, optionally followed by nested names
+/// separated by dots (`"posts.comments"`). Every level issues one batched
+/// `IN (...)` query (split every `EAGER_BATCH_SIZE` keys). Relations are
+/// read from the default database, in the current tenant's schema or
+/// database; `Builder::load()` uses the builder's own connection.
+pub async fn eager_load<T: Relations>(
+    models: &mut [T],
+    includes: &[String],
+) -> Result<(), sqlx::Error> {
+    if models.is_empty() || includes.is_empty() {
+        return Ok(());
+    }
+    load_relations(models, includes, &route(None, false).await?).await
+}
+
+/// `eager_load` on the connection the parent rows were routed to
+pub(crate) async fn load_relations<T: Relations>(
+    models: &mut [T],
+    includes: &[String],
+    routed: &Routed,
+) -> Result<(), sqlx::Error> {
+    if models.is_empty() || includes.is_empty() {
+        return Ok(());
+    }
+
+    let relations = T::relations();
+    for (name, nested) in group_includes(includes) {
+        let relation = relations.iter().find(|r| r.name == name).ok_or_else(|| {
+            sqlx::Error::Configuration(
+                format!(
+                    "Unknown relation `{}` on {}",
+                    name,
+                    std::any::type_name::<T>()
+                )
+                .into(),
+            )
+        })?;
+        (relation.load)(models, &nested, routed).await?;
+    }
+    Ok(())
+}
+
+/// Group include paths by their first segment, keeping nested remainders
+///
+/// `["posts.comments", "posts.tags", "profile"]` becomes
+/// `[("posts", ["comments", "tags"]), ("profile", [])]`.
+fn group_includes(includes: &[String]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for include in includes {
+        let (head, rest) = match include.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (include.as_str(), None),
+        };
+        let index = match groups.iter().position(|(name, _)| name == head) {
+            Some(index) => index,
+            None => {
+                groups.push((head.to_string(), Vec::new()));
+                groups.len() - 1
+            }
+        };
+        if let Some(rest) = rest {
+            groups[index].1.push(rest.to_string());
+        }
+    }
+    groups
+}
+
+/// The current tenant's key for a tenant-scoped related model
+///
+/// Included rows are filtered like `C::query()` would, so a tenant-scoped
+/// relation cannot be loaded outside a tenant context.
+fn tenant_value<C: Model>() -> Result<Option<QueryValue>, sqlx::Error> {
+    let Some(column) = C::tenant_column() else {
+        return Ok(None);
+    };
+    let tenant = Tenant::get().ok_or_else(|| {
+        sqlx::Error::Protocol(format!(
+            "{} is tenant-scoped but no tenant is set; include it within a tenant context",
+            C::table_name()
+        ))
+    })?;
+    C::tenant_key(&tenant).map(Some).ok_or_else(|| {
+        sqlx::Error::Protocol(format!(
+            "tenant {} is not a valid value for {}.{}",
+            tenant,
+            C::table_name(),
+            column
+        ))
+    })
+}
+
+/// `SELECT related.*, <key> AS __photon_parent_key FROM related <join> WHERE <key> IN (...)`
+///
+/// With a tenant column, the tenant key is bound after the parent keys.
+/// Soft-deleted related rows are left out.
+fn keyed_select(
+    db_type: DatabaseType,
+    table: &str,
+    key_column: &str,
+    join: &str,
+    count: usize,
+    tenant_column: Option<&str>,
+    soft_delete_column: Option<&str>,
+) -> String {
+    // Normalise the key to a 64-bit integer so it always decodes as i64
+    let key = match db_type {
+        DatabaseType::Sqlite => key_column.to_string(),
+        DatabaseType::Postgres => format!("CAST({} AS BIGINT)", key_column),
+        DatabaseType::MySql => format!("CAST({} AS SIGNED)", key_column),
+    };
+    let placeholders: Vec<String> = (1..=count).map(|i| db_type.placeholder(i)).collect();
+    let mut sql = format!(
+        "SELECT {table}.*, {key} AS {PARENT_KEY} FROM {table}{join} WHERE {key_column} IN ({})",
+        placeholders.join(", ")
+    );
+    if let Some(column) = tenant_column {
+        let _ = write!(
+            sql,
+            " AND {table}.{column} = {}",
+            db_type.placeholder(count + 1)
+        );
+    }
+    if let Some(column) = soft_delete_column {
+        let _ = write!(sql, " AND {table}.{column} IS NULL");
+    }
+    sql
+}
+
+/// Run a keyed select, decoding each row as `T` plus its parent key
+async fn fetch_keyed<T: FromDbRow>(
+    routed: &Routed,
+    sql: &str,
+    values: Vec<QueryValue>,
+) -> Result<Vec<(i64, T)>, sqlx::Error> {
+    match routed.pool() {
+        DatabasePool::Sqlite(p) => bind_values!(sqlx::query(sql), values)
+            .fetch_all(p)
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get(PARENT_KEY)?, <T as FromRow<_>>::from_row(row)?)))
+            .collect(),
+        DatabasePool::Postgres(p) => bind_values!(sqlx::query(sql), values)
+            .fetch_all(&mut *routed.checkout(p).await?)
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get(PARENT_KEY)?, <T as FromRow<_>>::from_row(row)?)))
+            .collect(),
+        DatabasePool::MySql(p) => bind_values!(sqlx::query(sql), values)
+            .fetch_all(p)
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get(PARENT_KEY)?, <T as FromRow<_>>::from_row(row)?)))
+            .collect(),
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
-// EAGER LOADING HELPERS
+// LEGACY LOADERS
 // ═══════════════════════════════════════════════════════════════════════════
 
 /// Load related records for a collection using HasMany (SQLite)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_has_many_sqlite<Parent, Child>(
     parents: &[Parent],
 ) -> Result<std::collections::HashMap<i64, Vec<Child>>, sqlx::Error>
@@ -70,8 +479,6 @@ where
     Parent: HasMany<Child>,
     Child: Model + for<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if parents.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -85,7 +492,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let sqlite_pool = pool
         .as_sqlite()
         .ok_or_else(|| sqlx::Error::Configuration("Not a SQLite database".into()))?;
@@ -106,6 +513,7 @@ where
 }
 
 /// Load related records for a collection using HasMany (PostgreSQL)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_has_many_postgres<Parent, Child>(
     parents: &[Parent],
 ) -> Result<std::collections::HashMap<i64, Vec<Child>>, sqlx::Error>
@@ -113,8 +521,6 @@ where
     Parent: HasMany<Child>,
     Child: Model + for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if parents.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -128,7 +534,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let pg_pool = pool
         .as_postgres()
         .ok_or_else(|| sqlx::Error::Configuration("Not a PostgreSQL database".into()))?;
@@ -149,6 +555,7 @@ where
 }
 
 /// Load related records for a collection using HasMany (MySQL)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_has_many_mysql<Parent, Child>(
     parents: &[Parent],
 ) -> Result<std::collections::HashMap<i64, Vec<Child>>, sqlx::Error>
@@ -156,8 +563,6 @@ where
     Parent: HasMany<Child>,
     Child: Model + for<'r> sqlx::FromRow<'r, sqlx::mysql::MySqlRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if parents.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -171,7 +576,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let mysql_pool = pool
         .as_mysql()
         .ok_or_else(|| sqlx::Error::Configuration("Not a MySQL database".into()))?;
@@ -192,6 +597,7 @@ where
 }
 
 /// Load a related record for a collection using BelongsTo (SQLite)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_belongs_to_sqlite<Child, Parent>(
     children: &[Child],
 ) -> Result<std::collections::HashMap<i64, Parent>, sqlx::Error>
@@ -199,8 +605,6 @@ where
     Child: BelongsTo<Parent>,
     Parent: Model + for<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if children.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -216,7 +620,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let sqlite_pool = pool
         .as_sqlite()
         .ok_or_else(|| sqlx::Error::Configuration("Not a SQLite database".into()))?;
@@ -239,6 +643,7 @@ where
 }
 
 /// Load a related record for a collection using BelongsTo (PostgreSQL)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_belongs_to_postgres<Child, Parent>(
     children: &[Child],
 ) -> Result<std::collections::HashMap<i64, Parent>, sqlx::Error>
@@ -246,8 +651,6 @@ where
     Child: BelongsTo<Parent>,
     Parent: Model + for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if children.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -263,7 +666,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let pg_pool = pool
         .as_postgres()
         .ok_or_else(|| sqlx::Error::Configuration("Not a PostgreSQL database".into()))?;
@@ -286,6 +689,7 @@ where
 }
 
 /// Load a related record for a collection using BelongsTo (MySQL)
+#[deprecated(note = "Use `Builder::include` with the `Relations` trait")]
 pub async fn load_belongs_to_mysql<Child, Parent>(
     children: &[Child],
 ) -> Result<std::collections::HashMap<i64, Parent>, sqlx::Error>
@@ -293,8 +697,6 @@ where
     Child: BelongsTo<Parent>,
     Parent: Model + for<'r> sqlx::FromRow<'r, sqlx::mysql::MySqlRow> + Send + Unpin,
 {
-    use crate::photon::db::db;
-
     if children.is_empty() {
         return Ok(std::collections::HashMap::new());
     }
@@ -310,7 +712,7 @@ where
         placeholders.join(", ")
     );
 
-    let pool = db();
+    let pool = db_read();
     let mysql_pool = pool
         .as_mysql()
         .ok_or_else(|| sqlx::Error::Configuration("Not a MySQL database".into()))?;
@@ -335,8 +737,9 @@ where
 /// Legacy function - uses SQLite
 #[deprecated(
     since = "0.2.0",
-    note = "Use load_has_many_sqlite, load_has_many_postgres, or load_has_many_mysql"
+    note = "Use `Builder::include` with the `Relations` trait"
 )]
+#[allow(deprecated)]
 pub async fn load_has_many<Parent, Child>(
     parents: &[Parent],
 ) -> Result<std::collections::HashMap<i64, Vec<Child>>, sqlx::Error>
@@ -350,8 +753,9 @@ where
 /// Legacy function - uses SQLite
 #[deprecated(
     since = "0.2.0",
-    note = "Use load_belongs_to_sqlite, load_belongs_to_postgres, or load_belongs_to_mysql"
+    note = "Use `Builder::include` with the `Relations` trait"
 )]
+#[allow(deprecated)]
 pub async fn load_belongs_to<Child, Parent>(
     children: &[Child],
 ) -> Result<std::collections::HashMap<i64, Parent>, sqlx::Error>
@@ -369,6 +773,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::photon::db::db;
 
     // Mock types for testing
     struct MockUser {
@@ -504,6 +909,461 @@ mod tests {
         assert!(users.is_empty());
     }
 
+    #[test]
+    fn test_group_includes() {
+        let includes: Vec<String> = ["posts.comments", "profile", "posts.tags.owner"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            group_includes(&includes),
+            vec![
+                (
+                    "posts".to_string(),
+                    vec!["comments".to_string(), "tags.owner".to_string()]
+                ),
+                ("profile".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyed_select_sql() {
+        let sql = keyed_select(
+            DatabaseType::Postgres,
+            "posts",
+            "posts.user_id",
+            "",
+            2,
+            None,
+            None,
+        );
+        assert_eq!(
+            sql,
+            "SELECT posts.*, CAST(posts.user_id AS BIGINT) AS __photon_parent_key FROM posts WHERE posts.user_id IN ($1, $2)"
+        );
+
+        let sql = keyed_select(
+            DatabaseType::Sqlite,
+            "roles",
+            "role_user.user_id",
+            " INNER JOIN role_user ON role_user.role_id = roles.id",
+            1,
+            None,
+            None,
+        );
+        assert!(sql.contains("FROM roles INNER JOIN role_user ON role_user.role_id = roles.id WHERE role_user.user_id IN (?)"));
+
+        let sql = keyed_select(
+            DatabaseType::Postgres,
+            "posts",
+            "posts.user_id",
+            "",
+            2,
+            Some("team_id"),
+            Some("deleted_at"),
+        );
+        assert!(sql.ends_with(
+            "WHERE posts.user_id IN ($1, $2) AND posts.team_id = $3 AND posts.deleted_at IS NULL"
+        ));
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Eager loading against SQLite
+    // ─────────────────────────────────────────────────────────────────────
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Author {
+        id: i64,
+        name: String,
+        #[sqlx(skip)]
+        posts: Vec<Article>,
+        #[sqlx(skip)]
+        profile: Option<Profile>,
+        #[sqlx(skip)]
+        roles: Vec<Role>,
+        #[sqlx(skip)]
+        notes: Vec<Note>,
+    }
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Article {
+        id: i64,
+        author_id: i64,
+        title: String,
+        #[sqlx(skip)]
+        comments: Vec<Comment>,
+        #[sqlx(skip)]
+        author: Option<Box<Author>>,
+    }
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Comment {
+        article_id: i64,
+        body: String,
+    }
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Profile {
+        author_id: i64,
+        bio: String,
+    }
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Role {
+        name: String,
+    }
+
+    #[derive(Debug, Clone, sqlx::FromRow)]
+    struct Note {
+        team: String,
+        body: String,
+    }
+
+    impl Model for Comment {
+        fn table_name() -> &'static str {
+            "eager_comments"
+        }
+        fn soft_delete_column() -> Option<&'static str> {
+            Some("deleted_at")
+        }
+    }
+
+    impl Model for Note {
+        fn table_name() -> &'static str {
+            "eager_notes"
+        }
+        fn tenant_column() -> Option<&'static str> {
+            Some("team")
+        }
+    }
+
+    crate::impl_model!(Author, "eager_authors");
+    crate::impl_model!(Article, "eager_articles");
+    crate::impl_model!(Profile, "eager_profiles");
+    crate::impl_model!(Role, "eager_roles");
+
+    impl HasMany<Article> for Author {
+        fn foreign_key() -> &'static str {
+            "author_id"
+        }
+        fn get_id(&self) -> i64 {
+            self.id
+        }
+    }
+
+    impl HasOne<Profile> for Author {
+        fn foreign_key() -> &'static str {
+            "author_id"
+        }
+        fn get_id(&self) -> i64 {
+            self.id
+        }
+    }
+
+    impl HasMany<Note> for Author {
+        fn foreign_key() -> &'static str {
+            "author_id"
+        }
+        fn get_id(&self) -> i64 {
+            self.id
+        }
+    }
+
+    impl BelongsToMany<Role> for Author {
+        fn pivot_table() -> &'static str {
+            "eager_author_roles"
+        }
+        fn foreign_pivot_key() -> &'static str {
+            "author_id"
+        }
+        fn related_pivot_key() -> &'static str {
+            "role_id"
+        }
+        fn get_id(&self) -> i64 {
+            self.id
+        }
+    }
+
+    impl HasMany<Comment> for Article {
+        fn foreign_key() -> &'static str {
+            "article_id"
+        }
+        fn get_id(&self) -> i64 {
+            self.id
+        }
+    }
+
+    impl BelongsTo<Author> for Article {
+        fn foreign_key() -> &'static str {
+            "author_id"
+        }
+        fn get_foreign_key_value(&self) -> i64 {
+            self.author_id
+        }
+    }
+
+    impl Relations for Author {
+        fn relations() -> Vec<Relation<Self>> {
+            vec![
+                Relation::has_many::<Article>("posts", |a, posts| a.posts = posts),
+                Relation::has_one::<Profile>("profile", |a, profile| a.profile = profile),
+                Relation::belongs_to_many::<Role>("roles", |a, roles| a.roles = roles),
+                Relation::has_many::<Note>("notes", |a, notes| a.notes = notes),
+            ]
+        }
+    }
+
+    impl Relations for Article {
+        fn relations() -> Vec<Relation<Self>> {
+            vec![
+                Relation::has_many::<Comment>("comments", |a, comments| a.comments = comments),
+                Relation::belongs_to::<Author>("author", |a, author| {
+                    a.author = author.map(Box::new)
+                }),
+            ]
+        }
+    }
+
+    impl Relations for Comment {}
+    impl Relations for Profile {}
+    impl Relations for Role {}
+    impl Relations for Note {}
+
+    async fn seed_eager_tables() -> tokio::sync::MutexGuard<'static, ()> {
+        let guard = crate::photon::db::test_db().await;
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS eager_authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS eager_articles (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, title TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS eager_comments (id INTEGER PRIMARY KEY, article_id INTEGER NOT NULL, body TEXT NOT NULL, deleted_at TEXT);
+             CREATE TABLE IF NOT EXISTS eager_profiles (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, bio TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS eager_roles (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS eager_author_roles (author_id INTEGER NOT NULL, role_id INTEGER NOT NULL, PRIMARY KEY (author_id, role_id));
+             CREATE TABLE IF NOT EXISTS eager_notes (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, team TEXT NOT NULL, body TEXT NOT NULL);
+             INSERT OR IGNORE INTO eager_authors VALUES (1, 'Ann'), (2, 'Bob'), (3, 'Cy');
+             INSERT OR IGNORE INTO eager_articles VALUES (10, 1, 'A1'), (11, 1, 'A2'), (12, 2, 'B1');
+             INSERT OR IGNORE INTO eager_comments VALUES (100, 10, 'c1', NULL), (101, 10, 'c2', NULL), (102, 12, 'c3', NULL), (103, 11, 'trashed', '2024-01-01 00:00:00');
+             INSERT OR IGNORE INTO eager_profiles VALUES (1, 2, 'bio of Bob');
+             INSERT OR IGNORE INTO eager_roles VALUES (1, 'admin'), (2, 'editor');
+             INSERT OR IGNORE INTO eager_author_roles VALUES (1, 1), (1, 2), (3, 2);
+             INSERT OR IGNORE INTO eager_notes VALUES (1, 1, 'acme', 'acme note'), (2, 1, 'globex', 'globex note');",
+        )
+        .execute(db().as_sqlite().unwrap())
+        .await
+        .unwrap();
+        guard
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_nested_relations() {
+        let _db = seed_eager_tables().await;
+
+        let authors = Author::query()
+            .include("posts.comments")
+            .include("profile")
+            .include("roles")
+            .order_by("id", "ASC")
+            .load::<Author>()
+            .await
+            .unwrap();
+
+        assert_eq!(authors.len(), 3);
+        let (ann, bob, cy) = (&authors[0], &authors[1], &authors[2]);
+        assert_eq!(ann.name, "Ann");
+
+        // HasMany + nested HasMany
+        assert_eq!(ann.posts.len(), 2);
+        let a1 = ann.posts.iter().find(|p| p.id == 10).unwrap();
+        assert_eq!(a1.title, "A1");
+        assert_eq!(a1.comments.len(), 2);
+        assert!(a1.comments.iter().all(|c| c.article_id == 10));
+        assert_eq!(bob.posts[0].comments[0].body, "c3");
+        assert!(cy.posts.is_empty());
+
+        // HasOne
+        assert!(ann.profile.is_none());
+        assert_eq!(bob.profile.as_ref().unwrap().author_id, 2);
+        assert_eq!(bob.profile.as_ref().unwrap().bio, "bio of Bob");
+
+        // BelongsToMany
+        let mut ann_roles: Vec<&str> = ann.roles.iter().map(|r| r.name.as_str()).collect();
+        ann_roles.sort();
+        assert_eq!(ann_roles, vec!["admin", "editor"]);
+        assert!(bob.roles.is_empty());
+        assert_eq!(cy.roles[0].name, "editor");
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_belongs_to_and_errors() {
+        let _db = seed_eager_tables().await;
+
+        let articles = Article::query()
+            .include("author")
+            .load::<Article>()
+            .await
+            .unwrap();
+        assert_eq!(articles.len(), 3);
+        for article in &articles {
+            let author = article.author.as_ref().unwrap();
+            assert_eq!(author.id, article.author_id);
+            assert_eq!(article.comments.len(), 0); // not included
+        }
+
+        let err = Author::query().include("followers").load::<Author>().await;
+        assert!(matches!(err, Err(sqlx::Error::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_first_and_paginate() {
+        let _db = seed_eager_tables().await;
+
+        let ann = Author::query()
+            .include("posts.comments")
+            .order_by("id", "ASC")
+            .load_first::<Author>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ann.name, "Ann");
+        assert_eq!(ann.posts.len(), 2);
+        assert_eq!(ann.posts.iter().map(|p| p.comments.len()).sum::<usize>(), 2);
+
+        let page = Author::query()
+            .include("profile")
+            .include("roles")
+            .order_by("id", "ASC")
+            .load_paginate::<Author>(1, 2)
+            .await
+            .unwrap();
+        assert_eq!((page.total, page.total_pages), (3, 2));
+        assert_eq!(page.data.len(), 2);
+        assert_eq!(page.data[0].roles.len(), 2);
+        assert_eq!(page.data[1].profile.as_ref().unwrap().bio, "bio of Bob");
+
+        // Fetches that cannot eager load refuse includes instead of dropping them
+        let first = Author::query().include("posts").first::<Author>().await;
+        assert!(matches!(first, Err(sqlx::Error::Configuration(_))));
+        let paged = Author::query()
+            .include("posts")
+            .paginate::<Author>(1, 2)
+            .await;
+        assert!(matches!(paged, Err(sqlx::Error::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_skips_trashed_relations() {
+        let _db = seed_eager_tables().await;
+
+        let articles = Article::query()
+            .r#where("id", 11)
+            .include("comments")
+            .load::<Article>()
+            .await
+            .unwrap();
+        assert_eq!(articles.len(), 1);
+        assert!(articles[0].comments.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_tenant_scoped_relation() {
+        let _db = seed_eager_tables().await;
+
+        let authors = Tenant::scope("acme", async {
+            Author::query()
+                .r#where("id", 1)
+                .include("notes")
+                .load::<Author>()
+                .await
+        })
+        .await
+        .unwrap();
+        assert_eq!(authors[0].notes.len(), 1);
+        assert_eq!(authors[0].notes[0].team, "acme");
+        assert_eq!(authors[0].notes[0].body, "acme note");
+
+        // Like Note::query(), a tenant-scoped include needs a tenant
+        let err = Author::query().include("notes").load::<Author>().await;
+        assert!(matches!(err, Err(sqlx::Error::Protocol(_))));
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_on_named_connection() {
+        let _db = seed_eager_tables().await;
+        let pool = DatabasePool::connect("sqlite:file:photon_eager_named?mode=memory&cache=shared")
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS eager_authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS eager_articles (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, title TEXT NOT NULL);
+             INSERT OR IGNORE INTO eager_authors VALUES (1, 'Ann');
+             INSERT OR IGNORE INTO eager_articles VALUES (50, 1, 'archived');",
+        )
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .unwrap();
+        crate::photon::db::register_database(
+            "eager_archive",
+            crate::photon::db::DatabaseCluster::new(pool, vec![]),
+        );
+
+        // Relations come from the same connection as their parents
+        let authors = Author::query()
+            .on("eager_archive")
+            .include("posts")
+            .load::<Author>()
+            .await
+            .unwrap();
+        assert_eq!(authors.len(), 1);
+        assert_eq!(authors[0].posts.len(), 1);
+        assert_eq!(authors[0].posts[0].title, "archived");
+    }
+
+    #[tokio::test]
+    async fn test_eager_load_in_tenant_schema() {
+        let Ok(url) = std::env::var("POSTGRES_URL") else {
+            return;
+        };
+        // One connection, so a leaked search_path would show up in the next load
+        let pool = DatabasePool::connect_with_max(&url, 1).await.unwrap();
+        sqlx::raw_sql(
+            "CREATE SCHEMA IF NOT EXISTS photon_eager;
+             DROP TABLE IF EXISTS public.eager_articles, photon_eager.eager_articles;
+             CREATE TABLE public.eager_articles (id BIGINT PRIMARY KEY, author_id BIGINT NOT NULL, title TEXT NOT NULL);
+             CREATE TABLE photon_eager.eager_articles (id BIGINT PRIMARY KEY, author_id BIGINT NOT NULL, title TEXT NOT NULL);
+             INSERT INTO public.eager_articles VALUES (1, 1, 'shared');
+             INSERT INTO photon_eager.eager_articles VALUES (1, 1, 'tenant'), (2, 1, 'tenant')",
+        )
+        .execute(pool.as_postgres().unwrap())
+        .await
+        .unwrap();
+
+        let author = || Author {
+            id: 1,
+            name: "Ann".to_string(),
+            posts: Vec::new(),
+            profile: None,
+            roles: Vec::new(),
+            notes: Vec::new(),
+        };
+        let includes = vec!["posts".to_string()];
+
+        let mut tenant = vec![author()];
+        let routed = Routed::new(pool.clone(), Some("photon_eager".to_string()));
+        load_relations(&mut tenant, &includes, &routed)
+            .await
+            .unwrap();
+        assert_eq!(tenant[0].posts.len(), 2);
+        assert!(tenant[0].posts.iter().all(|p| p.title == "tenant"));
+
+        let mut shared = vec![author()];
+        let routed = Routed::new(pool.clone(), None);
+        load_relations(&mut shared, &includes, &routed)
+            .await
+            .unwrap();
+        assert_eq!(shared[0].posts.len(), 1);
+        assert_eq!(shared[0].posts[0].title, "shared");
+    }
+
     #[test]
     fn test_foreign_key_static_str() {
         // Foreign keys should be static strings for efficiency
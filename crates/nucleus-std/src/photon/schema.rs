@@ -0,0 +1,1352 @@
+//! Schema Introspection and Diffing
+//!
+//! Reads the live schema from SQLite, PostgreSQL or MySQL, compares it with
+//! the schema described by your models, and renders up/down migrations for
+//! the differences.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::photon::schema::{diff_schemas, introspect, write_migration};
+//!
+//! let pool = db();
+//! let current = introspect(pool).await?;
+//! let changes = diff_schemas(&current, &desired_tables, pool.db_type());
+//! if !changes.is_empty() {
+//!     write_migration("schema_diff", "./migrations", &changes, pool.db_type())?;
+//! }
+//! ```
+
+use crate::photon::db::{DatabasePool, DatabaseType};
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SCHEMA TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Backend-neutral column type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlType {
+    Integer,
+    BigInt,
+    Real,
+    Text,
+    Boolean,
+    Blob,
+    Timestamp,
+    Uuid,
+    /// Any type Photon does not model; compared and emitted verbatim
+    Other(String),
+}
+
+impl SqlType {
+    /// Render the type for a specific database
+    pub fn to_sql(&self, db_type: DatabaseType) -> String {
+        let sql = match (self, db_type) {
+            (Self::Integer, _) => "INTEGER",
+            (Self::BigInt, DatabaseType::Sqlite) => "INTEGER",
+            (Self::BigInt, _) => "BIGINT",
+            (Self::Real, DatabaseType::Sqlite) => "REAL",
+            (Self::Real, DatabaseType::Postgres) => "DOUBLE PRECISION",
+            (Self::Real, DatabaseType::MySql) => "DOUBLE",
+            (Self::Text, DatabaseType::MySql) => "VARCHAR(255)",
+            (Self::Text, _) => "TEXT",
+            (Self::Boolean, _) => "BOOLEAN",
+            (Self::Blob, DatabaseType::Postgres) => "BYTEA",
+            (Self::Blob, _) => "BLOB",
+            (Self::Timestamp, DatabaseType::Postgres) => "TIMESTAMPTZ",
+            (Self::Timestamp, _) => "DATETIME",
+            (Self::Uuid, DatabaseType::Sqlite) => "TEXT",
+            (Self::Uuid, DatabaseType::Postgres) => "UUID",
+            (Self::Uuid, DatabaseType::MySql) => "CHAR(36)",
+            (Self::Other(raw), _) => return raw.clone(),
+        };
+        sql.to_string()
+    }
+
+    /// Normalize a type name reported by the database
+    pub fn from_db(raw: &str, db_type: DatabaseType) -> Self {
+        let t = raw.trim().to_lowercase();
+        let base = t.split('(').next().unwrap_or("").trim();
+
+        match db_type {
+            DatabaseType::Sqlite => {
+                // SQLite type affinity rules, plus the names Photon emits
+                if base.contains("bool") {
+                    Self::Boolean
+                } else if base.contains("int") {
+                    Self::Integer
+                } else if base.contains("char") || base.contains("clob") || base.contains("text") {
+                    Self::Text
+                } else if base.contains("blob") {
+                    Self::Blob
+                } else if base.contains("real") || base.contains("floa") || base.contains("doub") {
+                    Self::Real
+                } else if base.contains("date") || base.contains("time") {
+                    Self::Timestamp
+                } else {
+                    Self::Other(raw.to_string())
+                }
+            }
+            DatabaseType::Postgres => match base {
+                "integer" | "int" | "int4" | "smallint" | "int2" | "serial" => Self::Integer,
+                "bigint" | "int8" | "bigserial" => Self::BigInt,
+                "double precision" | "float8" | "real" | "float4" | "numeric" => Self::Real,
+                "text" | "character varying" | "varchar" | "character" | "char" => Self::Text,
+                "boolean" | "bool" => Self::Boolean,
+                "bytea" => Self::Blob,
+                "uuid" => Self::Uuid,
+                _ if base.starts_with("timestamp") || base == "date" => Self::Timestamp,
+                _ => Self::Other(raw.to_string()),
+            },
+            DatabaseType::MySql => match base {
+                "tinyint" | "boolean" | "bool" => Self::Boolean,
+                "int" | "integer" | "smallint" | "mediumint" => Self::Integer,
+                "bigint" => Self::BigInt,
+                "double" | "float" | "decimal" => Self::Real,
+                "varchar" | "char" | "text" | "tinytext" | "mediumtext" | "longtext" => Self::Text,
+                "blob" | "tinyblob" | "mediumblob" | "longblob" | "varbinary" | "binary" => {
+                    Self::Blob
+                }
+                "datetime" | "timestamp" | "date" => Self::Timestamp,
+                _ => Self::Other(raw.to_string()),
+            },
+        }
+    }
+
+    /// Map a Rust (or `<n:model>`) field type to a column type
+    ///
+    /// Returns the type and whether the column is nullable (`Option<T>`).
+    pub fn from_rust_type(ty: &str) -> (Self, bool) {
+        let ty: String = ty.chars().filter(|c| !c.is_whitespace()).collect();
+
+        if let Some(inner) = ty
+            .strip_prefix("Option<")
+            .or_else(|| ty.strip_prefix("std::option::Option<"))
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return (Self::from_rust_type(inner).0, true);
+        }
+
+        let last = ty.rsplit("::").next().unwrap_or(&ty);
+        let sql_type = match last {
+            "i8" | "i16" | "i32" | "u8" | "u16" | "int" | "Integer" => Self::Integer,
+            "i64" | "u32" | "u64" | "isize" | "usize" | "long" => Self::BigInt,
+            "f32" | "f64" | "float" | "Float" | "Decimal" => Self::Real,
+            "String" | "str" | "&str" | "string" | "Text" => Self::Text,
+            "bool" | "Boolean" => Self::Boolean,
+            "Vec<u8>" | "Bytes" => Self::Blob,
+            "Uuid" | "UUID" => Self::Uuid,
+            _ if last.starts_with("DateTime")
+                || last.starts_with("NaiveDate")
+                || last == "Timestamp" =>
+            {
+                Self::Timestamp
+            }
+            _ => Self::Other(last.to_string()),
+        };
+        (sql_type, false)
+    }
+
+    /// Whether two types are stored identically on this database
+    pub fn equivalent(&self, other: &Self, db_type: DatabaseType) -> bool {
+        Self::from_db(&self.to_sql(db_type), db_type)
+            == Self::from_db(&other.to_sql(db_type), db_type)
+    }
+
+    /// Literal used as the default when adding a NOT NULL column to a populated table
+    fn zero_value(&self) -> &'static str {
+        match self {
+            Self::Text | Self::Uuid | Self::Other(_) => "''",
+            Self::Blob => "X''",
+            Self::Timestamp => "'1970-01-01 00:00:00'",
+            Self::Integer | Self::BigInt | Self::Real | Self::Boolean => "0",
+        }
+    }
+}
+
+/// A table column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub sql_type: SqlType,
+    pub nullable: bool,
+    pub primary_key: bool,
+    /// Raw SQL default expression
+    pub default: Option<String>,
+}
+
+impl ColumnDef {
+    /// Create a NOT NULL column
+    pub fn new(name: &str, sql_type: SqlType) -> Self {
+        Self {
+            name: name.to_string(),
+            sql_type,
+            nullable: false,
+            primary_key: false,
+            default: None,
+        }
+    }
+
+    /// Mark the column nullable
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Mark the column as the primary key
+    pub fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self
+    }
+
+    /// Column definition as used in CREATE TABLE / ADD COLUMN
+    fn definition(&self, db_type: DatabaseType) -> String {
+        let integer_key = matches!(self.sql_type, SqlType::Integer | SqlType::BigInt);
+
+        if self.primary_key && integer_key {
+            let ty = match (db_type, &self.sql_type) {
+                (DatabaseType::Sqlite, _) => "INTEGER PRIMARY KEY AUTOINCREMENT",
+                (DatabaseType::Postgres, SqlType::Integer) => "SERIAL PRIMARY KEY",
+                (DatabaseType::Postgres, _) => "BIGSERIAL PRIMARY KEY",
+                (DatabaseType::MySql, SqlType::Integer) => "INT AUTO_INCREMENT PRIMARY KEY",
+                (DatabaseType::MySql, _) => "BIGINT AUTO_INCREMENT PRIMARY KEY",
+            };
+            return format!("{} {}", self.name, ty);
+        }
+
+        let mut sql = format!("{} {}", self.name, self.sql_type.to_sql(db_type));
+        if self.primary_key {
+            sql.push_str(" PRIMARY KEY");
+        } else if !self.nullable {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        sql
+    }
+
+    /// Definition for ADD COLUMN; NOT NULL columns get a zero default so
+    /// the statement succeeds on tables that already contain rows
+    fn add_definition(&self, db_type: DatabaseType) -> String {
+        if !self.nullable && !self.primary_key && self.default.is_none() {
+            let mut column = self.clone();
+            column.default = Some(self.sql_type.zero_value().to_string());
+            return column.definition(db_type);
+        }
+        self.definition(db_type)
+    }
+}
+
+/// A secondary index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDef {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// Backed by a table constraint rather than `CREATE INDEX`
+    pub constraint: bool,
+}
+
+/// A foreign key from `column` to `ref_table(ref_column)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKeyDef {
+    pub name: String,
+    pub column: String,
+    pub ref_table: String,
+    pub ref_column: String,
+}
+
+impl ForeignKeyDef {
+    /// Create a foreign key with the conventional `fk_<table>_<column>` name
+    pub fn new(table: &str, column: &str, ref_table: &str, ref_column: &str) -> Self {
+        Self {
+            name: format!("fk_{}_{}", table, column),
+            column: column.to_string(),
+            ref_table: ref_table.to_string(),
+            ref_column: ref_column.to_string(),
+        }
+    }
+
+    fn same_target(&self, other: &Self) -> bool {
+        self.column == other.column
+            && self.ref_table == other.ref_table
+            && self.ref_column == other.ref_column
+    }
+}
+
+/// A table with its columns, indexes and foreign keys
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TableDef {
+    pub name: String,
+    pub columns: Vec<ColumnDef>,
+    pub indexes: Vec<IndexDef>,
+    pub foreign_keys: Vec<ForeignKeyDef>,
+}
+
+impl TableDef {
+    /// Create an empty table definition
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Look up a column by name
+    pub fn column(&self, name: &str) -> Option<&ColumnDef> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Render `CREATE TABLE` for this table, excluding indexes
+    pub fn create_sql(&self, db_type: DatabaseType) -> String {
+        let mut lines: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| format!("    {}", c.definition(db_type)))
+            .collect();
+        for fk in &self.foreign_keys {
+            lines.push(format!(
+                "    CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}({})",
+                fk.name, fk.column, fk.ref_table, fk.ref_column
+            ));
+        }
+        format!("CREATE TABLE {} (\n{}\n);", self.name, lines.join(",\n"))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SCHEMA CHANGES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A single difference between the live schema and the desired schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    CreateTable(TableDef),
+    DropTable(TableDef),
+    AddColumn {
+        table: String,
+        column: ColumnDef,
+    },
+    DropColumn {
+        table: String,
+        column: ColumnDef,
+    },
+    AlterColumn {
+        table: String,
+        from: ColumnDef,
+        to: ColumnDef,
+    },
+    CreateIndex {
+        table: String,
+        index: IndexDef,
+    },
+    DropIndex {
+        table: String,
+        index: IndexDef,
+    },
+    AddForeignKey {
+        table: String,
+        foreign_key: ForeignKeyDef,
+    },
+    DropForeignKey {
+        table: String,
+        foreign_key: ForeignKeyDef,
+    },
+    /// Copy-and-swap rebuild, used by SQLite which cannot ALTER columns or constraints
+    RebuildTable {
+        from: TableDef,
+        to: TableDef,
+    },
+}
+
+impl SchemaChange {
+    /// Human-readable one-line summary
+    pub fn describe(&self) -> String {
+        match self {
+            Self::CreateTable(t) => format!("+ table {}", t.name),
+            Self::DropTable(t) => format!("- table {}", t.name),
+            Self::AddColumn { table, column } => format!("+ column {}.{}", table, column.name),
+            Self::DropColumn { table, column } => format!("- column {}.{}", table, column.name),
+            Self::AlterColumn { table, to, .. } => format!("~ column {}.{}", table, to.name),
+            Self::CreateIndex { table, index } => format!("+ index {} on {}", index.name, table),
+            Self::DropIndex { table, index } => format!("- index {} on {}", index.name, table),
+            Self::AddForeignKey { table, foreign_key } => format!(
+                "+ foreign key {}.{} -> {}.{}",
+                table, foreign_key.column, foreign_key.ref_table, foreign_key.ref_column
+            ),
+            Self::DropForeignKey { table, foreign_key } => format!(
+                "- foreign key {}.{} -> {}.{}",
+                table, foreign_key.column, foreign_key.ref_table, foreign_key.ref_column
+            ),
+            Self::RebuildTable { to, .. } => format!("~ table {} (rebuild)", to.name),
+        }
+    }
+
+    /// SQL applying this change
+    pub fn up_sql(&self, db_type: DatabaseType) -> Vec<String> {
+        match self {
+            Self::CreateTable(t) => {
+                let mut stmts = vec![t.create_sql(db_type)];
+                stmts.extend(
+                    t.indexes
+                        .iter()
+                        .map(|i| create_index_sql(&t.name, i, db_type)),
+                );
+                stmts
+            }
+            Self::DropTable(t) => vec![format!("DROP TABLE {};", t.name)],
+            Self::AddColumn { table, column } => vec![format!(
+                "ALTER TABLE {} ADD COLUMN {};",
+                table,
+                column.add_definition(db_type)
+            )],
+            Self::DropColumn { table, column } => {
+                vec![format!(
+                    "ALTER TABLE {} DROP COLUMN {};",
+                    table, column.name
+                )]
+            }
+            Self::AlterColumn { table, to, .. } => alter_column_sql(table, to, db_type),
+            Self::CreateIndex { table, index } => vec![create_index_sql(table, index, db_type)],
+            Self::DropIndex { table, index } => vec![drop_index_sql(table, index, db_type)],
+            Self::AddForeignKey { table, foreign_key } => vec![format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}({});",
+                table,
+                foreign_key.name,
+                foreign_key.column,
+                foreign_key.ref_table,
+                foreign_key.ref_column
+            )],
+            Self::DropForeignKey { table, foreign_key } => {
+                let keyword = match db_type {
+                    DatabaseType::MySql => "FOREIGN KEY",
+                    _ => "CONSTRAINT",
+                };
+                vec![format!(
+                    "ALTER TABLE {} DROP {} {};",
+                    table, keyword, foreign_key.name
+                )]
+            }
+            Self::RebuildTable { from, to } => rebuild_table_sql(from, to, db_type),
+        }
+    }
+
+    /// SQL reverting this change
+    pub fn down_sql(&self, db_type: DatabaseType) -> Vec<String> {
+        self.inverse().up_sql(db_type)
+    }
+
+    /// The change that undoes this one
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::CreateTable(t) => Self::DropTable(t),
+            Self::DropTable(t) => Self::CreateTable(t),
+            Self::AddColumn { table, column } => Self::DropColumn { table, column },
+            Self::DropColumn { table, column } => Self::AddColumn { table, column },
+            Self::AlterColumn { table, from, to } => Self::AlterColumn {
+                table,
+                from: to,
+                to: from,
+            },
+            Self::CreateIndex { table, index } => Self::DropIndex { table, index },
+            Self::DropIndex { table, index } => Self::CreateIndex { table, index },
+            Self::AddForeignKey { table, foreign_key } => {
+                Self::DropForeignKey { table, foreign_key }
+            }
+            Self::DropForeignKey { table, foreign_key } => {
+                Self::AddForeignKey { table, foreign_key }
+            }
+            Self::RebuildTable { from, to } => Self::RebuildTable { from: to, to: from },
+        }
+    }
+}
+
+fn create_index_sql(table: &str, index: &IndexDef, _db_type: DatabaseType) -> String {
+    format!(
+        "CREATE {}INDEX {} ON {} ({});",
+        if index.unique { "UNIQUE " } else { "" },
+        index.name,
+        table,
+        index.columns.join(", ")
+    )
+}
+
+fn drop_index_sql(table: &str, index: &IndexDef, db_type: DatabaseType) -> String {
+    match db_type {
+        DatabaseType::MySql => format!("DROP INDEX {} ON {};", index.name, table),
+        DatabaseType::Postgres if index.constraint => {
+            format!("ALTER TABLE {} DROP CONSTRAINT {};", table, index.name)
+        }
+        _ => format!("DROP INDEX {};", index.name),
+    }
+}
+
+fn alter_column_sql(table: &str, to: &ColumnDef, db_type: DatabaseType) -> Vec<String> {
+    match db_type {
+        DatabaseType::Postgres => {
+            let ty = to.sql_type.to_sql(db_type);
+            vec![
+                format!(
+                    "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{};",
+                    table, to.name, ty, to.name, ty
+                ),
+                format!(
+                    "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;",
+                    table,
+                    to.name,
+                    if to.nullable { "DROP" } else { "SET" }
+                ),
+            ]
+        }
+        DatabaseType::MySql => vec![format!(
+            "ALTER TABLE {} MODIFY COLUMN {};",
+            table,
+            to.definition(db_type)
+        )],
+        // `diff_schemas` emits RebuildTable instead for SQLite
+        DatabaseType::Sqlite => vec![format!(
+            "-- SQLite cannot alter {}.{}; rebuild the table",
+            table, to.name
+        )],
+    }
+}
+
+fn rebuild_table_sql(from: &TableDef, to: &TableDef, db_type: DatabaseType) -> Vec<String> {
+    let temp = format!("_photon_new_{}", to.name);
+    let mut staged = to.clone();
+    staged.name = temp.clone();
+
+    // Existing columns are copied; new NOT NULL columns without a default
+    // get the same zero value ADD COLUMN would give them
+    let (columns, values): (Vec<&str>, Vec<&str>) = to
+        .columns
+        .iter()
+        .filter_map(|c| {
+            if from.column(&c.name).is_some() {
+                Some((c.name.as_str(), c.name.as_str()))
+            } else if !c.nullable && !c.primary_key && c.default.is_none() {
+                Some((c.name.as_str(), c.sql_type.zero_value()))
+            } else {
+                None
+            }
+        })
+        .unzip();
+
+    let mut stmts = vec![
+        staged.create_sql(db_type),
+        format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {};",
+            temp,
+            columns.join(", "),
+            values.join(", "),
+            from.name
+        ),
+        format!("DROP TABLE {};", from.name),
+        format!("ALTER TABLE {} RENAME TO {};", temp, to.name),
+    ];
+    stmts.extend(
+        to.indexes
+            .iter()
+            .filter(|i| !i.constraint)
+            .map(|i| create_index_sql(&to.name, i, db_type)),
+    );
+    stmts
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DIFFING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Compute the changes needed to turn `current` into `desired`
+///
+/// Tables are matched by name, indexes by columns and uniqueness, and
+/// foreign keys by their column and target. Framework tables without a
+/// model are left alone. Primary keys and defaults are
+/// only emitted when creating tables, never altered.
+pub fn diff_schemas(
+    current: &[TableDef],
+    desired: &[TableDef],
+    db_type: DatabaseType,
+) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+
+    for want in desired {
+        let Some(have) = current.iter().find(|t| t.name == want.name) else {
+            changes.push(SchemaChange::CreateTable(want.clone()));
+            continue;
+        };
+        changes.extend(diff_table(have, want, db_type));
+    }
+
+    for have in current {
+        let internal = INTERNAL_TABLES.contains(&have.name.as_str());
+        if !internal && !desired.iter().any(|t| t.name == have.name) {
+            changes.push(SchemaChange::DropTable(have.clone()));
+        }
+    }
+
+    changes
+}
+
+fn diff_table(have: &TableDef, want: &TableDef, db_type: DatabaseType) -> Vec<SchemaChange> {
+    let table = want.name.clone();
+    let mut changes = Vec::new();
+    let mut needs_rebuild = false;
+
+    for column in &want.columns {
+        match have.column(&column.name) {
+            None => changes.push(SchemaChange::AddColumn {
+                table: table.clone(),
+                column: column.clone(),
+            }),
+            Some(existing) if !existing.primary_key && !column.primary_key => {
+                let type_changed = !existing.sql_type.equivalent(&column.sql_type, db_type);
+                if type_changed || existing.nullable != column.nullable {
+                    if db_type == DatabaseType::Sqlite {
+                        needs_rebuild = true;
+                    } else {
+                        changes.push(SchemaChange::AlterColumn {
+                            table: table.clone(),
+                            from: existing.clone(),
+                            to: column.clone(),
+                        });
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    for column in &have.columns {
+        if want.column(&column.name).is_none() {
+            changes.push(SchemaChange::DropColumn {
+                table: table.clone(),
+                column: column.clone(),
+            });
+        }
+    }
+
+    for index in &want.indexes {
+        let exists = have
+            .indexes
+            .iter()
+            .any(|i| i.columns == index.columns && i.unique == index.unique);
+        if !exists {
+            changes.push(SchemaChange::CreateIndex {
+                table: table.clone(),
+                index: index.clone(),
+            });
+        }
+    }
+
+    for index in &have.indexes {
+        let wanted = want
+            .indexes
+            .iter()
+            .any(|i| i.columns == index.columns && i.unique == index.unique);
+        if !wanted {
+            if db_type == DatabaseType::Sqlite && index.constraint {
+                needs_rebuild = true;
+            } else {
+                changes.push(SchemaChange::DropIndex {
+                    table: table.clone(),
+                    index: index.clone(),
+                });
+            }
+        }
+    }
+
+    for fk in &want.foreign_keys {
+        if !have.foreign_keys.iter().any(|f| f.same_target(fk)) {
+            if db_type == DatabaseType::Sqlite {
+                needs_rebuild = true;
+            } else {
+                changes.push(SchemaChange::AddForeignKey {
+                    table: table.clone(),
+                    foreign_key: fk.clone(),
+                });
+            }
+        }
+    }
+
+    for fk in &have.foreign_keys {
+        if !want.foreign_keys.iter().any(|f| f.same_target(fk)) {
+            if db_type == DatabaseType::Sqlite {
+                needs_rebuild = true;
+            } else {
+                changes.push(SchemaChange::DropForeignKey {
+                    table: table.clone(),
+                    foreign_key: fk.clone(),
+                });
+            }
+        }
+    }
+
+    if needs_rebuild {
+        // A rebuild recreates every column, index and key in one step
+        return vec![SchemaChange::RebuildTable {
+            from: have.clone(),
+            to: want.clone(),
+        }];
+    }
+
+    changes
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MIGRATION RENDERING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Render up and down SQL for a list of changes
+///
+/// Down statements are emitted in reverse order so they undo the up block.
+pub fn render_migration(changes: &[SchemaChange], db_type: DatabaseType) -> (String, String) {
+    let up: Vec<String> = changes.iter().flat_map(|c| c.up_sql(db_type)).collect();
+    let down: Vec<String> = changes
+        .iter()
+        .rev()
+        .flat_map(|c| c.down_sql(db_type))
+        .collect();
+    (up.join("\n\n"), down.join("\n\n"))
+}
+
+/// Changes that cannot apply to a table that already holds rows
+///
+/// A NOT NULL column added without a default gets the same zero value in
+/// every row, so a unique index over it fails once the table has two rows.
+pub fn migration_warnings(changes: &[SchemaChange]) -> Vec<String> {
+    let unique_over = |table: &str, column: &ColumnDef| {
+        changes.iter().find_map(|change| match change {
+            SchemaChange::CreateIndex { table: t, index }
+                if t == table && index.unique && index.columns.contains(&column.name) =>
+            {
+                Some(index.name.clone())
+            }
+            _ => None,
+        })
+    };
+    let filled =
+        |column: &ColumnDef| !column.nullable && !column.primary_key && column.default.is_none();
+
+    let mut warnings = Vec::new();
+    for change in changes {
+        let added: Vec<(&str, &ColumnDef, Option<String>)> = match change {
+            SchemaChange::AddColumn { table, column } if filled(column) => {
+                vec![(table.as_str(), column, unique_over(table, column))]
+            }
+            SchemaChange::RebuildTable { from, to } => to
+                .columns
+                .iter()
+                .filter(|c| from.column(&c.name).is_none() && filled(c))
+                .map(|c| {
+                    let index = to
+                        .indexes
+                        .iter()
+                        .find(|i| i.unique && i.columns.contains(&c.name))
+                        .map(|i| i.name.clone());
+                    (to.name.as_str(), c, index)
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (table, column, index) in added {
+            if let Some(index) = index {
+                warnings.push(format!(
+                    "{}.{} is added as NOT NULL without a default, so existing rows all get {} and unique index {} fails on 2+ rows; add a default, make it nullable, or backfill it before the index",
+                    table,
+                    column.name,
+                    column.sql_type.zero_value(),
+                    index
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Write a migration file for the given changes
+///
+/// Uses the same `-- UP` / `-- DOWN` layout as `create_migration`.
+/// Warnings from `migration_warnings` are written as comments above it.
+/// Returns the created filename.
+pub fn write_migration(
+    name: &str,
+    dir: &str,
+    changes: &[SchemaChange],
+    db_type: DatabaseType,
+) -> Result<String, std::io::Error> {
+    fs::create_dir_all(dir)?;
+
+    let timestamp = Utc::now().format("%Y%m%d%H%M%S");
+    let filename = format!("{}_{}.sql", timestamp, name);
+    let (up, down) = render_migration(changes, db_type);
+
+    let mut summary: Vec<String> = changes
+        .iter()
+        .map(|c| format!("--   {}", c.describe()))
+        .collect();
+    summary.extend(
+        migration_warnings(changes)
+            .iter()
+            .map(|w| format!("-- WARNING: {}", w)),
+    );
+    let content = format!(
+        "-- Migration: {}\n-- Created: {}\n-- Generated by schema diff ({}):\n{}\n\n-- UP\n{}\n\n-- DOWN\n{}\n",
+        name,
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        db_type.name(),
+        summary.join("\n"),
+        up,
+        down
+    );
+
+    fs::write(Path::new(dir).join(&filename), content)?;
+    Ok(filename)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// INTROSPECTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tables the framework creates under their default names: migrations,
+/// sessions, MFA and job queues. `diff_schemas` never drops them.
+const INTERNAL_TABLES: &[&str] = &[
+    "_migrations",
+    "sessions",
+    "mfa_totp",
+    "mfa_recovery_codes",
+    "mfa_passkeys",
+    "jobs",
+    "pulse_jobs",
+];
+
+/// Read the current schema of every table
+pub async fn introspect(pool: &DatabasePool) -> Result<Vec<TableDef>, sqlx::Error> {
+    let mut tables = match pool {
+        DatabasePool::Sqlite(p) => introspect_sqlite(p).await?,
+        DatabasePool::Postgres(p) => introspect_postgres(p).await?,
+        DatabasePool::MySql(p) => introspect_mysql(p).await?,
+    };
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tables)
+}
+
+async fn introspect_sqlite(pool: &sqlx::SqlitePool) -> Result<Vec<TableDef>, sqlx::Error> {
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tables = Vec::new();
+    for (name,) in names {
+        let mut table = TableDef::new(&name);
+
+        let columns: Vec<(String, String, i64, Option<String>, i64)> = sqlx::query_as(
+            "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?)",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        for (col, ty, not_null, default, pk) in columns {
+            table.columns.push(ColumnDef {
+                sql_type: SqlType::from_db(&ty, DatabaseType::Sqlite),
+                nullable: not_null == 0 && pk == 0,
+                primary_key: pk > 0,
+                default,
+                name: col,
+            });
+        }
+
+        let indexes: Vec<(String, i64, String)> =
+            sqlx::query_as("SELECT name, \"unique\", origin FROM pragma_index_list(?)")
+                .bind(&name)
+                .fetch_all(pool)
+                .await?;
+        for (index, unique, origin) in indexes {
+            if origin == "pk" {
+                continue;
+            }
+            let columns: Vec<(String,)> =
+                sqlx::query_as("SELECT name FROM pragma_index_info(?) ORDER BY seqno")
+                    .bind(&index)
+                    .fetch_all(pool)
+                    .await?;
+            table.indexes.push(IndexDef {
+                name: index,
+                columns: columns.into_iter().map(|(c,)| c).collect(),
+                unique: unique != 0,
+                constraint: origin == "u",
+            });
+        }
+
+        let fks: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT \"from\", \"table\", \"to\" FROM pragma_foreign_key_list(?)")
+                .bind(&name)
+                .fetch_all(pool)
+                .await?;
+        for (column, ref_table, ref_column) in fks {
+            table
+                .foreign_keys
+                .push(ForeignKeyDef::new(&name, &column, &ref_table, &ref_column));
+        }
+
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+async fn introspect_postgres(pool: &sqlx::PgPool) -> Result<Vec<TableDef>, sqlx::Error> {
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT table_name::text FROM information_schema.tables \
+         WHERE table_schema = current_schema() AND table_type = 'BASE TABLE'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tables = Vec::new();
+    for (name,) in names {
+        let mut table = TableDef::new(&name);
+
+        let primary: Vec<(String,)> = sqlx::query_as(
+            "SELECT kcu.column_name::text \
+             FROM information_schema.table_constraints tc \
+             JOIN information_schema.key_column_usage kcu \
+               ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema \
+             WHERE tc.table_schema = current_schema() AND tc.table_name = $1 \
+               AND tc.constraint_type = 'PRIMARY KEY'",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+
+        let columns: Vec<(String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT column_name::text, data_type::text, is_nullable::text, column_default::text \
+             FROM information_schema.columns \
+             WHERE table_schema = current_schema() AND table_name = $1 \
+             ORDER BY ordinal_position",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        for (col, ty, nullable, default) in columns {
+            table.columns.push(ColumnDef {
+                sql_type: SqlType::from_db(&ty, DatabaseType::Postgres),
+                nullable: nullable == "YES",
+                primary_key: primary.iter().any(|(p,)| *p == col),
+                default,
+                name: col,
+            });
+        }
+
+        let indexes: Vec<(String, bool, bool, String)> = sqlx::query_as(
+            "SELECT i.relname::text, ix.indisunique, \
+                    EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = ix.indexrelid), \
+                    a.attname::text \
+             FROM pg_class t \
+             JOIN pg_index ix ON t.oid = ix.indrelid \
+             JOIN pg_class i ON i.oid = ix.indexrelid \
+             JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ANY(ix.indkey) \
+             WHERE t.relname = $1 AND t.relnamespace = current_schema()::regnamespace \
+               AND NOT ix.indisprimary \
+             ORDER BY i.relname, array_position(ix.indkey::int2[], a.attnum)",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        push_index_rows(&mut table, indexes);
+
+        let fks: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT tc.constraint_name::text, kcu.column_name::text, \
+                    ccu.table_name::text, ccu.column_name::text \
+             FROM information_schema.table_constraints tc \
+             JOIN information_schema.key_column_usage kcu \
+               ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema \
+             JOIN information_schema.constraint_column_usage ccu \
+               ON ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema \
+             WHERE tc.table_schema = current_schema() AND tc.table_name = $1 \
+               AND tc.constraint_type = 'FOREIGN KEY'",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        for (fk_name, column, ref_table, ref_column) in fks {
+            table.foreign_keys.push(ForeignKeyDef {
+                name: fk_name,
+                column,
+                ref_table,
+                ref_column,
+            });
+        }
+
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+async fn introspect_mysql(pool: &sqlx::MySqlPool) -> Result<Vec<TableDef>, sqlx::Error> {
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT CAST(table_name AS CHAR) FROM information_schema.tables \
+         WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tables = Vec::new();
+    for (name,) in names {
+        let mut table = TableDef::new(&name);
+
+        let columns: Vec<(String, String, String, Option<String>, String)> = sqlx::query_as(
+            "SELECT CAST(column_name AS CHAR), CAST(column_type AS CHAR), \
+                    CAST(is_nullable AS CHAR), CAST(column_default AS CHAR), \
+                    CAST(column_key AS CHAR) \
+             FROM information_schema.columns \
+             WHERE table_schema = DATABASE() AND table_name = ? \
+             ORDER BY ordinal_position",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        for (col, ty, nullable, default, key) in columns {
+            table.columns.push(ColumnDef {
+                sql_type: SqlType::from_db(&ty, DatabaseType::MySql),
+                nullable: nullable == "YES",
+                primary_key: key == "PRI",
+                default,
+                name: col,
+            });
+        }
+
+        // MySQL implements UNIQUE constraints as indexes, so every index is droppable
+        let indexes: Vec<(String, i64, String)> = sqlx::query_as(
+            "SELECT CAST(index_name AS CHAR), CAST(non_unique AS SIGNED), CAST(column_name AS CHAR) \
+             FROM information_schema.statistics \
+             WHERE table_schema = DATABASE() AND table_name = ? AND index_name != 'PRIMARY' \
+             ORDER BY index_name, seq_in_index",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        push_index_rows(
+            &mut table,
+            indexes
+                .into_iter()
+                .map(|(index, non_unique, column)| (index, non_unique == 0, false, column))
+                .collect(),
+        );
+
+        let fks: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT CAST(constraint_name AS CHAR), CAST(column_name AS CHAR), \
+                    CAST(referenced_table_name AS CHAR), CAST(referenced_column_name AS CHAR) \
+             FROM information_schema.key_column_usage \
+             WHERE table_schema = DATABASE() AND table_name = ? \
+               AND referenced_table_name IS NOT NULL",
+        )
+        .bind(&name)
+        .fetch_all(pool)
+        .await?;
+        for (fk_name, column, ref_table, ref_column) in fks {
+            table.foreign_keys.push(ForeignKeyDef {
+                name: fk_name,
+                column,
+                ref_table,
+                ref_column,
+            });
+        }
+
+        // MySQL creates a plain index backing each foreign key; it is not a model index
+        table.indexes.retain(|i| {
+            !(i.columns.len() == 1
+                && !i.unique
+                && table.foreign_keys.iter().any(|fk| fk.name == i.name))
+        });
+
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+/// Group `(index, unique, constraint, column)` rows into index definitions
+fn push_index_rows(table: &mut TableDef, rows: Vec<(String, bool, bool, String)>) {
+    for (name, unique, constraint, column) in rows {
+        match table.indexes.iter_mut().find(|i| i.name == name) {
+            Some(index) => index.columns.push(column),
+            None => table.indexes.push(IndexDef {
+                name,
+                columns: vec![column],
+                unique,
+                constraint,
+            }),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users_table() -> TableDef {
+        let mut table = TableDef::new("users");
+        table.columns = vec![
+            ColumnDef::new("id", SqlType::BigInt).primary_key(),
+            ColumnDef::new("email", SqlType::Text),
+            ColumnDef::new("bio", SqlType::Text).nullable(),
+        ];
+        table.indexes.push(IndexDef {
+            name: "users_email_unique".into(),
+            columns: vec!["email".into()],
+            unique: true,
+            constraint: false,
+        });
+        table
+    }
+
+    #[test]
+    fn test_sql_type_round_trip() {
+        for db in [
+            DatabaseType::Sqlite,
+            DatabaseType::Postgres,
+            DatabaseType::MySql,
+        ] {
+            for ty in [
+                SqlType::Integer,
+                SqlType::Real,
+                SqlType::Text,
+                SqlType::Boolean,
+                SqlType::Blob,
+                SqlType::Timestamp,
+            ] {
+                assert_eq!(
+                    SqlType::from_db(&ty.to_sql(db), db),
+                    ty,
+                    "{:?} {:?}",
+                    ty,
+                    db
+                );
+            }
+        }
+        assert!(SqlType::BigInt.equivalent(&SqlType::Integer, DatabaseType::Sqlite));
+        assert!(!SqlType::BigInt.equivalent(&SqlType::Integer, DatabaseType::Postgres));
+        assert!(SqlType::Uuid.equivalent(&SqlType::Text, DatabaseType::Sqlite));
+    }
+
+    #[test]
+    fn test_from_rust_type() {
+        assert_eq!(SqlType::from_rust_type("i64"), (SqlType::BigInt, false));
+        assert_eq!(
+            SqlType::from_rust_type("Option<String>"),
+            (SqlType::Text, true)
+        );
+        assert_eq!(
+            SqlType::from_rust_type("chrono::DateTime<Utc>"),
+            (SqlType::Timestamp, false)
+        );
+        assert_eq!(
+            SqlType::from_rust_type("Integer"),
+            (SqlType::Integer, false)
+        );
+        assert_eq!(SqlType::from_rust_type("UUID"), (SqlType::Uuid, false));
+    }
+
+    #[test]
+    fn test_diff_creates_missing_table() {
+        let changes = diff_schemas(&[], &[users_table()], DatabaseType::Postgres);
+        assert_eq!(changes.len(), 1);
+
+        let (up, down) = render_migration(&changes, DatabaseType::Postgres);
+        assert!(up.contains("CREATE TABLE users"));
+        assert!(up.contains("id BIGSERIAL PRIMARY KEY"));
+        assert!(up.contains("email TEXT NOT NULL"));
+        assert!(up.contains("CREATE UNIQUE INDEX users_email_unique ON users (email);"));
+        assert_eq!(down, "DROP TABLE users;");
+    }
+
+    #[test]
+    fn test_diff_in_sync_is_empty() {
+        let table = users_table();
+        for db in [
+            DatabaseType::Sqlite,
+            DatabaseType::Postgres,
+            DatabaseType::MySql,
+        ] {
+            let tables = std::slice::from_ref(&table);
+            assert!(diff_schemas(tables, tables, db).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_diff_add_drop_and_alter_columns() {
+        let current = users_table();
+        let mut desired = users_table();
+        desired.columns.retain(|c| c.name != "bio");
+        desired
+            .columns
+            .push(ColumnDef::new("age", SqlType::Integer));
+        desired.columns[1].nullable = true;
+
+        let changes = diff_schemas(&[current], &[desired], DatabaseType::Postgres);
+        let (up, down) = render_migration(&changes, DatabaseType::Postgres);
+
+        assert!(up.contains("ALTER TABLE users ALTER COLUMN email DROP NOT NULL;"));
+        assert!(up.contains("ALTER TABLE users ADD COLUMN age INTEGER NOT NULL DEFAULT 0;"));
+        assert!(up.contains("ALTER TABLE users DROP COLUMN bio;"));
+        assert!(down.contains("ALTER TABLE users ADD COLUMN bio TEXT;"));
+        assert!(down.contains("ALTER TABLE users DROP COLUMN age;"));
+        assert!(down.contains("ALTER COLUMN email SET NOT NULL"));
+        // No unique index covers `age`, so a shared zero default is fine
+        assert!(migration_warnings(&changes).is_empty());
+    }
+
+    #[test]
+    fn test_diff_indexes_and_foreign_keys() {
+        let current = TableDef {
+            columns: vec![
+                ColumnDef::new("id", SqlType::BigInt).primary_key(),
+                ColumnDef::new("user_id", SqlType::BigInt),
+            ],
+            ..TableDef::new("posts")
+        };
+        let mut desired = current.clone();
+        desired
+            .foreign_keys
+            .push(ForeignKeyDef::new("posts", "user_id", "users", "id"));
+        desired.indexes.push(IndexDef {
+            name: "posts_user_id_idx".into(),
+            columns: vec!["user_id".into()],
+            unique: false,
+            constraint: false,
+        });
+
+        let changes = diff_schemas(
+            std::slice::from_ref(&current),
+            std::slice::from_ref(&desired),
+            DatabaseType::MySql,
+        );
+        let (up, down) = render_migration(&changes, DatabaseType::MySql);
+        assert!(up.contains("CREATE INDEX posts_user_id_idx ON posts (user_id);"));
+        assert!(up.contains(
+            "ALTER TABLE posts ADD CONSTRAINT fk_posts_user_id FOREIGN KEY (user_id) REFERENCES users(id);"
+        ));
+        assert!(down.contains("ALTER TABLE posts DROP FOREIGN KEY fk_posts_user_id;"));
+        assert!(down.contains("DROP INDEX posts_user_id_idx ON posts;"));
+
+        // SQLite cannot add constraints in place, so the table is rebuilt
+        let changes = diff_schemas(&[current], &[desired], DatabaseType::Sqlite);
+        assert!(matches!(changes[0], SchemaChange::RebuildTable { .. }));
+        let (up, _) = render_migration(&changes, DatabaseType::Sqlite);
+        assert!(up.contains("CREATE TABLE _photon_new_posts"));
+        assert!(up.contains(
+            "INSERT INTO _photon_new_posts (id, user_id) SELECT id, user_id FROM posts;"
+        ));
+        assert!(up.contains("ALTER TABLE _photon_new_posts RENAME TO posts;"));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_fills_new_not_null_columns() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY AUTOINCREMENT, user_id INTEGER NOT NULL);
+             INSERT INTO users DEFAULT VALUES;
+             INSERT INTO users DEFAULT VALUES;
+             INSERT INTO posts (user_id) VALUES (1), (2);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let current = TableDef {
+            columns: vec![
+                ColumnDef::new("id", SqlType::BigInt).primary_key(),
+                ColumnDef::new("user_id", SqlType::BigInt),
+            ],
+            ..TableDef::new("posts")
+        };
+        let mut desired = current.clone();
+        desired.columns.push(ColumnDef::new("slug", SqlType::Text));
+        desired
+            .foreign_keys
+            .push(ForeignKeyDef::new("posts", "user_id", "users", "id"));
+
+        let changes = diff_schemas(&[current], &[desired], DatabaseType::Sqlite);
+        let (up, _) = render_migration(&changes, DatabaseType::Sqlite);
+        assert!(up.contains(
+            "INSERT INTO _photon_new_posts (id, user_id, slug) SELECT id, user_id, '' FROM posts;"
+        ));
+
+        sqlx::raw_sql(&up).execute(&pool).await.unwrap();
+        let slugs: Vec<(String,)> = sqlx::query_as("SELECT slug FROM posts")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(slugs, vec![("".to_string(),), ("".to_string(),)]);
+    }
+
+    #[test]
+    fn test_diff_drops_removed_table() {
+        let changes = diff_schemas(&[users_table()], &[], DatabaseType::Sqlite);
+        assert_eq!(changes, vec![SchemaChange::DropTable(users_table())]);
+        assert_eq!(changes[0].describe(), "- table users");
+
+        // Framework tables have no model but are not drift
+        let current: Vec<TableDef> = ["_migrations", "sessions", "mfa_passkeys", "pulse_jobs"]
+            .into_iter()
+            .map(TableDef::new)
+            .collect();
+        assert!(diff_schemas(&current, &[], DatabaseType::Postgres).is_empty());
+    }
+
+    #[test]
+    fn test_write_migration_file() {
+        let dir = "/tmp/test_schema_diff_migrations";
+        let _ = fs::remove_dir_all(dir);
+
+        let changes = diff_schemas(&[], &[users_table()], DatabaseType::Sqlite);
+        let filename = write_migration("schema_diff", dir, &changes, DatabaseType::Sqlite).unwrap();
+        assert!(filename.ends_with("_schema_diff.sql"));
+
+        let content = fs::read_to_string(Path::new(dir).join(&filename)).unwrap();
+        assert!(content.contains("--   + table users"));
+        let (up, down) = content.split_once("-- DOWN").unwrap();
+        assert!(up.contains("id INTEGER PRIMARY KEY AUTOINCREMENT"));
+        assert!(down.contains("DROP TABLE users;"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_introspect_sqlite() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, email TEXT NOT NULL UNIQUE, bio TEXT);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES users(id), title TEXT NOT NULL);
+             CREATE INDEX posts_title_idx ON posts (title);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let tables = introspect(&DatabasePool::Sqlite(pool)).await.unwrap();
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["posts", "users"]);
+
+        let users = &tables[1];
+        assert!(users.column("id").unwrap().primary_key);
+        assert!(!users.column("email").unwrap().nullable);
+        assert!(users.column("bio").unwrap().nullable);
+        assert!(users.indexes.iter().any(|i| i.unique && i.constraint));
+
+        let posts = &tables[0];
+        assert_eq!(posts.foreign_keys.len(), 1);
+        assert_eq!(posts.foreign_keys[0].ref_table, "users");
+        assert_eq!(posts.indexes[0].columns, vec!["title".to_string()]);
+    }
+}
@@ -0,0 +1,1074 @@
+//! Nucleus Policy - resource and attribute based authorization
+//!
+//! `Fortress::check_permission` answers "does any role grant this permission".
+//! Policies answer "may this user perform this action on this record":
+//!
+//! - [`Policy`] objects per resource type, registered on a [`Gate`]
+//! - ownership and tenant isolation through the [`Resource`] trait
+//! - role inheritance with [`RoleHierarchy`]
+//! - declarative rules with [`RulePolicy`]
+//! - the [`Authz`] extractor and route guards for axum, and
+//!   `graph::AuthGuard` / `graph::PolicyGuard` for GraphQL resolvers
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::policy::{self, Action, Authz, Gate, Resource, RoleHierarchy, Rule, RulePolicy};
+//!
+//! impl Resource for Post {
+//!     fn owner_id(&self) -> Option<String> { Some(self.author_id.to_string()) }
+//!     fn tenant_id(&self) -> Option<String> { Some(self.org_id.to_string()) }
+//! }
+//!
+//! policy::init(
+//!     Gate::new()
+//!         .roles(RoleHierarchy::new().inherit("admin", "editor").inherit("editor", "viewer"))
+//!         .policy(
+//!             RulePolicy::<Post>::new()
+//!                 .allow(Action::View, Rule::Anyone)
+//!                 .allow(Action::Update, Rule::Owner.or(Rule::role("editor")))
+//!                 .allow(Action::Delete, Rule::role("admin")),
+//!         ),
+//! )?;
+//!
+//! async fn update_post(authz: Authz, Path(id): Path<i64>) -> Result<Json<Post>, PolicyError> {
+//!     let post = Post::find(id).await?;
+//!     authz.authorize(Action::Update, &post)?; // 403 when denied
+//!     // ...
+//! }
+//! ```
+
+use crate::fortress::{AuthUser, Permission, Role, User};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use tracing::debug;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ERRORS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Authorization errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyError {
+    #[error("Authentication required")]
+    Unauthenticated,
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Policy gate already initialized")]
+    AlreadyInitialized,
+}
+
+impl IntoResponse for PolicyError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            PolicyError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            PolicyError::Forbidden(_) => StatusCode::FORBIDDEN,
+            PolicyError::AlreadyInitialized => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ACTIONS, PRINCIPALS & RESOURCES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// What a principal wants to do with a resource
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    /// List or search; checked without an instance
+    ViewAny,
+    View,
+    /// Checked without an instance
+    Create,
+    Update,
+    Delete,
+    Custom(String),
+}
+
+impl Action {
+    pub fn custom(name: &str) -> Self {
+        Action::Custom(name.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Action::ViewAny => "view_any",
+            Action::View => "view",
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Custom(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The user (or service) being authorized
+///
+/// Built from the verified access token by the [`Authz`] extractor; insert a
+/// `Principal` into the request extensions from your own middleware to load
+/// roles from the database instead.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Principal {
+    pub id: String,
+    #[serde(default)]
+    pub roles: HashSet<String>,
+    #[serde(default)]
+    pub permissions: HashSet<Permission>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Extra attributes for attribute-based rules (department, plan, ...)
+    #[serde(default)]
+    pub attributes: serde_json::Map<String, Value>,
+}
+
+impl Principal {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_role(mut self, role: &str) -> Self {
+        self.roles.insert(role.to_string());
+        self
+    }
+
+    pub fn with_permission(mut self, permission: Permission) -> Self {
+        self.permissions.insert(permission);
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: &str) -> Self {
+        self.tenant_id = Some(tenant_id.to_string());
+        self
+    }
+
+    pub fn with_attribute(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.attributes.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Role check; inside a policy this includes inherited roles
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.contains(role)
+    }
+
+    /// Permission check; inside a policy this includes role grants
+    pub fn has_permission(&self, permission: &Permission) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Value> {
+        self.attributes.get(name)
+    }
+
+    /// Whether the principal owns `resource`
+    pub fn owns<R: Resource>(&self, resource: &R) -> bool {
+        resource.owner_id().is_some_and(|owner| owner == self.id)
+    }
+
+    /// Whether `resource` belongs to the principal's tenant (or to no tenant)
+    pub fn same_tenant<R: Resource>(&self, resource: &R) -> bool {
+        match resource.tenant_id() {
+            Some(tenant) => self.tenant_id.as_deref() == Some(tenant.as_str()),
+            None => true,
+        }
+    }
+}
+
+impl From<&User> for Principal {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id.clone(),
+            roles: user.roles.iter().map(|r| r.name.clone()).collect(),
+            permissions: user
+                .roles
+                .iter()
+                .flat_map(|r| r.permissions.iter().cloned())
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads `roles` (array) or `role`, and `tenant_id` from the token claims;
+/// other custom claims become attributes
+impl From<&AuthUser> for Principal {
+    fn from(user: &AuthUser) -> Self {
+        let custom = &user.claims.custom;
+        let mut roles: HashSet<String> = custom
+            .get("roles")
+            .and_then(Value::as_array)
+            .map(|roles| {
+                roles
+                    .iter()
+                    .filter_map(|r| r.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(role) = custom.get("role").and_then(Value::as_str) {
+            roles.insert(role.to_string());
+        }
+        Self {
+            id: user.user_id.clone(),
+            roles,
+            permissions: HashSet::new(),
+            tenant_id: custom
+                .get("tenant_id")
+                .and_then(Value::as_str)
+                .map(String::from),
+            attributes: custom.clone(),
+        }
+    }
+}
+
+/// A record that policies are evaluated against
+pub trait Resource: Send + Sync + 'static {
+    /// Id of the owning user, for ownership rules
+    fn owner_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Tenant the record belongs to; the gate denies access across tenants
+    fn tenant_id(&self) -> Option<String> {
+        None
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ROLE HIERARCHY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Role inheritance and role → permission grants
+///
+/// ```rust,ignore
+/// let roles = RoleHierarchy::new()
+///     .grant("viewer", [Permission::Read])
+///     .grant("editor", [Permission::Write])
+///     .inherit("editor", "viewer")   // editors can do what viewers can
+///     .inherit("admin", "editor");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoleHierarchy {
+    parents: HashMap<String, Vec<String>>,
+    grants: HashMap<String, HashSet<Permission>>,
+}
+
+impl RoleHierarchy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build grants from Fortress roles
+    pub fn from_roles(roles: &[Role]) -> Self {
+        roles.iter().fold(Self::new(), |hierarchy, role| {
+            hierarchy.grant(&role.name, role.permissions.iter().cloned())
+        })
+    }
+
+    /// `role` inherits every role and permission of `parent`
+    pub fn inherit(mut self, role: &str, parent: &str) -> Self {
+        self.parents
+            .entry(role.to_string())
+            .or_default()
+            .push(parent.to_string());
+        self
+    }
+
+    /// Grant permissions to a role
+    pub fn grant(mut self, role: &str, permissions: impl IntoIterator<Item = Permission>) -> Self {
+        self.grants
+            .entry(role.to_string())
+            .or_default()
+            .extend(permissions);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty() && self.grants.is_empty()
+    }
+
+    /// `roles` plus everything they inherit, transitively
+    pub fn expand(&self, roles: &HashSet<String>) -> HashSet<String> {
+        let mut seen = roles.clone();
+        let mut pending: Vec<&String> = roles.iter().collect();
+        while let Some(role) = pending.pop() {
+            for parent in self.parents.get(role).into_iter().flatten() {
+                if seen.insert(parent.clone()) {
+                    pending.push(parent);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Permissions granted to `roles`, including inherited grants
+    pub fn permissions(&self, roles: &HashSet<String>) -> HashSet<Permission> {
+        self.expand(roles)
+            .iter()
+            .filter_map(|role| self.grants.get(role))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// `principal` with inherited roles and granted permissions filled in
+    pub fn resolve(&self, principal: &Principal) -> Principal {
+        let mut resolved = principal.clone();
+        resolved
+            .permissions
+            .extend(self.permissions(&principal.roles));
+        resolved.roles = self.expand(&principal.roles);
+        resolved
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// POLICIES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Authorization rules for one resource type
+///
+/// ```rust,ignore
+/// struct PostPolicy;
+///
+/// impl Policy<Post> for PostPolicy {
+///     fn allows(&self, user: &Principal, action: &Action, post: &Post) -> bool {
+///         match action {
+///             Action::View => post.published || user.owns(post),
+///             Action::Update | Action::Delete => user.owns(post),
+///             _ => false,
+///         }
+///     }
+///
+///     fn allows_any(&self, user: &Principal, action: &Action) -> bool {
+///         matches!(action, Action::Create) && user.has_role("author")
+///     }
+/// }
+/// ```
+pub trait Policy<R: Resource>: Send + Sync + 'static {
+    /// Decide before anything else (e.g. super admins); `None` continues
+    fn before(&self, _user: &Principal, _action: &Action) -> Option<bool> {
+        None
+    }
+
+    /// Whether `user` may perform `action` on `resource`
+    fn allows(&self, user: &Principal, action: &Action, resource: &R) -> bool;
+
+    /// Actions without an instance (`ViewAny`, `Create`)
+    fn allows_any(&self, _user: &Principal, _action: &Action) -> bool {
+        false
+    }
+}
+
+type RuleFn<R> = Arc<dyn Fn(&Principal, &R) -> bool + Send + Sync>;
+
+/// A condition in a [`RulePolicy`]
+pub enum Rule<R> {
+    /// Every authenticated principal
+    Anyone,
+    /// The principal owns the resource
+    Owner,
+    /// The principal has the role (or inherits it)
+    Role(String),
+    /// The principal has the permission (or gets it from a role)
+    Permission(Permission),
+    /// The principal attribute equals the value
+    Attribute(String, Value),
+    /// Arbitrary check against the resource
+    Custom(RuleFn<R>),
+    Any(Vec<Rule<R>>),
+    All(Vec<Rule<R>>),
+}
+
+impl<R: Resource> Rule<R> {
+    pub fn role(role: &str) -> Self {
+        Rule::Role(role.to_string())
+    }
+
+    pub fn attribute(name: &str, value: impl Into<Value>) -> Self {
+        Rule::Attribute(name.to_string(), value.into())
+    }
+
+    pub fn custom(check: impl Fn(&Principal, &R) -> bool + Send + Sync + 'static) -> Self {
+        Rule::Custom(Arc::new(check))
+    }
+
+    pub fn or(self, other: Rule<R>) -> Self {
+        Rule::Any(vec![self, other])
+    }
+
+    pub fn and(self, other: Rule<R>) -> Self {
+        Rule::All(vec![self, other])
+    }
+
+    /// Without a resource, ownership and custom rules never match
+    fn matches(&self, user: &Principal, resource: Option<&R>) -> bool {
+        match self {
+            Rule::Anyone => true,
+            Rule::Owner => resource.is_some_and(|r| user.owns(r)),
+            Rule::Role(role) => user.has_role(role),
+            Rule::Permission(permission) => user.has_permission(permission),
+            Rule::Attribute(name, value) => user.attribute(name) == Some(value),
+            Rule::Custom(check) => resource.is_some_and(|r| check(user, r)),
+            Rule::Any(rules) => rules.iter().any(|rule| rule.matches(user, resource)),
+            Rule::All(rules) => rules.iter().all(|rule| rule.matches(user, resource)),
+        }
+    }
+}
+
+/// Policy built from allow rules; anything not allowed is denied
+pub struct RulePolicy<R> {
+    rules: HashMap<Action, Vec<Rule<R>>>,
+}
+
+impl<R: Resource> Default for RulePolicy<R> {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+        }
+    }
+}
+
+impl<R: Resource> RulePolicy<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `action` when `rule` matches; several rules for one action are OR'ed
+    pub fn allow(mut self, action: Action, rule: Rule<R>) -> Self {
+        self.rules.entry(action).or_default().push(rule);
+        self
+    }
+
+    fn check(&self, user: &Principal, action: &Action, resource: Option<&R>) -> bool {
+        self.rules
+            .get(action)
+            .is_some_and(|rules| rules.iter().any(|rule| rule.matches(user, resource)))
+    }
+}
+
+impl<R: Resource> Policy<R> for RulePolicy<R> {
+    fn allows(&self, user: &Principal, action: &Action, resource: &R) -> bool {
+        self.check(user, action, Some(resource))
+    }
+
+    fn allows_any(&self, user: &Principal, action: &Action) -> bool {
+        self.check(user, action, None)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// GATE
+// ═══════════════════════════════════════════════════════════════════════════
+
+type BeforeHook = Arc<dyn Fn(&Principal, &Action) -> Option<bool> + Send + Sync>;
+type SuperUserFn = Arc<dyn Fn(&Principal) -> bool + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct GateInner {
+    roles: RoleHierarchy,
+    policies: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    super_users: Vec<SuperUserFn>,
+    before: Vec<BeforeHook>,
+}
+
+/// Registry of policies; evaluates `can(user, action, &resource)`
+///
+/// Order of evaluation: `super_user` bypasses, tenant isolation, gate
+/// `before` hooks, the policy's `before`, then the policy itself. Only a
+/// super user reaches another tenant's resources. Resource types without a
+/// policy are denied. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct Gate {
+    inner: Arc<GateInner>,
+}
+
+impl Gate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Role inheritance and grants used to resolve principals
+    pub fn roles(mut self, roles: RoleHierarchy) -> Self {
+        Arc::make_mut(&mut self.inner).roles = roles;
+        self
+    }
+
+    /// Register the policy for resource type `R`
+    pub fn policy<R: Resource>(mut self, policy: impl Policy<R>) -> Self {
+        let policy: Box<dyn Policy<R>> = Box::new(policy);
+        Arc::make_mut(&mut self.inner)
+            .policies
+            .insert(TypeId::of::<R>(), Arc::new(policy));
+        self
+    }
+
+    /// Hook run before every policy check, after tenant isolation
+    ///
+    /// Returning `Some(true)` cannot grant access to another tenant's
+    /// resources; use `super_user` for that.
+    pub fn before(
+        mut self,
+        hook: impl Fn(&Principal, &Action) -> Option<bool> + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.inner).before.push(Arc::new(hook));
+        self
+    }
+
+    /// Principals matching `check` may do anything, in every tenant
+    ///
+    /// The only way past tenant isolation; keep it to platform operators.
+    pub fn super_user(
+        mut self,
+        check: impl Fn(&Principal) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Arc::make_mut(&mut self.inner)
+            .super_users
+            .push(Arc::new(check));
+        self
+    }
+
+    /// Convenience `super_user`: principals with `role` may do anything,
+    /// across tenants
+    pub fn super_role(self, role: &str) -> Self {
+        let role = role.to_string();
+        self.super_user(move |user| user.has_role(&role))
+    }
+
+    fn is_super_user(&self, user: &Principal) -> bool {
+        self.inner.super_users.iter().any(|check| check(user))
+    }
+
+    fn resolve<'a>(&self, user: &'a Principal) -> Cow<'a, Principal> {
+        if self.inner.roles.is_empty() {
+            Cow::Borrowed(user)
+        } else {
+            Cow::Owned(self.inner.roles.resolve(user))
+        }
+    }
+
+    fn policy_for<R: Resource>(&self) -> Option<&dyn Policy<R>> {
+        self.inner
+            .policies
+            .get(&TypeId::of::<R>())?
+            .downcast_ref::<Box<dyn Policy<R>>>()
+            .map(|policy| policy.as_ref())
+    }
+
+    fn decide_before<R: Resource>(
+        &self,
+        user: &Principal,
+        action: &Action,
+    ) -> Result<&dyn Policy<R>, bool> {
+        if let Some(decision) = self.inner.before.iter().find_map(|hook| hook(user, action)) {
+            return Err(decision);
+        }
+        let Some(policy) = self.policy_for::<R>() else {
+            debug!(
+                resource = std::any::type_name::<R>(),
+                "No policy registered; denying"
+            );
+            return Err(false);
+        };
+        match policy.before(user, action) {
+            Some(decision) => Err(decision),
+            None => Ok(policy),
+        }
+    }
+
+    /// Whether `user` may perform `action` on `resource`
+    pub fn can<R: Resource>(&self, user: &Principal, action: Action, resource: &R) -> bool {
+        let user = self.resolve(user);
+        if self.is_super_user(&user) {
+            return true;
+        }
+        if !user.same_tenant(resource) {
+            return false;
+        }
+        match self.decide_before::<R>(&user, &action) {
+            Err(decision) => decision,
+            Ok(policy) => policy.allows(&user, &action, resource),
+        }
+    }
+
+    /// Whether `user` may perform `action` on resources of type `R` in general
+    pub fn can_any<R: Resource>(&self, user: &Principal, action: Action) -> bool {
+        let user = self.resolve(user);
+        if self.is_super_user(&user) {
+            return true;
+        }
+        match self.decide_before::<R>(&user, &action) {
+            Err(decision) => decision,
+            Ok(policy) => policy.allows_any(&user, &action),
+        }
+    }
+
+    /// `can`, as a `Result` that renders as 403
+    pub fn authorize<R: Resource>(
+        &self,
+        user: &Principal,
+        action: Action,
+        resource: &R,
+    ) -> Result<(), PolicyError> {
+        let denied = PolicyError::Forbidden(format!("{} not allowed", action));
+        if self.can(user, action, resource) {
+            Ok(())
+        } else {
+            Err(denied)
+        }
+    }
+
+    /// `can_any`, as a `Result` that renders as 403
+    pub fn authorize_any<R: Resource>(
+        &self,
+        user: &Principal,
+        action: Action,
+    ) -> Result<(), PolicyError> {
+        let denied = PolicyError::Forbidden(format!("{} not allowed", action));
+        if self.can_any::<R>(user, action) {
+            Ok(())
+        } else {
+            Err(denied)
+        }
+    }
+
+    /// Role check including inherited roles
+    pub fn has_role(&self, user: &Principal, role: &str) -> bool {
+        self.resolve(user).has_role(role)
+    }
+
+    /// Permission check including role grants
+    pub fn has_permission(&self, user: &Principal, permission: &Permission) -> bool {
+        self.resolve(user).has_permission(permission)
+    }
+}
+
+static GATE: OnceLock<Gate> = OnceLock::new();
+
+/// Install the application's gate. Call once at startup.
+pub fn init(gate: Gate) -> Result<(), PolicyError> {
+    GATE.set(gate).map_err(|_| PolicyError::AlreadyInitialized)
+}
+
+/// The global gate; empty (deny everything) until `init` is called
+pub fn gate() -> &'static Gate {
+    GATE.get_or_init(Gate::new)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// AXUM INTEGRATION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A `Principal` from the request extensions, or from the verified access token
+#[async_trait]
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(principal) = parts.extensions.get::<Principal>() {
+            return Ok(principal.clone());
+        }
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        Ok(Principal::from(&user))
+    }
+}
+
+/// Extractor pairing the current principal with the gate
+///
+/// Uses a `Gate` from the request extensions if one was added with
+/// `Extension(gate)`, otherwise the global gate.
+#[derive(Clone)]
+pub struct Authz {
+    pub principal: Principal,
+    gate: Gate,
+}
+
+impl Authz {
+    pub fn new(principal: Principal, gate: Gate) -> Self {
+        Self { principal, gate }
+    }
+
+    pub fn can<R: Resource>(&self, action: Action, resource: &R) -> bool {
+        self.gate.can(&self.principal, action, resource)
+    }
+
+    pub fn can_any<R: Resource>(&self, action: Action) -> bool {
+        self.gate.can_any::<R>(&self.principal, action)
+    }
+
+    /// Deny with 403 unless allowed
+    pub fn authorize<R: Resource>(&self, action: Action, resource: &R) -> Result<(), PolicyError> {
+        self.gate.authorize(&self.principal, action, resource)
+    }
+
+    /// Deny with 403 unless allowed for the resource type
+    pub fn authorize_any<R: Resource>(&self, action: Action) -> Result<(), PolicyError> {
+        self.gate.authorize_any::<R>(&self.principal, action)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.gate.has_role(&self.principal, role)
+    }
+
+    pub fn has_permission(&self, permission: &Permission) -> bool {
+        self.gate.has_permission(&self.principal, permission)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Authz
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let principal = Principal::from_request_parts(parts, state).await?;
+        let gate = parts
+            .extensions
+            .get::<Gate>()
+            .cloned()
+            .unwrap_or_else(|| gate().clone());
+        Ok(Authz { principal, gate })
+    }
+}
+
+type GuardFuture = std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>;
+
+fn guard(
+    check: impl Fn(&Authz) -> bool + Clone + Send + Sync + 'static,
+) -> impl Fn(Request, Next) -> GuardFuture + Clone {
+    move |request: Request, next: Next| {
+        let check = check.clone();
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let authz = match Authz::from_request_parts(&mut parts, &()).await {
+                Ok(authz) => authz,
+                Err(rejection) => return rejection.into_response(),
+            };
+            if !check(&authz) {
+                return PolicyError::Forbidden("insufficient privileges".to_string())
+                    .into_response();
+            }
+            parts.extensions.insert(authz.principal);
+            next.run(Request::from_parts(parts, body)).await
+        })
+    }
+}
+
+/// Route guard: 401 without a valid token, 403 without the permission
+///
+/// ```rust,ignore
+/// .layer(axum::middleware::from_fn(require_permission(Permission::Admin)))
+/// ```
+pub fn require_permission(permission: Permission) -> impl Fn(Request, Next) -> GuardFuture + Clone {
+    guard(move |authz| authz.has_permission(&permission))
+}
+
+/// Route guard: 401 without a valid token, 403 without the role (or a role inheriting it)
+pub fn require_role(role: &str) -> impl Fn(Request, Next) -> GuardFuture + Clone {
+    let role = role.to_string();
+    guard(move |authz| authz.has_role(&role))
+}
+
+/// Route guard for instance-less actions, e.g. `Action::Create` on `Post`
+pub fn require_action<R: Resource>(
+    action: Action,
+) -> impl Fn(Request, Next) -> GuardFuture + Clone {
+    guard(move |authz| authz.can_any::<R>(action.clone()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    struct Post {
+        author_id: String,
+        org_id: Option<String>,
+        published: bool,
+    }
+
+    impl Resource for Post {
+        fn owner_id(&self) -> Option<String> {
+            Some(self.author_id.clone())
+        }
+
+        fn tenant_id(&self) -> Option<String> {
+            self.org_id.clone()
+        }
+    }
+
+    fn post(author: &str) -> Post {
+        Post {
+            author_id: author.to_string(),
+            org_id: None,
+            published: false,
+        }
+    }
+
+    struct Comment;
+    impl Resource for Comment {}
+
+    fn gate() -> Gate {
+        Gate::new()
+            .roles(
+                RoleHierarchy::new()
+                    .grant("viewer", [Permission::Read])
+                    .grant("editor", [Permission::Write])
+                    .inherit("editor", "viewer")
+                    .inherit("admin", "editor"),
+            )
+            .policy(
+                RulePolicy::<Post>::new()
+                    .allow(Action::ViewAny, Rule::Anyone)
+                    .allow(Action::View, Rule::custom(|_, post: &Post| post.published))
+                    .allow(Action::View, Rule::Owner)
+                    .allow(Action::Create, Rule::Permission(Permission::Write))
+                    .allow(Action::Update, Rule::Owner.or(Rule::role("editor")))
+                    .allow(Action::Delete, Rule::role("admin"))
+                    .allow(
+                        Action::custom("feature"),
+                        Rule::role("editor").and(Rule::attribute("plan", "pro")),
+                    ),
+            )
+    }
+
+    #[test]
+    fn test_ownership_rules() {
+        let gate = gate();
+        let ada = Principal::new("ada");
+        let bob = Principal::new("bob");
+        let draft = post("ada");
+
+        assert!(gate.can(&ada, Action::View, &draft));
+        assert!(gate.can(&ada, Action::Update, &draft));
+        assert!(!gate.can(&ada, Action::Delete, &draft));
+        assert!(!gate.can(&bob, Action::View, &draft));
+        assert!(!gate.can(&bob, Action::Update, &draft));
+
+        let published = Post {
+            published: true,
+            ..post("ada")
+        };
+        assert!(gate.can(&bob, Action::View, &published));
+    }
+
+    #[test]
+    fn test_role_inheritance() {
+        let hierarchy = RoleHierarchy::new()
+            .grant("viewer", [Permission::Read])
+            .inherit("editor", "viewer")
+            .inherit("admin", "editor")
+            // Cycles must not loop forever
+            .inherit("viewer", "admin");
+        let roles = hierarchy.expand(&HashSet::from(["editor".to_string()]));
+        assert_eq!(roles.len(), 3);
+
+        let gate = gate();
+        let admin = Principal::new("root").with_role("admin");
+        let editor = Principal::new("eve").with_role("editor");
+        let viewer = Principal::new("vic").with_role("viewer");
+
+        // Admins inherit the editor rule
+        assert!(gate.can(&admin, Action::Update, &post("ada")));
+        assert!(gate.can(&admin, Action::Delete, &post("ada")));
+        assert!(gate.can(&editor, Action::Update, &post("ada")));
+        assert!(!gate.can(&editor, Action::Delete, &post("ada")));
+
+        // Permissions granted through inherited roles
+        assert!(gate.has_permission(&admin, &Permission::Read));
+        assert!(gate.can_any::<Post>(&editor, Action::Create));
+        assert!(!gate.can_any::<Post>(&viewer, Action::Create));
+        assert!(gate.can_any::<Post>(&viewer, Action::ViewAny));
+    }
+
+    #[test]
+    fn test_tenant_isolation_and_attributes() {
+        let gate = gate();
+        let scoped = Post {
+            org_id: Some("acme".into()),
+            ..post("ada")
+        };
+
+        assert!(gate.can(
+            &Principal::new("ada").with_tenant("acme"),
+            Action::Update,
+            &scoped
+        ));
+        // Even the owner is denied from another tenant
+        assert!(!gate.can(
+            &Principal::new("ada").with_tenant("globex"),
+            Action::Update,
+            &scoped
+        ));
+        assert!(!gate.can(&Principal::new("ada"), Action::Update, &scoped));
+
+        let pro = Principal::new("eve")
+            .with_role("editor")
+            .with_attribute("plan", "pro");
+        let free = Principal::new("eve")
+            .with_role("editor")
+            .with_attribute("plan", "free");
+        assert!(gate.can(&pro, Action::custom("feature"), &post("ada")));
+        assert!(!gate.can(&free, Action::custom("feature"), &post("ada")));
+    }
+
+    #[test]
+    fn test_before_hooks_and_missing_policy() {
+        let gate = gate().super_role("root");
+        let root = Principal::new("r").with_role("root");
+        let scoped = Post {
+            org_id: Some("acme".into()),
+            ..post("ada")
+        };
+
+        assert!(gate.can(&root, Action::Delete, &scoped));
+        // No policy for Comment: denied unless a hook decides
+        assert!(!gate.can(&Principal::new("ada"), Action::View, &Comment));
+        assert!(gate.can(&root, Action::View, &Comment));
+        assert_eq!(
+            gate.authorize(&Principal::new("ada"), Action::View, &Comment),
+            Err(PolicyError::Forbidden("view not allowed".into()))
+        );
+    }
+
+    #[test]
+    fn test_before_hooks_keep_tenant_isolation() {
+        struct LenientPolicy;
+        impl Policy<Post> for LenientPolicy {
+            fn before(&self, _user: &Principal, _action: &Action) -> Option<bool> {
+                Some(true)
+            }
+            fn allows(&self, _user: &Principal, _action: &Action, _post: &Post) -> bool {
+                false
+            }
+        }
+
+        let other_tenant = Post {
+            org_id: Some("globex".into()),
+            ..post("ada")
+        };
+        let same_tenant = Post {
+            org_id: Some("acme".into()),
+            ..post("ada")
+        };
+        let admin = Principal::new("a").with_role("admin").with_tenant("acme");
+
+        let gate = Gate::new()
+            .policy(LenientPolicy)
+            .before(|user, _| user.has_role("admin").then_some(true));
+        assert!(gate.can(&admin, Action::Delete, &same_tenant));
+        assert!(!gate.can(&admin, Action::Delete, &other_tenant));
+        assert!(!gate.can(
+            &Principal::new("b").with_tenant("acme"),
+            Action::View,
+            &other_tenant
+        ));
+
+        // Only an explicit super user crosses tenants
+        let gate = gate.super_role("admin");
+        assert!(gate.can(&admin, Action::Delete, &other_tenant));
+    }
+
+    #[test]
+    fn test_principal_from_fortress_user() {
+        let user = User {
+            id: "1".into(),
+            username: "u".into(),
+            password_hash: String::new(),
+            roles: vec![Role {
+                name: "Moderator".into(),
+                permissions: HashSet::from([Permission::Custom("ban".into())]),
+            }],
+        };
+        let principal = Principal::from(&user);
+        assert!(principal.has_role("Moderator"));
+        assert!(principal.has_permission(&Permission::Custom("ban".into())));
+    }
+
+    fn app() -> Router {
+        async fn update(authz: Authz) -> Result<&'static str, PolicyError> {
+            authz.authorize(Action::Update, &post("ada"))?;
+            Ok("updated")
+        }
+
+        Router::new()
+            .route("/posts/ada", get(update))
+            .route(
+                "/admin",
+                get(|| async { "admin" }).layer(axum::middleware::from_fn(require_role("admin"))),
+            )
+            .route(
+                "/new",
+                get(|| async { "new" }).layer(axum::middleware::from_fn(require_action::<Post>(
+                    Action::Create,
+                ))),
+            )
+            .layer(axum::Extension(gate()))
+    }
+
+    async fn call(uri: &str, principal: Option<Principal>) -> StatusCode {
+        let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        if let Some(principal) = principal {
+            request.extensions_mut().insert(principal);
+        }
+        app().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_axum_guards() {
+        assert_eq!(
+            call("/posts/ada", Some(Principal::new("ada"))).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            call("/posts/ada", Some(Principal::new("bob"))).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(call("/posts/ada", None).await, StatusCode::UNAUTHORIZED);
+
+        let admin = Principal::new("root").with_role("admin");
+        let editor = Principal::new("eve").with_role("editor");
+        assert_eq!(call("/admin", Some(admin)).await, StatusCode::OK);
+        assert_eq!(
+            call("/admin", Some(editor.clone())).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(call("/new", Some(editor)).await, StatusCode::OK);
+        assert_eq!(
+            call("/new", Some(Principal::new("bob"))).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
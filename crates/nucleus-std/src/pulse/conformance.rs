@@ -0,0 +1,414 @@
+//! Conformance checks for `JobStore` implementations
+//!
+//! Every built-in store runs this suite in its tests; custom stores should
+//! too. Each check gets a fresh, empty store from the factory and panics on
+//! the first violated guarantee.
+//!
+//! ```rust,ignore
+//! #[tokio::test]
+//! async fn my_store_conforms() {
+//!     nucleus_std::pulse::conformance::check_store(|| async { MyStore::new() }).await;
+//! }
+//! ```
+
+use super::{Job, JobConfig, JobPriority, JobStatus, JobStore, DEFAULT_QUEUE, LEASE_EXPIRED};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+const LEASE: Duration = Duration::from_secs(30);
+
+/// Run every check, each against a new store from `new_store`
+pub async fn check_store<S, F, Fut>(new_store: F)
+where
+    S: JobStore + 'static,
+    F: Fn() -> Fut,
+    Fut: Future<Output = S>,
+{
+    check_roundtrip(&new_store().await).await;
+    check_claim_order(&new_store().await).await;
+    check_concurrent_claims(Arc::new(new_store().await)).await;
+    check_leases(&new_store().await).await;
+    check_recovery(&new_store().await).await;
+    check_unique(Arc::new(new_store().await)).await;
+    check_dependents(&new_store().await).await;
+}
+
+fn job(name: &str, config: &JobConfig) -> Job {
+    Job::new(name, "{}".to_string(), config)
+}
+
+fn retry_now() -> JobConfig {
+    JobConfig::default().with_backoff(Duration::ZERO, Duration::ZERO)
+}
+
+/// Stores may round timestamps (PostgreSQL keeps microseconds)
+fn same_time(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => (a - b).num_milliseconds().abs() <= 1,
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+fn ids(jobs: &[Job]) -> Vec<&str> {
+    jobs.iter().map(|j| j.id.as_str()).collect()
+}
+
+async fn stored<S: JobStore>(store: &S, id: &str) -> Job {
+    store
+        .get(id)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("job {} missing", id))
+}
+
+async fn check_roundtrip<S: JobStore>(store: &S) {
+    let config = JobConfig::critical()
+        .on_queue("mail")
+        .with_timeout(Duration::from_millis(1500))
+        .with_backoff(Duration::from_millis(250), Duration::from_secs(9))
+        .unique_for(Duration::from_secs(60));
+    let mut job = Job::new(
+        "send",
+        r#"{"to":"ada@example.com","note":"🚀 ünïcode"}"#.into(),
+        &config,
+    );
+    job.scheduled_at = Some(Utc::now() + chrono::Duration::hours(1));
+    job.depends_on = Some("parent".to_string());
+    job.batch_id = Some("batch".to_string());
+    store.save(&job).await.unwrap();
+
+    let got = stored(store, &job.id).await;
+    assert_eq!(got.name, job.name);
+    assert_eq!(got.payload, job.payload);
+    assert_eq!(got.status, JobStatus::Pending);
+    assert_eq!(got.max_retries, 5);
+    assert_eq!(got.priority, JobPriority::Critical);
+    assert_eq!(got.queue, "mail");
+    assert_eq!(got.timeout, job.timeout);
+    assert_eq!(got.backoff, job.backoff);
+    assert_eq!(got.unique_key, job.unique_key);
+    assert_eq!(got.depends_on, job.depends_on);
+    assert_eq!(got.batch_id, job.batch_id);
+    assert!(same_time(Some(got.created_at), Some(job.created_at)));
+    assert!(same_time(got.scheduled_at, job.scheduled_at));
+    assert!(store.get("missing").await.unwrap().is_none());
+
+    job.status = JobStatus::Cancelled;
+    job.last_error = Some("stopped".to_string());
+    store.update(&job).await.unwrap();
+    let got = stored(store, &job.id).await;
+    assert_eq!(got.status, JobStatus::Cancelled);
+    assert_eq!(got.last_error.as_deref(), Some("stopped"));
+
+    store.delete(&job.id).await.unwrap();
+    assert!(store.get(&job.id).await.unwrap().is_none());
+}
+
+async fn check_claim_order<S: JobStore>(store: &S) {
+    let normal = JobConfig::default();
+    let low = job(
+        "low",
+        &JobConfig {
+            priority: JobPriority::Low,
+            ..Default::default()
+        },
+    );
+    let first = job("first", &normal);
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let second = job("second", &normal);
+    let critical = job("critical", &JobConfig::critical());
+    let elsewhere = job("elsewhere", &normal.clone().on_queue("other"));
+    let mut later = job("later", &normal);
+    later.scheduled_at = Some(Utc::now() + chrono::Duration::hours(1));
+    for job in [&low, &first, &second, &critical, &elsewhere, &later] {
+        store.save(job).await.unwrap();
+    }
+
+    let pending = store.get_pending().await.unwrap();
+    assert_eq!(
+        ids(&pending).into_iter().collect::<HashSet<_>>(),
+        HashSet::from([
+            low.id.as_str(),
+            first.id.as_str(),
+            second.id.as_str(),
+            critical.id.as_str(),
+            elsewhere.id.as_str()
+        ]),
+        "get_pending returns ready jobs only"
+    );
+
+    // Highest priority first, then oldest first
+    let claimed = store.claim("a", DEFAULT_QUEUE, 2, LEASE).await.unwrap();
+    assert_eq!(ids(&claimed), [critical.id.as_str(), first.id.as_str()]);
+    for job in &claimed {
+        assert_eq!(job.status, JobStatus::Running);
+        assert_eq!(job.attempts, 1);
+        assert_eq!(job.worker_id.as_deref(), Some("a"));
+        assert!(job.lease_expires_at.is_some_and(|t| t > Utc::now()));
+        assert!(job.started_at.is_some());
+    }
+    let got = stored(store, &critical.id).await;
+    assert_eq!(got.status, JobStatus::Running);
+    assert_eq!(got.worker_id.as_deref(), Some("a"));
+
+    let rest = store.claim("b", DEFAULT_QUEUE, 10, LEASE).await.unwrap();
+    assert_eq!(ids(&rest), [second.id.as_str(), low.id.as_str()]);
+    assert!(store
+        .claim("c", DEFAULT_QUEUE, 10, LEASE)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Queues are separate, and scheduled jobs wait for their time
+    let other = store.claim("c", "other", 10, LEASE).await.unwrap();
+    assert_eq!(ids(&other), [elsewhere.id.as_str()]);
+    assert_eq!(stored(store, &later.id).await.status, JobStatus::Pending);
+}
+
+async fn check_concurrent_claims<S: JobStore + 'static>(store: Arc<S>) {
+    let mut expected = HashSet::new();
+    for _ in 0..40 {
+        let job = job("job", &JobConfig::default());
+        store.save(&job).await.unwrap();
+        expected.insert(job.id);
+    }
+
+    let mut workers = tokio::task::JoinSet::new();
+    for worker in 0..8 {
+        let store = Arc::clone(&store);
+        workers.spawn(async move {
+            let mut claimed = Vec::new();
+            loop {
+                let jobs = store
+                    .claim(&format!("w{}", worker), DEFAULT_QUEUE, 3, LEASE)
+                    .await
+                    .unwrap();
+                if jobs.is_empty() {
+                    return claimed;
+                }
+                claimed.extend(jobs.into_iter().map(|j| j.id));
+            }
+        });
+    }
+
+    let mut seen = HashSet::new();
+    while let Some(claimed) = workers.join_next().await {
+        for id in claimed.unwrap() {
+            assert!(seen.insert(id.clone()), "job {} claimed twice", id);
+        }
+    }
+    assert_eq!(seen, expected);
+}
+
+async fn check_leases<S: JobStore>(store: &S) {
+    let job = job("job", &JobConfig::default());
+    store.save(&job).await.unwrap();
+    let mut claimed = store.claim("a", DEFAULT_QUEUE, 1, LEASE).await.unwrap();
+    let mut job = claimed.remove(0);
+
+    // Only the owner may renew or finish
+    assert!(store.heartbeat(&job.id, "a", LEASE).await.unwrap());
+    assert!(!store.heartbeat(&job.id, "b", LEASE).await.unwrap());
+    assert!(!store.heartbeat("missing", "a", LEASE).await.unwrap());
+
+    job.status = JobStatus::Completed;
+    job.completed_at = Some(Utc::now());
+    assert!(!store.finish(&job, "b").await.unwrap());
+    assert_eq!(stored(store, &job.id).await.status, JobStatus::Running);
+    assert!(store.finish(&job, "a").await.unwrap());
+
+    let got = stored(store, &job.id).await;
+    assert_eq!(got.status, JobStatus::Completed);
+    assert!(got.completed_at.is_some());
+    assert!(got.worker_id.is_none() && got.lease_expires_at.is_none());
+    assert!(!store.heartbeat(&job.id, "a", LEASE).await.unwrap());
+    assert!(!store.finish(&job, "a").await.unwrap());
+
+    // A failed attempt waits out its backoff before it can be claimed again
+    let config = JobConfig {
+        jitter: false,
+        ..JobConfig::default().with_backoff(Duration::from_secs(3600), Duration::from_secs(3600))
+    };
+    let retried = self::job("retried", &config);
+    store.save(&retried).await.unwrap();
+    let mut retried = store
+        .claim("a", DEFAULT_QUEUE, 1, LEASE)
+        .await
+        .unwrap()
+        .remove(0);
+    retried.fail("boom".to_string(), true);
+    assert!(store.finish(&retried, "a").await.unwrap());
+
+    let got = stored(store, &retried.id).await;
+    assert_eq!(got.status, JobStatus::Pending);
+    assert_eq!(got.last_error.as_deref(), Some("boom"));
+    assert!(got
+        .scheduled_at
+        .is_some_and(|t| t > Utc::now() + chrono::Duration::minutes(59)));
+    assert!(store
+        .claim("a", DEFAULT_QUEUE, 1, LEASE)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn check_recovery<S: JobStore>(store: &S) {
+    let retryable = job("retryable", &retry_now());
+    let exhausted = job("exhausted", &JobConfig::no_retry());
+    let healthy = job("healthy", &retry_now().on_queue("other"));
+    for job in [&retryable, &exhausted, &healthy] {
+        store.save(job).await.unwrap();
+    }
+
+    let short = Duration::from_millis(20);
+    assert_eq!(
+        store
+            .claim("crashed", DEFAULT_QUEUE, 10, short)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    store.claim("alive", "other", 1, LEASE).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    let recovered = store.recover_expired().await.unwrap();
+    assert_eq!(
+        ids(&recovered).into_iter().collect::<HashSet<_>>(),
+        HashSet::from([retryable.id.as_str(), exhausted.id.as_str()])
+    );
+    assert!(store.recover_expired().await.unwrap().is_empty());
+
+    let got = stored(store, &retryable.id).await;
+    assert_eq!(got.status, JobStatus::Pending);
+    assert_eq!(got.last_error.as_deref(), Some(LEASE_EXPIRED));
+    assert!(got.worker_id.is_none() && got.lease_expires_at.is_none());
+
+    let got = stored(store, &exhausted.id).await;
+    assert_eq!(got.status, JobStatus::Dead);
+    assert_eq!(
+        ids(&store.get_dead().await.unwrap()),
+        [exhausted.id.as_str()]
+    );
+    assert_eq!(stored(store, &healthy.id).await.status, JobStatus::Running);
+
+    // Claimable again, as a new attempt; the crashed worker has lost it
+    let reclaimed = store.claim("b", DEFAULT_QUEUE, 10, LEASE).await.unwrap();
+    assert_eq!(ids(&reclaimed), [retryable.id.as_str()]);
+    assert_eq!(reclaimed[0].attempts, 2);
+    assert!(!store.finish(&reclaimed[0], "crashed").await.unwrap());
+}
+
+async fn check_unique<S: JobStore + 'static>(store: Arc<S>) {
+    let config = JobConfig::default().unique_for(Duration::from_secs(60));
+    let since = || Utc::now() - chrono::Duration::seconds(60);
+
+    let first = Job::new("sync", "1".to_string(), &config);
+    assert!(store.save_unique(&first, since()).await.unwrap().is_none());
+
+    let duplicate = Job::new("sync", "1".to_string(), &config);
+    let existing = store.save_unique(&duplicate, since()).await.unwrap();
+    assert_eq!(existing.map(|j| j.id), Some(first.id.clone()));
+    assert!(store.get(&duplicate.id).await.unwrap().is_none());
+
+    // Jobs created before the window do not count
+    let after_window = Job::new("sync", "1".to_string(), &config);
+    let since_first = first.created_at + chrono::Duration::milliseconds(1);
+    assert!(store
+        .save_unique(&after_window, since_first)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Neither do dead or cancelled jobs
+    for (id, status) in [
+        (&first.id, JobStatus::Dead),
+        (&after_window.id, JobStatus::Cancelled),
+    ] {
+        let mut job = stored(&*store, id).await;
+        job.status = status;
+        store.update(&job).await.unwrap();
+    }
+    let fresh = Job::new("sync", "1".to_string(), &config);
+    assert!(store.save_unique(&fresh, since()).await.unwrap().is_none());
+
+    // Racing enqueues of one key insert exactly once
+    let mut racers = tokio::task::JoinSet::new();
+    for _ in 0..8 {
+        let store = Arc::clone(&store);
+        let config = config.clone();
+        racers.spawn(async move {
+            let job = Job::new("sync", "2".to_string(), &config);
+            store.save_unique(&job, since()).await.unwrap().is_none()
+        });
+    }
+    let mut inserted = 0;
+    while let Some(result) = racers.join_next().await {
+        inserted += result.unwrap() as usize;
+    }
+    assert_eq!(inserted, 1);
+}
+
+async fn check_dependents<S: JobStore>(store: &S) {
+    let waiting = |parent: &str| {
+        let mut job = job("child", &JobConfig::default());
+        job.status = JobStatus::Waiting;
+        job.depends_on = Some(parent.to_string());
+        job
+    };
+    let (a, b, other) = (waiting("parent"), waiting("parent"), waiting("other"));
+    // Already pending, so not waiting any more
+    let mut started = job("started", &JobConfig::default());
+    started.depends_on = Some("parent".to_string());
+    for job in [&a, &b, &other, &started] {
+        store.save(job).await.unwrap();
+    }
+    let claimed = store.claim("w", DEFAULT_QUEUE, 10, LEASE).await.unwrap();
+    assert_eq!(ids(&claimed), [started.id.as_str()]);
+
+    let released = store.release_dependents("parent", true).await.unwrap();
+    assert_eq!(
+        ids(&released).into_iter().collect::<HashSet<_>>(),
+        HashSet::from([a.id.as_str(), b.id.as_str()])
+    );
+    assert!(released.iter().all(|j| j.status == JobStatus::Pending));
+    assert!(store
+        .release_dependents("parent", true)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store
+            .claim("w", DEFAULT_QUEUE, 10, LEASE)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let cancelled = store.release_dependents("other", false).await.unwrap();
+    assert_eq!(ids(&cancelled), [other.id.as_str()]);
+    let got = stored(store, &other.id).await;
+    assert_eq!(got.status, JobStatus::Cancelled);
+    assert!(got.last_error.is_some_and(|e| e.contains("other")));
+
+    // Batches
+    let member = |batch: &str| {
+        let mut job = job("member", &JobConfig::default());
+        job.batch_id = Some(batch.to_string());
+        job
+    };
+    let (x, y, z) = (member("b1"), member("b1"), member("b2"));
+    for job in [&x, &y, &z] {
+        store.save(job).await.unwrap();
+    }
+    let batch = store.get_batch("b1").await.unwrap();
+    assert_eq!(
+        ids(&batch).into_iter().collect::<HashSet<_>>(),
+        HashSet::from([x.id.as_str(), y.id.as_str()])
+    );
+    assert!(store.get_batch("none").await.unwrap().is_empty());
+}
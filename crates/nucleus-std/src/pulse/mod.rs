@@ -0,0 +1,3107 @@
+//! Nucleus Pulse - Enhanced Job Queue
+//!
+//! Production-ready background job processing with:
+//! - SQLite, PostgreSQL or Redis persistence (jobs survive restarts)
+//! - Automatic retries with exponential backoff and jitter
+//! - Execution timeouts per job or per handler
+//! - Unique jobs (deduplicated on name + payload within a window)
+//! - Workflows: jobs that run after another job, and batch callbacks
+//! - Typed handlers that receive the deserialized payload
+//! - Priority queues (Critical > High > Normal > Low)
+//! - Dead letter queue for failed jobs
+//! - Scheduled jobs (run at specific time)
+//! - Safe with many workers: jobs are claimed atomically under a lease,
+//!   long jobs renew it, and jobs of crashed workers are recovered
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::pulse::{Pulse, JobConfig, JobPriority};
+//!
+//! let pulse = Pulse::new("jobs.db").await?
+//!     .with_workers(8)
+//!     .with_queue("emails", 2); // at most 2 email jobs at a time
+//!
+//! // Simple job
+//! pulse.enqueue("send_email", json!({ "to": "user@example.com" })).await?;
+//!
+//! // Priority job with retries
+//! pulse.enqueue_with_config("process_payment", payment, JobConfig {
+//!     max_retries: 5,
+//!     priority: JobPriority::Critical,
+//!     ..Default::default()
+//! }).await?;
+//!
+//! // Register a typed handler
+//! pulse.handle_typed("send_email", |email: EmailPayload| async move {
+//!     Ok(())
+//! }).await;
+//!
+//! // Run `send_receipt` once `charge` has succeeded
+//! let charge = pulse.enqueue("charge", order).await?;
+//! pulse.enqueue_after(&charge, "send_receipt", order, JobConfig::default()).await?;
+//!
+//! // Start processing
+//! pulse.run().await?;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+pub mod conformance;
+mod postgres_store;
+mod redis_store;
+
+pub use postgres_store::PostgresJobStore;
+pub use redis_store::RedisJobStore;
+
+/// Queue used when a job does not name one
+pub const DEFAULT_QUEUE: &str = "default";
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TYPES & ENUMS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Job execution status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobStatus {
+    /// Waiting to be processed
+    Pending,
+    /// Waiting for a parent job or a batch to finish
+    Waiting,
+    /// Currently being executed
+    Running,
+    /// Successfully completed
+    Completed,
+    /// Failed but will retry
+    Failed { error: String, attempts: u32 },
+    /// Exceeded max retries, moved to dead letter queue
+    Dead,
+    /// Cancelled by user
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Dead | JobStatus::Cancelled
+        )
+    }
+}
+
+/// Job priority levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub enum JobPriority {
+    Low = 0,
+    #[default]
+    Normal = 1,
+    High = 2,
+    Critical = 3,
+}
+
+impl JobPriority {
+    fn from_i32(value: i32) -> Self {
+        match value {
+            0 => JobPriority::Low,
+            2 => JobPriority::High,
+            3 => JobPriority::Critical,
+            _ => JobPriority::Normal,
+        }
+    }
+}
+
+/// Delay before retrying a failed job
+///
+/// The delay doubles with every attempt, starting at `base` and capped at
+/// `max`. With `jitter`, a random half of it is dropped so that jobs that
+/// failed together do not retry together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+    pub jitter: bool,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(5),
+            max: Duration::from_secs(3600),
+            jitter: true,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay after the given (1-based) failed attempt
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base.saturating_mul(factor).min(self.max);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+}
+
+/// Job configuration options
+#[derive(Debug, Clone)]
+pub struct JobConfig {
+    /// Maximum retry attempts (default: 3)
+    pub max_retries: u32,
+    /// Base delay between retries (exponential backoff applied)
+    pub retry_delay: Duration,
+    /// Upper bound for the retry delay (default: 1 hour)
+    pub max_retry_delay: Duration,
+    /// Randomize retry delays (default: true)
+    pub jitter: bool,
+    /// Maximum execution time before timeout
+    pub timeout: Duration,
+    /// Job priority
+    pub priority: JobPriority,
+    /// Queue the job is placed on (default: "default")
+    pub queue: String,
+    /// Drop duplicates (same name and payload) enqueued within this window
+    pub unique_for: Option<Duration>,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        let backoff = Backoff::default();
+        Self {
+            max_retries: 3,
+            retry_delay: backoff.base,
+            max_retry_delay: backoff.max,
+            jitter: backoff.jitter,
+            timeout: Duration::from_secs(300), // 5 minutes
+            priority: JobPriority::Normal,
+            queue: DEFAULT_QUEUE.to_string(),
+            unique_for: None,
+        }
+    }
+}
+
+impl JobConfig {
+    pub fn critical() -> Self {
+        Self {
+            max_retries: 5,
+            priority: JobPriority::Critical,
+            ..Default::default()
+        }
+    }
+
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Same config on another queue
+    pub fn on_queue(mut self, queue: &str) -> Self {
+        self.queue = queue.to_string();
+        self
+    }
+
+    /// Retry after `base`, doubling up to `max`
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.retry_delay = base;
+        self.max_retry_delay = max;
+        self
+    }
+
+    /// Fail an attempt that runs longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Skip enqueueing when the same job was enqueued within `window`
+    ///
+    /// Jobs that were cancelled or went to the dead letter queue do not count.
+    pub fn unique_for(mut self, window: Duration) -> Self {
+        self.unique_for = Some(window);
+        self
+    }
+
+    fn backoff(&self) -> Backoff {
+        Backoff {
+            base: self.retry_delay,
+            max: self.max_retry_delay,
+            jitter: self.jitter,
+        }
+    }
+}
+
+fn default_queue() -> String {
+    DEFAULT_QUEUE.to_string()
+}
+
+fn default_timeout() -> Duration {
+    JobConfig::default().timeout
+}
+
+/// A job in the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// Unique job identifier
+    pub id: String,
+    /// Job type/name (maps to handler)
+    pub name: String,
+    /// JSON payload
+    pub payload: String,
+    /// Current status
+    pub status: JobStatus,
+    /// Number of execution attempts
+    pub attempts: u32,
+    /// Maximum retry attempts
+    pub max_retries: u32,
+    /// Priority level
+    pub priority: JobPriority,
+    /// When the job was created
+    pub created_at: DateTime<Utc>,
+    /// When the job should run (for scheduled jobs)
+    pub scheduled_at: Option<DateTime<Utc>>,
+    /// When execution started
+    pub started_at: Option<DateTime<Utc>>,
+    /// When execution completed
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Last error message
+    pub last_error: Option<String>,
+    /// Queue the job belongs to
+    #[serde(default = "default_queue")]
+    pub queue: String,
+    /// Worker that claimed the job while it is running
+    #[serde(default)]
+    pub worker_id: Option<String>,
+    /// Until when the claim is valid; expired claims are recovered
+    #[serde(default)]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    /// Maximum execution time of one attempt
+    #[serde(default = "default_timeout")]
+    pub timeout: Duration,
+    /// Delay before each retry
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// Hash of name and payload, set for unique jobs
+    #[serde(default)]
+    pub unique_key: Option<String>,
+    /// Job or batch that must finish before this job runs
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    /// Batch the job belongs to
+    #[serde(default)]
+    pub batch_id: Option<String>,
+}
+
+impl Job {
+    fn new(name: &str, payload: String, config: &JobConfig) -> Self {
+        let unique_key = config
+            .unique_for
+            .map(|_| hex::encode(Sha256::digest(format!("{}\0{}", name, payload))));
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            payload,
+            status: JobStatus::Pending,
+            attempts: 0,
+            max_retries: config.max_retries,
+            priority: config.priority,
+            created_at: Utc::now(),
+            scheduled_at: None,
+            started_at: None,
+            completed_at: None,
+            last_error: None,
+            queue: config.queue.clone(),
+            worker_id: None,
+            lease_expires_at: None,
+            timeout: config.timeout,
+            backoff: config.backoff(),
+            unique_key,
+            depends_on: None,
+            batch_id: None,
+        }
+    }
+
+    fn scheduled(name: &str, payload: String, run_at: DateTime<Utc>, config: &JobConfig) -> Self {
+        let mut job = Self::new(name, payload, config);
+        job.scheduled_at = Some(run_at);
+        job
+    }
+
+    fn is_ready(&self, queue: &str, now: DateTime<Utc>) -> bool {
+        matches!(self.status, JobStatus::Pending)
+            && self.queue == queue
+            && self.scheduled_at.is_none_or(|t| t <= now)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.status, JobStatus::Running) && self.lease_expires_at.is_some_and(|t| t < now)
+    }
+
+    /// Back to pending after the backoff delay, or to the dead letter queue
+    /// when out of attempts or the failure is not worth retrying
+    fn fail(&mut self, error: String, retryable: bool) {
+        if retryable && self.attempts < self.max_retries {
+            self.status = JobStatus::Pending;
+            self.scheduled_at = Some(from_now(self.backoff.delay(self.attempts)));
+        } else {
+            self.status = JobStatus::Dead;
+        }
+        self.last_error = Some(error);
+        self.worker_id = None;
+        self.lease_expires_at = None;
+    }
+}
+
+fn from_now(delay: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Highest priority first, then oldest first
+fn claim_order(a: &Job, b: &Job) -> std::cmp::Ordering {
+    b.priority
+        .cmp(&a.priority)
+        .then_with(|| a.created_at.cmp(&b.created_at))
+}
+
+const LEASE_EXPIRED: &str = "Lease expired: worker stopped responding";
+
+/// Whether a live job blocks a duplicate of itself
+fn blocks_duplicates(job: &Job, since: DateTime<Utc>) -> bool {
+    job.created_at >= since && !matches!(job.status, JobStatus::Dead | JobStatus::Cancelled)
+}
+
+/// Error stored on a dependent job whose parent did not complete
+fn dependency_failed(parent_id: &str) -> String {
+    format!("Dependency {} did not complete", parent_id)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ERRORS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Pulse error types
+#[derive(Debug, thiserror::Error)]
+pub enum PulseError {
+    #[error("Database error: {0}")]
+    Database(String),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Job not found: {0}")]
+    NotFound(String),
+
+    #[error("No handler registered for job: {0}")]
+    NoHandler(String),
+
+    #[error("Job execution failed: {0}")]
+    ExecutionFailed(String),
+
+    #[error("Queue closed")]
+    QueueClosed,
+
+    #[error("Job cancelled")]
+    Cancelled,
+
+    #[error("Job timeout")]
+    Timeout,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// STORAGE BACKENDS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Job storage backend trait
+///
+/// `claim`, `heartbeat`, `finish` and `recover_expired` must be atomic with
+/// respect to other workers using the same storage: a pending job is handed
+/// to exactly one `claim` call, and only the worker holding the lease may
+/// finish it.
+#[async_trait::async_trait]
+pub trait JobStore: Send + Sync {
+    async fn save(&self, job: &Job) -> Result<(), PulseError>;
+    async fn get(&self, id: &str) -> Result<Option<Job>, PulseError>;
+    async fn update(&self, job: &Job) -> Result<(), PulseError>;
+    async fn delete(&self, id: &str) -> Result<(), PulseError>;
+    async fn get_pending(&self) -> Result<Vec<Job>, PulseError>;
+    async fn get_dead(&self) -> Result<Vec<Job>, PulseError>;
+    async fn get_scheduled_ready(&self) -> Result<Vec<Job>, PulseError>;
+
+    /// Save `job` unless a job with the same `unique_key` created at or after
+    /// `since` is still live; returns that job instead
+    async fn save_unique(&self, job: &Job, since: DateTime<Utc>)
+        -> Result<Option<Job>, PulseError>;
+
+    /// Move jobs waiting on `parent_id` (a job or batch) to `Pending`, or
+    /// cancel them when `proceed` is false; returns the updated jobs
+    async fn release_dependents(
+        &self,
+        parent_id: &str,
+        proceed: bool,
+    ) -> Result<Vec<Job>, PulseError>;
+
+    /// All jobs of a batch
+    async fn get_batch(&self, batch_id: &str) -> Result<Vec<Job>, PulseError>;
+
+    /// Atomically move up to `limit` ready jobs of `queue` to `Running`,
+    /// owned by `worker_id` until `now + lease`; increments `attempts`
+    async fn claim(
+        &self,
+        worker_id: &str,
+        queue: &str,
+        limit: usize,
+        lease: Duration,
+    ) -> Result<Vec<Job>, PulseError>;
+
+    /// Extend the lease of a running job; false if `worker_id` lost it
+    async fn heartbeat(
+        &self,
+        id: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, PulseError>;
+
+    /// Store the outcome of a claimed job and release the lease; false (and
+    /// nothing written) if `worker_id` no longer holds it
+    async fn finish(&self, job: &Job, worker_id: &str) -> Result<bool, PulseError>;
+
+    /// Return running jobs with an expired lease to the queue (or to the dead
+    /// letter queue when out of attempts); returns the recovered jobs
+    async fn recover_expired(&self) -> Result<Vec<Job>, PulseError>;
+
+    /// Wait until new jobs may be ready, or at most `timeout`
+    ///
+    /// Idle workers call this between polls. Stores that can push
+    /// notifications return early to pick up new jobs right away.
+    async fn wait_for_jobs(&self, timeout: Duration) {
+        tokio::time::sleep(timeout).await;
+    }
+}
+
+/// In-memory job store (for testing); clones share the same jobs
+#[derive(Clone)]
+pub struct MemoryJobStore {
+    jobs: Arc<RwLock<HashMap<String, Job>>>,
+}
+
+impl MemoryJobStore {
+    pub fn new() -> Self {
+        Self {
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for MemoryJobStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl JobStore for MemoryJobStore {
+    async fn save(&self, job: &Job) -> Result<(), PulseError> {
+        let mut jobs = self.jobs.write().await;
+        jobs.insert(job.id.clone(), job.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Job>, PulseError> {
+        let jobs = self.jobs.read().await;
+        Ok(jobs.get(id).cloned())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), PulseError> {
+        let mut jobs = self.jobs.write().await;
+        jobs.insert(job.id.clone(), job.clone());
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), PulseError> {
+        let mut jobs = self.jobs.write().await;
+        jobs.remove(id);
+        Ok(())
+    }
+
+    async fn get_pending(&self) -> Result<Vec<Job>, PulseError> {
+        let jobs = self.jobs.read().await;
+        let now = Utc::now();
+        let mut pending: Vec<_> = jobs
+            .values()
+            .filter(|j| {
+                matches!(j.status, JobStatus::Pending) && j.scheduled_at.is_none_or(|t| t <= now)
+            })
+            .cloned()
+            .collect();
+
+        pending.sort_by(claim_order);
+
+        Ok(pending)
+    }
+
+    async fn get_dead(&self) -> Result<Vec<Job>, PulseError> {
+        let jobs = self.jobs.read().await;
+        Ok(jobs
+            .values()
+            .filter(|j| matches!(j.status, JobStatus::Dead))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_scheduled_ready(&self) -> Result<Vec<Job>, PulseError> {
+        let jobs = self.jobs.read().await;
+        let now = Utc::now();
+        Ok(jobs
+            .values()
+            .filter(|j| {
+                matches!(j.status, JobStatus::Pending) && j.scheduled_at.is_some_and(|t| t <= now)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn save_unique(
+        &self,
+        job: &Job,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Job>, PulseError> {
+        let mut jobs = self.jobs.write().await;
+        let existing = jobs
+            .values()
+            .filter(|j| j.unique_key.is_some() && j.unique_key == job.unique_key)
+            .filter(|j| blocks_duplicates(j, since))
+            .max_by_key(|j| j.created_at);
+        if let Some(existing) = existing {
+            return Ok(Some(existing.clone()));
+        }
+        jobs.insert(job.id.clone(), job.clone());
+        Ok(None)
+    }
+
+    async fn release_dependents(
+        &self,
+        parent_id: &str,
+        proceed: bool,
+    ) -> Result<Vec<Job>, PulseError> {
+        let mut jobs = self.jobs.write().await;
+        Ok(jobs
+            .values_mut()
+            .filter(|j| {
+                matches!(j.status, JobStatus::Waiting) && j.depends_on.as_deref() == Some(parent_id)
+            })
+            .map(|job| {
+                if proceed {
+                    job.status = JobStatus::Pending;
+                } else {
+                    job.status = JobStatus::Cancelled;
+                    job.last_error = Some(dependency_failed(parent_id));
+                }
+                job.clone()
+            })
+            .collect())
+    }
+
+    async fn get_batch(&self, batch_id: &str) -> Result<Vec<Job>, PulseError> {
+        let jobs = self.jobs.read().await;
+        Ok(jobs
+            .values()
+            .filter(|j| j.batch_id.as_deref() == Some(batch_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn claim(
+        &self,
+        worker_id: &str,
+        queue: &str,
+        limit: usize,
+        lease: Duration,
+    ) -> Result<Vec<Job>, PulseError> {
+        // The write lock makes select + update one atomic step
+        let mut jobs = self.jobs.write().await;
+        let now = Utc::now();
+        let mut ready: Vec<&mut Job> = jobs
+            .values_mut()
+            .filter(|j| j.is_ready(queue, now))
+            .collect();
+        ready.sort_by(|a, b| claim_order(a, b));
+
+        Ok(ready
+            .into_iter()
+            .take(limit)
+            .map(|job| {
+                job.status = JobStatus::Running;
+                job.attempts += 1;
+                job.started_at = Some(now);
+                job.worker_id = Some(worker_id.to_string());
+                job.lease_expires_at = Some(from_now(lease));
+                job.clone()
+            })
+            .collect())
+    }
+
+    async fn heartbeat(
+        &self,
+        id: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, PulseError> {
+        let mut jobs = self.jobs.write().await;
+        match jobs.get_mut(id) {
+            Some(job)
+                if matches!(job.status, JobStatus::Running)
+                    && job.worker_id.as_deref() == Some(worker_id) =>
+            {
+                job.lease_expires_at = Some(from_now(lease));
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn finish(&self, job: &Job, worker_id: &str) -> Result<bool, PulseError> {
+        let mut jobs = self.jobs.write().await;
+        match jobs.get_mut(&job.id) {
+            Some(stored) if stored.worker_id.as_deref() == Some(worker_id) => {
+                *stored = Job {
+                    worker_id: None,
+                    lease_expires_at: None,
+                    ..job.clone()
+                };
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn recover_expired(&self) -> Result<Vec<Job>, PulseError> {
+        let mut jobs = self.jobs.write().await;
+        let now = Utc::now();
+        Ok(jobs
+            .values_mut()
+            .filter(|j| j.is_expired(now))
+            .map(|job| {
+                job.fail(LEASE_EXPIRED.to_string(), true);
+                job.clone()
+            })
+            .collect())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: String,
+    name: String,
+    payload: String,
+    status: String,
+    attempts: i32,
+    max_retries: i32,
+    priority: i32,
+    created_at: String,
+    scheduled_at: Option<String>,
+    started_at: Option<String>,
+    completed_at: Option<String>,
+    last_error: Option<String>,
+    queue: String,
+    worker_id: Option<String>,
+    lease_expires_at: Option<String>,
+    timeout_ms: i64,
+    backoff: Option<String>,
+    unique_key: Option<String>,
+    depends_on: Option<String>,
+    batch_id: Option<String>,
+}
+
+const JOB_COLUMNS: &str =
+    "id, name, payload, status, attempts, max_retries, priority, created_at, \
+     scheduled_at, started_at, completed_at, last_error, queue, worker_id, lease_expires_at, \
+     timeout_ms, backoff, unique_key, depends_on, batch_id";
+
+/// `?, ?, ...` with one placeholder per column in `JOB_COLUMNS`
+fn job_placeholders() -> String {
+    vec!["?"; JOB_COLUMNS.split(',').count()].join(", ")
+}
+
+fn parse_time(value: Option<String>) -> Option<DateTime<Utc>> {
+    value.and_then(|s| {
+        DateTime::parse_from_rfc3339(&s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    })
+}
+
+fn job_from_row(row: JobRow) -> Result<Job, PulseError> {
+    Ok(Job {
+        id: row.id,
+        name: row.name,
+        payload: row.payload,
+        status: serde_json::from_str(&row.status)?,
+        attempts: row.attempts as u32,
+        max_retries: row.max_retries as u32,
+        priority: JobPriority::from_i32(row.priority),
+        created_at: parse_time(Some(row.created_at)).unwrap_or_else(Utc::now),
+        scheduled_at: parse_time(row.scheduled_at),
+        started_at: parse_time(row.started_at),
+        completed_at: parse_time(row.completed_at),
+        last_error: row.last_error,
+        queue: row.queue,
+        worker_id: row.worker_id,
+        lease_expires_at: parse_time(row.lease_expires_at),
+        timeout: Duration::from_millis(row.timeout_ms.max(0) as u64),
+        backoff: match row.backoff {
+            Some(backoff) => serde_json::from_str(&backoff)?,
+            None => Backoff::default(),
+        },
+        unique_key: row.unique_key,
+        depends_on: row.depends_on,
+        batch_id: row.batch_id,
+    })
+}
+
+type SqliteQuery<'q> = sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>;
+
+/// Bind every column of `job`, in `JOB_COLUMNS` order
+fn bind_job<'q>(query: SqliteQuery<'q>, job: &'q Job) -> Result<SqliteQuery<'q>, PulseError> {
+    Ok(query
+        .bind(&job.id)
+        .bind(&job.name)
+        .bind(&job.payload)
+        .bind(serde_json::to_string(&job.status)?)
+        .bind(job.attempts as i32)
+        .bind(job.max_retries as i32)
+        .bind(job.priority as i32)
+        .bind(job.created_at.to_rfc3339())
+        .bind(job.scheduled_at.map(|t| t.to_rfc3339()))
+        .bind(job.started_at.map(|t| t.to_rfc3339()))
+        .bind(job.completed_at.map(|t| t.to_rfc3339()))
+        .bind(&job.last_error)
+        .bind(&job.queue)
+        .bind(&job.worker_id)
+        .bind(job.lease_expires_at.map(|t| t.to_rfc3339()))
+        .bind(job.timeout.as_millis().min(i64::MAX as u128) as i64)
+        .bind(serde_json::to_string(&job.backoff)?)
+        .bind(&job.unique_key)
+        .bind(&job.depends_on)
+        .bind(&job.batch_id))
+}
+
+/// SQLite job store (for persistence)
+///
+/// Several processes may share one database file; jobs are claimed with a
+/// single `UPDATE ... RETURNING` statement.
+pub struct SqliteJobStore {
+    pool: sqlx::SqlitePool,
+}
+
+impl SqliteJobStore {
+    pub async fn new(path: &str) -> Result<Self, PulseError> {
+        let (url, max_connections) = if path == ":memory:" {
+            // Every connection would otherwise get its own empty database
+            ("sqlite::memory:".to_string(), 1)
+        } else {
+            (format!("sqlite:{}?mode=rwc", path), 10)
+        };
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect(&url)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        // Create tables
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS jobs (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                max_retries INTEGER NOT NULL DEFAULT 3,
+                priority INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                scheduled_at TEXT,
+                started_at TEXT,
+                completed_at TEXT,
+                last_error TEXT,
+                queue TEXT NOT NULL DEFAULT 'default',
+                worker_id TEXT,
+                lease_expires_at TEXT,
+                timeout_ms INTEGER NOT NULL DEFAULT 300000,
+                backoff TEXT,
+                unique_key TEXT,
+                depends_on TEXT,
+                batch_id TEXT
+            )
+        "#,
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        // Databases created by older versions
+        let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('jobs')")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        for (column, definition) in [
+            ("queue", "TEXT NOT NULL DEFAULT 'default'"),
+            ("worker_id", "TEXT"),
+            ("lease_expires_at", "TEXT"),
+            ("timeout_ms", "INTEGER NOT NULL DEFAULT 300000"),
+            ("backoff", "TEXT"),
+            ("unique_key", "TEXT"),
+            ("depends_on", "TEXT"),
+            ("batch_id", "TEXT"),
+        ] {
+            if !columns.iter().any(|(name,)| name == column) {
+                sqlx::query(&format!(
+                    "ALTER TABLE jobs ADD COLUMN {} {}",
+                    column, definition
+                ))
+                .execute(&pool)
+                .await
+                .map_err(|e| PulseError::Database(e.to_string()))?;
+            }
+        }
+
+        // Create indexes for efficient queries
+        for index in [
+            "CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status)",
+            "CREATE INDEX IF NOT EXISTS idx_jobs_claim ON jobs(queue, status, priority, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_jobs_unique ON jobs(unique_key)",
+            "CREATE INDEX IF NOT EXISTS idx_jobs_depends_on ON jobs(depends_on)",
+            "CREATE INDEX IF NOT EXISTS idx_jobs_batch ON jobs(batch_id)",
+        ] {
+            sqlx::query(index)
+                .execute(&pool)
+                .await
+                .map_err(|e| PulseError::Database(e.to_string()))?;
+        }
+
+        Ok(Self { pool })
+    }
+
+    async fn fetch_jobs(&self, sql: &str, binds: &[String]) -> Result<Vec<Job>, PulseError> {
+        let mut query = sqlx::query_as::<_, JobRow>(sql);
+        for value in binds {
+            query = query.bind(value);
+        }
+        query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?
+            .into_iter()
+            .map(job_from_row)
+            .collect()
+    }
+}
+
+/// Statuses that no longer block a unique job
+const NOT_LIVE: &str = r#"('"Dead"', '"Cancelled"')"#;
+
+#[async_trait::async_trait]
+impl JobStore for SqliteJobStore {
+    async fn save(&self, job: &Job) -> Result<(), PulseError> {
+        let sql = format!(
+            "INSERT INTO jobs ({}) VALUES ({})",
+            JOB_COLUMNS,
+            job_placeholders()
+        );
+        bind_job(sqlx::query(&sql), job)?
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Job>, PulseError> {
+        let sql = format!("SELECT {} FROM jobs WHERE id = ?", JOB_COLUMNS);
+        Ok(self
+            .fetch_jobs(&sql, &[id.to_string()])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), PulseError> {
+        let status = serde_json::to_string(&job.status)?;
+
+        sqlx::query(
+            r#"
+            UPDATE jobs SET
+                status = ?, attempts = ?, scheduled_at = ?, started_at = ?, completed_at = ?,
+                last_error = ?, worker_id = ?, lease_expires_at = ?
+            WHERE id = ?
+        "#,
+        )
+        .bind(&status)
+        .bind(job.attempts as i32)
+        .bind(job.scheduled_at.map(|t| t.to_rfc3339()))
+        .bind(job.started_at.map(|t| t.to_rfc3339()))
+        .bind(job.completed_at.map(|t| t.to_rfc3339()))
+        .bind(&job.last_error)
+        .bind(&job.worker_id)
+        .bind(job.lease_expires_at.map(|t| t.to_rfc3339()))
+        .bind(&job.id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), PulseError> {
+        sqlx::query("DELETE FROM jobs WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_pending(&self) -> Result<Vec<Job>, PulseError> {
+        let sql = format!(
+            r#"
+                SELECT {}
+                FROM jobs
+                WHERE status = '"Pending"' AND (scheduled_at IS NULL OR scheduled_at <= ?)
+                ORDER BY priority DESC, created_at ASC
+            "#,
+            JOB_COLUMNS
+        );
+        self.fetch_jobs(&sql, &[Utc::now().to_rfc3339()]).await
+    }
+
+    async fn get_dead(&self) -> Result<Vec<Job>, PulseError> {
+        let sql = format!(
+            r#"SELECT {} FROM jobs WHERE status = '"Dead"'"#,
+            JOB_COLUMNS
+        );
+        self.fetch_jobs(&sql, &[]).await
+    }
+
+    async fn get_scheduled_ready(&self) -> Result<Vec<Job>, PulseError> {
+        self.get_pending().await
+    }
+
+    async fn save_unique(
+        &self,
+        job: &Job,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Job>, PulseError> {
+        let Some(key) = &job.unique_key else {
+            self.save(job).await?;
+            return Ok(None);
+        };
+
+        // Check and insert in one statement
+        let live = format!(
+            "unique_key = ? AND created_at >= ? AND status NOT IN {}",
+            NOT_LIVE
+        );
+        let sql = format!(
+            "INSERT INTO jobs ({}) SELECT {} WHERE NOT EXISTS (SELECT 1 FROM jobs WHERE {})",
+            JOB_COLUMNS,
+            job_placeholders(),
+            live
+        );
+        let inserted = bind_job(sqlx::query(&sql), job)?
+            .bind(key)
+            .bind(since.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?
+            .rows_affected()
+            == 1;
+        if inserted {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "SELECT {} FROM jobs WHERE {} ORDER BY created_at DESC LIMIT 1",
+            JOB_COLUMNS, live
+        );
+        Ok(self
+            .fetch_jobs(&sql, &[key.clone(), since.to_rfc3339()])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn release_dependents(
+        &self,
+        parent_id: &str,
+        proceed: bool,
+    ) -> Result<Vec<Job>, PulseError> {
+        let (status, error) = if proceed {
+            (JobStatus::Pending, None)
+        } else {
+            (JobStatus::Cancelled, Some(dependency_failed(parent_id)))
+        };
+        let sql = format!(
+            r#"
+                UPDATE jobs SET status = ?, last_error = COALESCE(?, last_error)
+                WHERE depends_on = ? AND status = '"Waiting"'
+                RETURNING {}
+            "#,
+            JOB_COLUMNS
+        );
+        sqlx::query_as::<_, JobRow>(&sql)
+            .bind(serde_json::to_string(&status)?)
+            .bind(error)
+            .bind(parent_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?
+            .into_iter()
+            .map(job_from_row)
+            .collect()
+    }
+
+    async fn get_batch(&self, batch_id: &str) -> Result<Vec<Job>, PulseError> {
+        let sql = format!("SELECT {} FROM jobs WHERE batch_id = ?", JOB_COLUMNS);
+        self.fetch_jobs(&sql, &[batch_id.to_string()]).await
+    }
+
+    async fn claim(
+        &self,
+        worker_id: &str,
+        queue: &str,
+        limit: usize,
+        lease: Duration,
+    ) -> Result<Vec<Job>, PulseError> {
+        let now = Utc::now();
+        // A single statement, so two workers can never select the same row
+        let sql = format!(
+            r#"
+                UPDATE jobs SET
+                    status = '"Running"', attempts = attempts + 1, started_at = ?,
+                    worker_id = ?, lease_expires_at = ?
+                WHERE id IN (
+                    SELECT id FROM jobs
+                    WHERE status = '"Pending"' AND queue = ?
+                        AND (scheduled_at IS NULL OR scheduled_at <= ?)
+                    ORDER BY priority DESC, created_at ASC
+                    LIMIT ?
+                )
+                RETURNING {}
+            "#,
+            JOB_COLUMNS
+        );
+        let rows: Vec<JobRow> = sqlx::query_as(&sql)
+            .bind(now.to_rfc3339())
+            .bind(worker_id)
+            .bind(from_now(lease).to_rfc3339())
+            .bind(queue)
+            .bind(now.to_rfc3339())
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        let mut jobs = rows
+            .into_iter()
+            .map(job_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        jobs.sort_by(claim_order);
+        Ok(jobs)
+    }
+
+    async fn heartbeat(
+        &self,
+        id: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, PulseError> {
+        let result = sqlx::query(
+            r#"UPDATE jobs SET lease_expires_at = ? WHERE id = ? AND worker_id = ? AND status = '"Running"'"#,
+        )
+        .bind(from_now(lease).to_rfc3339())
+        .bind(id)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn finish(&self, job: &Job, worker_id: &str) -> Result<bool, PulseError> {
+        let status = serde_json::to_string(&job.status)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs SET
+                status = ?, attempts = ?, scheduled_at = ?, started_at = ?, completed_at = ?,
+                last_error = ?, worker_id = NULL, lease_expires_at = NULL
+            WHERE id = ? AND worker_id = ?
+        "#,
+        )
+        .bind(&status)
+        .bind(job.attempts as i32)
+        .bind(job.scheduled_at.map(|t| t.to_rfc3339()))
+        .bind(job.started_at.map(|t| t.to_rfc3339()))
+        .bind(job.completed_at.map(|t| t.to_rfc3339()))
+        .bind(&job.last_error)
+        .bind(&job.id)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn recover_expired(&self) -> Result<Vec<Job>, PulseError> {
+        let now = Utc::now().to_rfc3339();
+        let expired_sql =
+            r#"status = '"Running"' AND lease_expires_at IS NOT NULL AND lease_expires_at < ?"#;
+        let sql = format!("SELECT {} FROM jobs WHERE {}", JOB_COLUMNS, expired_sql);
+
+        let mut recovered = Vec::new();
+        for mut job in self.fetch_jobs(&sql, std::slice::from_ref(&now)).await? {
+            // The backoff delay is computed here, so each row is updated on
+            // its own; one renewed or finished meanwhile no longer matches
+            job.fail(LEASE_EXPIRED.to_string(), true);
+            let result = sqlx::query(&format!(
+                r#"
+                UPDATE jobs SET
+                    status = ?, scheduled_at = ?, last_error = ?,
+                    worker_id = NULL, lease_expires_at = NULL
+                WHERE id = ? AND {}
+            "#,
+                expired_sql
+            ))
+            .bind(serde_json::to_string(&job.status)?)
+            .bind(job.scheduled_at.map(|t| t.to_rfc3339()))
+            .bind(&job.last_error)
+            .bind(&job.id)
+            .bind(&now)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+            if result.rows_affected() == 1 {
+                recovered.push(job);
+            }
+        }
+        Ok(recovered)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PULSE (MAIN STRUCT)
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Why a handler run failed, and whether another attempt could succeed
+struct Failure {
+    error: String,
+    retryable: bool,
+}
+
+impl Failure {
+    fn retry(error: String) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+
+    fn fatal(error: String) -> Self {
+        Self {
+            error,
+            retryable: false,
+        }
+    }
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<(), Failure>> + Send>>;
+type BoxedHandler = Arc<dyn Fn(String) -> HandlerFuture + Send + Sync>;
+
+/// Enhanced job queue with persistence and retries
+///
+/// Any number of `Pulse` workers (threads or processes) may share a store.
+/// Each claims jobs under a lease that it renews while the handler runs; if a
+/// worker dies, its jobs are picked up again once the lease expires.
+pub struct Pulse<S: JobStore = MemoryJobStore> {
+    store: Arc<S>,
+    handlers: Arc<RwLock<HashMap<String, BoxedHandler>>>,
+    /// Per-handler timeouts, overriding the job's own
+    timeouts: HashMap<String, Duration>,
+    workers: usize,
+    /// Queues this worker serves, with their concurrency limit
+    queues: Vec<(String, usize)>,
+    worker_id: String,
+    lease: Duration,
+    poll_interval: Duration,
+    running: Arc<RwLock<bool>>,
+    default_config: JobConfig,
+}
+
+impl Pulse<MemoryJobStore> {
+    /// Create an in-memory queue (for testing)
+    pub fn in_memory() -> Self {
+        Self::with_store(MemoryJobStore::new())
+    }
+}
+
+impl Pulse<SqliteJobStore> {
+    /// Create with SQLite persistence
+    pub async fn new(db_path: &str) -> Result<Self, PulseError> {
+        let store = SqliteJobStore::new(db_path).await?;
+        Ok(Self::with_store(store))
+    }
+}
+
+impl<S: JobStore + 'static> Pulse<S> {
+    /// Create on any job store
+    ///
+    /// Defaults: one worker per CPU core, the `default` queue, 30 second leases.
+    pub fn with_store(store: S) -> Self {
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        Self {
+            store: Arc::new(store),
+            handlers: Arc::new(RwLock::new(HashMap::new())),
+            timeouts: HashMap::new(),
+            workers,
+            queues: vec![(DEFAULT_QUEUE.to_string(), usize::MAX)],
+            worker_id: format!(
+                "{}-{}",
+                std::process::id(),
+                &Uuid::new_v4().simple().to_string()[..8]
+            ),
+            lease: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(100),
+            running: Arc::new(RwLock::new(false)),
+            default_config: JobConfig::default(),
+        }
+    }
+
+    /// Maximum number of jobs this worker runs at once
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Also serve `queue`, running at most `max_concurrency` of its jobs at once
+    ///
+    /// Calling it for `default` sets the limit of the default queue. Jobs on
+    /// queues that are not served stay pending for other workers.
+    pub fn with_queue(mut self, queue: &str, max_concurrency: usize) -> Self {
+        match self.queues.iter_mut().find(|(name, _)| name == queue) {
+            Some(entry) => entry.1 = max_concurrency,
+            None => self.queues.push((queue.to_string(), max_concurrency)),
+        }
+        self
+    }
+
+    /// Serve only the given queues (without limits besides `with_workers`)
+    pub fn only_queues(mut self, queues: &[&str]) -> Self {
+        self.queues = queues.iter().map(|q| (q.to_string(), usize::MAX)).collect();
+        self
+    }
+
+    /// How long a claim is valid without a heartbeat
+    ///
+    /// Heartbeats are sent every third of the lease, so a crashed worker's
+    /// jobs become available again after at most one lease.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Identify this worker in the store (default: pid and a random suffix)
+    pub fn with_worker_id(mut self, worker_id: &str) -> Self {
+        self.worker_id = worker_id.to_string();
+        self
+    }
+
+    /// How long `run` waits when no job is ready
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Time limit for every attempt of `job_name`, instead of `JobConfig::timeout`
+    pub fn with_timeout(mut self, job_name: &str, timeout: Duration) -> Self {
+        self.timeouts.insert(job_name.to_string(), timeout);
+        self
+    }
+
+    /// This worker's id
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    /// The underlying store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Enqueue a job with default config
+    pub async fn enqueue<T: Serialize>(
+        &self,
+        name: &str,
+        payload: T,
+    ) -> Result<String, PulseError> {
+        self.enqueue_with_config(name, payload, self.default_config.clone())
+            .await
+    }
+
+    /// Enqueue a job with custom config
+    ///
+    /// For unique jobs, returns the id of the live duplicate if there is one.
+    pub async fn enqueue_with_config<T: Serialize>(
+        &self,
+        name: &str,
+        payload: T,
+        config: JobConfig,
+    ) -> Result<String, PulseError> {
+        let payload_json = serde_json::to_string(&payload)?;
+        self.save(Job::new(name, payload_json, &config), &config)
+            .await
+    }
+
+    /// Schedule a job to run at a specific time
+    pub async fn schedule<T: Serialize>(
+        &self,
+        name: &str,
+        payload: T,
+        run_at: DateTime<Utc>,
+    ) -> Result<String, PulseError> {
+        let payload_json = serde_json::to_string(&payload)?;
+        let job = Job::scheduled(name, payload_json, run_at, &self.default_config);
+        self.save(job, &self.default_config).await
+    }
+
+    /// Enqueue a job that runs once `parent_id` has completed
+    ///
+    /// If the parent ends up dead or cancelled, the job is cancelled too.
+    pub async fn enqueue_after<T: Serialize>(
+        &self,
+        parent_id: &str,
+        name: &str,
+        payload: T,
+        config: JobConfig,
+    ) -> Result<String, PulseError> {
+        self.status(parent_id).await?;
+
+        let payload_json = serde_json::to_string(&payload)?;
+        let mut job = Job::new(name, payload_json, &config);
+        job.status = JobStatus::Waiting;
+        job.depends_on = Some(parent_id.to_string());
+        let job_id = self.save(job, &config).await?;
+
+        // The parent may have finished before the job was saved
+        let parent = self.status(parent_id).await?;
+        if parent.status.is_terminal() {
+            self.settle(parent).await?;
+        }
+
+        Ok(job_id)
+    }
+
+    /// Start a batch of jobs with an optional completion callback
+    pub fn batch(&self) -> Batch<'_, S> {
+        Batch {
+            pulse: self,
+            id: Uuid::new_v4().to_string(),
+            jobs: Vec::new(),
+            callback: None,
+            error: None,
+        }
+    }
+
+    /// Progress of a batch
+    pub async fn batch_status(&self, batch_id: &str) -> Result<BatchStatus, PulseError> {
+        let jobs = self.store.get_batch(batch_id).await?;
+        if jobs.is_empty() {
+            return Err(PulseError::NotFound(batch_id.to_string()));
+        }
+
+        let count = |f: fn(&JobStatus) -> bool| jobs.iter().filter(|j| f(&j.status)).count();
+        Ok(BatchStatus {
+            id: batch_id.to_string(),
+            total: jobs.len(),
+            completed: count(|s| matches!(s, JobStatus::Completed)),
+            failed: count(|s| matches!(s, JobStatus::Dead)),
+            cancelled: count(|s| matches!(s, JobStatus::Cancelled)),
+        })
+    }
+
+    async fn save(&self, job: Job, config: &JobConfig) -> Result<String, PulseError> {
+        if let Some(window) = config.unique_for {
+            let since = job.created_at
+                - chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+            if let Some(existing) = self.store.save_unique(&job, since).await? {
+                debug!(job_id = %existing.id, name = %job.name, "Skipped duplicate job");
+                return Ok(existing.id);
+            }
+        } else {
+            self.store.save(&job).await?;
+        }
+        Ok(job.id)
+    }
+
+    /// Register a job handler
+    pub async fn handle<F, Fut>(&self, name: &str, handler: F)
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let mut handlers = self.handlers.write().await;
+        handlers.insert(
+            name.to_string(),
+            Arc::new(move |payload| {
+                let task = handler(payload);
+                Box::pin(async move { task.await.map_err(Failure::retry) })
+            }),
+        );
+    }
+
+    /// Register a handler that receives the deserialized payload
+    ///
+    /// A payload that does not deserialize into `T` sends the job straight to
+    /// the dead letter queue, since retrying cannot fix it.
+    pub async fn handle_typed<T, F, Fut>(&self, name: &str, handler: F)
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let mut handlers = self.handlers.write().await;
+        handlers.insert(
+            name.to_string(),
+            Arc::new(move |payload| match serde_json::from_str::<T>(&payload) {
+                Ok(payload) => {
+                    let task = handler(payload);
+                    Box::pin(async move { task.await.map_err(Failure::retry) })
+                }
+                Err(e) => {
+                    let error = format!("Invalid payload: {}", e);
+                    Box::pin(async move { Err(Failure::fatal(error)) })
+                }
+            }),
+        );
+    }
+
+    /// Get job status
+    pub async fn status(&self, job_id: &str) -> Result<Job, PulseError> {
+        self.store
+            .get(job_id)
+            .await?
+            .ok_or_else(|| PulseError::NotFound(job_id.to_string()))
+    }
+
+    /// Retry a failed/dead job
+    pub async fn retry(&self, job_id: &str) -> Result<(), PulseError> {
+        let mut job = self.status(job_id).await?;
+        job.status = JobStatus::Pending;
+        job.attempts = 0;
+        job.scheduled_at = None;
+        job.last_error = None;
+        job.worker_id = None;
+        job.lease_expires_at = None;
+        self.store.update(&job).await
+    }
+
+    /// Cancel a pending or waiting job, along with the jobs waiting on it
+    pub async fn cancel(&self, job_id: &str) -> Result<(), PulseError> {
+        let mut job = self.status(job_id).await?;
+        if !matches!(job.status, JobStatus::Pending | JobStatus::Waiting) {
+            return Err(PulseError::ExecutionFailed(
+                "Can only cancel pending jobs".into(),
+            ));
+        }
+        job.status = JobStatus::Cancelled;
+        self.store.update(&job).await?;
+        self.settle(job).await
+    }
+
+    /// Get all dead letter jobs
+    pub async fn dead_jobs(&self) -> Result<Vec<Job>, PulseError> {
+        self.store.get_dead().await
+    }
+
+    /// Requeue jobs whose worker stopped renewing its lease
+    ///
+    /// `run` and `process_batch` call this themselves.
+    pub async fn recover_expired(&self) -> Result<usize, PulseError> {
+        let recovered = self.store.recover_expired().await?;
+        if recovered.is_empty() {
+            return Ok(0);
+        }
+        warn!(
+            count = recovered.len(),
+            "Recovered jobs with expired leases"
+        );
+
+        let count = recovered.len();
+        for job in recovered {
+            self.settle(job).await?;
+        }
+        Ok(count)
+    }
+
+    /// Release or cancel what waits on a finished job, and run the batch
+    /// callback once the job's batch is done
+    async fn settle(&self, job: Job) -> Result<(), PulseError> {
+        let mut finished = vec![job];
+        while let Some(job) = finished.pop() {
+            if !job.status.is_terminal() {
+                continue;
+            }
+
+            let completed = matches!(job.status, JobStatus::Completed);
+            let dependents = self.store.release_dependents(&job.id, completed).await?;
+            finished.extend(dependents);
+
+            if let Some(batch_id) = &job.batch_id {
+                let batch = self.store.get_batch(batch_id).await?;
+                // Releasing is idempotent, so racing members may both do it
+                if batch.iter().all(|j| j.status.is_terminal()) {
+                    self.store.release_dependents(batch_id, true).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the handler, renewing the lease until it finishes
+    async fn run_with_heartbeat(&self, job: &Job, mut task: HandlerFuture) -> Result<(), Failure> {
+        let mut heartbeat = tokio::time::interval((self.lease / 3).max(Duration::from_millis(10)));
+        heartbeat.tick().await;
+
+        loop {
+            tokio::select! {
+                result = &mut task => return result,
+                _ = heartbeat.tick() => {
+                    match self.store.heartbeat(&job.id, &self.worker_id, self.lease).await {
+                        Ok(true) => {}
+                        Ok(false) => warn!(job_id = %job.id, "Lost the lease of a running job"),
+                        Err(e) => warn!(job_id = %job.id, error = %e, "Lease heartbeat failed"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Execute a claimed job and record the outcome
+    async fn process_job(&self, mut job: Job) -> Result<(), PulseError> {
+        let handler = self.handlers.read().await.get(&job.name).cloned();
+
+        let result = match &handler {
+            Some(handler) => {
+                let timeout = self.timeouts.get(&job.name).copied().unwrap_or(job.timeout);
+                let task = self.run_with_heartbeat(&job, handler(job.payload.clone()));
+                match tokio::time::timeout(timeout, task).await {
+                    Ok(result) => result,
+                    Err(_) => Err(Failure::retry(format!(
+                        "{} after {:?}",
+                        PulseError::Timeout,
+                        timeout
+                    ))),
+                }
+            }
+            None => Err(Failure::retry(
+                PulseError::NoHandler(job.name.clone()).to_string(),
+            )),
+        };
+
+        match result {
+            Ok(()) => {
+                job.status = JobStatus::Completed;
+                job.completed_at = Some(Utc::now());
+            }
+            Err(failure) => job.fail(failure.error, failure.retryable),
+        }
+
+        if self.store.finish(&job, &self.worker_id).await? {
+            self.settle(job.clone()).await?;
+        } else {
+            warn!(
+                job_id = %job.id,
+                "Job was reclaimed after its lease expired; result discarded"
+            );
+        }
+
+        match handler {
+            Some(_) => Ok(()),
+            None => Err(PulseError::NoHandler(job.name)),
+        }
+    }
+
+    /// Start processing jobs until `stop` is called
+    ///
+    /// Runs up to `with_workers` jobs concurrently, honouring per-queue
+    /// limits. After `stop`, no new jobs are claimed and running jobs finish
+    /// before `run` returns.
+    pub async fn run(&self) -> Result<(), PulseError> {
+        {
+            let mut running = self.running.write().await;
+            *running = true;
+        }
+
+        let mut tasks = tokio::task::JoinSet::new();
+        // Queue of each running task, so a panicked one still frees its slot
+        let mut task_queues: HashMap<tokio::task::Id, String> = HashMap::new();
+        let mut active: HashMap<String, usize> = HashMap::new();
+        let mut last_recovery: Option<tokio::time::Instant> = None;
+
+        loop {
+            {
+                let running = self.running.read().await;
+                if !*running {
+                    break;
+                }
+            }
+
+            while let Some(finished) = tasks.try_join_next_with_id() {
+                Self::task_done(finished, &mut task_queues, &mut active);
+            }
+
+            if last_recovery.is_none_or(|at| at.elapsed() >= self.lease / 2) {
+                if let Err(e) = self.recover_expired().await {
+                    error!(error = %e, "Failed to recover expired jobs");
+                }
+                last_recovery = Some(tokio::time::Instant::now());
+            }
+
+            let mut claimed = 0;
+            for (queue, limit) in &self.queues {
+                let in_queue = active.get(queue).copied().unwrap_or(0);
+                let free = limit
+                    .saturating_sub(in_queue)
+                    .min(self.workers.saturating_sub(tasks.len()));
+                if free == 0 {
+                    continue;
+                }
+
+                let jobs = match self
+                    .store
+                    .claim(&self.worker_id, queue, free, self.lease)
+                    .await
+                {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        error!(queue = %queue, error = %e, "Failed to claim jobs");
+                        continue;
+                    }
+                };
+                for job in jobs {
+                    debug!(job_id = %job.id, queue = %queue, "Claimed job");
+                    claimed += 1;
+                    *active.entry(queue.clone()).or_default() += 1;
+                    let worker = self.clone();
+                    let task = tasks.spawn(async move {
+                        if let Err(e) = worker.process_job(job).await {
+                            error!(error = %e, "Job processing failed");
+                        }
+                    });
+                    task_queues.insert(task.id(), queue.clone());
+                }
+            }
+
+            if claimed == 0 {
+                // Wake up early when a slot frees
+                tokio::select! {
+                    Some(finished) = tasks.join_next_with_id() => {
+                        Self::task_done(finished, &mut task_queues, &mut active)
+                    }
+                    _ = self.store.wait_for_jobs(self.poll_interval) => {}
+                }
+            }
+        }
+
+        // Graceful shutdown: let running jobs finish
+        while let Some(finished) = tasks.join_next_with_id().await {
+            Self::task_done(finished, &mut task_queues, &mut active);
+        }
+
+        Ok(())
+    }
+
+    fn task_done(
+        finished: Result<(tokio::task::Id, ()), tokio::task::JoinError>,
+        task_queues: &mut HashMap<tokio::task::Id, String>,
+        active: &mut HashMap<String, usize>,
+    ) {
+        let id = match finished {
+            Ok((id, ())) => id,
+            // A panicking handler leaves its lease to expire
+            Err(e) => {
+                error!(error = %e, "Job task panicked");
+                e.id()
+            }
+        };
+        if let Some(count) = task_queues
+            .remove(&id)
+            .and_then(|queue| active.get_mut(&queue))
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Stop processing jobs
+    pub async fn stop(&self) {
+        let mut running = self.running.write().await;
+        *running = false;
+    }
+
+    /// Claim and process one batch of jobs (for testing)
+    pub async fn process_batch(&self) -> Result<usize, PulseError> {
+        self.recover_expired().await?;
+
+        let mut processed = 0;
+        let mut first_error = None;
+        for (queue, limit) in &self.queues {
+            let free = (*limit).min(self.workers - processed);
+            if free == 0 {
+                continue;
+            }
+            let jobs = self
+                .store
+                .claim(&self.worker_id, queue, free, self.lease)
+                .await?;
+            for job in jobs {
+                processed += 1;
+                if let Err(e) = self.process_job(job).await {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(processed),
+        }
+    }
+}
+
+impl<S: JobStore> Clone for Pulse<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            handlers: Arc::clone(&self.handlers),
+            timeouts: self.timeouts.clone(),
+            workers: self.workers,
+            queues: self.queues.clone(),
+            worker_id: self.worker_id.clone(),
+            lease: self.lease,
+            poll_interval: self.poll_interval,
+            running: Arc::clone(&self.running),
+            default_config: self.default_config.clone(),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// BATCHES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A group of jobs, built with `Pulse::batch`
+///
+/// The callback job runs once every job of the batch has finished, whether
+/// it completed, failed or was cancelled; use `Pulse::batch_status` inside it
+/// to tell.
+pub struct Batch<'a, S: JobStore> {
+    pulse: &'a Pulse<S>,
+    id: String,
+    jobs: Vec<Job>,
+    callback: Option<Job>,
+    error: Option<PulseError>,
+}
+
+impl<S: JobStore + 'static> Batch<'_, S> {
+    /// Batch id, known before dispatch so it can go into payloads
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Add a job with default config
+    pub fn add<T: Serialize>(self, name: &str, payload: T) -> Self {
+        let config = self.pulse.default_config.clone();
+        self.add_with_config(name, payload, config)
+    }
+
+    /// Add a job with custom config
+    pub fn add_with_config<T: Serialize>(
+        mut self,
+        name: &str,
+        payload: T,
+        config: JobConfig,
+    ) -> Self {
+        match serde_json::to_string(&payload) {
+            Ok(payload) => {
+                let mut job = Job::new(name, payload, &config);
+                job.batch_id = Some(self.id.clone());
+                self.jobs.push(job);
+            }
+            Err(e) => {
+                self.error.get_or_insert(e.into());
+            }
+        }
+        self
+    }
+
+    /// Job to run when the whole batch has finished
+    pub fn then<T: Serialize>(mut self, name: &str, payload: T) -> Self {
+        match serde_json::to_string(&payload) {
+            Ok(payload) => {
+                let mut job = Job::new(name, payload, &self.pulse.default_config);
+                job.status = JobStatus::Waiting;
+                job.depends_on = Some(self.id.clone());
+                self.callback = Some(job);
+            }
+            Err(e) => {
+                self.error.get_or_insert(e.into());
+            }
+        }
+        self
+    }
+
+    /// Enqueue the jobs and the callback; returns the batch id
+    pub async fn dispatch(self) -> Result<String, PulseError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        for job in &self.jobs {
+            self.pulse.store.save(job).await?;
+        }
+
+        // Saved last, so it cannot be released while members are still being
+        // added; members that finished before that are caught up below
+        if let Some(callback) = &self.callback {
+            self.pulse.store.save(callback).await?;
+            let batch = self.pulse.store.get_batch(&self.id).await?;
+            if batch.iter().all(|j| j.status.is_terminal()) {
+                self.pulse.store.release_dependents(&self.id, true).await?;
+            }
+        }
+
+        Ok(self.id)
+    }
+}
+
+/// Progress of a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchStatus {
+    pub id: String,
+    pub total: usize,
+    pub completed: usize,
+    /// Jobs that ended in the dead letter queue
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+impl BatchStatus {
+    /// Jobs that have not finished yet
+    pub fn pending(&self) -> usize {
+        self.total - self.completed - self.failed - self.cancelled
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending() == 0
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // BASIC OPERATIONS
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_enqueue_job() {
+        let pulse = Pulse::in_memory();
+        let job_id = pulse
+            .enqueue("test", serde_json::json!({"key": "value"}))
+            .await
+            .unwrap();
+
+        assert!(!job_id.is_empty());
+        assert_eq!(job_id.len(), 36); // UUID format
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_empty_payload() {
+        let pulse = Pulse::in_memory();
+        let job_id = pulse.enqueue("test", serde_json::json!({})).await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert_eq!(job.payload, "{}");
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_large_payload() {
+        let pulse = Pulse::in_memory();
+        let large_data = "x".repeat(1_000_000); // 1MB
+        let job_id = pulse
+            .enqueue("test", serde_json::json!({"data": large_data}))
+            .await
+            .unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(job.payload.len() > 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_unicode_payload() {
+        let pulse = Pulse::in_memory();
+        let job_id = pulse
+            .enqueue(
+                "test",
+                serde_json::json!({
+                    "chinese": "你好世界",
+                    "emoji": "🚀🔥💯",
+                    "arabic": "مرحبا"
+                }),
+            )
+            .await
+            .unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(job.payload.contains("你好"));
+        assert!(job.payload.contains("🚀"));
+    }
+
+    #[tokio::test]
+    async fn test_job_execution() {
+        let pulse = Pulse::in_memory();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = counter.clone();
+
+        pulse
+            .handle("increment", move |_| {
+                let c = counter_clone.clone();
+                async move {
+                    c.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .await;
+
+        pulse
+            .enqueue("increment", serde_json::json!({}))
+            .await
+            .unwrap();
+        pulse.process_batch().await.unwrap();
+
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_job_status_tracking() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("test", |_| async { Ok(()) }).await;
+
+        let job_id = pulse.enqueue("test", serde_json::json!({})).await.unwrap();
+
+        // Initially pending
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Pending));
+
+        // After processing, completed
+        pulse.process_batch().await.unwrap();
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Completed));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // RETRIES & FAILURES
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_retry_on_failure() {
+        let pulse = Pulse::in_memory();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_clone = attempts.clone();
+
+        pulse
+            .handle("fail_once", move |_| {
+                let a = attempts_clone.clone();
+                async move {
+                    let count = a.fetch_add(1, Ordering::SeqCst);
+                    if count == 0 {
+                        Err("First attempt fails".to_string())
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+
+        pulse
+            .enqueue_with_config(
+                "fail_once",
+                serde_json::json!({}),
+                JobConfig {
+                    max_retries: 3,
+                    retry_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // First attempt fails
+        pulse.process_batch().await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // Second attempt succeeds
+        pulse.process_batch().await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_retries_exceeded() {
+        let pulse = Pulse::in_memory();
+
+        pulse
+            .handle("always_fail", |_| async { Err("Always fails".to_string()) })
+            .await;
+
+        let job_id = pulse
+            .enqueue_with_config(
+                "always_fail",
+                serde_json::json!({}),
+                JobConfig {
+                    max_retries: 2,
+                    retry_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // Attempt 1
+        pulse.process_batch().await.unwrap();
+        // Attempt 2 (max reached)
+        pulse.process_batch().await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Dead));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_queue() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("fail", |_| async { Err("fail".into()) }).await;
+
+        pulse
+            .enqueue_with_config("fail", serde_json::json!({}), JobConfig::no_retry())
+            .await
+            .unwrap();
+        pulse.process_batch().await.unwrap();
+
+        let dead = pulse.dead_jobs().await.unwrap();
+        assert_eq!(dead.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_manual_retry() {
+        let pulse = Pulse::in_memory();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_clone = attempts.clone();
+
+        pulse
+            .handle("retry_test", move |_| {
+                let a = attempts_clone.clone();
+                async move {
+                    if a.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err("fail".into())
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+
+        let job_id = pulse
+            .enqueue_with_config("retry_test", serde_json::json!({}), JobConfig::no_retry())
+            .await
+            .unwrap();
+
+        // First attempt fails, goes to dead
+        pulse.process_batch().await.unwrap();
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Dead));
+
+        // Manual retry
+        pulse.retry(&job_id).await.unwrap();
+
+        // Now pending again
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Pending));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // PRIORITY & SCHEDULING
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_priority_ordering() {
+        let pulse = Pulse::in_memory();
+        let order = Arc::new(RwLock::new(Vec::<String>::new()));
+
+        let order_clone = order.clone();
+        pulse
+            .handle("record", move |payload| {
+                let o = order_clone.clone();
+                async move {
+                    let mut vec = o.write().await;
+                    vec.push(payload);
+                    Ok(())
+                }
+            })
+            .await;
+
+        // Enqueue in reverse priority order
+        pulse
+            .enqueue_with_config(
+                "record",
+                "low".to_string(),
+                JobConfig {
+                    priority: JobPriority::Low,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        pulse
+            .enqueue_with_config(
+                "record",
+                "critical".to_string(),
+                JobConfig {
+                    priority: JobPriority::Critical,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        pulse
+            .enqueue_with_config("record", "normal".to_string(), JobConfig::default())
+            .await
+            .unwrap();
+
+        // Process all
+        pulse.process_batch().await.unwrap();
+        pulse.process_batch().await.unwrap();
+        pulse.process_batch().await.unwrap();
+
+        let order_vec = order.read().await;
+        // Critical should be first
+        assert!(order_vec[0].contains("critical"));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_future() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("scheduled", |_| async { Ok(()) }).await;
+
+        let future_time = Utc::now() + chrono::Duration::hours(1);
+        let job_id = pulse
+            .schedule("scheduled", serde_json::json!({}), future_time)
+            .await
+            .unwrap();
+
+        // Should not process (scheduled for future)
+        let processed = pulse.process_batch().await.unwrap();
+        assert_eq!(processed, 0);
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Pending));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_past() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("scheduled", |_| async { Ok(()) }).await;
+
+        let past_time = Utc::now() - chrono::Duration::hours(1);
+        let job_id = pulse
+            .schedule("scheduled", serde_json::json!({}), past_time)
+            .await
+            .unwrap();
+
+        // Should process immediately
+        pulse.process_batch().await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Completed));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // EDGE CASES
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_cancel_pending_job() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("test", |_| async { Ok(()) }).await;
+
+        let job_id = pulse.enqueue("test", serde_json::json!({})).await.unwrap();
+        pulse.cancel(&job_id).await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Cancelled));
+
+        // Cancelled jobs should not process
+        let processed = pulse.process_batch().await.unwrap();
+        assert_eq!(processed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_missing_handler() {
+        let pulse = Pulse::in_memory();
+
+        pulse
+            .enqueue("no_handler", serde_json::json!({}))
+            .await
+            .unwrap();
+
+        let result = pulse.process_batch().await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_job_not_found() {
+        let pulse = Pulse::in_memory();
+
+        let result = pulse.status("nonexistent").await;
+        assert!(matches!(result, Err(PulseError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_job_config_presets() {
+        let critical = JobConfig::critical();
+        assert_eq!(critical.priority, JobPriority::Critical);
+        assert_eq!(critical.max_retries, 5);
+
+        let no_retry = JobConfig::no_retry();
+        assert_eq!(no_retry.max_retries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_job_status_terminal() {
+        assert!(JobStatus::Completed.is_terminal());
+        assert!(JobStatus::Dead.is_terminal());
+        assert!(JobStatus::Cancelled.is_terminal());
+        assert!(!JobStatus::Pending.is_terminal());
+        assert!(!JobStatus::Running.is_terminal());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_mode() {
+        let pulse = Pulse::in_memory();
+
+        pulse.handle("test", |_| async { Ok(()) }).await;
+
+        let job_id = pulse.enqueue("test", serde_json::json!({})).await.unwrap();
+        pulse.process_batch().await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Completed));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // CLAIMING & LEASES
+    // ═══════════════════════════════════════════════════════════════════════
+
+    fn immediate_retry() -> JobConfig {
+        JobConfig::default().with_backoff(Duration::ZERO, Duration::ZERO)
+    }
+
+    async fn count_runs(
+        pulse: &Pulse<impl JobStore + 'static>,
+        runs: Arc<std::sync::Mutex<HashMap<String, usize>>>,
+    ) {
+        pulse
+            .handle("track", move |payload| {
+                let runs = runs.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                    *runs.lock().unwrap().entry(payload).or_default() += 1;
+                    Ok(())
+                }
+            })
+            .await;
+    }
+
+    async fn assert_each_job_runs_once<S: JobStore + 'static>(a: Pulse<S>, b: Pulse<S>) {
+        let runs = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        count_runs(&a, runs.clone()).await;
+        count_runs(&b, runs.clone()).await;
+
+        for i in 0..40 {
+            a.enqueue("track", format!("job-{}", i)).await.unwrap();
+        }
+
+        let (ra, rb) = (a.clone(), b.clone());
+        let workers = [
+            tokio::spawn(async move { ra.run().await }),
+            tokio::spawn(async move { rb.run().await }),
+        ];
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while runs.lock().unwrap().len() < 40 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        a.stop().await;
+        b.stop().await;
+        for worker in workers {
+            worker.await.unwrap().unwrap();
+        }
+
+        let runs = runs.lock().unwrap();
+        assert_eq!(runs.len(), 40);
+        assert!(
+            runs.values().all(|&count| count == 1),
+            "a job ran twice: {:?}",
+            runs
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_conformance() {
+        conformance::check_store(|| async { MemoryJobStore::new() }).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_conformance() {
+        conformance::check_store(|| async { SqliteJobStore::new(":memory:").await.unwrap() }).await;
+    }
+
+    #[tokio::test]
+    async fn test_two_workers_memory_store() {
+        let store = MemoryJobStore::new();
+        let a = Pulse::with_store(store.clone())
+            .with_workers(4)
+            .with_worker_id("a");
+        let b = Pulse::with_store(store).with_workers(4).with_worker_id("b");
+        assert_each_job_runs_once(a, b).await;
+    }
+
+    #[tokio::test]
+    async fn test_two_workers_sqlite_store() {
+        // Two pools on one file, like two worker processes
+        let path = std::env::temp_dir().join(format!("pulse-{}.db", Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        let a = Pulse::new(path)
+            .await
+            .unwrap()
+            .with_workers(4)
+            .with_worker_id("a");
+        let b = Pulse::new(path)
+            .await
+            .unwrap()
+            .with_workers(4)
+            .with_worker_id("b");
+        assert_each_job_runs_once(a, b).await;
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_claim_is_exclusive() {
+        let store = SqliteJobStore::new(":memory:").await.unwrap();
+        let pulse = Pulse::with_store(store);
+        for i in 0..3 {
+            pulse.enqueue("job", i).await.unwrap();
+        }
+        let lease = Duration::from_secs(30);
+
+        let first = pulse
+            .store()
+            .claim("a", DEFAULT_QUEUE, 2, lease)
+            .await
+            .unwrap();
+        let second = pulse
+            .store()
+            .claim("b", DEFAULT_QUEUE, 2, lease)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert!(first
+            .iter()
+            .all(|j| j.worker_id.as_deref() == Some("a") && j.attempts == 1));
+        assert!(!first.iter().any(|j| j.id == second[0].id));
+        assert!(pulse
+            .store()
+            .claim("c", DEFAULT_QUEUE, 2, lease)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Only the owner may renew or finish
+        let job = &second[0];
+        assert!(pulse.store().heartbeat(&job.id, "b", lease).await.unwrap());
+        assert!(!pulse.store().heartbeat(&job.id, "a", lease).await.unwrap());
+        assert!(!pulse.store().finish(job, "a").await.unwrap());
+        assert!(pulse.store().finish(job, "b").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recover_jobs_of_dead_worker() {
+        check_recovery(Pulse::in_memory()).await;
+        check_recovery(Pulse::with_store(
+            SqliteJobStore::new(":memory:").await.unwrap(),
+        ))
+        .await;
+    }
+
+    async fn check_recovery<S: JobStore + 'static>(pulse: Pulse<S>) {
+        pulse.handle("job", |_| async { Ok(()) }).await;
+        let job_id = pulse
+            .enqueue_with_config("job", "x", immediate_retry())
+            .await
+            .unwrap();
+
+        // A worker claims the job and dies without finishing it
+        let claimed = pulse
+            .store()
+            .claim("crashed", DEFAULT_QUEUE, 1, Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(pulse.process_batch().await.unwrap(), 0);
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(pulse.process_batch().await.unwrap(), 1);
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Completed));
+        assert_eq!(job.attempts, 2);
+        assert!(job.worker_id.is_none());
+
+        // The crashed worker can no longer record a result
+        assert!(!pulse.store().finish(&claimed[0], "crashed").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_counts_as_attempt() {
+        let pulse = Pulse::in_memory();
+        let job_id = pulse
+            .enqueue_with_config("job", "x", JobConfig::no_retry())
+            .await
+            .unwrap();
+        pulse
+            .store()
+            .claim("crashed", DEFAULT_QUEUE, 1, Duration::from_millis(1))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(pulse.recover_expired().await.unwrap(), 1);
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Dead));
+        assert_eq!(job.last_error.as_deref(), Some(LEASE_EXPIRED));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_keeps_long_job_leased() {
+        let store = MemoryJobStore::new();
+        let slow = Pulse::with_store(store.clone())
+            .with_worker_id("slow")
+            .with_lease(Duration::from_millis(150));
+        let other = Pulse::with_store(store)
+            .with_worker_id("other")
+            .with_lease(Duration::from_millis(150));
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        for pulse in [&slow, &other] {
+            let runs = runs.clone();
+            pulse
+                .handle("slow", move |_| {
+                    let runs = runs.clone();
+                    async move {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(600)).await;
+                        Ok(())
+                    }
+                })
+                .await;
+        }
+        let job_id = slow.enqueue("slow", "x").await.unwrap();
+
+        let worker = slow.clone();
+        let running = tokio::spawn(async move { worker.process_batch().await });
+        // Lease is 150ms; without heartbeats the other worker would take over
+        for _ in 0..15 {
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            assert_eq!(other.process_batch().await.unwrap(), 0);
+        }
+        assert_eq!(running.await.unwrap().unwrap(), 1);
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        let job = slow.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Completed));
+    }
+
+    #[tokio::test]
+    async fn test_per_queue_concurrency() {
+        let pulse = Pulse::in_memory()
+            .with_workers(4)
+            .with_queue("emails", 1)
+            .with_poll_interval(Duration::from_millis(5));
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak_emails = Arc::new(AtomicUsize::new(0));
+        let peak_total = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+        let emails_active = Arc::new(AtomicUsize::new(0));
+
+        for (name, is_email) in [("email", true), ("report", false)] {
+            let (active, peak_emails, peak_total, done, emails_active) = (
+                active.clone(),
+                peak_emails.clone(),
+                peak_total.clone(),
+                done.clone(),
+                emails_active.clone(),
+            );
+            pulse
+                .handle(name, move |_| {
+                    let (active, peak_emails, peak_total, done, emails_active) = (
+                        active.clone(),
+                        peak_emails.clone(),
+                        peak_total.clone(),
+                        done.clone(),
+                        emails_active.clone(),
+                    );
+                    async move {
+                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak_total.fetch_max(now, Ordering::SeqCst);
+                        if is_email {
+                            let now = emails_active.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak_emails.fetch_max(now, Ordering::SeqCst);
+                        }
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        if is_email {
+                            emails_active.fetch_sub(1, Ordering::SeqCst);
+                        }
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        done.fetch_add(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                })
+                .await;
+        }
+
+        for _ in 0..4 {
+            pulse
+                .enqueue_with_config("email", "x", JobConfig::default().on_queue("emails"))
+                .await
+                .unwrap();
+        }
+        for _ in 0..8 {
+            pulse.enqueue("report", "x").await.unwrap();
+        }
+
+        let worker = pulse.clone();
+        let handle = tokio::spawn(async move { worker.run().await });
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while done.load(Ordering::SeqCst) < 12 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        pulse.stop().await;
+        handle.await.unwrap().unwrap();
+
+        assert_eq!(done.load(Ordering::SeqCst), 12);
+        assert_eq!(peak_emails.load(Ordering::SeqCst), 1);
+        assert!(peak_total.load(Ordering::SeqCst) <= 4);
+        assert!(peak_total.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicked_task_frees_only_its_queue() {
+        let mut tasks = tokio::task::JoinSet::new();
+        let mut task_queues = HashMap::new();
+        let panicked = tasks.spawn(async { panic!("handler bug") });
+        task_queues.insert(panicked.id(), "emails".to_string());
+        let running = tasks.spawn(std::future::pending::<()>());
+        task_queues.insert(running.id(), "reports".to_string());
+        let mut active = HashMap::from([("emails".to_string(), 1), ("reports".to_string(), 1)]);
+
+        let finished = tasks.join_next_with_id().await.unwrap();
+        assert!(finished.as_ref().is_err_and(|e| e.is_panic()));
+        Pulse::<MemoryJobStore>::task_done(finished, &mut task_queues, &mut active);
+
+        // The job still running in another queue keeps its slot
+        assert_eq!(active["emails"], 0);
+        assert_eq!(active["reports"], 1);
+        assert_eq!(task_queues.len(), 1);
+        tasks.abort_all();
+    }
+
+    #[tokio::test]
+    async fn test_unserved_queue_stays_pending() {
+        let pulse = Pulse::in_memory();
+        pulse.handle("job", |_| async { Ok(()) }).await;
+        let job_id = pulse
+            .enqueue_with_config("job", "x", JobConfig::default().on_queue("elsewhere"))
+            .await
+            .unwrap();
+
+        assert_eq!(pulse.process_batch().await.unwrap(), 0);
+        let other = pulse.clone().only_queues(&["elsewhere"]);
+        assert_eq!(other.process_batch().await.unwrap(), 1);
+        assert!(matches!(
+            pulse.status(&job_id).await.unwrap().status,
+            JobStatus::Completed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_upgrades_old_schema() {
+        let path = std::env::temp_dir().join(format!("pulse-old-{}.db", Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        {
+            let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path))
+                .await
+                .unwrap();
+            sqlx::query(
+                "CREATE TABLE jobs (id TEXT PRIMARY KEY, name TEXT NOT NULL, payload TEXT NOT NULL, \
+                 status TEXT NOT NULL, attempts INTEGER NOT NULL DEFAULT 0, max_retries INTEGER NOT NULL DEFAULT 3, \
+                 priority INTEGER NOT NULL DEFAULT 1, created_at TEXT NOT NULL, scheduled_at TEXT, \
+                 started_at TEXT, completed_at TEXT, last_error TEXT)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                r#"INSERT INTO jobs (id, name, payload, status, created_at) VALUES ('old', 'job', '{}', '"Pending"', '2024-01-01T00:00:00+00:00')"#,
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+        }
+
+        let pulse = Pulse::new(path).await.unwrap();
+        pulse.handle("job", |_| async { Ok(()) }).await;
+        assert_eq!(pulse.process_batch().await.unwrap(), 1);
+        let job = pulse.status("old").await.unwrap();
+        assert_eq!(job.queue, DEFAULT_QUEUE);
+        assert!(matches!(job.status, JobStatus::Completed));
+        let _ = std::fs::remove_file(path);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // BACKOFF & TIMEOUTS
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = Backoff {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            jitter: false,
+        };
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::from_secs(2));
+        assert_eq!(backoff.delay(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay(5), Duration::from_secs(10));
+        assert_eq!(backoff.delay(100), Duration::from_secs(10));
+
+        let jittered = Backoff {
+            jitter: true,
+            ..backoff
+        };
+        for _ in 0..20 {
+            let delay = jittered.delay(3);
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_waits_for_backoff() {
+        let pulse = Pulse::in_memory();
+        pulse
+            .handle("fail", |_| async { Err("fail".to_string()) })
+            .await;
+        let config = JobConfig {
+            jitter: false,
+            ..JobConfig::default().with_backoff(Duration::from_secs(60), Duration::from_secs(600))
+        };
+        let job_id = pulse
+            .enqueue_with_config("fail", "x", config)
+            .await
+            .unwrap();
+
+        assert_eq!(pulse.process_batch().await.unwrap(), 1);
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Pending));
+        assert!(job.scheduled_at.unwrap() > Utc::now() + chrono::Duration::seconds(55));
+
+        // Not retried before the delay is over
+        assert_eq!(pulse.process_batch().await.unwrap(), 0);
+        assert_eq!(pulse.status(&job_id).await.unwrap().attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_job_timeout_fails_attempt() {
+        let pulse = Pulse::in_memory();
+        pulse
+            .handle("slow", |_| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+        let job_id = pulse
+            .enqueue_with_config(
+                "slow",
+                "x",
+                JobConfig::no_retry().with_timeout(Duration::from_millis(50)),
+            )
+            .await
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        pulse.process_batch().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Dead));
+        assert!(job.last_error.unwrap().contains("timeout"));
+    }
+
+    #[tokio::test]
+    async fn test_handler_timeout_overrides_job_timeout() {
+        let pulse = Pulse::in_memory().with_timeout("slow", Duration::from_millis(50));
+        pulse
+            .handle("slow", |_| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+        let job_id = pulse
+            .enqueue_with_config("slow", "x", immediate_retry())
+            .await
+            .unwrap();
+
+        pulse.process_batch().await.unwrap();
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Pending));
+        assert_eq!(job.attempts, 1);
+        assert!(job.last_error.unwrap().contains("timeout"));
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // UNIQUE JOBS
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_unique_jobs() {
+        check_unique(Pulse::in_memory()).await;
+        check_unique(Pulse::with_store(
+            SqliteJobStore::new(":memory:").await.unwrap(),
+        ))
+        .await;
+    }
+
+    async fn check_unique<S: JobStore + 'static>(pulse: Pulse<S>) {
+        let unique = JobConfig::default().unique_for(Duration::from_secs(60));
+
+        let first = pulse
+            .enqueue_with_config("sync", "user-1", unique.clone())
+            .await
+            .unwrap();
+        let duplicate = pulse
+            .enqueue_with_config("sync", "user-1", unique.clone())
+            .await
+            .unwrap();
+        assert_eq!(first, duplicate);
+
+        // Other payloads and other job names are not duplicates
+        let other = pulse
+            .enqueue_with_config("sync", "user-2", unique.clone())
+            .await
+            .unwrap();
+        assert_ne!(first, other);
+        let renamed = pulse
+            .enqueue_with_config("export", "user-1", unique.clone())
+            .await
+            .unwrap();
+        assert_ne!(first, renamed);
+
+        // Non-unique enqueues are never deduplicated
+        let plain = pulse.enqueue("sync", "user-1").await.unwrap();
+        assert_ne!(first, plain);
+
+        // A cancelled job no longer blocks its duplicates
+        pulse.cancel(&first).await.unwrap();
+        let again = pulse
+            .enqueue_with_config("sync", "user-1", unique.clone())
+            .await
+            .unwrap();
+        assert_ne!(first, again);
+    }
+
+    #[tokio::test]
+    async fn test_unique_window_expires() {
+        let pulse = Pulse::in_memory();
+        let unique = JobConfig::default().unique_for(Duration::from_millis(20));
+
+        let first = pulse
+            .enqueue_with_config("sync", "x", unique.clone())
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let second = pulse
+            .enqueue_with_config("sync", "x", unique)
+            .await
+            .unwrap();
+        assert_ne!(first, second);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // WORKFLOWS
+    // ═══════════════════════════════════════════════════════════════════════
+
+    async fn record_order(pulse: &Pulse<impl JobStore + 'static>) -> Arc<RwLock<Vec<String>>> {
+        let order = Arc::new(RwLock::new(Vec::new()));
+        for name in ["a", "b", "c", "done"] {
+            let order = order.clone();
+            pulse
+                .handle(name, move |_| {
+                    let order = order.clone();
+                    async move {
+                        order.write().await.push(name.to_string());
+                        Ok(())
+                    }
+                })
+                .await;
+        }
+        pulse
+            .handle("fail", |_| async { Err("fail".to_string()) })
+            .await;
+        order
+    }
+
+    #[tokio::test]
+    async fn test_chained_jobs_run_in_order() {
+        check_chain(Pulse::in_memory()).await;
+        check_chain(Pulse::with_store(
+            SqliteJobStore::new(":memory:").await.unwrap(),
+        ))
+        .await;
+    }
+
+    async fn check_chain<S: JobStore + 'static>(pulse: Pulse<S>) {
+        let order = record_order(&pulse).await;
+
+        let a = pulse.enqueue("a", "x").await.unwrap();
+        let b = pulse
+            .enqueue_after(&a, "b", "x", JobConfig::default())
+            .await
+            .unwrap();
+        let c = pulse
+            .enqueue_after(&b, "c", "x", JobConfig::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            pulse.status(&c).await.unwrap().status,
+            JobStatus::Waiting
+        ));
+
+        for _ in 0..3 {
+            assert_eq!(pulse.process_batch().await.unwrap(), 1);
+        }
+        assert_eq!(pulse.process_batch().await.unwrap(), 0);
+        assert_eq!(*order.read().await, ["a", "b", "c"]);
+
+        // Chaining onto a completed job runs right away
+        let late = pulse
+            .enqueue_after(&a, "done", "x", JobConfig::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            pulse.status(&late).await.unwrap().status,
+            JobStatus::Pending
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_parent_cancels_chain() {
+        let pulse = Pulse::in_memory();
+        let order = record_order(&pulse).await;
+
+        let parent = pulse
+            .enqueue_with_config("fail", "x", JobConfig::no_retry())
+            .await
+            .unwrap();
+        let child = pulse
+            .enqueue_after(&parent, "a", "x", JobConfig::default())
+            .await
+            .unwrap();
+        let grandchild = pulse
+            .enqueue_after(&child, "b", "x", JobConfig::default())
+            .await
+            .unwrap();
+
+        pulse.process_batch().await.unwrap();
+        assert_eq!(pulse.process_batch().await.unwrap(), 0);
+        assert!(order.read().await.is_empty());
+
+        for id in [&child, &grandchild] {
+            let job = pulse.status(id).await.unwrap();
+            assert!(matches!(job.status, JobStatus::Cancelled));
+            assert!(job.last_error.unwrap().contains("did not complete"));
+        }
+
+        assert!(matches!(
+            pulse
+                .enqueue_after("missing", "a", "x", JobConfig::default())
+                .await,
+            Err(PulseError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_batch_callback() {
+        check_batch(Pulse::in_memory()).await;
+        check_batch(Pulse::with_store(
+            SqliteJobStore::new(":memory:").await.unwrap(),
+        ))
+        .await;
+    }
+
+    async fn check_batch<S: JobStore + 'static>(pulse: Pulse<S>) {
+        let order = record_order(&pulse).await;
+
+        let batch = pulse.batch();
+        let batch_id = batch.id().to_string();
+        let dispatched = batch
+            .add("a", 1)
+            .add("b", 2)
+            .add_with_config("fail", 3, JobConfig::no_retry())
+            .then("done", serde_json::json!({ "batch": batch_id }))
+            .dispatch()
+            .await
+            .unwrap();
+        assert_eq!(dispatched, batch_id);
+
+        let status = pulse.batch_status(&batch_id).await.unwrap();
+        assert_eq!(status.total, 3);
+        assert_eq!(status.pending(), 3);
+
+        // The callback only becomes ready once every member has finished
+        let mut processed = 0;
+        while order.read().await.len() < 3 && processed < 10 {
+            processed += pulse.process_batch().await.unwrap();
+        }
+        let order = order.read().await;
+        assert_eq!(order.len(), 3);
+        assert_eq!(order.last().map(String::as_str), Some("done"));
+
+        let status = pulse.batch_status(&batch_id).await.unwrap();
+        assert!(status.is_finished());
+        assert_eq!((status.completed, status.failed), (2, 1));
+
+        assert!(matches!(
+            pulse.batch_status("missing").await,
+            Err(PulseError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_empty_batch_runs_callback() {
+        let pulse = Pulse::in_memory();
+        let order = record_order(&pulse).await;
+
+        pulse.batch().then("done", "x").dispatch().await.unwrap();
+        assert_eq!(pulse.process_batch().await.unwrap(), 1);
+        assert_eq!(*order.read().await, ["done"]);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // TYPED HANDLERS
+    // ═══════════════════════════════════════════════════════════════════════
+
+    #[derive(Serialize, Deserialize)]
+    struct Email {
+        to: String,
+        subject: String,
+    }
+
+    #[tokio::test]
+    async fn test_typed_handler() {
+        let pulse = Pulse::in_memory();
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let sent_clone = sent.clone();
+        pulse
+            .handle_typed("email", move |email: Email| {
+                let sent = sent_clone.clone();
+                async move {
+                    sent.write()
+                        .await
+                        .push(format!("{}: {}", email.to, email.subject));
+                    Ok(())
+                }
+            })
+            .await;
+
+        pulse
+            .enqueue(
+                "email",
+                Email {
+                    to: "user@example.com".into(),
+                    subject: "Welcome".into(),
+                },
+            )
+            .await
+            .unwrap();
+        pulse.process_batch().await.unwrap();
+        assert_eq!(*sent.read().await, ["user@example.com: Welcome"]);
+    }
+
+    #[tokio::test]
+    async fn test_typed_handler_rejects_bad_payload() {
+        let pulse = Pulse::in_memory();
+        pulse
+            .handle_typed("email", |_: Email| async { Ok(()) })
+            .await;
+
+        // Retries are left, but a malformed payload never gets better
+        let job_id = pulse
+            .enqueue_with_config("email", "not an email", immediate_retry())
+            .await
+            .unwrap();
+        pulse.process_batch().await.unwrap();
+
+        let job = pulse.status(&job_id).await.unwrap();
+        assert!(matches!(job.status, JobStatus::Dead));
+        assert_eq!(job.attempts, 1);
+        assert!(job.last_error.unwrap().starts_with("Invalid payload"));
+    }
+}
@@ -0,0 +1,709 @@
+//! PostgreSQL job store
+//!
+//! Workers claim jobs with `FOR UPDATE SKIP LOCKED`, so any number of hosts
+//! can share one table without blocking each other. Writes that make a job
+//! ready send a `NOTIFY`, which wakes idle workers immediately.
+
+use super::{
+    claim_order, dependency_failed, from_now, Backoff, Job, JobPriority, JobStatus, JobStore,
+    PulseError, LEASE_EXPIRED, NOT_LIVE,
+};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgListener, PgPool, PgPoolOptions};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::warn;
+
+/// Table used by `PostgresJobStore::new`
+const DEFAULT_TABLE: &str = "pulse_jobs";
+
+#[derive(sqlx::FromRow)]
+struct PgJobRow {
+    id: String,
+    name: String,
+    payload: String,
+    status: String,
+    attempts: i32,
+    max_retries: i32,
+    priority: i32,
+    created_at: DateTime<Utc>,
+    scheduled_at: Option<DateTime<Utc>>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    queue: String,
+    worker_id: Option<String>,
+    lease_expires_at: Option<DateTime<Utc>>,
+    timeout_ms: i64,
+    backoff: Option<String>,
+    unique_key: Option<String>,
+    depends_on: Option<String>,
+    batch_id: Option<String>,
+}
+
+const PG_JOB_COLUMNS: &str =
+    "id, name, payload, status, attempts, max_retries, priority, created_at, \
+     scheduled_at, started_at, completed_at, last_error, queue, worker_id, lease_expires_at, \
+     timeout_ms, backoff, unique_key, depends_on, batch_id";
+
+/// `$1, $2, ...` with one placeholder per column in `PG_JOB_COLUMNS`
+fn job_placeholders() -> String {
+    (1..=PG_JOB_COLUMNS.split(',').count())
+        .map(|i| format!("${}", i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn job_from_row(row: PgJobRow) -> Result<Job, PulseError> {
+    Ok(Job {
+        id: row.id,
+        name: row.name,
+        payload: row.payload,
+        status: serde_json::from_str(&row.status)?,
+        attempts: row.attempts as u32,
+        max_retries: row.max_retries as u32,
+        priority: JobPriority::from_i32(row.priority),
+        created_at: row.created_at,
+        scheduled_at: row.scheduled_at,
+        started_at: row.started_at,
+        completed_at: row.completed_at,
+        last_error: row.last_error,
+        queue: row.queue,
+        worker_id: row.worker_id,
+        lease_expires_at: row.lease_expires_at,
+        timeout: Duration::from_millis(row.timeout_ms.max(0) as u64),
+        backoff: match row.backoff {
+            Some(backoff) => serde_json::from_str(&backoff)?,
+            None => Backoff::default(),
+        },
+        unique_key: row.unique_key,
+        depends_on: row.depends_on,
+        batch_id: row.batch_id,
+    })
+}
+
+type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
+/// Bind every column of `job`, in `PG_JOB_COLUMNS` order
+fn bind_job<'q>(query: PgQuery<'q>, job: &'q Job) -> Result<PgQuery<'q>, PulseError> {
+    Ok(query
+        .bind(&job.id)
+        .bind(&job.name)
+        .bind(&job.payload)
+        .bind(serde_json::to_string(&job.status)?)
+        .bind(job.attempts as i32)
+        .bind(job.max_retries as i32)
+        .bind(job.priority as i32)
+        .bind(job.created_at)
+        .bind(job.scheduled_at)
+        .bind(job.started_at)
+        .bind(job.completed_at)
+        .bind(&job.last_error)
+        .bind(&job.queue)
+        .bind(&job.worker_id)
+        .bind(job.lease_expires_at)
+        .bind(job.timeout.as_millis().min(i64::MAX as u128) as i64)
+        .bind(serde_json::to_string(&job.backoff)?)
+        .bind(&job.unique_key)
+        .bind(&job.depends_on)
+        .bind(&job.batch_id))
+}
+
+fn db_error(e: sqlx::Error) -> PulseError {
+    PulseError::Database(e.to_string())
+}
+
+/// Whether a worker could claim the job right now
+fn is_ready_now(job: &Job) -> bool {
+    matches!(job.status, JobStatus::Pending) && job.scheduled_at.is_none_or(|t| t <= Utc::now())
+}
+
+/// PostgreSQL job store (for multi-host deployments)
+pub struct PostgresJobStore {
+    pool: PgPool,
+    table: String,
+    channel: String,
+    ready: Arc<Notify>,
+    listener: tokio::task::JoinHandle<()>,
+}
+
+impl PostgresJobStore {
+    /// Connect and create the `pulse_jobs` table if needed
+    pub async fn new(url: &str) -> Result<Self, PulseError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .connect(url)
+            .await
+            .map_err(db_error)?;
+        Self::with_pool(pool, DEFAULT_TABLE).await
+    }
+
+    /// Use an existing pool and table name (created if needed)
+    pub async fn with_pool(pool: PgPool, table: &str) -> Result<Self, PulseError> {
+        let valid = table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && table.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if !valid {
+            return Err(PulseError::Database(format!(
+                "Invalid table name: {}",
+                table
+            )));
+        }
+
+        let statements = [
+            format!(
+                r#"
+                CREATE TABLE IF NOT EXISTS {t} (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    max_retries INTEGER NOT NULL DEFAULT 3,
+                    priority INTEGER NOT NULL DEFAULT 1,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    scheduled_at TIMESTAMPTZ,
+                    started_at TIMESTAMPTZ,
+                    completed_at TIMESTAMPTZ,
+                    last_error TEXT,
+                    queue TEXT NOT NULL DEFAULT 'default',
+                    worker_id TEXT,
+                    lease_expires_at TIMESTAMPTZ,
+                    timeout_ms BIGINT NOT NULL DEFAULT 300000,
+                    backoff TEXT,
+                    unique_key TEXT,
+                    depends_on TEXT,
+                    batch_id TEXT
+                )
+            "#,
+                t = table
+            ),
+            format!(
+                r#"CREATE INDEX IF NOT EXISTS {t}_claim ON {t} (queue, priority DESC, created_at) WHERE status = '"Pending"'"#,
+                t = table
+            ),
+            format!(
+                r#"CREATE INDEX IF NOT EXISTS {t}_leases ON {t} (lease_expires_at) WHERE status = '"Running"'"#,
+                t = table
+            ),
+            format!(
+                "CREATE INDEX IF NOT EXISTS {t}_status ON {t} (status)",
+                t = table
+            ),
+            format!(
+                "CREATE INDEX IF NOT EXISTS {t}_unique ON {t} (unique_key)",
+                t = table
+            ),
+            format!(
+                "CREATE INDEX IF NOT EXISTS {t}_depends_on ON {t} (depends_on)",
+                t = table
+            ),
+            format!(
+                "CREATE INDEX IF NOT EXISTS {t}_batch ON {t} (batch_id)",
+                t = table
+            ),
+        ];
+        for statement in &statements {
+            sqlx::query(statement)
+                .execute(&pool)
+                .await
+                .map_err(db_error)?;
+        }
+
+        let channel = format!("{}_ready", table);
+        let mut listener = PgListener::connect_with(&pool).await.map_err(db_error)?;
+        listener.listen(&channel).await.map_err(db_error)?;
+
+        let ready = Arc::new(Notify::new());
+        let wake = Arc::clone(&ready);
+        let listener = tokio::spawn(async move {
+            loop {
+                match listener.recv().await {
+                    Ok(_) => wake.notify_waiters(),
+                    // `recv` reconnects on the next call; workers keep polling meanwhile
+                    Err(e) => {
+                        warn!(error = %e, "Job notification listener failed");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            pool,
+            table: table.to_string(),
+            channel,
+            ready,
+            listener,
+        })
+    }
+
+    /// The underlying pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Wake idle workers on every host
+    async fn notify_ready(&self, queue: &str) -> Result<(), PulseError> {
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(&self.channel)
+            .bind(queue)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn fetch_jobs(&self, sql: &str, binds: &[String]) -> Result<Vec<Job>, PulseError> {
+        let mut query = sqlx::query_as::<_, PgJobRow>(sql);
+        for value in binds {
+            query = query.bind(value);
+        }
+        query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(job_from_row)
+            .collect()
+    }
+}
+
+impl Drop for PostgresJobStore {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+#[async_trait::async_trait]
+impl JobStore for PostgresJobStore {
+    async fn save(&self, job: &Job) -> Result<(), PulseError> {
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.table,
+            PG_JOB_COLUMNS,
+            job_placeholders()
+        );
+        bind_job(sqlx::query(&sql), job)?
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        if is_ready_now(job) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Job>, PulseError> {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE id = $1",
+            PG_JOB_COLUMNS, self.table
+        );
+        Ok(self
+            .fetch_jobs(&sql, &[id.to_string()])
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), PulseError> {
+        let sql = format!(
+            r#"
+            UPDATE {} SET
+                status = $1, attempts = $2, scheduled_at = $3, started_at = $4, completed_at = $5,
+                last_error = $6, worker_id = $7, lease_expires_at = $8
+            WHERE id = $9
+        "#,
+            self.table
+        );
+        sqlx::query(&sql)
+            .bind(serde_json::to_string(&job.status)?)
+            .bind(job.attempts as i32)
+            .bind(job.scheduled_at)
+            .bind(job.started_at)
+            .bind(job.completed_at)
+            .bind(&job.last_error)
+            .bind(&job.worker_id)
+            .bind(job.lease_expires_at)
+            .bind(&job.id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        if is_ready_now(job) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), PulseError> {
+        sqlx::query(&format!("DELETE FROM {} WHERE id = $1", self.table))
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get_pending(&self) -> Result<Vec<Job>, PulseError> {
+        let sql = format!(
+            r#"
+                SELECT {}
+                FROM {}
+                WHERE status = '"Pending"' AND (scheduled_at IS NULL OR scheduled_at <= $1)
+                ORDER BY priority DESC, created_at ASC
+            "#,
+            PG_JOB_COLUMNS, self.table
+        );
+        let rows: Vec<PgJobRow> = sqlx::query_as(&sql)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(job_from_row).collect()
+    }
+
+    async fn get_dead(&self) -> Result<Vec<Job>, PulseError> {
+        let sql = format!(
+            r#"SELECT {} FROM {} WHERE status = '"Dead"'"#,
+            PG_JOB_COLUMNS, self.table
+        );
+        self.fetch_jobs(&sql, &[]).await
+    }
+
+    async fn get_scheduled_ready(&self) -> Result<Vec<Job>, PulseError> {
+        self.get_pending().await
+    }
+
+    async fn save_unique(
+        &self,
+        job: &Job,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Job>, PulseError> {
+        let Some(key) = &job.unique_key else {
+            self.save(job).await?;
+            return Ok(None);
+        };
+
+        // Concurrent enqueues of the same key queue up on an advisory lock
+        // held until commit, so only the first one inserts
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(key)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        let sql = format!(
+            "SELECT {} FROM {} WHERE unique_key = $1 AND created_at >= $2 AND status NOT IN {} \
+             ORDER BY created_at DESC LIMIT 1",
+            PG_JOB_COLUMNS, self.table, NOT_LIVE
+        );
+        let existing: Option<PgJobRow> = sqlx::query_as(&sql)
+            .bind(key)
+            .bind(since)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        if let Some(existing) = existing {
+            tx.commit().await.map_err(db_error)?;
+            return job_from_row(existing).map(Some);
+        }
+
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.table,
+            PG_JOB_COLUMNS,
+            job_placeholders()
+        );
+        bind_job(sqlx::query(&sql), job)?
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        if is_ready_now(job) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(None)
+    }
+
+    async fn release_dependents(
+        &self,
+        parent_id: &str,
+        proceed: bool,
+    ) -> Result<Vec<Job>, PulseError> {
+        let (status, error) = if proceed {
+            (JobStatus::Pending, None)
+        } else {
+            (JobStatus::Cancelled, Some(dependency_failed(parent_id)))
+        };
+        let sql = format!(
+            r#"
+                UPDATE {} SET status = $1, last_error = COALESCE($2, last_error)
+                WHERE depends_on = $3 AND status = '"Waiting"'
+                RETURNING {}
+            "#,
+            self.table, PG_JOB_COLUMNS
+        );
+        let released = sqlx::query_as::<_, PgJobRow>(&sql)
+            .bind(serde_json::to_string(&status)?)
+            .bind(error)
+            .bind(parent_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(job_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(job) = released.iter().find(|j| is_ready_now(j)) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(released)
+    }
+
+    async fn get_batch(&self, batch_id: &str) -> Result<Vec<Job>, PulseError> {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE batch_id = $1",
+            PG_JOB_COLUMNS, self.table
+        );
+        self.fetch_jobs(&sql, &[batch_id.to_string()]).await
+    }
+
+    async fn claim(
+        &self,
+        worker_id: &str,
+        queue: &str,
+        limit: usize,
+        lease: Duration,
+    ) -> Result<Vec<Job>, PulseError> {
+        let now = Utc::now();
+        // Rows locked by another claim are skipped rather than waited for
+        let sql = format!(
+            r#"
+                UPDATE {t} SET
+                    status = '"Running"', attempts = attempts + 1, started_at = $5,
+                    worker_id = $1, lease_expires_at = $2
+                WHERE id IN (
+                    SELECT id FROM {t}
+                    WHERE status = '"Pending"' AND queue = $3
+                        AND (scheduled_at IS NULL OR scheduled_at <= $5)
+                    ORDER BY priority DESC, created_at ASC
+                    LIMIT $4
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING {c}
+            "#,
+            t = self.table,
+            c = PG_JOB_COLUMNS
+        );
+        let mut jobs = sqlx::query_as::<_, PgJobRow>(&sql)
+            .bind(worker_id)
+            .bind(from_now(lease))
+            .bind(queue)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(job_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        jobs.sort_by(claim_order);
+        Ok(jobs)
+    }
+
+    async fn heartbeat(
+        &self,
+        id: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, PulseError> {
+        let sql = format!(
+            r#"UPDATE {} SET lease_expires_at = $1 WHERE id = $2 AND worker_id = $3 AND status = '"Running"'"#,
+            self.table
+        );
+        let result = sqlx::query(&sql)
+            .bind(from_now(lease))
+            .bind(id)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn finish(&self, job: &Job, worker_id: &str) -> Result<bool, PulseError> {
+        let sql = format!(
+            r#"
+            UPDATE {} SET
+                status = $1, attempts = $2, scheduled_at = $3, started_at = $4, completed_at = $5,
+                last_error = $6, worker_id = NULL, lease_expires_at = NULL
+            WHERE id = $7 AND worker_id = $8
+        "#,
+            self.table
+        );
+        let result = sqlx::query(&sql)
+            .bind(serde_json::to_string(&job.status)?)
+            .bind(job.attempts as i32)
+            .bind(job.scheduled_at)
+            .bind(job.started_at)
+            .bind(job.completed_at)
+            .bind(&job.last_error)
+            .bind(&job.id)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let finished = result.rows_affected() == 1;
+        if finished && is_ready_now(job) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(finished)
+    }
+
+    async fn recover_expired(&self) -> Result<Vec<Job>, PulseError> {
+        let now = Utc::now();
+        let expired =
+            r#"status = '"Running"' AND lease_expires_at IS NOT NULL AND lease_expires_at < "#;
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {}$1",
+            PG_JOB_COLUMNS, self.table, expired
+        );
+        let rows: Vec<PgJobRow> = sqlx::query_as(&sql)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut recovered = Vec::new();
+        for row in rows {
+            let mut job = job_from_row(row)?;
+            // The backoff delay is computed here, so each row is updated on
+            // its own; one renewed or finished meanwhile no longer matches
+            job.fail(LEASE_EXPIRED.to_string(), true);
+            let sql = format!(
+                r#"
+                UPDATE {} SET
+                    status = $1, scheduled_at = $2, last_error = $3,
+                    worker_id = NULL, lease_expires_at = NULL
+                WHERE id = $4 AND {}$5
+            "#,
+                self.table, expired
+            );
+            let result = sqlx::query(&sql)
+                .bind(serde_json::to_string(&job.status)?)
+                .bind(job.scheduled_at)
+                .bind(&job.last_error)
+                .bind(&job.id)
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+            if result.rows_affected() == 1 {
+                recovered.push(job);
+            }
+        }
+
+        if let Some(job) = recovered.iter().find(|j| is_ready_now(j)) {
+            self.notify_ready(&job.queue).await?;
+        }
+        Ok(recovered)
+    }
+
+    async fn wait_for_jobs(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.ready.notified()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pulse::{conformance, Pulse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn test_pool() -> Option<PgPool> {
+        let url = std::env::var("POSTGRES_URL").ok()?;
+        Some(PgPool::connect(&url).await.unwrap())
+    }
+
+    async fn drop_tables(pool: &PgPool, tables: &[String]) {
+        for table in tables {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", table))
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_postgres_store_conformance() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let tables = std::sync::Mutex::new(Vec::new());
+        let (pool_ref, tables_ref) = (&pool, &tables);
+        conformance::check_store(move || async move {
+            let table = format!("pulse_test_{}", uuid::Uuid::new_v4().simple());
+            tables_ref.lock().unwrap().push(table.clone());
+            PostgresJobStore::with_pool(pool_ref.clone(), &table)
+                .await
+                .unwrap()
+        })
+        .await;
+        drop_tables(&pool, &tables.into_inner().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_notify_wakes_idle_worker() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let table = format!("pulse_test_{}", uuid::Uuid::new_v4().simple());
+        let store = PostgresJobStore::with_pool(pool.clone(), &table)
+            .await
+            .unwrap();
+        // Without the notification the worker would sleep for a minute
+        let pulse = Pulse::with_store(store).with_poll_interval(Duration::from_secs(60));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        pulse
+            .handle("ping", move |_| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .await;
+
+        let worker = pulse.clone();
+        let handle = tokio::spawn(async move { worker.run().await });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        pulse.enqueue("ping", "x").await.unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while runs.load(Ordering::SeqCst) == 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        pulse.stop().await;
+        handle.abort();
+        drop_tables(&pool, &[table]).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_rejects_bad_table_name() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        assert!(matches!(
+            PostgresJobStore::with_pool(pool, "jobs; DROP TABLE users").await,
+            Err(PulseError::Database(_))
+        ));
+    }
+}
@@ -0,0 +1,504 @@
+//! Redis job store
+//!
+//! Jobs are stored as JSON under `{prefix}job:{id}` and indexed by state:
+//!
+//! - `ready:{queue}:{priority}` lists hold jobs that can be claimed now
+//! - the `scheduled` sorted set holds delayed jobs, scored by run time
+//! - the `running` sorted set holds claimed jobs, scored by lease expiry
+//! - `dead`, `waiting:{parent}` and `batch:{id}` sets hold the rest
+//!
+//! Every state change is one Lua script, so claims are atomic across hosts.
+
+use super::{claim_order, dependency_failed, Job, JobStatus, JobStore, PulseError, LEASE_EXPIRED};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Redis job store (for multi-host deployments)
+pub struct RedisJobStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+impl RedisJobStore {
+    pub fn new(url: &str) -> Result<Self, PulseError> {
+        let client = redis::Client::open(url).map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(Self {
+            client,
+            prefix: "nucleus:pulse:".to_string(),
+        })
+    }
+
+    /// Key prefix; give each queue sharing a Redis its own
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    async fn connection(&self) -> Result<redis::aio::MultiplexedConnection, PulseError> {
+        self.client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))
+    }
+
+    /// Load jobs by id, skipping ids whose job is gone
+    async fn load(&self, ids: Vec<String>) -> Result<Vec<Job>, PulseError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut con = self.connection().await?;
+        let raw: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(
+                ids.iter()
+                    .map(|id| format!("{}job:{}", self.prefix, id))
+                    .collect::<Vec<_>>(),
+            )
+            .query_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        raw.into_iter()
+            .flatten()
+            .map(|json| serde_json::from_str(&json).map_err(PulseError::from))
+            .collect()
+    }
+
+    async fn members(&self, set: &str) -> Result<Vec<String>, PulseError> {
+        let mut con = self.connection().await?;
+        redis::cmd("SMEMBERS")
+            .arg(format!("{}{}", self.prefix, set))
+            .query_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))
+    }
+
+    /// Write `job` and move it to the index matching its state; `guard` is
+    /// the condition on the stored job (see `PUT_LUA`)
+    async fn put(&self, job: &Job, guard: &str, worker_id: &str) -> Result<bool, PulseError> {
+        let mut con = self.connection().await?;
+        let written: i64 = script(PUT_LUA)
+            .arg(&self.prefix)
+            .arg(serde_json::to_string(job)?)
+            .arg(millis(job.scheduled_at))
+            .arg(millis(job.lease_expires_at))
+            .arg(Utc::now().timestamp_millis())
+            .arg(guard)
+            .arg(worker_id)
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(written == 1)
+    }
+}
+
+/// Milliseconds since the epoch, or an empty argument
+fn millis(time: Option<DateTime<Utc>>) -> String {
+    time.map(|t| t.timestamp_millis().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_jobs(raw: Vec<String>) -> Result<Vec<Job>, PulseError> {
+    raw.iter()
+        .map(|json| serde_json::from_str(json).map_err(PulseError::from))
+        .collect()
+}
+
+// Every script gets the key prefix as ARGV[1]. Jobs are decoded with cjson,
+// which keeps the serde field names; the status is a string ("Pending") or a
+// single-key table ({"Failed": {...}}).
+
+const PRELUDE_LUA: &str = r#"
+local p = ARGV[1]
+local PRIORITIES = {'Critical', 'High', 'Normal', 'Low'}
+local function job_key(id) return p .. 'job:' .. id end
+local function ready_key(job) return p .. 'ready:' .. job.queue .. ':' .. job.priority end
+local function is_set(value) return value ~= nil and value ~= cjson.null end
+local function load(id)
+  local raw = redis.call('GET', job_key(id))
+  if raw then return cjson.decode(raw) end
+  return nil
+end
+local function status_of(job)
+  if type(job.status) == 'string' then return job.status end
+  for name, _ in pairs(job.status) do return name end
+end
+local function save(job)
+  local raw = cjson.encode(job)
+  redis.call('SET', job_key(job.id), raw)
+  return raw
+end
+local function unplace(job)
+  redis.call('LREM', ready_key(job), 0, job.id)
+  redis.call('ZREM', p .. 'scheduled', job.id)
+  redis.call('ZREM', p .. 'running', job.id)
+  redis.call('SREM', p .. 'dead', job.id)
+  if is_set(job.depends_on) then redis.call('SREM', p .. 'waiting:' .. job.depends_on, job.id) end
+end
+local function place(job, scheduled, lease, now)
+  local status = status_of(job)
+  redis.call('SADD', p .. 'queues', job.queue)
+  if is_set(job.batch_id) then redis.call('SADD', p .. 'batch:' .. job.batch_id, job.id) end
+  if status == 'Pending' then
+    if scheduled and scheduled > now then
+      redis.call('ZADD', p .. 'scheduled', scheduled, job.id)
+    else
+      redis.call('LPUSH', ready_key(job), job.id)
+    end
+  elseif status == 'Running' then
+    if lease then redis.call('ZADD', p .. 'running', lease, job.id) end
+  elseif status == 'Waiting' then
+    if is_set(job.depends_on) then redis.call('SADD', p .. 'waiting:' .. job.depends_on, job.id) end
+  elseif status == 'Dead' then
+    redis.call('SADD', p .. 'dead', job.id)
+  end
+end
+-- ARGV[2..7] = job json, scheduled ms, lease ms, now ms, guard, worker id
+-- Guards: 'any'; 'owner' (stored job held by the worker); 'expired' (stored
+-- job running with an expired lease)
+local function put()
+  local raw, now, guard = ARGV[2], tonumber(ARGV[5]), ARGV[6]
+  local job = cjson.decode(raw)
+  local old = load(job.id)
+  if guard == 'owner' then
+    if not old or old.worker_id ~= ARGV[7] then return 0 end
+  elseif guard == 'expired' then
+    if not old or status_of(old) ~= 'Running' then return 0 end
+    local lease = redis.call('ZSCORE', p .. 'running', job.id)
+    if not lease or tonumber(lease) >= now then return 0 end
+  end
+  if old then unplace(old) end
+  redis.call('SET', job_key(job.id), raw)
+  place(job, tonumber(ARGV[3]), tonumber(ARGV[4]), now)
+  return 1
+end
+"#;
+
+const PUT_LUA: &str = "return put()";
+
+// ARGV[8..10] = unique key, window start ms, created ms; returns the live
+// duplicate, or nothing after saving the job
+const SAVE_UNIQUE_LUA: &str = r#"
+local key = p .. 'unique:' .. ARGV[8]
+local since = tonumber(ARGV[9])
+for _, id in ipairs(redis.call('ZRANGEBYSCORE', key, since, '+inf')) do
+  local job = load(id)
+  if job then
+    local status = status_of(job)
+    if status ~= 'Dead' and status ~= 'Cancelled' then return cjson.encode(job) end
+  end
+end
+redis.call('ZREMRANGEBYSCORE', key, '-inf', '(' .. since)
+put()
+redis.call('ZADD', key, ARGV[10], cjson.decode(ARGV[2]).id)
+redis.call('PEXPIRE', key, math.max(1, tonumber(ARGV[10]) - since))
+return false
+"#;
+
+// ARGV[2..8] = queue, limit, now ms, now (RFC 3339), worker id, lease ms,
+// lease expiry (RFC 3339)
+const CLAIM_LUA: &str = r#"
+local queue, limit, now = ARGV[2], tonumber(ARGV[3]), tonumber(ARGV[4])
+for _, id in ipairs(redis.call('ZRANGEBYSCORE', p .. 'scheduled', '-inf', now)) do
+  redis.call('ZREM', p .. 'scheduled', id)
+  local job = load(id)
+  if job and status_of(job) == 'Pending' then redis.call('LPUSH', ready_key(job), id) end
+end
+local claimed = {}
+for _, priority in ipairs(PRIORITIES) do
+  local list = p .. 'ready:' .. queue .. ':' .. priority
+  while #claimed < limit do
+    local id = redis.call('RPOP', list)
+    if not id then break end
+    local job = load(id)
+    if job and status_of(job) == 'Pending' then
+      job.status = 'Running'
+      job.attempts = job.attempts + 1
+      job.started_at = ARGV[5]
+      job.worker_id = ARGV[6]
+      job.lease_expires_at = ARGV[8]
+      redis.call('ZADD', p .. 'running', ARGV[7], id)
+      table.insert(claimed, save(job))
+    end
+  end
+end
+return claimed
+"#;
+
+// ARGV[2..5] = job id, worker id, lease ms, lease expiry (RFC 3339)
+const HEARTBEAT_LUA: &str = r#"
+local job = load(ARGV[2])
+if not job or status_of(job) ~= 'Running' or job.worker_id ~= ARGV[3] then return 0 end
+job.lease_expires_at = ARGV[5]
+save(job)
+redis.call('ZADD', p .. 'running', ARGV[4], job.id)
+return 1
+"#;
+
+// ARGV[2..4] = parent id, proceed (1/0), error for cancelled jobs
+const RELEASE_LUA: &str = r#"
+local key = p .. 'waiting:' .. ARGV[2]
+local released = {}
+for _, id in ipairs(redis.call('SMEMBERS', key)) do
+  local job = load(id)
+  if job and status_of(job) == 'Waiting' then
+    if ARGV[3] == '1' then
+      job.status = 'Pending'
+      redis.call('LPUSH', ready_key(job), id)
+    else
+      job.status = 'Cancelled'
+      job.last_error = ARGV[4]
+    end
+    table.insert(released, save(job))
+  end
+end
+redis.call('DEL', key)
+return released
+"#;
+
+// ARGV[2] = job id
+const DELETE_LUA: &str = r#"
+local job = load(ARGV[2])
+if not job then return 0 end
+unplace(job)
+if is_set(job.batch_id) then redis.call('SREM', p .. 'batch:' .. job.batch_id, job.id) end
+redis.call('DEL', job_key(job.id))
+return 1
+"#;
+
+fn script(body: &str) -> redis::Script {
+    redis::Script::new(&format!("{}{}", PRELUDE_LUA, body))
+}
+
+#[async_trait::async_trait]
+impl JobStore for RedisJobStore {
+    async fn save(&self, job: &Job) -> Result<(), PulseError> {
+        self.put(job, "any", "").await?;
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Job>, PulseError> {
+        Ok(self.load(vec![id.to_string()]).await?.into_iter().next())
+    }
+
+    async fn update(&self, job: &Job) -> Result<(), PulseError> {
+        self.put(job, "any", "").await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), PulseError> {
+        let mut con = self.connection().await?;
+        let _: i64 = script(DELETE_LUA)
+            .arg(&self.prefix)
+            .arg(id)
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_pending(&self) -> Result<Vec<Job>, PulseError> {
+        let mut con = self.connection().await?;
+        let mut ids: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(format!("{}scheduled", self.prefix))
+            .arg("-inf")
+            .arg(Utc::now().timestamp_millis())
+            .query_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        for queue in self.members("queues").await? {
+            for priority in ["Critical", "High", "Normal", "Low"] {
+                let ready: Vec<String> = redis::cmd("LRANGE")
+                    .arg(format!("{}ready:{}:{}", self.prefix, queue, priority))
+                    .arg(0)
+                    .arg(-1)
+                    .query_async(&mut con)
+                    .await
+                    .map_err(|e| PulseError::Database(e.to_string()))?;
+                ids.extend(ready);
+            }
+        }
+
+        let mut pending: Vec<Job> = self
+            .load(ids)
+            .await?
+            .into_iter()
+            .filter(|j| matches!(j.status, JobStatus::Pending))
+            .collect();
+        pending.sort_by(claim_order);
+        Ok(pending)
+    }
+
+    async fn get_dead(&self) -> Result<Vec<Job>, PulseError> {
+        let ids = self.members("dead").await?;
+        self.load(ids).await
+    }
+
+    async fn get_scheduled_ready(&self) -> Result<Vec<Job>, PulseError> {
+        self.get_pending().await
+    }
+
+    async fn save_unique(
+        &self,
+        job: &Job,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Job>, PulseError> {
+        let Some(key) = &job.unique_key else {
+            self.save(job).await?;
+            return Ok(None);
+        };
+
+        let mut con = self.connection().await?;
+        let existing: Option<String> = script(SAVE_UNIQUE_LUA)
+            .arg(&self.prefix)
+            .arg(serde_json::to_string(job)?)
+            .arg(millis(job.scheduled_at))
+            .arg(millis(job.lease_expires_at))
+            .arg(Utc::now().timestamp_millis())
+            .arg("any")
+            .arg("")
+            .arg(key)
+            .arg(since.timestamp_millis())
+            .arg(job.created_at.timestamp_millis())
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        existing
+            .map(|json| serde_json::from_str(&json).map_err(PulseError::from))
+            .transpose()
+    }
+
+    async fn release_dependents(
+        &self,
+        parent_id: &str,
+        proceed: bool,
+    ) -> Result<Vec<Job>, PulseError> {
+        let mut con = self.connection().await?;
+        let released: Vec<String> = script(RELEASE_LUA)
+            .arg(&self.prefix)
+            .arg(parent_id)
+            .arg(if proceed { 1 } else { 0 })
+            .arg(dependency_failed(parent_id))
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        parse_jobs(released)
+    }
+
+    async fn get_batch(&self, batch_id: &str) -> Result<Vec<Job>, PulseError> {
+        let ids = self.members(&format!("batch:{}", batch_id)).await?;
+        self.load(ids).await
+    }
+
+    async fn claim(
+        &self,
+        worker_id: &str,
+        queue: &str,
+        limit: usize,
+        lease: Duration,
+    ) -> Result<Vec<Job>, PulseError> {
+        let now = Utc::now();
+        let expires = super::from_now(lease);
+        let mut con = self.connection().await?;
+        let claimed: Vec<String> = script(CLAIM_LUA)
+            .arg(&self.prefix)
+            .arg(queue)
+            .arg(limit.min(i64::MAX as usize) as i64)
+            .arg(now.timestamp_millis())
+            .arg(serde_json::to_value(now)?.as_str().unwrap_or_default())
+            .arg(worker_id)
+            .arg(expires.timestamp_millis())
+            .arg(serde_json::to_value(expires)?.as_str().unwrap_or_default())
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        parse_jobs(claimed)
+    }
+
+    async fn heartbeat(
+        &self,
+        id: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, PulseError> {
+        let expires = super::from_now(lease);
+        let mut con = self.connection().await?;
+        let renewed: i64 = script(HEARTBEAT_LUA)
+            .arg(&self.prefix)
+            .arg(id)
+            .arg(worker_id)
+            .arg(expires.timestamp_millis())
+            .arg(serde_json::to_value(expires)?.as_str().unwrap_or_default())
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+        Ok(renewed == 1)
+    }
+
+    async fn finish(&self, job: &Job, worker_id: &str) -> Result<bool, PulseError> {
+        let job = Job {
+            worker_id: None,
+            lease_expires_at: None,
+            ..job.clone()
+        };
+        self.put(&job, "owner", worker_id).await
+    }
+
+    async fn recover_expired(&self) -> Result<Vec<Job>, PulseError> {
+        let mut con = self.connection().await?;
+        let ids: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(format!("{}running", self.prefix))
+            .arg("-inf")
+            .arg(format!("({}", Utc::now().timestamp_millis()))
+            .query_async(&mut con)
+            .await
+            .map_err(|e| PulseError::Database(e.to_string()))?;
+
+        let mut recovered = Vec::new();
+        for mut job in self.load(ids).await? {
+            // The backoff delay is computed here; the script only writes if
+            // the lease is still expired, so a renewed job is left alone
+            job.fail(LEASE_EXPIRED.to_string(), true);
+            if self.put(&job, "expired", "").await? {
+                recovered.push(job);
+            }
+        }
+        Ok(recovered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pulse::conformance;
+
+    async fn clear(url: &str, prefix: &str) {
+        let client = redis::Client::open(url).unwrap();
+        let mut con = client.get_multiplexed_async_connection().await.unwrap();
+        let keys: Vec<String> = redis::cmd("KEYS")
+            .arg(format!("{}*", prefix))
+            .query_async(&mut con)
+            .await
+            .unwrap();
+        if !keys.is_empty() {
+            let _: () = redis::cmd("DEL")
+                .arg(keys)
+                .query_async(&mut con)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_conformance() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let run = format!("test:pulse:{}:", uuid::Uuid::new_v4().simple());
+        let (url_ref, run_ref) = (&url, &run);
+        conformance::check_store(move || async move {
+            let prefix = format!("{}{}:", run_ref, uuid::Uuid::new_v4().simple());
+            RedisJobStore::new(url_ref).unwrap().with_prefix(&prefix)
+        })
+        .await;
+        clear(&url, &run).await;
+    }
+}
@@ -23,13 +23,28 @@
 //! let message = session.get_flash("success"); // Consumed after reading
 //! ```
 
+use crate::config::GLOBAL_CONFIG;
+use crate::photon::{on_pool, DatabasePool, DatabaseType};
+use crate::redis_cache::{CacheBackend, RedisBackend};
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::Response;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use ring::aead;
+use ring::rand::{SecureRandom, SystemRandom};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+type HmacSha256 = Hmac<Sha256>;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // ERRORS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -60,6 +75,9 @@ pub enum SessionError {
 
     #[error("Session cookie invalid")]
     InvalidCookie,
+
+    #[error("Session unavailable; is SessionLayer installed?")]
+    Unavailable,
 }
 
 impl From<serde_json::Error> for SessionError {
@@ -98,6 +116,10 @@ impl axum::response::IntoResponse for SessionError {
                 StatusCode::BAD_REQUEST,
                 "Invalid session cookie".to_string(),
             ),
+            SessionError::Unavailable => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Session error".to_string(),
+            ),
         };
 
         (status, Json(json!({ "error": msg }))).into_response()
@@ -167,6 +189,26 @@ pub trait SessionStore: Send + Sync {
     async fn cleanup_expired(&self) -> usize;
 }
 
+/// Lets apps pick a backend at startup, e.g. `Box<dyn SessionStore>`
+#[async_trait::async_trait]
+impl<S: SessionStore + ?Sized> SessionStore for Box<S> {
+    async fn get(&self, id: &str) -> Option<SessionData> {
+        (**self).get(id).await
+    }
+
+    async fn set(&self, id: &str, data: SessionData) {
+        (**self).set(id, data).await
+    }
+
+    async fn delete(&self, id: &str) {
+        (**self).delete(id).await
+    }
+
+    async fn cleanup_expired(&self) -> usize {
+        (**self).cleanup_expired().await
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MEMORY SESSION STORE
 // ═══════════════════════════════════════════════════════════════════════════
@@ -223,6 +265,231 @@ impl SessionStore for MemorySessionStore {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// SQL SESSION STORE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Session store backed by a Photon `DatabasePool` (SQLite, Postgres or MySQL)
+///
+/// Sessions live in one table as JSON. Call `migrate()` once at startup to
+/// create it.
+#[derive(Clone)]
+pub struct SqlSessionStore {
+    pool: DatabasePool,
+    table: String,
+}
+
+impl SqlSessionStore {
+    /// Store sessions in the `sessions` table
+    pub fn new(pool: DatabasePool) -> Self {
+        Self {
+            pool,
+            table: "sessions".to_string(),
+        }
+    }
+
+    /// Use a different table name
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.to_string();
+        self
+    }
+
+    /// Create the session table if it does not exist
+    pub async fn migrate(&self) -> Result<(), SessionError> {
+        let t = &self.table;
+        let statements = match self.pool.db_type() {
+            DatabaseType::MySql => vec![format!(
+                "CREATE TABLE IF NOT EXISTS {t} (id VARCHAR(128) PRIMARY KEY, data TEXT NOT NULL, \
+                 expires_at BIGINT NOT NULL, INDEX idx_{t}_expires_at (expires_at))"
+            )],
+            _ => vec![
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {t} (id VARCHAR(128) PRIMARY KEY, data TEXT NOT NULL, \
+                     expires_at BIGINT NOT NULL)"
+                ),
+                format!("CREATE INDEX IF NOT EXISTS idx_{t}_expires_at ON {t} (expires_at)"),
+            ],
+        };
+        for sql in statements {
+            on_pool!(&self.pool, p => sqlx::query(&sql).execute(p).await.map(|r| r.rows_affected()))
+                .map_err(|e| SessionError::StorageError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<SessionData>, SessionError> {
+        let sql = format!(
+            "SELECT data FROM {} WHERE id = {} AND expires_at > {}",
+            self.table,
+            self.pool.placeholder(1),
+            self.pool.placeholder(2)
+        );
+        let now = Utc::now().timestamp();
+        let row: Option<(String,)> = on_pool!(&self.pool, p => {
+            sqlx::query_as(&sql).bind(id).bind(now).fetch_optional(p).await
+        })
+        .map_err(|e| SessionError::StorageError(e.to_string()))?;
+        row.map(|(json,)| serde_json::from_str(&json).map_err(SessionError::from))
+            .transpose()
+    }
+
+    async fn store(&self, id: &str, data: &SessionData) -> Result<(), SessionError> {
+        let (p1, p2, p3) = (
+            self.pool.placeholder(1),
+            self.pool.placeholder(2),
+            self.pool.placeholder(3),
+        );
+        let sql = match self.pool.db_type() {
+            DatabaseType::MySql => format!(
+                "INSERT INTO {} (id, data, expires_at) VALUES ({p1}, {p2}, {p3}) \
+                 ON DUPLICATE KEY UPDATE data = VALUES(data), expires_at = VALUES(expires_at)",
+                self.table
+            ),
+            _ => format!(
+                "INSERT INTO {} (id, data, expires_at) VALUES ({p1}, {p2}, {p3}) \
+                 ON CONFLICT (id) DO UPDATE SET data = excluded.data, expires_at = excluded.expires_at",
+                self.table
+            ),
+        };
+        let json = serde_json::to_string(data)?;
+        let expires_at = data.expires_at.timestamp();
+        on_pool!(&self.pool, p => {
+            sqlx::query(&sql).bind(id).bind(&json).bind(expires_at).execute(p).await.map(|r| r.rows_affected())
+        })
+        .map_err(|e| SessionError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), SessionError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE id = {}",
+            self.table,
+            self.pool.placeholder(1)
+        );
+        on_pool!(&self.pool, p => sqlx::query(&sql).bind(id).execute(p).await.map(|r| r.rows_affected()))
+            .map_err(|e| SessionError::StorageError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove_expired(&self) -> Result<usize, SessionError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE expires_at <= {}",
+            self.table,
+            self.pool.placeholder(1)
+        );
+        let now = Utc::now().timestamp();
+        let removed = on_pool!(&self.pool, p => {
+            sqlx::query(&sql).bind(now).execute(p).await.map(|r| r.rows_affected())
+        })
+        .map_err(|e| SessionError::StorageError(e.to_string()))?;
+        Ok(removed as usize)
+    }
+}
+
+// The trait has no error channel; failures are logged and treated as a miss
+#[async_trait::async_trait]
+impl SessionStore for SqlSessionStore {
+    async fn get(&self, id: &str) -> Option<SessionData> {
+        self.load(id)
+            .await
+            .unwrap_or_else(|e| log_store_error("load", e))
+    }
+
+    async fn set(&self, id: &str, data: SessionData) {
+        if let Err(e) = self.store(id, &data).await {
+            log_store_error::<()>("save", e);
+        }
+    }
+
+    async fn delete(&self, id: &str) {
+        if let Err(e) = self.remove(id).await {
+            log_store_error::<()>("delete", e);
+        }
+    }
+
+    async fn cleanup_expired(&self) -> usize {
+        self.remove_expired()
+            .await
+            .unwrap_or_else(|e| log_store_error("cleanup", e))
+    }
+}
+
+fn log_store_error<T: Default>(op: &str, err: SessionError) -> T {
+    tracing::warn!(error = %err, "session store {} failed", op);
+    T::default()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// REDIS SESSION STORE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Session store on top of `RedisBackend`; Redis expires the keys itself
+#[derive(Clone)]
+pub struct RedisSessionStore {
+    backend: RedisBackend,
+    prefix: String,
+}
+
+impl RedisSessionStore {
+    /// Store sessions under `session:<id>`
+    pub fn new(backend: RedisBackend) -> Self {
+        Self {
+            backend,
+            prefix: "session:".to_string(),
+        }
+    }
+
+    /// Use a different key prefix
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn get(&self, id: &str) -> Option<SessionData> {
+        let bytes = match self.backend.get_bytes(&self.key(id)).await {
+            Ok(bytes) => bytes?,
+            Err(e) => return log_store_error("load", SessionError::StorageError(e.to_string())),
+        };
+        match serde_json::from_slice::<SessionData>(&bytes) {
+            Ok(data) if !data.is_expired() => Some(data),
+            Ok(_) => None,
+            Err(e) => log_store_error("load", e.into()),
+        }
+    }
+
+    async fn set(&self, id: &str, data: SessionData) {
+        let ttl = (data.expires_at - Utc::now()).num_seconds().max(1) as u64;
+        let result = match serde_json::to_vec(&data) {
+            Ok(bytes) => self
+                .backend
+                .set_bytes(&self.key(id), bytes, ttl)
+                .await
+                .map_err(|e| SessionError::StorageError(e.to_string())),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            log_store_error::<()>("save", e);
+        }
+    }
+
+    async fn delete(&self, id: &str) {
+        if let Err(e) = self.backend.delete(&self.key(id)).await {
+            log_store_error::<()>("delete", SessionError::StorageError(e.to_string()));
+        }
+    }
+
+    async fn cleanup_expired(&self) -> usize {
+        0
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // SESSION CONFIG
 // ═══════════════════════════════════════════════════════════════════════════
@@ -253,6 +520,10 @@ pub struct SessionConfig {
     pub ttl: Duration,
     /// Regenerate session ID on login
     pub regenerate_on_login: bool,
+    /// Extend the expiry while the session is in use
+    pub rolling: bool,
+    /// Encrypt the session ID in the cookie instead of only signing it
+    pub cookie_encrypted: bool,
 }
 
 impl Default for SessionConfig {
@@ -265,6 +536,8 @@ impl Default for SessionConfig {
             cookie_same_site: SameSite::Lax,
             ttl: Duration::hours(24),
             regenerate_on_login: true,
+            rolling: true,
+            cookie_encrypted: false,
         }
     }
 }
@@ -298,6 +571,18 @@ impl SessionConfig {
         self.cookie_name = name.to_string();
         self
     }
+
+    /// Enable or disable rolling expiry
+    pub fn rolling(mut self, rolling: bool) -> Self {
+        self.rolling = rolling;
+        self
+    }
+
+    /// Encrypt the cookie value (AES-256-GCM)
+    pub fn encrypted(mut self) -> Self {
+        self.cookie_encrypted = true;
+        self
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -309,6 +594,10 @@ pub struct Session {
     data: SessionData,
     modified: bool,
     destroyed: bool,
+    /// ID the session was loaded under; differs from `data.id` after `regenerate()`
+    loaded_id: Option<String>,
+    /// Where `SessionLayer` expects the session back once the handler drops it
+    slot: Option<SessionSlot>,
 }
 
 impl Session {
@@ -318,15 +607,26 @@ impl Session {
             data: SessionData::new(ttl),
             modified: true,
             destroyed: false,
+            loaded_id: None,
+            slot: None,
         }
     }
 
+    /// New session that is only persisted once something is written to it
+    fn fresh(ttl: Duration) -> Self {
+        let mut session = Self::new(ttl);
+        session.modified = false;
+        session
+    }
+
     /// Load from existing session data
     pub fn from_data(data: SessionData) -> Self {
         Self {
+            loaded_id: Some(data.id.clone()),
             data,
             modified: false,
             destroyed: false,
+            slot: None,
         }
     }
 
@@ -489,8 +789,52 @@ impl Session {
     }
 
     /// Consume and get underlying data
-    pub fn into_data(self) -> SessionData {
-        self.data
+    pub fn into_data(mut self) -> SessionData {
+        self.slot = None;
+        std::mem::replace(&mut self.data, SessionData::new(Duration::zero()))
+    }
+
+    /// Whether the session was loaded from the store
+    pub(crate) fn is_stored(&self) -> bool {
+        self.loaded_id.is_some()
+    }
+
+    /// ID the session is stored under once the request finishes, if any
+    pub(crate) fn stored_id(&self) -> Option<&str> {
+        let stored = !self.destroyed && (self.loaded_id.is_some() || self.modified);
+        stored.then_some(self.data.id.as_str())
+    }
+
+    /// Store a fresh session even if nothing else changes, so the CSRF token
+    /// rendered into a form is still valid when the form is submitted
+    pub(crate) fn retain(&mut self) {
+        if self.loaded_id.is_none() {
+            self.modified = true;
+        }
+    }
+
+    /// Push the expiry out to `ttl` from now
+    fn extend(&mut self, ttl: Duration) {
+        self.data.expires_at = Utc::now() + ttl;
+        self.modified = true;
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Hand the state back to the layer so it can be saved after the response
+        if let Some(slot) = self.slot.take() {
+            let session = Session {
+                data: std::mem::replace(&mut self.data, SessionData::new(Duration::zero())),
+                modified: self.modified,
+                destroyed: self.destroyed,
+                loaded_id: self.loaded_id.take(),
+                slot: None,
+            };
+            if let Ok(mut returned) = slot.lock() {
+                *returned = Some(session);
+            }
+        }
     }
 }
 
@@ -520,7 +864,17 @@ impl<S: SessionStore> SessionManager<S> {
     }
 
     /// Save session
+    ///
+    /// A regenerated session is stored under its new ID and the old one is
+    /// deleted, so a fixated ID stops working.
     pub async fn save(&self, session: &Session) {
+        if let Some(old) = session
+            .loaded_id
+            .as_deref()
+            .filter(|old| *old != session.id())
+        {
+            self.store.delete(old).await;
+        }
         if session.is_destroyed() {
             self.store.delete(session.id()).await;
         } else if session.is_modified() {
@@ -548,6 +902,346 @@ impl<S: SessionStore + Clone> Clone for SessionManager<S> {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// SESSION LAYER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Hand-off point between `SessionLayer` and the `Session` extractor
+pub(crate) type SessionSlot = Arc<std::sync::Mutex<Option<Session>>>;
+
+/// Tower layer that loads the session named by the cookie and saves it after
+/// the handler returns
+///
+/// Only modified sessions are written. With `rolling` enabled the expiry is
+/// pushed out on writes and once less than half the TTL remains. A session
+/// `regenerate()`d by the handler is moved to its new ID and the old one is
+/// deleted from the store.
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/cart", post(add_to_cart))
+///     .layer(SessionLayer::new(store, SessionConfig::production()));
+///
+/// async fn add_to_cart(mut session: Session) -> &'static str {
+///     let count: i32 = session.get("cart_items").unwrap_or(0);
+///     session.set("cart_items", count + 1);
+///     "added"
+/// }
+/// ```
+pub struct SessionLayer<S: SessionStore> {
+    shared: Arc<LayerShared<S>>,
+}
+
+struct LayerShared<S: SessionStore> {
+    manager: SessionManager<S>,
+    codec: CookieCodec,
+}
+
+impl<S: SessionStore> SessionLayer<S> {
+    /// Sign cookies with `app.secret_key` / `SECRET_KEY`
+    ///
+    /// Without either, a random key is used and sessions do not survive a restart.
+    pub fn new(store: S, config: SessionConfig) -> Self {
+        let secret = app_secret().unwrap_or_else(|| {
+            tracing::warn!("no app.secret_key or SECRET_KEY set; using a random session key");
+            let mut key = vec![0u8; 32];
+            SystemRandom::new()
+                .fill(&mut key)
+                .expect("system random session key");
+            key
+        });
+        Self::with_secret(store, config, secret)
+    }
+
+    /// Sign (and, if configured, encrypt) cookies with an explicit secret
+    pub fn with_secret(store: S, config: SessionConfig, secret: impl AsRef<[u8]>) -> Self {
+        let codec = CookieCodec::new(secret.as_ref(), &config);
+        Self {
+            shared: Arc::new(LayerShared {
+                manager: SessionManager::new(store, config),
+                codec,
+            }),
+        }
+    }
+}
+
+impl<S: SessionStore> Clone for SessionLayer<S> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<S: SessionStore, I> tower::Layer<I> for SessionLayer<S> {
+    type Service = SessionService<S, I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        SessionService {
+            inner,
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+/// Service produced by `SessionLayer`
+pub struct SessionService<S: SessionStore, I> {
+    inner: I,
+    shared: Arc<LayerShared<S>>,
+}
+
+impl<S: SessionStore, I: Clone> Clone for SessionService<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<S, I> tower::Service<Request> for SessionService<S, I>
+where
+    S: SessionStore + 'static,
+    I: tower::Service<Request, Response = Response> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = I::Error;
+    type Future = futures_util::future::BoxFuture<'static, Result<Response, I::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // The clone is not necessarily ready; keep the one that was polled
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let shared = Arc::clone(&self.shared);
+
+        Box::pin(async move {
+            let config = shared.manager.config();
+            let cookie = read_cookie(req.headers(), &config.cookie_name);
+            let loaded = match cookie.as_deref().and_then(|v| shared.codec.decode(v)) {
+                Some(id) => shared.manager.load(&id).await,
+                None => None,
+            };
+            let session = loaded.unwrap_or_else(|| Session::fresh(config.ttl));
+
+            let slot: SessionSlot = Arc::new(std::sync::Mutex::new(Some(session)));
+            req.extensions_mut().insert(Arc::clone(&slot));
+
+            let mut response = inner.call(req).await?;
+
+            let session = slot.lock().ok().and_then(|mut s| s.take());
+            if let Some(session) = session {
+                shared
+                    .finish(session, cookie.is_some(), &mut response)
+                    .await;
+            }
+            Ok(response)
+        })
+    }
+}
+
+impl<S: SessionStore> LayerShared<S> {
+    async fn finish(&self, mut session: Session, had_cookie: bool, response: &mut Response) {
+        let config = self.manager.config();
+
+        if session.is_destroyed() {
+            self.manager.save(&session).await;
+            if had_cookie {
+                self.set_cookie(response, "", 0);
+            }
+            return;
+        }
+
+        if config.rolling {
+            let remaining = session.data.expires_at - Utc::now();
+            if session.is_modified() || remaining < config.ttl / 2 {
+                session.extend(config.ttl);
+            }
+        }
+        if !session.is_modified() {
+            return;
+        }
+
+        self.manager.save(&session).await;
+        let remaining = session.data.expires_at - Utc::now();
+        let max_age = ((remaining.num_milliseconds() + 999) / 1000).max(0);
+        self.set_cookie(response, &self.codec.encode(session.id()), max_age);
+    }
+
+    fn set_cookie(&self, response: &mut Response, value: &str, max_age: i64) {
+        let config = self.manager.config();
+        let mut cookie = format!(
+            "{}={}; Path={}; Max-Age={}",
+            config.cookie_name, value, config.cookie_path, max_age
+        );
+        if config.cookie_http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if config.cookie_secure {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(match config.cookie_same_site {
+            SameSite::Strict => "; SameSite=Strict",
+            SameSite::Lax => "; SameSite=Lax",
+            SameSite::None => "; SameSite=None",
+        });
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+}
+
+pub(crate) fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v.to_string())
+}
+
+pub(crate) fn app_secret() -> Option<Vec<u8>> {
+    if !GLOBAL_CONFIG.app.secret_key.is_empty() {
+        return Some(GLOBAL_CONFIG.app.secret_key.as_bytes().to_vec());
+    }
+    std::env::var("SECRET_KEY")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(String::into_bytes)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Cookie codec
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Turns session IDs into cookie values: `id.signature`, or an opaque
+/// AES-256-GCM blob when `cookie_encrypted` is set
+struct CookieCodec {
+    sign_key: Vec<u8>,
+    seal_key: Option<aead::LessSafeKey>,
+    cookie_name: String,
+}
+
+impl CookieCodec {
+    fn new(secret: &[u8], config: &SessionConfig) -> Self {
+        // Separate keys per purpose, derived from the one app secret
+        let derive = |label: &[u8]| {
+            let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+            mac.update(label);
+            mac.finalize().into_bytes().to_vec()
+        };
+        let seal_key = config.cookie_encrypted.then(|| {
+            let key =
+                aead::UnboundKey::new(&aead::AES_256_GCM, &derive(b"nucleus.session.encrypt"))
+                    .expect("32-byte AES key");
+            aead::LessSafeKey::new(key)
+        });
+        Self {
+            sign_key: derive(b"nucleus.session.sign"),
+            seal_key,
+            cookie_name: config.cookie_name.clone(),
+        }
+    }
+
+    fn encode(&self, id: &str) -> String {
+        match &self.seal_key {
+            Some(key) => {
+                // 96 random bits per cookie; a repeated nonce would leak the key stream
+                let mut nonce = [0u8; aead::NONCE_LEN];
+                SystemRandom::new()
+                    .fill(&mut nonce)
+                    .expect("system random nonce");
+                let mut sealed = id.as_bytes().to_vec();
+                key.seal_in_place_append_tag(
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::from(self.cookie_name.as_bytes()),
+                    &mut sealed,
+                )
+                .expect("AES-GCM seal");
+                let mut out = nonce.to_vec();
+                out.extend_from_slice(&sealed);
+                URL_SAFE_NO_PAD.encode(out)
+            }
+            None => format!(
+                "{}.{}",
+                id,
+                URL_SAFE_NO_PAD.encode(self.mac(id).finalize().into_bytes())
+            ),
+        }
+    }
+
+    fn decode(&self, value: &str) -> Option<String> {
+        match &self.seal_key {
+            Some(key) => {
+                let raw = URL_SAFE_NO_PAD.decode(value).ok()?;
+                if raw.len() < aead::NONCE_LEN {
+                    return None;
+                }
+                let (nonce, sealed) = raw.split_at(aead::NONCE_LEN);
+                let nonce = aead::Nonce::try_assume_unique_for_key(nonce).ok()?;
+                let mut sealed = sealed.to_vec();
+                let id = key
+                    .open_in_place(
+                        nonce,
+                        aead::Aad::from(self.cookie_name.as_bytes()),
+                        &mut sealed,
+                    )
+                    .ok()?;
+                String::from_utf8(id.to_vec()).ok()
+            }
+            None => {
+                let (id, signature) = value.rsplit_once('.')?;
+                let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+                self.mac(id).verify_slice(&signature).ok()?;
+                Some(id.to_string())
+            }
+        }
+    }
+
+    fn mac(&self, id: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.sign_key).expect("HMAC accepts any key length");
+        mac.update(id.as_bytes());
+        mac
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Extractor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Takes the request's session from `SessionLayer`. Changes are saved when the
+/// `Session` is dropped, so middleware extracting it must drop it before
+/// calling the next service.
+#[axum::async_trait]
+impl<St: Send + Sync> FromRequestParts<St> for Session {
+    type Rejection = SessionError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &St) -> Result<Self, Self::Rejection> {
+        let slot = parts
+            .extensions
+            .get::<SessionSlot>()
+            .cloned()
+            .ok_or(SessionError::Unavailable)?;
+        let mut session = slot
+            .lock()
+            .ok()
+            .and_then(|mut s| s.take())
+            .ok_or(SessionError::Unavailable)?;
+        session.slot = Some(slot);
+        Ok(session)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // TESTS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -908,6 +1602,18 @@ mod tests {
         assert!(store.get(session.id()).await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_boxed_store_delegates() {
+        let store: Box<dyn SessionStore> = Box::new(MemorySessionStore::new());
+        let session = Session::new(Duration::hours(1));
+
+        store.set(session.id(), session.data().clone()).await;
+        assert!(store.get(session.id()).await.is_some());
+
+        store.delete(session.id()).await;
+        assert!(store.get(session.id()).await.is_none());
+    }
+
     #[tokio::test]
     async fn test_memory_store_cleanup() {
         let store = MemorySessionStore::new();
@@ -1008,4 +1714,245 @@ mod tests {
 
         assert!(manager.load(&id).await.is_none());
     }
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // SESSION LAYER
+    // ═══════════════════════════════════════════════════════════════════════
+
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn session_app(store: MemorySessionStore, config: SessionConfig) -> Router {
+        Router::new()
+            .route(
+                "/set",
+                get(|mut session: Session| async move {
+                    session.set("name", "ada");
+                    "ok"
+                }),
+            )
+            .route(
+                "/get",
+                get(|session: Session| async move {
+                    session.get::<String>("name").unwrap_or_default()
+                }),
+            )
+            .route(
+                "/login",
+                get(|mut session: Session| async move {
+                    session.login("user_1", true);
+                    "ok"
+                }),
+            )
+            .route(
+                "/logout",
+                get(|mut session: Session| async move {
+                    session.destroy();
+                    "ok"
+                }),
+            )
+            .layer(SessionLayer::with_secret(store, config, "test-secret"))
+    }
+
+    async fn send(app: &Router, path: &str, cookie: Option<&str>) -> (Option<String>, String) {
+        let mut req = axum::http::Request::get(path);
+        if let Some(cookie) = cookie {
+            req = req.header(header::COOKIE, cookie);
+        }
+        let res = app
+            .clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let set_cookie = res
+            .headers()
+            .get(header::SET_COOKIE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(res.into_body(), 1024).await.unwrap();
+        (set_cookie, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn cookie_pair(set_cookie: &str) -> &str {
+        set_cookie.split(';').next().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_layer_persists_modified_session() {
+        let app = session_app(MemorySessionStore::new(), SessionConfig::development());
+
+        // Reading a fresh session neither stores it nor sets a cookie
+        let (set_cookie, body) = send(&app, "/get", None).await;
+        assert!(set_cookie.is_none());
+        assert_eq!(body, "");
+
+        let (set_cookie, _) = send(&app, "/set", None).await;
+        let set_cookie = set_cookie.expect("cookie issued");
+        assert!(set_cookie.contains("HttpOnly"));
+        assert!(set_cookie.contains("Max-Age=86400"));
+
+        let (again, body) = send(&app, "/get", Some(cookie_pair(&set_cookie))).await;
+        assert_eq!(body, "ada");
+        assert!(again.is_none(), "unmodified session is not rewritten");
+    }
+
+    #[tokio::test]
+    async fn test_layer_rejects_tampered_cookie() {
+        let store = MemorySessionStore::new();
+        let app = session_app(store.clone(), SessionConfig::development());
+        let (set_cookie, _) = send(&app, "/set", None).await;
+        let pair = cookie_pair(set_cookie.as_deref().unwrap()).to_string();
+
+        // Valid ID with a forged signature
+        let id = pair.split_once('=').unwrap().1.split_once('.').unwrap().0;
+        let forged = format!("nucleus_session={}.AAAA", id);
+        assert_eq!(send(&app, "/get", Some(&forged)).await.1, "");
+
+        // Raw ID without any signature
+        let bare = format!("nucleus_session={}", id);
+        assert_eq!(send(&app, "/get", Some(&bare)).await.1, "");
+        assert!(store.get(id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_layer_regenerate_drops_old_id() {
+        let store = MemorySessionStore::new();
+        let app = session_app(store.clone(), SessionConfig::development());
+        let (first, _) = send(&app, "/set", None).await;
+        let first = cookie_pair(first.as_deref().unwrap()).to_string();
+        let old_id = first
+            .split_once('=')
+            .unwrap()
+            .1
+            .split_once('.')
+            .unwrap()
+            .0
+            .to_string();
+
+        let (second, _) = send(&app, "/login", Some(&first)).await;
+        let second = cookie_pair(second.as_deref().unwrap()).to_string();
+        assert_ne!(first, second);
+        assert!(store.get(&old_id).await.is_none());
+
+        // Data carries over to the new ID, the old cookie no longer works
+        assert_eq!(send(&app, "/get", Some(&second)).await.1, "ada");
+        assert_eq!(send(&app, "/get", Some(&first)).await.1, "");
+    }
+
+    #[tokio::test]
+    async fn test_layer_destroy_expires_cookie() {
+        let store = MemorySessionStore::new();
+        let app = session_app(store.clone(), SessionConfig::development());
+        let (set_cookie, _) = send(&app, "/set", None).await;
+        let pair = cookie_pair(set_cookie.as_deref().unwrap()).to_string();
+
+        let (cleared, _) = send(&app, "/logout", Some(&pair)).await;
+        assert!(cleared.unwrap().contains("Max-Age=0"));
+        assert_eq!(send(&app, "/get", Some(&pair)).await.1, "");
+        assert_eq!(store.cleanup_expired().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_layer_rolling_expiry() {
+        let store = MemorySessionStore::new();
+        let mut data = SessionData::new(Duration::hours(1));
+        data.data.insert("name".into(), "ada".into());
+        let id = data.id.clone();
+        store.set(&id, data).await;
+
+        let layer =
+            SessionLayer::with_secret(store.clone(), SessionConfig::development(), "test-secret");
+        let cookie = format!("nucleus_session={}", layer.shared.codec.encode(&id));
+        let app = session_app(store.clone(), SessionConfig::development());
+
+        // An hour left of a 24 hour TTL is under half, so the expiry is pushed out
+        let (set_cookie, body) = send(&app, "/get", Some(&cookie)).await;
+        assert_eq!(body, "ada");
+        assert!(set_cookie.unwrap().contains("Max-Age=86"));
+        assert!(store.get(&id).await.unwrap().expires_at > Utc::now() + Duration::hours(23));
+
+        // Without rolling the original expiry stands
+        let fixed = session_app(store.clone(), SessionConfig::development().rolling(false));
+        let mut data = store.get(&id).await.unwrap();
+        data.expires_at = Utc::now() + Duration::hours(1);
+        store.set(&id, data).await;
+        assert!(send(&fixed, "/get", Some(&cookie)).await.0.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_layer_encrypted_cookie() {
+        let store = MemorySessionStore::new();
+        let app = session_app(store.clone(), SessionConfig::development().encrypted());
+        let (set_cookie, _) = send(&app, "/set", None).await;
+        let pair = cookie_pair(set_cookie.as_deref().unwrap()).to_string();
+
+        let ids: Vec<String> = store.sessions.read().await.keys().cloned().collect();
+        assert_eq!(ids.len(), 1);
+        assert!(
+            !pair.contains(&ids[0]),
+            "session ID is not visible in the cookie"
+        );
+        assert_eq!(send(&app, "/get", Some(&pair)).await.1, "ada");
+
+        // A signing-only layer with the same secret cannot read it
+        let signed = session_app(store, SessionConfig::development());
+        assert_eq!(send(&signed, "/get", Some(&pair)).await.1, "");
+    }
+
+    #[test]
+    fn test_sealed_cookies_use_fresh_nonces() {
+        let codec = CookieCodec::new(b"secret", &SessionConfig::development().encrypted());
+        let (a, b) = (codec.encode("session-1"), codec.encode("session-1"));
+        let nonce = |v: &str| URL_SAFE_NO_PAD.decode(v).unwrap()[..aead::NONCE_LEN].to_vec();
+        assert_ne!(nonce(&a), nonce(&b));
+        assert_eq!(codec.decode(&a).as_deref(), Some("session-1"));
+        assert_eq!(codec.decode(&b).as_deref(), Some("session-1"));
+    }
+
+    #[tokio::test]
+    async fn test_extractor_without_layer() {
+        let app = Router::new().route("/", get(|_session: Session| async { "unreachable" }));
+        let res = app
+            .oneshot(axum::http::Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_sql_session_store() {
+        let pool = DatabasePool::connect_with_max("sqlite::memory:", 1)
+            .await
+            .unwrap();
+        let store = SqlSessionStore::new(pool);
+        store.migrate().await.unwrap();
+        store.migrate().await.unwrap();
+
+        let mut session = Session::new(Duration::hours(1));
+        session.set("cart", vec![1, 2, 3]);
+        let id = session.id().to_string();
+        store.set(&id, session.data().clone()).await;
+
+        // Upsert replaces the row
+        let mut data = store.get(&id).await.unwrap();
+        assert_eq!(
+            Session::from_data(data.clone()).get::<Vec<i32>>("cart"),
+            Some(vec![1, 2, 3])
+        );
+        data.user_id = Some("user_1".into());
+        store.set(&id, data).await;
+        assert_eq!(
+            store.get(&id).await.unwrap().user_id.as_deref(),
+            Some("user_1")
+        );
+
+        let expired = SessionData::new(Duration::seconds(-5));
+        store.set(&expired.id.clone(), expired.clone()).await;
+        assert!(store.get(&expired.id).await.is_none());
+        assert_eq!(store.cleanup_expired().await, 1);
+
+        store.delete(&id).await;
+        assert!(store.get(&id).await.is_none());
+    }
 }
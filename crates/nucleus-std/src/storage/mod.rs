@@ -0,0 +1,1120 @@
+//! Nucleus Storage - Pluggable Object Storage
+//!
+//! Stores objects (bytes plus a content type) under keys like
+//! `avatars/3f2c.png`. Backends:
+//! - `LocalStorage`: a directory on disk
+//! - `MemoryStorage`: a map in memory, for tests
+//! - `S3Storage`: any S3-compatible service (AWS S3, MinIO, R2, ...)
+//!
+//! Large objects can be written from a stream with `put_stream`, which keeps
+//! only a bounded buffer in memory.
+//!
+//! Presigned URLs let clients download or upload one object directly until
+//! they expire. S3 signs them itself; local and memory storage sign them with
+//! a `UrlSigner` and serve them with `signed_routes`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::storage::{S3Storage, Storage};
+//!
+//! let storage = S3Storage::new("http://localhost:9000", "uploads", "minio", "minio123")
+//!     .with_path_style(true);
+//!
+//! storage.put("avatars/1.png", bytes, "image/png").await?;
+//! let url = storage.presigned_get("avatars/1.png", Duration::from_secs(300)).await?;
+//! ```
+
+use axum::body::Bytes;
+use axum::extract::{Path as RoutePath, Query, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use futures_util::TryStreamExt;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+mod s3;
+
+pub use s3::S3Storage;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Chunks of an object written with `put_stream` or read with `get_stream`
+pub type ByteStream<'a> = BoxStream<'a, Result<Bytes, StorageError>>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A stored object's contents
+#[derive(Debug, Clone)]
+pub struct Object {
+    pub data: Bytes,
+    pub content_type: String,
+}
+
+/// A stored object's metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMeta {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// Storage error types
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Object not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid key: {0:?}")]
+    InvalidKey(String),
+
+    #[error("Object already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Not supported: {0}")]
+    Unsupported(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+}
+
+/// Object storage backend
+///
+/// Keys are `/`-separated relative paths; see [`validate_key`].
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync + fmt::Debug {
+    /// Store `data` under `key`, replacing any existing object
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError>;
+
+    /// Store the chunks of `stream` under `key`. If the stream yields an
+    /// error nothing is stored and that error is returned.
+    ///
+    /// The default buffers the whole stream and calls `put`; backends that
+    /// can write incrementally override it.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        let chunks: Vec<Bytes> = stream.try_collect().await?;
+        self.put(key, chunks.concat().into(), content_type).await
+    }
+
+    /// Like `put_stream`, but only if `key` doesn't exist yet; when another
+    /// writer stores it first this fails with `AlreadyExists`
+    ///
+    /// The default checks `exists` before writing, which is not atomic;
+    /// backends with conditional writes override it.
+    async fn put_stream_if_absent(
+        &self,
+        key: &str,
+        stream: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        if self.exists(key).await? {
+            return Err(StorageError::AlreadyExists(key.to_string()));
+        }
+        self.put_stream(key, stream, content_type).await
+    }
+
+    /// Read an object; `NotFound` if it doesn't exist
+    async fn get(&self, key: &str) -> Result<Object, StorageError>;
+
+    /// Read an object as a stream of chunks; `NotFound` if it doesn't exist
+    ///
+    /// The default reads the whole object with `get`; backends that can read
+    /// incrementally override it.
+    async fn get_stream(&self, key: &str) -> Result<ByteStream<'static>, StorageError> {
+        let data = self.get(key).await?.data;
+        Ok(Box::pin(futures_util::stream::once(
+            async move { Ok(data) },
+        )))
+    }
+
+    /// Metadata of an object, if it exists
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, StorageError>;
+
+    /// Delete an object; deleting a missing object succeeds
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// All objects whose key starts with `prefix`, sorted by key
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, StorageError>;
+
+    /// Public URL of an object (not signed)
+    fn url(&self, key: &str) -> String;
+
+    /// URL that downloads the object until `expires_in` has passed
+    async fn presigned_get(&self, key: &str, expires_in: Duration) -> Result<String, StorageError>;
+
+    /// URL that accepts a `PUT` of the object until `expires_in` has passed;
+    /// the request must send `content_type` as its `Content-Type`
+    async fn presigned_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<String, StorageError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(self.head(key).await?.is_some())
+    }
+}
+
+/// Check that `key` is a relative `/`-separated path without `.` or `..`
+/// segments, so it maps safely onto a directory or a bucket
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.len() <= 1024
+        && !key.contains('\\')
+        && !key.chars().any(char::is_control)
+        && key
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+/// Percent-encode each segment of a key, keeping the `/` separators
+pub(crate) fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SIGNED URLS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Signs and verifies presigned URLs for `LocalStorage` and `MemoryStorage`
+///
+/// URLs look like `{base_url}/{key}?expires=..&signature=..`. Serve them
+/// with [`signed_routes`] mounted at `base_url`.
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: Arc<Vec<u8>>,
+    base_url: String,
+}
+
+impl fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlSigner")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl UrlSigner {
+    pub fn new(secret: impl AsRef<[u8]>, base_url: &str) -> Self {
+        Self {
+            key: Arc::new(secret.as_ref().to_vec()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Presigned URL for `method` on `key`; `content_type` is only checked
+    /// for uploads
+    pub fn sign(
+        &self,
+        method: &Method,
+        key: &str,
+        content_type: Option<&str>,
+        expires_in: Duration,
+    ) -> String {
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+        format!(
+            "{}/{}?expires={}&signature={}",
+            self.base_url,
+            encode_key(key),
+            expires,
+            hex::encode(
+                self.mac(method, key, content_type, expires)
+                    .finalize()
+                    .into_bytes()
+            )
+        )
+    }
+
+    /// Whether `signature` was issued for this request and hasn't expired
+    pub fn verify(
+        &self,
+        method: &Method,
+        key: &str,
+        content_type: Option<&str>,
+        expires: i64,
+        signature: &str,
+    ) -> bool {
+        expires >= Utc::now().timestamp()
+            && hex::decode(signature).is_ok_and(|sig| {
+                self.mac(method, key, content_type, expires)
+                    .verify_slice(&sig)
+                    .is_ok()
+            })
+    }
+
+    fn mac(
+        &self,
+        method: &Method,
+        key: &str,
+        content_type: Option<&str>,
+        expires: i64,
+    ) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(
+            format!(
+                "{}\n{}\n{}\n{}",
+                method,
+                key,
+                content_type.unwrap_or(""),
+                expires
+            )
+            .as_bytes(),
+        );
+        mac
+    }
+}
+
+#[derive(Deserialize)]
+struct SignedQuery {
+    expires: i64,
+    signature: String,
+}
+
+/// Routes serving presigned URLs issued by `signer`: `GET /{key}` downloads
+/// and `PUT /{key}` uploads. Nest them at the signer's base URL:
+///
+/// ```rust,ignore
+/// let app = Router::new().nest("/files", signed_routes(storage.clone(), signer));
+/// ```
+///
+/// Uploads go through axum's default body limit; raise it with
+/// `DefaultBodyLimit` for large files.
+pub fn signed_routes(storage: Arc<dyn Storage>, signer: UrlSigner) -> Router {
+    Router::new()
+        .route("/*key", get(signed_get).put(signed_put))
+        .with_state((storage, signer))
+}
+
+async fn signed_get(
+    State((storage, signer)): State<(Arc<dyn Storage>, UrlSigner)>,
+    RoutePath(key): RoutePath<String>,
+    Query(query): Query<SignedQuery>,
+) -> Response {
+    if !signer.verify(&Method::GET, &key, None, query.expires, &query.signature) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match storage.get(&key).await {
+        Ok(object) => ([(header::CONTENT_TYPE, object.content_type)], object.data).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+async fn signed_put(
+    State((storage, signer)): State<(Arc<dyn Storage>, UrlSigner)>,
+    RoutePath(key): RoutePath<String>,
+    Query(query): Query<SignedQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !signer.verify(
+        &Method::PUT,
+        &key,
+        Some(content_type),
+        query.expires,
+        &query.signature,
+    ) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match storage.put(&key, body, content_type).await {
+        Ok(()) => StatusCode::CREATED.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+fn error_response(error: StorageError) -> Response {
+    match error {
+        StorageError::NotFound(_) => StatusCode::NOT_FOUND.into_response(),
+        StorageError::InvalidKey(_) => StatusCode::BAD_REQUEST.into_response(),
+        e => {
+            tracing::error!("Storage error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn signed_url(
+    signer: Option<&UrlSigner>,
+    method: Method,
+    key: &str,
+    content_type: Option<&str>,
+    expires_in: Duration,
+) -> Result<String, StorageError> {
+    validate_key(key)?;
+    let signer = signer.ok_or_else(|| {
+        StorageError::Unsupported("presigned URLs need a UrlSigner (use with_signer)".to_string())
+    })?;
+    Ok(signer.sign(&method, key, content_type, expires_in))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// LOCAL STORAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Directory under the root for files being streamed in; hidden from `list`
+const LOCAL_TMP_DIR: &str = ".nucleus-tmp";
+
+/// Stores objects as files under a root directory
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+    signer: Option<UrlSigner>,
+}
+
+impl LocalStorage {
+    /// Store under `root`, served at `/uploads`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: "/uploads".to_string(),
+            signer: None,
+        }
+    }
+
+    /// URL prefix the directory is served at
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Enable presigned URLs
+    pub fn with_signer(mut self, signer: UrlSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+
+    fn meta(key: String, metadata: &std::fs::Metadata) -> ObjectMeta {
+        ObjectMeta {
+            key,
+            size: metadata.len(),
+            last_modified: metadata.modified().map(DateTime::from).unwrap_or_default(),
+        }
+    }
+
+    /// Write `stream` to a temporary file and move it into place once
+    /// complete; with `if_absent` the move fails if `key` exists
+    async fn write_stream(
+        &self,
+        key: &str,
+        mut stream: ByteStream<'_>,
+        if_absent: bool,
+    ) -> Result<(), StorageError> {
+        use tokio::io::AsyncWriteExt;
+
+        // Readers never see a partial object
+        let path = self.path(key)?;
+        let tmp_dir = self.root.join(LOCAL_TMP_DIR);
+        tokio::fs::create_dir_all(&tmp_dir).await?;
+        let tmp = tmp_dir.join(uuid::Uuid::new_v4().simple().to_string());
+
+        let written = async {
+            let mut file = tokio::fs::File::create(&tmp).await?;
+            while let Some(chunk) = stream.try_next().await? {
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            if !if_absent {
+                tokio::fs::rename(&tmp, &path).await?;
+                return Ok(());
+            }
+            // A hard link is created atomically and never replaces a file
+            match tokio::fs::hard_link(&tmp, &path).await {
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    Err(StorageError::AlreadyExists(key.to_string()))
+                }
+                linked => linked.map_err(StorageError::from),
+            }
+        }
+        .await;
+
+        if written.is_err() || if_absent {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        written
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream<'_>,
+        _content_type: &str,
+    ) -> Result<(), StorageError> {
+        self.write_stream(key, stream, false).await
+    }
+
+    async fn put_stream_if_absent(
+        &self,
+        key: &str,
+        stream: ByteStream<'_>,
+        _content_type: &str,
+    ) -> Result<(), StorageError> {
+        self.write_stream(key, stream, true).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Object, StorageError> {
+        let data = match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Object {
+            data: data.into(),
+            content_type: mime_guess::from_path(key)
+                .first_or_octet_stream()
+                .to_string(),
+        })
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<ByteStream<'static>, StorageError> {
+        let file = match tokio::fs::File::open(self.path(key)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(StorageError::NotFound(key.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Box::pin(
+            tokio_util::io::ReaderStream::new(file).map_err(StorageError::from),
+        ))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, StorageError> {
+        match tokio::fs::metadata(self.path(key)?).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(Self::meta(key.to_string(), &metadata))),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, StorageError> {
+        let mut objects = Vec::new();
+        let mut dirs = vec![(self.root.clone(), String::new())];
+
+        while let Some((dir, dir_key)) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let key = format!("{}{}", dir_key, entry.file_name().to_string_lossy());
+                if key == LOCAL_TMP_DIR {
+                    continue;
+                }
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    // Only descend where keys can still match the prefix
+                    let dir_prefix = format!("{}/", key);
+                    if dir_prefix.starts_with(prefix) || prefix.starts_with(&dir_prefix) {
+                        dirs.push((entry.path(), dir_prefix));
+                    }
+                } else if key.starts_with(prefix) {
+                    objects.push(Self::meta(key, &metadata));
+                }
+            }
+        }
+
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, encode_key(key))
+    }
+
+    async fn presigned_get(&self, key: &str, expires_in: Duration) -> Result<String, StorageError> {
+        signed_url(self.signer.as_ref(), Method::GET, key, None, expires_in)
+    }
+
+    async fn presigned_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<String, StorageError> {
+        signed_url(
+            self.signer.as_ref(),
+            Method::PUT,
+            key,
+            Some(content_type),
+            expires_in,
+        )
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MEMORY STORAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug)]
+struct MemoryObject {
+    data: Bytes,
+    content_type: String,
+    last_modified: DateTime<Utc>,
+}
+
+/// Keeps objects in memory (for tests and development)
+#[derive(Debug)]
+pub struct MemoryStorage {
+    objects: RwLock<HashMap<String, MemoryObject>>,
+    base_url: String,
+    signer: Option<UrlSigner>,
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            objects: RwLock::new(HashMap::new()),
+            base_url: "/uploads".to_string(),
+            signer: None,
+        }
+    }
+
+    /// URL prefix objects are served at
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Enable presigned URLs
+    pub fn with_signer(mut self, signer: UrlSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Number of stored objects
+    pub fn len(&self) -> usize {
+        self.objects.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for MemoryStorage {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError> {
+        validate_key(key)?;
+        self.objects.write().unwrap().insert(
+            key.to_string(),
+            MemoryObject {
+                data,
+                content_type: content_type.to_string(),
+                last_modified: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn put_stream_if_absent(
+        &self,
+        key: &str,
+        stream: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        validate_key(key)?;
+        let chunks: Vec<Bytes> = stream.try_collect().await?;
+        let mut objects = self.objects.write().unwrap();
+        if objects.contains_key(key) {
+            return Err(StorageError::AlreadyExists(key.to_string()));
+        }
+        objects.insert(
+            key.to_string(),
+            MemoryObject {
+                data: chunks.concat().into(),
+                content_type: content_type.to_string(),
+                last_modified: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Object, StorageError> {
+        validate_key(key)?;
+        self.objects
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|object| Object {
+                data: object.data.clone(),
+                content_type: object.content_type.clone(),
+            })
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, StorageError> {
+        validate_key(key)?;
+        Ok(self
+            .objects
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|object| ObjectMeta {
+                key: key.to_string(),
+                size: object.data.len() as u64,
+                last_modified: object.last_modified,
+            }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        validate_key(key)?;
+        self.objects.write().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, StorageError> {
+        let mut objects: Vec<ObjectMeta> = self
+            .objects
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, object)| ObjectMeta {
+                key: key.clone(),
+                size: object.data.len() as u64,
+                last_modified: object.last_modified,
+            })
+            .collect();
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, encode_key(key))
+    }
+
+    async fn presigned_get(&self, key: &str, expires_in: Duration) -> Result<String, StorageError> {
+        signed_url(self.signer.as_ref(), Method::GET, key, None, expires_in)
+    }
+
+    async fn presigned_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<String, StorageError> {
+        signed_url(
+            self.signer.as_ref(),
+            Method::PUT,
+            key,
+            Some(content_type),
+            expires_in,
+        )
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Build the storage described by `[storage]` in `nucleus.config`, or local
+/// storage in `uploads/` when the section is missing
+///
+/// Local storage signs URLs with `app.secret_key`.
+pub fn from_config(config: &crate::config::Config) -> Arc<dyn Storage> {
+    use crate::config::StorageConfig;
+
+    match &config.storage {
+        None => Arc::new(LocalStorage::new("uploads")),
+        Some(StorageConfig::Local { path, base_url }) => Arc::new(
+            LocalStorage::new(path)
+                .with_base_url(base_url)
+                .with_signer(UrlSigner::new(&config.app.secret_key, base_url)),
+        ),
+        Some(StorageConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            path_style,
+            public_url,
+        }) => {
+            let mut storage = S3Storage::new(endpoint, bucket, access_key, secret_key)
+                .with_region(region)
+                .with_path_style(*path_style);
+            if let Some(url) = public_url {
+                storage = storage.with_public_url(url);
+            }
+            Arc::new(storage)
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    /// Behaviour every backend must share
+    pub(super) async fn check_storage(storage: &dyn Storage, prefix: &str) {
+        let key = |name: &str| format!("{}{}", prefix, name);
+
+        assert!(!storage.exists(&key("a.txt")).await.unwrap());
+        assert!(matches!(
+            storage.get(&key("a.txt")).await,
+            Err(StorageError::NotFound(_))
+        ));
+
+        storage
+            .put(&key("a.txt"), Bytes::from_static(b"hello"), "text/plain")
+            .await
+            .unwrap();
+        storage
+            .put(
+                &key("dir/b.bin"),
+                Bytes::from_static(b"\x00\x01"),
+                "application/octet-stream",
+            )
+            .await
+            .unwrap();
+        storage
+            .put(
+                &key("a.txt"),
+                Bytes::from_static(b"hello again"),
+                "text/plain",
+            )
+            .await
+            .unwrap();
+
+        let object = storage.get(&key("a.txt")).await.unwrap();
+        assert_eq!(&object.data[..], b"hello again");
+
+        let chunks = vec![
+            Ok(Bytes::from_static(b"stream")),
+            Ok(Bytes::from_static(b"ed")),
+        ];
+        storage
+            .put_stream(
+                &key("s.txt"),
+                Box::pin(futures_util::stream::iter(chunks)),
+                "text/plain",
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            &storage.get(&key("s.txt")).await.unwrap().data[..],
+            b"streamed"
+        );
+        let chunks: Vec<Bytes> = storage
+            .get_stream(&key("s.txt"))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"streamed");
+        assert!(matches!(
+            storage.get_stream(&key("missing.txt")).await,
+            Err(StorageError::NotFound(_))
+        ));
+        storage.delete(&key("s.txt")).await.unwrap();
+
+        // A failing stream stores nothing
+        let chunks = vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err(StorageError::Backend("connection reset".to_string())),
+        ];
+        assert!(storage
+            .put_stream(
+                &key("broken.txt"),
+                Box::pin(futures_util::stream::iter(chunks)),
+                "text/plain",
+            )
+            .await
+            .is_err());
+        assert!(!storage.exists(&key("broken.txt")).await.unwrap());
+        assert_eq!(object.content_type, "text/plain");
+
+        let meta = storage.head(&key("a.txt")).await.unwrap().unwrap();
+        assert_eq!(meta.key, key("a.txt"));
+        assert_eq!(meta.size, 11);
+        assert!(meta.last_modified <= Utc::now() + chrono::Duration::seconds(5));
+
+        let keys: Vec<String> = storage
+            .list(prefix)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.key)
+            .collect();
+        assert_eq!(keys, vec![key("a.txt"), key("dir/b.bin")]);
+        assert_eq!(storage.list(&key("dir/")).await.unwrap().len(), 1);
+        assert!(storage.list(&key("missing/")).await.unwrap().is_empty());
+
+        for bad in ["", "/abs", "a/../b", "a//b", "./a", "a\\b"] {
+            assert!(matches!(
+                storage.put(bad, Bytes::new(), "text/plain").await,
+                Err(StorageError::InvalidKey(_))
+            ));
+        }
+
+        storage.delete(&key("a.txt")).await.unwrap();
+        storage.delete(&key("a.txt")).await.unwrap();
+        storage.delete(&key("dir/b.bin")).await.unwrap();
+        assert!(!storage.exists(&key("a.txt")).await.unwrap());
+        assert!(storage.list(prefix).await.unwrap().is_empty());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nucleus_storage_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_validate_key() {
+        for good in ["a", "avatars/1.png", "a b/ü.txt", "..hidden", "a/.b"] {
+            assert!(validate_key(good).is_ok(), "{:?} rejected", good);
+        }
+        for bad in [
+            "", "/a", "a/", "a//b", ".", "a/./b", "../a", "a/..", "a\\b", "a\nb",
+        ] {
+            assert!(validate_key(bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        check_storage(&storage, "").await;
+        check_storage(&storage, "prefix/").await;
+        assert!(storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_storage() {
+        let dir = temp_dir("local");
+        let storage = LocalStorage::new(&dir);
+        check_storage(&storage, "").await;
+        check_storage(&storage, "nested/prefix/").await;
+
+        storage
+            .put("photos/cat.png", Bytes::from_static(b"png"), "image/png")
+            .await
+            .unwrap();
+        assert!(dir.join("photos/cat.png").is_file());
+        assert_eq!(
+            storage.get("photos/cat.png").await.unwrap().content_type,
+            "image/png"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_urls() {
+        let storage = LocalStorage::new("uploads").with_base_url("https://cdn.example.com/");
+        assert_eq!(
+            storage.url("a b/c.png"),
+            "https://cdn.example.com/a%20b/c.png"
+        );
+        assert_eq!(MemoryStorage::new().url("x.txt"), "/uploads/x.txt");
+    }
+
+    #[tokio::test]
+    async fn test_presign_requires_signer() {
+        let storage = MemoryStorage::new();
+        assert!(matches!(
+            storage
+                .presigned_get("a.txt", Duration::from_secs(60))
+                .await,
+            Err(StorageError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_url_signer() {
+        let signer = UrlSigner::new("secret", "/files");
+        let url = signer.sign(&Method::GET, "a/b.txt", None, Duration::from_secs(60));
+        assert!(url.starts_with("/files/a/b.txt?expires="));
+
+        let query = url.split_once('?').unwrap().1;
+        let params: HashMap<&str, &str> =
+            query.split('&').filter_map(|p| p.split_once('=')).collect();
+        let expires: i64 = params["expires"].parse().unwrap();
+        let signature = params["signature"];
+
+        assert!(signer.verify(&Method::GET, "a/b.txt", None, expires, signature));
+        assert!(!signer.verify(&Method::PUT, "a/b.txt", None, expires, signature));
+        assert!(!signer.verify(&Method::GET, "a/c.txt", None, expires, signature));
+        assert!(!signer.verify(&Method::GET, "a/b.txt", None, expires + 1, signature));
+        assert!(!UrlSigner::new("other", "/files").verify(
+            &Method::GET,
+            "a/b.txt",
+            None,
+            expires,
+            signature
+        ));
+
+        let expired = Utc::now().timestamp() - 1;
+        let mac = signer.mac(&Method::GET, "a/b.txt", None, expired);
+        let signature = hex::encode(mac.finalize().into_bytes());
+        assert!(!signer.verify(&Method::GET, "a/b.txt", None, expired, &signature));
+    }
+
+    #[tokio::test]
+    async fn test_signed_routes() {
+        let signer = UrlSigner::new("secret", "/files");
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new().with_signer(signer.clone()));
+        let app = Router::new().nest("/files", signed_routes(storage.clone(), signer));
+        let ttl = Duration::from_secs(60);
+
+        let upload = storage
+            .presigned_put("docs/a b.txt", "text/plain", ttl)
+            .await
+            .unwrap();
+        let put = |uri: &str, content_type: &str| {
+            Request::put(uri)
+                .header("content-type", content_type)
+                .body(Body::from("hello"))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(put(&upload, "text/html"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .clone()
+            .oneshot(put(&upload, "text/plain"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(
+            &storage.get("docs/a b.txt").await.unwrap().data[..],
+            b"hello"
+        );
+
+        let download = storage.presigned_get("docs/a b.txt", ttl).await.unwrap();
+        let response = app
+            .clone()
+            .oneshot(Request::get(&download).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/plain");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"hello");
+
+        // The upload URL doesn't grant downloads
+        let response = app
+            .clone()
+            .oneshot(Request::get(&upload).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let missing = storage.presigned_get("docs/none.txt", ttl).await.unwrap();
+        let response = app
+            .oneshot(Request::get(&missing).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: crate::config::Config = toml::from_str(
+            "
+            [storage]
+            driver = \"s3\"
+            endpoint = \"http://localhost:9000\"
+            bucket = \"uploads\"
+            access_key = \"minio\"
+            secret_key = \"minio123\"
+            path_style = true
+            ",
+        )
+        .unwrap();
+        let storage = from_config(&config);
+        assert_eq!(storage.url("a.png"), "http://localhost:9000/uploads/a.png");
+        assert!(!format!("{:?}", storage).contains("minio123"));
+
+        let storage = from_config(&crate::config::Config::default());
+        assert_eq!(storage.url("a.png"), "/uploads/a.png");
+    }
+}
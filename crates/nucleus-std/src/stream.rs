@@ -325,6 +325,24 @@ impl StreamHub {
         sockets.len()
     }
 
+    /// Send Close to every socket and wait for them to unregister.
+    /// Returns how many were still connected when `timeout` elapsed.
+    pub async fn drain(&self, timeout: std::time::Duration) -> usize {
+        if self.connection_count().await == 0 {
+            return 0;
+        }
+        self.broadcast_all(SocketMessage::Close).await;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let remaining = self.connection_count().await;
+            if remaining == 0 || tokio::time::Instant::now() >= deadline {
+                return remaining;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+        }
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // ROOM MANAGEMENT
     // ═══════════════════════════════════════════════════════════════════════
@@ -806,6 +824,27 @@ mod tests {
         assert!(rx2.recv().await.is_some());
     }
 
+    #[tokio::test]
+    async fn test_drain() {
+        let hub = StreamHub::new();
+        let (s1, mut rx1) = create_socket("sock1");
+        let (s2, _rx2) = create_socket("sock2");
+        hub.register(s1).await;
+        hub.register(s2).await;
+
+        // sock1 closes when asked, sock2 never does
+        let closer = hub.clone();
+        tokio::spawn(async move {
+            if let Some(SocketMessage::Close) = rx1.recv().await {
+                closer.unregister("sock1").await;
+            }
+        });
+
+        let remaining = hub.drain(std::time::Duration::from_millis(200)).await;
+        assert_eq!(remaining, 1);
+        assert!(hub.get_socket("sock2").await.is_some());
+    }
+
     #[tokio::test]
     async fn test_broadcast_except() {
         let hub = StreamHub::new();
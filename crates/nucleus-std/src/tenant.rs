@@ -2,60 +2,78 @@
 //!
 //! Row-level security pattern for SaaS applications with:
 //! - Multiple tenant resolution strategies (subdomain, header, path)
-//! - Thread-local tenant context
-//! - Automatic query scoping helpers
+//! - Task-local tenant context that follows a request across `.await`s
+//! - Automatic scoping of Photon queries for tenant-scoped models
 //! - Middleware integration
 //!
 //! # Example
 //!
 //! ```rust,ignore
-//! use nucleus_std::tenant::{Tenant, TenantExtractor, TenantStrategy};
+//! use nucleus_std::tenant::{Tenant, TenantExtractor, TenantGuard};
 //!
-//! // Configure tenant extraction
-//! let extractor = TenantExtractor::new(TenantStrategy::Subdomain);
+//! // Every request runs inside its tenant's context
+//! let app = Router::new()
+//!     .route("/users", get(list_users))
+//!     .layer(TenantGuard::new(TenantExtractor::header("X-Tenant-ID")));
 //!
-//! // In middleware or handler
-//! let tenant_id = extractor.extract(&request)?;
-//! Tenant::set(&tenant_id);
+//! async fn list_users() -> Json<Vec<User>> {
+//!     // `#[photon(tenant)]` models are filtered by tenant_id automatically
+//!     Json(User::query().all::<User>().await.unwrap())
+//! }
 //!
-//! // All subsequent queries are scoped to this tenant
-//! let users = User::find_all().await?; // Automatically filtered by tenant_id
-//!
-//! // Clear when request ends
-//! Tenant::clear();
+//! // Outside a request (jobs, scripts), scope the work explicitly
+//! Tenant::scope("acme", async { User::query().count().await }).await?;
 //! ```
 
 use axum::{
-    http::{HeaderMap, StatusCode},
+    extract::Request,
+    http::{header::HOST, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // TENANT CONTEXT
 // ═══════════════════════════════════════════════════════════════════════════
 
-thread_local! {
-    static CURRENT_TENANT: RefCell<Option<String>> = const { RefCell::new(None) };
+tokio::task_local! {
+    static CURRENT_TENANT: String;
 }
 
-/// Thread-local tenant context for row-level security
+/// Task-local tenant context for row-level security
+///
+/// The tenant is carried by the task rather than the thread, so it survives
+/// a request resuming on another worker thread. Spawned tasks don't inherit
+/// it; wrap their futures in `Tenant::scope()`.
+///
+/// IDs are strings; tenant-scoped models convert them to the tenant column's
+/// type with `photon::TenantKey` (e.g. `"42"` binds as an integer).
 #[derive(Debug, Clone)]
 pub struct Tenant;
 
 impl Tenant {
-    /// Set the current tenant for this thread/task
-    pub fn set(tenant_id: &str) {
-        CURRENT_TENANT.with(|t| {
-            *t.borrow_mut() = Some(tenant_id.to_string());
-        });
+    /// Run a future with `tenant_id` as the current tenant
+    pub async fn scope<F: Future>(tenant_id: &str, f: F) -> F::Output {
+        CURRENT_TENANT.scope(tenant_id.to_string(), f).await
+    }
+
+    /// Run a closure with `tenant_id` as the current tenant
+    ///
+    /// The previous tenant, if any, is current again afterwards.
+    pub fn with<F, R>(tenant_id: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        CURRENT_TENANT.sync_scope(tenant_id.to_string(), f)
     }
 
     /// Get the current tenant ID
     pub fn get() -> Option<String> {
-        CURRENT_TENANT.with(|t| t.borrow().clone())
+        CURRENT_TENANT.try_with(|t| t.clone()).ok()
     }
 
     /// Get the current tenant ID, returning error if not set
@@ -63,32 +81,15 @@ impl Tenant {
         Self::get().ok_or(TenantError::NotSet)
     }
 
-    /// Clear the current tenant (call at end of request)
-    pub fn clear() {
-        CURRENT_TENANT.with(|t| {
-            *t.borrow_mut() = None;
-        });
-    }
-
-    /// Execute a closure with a specific tenant context
-    pub fn with<F, R>(tenant_id: &str, f: F) -> R
-    where
-        F: FnOnce() -> R,
-    {
-        let previous = Self::get();
-        Self::set(tenant_id);
-        let result = f();
-        match previous {
-            Some(id) => Self::set(&id),
-            None => Self::clear(),
-        }
-        result
-    }
-
     /// Check if a tenant is currently set
     pub fn is_set() -> bool {
         Self::get().is_some()
     }
+
+    /// The registered info of the current tenant
+    pub fn info() -> Option<TenantInfo> {
+        tenant_info(&Self::get()?)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -312,6 +313,9 @@ pub struct TenantInfo {
     pub created_at: i64,
     pub settings: serde_json::Value,
     pub active: bool,
+    /// Where this tenant's data lives
+    #[serde(default)]
+    pub isolation: Isolation,
 }
 
 impl TenantInfo {
@@ -331,15 +335,130 @@ impl TenantInfo {
             created_at: chrono::Utc::now().timestamp(),
             settings: serde_json::json!({}),
             active: true,
+            isolation: Isolation::Shared,
         }
     }
 
+    /// Keep this tenant's tables in its own PostgreSQL schema
+    pub fn with_schema(self, schema: &str) -> Self {
+        self.with_isolation(Isolation::Schema(schema.to_string()))
+    }
+
+    /// Keep this tenant's data in a database of its own
+    pub fn with_database(self, url: &str) -> Self {
+        self.with_isolation(Isolation::Database(url.to_string()))
+    }
+
+    /// Set how this tenant's data is isolated
+    pub fn with_isolation(mut self, isolation: Isolation) -> Self {
+        self.isolation = isolation;
+        self
+    }
+
     /// Check if tenant is active
     pub fn is_active(&self) -> bool {
         self.active
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// TENANT ISOLATION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How a tenant's data is kept apart from other tenants'
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Isolation {
+    /// Shared tables, filtered by the tenant column of `#[photon(tenant)]` models
+    #[default]
+    Shared,
+    /// A PostgreSQL schema in the default database, selected with
+    /// `search_path` on each checked-out connection
+    Schema(String),
+    /// A database of its own, reached through a per-tenant pool
+    Database(String),
+}
+
+static TENANTS: OnceLock<RwLock<HashMap<String, TenantInfo>>> = OnceLock::new();
+static SCHEMA_ROUTING: AtomicBool = AtomicBool::new(false);
+
+/// Register a tenant so queries in its context follow its `Isolation`
+///
+/// Tenants that are never registered use shared tables. Registering an id
+/// again replaces the previous entry.
+///
+/// ```rust,ignore
+/// register_tenant(TenantInfo::new("acme", "Acme Corp").with_schema("tenant_acme"))?;
+/// register_tenant(TenantInfo::new("globex", "Globex").with_database("postgres://db2/globex"))?;
+/// ```
+pub fn register_tenant(info: TenantInfo) -> Result<(), TenantError> {
+    match &info.isolation {
+        Isolation::Schema(schema) => {
+            if !is_valid_schema(schema) {
+                return Err(TenantError::InvalidConfig(format!(
+                    "invalid schema name for tenant {}: {}",
+                    info.id, schema
+                )));
+            }
+            SCHEMA_ROUTING.store(true, Ordering::Relaxed);
+        }
+        Isolation::Database(url) if url.is_empty() => {
+            return Err(TenantError::InvalidConfig(format!(
+                "empty database URL for tenant {}",
+                info.id
+            )));
+        }
+        _ => {}
+    }
+    TENANTS
+        .get_or_init(Default::default)
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(info.id.clone(), info);
+    Ok(())
+}
+
+/// Look up a registered tenant
+pub fn tenant_info(id: &str) -> Option<TenantInfo> {
+    TENANTS
+        .get()?
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(id)
+        .cloned()
+}
+
+/// All registered tenants, ordered by id
+pub fn registered_tenants() -> Vec<TenantInfo> {
+    let Some(tenants) = TENANTS.get() else {
+        return Vec::new();
+    };
+    let mut tenants: Vec<TenantInfo> = tenants
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .cloned()
+        .collect();
+    tenants.sort_by(|a, b| a.id.cmp(&b.id));
+    tenants
+}
+
+/// Whether any tenant uses schema isolation, so pooled PostgreSQL
+/// connections need their `search_path` reset on release
+pub(crate) fn schema_routing() -> bool {
+    SCHEMA_ROUTING.load(Ordering::Relaxed)
+}
+
+/// Lowercase identifier PostgreSQL won't fold or need quoted
+fn is_valid_schema(name: &str) -> bool {
+    name.len() <= 63
+        && !name.starts_with("pg_")
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // ERRORS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -451,7 +570,17 @@ impl TenantQuery {
 // GUARD / MIDDLEWARE HELPERS
 // ═══════════════════════════════════════════════════════════════════════════
 
-/// Guard that ensures tenant is set before proceeding
+/// Layer that resolves each request's tenant and runs it in that context
+///
+/// Requests without a tenant are rejected unless the extractor is
+/// `optional()`, in which case they run with no tenant set.
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/projects", get(list_projects))
+///     .layer(TenantGuard::new(TenantExtractor::subdomain("example.com")));
+/// ```
+#[derive(Clone)]
 pub struct TenantGuard {
     extractor: Arc<TenantExtractor>,
 }
@@ -464,31 +593,72 @@ impl TenantGuard {
         }
     }
 
-    /// Check request and set tenant context
+    /// Resolve the tenant for a request
     pub fn check(
         &self,
         headers: &HeaderMap,
         uri: &str,
         host: Option<&str>,
-    ) -> Result<String, TenantError> {
-        let tenant = self
-            .extractor
-            .extract(headers, uri, host)?
-            .ok_or(TenantError::NotFound)?;
-
-        Tenant::set(&tenant);
-        Ok(tenant)
+    ) -> Result<Option<String>, TenantError> {
+        self.extractor.extract(headers, uri, host)
     }
 }
 
-impl Clone for TenantGuard {
-    fn clone(&self) -> Self {
-        Self {
+impl<I> tower::Layer<I> for TenantGuard {
+    type Service = TenantService<I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        TenantService {
+            inner,
             extractor: Arc::clone(&self.extractor),
         }
     }
 }
 
+/// Service produced by `TenantGuard`
+#[derive(Clone)]
+pub struct TenantService<I> {
+    inner: I,
+    extractor: Arc<TenantExtractor>,
+}
+
+impl<I> tower::Service<Request> for TenantService<I>
+where
+    I: tower::Service<Request, Response = Response> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = I::Error;
+    type Future = futures_util::future::BoxFuture<'static, Result<Response, I::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let host = req.headers().get(HOST).and_then(|h| h.to_str().ok());
+        let uri = req
+            .uri()
+            .path_and_query()
+            .map_or_else(|| req.uri().path(), |pq| pq.as_str());
+        let tenant = self.extractor.extract(req.headers(), uri, host);
+
+        Box::pin(async move {
+            match tenant {
+                Ok(Some(tenant)) => Tenant::scope(&tenant, inner.call(req)).await,
+                Ok(None) => inner.call(req).await,
+                Err(e) => Ok(e.into_response()),
+            }
+        })
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // TESTS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -496,35 +666,31 @@ impl Clone for TenantGuard {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::http::HeaderValue;
+    use axum::{body::Body, http::HeaderValue, routing::get, Router};
+    use tower::ServiceExt;
 
-    #[test]
-    fn test_tenant_set_get_clear() {
-        Tenant::clear();
+    #[tokio::test]
+    async fn test_tenant_scope() {
         assert!(Tenant::get().is_none());
 
-        Tenant::set("tenant_123");
-        assert_eq!(Tenant::get(), Some("tenant_123".to_string()));
+        let inside = Tenant::scope("tenant_123", async { Tenant::get() }).await;
+        assert_eq!(inside, Some("tenant_123".to_string()));
 
-        Tenant::clear();
         assert!(Tenant::get().is_none());
     }
 
     #[test]
     fn test_tenant_require() {
-        Tenant::clear();
         assert!(Tenant::require().is_err());
 
-        Tenant::set("tenant_456");
-        assert_eq!(Tenant::require().unwrap(), "tenant_456");
-        Tenant::clear();
+        Tenant::with("tenant_456", || {
+            assert_eq!(Tenant::require().unwrap(), "tenant_456");
+        });
     }
 
     #[test]
     fn test_tenant_with() {
-        Tenant::clear();
-
-        let result = Tenant::with("temp_tenant", || Tenant::get());
+        let result = Tenant::with("temp_tenant", Tenant::get);
 
         assert_eq!(result, Some("temp_tenant".to_string()));
         assert!(Tenant::get().is_none()); // Restored to none
@@ -532,14 +698,44 @@ mod tests {
 
     #[test]
     fn test_tenant_with_preserves_previous() {
-        Tenant::set("original");
+        Tenant::with("original", || {
+            Tenant::with("temp", || {
+                assert_eq!(Tenant::get(), Some("temp".to_string()));
+            });
 
-        Tenant::with("temp", || {
-            assert_eq!(Tenant::get(), Some("temp".to_string()));
+            assert_eq!(Tenant::get(), Some("original".to_string()));
         });
+    }
 
-        assert_eq!(Tenant::get(), Some("original".to_string()));
-        Tenant::clear();
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tenant_survives_awaits_across_threads() {
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                tokio::spawn(async move {
+                    let id = format!("tenant_{}", i);
+                    Tenant::scope(&id, async {
+                        for _ in 0..20 {
+                            tokio::task::yield_now().await;
+                            assert_eq!(Tenant::get().as_deref(), Some(id.as_str()));
+                        }
+                    })
+                    .await;
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawned_tasks_do_not_inherit() {
+        Tenant::scope("parent", async {
+            let child = tokio::spawn(async { Tenant::get() }).await.unwrap();
+            assert!(child.is_none());
+        })
+        .await;
     }
 
     #[test]
@@ -653,24 +849,64 @@ mod tests {
         assert!(info.active);
     }
 
+    #[test]
+    fn test_register_tenant_isolation() {
+        register_tenant(TenantInfo::new("iso_schema", "Schema").with_schema("tenant_iso")).unwrap();
+        register_tenant(TenantInfo::new("iso_db", "Database").with_database("postgres://db2/iso"))
+            .unwrap();
+
+        assert_eq!(
+            tenant_info("iso_schema").unwrap().isolation,
+            Isolation::Schema("tenant_iso".to_string())
+        );
+        assert!(schema_routing());
+        assert!(registered_tenants()
+            .windows(2)
+            .all(|pair| pair[0].id < pair[1].id));
+        assert!(tenant_info("iso_missing").is_none());
+        assert_eq!(
+            Tenant::with("iso_db", Tenant::info).unwrap().isolation,
+            Isolation::Database("postgres://db2/iso".to_string())
+        );
+
+        for bad in ["", "Tenant", "pg_catalog", "1st", "a-b", "a\"; DROP"] {
+            let info = TenantInfo::new("iso_bad", "Bad").with_schema(bad);
+            assert!(register_tenant(info).is_err(), "{:?} accepted", bad);
+        }
+        assert!(register_tenant(TenantInfo::new("iso_bad", "Bad").with_database("")).is_err());
+        assert!(tenant_info("iso_bad").is_none());
+    }
+
+    #[test]
+    fn test_tenant_info_isolation_defaults_to_shared() {
+        let info: TenantInfo = serde_json::from_value(serde_json::json!({
+            "id": "t_1",
+            "name": "One",
+            "slug": "one",
+            "created_at": 0,
+            "settings": {},
+            "active": true
+        }))
+        .unwrap();
+        assert_eq!(info.isolation, Isolation::Shared);
+
+        let json = serde_json::to_value(TenantInfo::new("t_2", "Two").with_schema("two")).unwrap();
+        assert_eq!(json["isolation"], serde_json::json!({ "schema": "two" }));
+    }
+
     #[test]
     fn test_tenant_query_select() {
-        Tenant::clear();
         let sql = TenantQuery::select("users", "*");
         assert_eq!(sql, "SELECT * FROM users");
 
-        Tenant::set("t_123");
-        let sql = TenantQuery::select("users", "*");
+        let sql = Tenant::with("t_123", || TenantQuery::select("users", "*"));
         assert_eq!(sql, "SELECT * FROM users WHERE tenant_id = ?");
-        Tenant::clear();
     }
 
     #[test]
     fn test_tenant_query_delete() {
-        Tenant::set("t_123");
-        let sql = TenantQuery::delete("users");
+        let sql = Tenant::with("t_123", || TenantQuery::delete("users"));
         assert_eq!(sql, "DELETE FROM users WHERE tenant_id = ?");
-        Tenant::clear();
     }
 
     #[test]
@@ -687,28 +923,95 @@ mod tests {
     }
 
     #[test]
-    fn test_tenant_guard() {
-        Tenant::clear();
-
+    fn test_tenant_guard_check() {
         let guard = TenantGuard::new(TenantExtractor::header("X-Tenant"));
         let mut headers = HeaderMap::new();
         headers.insert("X-Tenant", HeaderValue::from_static("guarded"));
 
         let result = guard.check(&headers, "/api", None);
-        assert_eq!(result.unwrap(), "guarded");
-        assert_eq!(Tenant::get(), Some("guarded".to_string()));
+        assert_eq!(result.unwrap(), Some("guarded".to_string()));
+        assert!(guard.check(&HeaderMap::new(), "/api", None).is_err());
+    }
+
+    fn tenant_app(extractor: TenantExtractor) -> Router {
+        Router::new()
+            .route(
+                "/whoami",
+                get(|| async {
+                    tokio::task::yield_now().await;
+                    Tenant::get().unwrap_or_else(|| "none".to_string())
+                }),
+            )
+            .layer(TenantGuard::new(extractor))
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tenant_guard_layer_scopes_request() {
+        let app = tenant_app(TenantExtractor::header("X-Tenant"));
+        let request = axum::http::Request::get("/whoami")
+            .header("X-Tenant", "acme")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, "acme");
+        assert!(Tenant::get().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tenant_guard_layer_uses_host_and_query() {
+        let app = tenant_app(TenantExtractor::subdomain("example.com"));
+        let request = axum::http::Request::get("/whoami")
+            .header("Host", "globex.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(body_text(response).await, "globex");
+
+        let app = tenant_app(TenantExtractor::new(TenantStrategy::QueryParam(
+            "org".to_string(),
+        )));
+        let request = axum::http::Request::get("/whoami?org=initech")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(body_text(response).await, "initech");
+    }
+
+    #[tokio::test]
+    async fn test_tenant_guard_layer_rejects_missing_tenant() {
+        let app = tenant_app(TenantExtractor::header("X-Tenant"));
+        let request = axum::http::Request::get("/whoami")
+            .body(Body::empty())
+            .unwrap();
 
-        Tenant::clear();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_guard_layer_optional() {
+        let app = tenant_app(TenantExtractor::header("X-Tenant").optional());
+        let request = axum::http::Request::get("/whoami")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, "none");
     }
 
     #[test]
     fn test_is_set() {
-        Tenant::clear();
         assert!(!Tenant::is_set());
-
-        Tenant::set("test");
-        assert!(Tenant::is_set());
-
-        Tenant::clear();
+        assert!(Tenant::with("test", Tenant::is_set));
     }
 }
@@ -0,0 +1,1016 @@
+//! Nucleus Tokens - expiring, revocable, key-rotated auth tokens
+//!
+//! Tokens are standard compact JWTs (`HS256` or `EdDSA`) carrying
+//! `sub`/`iat`/`exp`/`jti`, optional `iss`/`aud`/`scope` and any custom claims.
+//! Every token names the key that signed it in the `kid` header, so new keys
+//! can be rolled out while tokens signed by the previous one keep verifying.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use nucleus_std::tokens::{self, SigningKey, TokenManager};
+//! use std::time::Duration;
+//!
+//! tokens::init(
+//!     TokenManager::new(SigningKey::hs256("2024-01", secret))
+//!         .access_ttl(Duration::from_secs(900))
+//!         .issuer("https://api.example.com"),
+//! )?;
+//!
+//! let claims = tokens::manager()?.claims("user_123").with_scope("posts:write");
+//! let pair = tokens::manager()?.issue_pair(claims).await?;
+//!
+//! // Later: exchange the refresh token (it can only be used once)
+//! let pair = tokens::manager()?.refresh(&pair.refresh_token).await?;
+//!
+//! // Rotate: new tokens use the new key, old ones verify until retired
+//! tokens::manager()?.rotate(SigningKey::hs256("2024-02", new_secret))?;
+//! ```
+
+use crate::config::GLOBAL_CONFIG;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use ring::signature::{self as ed, Ed25519KeyPair, KeyPair};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ERRORS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Token error types
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TokenError {
+    #[error("Malformed token: {0}")]
+    Malformed(String),
+
+    #[error("Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("Unknown signing key: {0}")]
+    UnknownKey(String),
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Token expired")]
+    Expired,
+
+    #[error("Token not yet valid")]
+    NotYetValid,
+
+    #[error("Invalid issuer")]
+    InvalidIssuer,
+
+    #[error("Invalid audience")]
+    InvalidAudience,
+
+    #[error("Token revoked")]
+    Revoked,
+
+    #[error("Expected a {0} token")]
+    WrongTokenType(&'static str),
+
+    #[error("Key error: {0}")]
+    Key(String),
+
+    #[error("Revocation store error: {0}")]
+    Storage(String),
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CLAIMS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Whether a token grants access or can only be exchanged for new tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenUse {
+    #[default]
+    Access,
+    Refresh,
+}
+
+/// `aud` is either a single string or an array
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::One(a) => a == audience,
+            Audience::Many(all) => all.iter().any(|a| a == audience),
+        }
+    }
+}
+
+/// Registered and custom claims of a token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    #[serde(default)]
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    #[serde(default)]
+    pub jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// Space separated scopes (RFC 8693)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Session shared by a refresh chain; revoking it ends the whole session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    #[serde(default)]
+    pub token_use: TokenUse,
+    /// Application specific claims
+    #[serde(flatten)]
+    pub custom: serde_json::Map<String, Value>,
+}
+
+impl Claims {
+    /// Add a scope
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.scope = Some(match self.scope.take() {
+            Some(existing) if !existing.is_empty() => format!("{} {}", existing, scope),
+            _ => scope.to_string(),
+        });
+        self
+    }
+
+    /// Add a custom claim
+    pub fn with_claim(mut self, key: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.custom.insert(key.to_string(), value);
+        self
+    }
+
+    /// Read a custom claim
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.custom
+            .get(key)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope.as_deref().unwrap_or("").split_whitespace()
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|s| s == scope)
+    }
+
+    pub fn expires_at(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(self.exp, 0).unwrap_or_default()
+    }
+}
+
+/// Access and refresh token issued together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SIGNING KEYS
+// ═══════════════════════════════════════════════════════════════════════════
+
+enum KeyKind {
+    Hs256(Vec<u8>),
+    Ed25519(Ed25519KeyPair),
+    Ed25519Public(Vec<u8>),
+}
+
+/// A key identified by `kid`
+pub struct SigningKey {
+    kid: String,
+    kind: KeyKind,
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("kid", &self.kid)
+            .field("alg", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKey {
+    /// Shared-secret HMAC-SHA256 key
+    pub fn hs256(kid: &str, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            kid: kid.to_string(),
+            kind: KeyKind::Hs256(secret.as_ref().to_vec()),
+        }
+    }
+
+    /// Ed25519 private key from PKCS#8 DER
+    pub fn ed25519_pkcs8(kid: &str, der: &[u8]) -> Result<Self, TokenError> {
+        let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der)
+            .map_err(|e| TokenError::Key(format!("invalid Ed25519 key: {}", e)))?;
+        Ok(Self {
+            kid: kid.to_string(),
+            kind: KeyKind::Ed25519(pair),
+        })
+    }
+
+    /// Ed25519 public key that can only verify, e.g. another service's key
+    pub fn ed25519_public(kid: &str, public_key: &[u8]) -> Self {
+        Self {
+            kid: kid.to_string(),
+            kind: KeyKind::Ed25519Public(public_key.to_vec()),
+        }
+    }
+
+    /// New random Ed25519 key; persist the returned PKCS#8 bytes to reload it
+    pub fn generate_ed25519(kid: &str) -> Result<(Self, Vec<u8>), TokenError> {
+        let rng = ring::rand::SystemRandom::new();
+        let der = Ed25519KeyPair::generate_pkcs8(&rng)
+            .map_err(|_| TokenError::Key("Ed25519 key generation failed".into()))?;
+        let key = Self::ed25519_pkcs8(kid, der.as_ref())?;
+        Ok((key, der.as_ref().to_vec()))
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// JOSE `alg` name
+    pub fn algorithm(&self) -> &'static str {
+        match self.kind {
+            KeyKind::Hs256(_) => "HS256",
+            KeyKind::Ed25519(_) | KeyKind::Ed25519Public(_) => "EdDSA",
+        }
+    }
+
+    pub fn can_sign(&self) -> bool {
+        !matches!(self.kind, KeyKind::Ed25519Public(_))
+    }
+
+    /// Public key bytes for Ed25519 keys
+    pub fn public_key(&self) -> Option<&[u8]> {
+        match &self.kind {
+            KeyKind::Ed25519(pair) => Some(pair.public_key().as_ref()),
+            KeyKind::Ed25519Public(public) => Some(public),
+            KeyKind::Hs256(_) => None,
+        }
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, TokenError> {
+        match &self.kind {
+            KeyKind::Hs256(secret) => {
+                let mut mac =
+                    HmacSha256::new_from_slice(secret).expect("HMAC can take any size key");
+                mac.update(message);
+                Ok(mac.finalize().into_bytes().to_vec())
+            }
+            KeyKind::Ed25519(pair) => Ok(pair.sign(message).as_ref().to_vec()),
+            KeyKind::Ed25519Public(_) => {
+                Err(TokenError::Key(format!("key {} is verify-only", self.kid)))
+            }
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match &self.kind {
+            KeyKind::Hs256(secret) => {
+                let mut mac =
+                    HmacSha256::new_from_slice(secret).expect("HMAC can take any size key");
+                mac.update(message);
+                // Constant-time comparison
+                mac.verify_slice(signature).is_ok()
+            }
+            KeyKind::Ed25519(pair) => ed::UnparsedPublicKey::new(&ed::ED25519, pair.public_key())
+                .verify(message, signature)
+                .is_ok(),
+            KeyKind::Ed25519Public(public) => ed::UnparsedPublicKey::new(&ed::ED25519, public)
+                .verify(message, signature)
+                .is_ok(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// REVOCATION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Storage for revoked token and session ids
+#[async_trait]
+pub trait RevocationList: Send + Sync {
+    /// Revoke `id` until `expires_at` (unix seconds).
+    /// Returns `false` if it was already revoked.
+    async fn revoke(&self, id: &str, expires_at: i64) -> Result<bool, TokenError>;
+
+    async fn is_revoked(&self, id: &str) -> Result<bool, TokenError>;
+}
+
+/// In-process revocation list (single instance deployments and tests)
+#[derive(Default)]
+pub struct MemoryRevocationList {
+    entries: Mutex<HashMap<String, i64>>,
+}
+
+impl MemoryRevocationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevocationList for MemoryRevocationList {
+    async fn revoke(&self, id: &str, expires_at: i64) -> Result<bool, TokenError> {
+        let now = now();
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| TokenError::Storage("lock poisoned".into()))?;
+        // Entries outlive their tokens only until the next revocation
+        entries.retain(|_, until| *until > now);
+        Ok(entries.insert(id.to_string(), expires_at).is_none())
+    }
+
+    async fn is_revoked(&self, id: &str) -> Result<bool, TokenError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|_| TokenError::Storage("lock poisoned".into()))?;
+        Ok(entries.get(id).is_some_and(|until| *until > now()))
+    }
+}
+
+/// Revocation list shared through Redis, entries expire with their tokens
+pub struct RedisRevocationList {
+    client: redis::Client,
+    prefix: String,
+}
+
+impl RedisRevocationList {
+    pub fn new(url: &str) -> Result<Self, TokenError> {
+        let client = redis::Client::open(url).map_err(|e| TokenError::Storage(e.to_string()))?;
+        Ok(Self {
+            client,
+            prefix: "nucleus:revoked:".to_string(),
+        })
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    async fn connection(&self) -> Result<redis::aio::MultiplexedConnection, TokenError> {
+        self.client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| TokenError::Storage(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl RevocationList for RedisRevocationList {
+    async fn revoke(&self, id: &str, expires_at: i64) -> Result<bool, TokenError> {
+        let mut con = self.connection().await?;
+        let ttl = (expires_at - now()).max(1);
+        let set: Option<String> = redis::cmd("SET")
+            .arg(format!("{}{}", self.prefix, id))
+            .arg(1)
+            .arg("EX")
+            .arg(ttl)
+            .arg("NX")
+            .query_async(&mut con)
+            .await
+            .map_err(|e| TokenError::Storage(e.to_string()))?;
+        Ok(set.is_some())
+    }
+
+    async fn is_revoked(&self, id: &str) -> Result<bool, TokenError> {
+        let mut con = self.connection().await?;
+        let exists: bool = redis::cmd("EXISTS")
+            .arg(format!("{}{}", self.prefix, id))
+            .query_async(&mut con)
+            .await
+            .map_err(|e| TokenError::Storage(e.to_string()))?;
+        Ok(exists)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TOKEN MANAGER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Issues, verifies, refreshes and revokes tokens
+pub struct TokenManager {
+    /// First key signs; all keys verify
+    keys: RwLock<Vec<Arc<SigningKey>>>,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway: i64,
+    revocations: Arc<dyn RevocationList>,
+    /// Secret for pre-JWT `Fortress::generate_token` tokens, if still accepted
+    legacy_secret: Option<String>,
+}
+
+impl TokenManager {
+    /// Manager signing with `key`; 15 minute access and 30 day refresh tokens
+    pub fn new(key: SigningKey) -> Self {
+        Self {
+            keys: RwLock::new(vec![Arc::new(key)]),
+            access_ttl: Duration::from_secs(15 * 60),
+            refresh_ttl: Duration::from_secs(30 * 24 * 3600),
+            issuer: None,
+            audience: None,
+            leeway: 30,
+            revocations: Arc::new(MemoryRevocationList::new()),
+            legacy_secret: None,
+        }
+    }
+
+    /// Also accept tokens signed by `key` (e.g. a retiring key)
+    pub fn with_key(self, key: SigningKey) -> Self {
+        self.write_keys().push(Arc::new(key));
+        self
+    }
+
+    pub fn access_ttl(mut self, ttl: Duration) -> Self {
+        self.access_ttl = ttl;
+        self
+    }
+
+    pub fn refresh_ttl(mut self, ttl: Duration) -> Self {
+        self.refresh_ttl = ttl;
+        self
+    }
+
+    /// Set `iss` on issued tokens and require it when verifying
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// Set `aud` on issued tokens and require it when verifying
+    pub fn audience(mut self, audience: &str) -> Self {
+        self.audience = Some(audience.to_string());
+        self
+    }
+
+    /// Allowed clock skew for `exp`/`nbf`/`iat`
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway.as_secs() as i64;
+        self
+    }
+
+    pub fn revocation_list(mut self, list: Arc<dyn RevocationList>) -> Self {
+        self.revocations = list;
+        self
+    }
+
+    /// Keep accepting legacy `hex(user_id).signature` tokens from
+    /// `Fortress::generate_token` signed with `secret`
+    ///
+    /// Deprecated migration path: those tokens never expire and cannot be
+    /// revoked, so turn this off once clients hold JWTs.
+    pub fn accept_legacy(mut self, secret: &str) -> Self {
+        self.legacy_secret = Some(secret.to_string());
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Key rotation
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Sign new tokens with `key`; previous keys keep verifying until retired
+    pub fn rotate(&self, key: SigningKey) -> Result<(), TokenError> {
+        if !key.can_sign() {
+            return Err(TokenError::Key(format!("key {} is verify-only", key.kid)));
+        }
+        let mut keys = self.write_keys();
+        keys.retain(|k| k.kid != key.kid);
+        keys.insert(0, Arc::new(key));
+        Ok(())
+    }
+
+    /// Stop accepting tokens signed by `kid`. The active key cannot be retired.
+    pub fn retire(&self, kid: &str) -> bool {
+        let mut keys = self.write_keys();
+        let before = keys.len();
+        let active = keys[0].kid.clone();
+        keys.retain(|k| k.kid != kid || k.kid == active);
+        keys.len() != before
+    }
+
+    pub fn active_kid(&self) -> String {
+        self.read_keys()[0].kid.clone()
+    }
+
+    /// Public Ed25519 keys as a JWKS document
+    pub fn jwks(&self) -> Value {
+        let keys: Vec<Value> = self
+            .read_keys()
+            .iter()
+            .filter_map(|k| {
+                k.public_key().map(|public| {
+                    serde_json::json!({
+                        "kty": "OKP",
+                        "crv": "Ed25519",
+                        "use": "sig",
+                        "alg": "EdDSA",
+                        "kid": k.kid,
+                        "x": URL_SAFE_NO_PAD.encode(public),
+                    })
+                })
+            })
+            .collect();
+        serde_json::json!({ "keys": keys })
+    }
+
+    fn read_keys(&self) -> std::sync::RwLockReadGuard<'_, Vec<Arc<SigningKey>>> {
+        self.keys.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_keys(&self) -> std::sync::RwLockWriteGuard<'_, Vec<Arc<SigningKey>>> {
+        self.keys.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Issuing
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Fresh access-token claims for `sub`
+    pub fn claims(&self, sub: &str) -> Claims {
+        let now = now();
+        Claims {
+            sub: sub.to_string(),
+            iat: now,
+            exp: now + self.access_ttl.as_secs() as i64,
+            nbf: None,
+            jti: uuid::Uuid::new_v4().to_string(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone().map(Audience::One),
+            scope: None,
+            sid: None,
+            token_use: TokenUse::Access,
+            custom: serde_json::Map::new(),
+        }
+    }
+
+    /// Sign `claims` with the active key
+    pub fn sign(&self, claims: &Claims) -> Result<String, TokenError> {
+        let key = self.read_keys()[0].clone();
+        let header = Header {
+            alg: key.algorithm().to_string(),
+            typ: Some("JWT".to_string()),
+            kid: Some(key.kid.clone()),
+        };
+        let signing_input = format!("{}.{}", encode_json(&header)?, encode_json(claims)?);
+        let signature = key.sign(signing_input.as_bytes())?;
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Issue an access token for `sub`
+    pub fn issue(&self, sub: &str) -> Result<String, TokenError> {
+        self.sign(&self.claims(sub))
+    }
+
+    /// Issue an access token and a single-use refresh token sharing a session
+    pub async fn issue_pair(&self, claims: Claims) -> Result<TokenPair, TokenError> {
+        let now = now();
+        let sid = claims
+            .sid
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        let access = Claims {
+            iat: now,
+            exp: now + self.access_ttl.as_secs() as i64,
+            jti: uuid::Uuid::new_v4().to_string(),
+            sid: Some(sid.clone()),
+            token_use: TokenUse::Access,
+            ..claims.clone()
+        };
+        let refresh = Claims {
+            iat: now,
+            exp: now + self.refresh_ttl.as_secs() as i64,
+            jti: uuid::Uuid::new_v4().to_string(),
+            sid: Some(sid),
+            token_use: TokenUse::Refresh,
+            ..claims
+        };
+
+        Ok(TokenPair {
+            access_token: self.sign(&access)?,
+            refresh_token: self.sign(&refresh)?,
+            token_type: "Bearer".to_string(),
+            expires_in: self.access_ttl.as_secs(),
+        })
+    }
+
+    /// Exchange a refresh token for a new pair. The old refresh token is
+    /// revoked; presenting it again revokes the whole session.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair, TokenError> {
+        let claims = self.decode(refresh_token)?;
+        if claims.token_use != TokenUse::Refresh {
+            return Err(TokenError::WrongTokenType("refresh"));
+        }
+        self.check_session(&claims).await?;
+
+        if !self
+            .revocations
+            .revoke(&jti_key(&claims.jti), claims.exp)
+            .await?
+        {
+            // Reuse of a rotated refresh token: assume it leaked
+            if let Some(sid) = &claims.sid {
+                self.revoke_session(sid).await?;
+            }
+            return Err(TokenError::Revoked);
+        }
+
+        self.issue_pair(claims).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Verification & revocation
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Check signature, key, algorithm, time window, issuer and audience.
+    /// Does not consult the revocation list; use [`verify`](Self::verify).
+    pub fn decode(&self, token: &str) -> Result<Claims, TokenError> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(TokenError::Malformed("expected three segments".into()));
+        };
+
+        let header: Header = decode_json(header)?;
+        let key = {
+            let keys = self.read_keys();
+            match &header.kid {
+                Some(kid) => keys.iter().find(|k| &k.kid == kid).cloned(),
+                // Tokens without kid are only accepted while there is one key
+                None if keys.len() == 1 => Some(keys[0].clone()),
+                None => None,
+            }
+            .ok_or_else(|| TokenError::UnknownKey(header.kid.clone().unwrap_or_default()))?
+        };
+        // The key decides the algorithm, never the token
+        if header.alg != key.algorithm() {
+            return Err(TokenError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let signature_b64 = signature;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| TokenError::Malformed("signature encoding".into()))?;
+        let signing_input = &token[..token.len() - signature_b64.len() - 1];
+        if !key.verify(signing_input.as_bytes(), &signature) {
+            return Err(TokenError::InvalidSignature);
+        }
+
+        let claims: Claims = decode_json(payload)?;
+        let now = now();
+        if claims.exp + self.leeway < now {
+            return Err(TokenError::Expired);
+        }
+        if claims.nbf.is_some_and(|nbf| nbf - self.leeway > now) || claims.iat - self.leeway > now {
+            return Err(TokenError::NotYetValid);
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.iss.as_deref() != Some(issuer.as_str()) {
+                return Err(TokenError::InvalidIssuer);
+            }
+        }
+        if let Some(audience) = &self.audience {
+            if !claims
+                .aud
+                .as_ref()
+                .is_some_and(|aud| aud.contains(audience))
+            {
+                return Err(TokenError::InvalidAudience);
+            }
+        }
+        Ok(claims)
+    }
+
+    /// Decode an access token and make sure neither it nor its session is revoked
+    pub async fn verify(&self, token: &str) -> Result<Claims, TokenError> {
+        let claims = match self.decode(token) {
+            Ok(claims) => claims,
+            Err(err @ TokenError::Malformed(_)) => return self.decode_legacy(token).ok_or(err),
+            Err(err) => return Err(err),
+        };
+        if claims.token_use != TokenUse::Access {
+            return Err(TokenError::WrongTokenType("access"));
+        }
+        if self.revocations.is_revoked(&jti_key(&claims.jti)).await? {
+            return Err(TokenError::Revoked);
+        }
+        self.check_session(&claims).await?;
+        Ok(claims)
+    }
+
+    /// Revoke a single access or refresh token
+    pub async fn revoke(&self, token: &str) -> Result<(), TokenError> {
+        let claims = self.decode(token)?;
+        self.revocations
+            .revoke(&jti_key(&claims.jti), claims.exp)
+            .await?;
+        Ok(())
+    }
+
+    /// Revoke every token issued for a session (logout everywhere)
+    pub async fn revoke_session(&self, sid: &str) -> Result<(), TokenError> {
+        let until = now() + self.refresh_ttl.as_secs() as i64;
+        self.revocations
+            .revoke(&format!("sid:{}", sid), until)
+            .await?;
+        Ok(())
+    }
+
+    /// Claims for a legacy token when `accept_legacy` is on; `exp` is only
+    /// the lifetime of this verification and `jti` is empty
+    fn decode_legacy(&self, token: &str) -> Option<Claims> {
+        let secret = self.legacy_secret.as_deref()?;
+        let user_id = crate::fortress::Fortress::validate_token(token, secret).ok()?;
+        tracing::warn!("accepted a deprecated legacy auth token; issue JWTs instead");
+        let mut claims = self.claims(&user_id);
+        claims.jti = String::new();
+        claims
+            .custom
+            .insert("legacy".to_string(), Value::Bool(true));
+        Some(claims)
+    }
+
+    async fn check_session(&self, claims: &Claims) -> Result<(), TokenError> {
+        if let Some(sid) = &claims.sid {
+            if self.revocations.is_revoked(&format!("sid:{}", sid)).await? {
+                return Err(TokenError::Revoked);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn jti_key(jti: &str) -> String {
+    format!("jti:{}", jti)
+}
+
+fn encode_json<T: Serialize>(value: &T) -> Result<String, TokenError> {
+    let json = serde_json::to_vec(value).map_err(|e| TokenError::Malformed(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+fn decode_json<T: DeserializeOwned>(segment: &str) -> Result<T, TokenError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| TokenError::Malformed("base64url encoding".into()))?;
+    serde_json::from_slice(&bytes).map_err(|e| TokenError::Malformed(e.to_string()))
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// GLOBAL MANAGER
+// ═══════════════════════════════════════════════════════════════════════════
+
+static MANAGER: OnceLock<TokenManager> = OnceLock::new();
+
+/// Install the application's token manager. Call once at startup.
+pub fn init(manager: TokenManager) -> Result<(), TokenError> {
+    MANAGER
+        .set(manager)
+        .map_err(|_| TokenError::Key("token manager already initialized".into()))
+}
+
+/// The global manager; defaults to HS256 with `app.secret_key` / `SECRET_KEY`,
+/// still accepting legacy tokens signed with the same secret
+pub fn manager() -> Result<&'static TokenManager, TokenError> {
+    if let Some(manager) = MANAGER.get() {
+        return Ok(manager);
+    }
+    let secret = if !GLOBAL_CONFIG.app.secret_key.is_empty() {
+        GLOBAL_CONFIG.app.secret_key.clone()
+    } else {
+        std::env::var("SECRET_KEY")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| TokenError::Key("tokens require app.secret_key or SECRET_KEY".into()))?
+    };
+    Ok(MANAGER.get_or_init(|| {
+        TokenManager::new(SigningKey::hs256("default", &secret)).accept_legacy(&secret)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> TokenManager {
+        TokenManager::new(SigningKey::hs256("k1", "test-secret"))
+    }
+
+    #[tokio::test]
+    async fn test_issue_and_verify() {
+        let tokens = manager();
+        let claims = tokens
+            .claims("user_1")
+            .with_scope("posts:read")
+            .with_scope("posts:write")
+            .with_claim("role", "admin");
+        let token = tokens.sign(&claims).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+
+        let decoded = tokens.verify(&token).await.unwrap();
+        assert_eq!(decoded, claims);
+        assert!(decoded.has_scope("posts:write"));
+        assert_eq!(decoded.get::<String>("role").as_deref(), Some("admin"));
+
+        // Flip one character of the payload
+        let mut tampered: Vec<&str> = token.split('.').collect();
+        let payload = format!("{}A", &tampered[1][..tampered[1].len() - 1]);
+        tampered[1] = &payload;
+        assert!(tokens.decode(&tampered.join(".")).is_err());
+
+        let other = TokenManager::new(SigningKey::hs256("k1", "other-secret"));
+        assert_eq!(other.decode(&token), Err(TokenError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_expiry_and_claim_checks() {
+        let tokens = manager().issuer("nucleus").audience("api");
+        let mut claims = tokens.claims("user_1");
+        claims.exp = now() - 120;
+        assert_eq!(
+            tokens.decode(&tokens.sign(&claims).unwrap()),
+            Err(TokenError::Expired)
+        );
+
+        let mut claims = tokens.claims("user_1");
+        claims.nbf = Some(now() + 3600);
+        assert_eq!(
+            tokens.decode(&tokens.sign(&claims).unwrap()),
+            Err(TokenError::NotYetValid)
+        );
+
+        let mut claims = tokens.claims("user_1");
+        claims.aud = Some(Audience::Many(vec!["web".into(), "api".into()]));
+        assert!(tokens.decode(&tokens.sign(&claims).unwrap()).is_ok());
+        claims.aud = Some(Audience::One("web".into()));
+        assert_eq!(
+            tokens.decode(&tokens.sign(&claims).unwrap()),
+            Err(TokenError::InvalidAudience)
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let tokens = manager();
+        let old = tokens.issue("user_1").unwrap();
+
+        tokens
+            .rotate(SigningKey::hs256("k2", "rotated-secret"))
+            .unwrap();
+        assert_eq!(tokens.active_kid(), "k2");
+        let new = tokens.issue("user_1").unwrap();
+        assert!(tokens.decode(&old).is_ok());
+        assert!(tokens.decode(&new).is_ok());
+
+        assert!(tokens.retire("k1"));
+        assert!(!tokens.retire("k2"));
+        assert_eq!(
+            tokens.decode(&old),
+            Err(TokenError::UnknownKey("k1".into()))
+        );
+        assert!(tokens.decode(&new).is_ok());
+    }
+
+    #[test]
+    fn test_eddsa_and_algorithm_confusion() {
+        let (key, der) = SigningKey::generate_ed25519("ed1").unwrap();
+        let public = key.public_key().unwrap().to_vec();
+        let signer = TokenManager::new(key);
+        let token = signer.issue("user_1").unwrap();
+        assert!(signer.decode(&token).is_ok());
+
+        // A verify-only manager built from the public key accepts it
+        let verifier = TokenManager::new(SigningKey::ed25519_public("ed1", &public));
+        assert_eq!(verifier.decode(&token).unwrap().sub, "user_1");
+        assert!(verifier.issue("user_2").is_err());
+        assert_eq!(
+            verifier.jwks()["keys"][0]["x"],
+            URL_SAFE_NO_PAD.encode(&public)
+        );
+
+        // The same key reloads from PKCS#8
+        let reloaded = TokenManager::new(SigningKey::ed25519_pkcs8("ed1", &der).unwrap());
+        assert!(reloaded.decode(&token).is_ok());
+
+        // An HS256 token claiming the Ed25519 kid is rejected
+        let forged = TokenManager::new(SigningKey::hs256("ed1", &public))
+            .issue("admin")
+            .unwrap();
+        assert_eq!(
+            verifier.decode(&forged),
+            Err(TokenError::UnsupportedAlgorithm("HS256".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotation_and_reuse() {
+        let tokens = manager();
+        let pair = tokens
+            .issue_pair(tokens.claims("user_1").with_claim("tenant", 7))
+            .await
+            .unwrap();
+        assert_eq!(
+            tokens.verify(&pair.refresh_token).await,
+            Err(TokenError::WrongTokenType("access"))
+        );
+
+        let next = tokens.refresh(&pair.refresh_token).await.unwrap();
+        let claims = tokens.verify(&next.access_token).await.unwrap();
+        assert_eq!(claims.get::<i64>("tenant"), Some(7));
+
+        // Replaying the first refresh token kills the session
+        assert_eq!(
+            tokens.refresh(&pair.refresh_token).await.err(),
+            Some(TokenError::Revoked)
+        );
+        assert_eq!(
+            tokens.verify(&next.access_token).await,
+            Err(TokenError::Revoked)
+        );
+        assert!(tokens.refresh(&next.refresh_token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_single_token() {
+        let tokens = manager();
+        let a = tokens.issue("user_1").unwrap();
+        let b = tokens.issue("user_1").unwrap();
+        tokens.revoke(&a).await.unwrap();
+        assert_eq!(tokens.verify(&a).await, Err(TokenError::Revoked));
+        assert!(tokens.verify(&b).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_tokens_during_migration() {
+        use crate::fortress::Fortress;
+
+        let legacy = Fortress::generate_token("user_7", "test-secret");
+        assert!(matches!(
+            manager().verify(&legacy).await,
+            Err(TokenError::Malformed(_))
+        ));
+
+        let tokens = manager().accept_legacy("test-secret");
+        let claims = tokens.verify(&legacy).await.unwrap();
+        assert_eq!(claims.sub, "user_7");
+        assert_eq!(claims.get::<bool>("legacy"), Some(true));
+
+        let forged = Fortress::generate_token("user_7", "other-secret");
+        assert!(tokens.verify(&forged).await.is_err());
+        // JWTs are unaffected
+        let jwt = tokens.issue("user_8").unwrap();
+        assert_eq!(tokens.verify(&jwt).await.unwrap().sub, "user_8");
+    }
+}
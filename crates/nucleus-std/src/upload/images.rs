@@ -0,0 +1,597 @@
+//! Image variants for uploads
+//!
+//! Connects `upload` and `lens`: image uploads can get resized variants
+//! (on upload or on first request), have their EXIF orientation applied and
+//! metadata removed, and carry a BlurHash placeholder. `resize_routes` serves
+//! arbitrary sizes on the fly, but only for URLs signed by an `ImageSigner`,
+//! so clients can't make the server render sizes nobody asked for.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let config = UploadConfig::new()
+//!     .images_only()
+//!     .prefix("photos")
+//!     .variant("thumb", "150x150 cover webp")
+//!     .variant("large", "fit 1600 avif")
+//!     .strip_metadata()
+//!     .placeholders();
+//!
+//! let file = Upload::single(body, content_type, &config).await?;
+//! println!("{}", file.variants["thumb"]);
+//! ```
+
+use super::{Upload, UploadConfig, UploadError, UploadedFile};
+use crate::lens::{Lens, LensError, Transform};
+use crate::storage::{encode_key, Object, Storage, StorageError};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Where `resize_routes` keeps rendered images
+const RESIZE_CACHE: &str = "resized";
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PIPELINE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Formats Lens can decode
+fn is_raster(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "image/jpeg" | "image/png" | "image/gif" | "image/webp" | "image/bmp" | "image/tiff"
+    )
+}
+
+/// Storage key of a variant: `photos/abc.png` becomes `photos/abc_thumb.webp`
+pub fn variant_key(key: &str, name: &str, transform: &Transform) -> String {
+    let (dir, file) = match key.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, key),
+    };
+    let (stem, ext) = match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (file, None),
+    };
+    let variant = match transform.format.map(|f| f.extension()).or(ext) {
+        Some(ext) => format!("{}_{}.{}", stem, name, ext),
+        None => format!("{}_{}", stem, name),
+    };
+    match dir {
+        Some(dir) => format!("{}/{}", dir, variant),
+        None => variant,
+    }
+}
+
+/// Whether `key` looks like a variant output (`abc_thumb.webp`) of one of
+/// the config's variants rather than an original upload
+fn is_variant_output(config: &UploadConfig, key: &str) -> bool {
+    let file = key.rsplit_once('/').map_or(key, |(_, file)| file);
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file,
+    };
+    config.variants.iter().any(|(name, _)| {
+        stem.strip_suffix(name.as_str())
+            .is_some_and(|s| s.ends_with('_'))
+    })
+}
+
+/// Run the config's image steps on a freshly stored upload
+pub(super) async fn process(
+    config: &UploadConfig,
+    file: &mut UploadedFile,
+) -> Result<(), UploadError> {
+    let wanted = config.strip_metadata || config.placeholders || !config.variants.is_empty();
+    if !wanted || !is_raster(&file.mime_type) {
+        return Ok(());
+    }
+    let mut data = config.storage.get(&file.key).await?.data;
+
+    if config.strip_metadata && matches!(file.mime_type.as_str(), "image/jpeg" | "image/png") {
+        let source = data.clone();
+        data = blocking(move || Lens::strip_metadata(&source))
+            .await?
+            .into();
+        config
+            .storage
+            .put(&file.key, data.clone(), &file.mime_type)
+            .await?;
+        file.size = data.len();
+        file.sha256 = hex::encode(Sha256::digest(&data));
+    }
+
+    if config.placeholders {
+        let source = data.clone();
+        file.placeholder = Some(blocking(move || Lens::placeholder(&source)).await?);
+    }
+
+    for (name, transform) in &config.variants {
+        let url = match &config.lazy_variants {
+            Some(base_url) => format!("{}/{}/{}", base_url, name, encode_key(&file.key)),
+            None => {
+                let key = variant_key(&file.key, name, transform);
+                render(config.storage.as_ref(), data.clone(), &key, *transform).await?;
+                config.storage.url(&key)
+            }
+        };
+        file.variants.insert(name.clone(), url);
+    }
+    Ok(())
+}
+
+/// Delete every variant of `key` the config defines
+pub(super) async fn delete_variants(config: &UploadConfig, key: &str) -> Result<(), UploadError> {
+    for (name, transform) in &config.variants {
+        config
+            .storage
+            .delete(&variant_key(key, name, transform))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Transform `source` and store the result under `key`
+async fn render(
+    storage: &dyn Storage,
+    source: Bytes,
+    key: &str,
+    transform: Transform,
+) -> Result<Object, UploadError> {
+    let content_type = transform.output_format(&source).mime_type().to_string();
+    let data: Bytes = blocking(move || Lens::transform(&source, &transform))
+        .await?
+        .into();
+    storage.put(key, data.clone(), &content_type).await?;
+    Ok(Object { data, content_type })
+}
+
+/// Image work is CPU bound; keep it off the async workers
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, LensError> + Send + 'static,
+) -> Result<T, UploadError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| UploadError::Interrupted(e.to_string()))?
+        .map_err(UploadError::from)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// ROUTES
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn image_response(object: Result<Object, UploadError>, cache_control: &'static str) -> Response {
+    match object {
+        Ok(object) => (
+            [
+                (header::CONTENT_TYPE, object.content_type),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+            ],
+            object.data,
+        )
+            .into_response(),
+        Err(UploadError::StorageError(StorageError::NotFound(_))) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(UploadError::StorageError(StorageError::InvalidKey(_))) => {
+            StatusCode::BAD_REQUEST.into_response()
+        }
+        Err(UploadError::ImageError(_)) => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+        Err(e) => {
+            tracing::error!("Image variant failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// The stored object at `cached`, or `transform` of `source` stored there
+async fn cached_render(
+    storage: &dyn Storage,
+    source: &str,
+    cached: &str,
+    transform: Transform,
+) -> Result<Object, UploadError> {
+    match storage.get(cached).await {
+        Err(StorageError::NotFound(_)) => {
+            let original = storage.get(source).await?;
+            render(storage, original.data, cached, transform).await
+        }
+        object => Ok(object?),
+    }
+}
+
+/// Routes generating the config's variants on first request:
+/// `GET /{variant}/{key}`. Mount them at the URL passed to `lazy_variants`:
+///
+/// Only originals under the config's `prefix` are rendered, so the config
+/// needs one; without it every request is a 404. Keys that are themselves
+/// variant outputs are refused, so `thumb` of a thumbnail can't be chained.
+///
+/// ```rust,ignore
+/// let config = UploadConfig::new().variant("thumb", "150x150 cover webp").lazy_variants("/images");
+/// let app = Router::new().nest("/images", variant_routes(config.clone()));
+/// ```
+pub fn variant_routes(config: UploadConfig) -> Router {
+    Router::new()
+        .route("/:variant/*key", get(serve_variant))
+        .with_state(Arc::new(config))
+}
+
+async fn serve_variant(
+    State(config): State<Arc<UploadConfig>>,
+    Path((name, key)): Path<(String, String)>,
+) -> Response {
+    let Some((_, transform)) = config.variants.iter().find(|(n, _)| *n == name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // Only originals uploaded with this config, not anything else in the storage
+    if config.prefix.is_empty()
+        || !key.starts_with(&format!("{}/", config.prefix))
+        || is_variant_output(&config, &key)
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let variant = variant_key(&key, &name, transform);
+    let object = cached_render(config.storage.as_ref(), &key, &variant, *transform).await;
+    image_response(object, "public, max-age=86400")
+}
+
+/// Issues URLs for `resize_routes` mounted at `base_url`
+#[derive(Clone)]
+pub struct ImageSigner {
+    key: Arc<Vec<u8>>,
+    base_url: String,
+}
+
+impl fmt::Debug for ImageSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageSigner")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ImageSigner {
+    pub fn new(secret: impl AsRef<[u8]>, base_url: &str) -> Self {
+        Self {
+            key: Arc::new(secret.as_ref().to_vec()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// URL serving the object at `key` transformed by `transform`
+    pub fn url(&self, key: &str, transform: &Transform) -> String {
+        let spec = transform.to_string().replace(' ', "-");
+        format!(
+            "{}/{}?t={}&signature={}",
+            self.base_url,
+            encode_key(key),
+            spec,
+            hex::encode(self.mac(key, &spec).finalize().into_bytes())
+        )
+    }
+
+    /// Whether `signature` was issued for `spec` of `key`
+    pub fn verify(&self, key: &str, spec: &str, signature: &str) -> bool {
+        hex::decode(signature).is_ok_and(|sig| self.mac(key, spec).verify_slice(&sig).is_ok())
+    }
+
+    fn mac(&self, key: &str, spec: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(format!("{}\n{}", key, spec).as_bytes());
+        mac
+    }
+}
+
+#[derive(Deserialize)]
+struct ResizeQuery {
+    t: String,
+    signature: String,
+}
+
+/// Routes resizing stored images on the fly: `GET /{key}?t=...&signature=...`
+/// as issued by `signer`. Results are kept under `resized/` in the storage.
+///
+/// ```rust,ignore
+/// let signer = ImageSigner::new(&secret, "/img");
+/// let app = Router::new().nest("/img", resize_routes(storage.clone(), signer.clone()));
+///
+/// let src = signer.url(&file.key, &"640x480 cover webp".parse()?);
+/// ```
+pub fn resize_routes(storage: Arc<dyn Storage>, signer: ImageSigner) -> Router {
+    Router::new()
+        .route("/*key", get(serve_resized))
+        .with_state((storage, signer))
+}
+
+async fn serve_resized(
+    State((storage, signer)): State<(Arc<dyn Storage>, ImageSigner)>,
+    Path(key): Path<String>,
+    Query(query): Query<ResizeQuery>,
+) -> Response {
+    if !signer.verify(&key, &query.t, &query.signature) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Ok(transform) = query.t.parse::<Transform>() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let cached = format!("{}/{}/{}", RESIZE_CACHE, query.t, key);
+    let object = cached_render(storage.as_ref(), &key, &cached, transform).await;
+    // A signed URL always renders the same image
+    image_response(object, "public, max-age=31536000, immutable")
+}
+
+impl Upload {
+    /// Stored variant `name` of an upload, generating it if it's missing
+    pub async fn variant(
+        file: &UploadedFile,
+        name: &str,
+        config: &UploadConfig,
+    ) -> Result<Object, UploadError> {
+        let (_, transform) = config
+            .variants
+            .iter()
+            .find(|(n, _)| n == name)
+            .ok_or_else(|| StorageError::NotFound(format!("variant '{}'", name)))?;
+        let key = variant_key(&file.key, name, transform);
+        cached_render(config.storage.as_ref(), &file.key, &key, *transform).await
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lens::{Fit, ImageFormat};
+    use crate::storage::MemoryStorage;
+    use axum::body::Body;
+    use axum::http::Request;
+    use futures_util::stream;
+    use tower::ServiceExt;
+
+    /// A 16x8 PNG
+    fn png() -> Vec<u8> {
+        let img = image::RgbImage::from_fn(16, 8, |x, _| {
+            if x < 8 {
+                image::Rgb([255, 0, 0])
+            } else {
+                image::Rgb([0, 0, 255])
+            }
+        });
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buffer, image::ImageFormat::Png).unwrap();
+        buffer.into_inner()
+    }
+
+    async fn upload(
+        config: &UploadConfig,
+        name: &str,
+        data: Vec<u8>,
+    ) -> Result<UploadedFile, UploadError> {
+        let chunks = stream::once(async move { Ok(Bytes::from(data)) });
+        super::super::store(
+            config,
+            name.to_string(),
+            "file".to_string(),
+            Box::pin(chunks),
+        )
+        .await
+    }
+
+    async fn get(app: &Router, uri: &str) -> (StatusCode, String, Bytes) {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let status = response.status();
+        (
+            status,
+            content_type,
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_variant_key() {
+        let thumb = Transform::new(4, 4, Fit::Cover).with_format(ImageFormat::WebP(80));
+        let same = Transform::new(4, 4, Fit::Cover);
+        assert_eq!(
+            variant_key("photos/a.png", "thumb", &thumb),
+            "photos/a_thumb.webp"
+        );
+        assert_eq!(
+            variant_key("photos/a.png", "small", &same),
+            "photos/a_small.png"
+        );
+        assert_eq!(variant_key("a", "thumb", &same), "a_thumb");
+        assert_eq!(
+            variant_key(".hidden", "thumb", &thumb),
+            ".hidden_thumb.webp"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_variants_on_upload() {
+        let storage = Arc::new(MemoryStorage::new());
+        let config = UploadConfig::new()
+            .backend(storage.clone())
+            .prefix("photos")
+            .variant("thumb", "4x4 cover webp")
+            .variant("small", "fit 8 jpeg")
+            .strip_metadata()
+            .placeholders();
+
+        let file = upload(&config, "photo.png", png()).await.unwrap();
+        assert_eq!(file.mime_type, "image/png");
+
+        let thumb_key = variant_key(&file.key, "thumb", &config.variants[0].1);
+        assert_eq!(file.variants["thumb"], storage.url(&thumb_key));
+        let thumb = storage.get(&thumb_key).await.unwrap();
+        assert_eq!(thumb.content_type, "image/webp");
+        let dims = Lens::dimensions(&thumb.data).unwrap();
+        assert_eq!((dims.width, dims.height), (4, 4));
+
+        let small = Upload::variant(&file, "small", &config).await.unwrap();
+        assert_eq!(small.content_type, "image/jpeg");
+        let dims = Lens::dimensions(&small.data).unwrap();
+        assert_eq!((dims.width, dims.height), (8, 4));
+
+        let placeholder = file.placeholder.clone().unwrap();
+        assert_eq!((placeholder.width, placeholder.height), (16, 8));
+        assert_eq!(placeholder.blurhash.len(), 28);
+
+        // The stored original was re-encoded; size and hash describe it
+        let original = storage.get(&file.key).await.unwrap();
+        assert_eq!(file.size, original.data.len());
+        assert_eq!(file.sha256, hex::encode(Sha256::digest(&original.data)));
+
+        assert_eq!(storage.len(), 3);
+        Upload::delete(&file, &config).await.unwrap();
+        assert!(storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_non_images_are_left_alone() {
+        let storage = Arc::new(MemoryStorage::new());
+        let config = UploadConfig::new()
+            .backend(storage.clone())
+            .variant("thumb", "4x4 cover webp")
+            .placeholders();
+
+        let file = upload(&config, "notes.txt", b"hello".to_vec())
+            .await
+            .unwrap();
+        assert!(file.variants.is_empty());
+        assert!(file.placeholder.is_none());
+        assert_eq!(storage.len(), 1);
+
+        // Claims to be a PNG but doesn't decode: rejected and removed
+        let err = upload(&config, "broken.png", b"\x89PNG\r\n\x1A\ngarbage".to_vec())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UploadError::ImageError(_)));
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_variants() {
+        let storage = Arc::new(MemoryStorage::new());
+        let config = UploadConfig::new()
+            .backend(storage.clone())
+            .prefix("photos")
+            .variant("thumb", "4x4 cover webp")
+            .lazy_variants("/images/");
+
+        let file = upload(&config, "photo.png", png()).await.unwrap();
+        let url = file.variants["thumb"].clone();
+        assert_eq!(url, format!("/images/thumb/{}", file.key));
+        assert_eq!(storage.len(), 1);
+
+        let app = Router::new().nest("/images", variant_routes(config.clone()));
+        let (status, content_type, body) = get(&app, &url).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "image/webp");
+        assert_eq!(Lens::dimensions(&body).unwrap().width, 4);
+        assert_eq!(storage.len(), 2);
+
+        // Served from storage the second time
+        let (status, _, cached) = get(&app, &url).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cached, body);
+
+        storage
+            .put("private/secret.png", png().into(), "image/png")
+            .await
+            .unwrap();
+        assert_eq!(
+            get(&app, "/images/thumb/private/secret.png").await.0,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            get(&app, &format!("/images/huge/{}", file.key)).await.0,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            get(&app, "/images/thumb/photos/missing.png").await.0,
+            StatusCode::NOT_FOUND
+        );
+
+        // Variant outputs are not sources for further variants
+        let chained = format!(
+            "/images/thumb/{}",
+            variant_key(&file.key, "thumb", &"4x4 cover webp".parse().unwrap())
+        );
+        assert_eq!(get(&app, &chained).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(storage.len(), 3);
+
+        // Without a prefix nothing in the storage is fair game
+        let unscoped = UploadConfig::new()
+            .backend(storage.clone())
+            .variant("thumb", "4x4 cover webp")
+            .lazy_variants("/images");
+        let app = Router::new().nest("/images", variant_routes(unscoped));
+        assert_eq!(get(&app, &url).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_resize_routes() {
+        let storage = Arc::new(MemoryStorage::new());
+        storage
+            .put("photos/a.png", png().into(), "image/png")
+            .await
+            .unwrap();
+        let signer = ImageSigner::new("secret", "/img/");
+        let app = Router::new().nest("/img", resize_routes(storage.clone(), signer.clone()));
+
+        let url = signer.url("photos/a.png", &"6x6 cover jpeg q70".parse().unwrap());
+        assert!(url.starts_with("/img/photos/a.png?t=6x6-cover-jpg-q70&signature="));
+        let (status, content_type, body) = get(&app, &url).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "image/jpeg");
+        let dims = Lens::dimensions(&body).unwrap();
+        assert_eq!((dims.width, dims.height), (6, 6));
+        assert!(storage
+            .get("resized/6x6-cover-jpg-q70/photos/a.png")
+            .await
+            .is_ok());
+
+        // Asking for another size with the same signature
+        let tampered = url.replace("6x6", "4000x4000");
+        assert_eq!(get(&app, &tampered).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(
+            get(&app, "/img/photos/a.png?t=6x6&signature=00").await.0,
+            StatusCode::FORBIDDEN
+        );
+
+        let missing = signer.url("photos/b.png", &"6x6 cover".parse().unwrap());
+        assert_eq!(get(&app, &missing).await.0, StatusCode::NOT_FOUND);
+
+        storage
+            .put("notes.txt", "hello".into(), "text/plain")
+            .await
+            .unwrap();
+        let text = signer.url("notes.txt", &"6x6 cover".parse().unwrap());
+        assert_eq!(get(&app, &text).await.0, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -1,9 +1,13 @@
 //! Nucleus File Upload Module
 //!
 //! Provides comprehensive file upload handling with:
-//! - Multipart form parsing
-//! - File size and MIME type validation
-//! - Secure file storage
+//! - Multipart form parsing, streamed to storage chunk by chunk
+//! - File size and MIME type validation (types come from the file's magic
+//!   bytes, not the client's `Content-Type`)
+//! - SHA-256 of every file
+//! - Storage on disk, in memory or in S3 (see `storage`)
+//! - Resumable uploads with the tus protocol (see `TusServer`)
+//! - Image variants, metadata stripping and placeholders (see `images`)
 //! - Unique filename generation
 //!
 //! # Example
@@ -13,19 +17,34 @@
 //!
 //! let config = UploadConfig::default()
 //!     .max_size(5_000_000) // 5MB
-//!     .allowed_types(vec!["image/jpeg", "image/png"]);
+//!     .allowed_types(vec!["image/jpeg", "image/png"])
+//!     .backend(Arc::new(S3Storage::new(endpoint, "uploads", key, secret)))
+//!     .prefix("avatars");
 //!
 //! let files = Upload::from_multipart(body, content_type, &config).await?;
 //! for file in files {
-//!     println!("Uploaded: {} ({} bytes)", file.original_name, file.size);
+//!     println!("Uploaded: {} ({} bytes)", file.key, file.size);
 //! }
 //! ```
 
-use std::path::{Path, PathBuf};
-// use std::io::Write;
+use crate::lens::{LensError, Placeholder, Transform};
+use crate::storage::{LocalStorage, Storage, StorageError};
+use axum::body::Bytes;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+pub mod images;
+pub mod sniff;
+mod tus;
+
+pub use tus::{TusServer, TUS_VERSION};
+
 // ═══════════════════════════════════════════════════════════════════════════
 // CONFIGURATION
 // ═══════════════════════════════════════════════════════════════════════════
@@ -35,14 +54,25 @@ use uuid::Uuid;
 pub struct UploadConfig {
     /// Maximum file size in bytes (default: 10MB)
     pub max_file_size: usize,
-    /// Allowed MIME types (empty = allow all)
+    /// Allowed MIME types, e.g. `image/png` or `image/*` (empty = allow all)
     pub allowed_types: Vec<String>,
-    /// Storage directory path
-    pub storage_path: PathBuf,
+    /// Where files are stored (default: local `uploads/` directory)
+    pub storage: Arc<dyn Storage>,
+    /// Key prefix for stored files, e.g. `avatars`
+    pub prefix: String,
     /// Whether to generate unique filenames
     pub generate_unique_names: bool,
     /// Preserve original extension
     pub preserve_extension: bool,
+    /// Image variants by name, e.g. `thumb`
+    pub variants: Vec<(String, Transform)>,
+    /// Where `images::variant_routes` is mounted, if variants are generated
+    /// on first request instead of on upload
+    pub lazy_variants: Option<String>,
+    /// Apply EXIF orientation to JPEG and PNG uploads and drop their metadata
+    pub strip_metadata: bool,
+    /// Compute a `Placeholder` for image uploads
+    pub placeholders: bool,
 }
 
 impl Default for UploadConfig {
@@ -50,9 +80,14 @@ impl Default for UploadConfig {
         Self {
             max_file_size: 10 * 1024 * 1024, // 10MB
             allowed_types: vec![],
-            storage_path: PathBuf::from("uploads"),
+            storage: Arc::new(LocalStorage::new("uploads")),
+            prefix: String::new(),
             generate_unique_names: true,
             preserve_extension: true,
+            variants: vec![],
+            lazy_variants: None,
+            strip_metadata: false,
+            placeholders: false,
         }
     }
 }
@@ -98,17 +133,100 @@ impl UploadConfig {
         self
     }
 
-    /// Set storage path
+    /// Store files in a local directory
     pub fn storage(mut self, path: impl Into<PathBuf>) -> Self {
-        self.storage_path = path.into();
+        self.storage = Arc::new(LocalStorage::new(path));
+        self
+    }
+
+    /// Store files in any storage backend
+    pub fn backend(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Store files under `prefix/`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_matches('/').to_string();
         self
     }
 
+    /// Storage key for a stored file name
+    fn key(&self, stored_name: &str) -> String {
+        if self.prefix.is_empty() {
+            stored_name.to_string()
+        } else {
+            format!("{}/{}", self.prefix, stored_name)
+        }
+    }
+
+    /// Name to store a file under
+    fn stored_name(&self, original_name: &str) -> String {
+        if !self.generate_unique_names {
+            return original_name.to_string();
+        }
+        let uuid = Uuid::new_v4();
+        match Path::new(original_name).extension() {
+            Some(ext) if self.preserve_extension => format!("{}.{}", uuid, ext.to_string_lossy()),
+            _ => uuid.to_string(),
+        }
+    }
+
+    /// Whether `mime_type` matches `allowed_types`
+    pub fn allows(&self, mime_type: &str) -> bool {
+        self.allowed_types.is_empty()
+            || self
+                .allowed_types
+                .iter()
+                .any(|allowed| match allowed.strip_suffix("/*") {
+                    Some(family) => mime_type
+                        .split_once('/')
+                        .is_some_and(|(ty, _)| ty == family),
+                    None => allowed == mime_type,
+                })
+    }
+
     /// Disable unique name generation
     pub fn keep_original_names(mut self) -> Self {
         self.generate_unique_names = false;
         self
     }
+
+    /// Generate a variant of every image upload, e.g.
+    /// `.variant("thumb", "150x150 cover webp")` (see `lens::Transform`)
+    ///
+    /// # Panics
+    ///
+    /// If `spec` isn't a valid transform.
+    pub fn variant(mut self, name: &str, spec: &str) -> Self {
+        let transform = spec
+            .parse()
+            .unwrap_or_else(|e: LensError| panic!("variant '{}': {}", name, e.0));
+        self.variants.push((name.to_string(), transform));
+        self
+    }
+
+    /// Generate variants on first request through `images::variant_routes`
+    /// mounted at `base_url`, instead of on upload
+    ///
+    /// The routes only serve uploads under `prefix`, so set one as well.
+    pub fn lazy_variants(mut self, base_url: &str) -> Self {
+        self.lazy_variants = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Correct the orientation of JPEG and PNG uploads and remove their EXIF
+    /// data (GPS position, camera details)
+    pub fn strip_metadata(mut self) -> Self {
+        self.strip_metadata = true;
+        self
+    }
+
+    /// Compute dimensions, a BlurHash and the dominant color of image uploads
+    pub fn placeholders(mut self) -> Self {
+        self.placeholders = true;
+        self
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -122,16 +240,25 @@ pub struct UploadedFile {
     pub original_name: String,
     /// Name used for storage
     pub stored_name: String,
-    /// Full path to the stored file
-    pub path: PathBuf,
+    /// Storage key (prefix and stored name)
+    pub key: String,
     /// File size in bytes
     pub size: usize,
-    /// MIME type
+    /// MIME type, detected from the file's contents
     pub mime_type: String,
+    /// SHA-256 of the contents, hex encoded
+    #[serde(default)]
+    pub sha256: String,
     /// URL path to access the file
     pub url: String,
     /// Field name from the form
     pub field_name: String,
+    /// URLs of the image variants by name
+    #[serde(default)]
+    pub variants: HashMap<String, String>,
+    /// Shown while the image loads, when `placeholders` is enabled
+    #[serde(default)]
+    pub placeholder: Option<Placeholder>,
 }
 
 impl UploadedFile {
@@ -153,9 +280,9 @@ impl UploadedFile {
         self.stored_name.rsplit('.').next()
     }
 
-    /// Delete the uploaded file
-    pub async fn delete(&self) -> Result<(), std::io::Error> {
-        tokio::fs::remove_file(&self.path).await
+    /// Delete the uploaded file from the storage it was saved to
+    pub async fn delete(&self, storage: &dyn Storage) -> Result<(), StorageError> {
+        storage.delete(&self.key).await
     }
 }
 
@@ -176,11 +303,17 @@ pub enum UploadError {
     },
 
     #[error("Storage error: {0}")]
-    StorageError(#[from] std::io::Error),
+    StorageError(#[from] StorageError),
+
+    #[error("Image processing error: {0}")]
+    ImageError(#[from] LensError),
 
     #[error("Multipart parse error: {0}")]
     ParseError(String),
 
+    #[error("Upload interrupted: {0}")]
+    Interrupted(String),
+
     #[error("No file provided")]
     NoFile,
 
@@ -197,6 +330,9 @@ pub struct Upload;
 
 impl Upload {
     /// Parse multipart request and save files
+    ///
+    /// Parts without a filename are plain form fields (e.g. `_csrf`) and are
+    /// skipped, as are file inputs left empty.
     pub async fn from_multipart(
         body: axum::body::Body,
         content_type: &str,
@@ -209,93 +345,49 @@ impl Upload {
             .ok_or(UploadError::InvalidContentType)?
             .trim_matches('"');
 
-        // Ensure storage directory exists
-        tokio::fs::create_dir_all(&config.storage_path).await?;
-
         // Parse multipart
         let stream = body.into_data_stream();
         let mut multipart = multer::Multipart::new(stream, boundary);
 
         let mut files = Vec::new();
+        let parsed = Self::store_fields(&mut multipart, config, &mut files).await;
+
+        // Don't leave earlier files of a rejected request behind
+        if let Err(e) = parsed {
+            for file in &files {
+                let _ = Self::delete(file, config).await;
+            }
+            return Err(e);
+        }
+        Ok(files)
+    }
 
+    async fn store_fields(
+        multipart: &mut multer::Multipart<'_>,
+        config: &UploadConfig,
+        files: &mut Vec<UploadedFile>,
+    ) -> Result<(), UploadError> {
         while let Some(field) = multipart
             .next_field()
             .await
             .map_err(|e| UploadError::ParseError(e.to_string()))?
         {
             let field_name = field.name().unwrap_or("file").to_string();
-            let original_name = field
+            // Browsers may send a full client path; keep only the name
+            let Some(original_name) = field
                 .file_name()
+                .and_then(|s| s.rsplit(['/', '\\']).next())
+                .filter(|s| !s.is_empty())
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| format!("upload_{}", Uuid::new_v4()));
-
-            let content_type = field
-                .content_type()
-                .map(|m| m.to_string())
-                .unwrap_or_else(|| {
-                    mime_guess::from_path(&original_name)
-                        .first_or_octet_stream()
-                        .to_string()
-                });
-
-            // Validate MIME type
-            if !config.allowed_types.is_empty() && !config.allowed_types.contains(&content_type) {
-                return Err(UploadError::InvalidMimeType {
-                    expected: config.allowed_types.clone(),
-                    actual: content_type,
-                });
-            }
-
-            // Read file data
-            let data = field
-                .bytes()
-                .await
-                .map_err(|e| UploadError::ParseError(e.to_string()))?;
-
-            // Validate size
-            if data.len() > config.max_file_size {
-                return Err(UploadError::FileTooLarge {
-                    max: config.max_file_size,
-                    actual: data.len(),
-                });
-            }
-
-            // Generate filename
-            let stored_name = if config.generate_unique_names {
-                let uuid = Uuid::new_v4();
-                if config.preserve_extension {
-                    if let Some(ext) = Path::new(&original_name).extension() {
-                        format!("{}.{}", uuid, ext.to_string_lossy())
-                    } else {
-                        uuid.to_string()
-                    }
-                } else {
-                    uuid.to_string()
-                }
-            } else {
-                original_name.clone()
+            else {
+                continue;
             };
 
-            // Save file
-            let path = config.storage_path.join(&stored_name);
-            use tokio::io::AsyncWriteExt;
-            let mut file = tokio::fs::File::create(&path).await?;
-            file.write_all(&data).await?;
-
-            let url = format!("/uploads/{}", stored_name);
-
-            files.push(UploadedFile {
-                original_name,
-                stored_name,
-                path,
-                size: data.len(),
-                mime_type: content_type,
-                url,
-                field_name,
-            });
+            let chunks = field.map_err(|e| UploadError::ParseError(e.to_string()));
+            let file = store(config, original_name, field_name, Box::pin(chunks)).await?;
+            files.push(file);
         }
-
-        Ok(files)
+        Ok(())
     }
 
     /// Parse a single file from multipart
@@ -319,7 +411,7 @@ impl Upload {
         }
 
         // Check MIME type
-        if !config.allowed_types.is_empty() && !config.allowed_types.contains(&file.mime_type) {
+        if !config.allows(&file.mime_type) {
             return Err(UploadError::InvalidMimeType {
                 expected: config.allowed_types.clone(),
                 actual: file.mime_type.clone(),
@@ -329,37 +421,136 @@ impl Upload {
         Ok(())
     }
 
-    /// Delete an uploaded file
-    pub async fn delete(file: &UploadedFile) -> Result<(), std::io::Error> {
-        tokio::fs::remove_file(&file.path).await
+    /// Delete an uploaded file and its image variants
+    pub async fn delete(file: &UploadedFile, config: &UploadConfig) -> Result<(), UploadError> {
+        file.delete(config.storage.as_ref()).await?;
+        images::delete_variants(config, &file.key).await
     }
 
-    /// Delete files older than specified duration
+    /// Delete files under the config's prefix older than `max_age`
     pub async fn cleanup_old_files(
-        storage_path: &Path,
+        config: &UploadConfig,
         max_age: std::time::Duration,
-    ) -> Result<usize, std::io::Error> {
+    ) -> Result<usize, UploadError> {
+        let prefix = if config.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", config.prefix)
+        };
+        let cutoff = chrono::Utc::now()
+            - chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX);
+
         let mut deleted = 0;
-        let now = std::time::SystemTime::now();
+        for object in config.storage.list(&prefix).await? {
+            if object.last_modified < cutoff {
+                config.storage.delete(&object.key).await?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// STREAMING
+// ═══════════════════════════════════════════════════════════════════════════
 
-        // Read dir is a bit complex with tokio, reading entries into a vec first
-        let mut entries = tokio::fs::read_dir(storage_path).await?;
+/// Running totals of a file being streamed to storage
+#[derive(Default)]
+struct Progress {
+    size: usize,
+    hasher: Sha256,
+    error: Option<UploadError>,
+}
 
-        while let Some(entry) = entries.next_entry().await? {
-            let metadata = entry.metadata().await?;
+/// Stream a file to storage: detect its type from the first bytes, then
+/// write it while enforcing `max_file_size` and hashing it
+async fn store(
+    config: &UploadConfig,
+    original_name: String,
+    field_name: String,
+    mut chunks: BoxStream<'_, Result<Bytes, UploadError>>,
+) -> Result<UploadedFile, UploadError> {
+    let mut head = Vec::new();
+    while head.len() < sniff::SNIFF_LEN {
+        match chunks.try_next().await? {
+            Some(chunk) => head.extend_from_slice(&chunk),
+            None => break,
+        }
+    }
+    let mime_type = sniff::detect(&head, &original_name);
+    if !config.allows(&mime_type) {
+        return Err(UploadError::InvalidMimeType {
+            expected: config.allowed_types.clone(),
+            actual: mime_type,
+        });
+    }
 
-            if let Ok(modified) = metadata.modified() {
-                if let Ok(age) = now.duration_since(modified) {
-                    if age > max_age {
-                        tokio::fs::remove_file(entry.path()).await?;
-                        deleted += 1;
+    let max = config.max_file_size;
+    let progress = Arc::new(Mutex::new(Progress::default()));
+    let tracked = {
+        let progress = progress.clone();
+        stream::once(async { Ok(Bytes::from(head)) })
+            .chain(chunks)
+            .map(move |chunk| {
+                let mut progress = progress.lock().unwrap();
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        progress.error = Some(e);
+                        return Err(StorageError::Backend("upload aborted".to_string()));
                     }
+                };
+                progress.size += chunk.len();
+                if progress.size > max {
+                    progress.error = Some(UploadError::FileTooLarge {
+                        max,
+                        actual: progress.size,
+                    });
+                    return Err(StorageError::Backend("upload aborted".to_string()));
                 }
-            }
+                progress.hasher.update(&chunk);
+                Ok(chunk)
+            })
+    };
+
+    let stored_name = config.stored_name(&original_name);
+    let key = config.key(&stored_name);
+    let written = config
+        .storage
+        .put_stream(&key, Box::pin(tracked), &mime_type)
+        .await;
+
+    let (size, sha256) = {
+        let mut progress = progress.lock().unwrap();
+        if let Some(e) = progress.error.take() {
+            return Err(e);
         }
-
-        Ok(deleted)
+        written?;
+        (
+            progress.size,
+            hex::encode(std::mem::take(&mut progress.hasher).finalize()),
+        )
+    };
+
+    let mut file = UploadedFile {
+        original_name,
+        stored_name,
+        url: config.storage.url(&key),
+        key,
+        size,
+        mime_type,
+        sha256,
+        field_name,
+        variants: HashMap::new(),
+        placeholder: None,
+    };
+
+    if let Err(e) = images::process(config, &mut file).await {
+        let _ = Upload::delete(&file, config).await;
+        return Err(e);
     }
+    Ok(file)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -387,7 +578,7 @@ mod tests {
 
         assert_eq!(config.max_file_size, 5_000_000);
         assert!(config.allowed_types.contains(&"image/jpeg".to_string()));
-        assert_eq!(config.storage_path, PathBuf::from("custom/path"));
+        assert!(format!("{:?}", config.storage).contains("custom/path"));
     }
 
     #[test]
@@ -395,11 +586,14 @@ mod tests {
         let file = UploadedFile {
             original_name: "test.jpg".to_string(),
             stored_name: "abc123.jpg".to_string(),
-            path: PathBuf::from("/uploads/abc123.jpg"),
+            key: "abc123.jpg".to_string(),
             size: 1024,
             mime_type: "image/jpeg".to_string(),
+            sha256: String::new(),
             url: "/uploads/abc123.jpg".to_string(),
             field_name: "avatar".to_string(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
 
         assert!(file.is_image());
@@ -447,11 +641,14 @@ mod tests {
         let jpeg = UploadedFile {
             original_name: "".into(),
             stored_name: "".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 0,
             mime_type: "image/jpeg".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
         let png = UploadedFile {
             mime_type: "image/png".into(),
@@ -487,11 +684,14 @@ mod tests {
         let base = UploadedFile {
             original_name: "".into(),
             stored_name: "".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 0,
             mime_type: "".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
 
         let pdf = UploadedFile {
@@ -528,11 +728,14 @@ mod tests {
         let with_ext = UploadedFile {
             original_name: "".into(),
             stored_name: "file.png".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 0,
             mime_type: "".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
         let multi_ext = UploadedFile {
             stored_name: "file.tar.gz".into(),
@@ -560,11 +763,14 @@ mod tests {
         let small_file = UploadedFile {
             original_name: "".into(),
             stored_name: "".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 500,
             mime_type: "image/png".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
         let large_file = UploadedFile {
             size: 2000,
@@ -595,11 +801,14 @@ mod tests {
         let image = UploadedFile {
             original_name: "".into(),
             stored_name: "".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 100,
             mime_type: "image/png".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
         let pdf = UploadedFile {
             mime_type: "application/pdf".into(),
@@ -625,11 +834,14 @@ mod tests {
         let any_file = UploadedFile {
             original_name: "".into(),
             stored_name: "".into(),
-            path: PathBuf::new(),
+            key: "".into(),
             size: 100,
             mime_type: "some/random-type".into(),
+            sha256: "".into(),
             url: "".into(),
             field_name: "".into(),
+            variants: HashMap::new(),
+            placeholder: None,
         };
 
         assert!(Upload::validate(&any_file, &config).is_ok());
@@ -657,6 +869,132 @@ mod tests {
         assert!(err.to_string().contains("content type"));
     }
 
+    fn multipart(files: &[(&str, &str, &[u8])]) -> (axum::body::Body, String) {
+        let mut body = Vec::new();
+        for (name, content_type, contents) in files {
+            body.extend_from_slice(format!(
+                "--XBOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                name, content_type
+            ).as_bytes());
+            body.extend_from_slice(contents);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"--XBOUNDARY--\r\n");
+        (
+            axum::body::Body::from(body),
+            "multipart/form-data; boundary=XBOUNDARY".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_upload_to_storage_backend() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let config = UploadConfig::new()
+            .backend(storage.clone())
+            .prefix("/docs/")
+            .keep_original_names();
+
+        let (body, content_type) = multipart(&[
+            ("notes.txt", "text/plain", b"hello"),
+            ("C:\\Users\\me\\report.txt", "text/plain", b"report"),
+        ]);
+        let files = Upload::from_multipart(body, &content_type, &config)
+            .await
+            .unwrap();
+
+        assert_eq!(files[0].key, "docs/notes.txt");
+        assert_eq!(files[0].url, "/uploads/docs/notes.txt");
+        assert_eq!(files[0].size, 5);
+        assert_eq!(files[1].original_name, "report.txt");
+        assert_eq!(
+            &storage.get("docs/report.txt").await.unwrap().data[..],
+            b"report"
+        );
+
+        assert_eq!(
+            Upload::cleanup_old_files(&config, std::time::Duration::from_secs(60))
+                .await
+                .unwrap(),
+            0
+        );
+        Upload::delete(&files[0], &config).await.unwrap();
+        assert_eq!(
+            Upload::cleanup_old_files(&config, std::time::Duration::ZERO)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_skips_form_fields() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let config = UploadConfig::new()
+            .backend(storage.clone())
+            .allowed_types(vec!["text/plain"]);
+
+        let mut body =
+            b"--XBOUNDARY\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\ntoken123\r\n"
+                .to_vec();
+        let (files, content_type) = multipart(&[("notes.txt", "text/plain", b"hello")]);
+        body.extend_from_slice(&axum::body::to_bytes(files, usize::MAX).await.unwrap());
+
+        let files = Upload::from_multipart(axum::body::Body::from(body), &content_type, &config)
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].original_name, "notes.txt");
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_type_from_content() {
+        let png: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let config = UploadConfig::new().backend(storage.clone()).images_only();
+
+        // The declared type is ignored in favour of the bytes
+        let (body, content_type) = multipart(&[("dot", "text/plain", png)]);
+        let files = Upload::from_multipart(body, &content_type, &config)
+            .await
+            .unwrap();
+        assert_eq!(files[0].mime_type, "image/png");
+        assert_eq!(files[0].size, png.len());
+        assert_eq!(files[0].sha256, hex::encode(Sha256::digest(png)));
+
+        let (body, content_type) = multipart(&[(
+            "cat.png",
+            "image/png",
+            b"<!DOCTYPE html><script>alert(1)</script>",
+        )]);
+        let err = Upload::from_multipart(body, &content_type, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UploadError::InvalidMimeType { .. }));
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_size_limit_stores_nothing() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let config = UploadConfig::new().backend(storage.clone()).max_size(8);
+
+        let (body, content_type) = multipart(&[
+            ("small.txt", "text/plain", b"fits"),
+            ("large.txt", "text/plain", b"far too large"),
+        ]);
+        let err = Upload::from_multipart(body, &content_type, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            UploadError::FileTooLarge { max: 8, actual } if actual > 8
+        ));
+        // The file stored before the failure is removed as well
+        assert!(storage.is_empty());
+    }
+
     #[test]
     fn test_config_chaining() {
         let config = UploadConfig::new()
@@ -667,7 +1005,7 @@ mod tests {
 
         assert_eq!(config.max_file_size, 1_000_000);
         assert_eq!(config.allowed_types.len(), 2);
-        assert_eq!(config.storage_path, PathBuf::from("/custom/uploads"));
+        assert!(format!("{:?}", config.storage).contains("/custom/uploads"));
         assert!(!config.generate_unique_names);
     }
 }
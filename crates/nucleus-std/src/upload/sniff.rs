@@ -0,0 +1,237 @@
+//! Content type detection from magic bytes
+//!
+//! Uploads are typed by what they contain, not by the `Content-Type` the
+//! client sent, so a script renamed to `photo.png` is not accepted as an
+//! image.
+
+/// Bytes of a file needed to recognise every supported format
+pub const SNIFF_LEN: usize = 512;
+
+/// Detect the MIME type of a file from its first bytes (up to `SNIFF_LEN`)
+///
+/// The file name only disambiguates formats that share a container:
+/// ZIP-based documents (docx, xlsx, epub, ...), legacy Office files and
+/// plain text (csv, json, ...). Unknown binary data is
+/// `application/octet-stream`.
+pub fn detect(head: &[u8], name: &str) -> String {
+    if let Some(mime) = binary(head) {
+        return mime.to_string();
+    }
+
+    let by_name = mime_guess::from_path(name).first_or_octet_stream();
+    let by_name = by_name.essence_str();
+
+    if head.starts_with(b"PK\x03\x04") {
+        let zip_based = by_name.starts_with("application/vnd.openxmlformats-")
+            || by_name.starts_with("application/vnd.oasis.opendocument.")
+            || matches!(by_name, "application/epub+zip" | "application/java-archive");
+        return if zip_based {
+            by_name
+        } else {
+            "application/zip"
+        }
+        .to_string();
+    }
+    if head.starts_with(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1") {
+        let office = by_name == "application/msword" || by_name.starts_with("application/vnd.ms-");
+        return if office {
+            by_name
+        } else {
+            "application/octet-stream"
+        }
+        .to_string();
+    }
+
+    match text(head) {
+        Some(text) => {
+            let start = text.trim_start().to_ascii_lowercase();
+            if start.starts_with("<svg") || (start.starts_with("<?xml") && start.contains("<svg")) {
+                "image/svg+xml"
+            } else if start.starts_with("<!doctype html") || start.starts_with("<html") {
+                "text/html"
+            } else if by_name.starts_with("text/")
+                || matches!(
+                    by_name,
+                    "application/json" | "application/xml" | "application/javascript"
+                )
+            {
+                by_name
+            } else {
+                "text/plain"
+            }
+        }
+        None => "application/octet-stream",
+    }
+    .to_string()
+}
+
+/// Formats with an unambiguous signature
+fn binary(head: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+
+    let mime = if at(0, b"\xFF\xD8\xFF") {
+        "image/jpeg"
+    } else if at(0, b"\x89PNG\r\n\x1A\n") {
+        "image/png"
+    } else if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        "image/gif"
+    } else if at(0, b"RIFF") && at(8, b"WEBP") {
+        "image/webp"
+    } else if at(0, b"RIFF") && at(8, b"WAVE") {
+        "audio/wav"
+    } else if at(0, b"RIFF") && at(8, b"AVI ") {
+        "video/x-msvideo"
+    } else if at(0, b"II*\0") || at(0, b"MM\0*") {
+        "image/tiff"
+    } else if at(0, b"BM") && head.len() >= 14 {
+        "image/bmp"
+    } else if at(0, b"\0\0\x01\0") {
+        "image/x-icon"
+    } else if at(4, b"ftyp") {
+        match head.get(8..12)? {
+            b"avif" | b"avis" => "image/avif",
+            b"heic" | b"heix" | b"heim" | b"heis" => "image/heic",
+            b"qt  " => "video/quicktime",
+            b"M4A " | b"M4B " => "audio/mp4",
+            _ => "video/mp4",
+        }
+    } else if at(0, b"\x1A\x45\xDF\xA3") {
+        if head.windows(4).any(|w| w == b"webm") {
+            "video/webm"
+        } else {
+            "video/x-matroska"
+        }
+    } else if at(0, b"OggS") {
+        "audio/ogg"
+    } else if at(0, b"fLaC") {
+        "audio/flac"
+    } else if at(0, b"ID3") {
+        "audio/mpeg"
+    } else if head.len() >= 2 && head[0] == 0xFF && head[1] & 0xF6 == 0xF0 {
+        "audio/aac"
+    } else if head.len() >= 2 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0 {
+        "audio/mpeg"
+    } else if at(0, b"%PDF-") {
+        "application/pdf"
+    } else if at(0, b"\x1F\x8B") {
+        "application/gzip"
+    } else if at(0, b"7z\xBC\xAF\x27\x1C") {
+        "application/x-7z-compressed"
+    } else if at(0, b"Rar!\x1A\x07") {
+        "application/vnd.rar"
+    } else if at(0, b"\0asm") {
+        "application/wasm"
+    } else if at(0, b"wOFF") {
+        "font/woff"
+    } else if at(0, b"wOF2") {
+        "font/woff2"
+    } else {
+        return None;
+    };
+    Some(mime)
+}
+
+/// The bytes as text, if they look like text. A multi-byte character cut
+/// off at the end of `head` still counts.
+fn text(head: &[u8]) -> Option<&str> {
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    let printable = text
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0C' | '\x1B'));
+    printable.then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_binary_formats() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"\xFF\xD8\xFF\xE0\0\x10JFIF", "image/jpeg"),
+            (b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR", "image/png"),
+            (b"GIF89a\x01\0\x01\0", "image/gif"),
+            (b"RIFF\x24\0\0\0WEBPVP8 ", "image/webp"),
+            (b"RIFF\x24\0\0\0WAVEfmt ", "audio/wav"),
+            (b"\0\0\0\x1Cftypavif\0\0\0\0", "image/avif"),
+            (b"\0\0\0\x18ftypmp42\0\0\0\0", "video/mp4"),
+            (b"\0\0\0\x14ftypqt  \0\0\0\0", "video/quicktime"),
+            (
+                b"\x1A\x45\xDF\xA3\x9F\x42\x86\x81\x01\x42\x82\x84webm",
+                "video/webm",
+            ),
+            (
+                b"\x1A\x45\xDF\xA3\x9F\x42\x86\x81\x01\x42\x82\x88matroska",
+                "video/x-matroska",
+            ),
+            (b"ID3\x04\0\0\0\0\0\0", "audio/mpeg"),
+            (b"\xFF\xFB\x90\x64", "audio/mpeg"),
+            (b"\xFF\xF1\x50\x80", "audio/aac"),
+            (b"OggS\0\x02", "audio/ogg"),
+            (b"fLaC\0\0\0\x22", "audio/flac"),
+            (b"%PDF-1.7\n", "application/pdf"),
+            (b"\x1F\x8B\x08\0", "application/gzip"),
+            (b"\0asm\x01\0\0\0", "application/wasm"),
+        ];
+        for (head, expected) in cases {
+            assert_eq!(detect(head, "upload.bin"), *expected, "{:?}", head);
+        }
+    }
+
+    #[test]
+    fn test_detect_ignores_name_for_known_formats() {
+        assert_eq!(detect(b"\x89PNG\r\n\x1A\n", "notes.txt"), "image/png");
+        assert_eq!(detect(b"<!DOCTYPE html><script>", "photo.png"), "text/html");
+        assert_eq!(
+            detect(b"\x7FELF\x02\x01\x01\0", "photo.jpg"),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_detect_containers_by_name() {
+        let zip = b"PK\x03\x04\x14\0\x06\0";
+        assert_eq!(
+            detect(zip, "report.docx"),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        );
+        assert_eq!(detect(zip, "book.epub"), "application/epub+zip");
+        assert_eq!(detect(zip, "archive.zip"), "application/zip");
+        assert_eq!(detect(zip, "photo.png"), "application/zip");
+
+        let ole = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1\0\0";
+        assert_eq!(detect(ole, "old.doc"), "application/msword");
+        assert_eq!(detect(ole, "old.png"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_detect_text() {
+        assert_eq!(detect(b"hello world\n", "notes.txt"), "text/plain");
+        assert_eq!(detect(b"a,b\n1,2\n", "data.csv"), "text/csv");
+        assert_eq!(detect(b"{\"a\": 1}", "data.json"), "application/json");
+        assert_eq!(detect(b"just text", "photo.png"), "text/plain");
+        assert_eq!(detect(b"\xEF\xBB\xBFtext with BOM", "a.txt"), "text/plain");
+        assert_eq!(
+            detect(
+                b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\">",
+                "a.svg"
+            ),
+            "image/svg+xml"
+        );
+        assert_eq!(
+            detect(b"  <svg viewBox=\"0 0 1 1\">", "icon"),
+            "image/svg+xml"
+        );
+        // A UTF-8 character cut off by the sniffing window
+        assert_eq!(
+            detect("caf\u{e9}".as_bytes()[..4].as_ref(), "a.txt"),
+            "text/plain"
+        );
+        assert_eq!(detect(b"bin\0ary", "a.txt"), "application/octet-stream");
+    }
+}
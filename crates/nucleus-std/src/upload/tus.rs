@@ -0,0 +1,847 @@
+//! Resumable uploads with the tus protocol
+//!
+//! Implements tus 1.0.0 (<https://tus.io/protocols/resumable-upload>) with
+//! the `creation` and `termination` extensions. Each `PATCH` is kept as a
+//! part in storage, so after a dropped connection the client asks for the
+//! offset with `HEAD` and continues, on any replica sharing the storage.
+//!
+//! When the last byte arrives the parts are joined into the final file,
+//! which goes through the same type, size and hashing checks as multipart
+//! uploads.
+
+use super::{store, UploadConfig, UploadError, UploadedFile};
+use crate::storage::Storage;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{patch, post};
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Protocol version spoken by `TusServer`
+pub const TUS_VERSION: &str = "1.0.0";
+
+const TUS_EXTENSIONS: &str = "creation,termination";
+
+type CompleteHandler = Arc<dyn Fn(UploadedFile) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// An upload in progress, saved as `{staging}/{id}/info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TusUpload {
+    length: u64,
+    offset: u64,
+    metadata: HashMap<String, String>,
+    created_at: DateTime<Utc>,
+    /// Set once all bytes have arrived and the file is stored
+    file: Option<UploadedFile>,
+}
+
+/// tus endpoint storing finished files according to an `UploadConfig`
+///
+/// ```rust,ignore
+/// let tus = TusServer::new(UploadConfig::new().max_size(5 << 30), "/uploads/videos")
+///     .on_complete(|file| async move {
+///         println!("{} arrived ({} bytes)", file.original_name, file.size);
+///     });
+///
+/// let app = Router::new().nest("/uploads/videos", tus.router());
+/// ```
+pub struct TusServer {
+    config: UploadConfig,
+    base_url: String,
+    staging: Arc<dyn Storage>,
+    staging_prefix: String,
+    on_complete: Option<CompleteHandler>,
+}
+
+impl TusServer {
+    /// Serve uploads at `base_url`; unfinished uploads are kept under `tus/`
+    /// in the config's storage
+    pub fn new(config: UploadConfig, base_url: &str) -> Self {
+        Self {
+            staging: config.storage.clone(),
+            config,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            staging_prefix: "tus".to_string(),
+            on_complete: None,
+        }
+    }
+
+    /// Keep unfinished uploads under `prefix` in `storage` instead
+    pub fn with_staging(mut self, storage: Arc<dyn Storage>, prefix: &str) -> Self {
+        self.staging = storage;
+        self.staging_prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// Run `handler` for every finished upload
+    pub fn on_complete<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(UploadedFile) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_complete = Some(Arc::new(move |file| Box::pin(handler(file))));
+        self
+    }
+
+    /// Routes for the protocol; nest them at `base_url`
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/", post(create).options(options))
+            .route("/:id", patch(append).head(status).delete(terminate))
+            .with_state(Arc::new(self))
+    }
+
+    /// The stored file of a finished upload
+    pub async fn file(&self, id: &str) -> Result<Option<UploadedFile>, UploadError> {
+        Ok(self.load(id).await?.and_then(|upload| upload.file))
+    }
+
+    /// Delete uploads that haven't received data within `max_age`;
+    /// returns how many were removed
+    pub async fn cleanup(&self, max_age: Duration) -> Result<usize, UploadError> {
+        let cutoff =
+            Utc::now() - chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX);
+        let mut latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for object in self.staging.list(&self.key("")).await? {
+            let rest = &object.key[self.key("").len()..];
+            if let Some((id, _)) = rest.split_once('/') {
+                let seen = latest.entry(id.to_string()).or_insert(object.last_modified);
+                *seen = (*seen).max(object.last_modified);
+            }
+        }
+
+        let mut removed = 0;
+        for (id, last_modified) in latest {
+            if last_modified < cutoff {
+                self.remove(&id).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn key(&self, path: &str) -> String {
+        if self.staging_prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.staging_prefix, path)
+        }
+    }
+
+    fn info_key(&self, id: &str) -> String {
+        self.key(&format!("{}/info", id))
+    }
+
+    fn part_key(&self, id: &str, offset: u64) -> String {
+        self.key(&format!("{}/part-{:020}", id, offset))
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<TusUpload>, UploadError> {
+        if !is_upload_id(id) {
+            return Ok(None);
+        }
+        let mut upload: TusUpload = match self.staging.get(&self.info_key(id)).await {
+            Ok(object) => serde_json::from_slice(&object.data)
+                .map_err(|e| UploadError::Interrupted(format!("corrupt upload state: {}", e)))?,
+            Err(crate::storage::StorageError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        // Parts count once stored, even if their request failed before saving
+        while upload.file.is_none() && upload.offset < upload.length {
+            match self.staging.head(&self.part_key(id, upload.offset)).await? {
+                Some(part) if part.size > 0 => upload.offset += part.size,
+                _ => break,
+            }
+        }
+        Ok(Some(upload))
+    }
+
+    async fn save(&self, id: &str, upload: &TusUpload) -> Result<(), UploadError> {
+        let json = serde_json::to_vec(upload).expect("upload state serializes");
+        self.staging
+            .put(&self.info_key(id), json.into(), "application/json")
+            .await?;
+        Ok(())
+    }
+
+    /// Delete everything staged for an upload
+    async fn remove(&self, id: &str) -> Result<(), UploadError> {
+        for object in self.staging.list(&self.key(&format!("{}/", id))).await? {
+            self.staging.delete(&object.key).await?;
+        }
+        Ok(())
+    }
+
+    /// Join the parts into the final file and drop them
+    async fn finish(&self, id: &str, upload: &mut TusUpload) -> Result<UploadedFile, UploadError> {
+        let parts: Vec<String> = self
+            .staging
+            .list(&self.key(&format!("{}/part-", id)))
+            .await?
+            .into_iter()
+            .map(|object| object.key)
+            .collect();
+        // Parts are streamed one after another, so only a chunk at a time is in memory
+        let staging = self.staging.clone();
+        let chunks = futures_util::stream::iter(parts)
+            .then(move |key| {
+                let staging = staging.clone();
+                async move { staging.get_stream(&key).await }
+            })
+            .try_flatten()
+            .map_err(UploadError::from);
+
+        let name = upload
+            .metadata
+            .get("filename")
+            .and_then(|name| name.rsplit(['/', '\\']).next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("upload_{}", id));
+        let file = store(&self.config, name, "file".to_string(), Box::pin(chunks)).await?;
+
+        upload.file = Some(file.clone());
+        self.save(id, upload).await?;
+        for part in self
+            .staging
+            .list(&self.key(&format!("{}/part-", id)))
+            .await?
+        {
+            self.staging.delete(&part.key).await?;
+        }
+        Ok(file)
+    }
+}
+
+/// Upload ids are generated by `create`; anything else can't name one
+fn is_upload_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn reply(status: StatusCode) -> Response {
+    let mut response = status.into_response();
+    response
+        .headers_mut()
+        .insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+    response
+}
+
+fn reply_with(status: StatusCode, headers: &[(&'static str, String)]) -> Response {
+    let mut response = reply(status);
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            response.headers_mut().insert(*name, value);
+        }
+    }
+    response
+}
+
+fn error_reply(error: UploadError) -> Response {
+    let status = match &error {
+        UploadError::FileTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        UploadError::InvalidMimeType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        UploadError::ImageError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        UploadError::StorageError(crate::storage::StorageError::InvalidKey(_)) => {
+            StatusCode::BAD_REQUEST
+        }
+        _ => {
+            tracing::error!("tus upload failed: {}", error);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    let mut response = reply(status);
+    *response.body_mut() = Body::from(error.to_string());
+    response
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// `412` unless the client speaks our version
+fn version_mismatch(headers: &HeaderMap) -> Option<Response> {
+    match header(headers, "tus-resumable") {
+        Some(TUS_VERSION) => None,
+        _ => Some(reply_with(
+            StatusCode::PRECONDITION_FAILED,
+            &[("tus-version", TUS_VERSION.to_string())],
+        )),
+    }
+}
+
+/// `key base64(value)` pairs separated by commas; the value is optional
+fn parse_metadata(raw: &str) -> Option<HashMap<String, String>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once(' ') {
+            Some((key, value)) => {
+                let value = String::from_utf8(STANDARD.decode(value.trim()).ok()?).ok()?;
+                Some((key.to_string(), value))
+            }
+            None => Some((pair.to_string(), String::new())),
+        })
+        .collect()
+}
+
+fn encode_metadata(metadata: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<String> = metadata
+        .iter()
+        .map(|(key, value)| match value.is_empty() {
+            true => key.clone(),
+            false => format!("{} {}", key, STANDARD.encode(value)),
+        })
+        .collect();
+    pairs.sort();
+    pairs.join(",")
+}
+
+async fn options(State(server): State<Arc<TusServer>>) -> Response {
+    reply_with(
+        StatusCode::NO_CONTENT,
+        &[
+            ("tus-version", TUS_VERSION.to_string()),
+            ("tus-extension", TUS_EXTENSIONS.to_string()),
+            ("tus-max-size", server.config.max_file_size.to_string()),
+        ],
+    )
+}
+
+async fn create(State(server): State<Arc<TusServer>>, headers: HeaderMap) -> Response {
+    if let Some(response) = version_mismatch(&headers) {
+        return response;
+    }
+    let Some(length) = header(&headers, "upload-length").and_then(|v| v.parse::<u64>().ok()) else {
+        return reply(StatusCode::BAD_REQUEST);
+    };
+    if length > server.config.max_file_size as u64 {
+        return error_reply(UploadError::FileTooLarge {
+            max: server.config.max_file_size,
+            actual: length as usize,
+        });
+    }
+    let Some(metadata) = parse_metadata(header(&headers, "upload-metadata").unwrap_or("")) else {
+        return reply(StatusCode::BAD_REQUEST);
+    };
+
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let mut upload = TusUpload {
+        length,
+        offset: 0,
+        metadata,
+        created_at: Utc::now(),
+        file: None,
+    };
+    let saved = match length {
+        // Nothing will be PATCHed, so the upload is already complete
+        0 => server.finish(&id, &mut upload).await.map(Some),
+        _ => server.save(&id, &upload).await.map(|_| None),
+    };
+    let file = match saved {
+        Ok(file) => file,
+        Err(e) => return error_reply(e),
+    };
+    if let (Some(file), Some(handler)) = (file, &server.on_complete) {
+        handler(file).await;
+    }
+
+    reply_with(
+        StatusCode::CREATED,
+        &[
+            ("location", format!("{}/{}", server.base_url, id)),
+            ("upload-offset", upload.offset.to_string()),
+        ],
+    )
+}
+
+async fn status(
+    State(server): State<Arc<TusServer>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(response) = version_mismatch(&headers) {
+        return response;
+    }
+    match server.load(&id).await {
+        Ok(Some(upload)) => {
+            let mut headers = vec![
+                ("upload-offset", upload.offset.to_string()),
+                ("upload-length", upload.length.to_string()),
+                ("cache-control", "no-store".to_string()),
+            ];
+            if !upload.metadata.is_empty() {
+                headers.push(("upload-metadata", encode_metadata(&upload.metadata)));
+            }
+            reply_with(StatusCode::OK, &headers)
+        }
+        Ok(None) => reply(StatusCode::NOT_FOUND),
+        Err(e) => error_reply(e),
+    }
+}
+
+async fn append(
+    State(server): State<Arc<TusServer>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    if let Some(response) = version_mismatch(&headers) {
+        return response;
+    }
+    if header(&headers, header::CONTENT_TYPE.as_str()) != Some("application/offset+octet-stream") {
+        return reply(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+    let Some(offset) = header(&headers, "upload-offset").and_then(|v| v.parse::<u64>().ok()) else {
+        return reply(StatusCode::BAD_REQUEST);
+    };
+    let mut upload = match server.load(&id).await {
+        Ok(Some(upload)) => upload,
+        Ok(None) => return reply(StatusCode::NOT_FOUND),
+        Err(e) => return error_reply(e),
+    };
+    if offset != upload.offset || upload.file.is_some() {
+        return reply(StatusCode::CONFLICT);
+    }
+
+    // Store this request's bytes as one part, refusing more than remain.
+    // The part is only created if absent, so of two requests racing for the
+    // same offset, on this or another replica, one gets 409.
+    let remaining = upload.length - upload.offset;
+    let received = Arc::new(Mutex::new(0u64));
+    let chunks = {
+        let received = received.clone();
+        body.into_data_stream().map(move |chunk| {
+            let chunk = chunk.map_err(|e| crate::storage::StorageError::Backend(e.to_string()))?;
+            let mut received = received.lock().unwrap();
+            *received += chunk.len() as u64;
+            if *received > remaining {
+                return Err(crate::storage::StorageError::Backend(
+                    "body exceeds Upload-Length".to_string(),
+                ));
+            }
+            Ok::<Bytes, _>(chunk)
+        })
+    };
+    let written = server
+        .staging
+        .put_stream_if_absent(
+            &server.part_key(&id, offset),
+            Box::pin(chunks),
+            "application/octet-stream",
+        )
+        .await;
+    let received = *received.lock().unwrap();
+    if received > remaining {
+        return reply(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    match written {
+        Err(crate::storage::StorageError::AlreadyExists(_)) => return reply(StatusCode::CONFLICT),
+        // The client retries from the last saved offset
+        Err(e) => return error_reply(UploadError::Interrupted(e.to_string())),
+        Ok(()) => {}
+    }
+    if received == 0 {
+        let _ = server.staging.delete(&server.part_key(&id, offset)).await;
+    }
+
+    upload.offset += received;
+    if upload.offset < upload.length {
+        if let Err(e) = server.save(&id, &upload).await {
+            return error_reply(e);
+        }
+    } else {
+        match server.finish(&id, &mut upload).await {
+            Ok(file) => {
+                if let Some(handler) = &server.on_complete {
+                    handler(file).await;
+                }
+            }
+            Err(e) => {
+                // The parts can never form an acceptable file
+                let _ = server.remove(&id).await;
+                return error_reply(e);
+            }
+        }
+    }
+
+    reply_with(
+        StatusCode::NO_CONTENT,
+        &[("upload-offset", upload.offset.to_string())],
+    )
+}
+
+async fn terminate(
+    State(server): State<Arc<TusServer>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(response) = version_mismatch(&headers) {
+        return response;
+    }
+    match server.load(&id).await {
+        Ok(Some(_)) => match server.remove(&id).await {
+            Ok(()) => reply(StatusCode::NO_CONTENT),
+            Err(e) => error_reply(e),
+        },
+        Ok(None) => reply(StatusCode::NOT_FOUND),
+        Err(e) => error_reply(e),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+
+    fn request(method: &str, uri: &str) -> axum::http::request::Builder {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("tus-resumable", TUS_VERSION)
+    }
+
+    fn patch_request(uri: &str, offset: u64, data: &[u8]) -> Request<Body> {
+        request("PATCH", uri)
+            .header("content-type", "application/offset+octet-stream")
+            .header("upload-offset", offset.to_string())
+            .body(Body::from(data.to_vec()))
+            .unwrap()
+    }
+
+    async fn create_upload(app: &Router, length: usize, name: &str) -> String {
+        let response = app
+            .clone()
+            .oneshot(
+                request("POST", "/files")
+                    .header("upload-length", length.to_string())
+                    .header(
+                        "upload-metadata",
+                        format!("filename {},is_private", STANDARD.encode(name)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["tus-resumable"], TUS_VERSION);
+        response.headers()["location"].to_str().unwrap().to_string()
+    }
+
+    fn server(storage: Arc<MemoryStorage>) -> (Router, Arc<Mutex<Vec<UploadedFile>>>) {
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let seen = completed.clone();
+        let config = UploadConfig::new()
+            .backend(storage)
+            .prefix("videos")
+            .max_size(1024)
+            .images_only();
+        let tus = TusServer::new(config, "/files").on_complete(move |file| {
+            let seen = seen.clone();
+            async move { seen.lock().unwrap().push(file) }
+        });
+        (Router::new().nest("/files", tus.router()), completed)
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload() {
+        let storage = Arc::new(MemoryStorage::new());
+        let (app, completed) = server(storage.clone());
+        let location = create_upload(&app, PNG.len(), "dot.png").await;
+        assert!(location.starts_with("/files/"));
+
+        let response = app
+            .clone()
+            .oneshot(patch_request(&location, 0, &PNG[..10]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()["upload-offset"], "10");
+
+        // The client lost track; it asks where to continue
+        let response = app
+            .clone()
+            .oneshot(request("HEAD", &location).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["upload-offset"], "10");
+        assert_eq!(response.headers()["upload-length"], PNG.len().to_string());
+        assert_eq!(response.headers()["cache-control"], "no-store");
+        let metadata = response.headers()["upload-metadata"].to_str().unwrap();
+        assert_eq!(parse_metadata(metadata).unwrap()["filename"], "dot.png");
+
+        let response = app
+            .clone()
+            .oneshot(patch_request(&location, 3, &PNG[3..]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = app
+            .clone()
+            .oneshot(patch_request(&location, 10, &PNG[10..]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()["upload-offset"], PNG.len().to_string());
+
+        let files = completed.lock().unwrap().clone();
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.original_name, "dot.png");
+        assert_eq!(file.mime_type, "image/png");
+        assert_eq!(file.size, PNG.len());
+        assert_eq!(
+            file.sha256,
+            hex::encode(<sha2::Sha256 as sha2::Digest>::digest(PNG))
+        );
+        assert!(file.key.starts_with("videos/"));
+        assert_eq!(&storage.get(&file.key).await.unwrap().data[..], PNG);
+
+        // Only the file and the upload's state remain
+        let keys: Vec<String> = storage
+            .list("")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys
+            .iter()
+            .any(|k| k.starts_with("tus/") && k.ends_with("/info")));
+
+        let response = app
+            .oneshot(patch_request(&location, PNG.len() as u64, b"x"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_patches_at_same_offset() {
+        let storage = Arc::new(MemoryStorage::new());
+        let (app, _) = server(storage);
+        let location = create_upload(&app, PNG.len(), "dot.png").await;
+
+        // Two clients send offset 0; each body stays open until both have started
+        let mut senders = Vec::new();
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            let (tx, rx) = tokio::sync::mpsc::channel::<Result<Bytes, std::io::Error>>(1);
+            let body = futures_util::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|chunk| (chunk, rx))
+            });
+            let patch = request("PATCH", &location)
+                .header("content-type", "application/offset+octet-stream")
+                .header("upload-offset", "0")
+                .body(Body::from_stream(body))
+                .unwrap();
+            responses.push(tokio::spawn(app.clone().oneshot(patch)));
+            senders.push(tx);
+        }
+        for tx in &senders {
+            tx.send(Ok(Bytes::copy_from_slice(&PNG[..5])))
+                .await
+                .unwrap();
+            // Returns once the handler has read the first chunk
+            tx.send(Ok(Bytes::copy_from_slice(&PNG[5..10])))
+                .await
+                .unwrap();
+        }
+        drop(senders);
+
+        let mut statuses = Vec::new();
+        for response in responses {
+            statuses.push(response.await.unwrap().unwrap().status());
+        }
+        statuses.sort();
+        assert_eq!(statuses, vec![StatusCode::NO_CONTENT, StatusCode::CONFLICT]);
+
+        let response = app
+            .oneshot(request("HEAD", &location).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["upload-offset"], "10");
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let storage = Arc::new(MemoryStorage::new());
+        let (app, _) = server(storage);
+
+        let response = app
+            .clone()
+            .oneshot(Request::options("/files").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()["tus-version"], TUS_VERSION);
+        assert_eq!(response.headers()["tus-extension"], TUS_EXTENSIONS);
+        assert_eq!(response.headers()["tus-max-size"], "1024");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/files")
+                    .header("tus-resumable", "0.2.2")
+                    .header("upload-length", "10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = app
+            .clone()
+            .oneshot(
+                request("POST", "/files")
+                    .header("upload-length", "4096")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let location = create_upload(&app, 4, "a.png").await;
+        let response = app
+            .clone()
+            .oneshot(
+                request("PATCH", &location)
+                    .header("content-type", "application/octet-stream")
+                    .header("upload-offset", "0")
+                    .body(Body::from("abcd"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = app
+            .clone()
+            .oneshot(patch_request(&location, 0, b"abcdef"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = app
+            .clone()
+            .oneshot(patch_request(
+                "/files/0123456789abcdef0123456789abcdef",
+                0,
+                b"a",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app
+            .oneshot(patch_request("/files/..", 0, b"a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_content_discards_upload() {
+        let storage = Arc::new(MemoryStorage::new());
+        let (app, completed) = server(storage.clone());
+
+        // Named like an image, but it's HTML
+        let html = b"<!DOCTYPE html><script>alert(1)</script>";
+        let location = create_upload(&app, html.len(), "cat.png").await;
+        let response = app
+            .clone()
+            .oneshot(patch_request(&location, 0, html))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(completed.lock().unwrap().is_empty());
+        assert!(storage.is_empty());
+
+        let response = app
+            .oneshot(request("HEAD", &location).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_terminate_and_cleanup() {
+        let storage = Arc::new(MemoryStorage::new());
+        let staging = Arc::new(MemoryStorage::new());
+        let tus = TusServer::new(UploadConfig::new().backend(storage.clone()), "/files")
+            .with_staging(staging.clone(), "");
+        let app = Router::new().nest("/files", tus.router());
+
+        let location = create_upload(&app, 8, "a.txt").await;
+        app.clone()
+            .oneshot(patch_request(&location, 0, b"abcd"))
+            .await
+            .unwrap();
+        assert_eq!(staging.len(), 2);
+        assert!(storage.is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", &location).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(staging.is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(request("HEAD", &location).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let tus = TusServer::new(UploadConfig::new().backend(storage.clone()), "/files")
+            .with_staging(staging.clone(), "");
+        create_upload(&app, 8, "b.txt").await;
+        assert_eq!(tus.cleanup(Duration::from_secs(60)).await.unwrap(), 0);
+        assert_eq!(tus.cleanup(Duration::ZERO).await.unwrap(), 1);
+        assert!(staging.is_empty());
+    }
+
+    #[test]
+    fn test_metadata_encoding() {
+        let metadata =
+            parse_metadata("filename d29ybGRfZG9taW5hdGlvbl9wbGFuLnBkZg==,is_confidential")
+                .unwrap();
+        assert_eq!(metadata["filename"], "world_domination_plan.pdf");
+        assert_eq!(metadata["is_confidential"], "");
+        assert_eq!(
+            parse_metadata(&encode_metadata(&metadata)).unwrap(),
+            metadata
+        );
+        assert!(parse_metadata("filename not-base64!").is_none());
+        assert!(parse_metadata("").unwrap().is_empty());
+    }
+}
@@ -37,11 +37,16 @@ pub async fn fetch_admin_data() -> AdminData {
     }
 }
 
-// Manual implementation to avoid macro/crate version potential conflicts
-impl<'r> nucleus_std::sqlx::FromRow<'r, nucleus_std::sqlx::sqlite::SqliteRow> for Subscriber {
-    fn from_row(
-        row: &'r nucleus_std::sqlx::sqlite::SqliteRow,
-    ) -> Result<Self, nucleus_std::sqlx::Error> {
+// Manual implementation to avoid macro/crate version potential conflicts.
+// Generic over the row type so Photon can decode it on any backend.
+impl<'r, R> nucleus_std::sqlx::FromRow<'r, R> for Subscriber
+where
+    R: Row,
+    &'r str: nucleus_std::sqlx::ColumnIndex<R>,
+    i64: nucleus_std::sqlx::Decode<'r, R::Database> + nucleus_std::sqlx::Type<R::Database>,
+    String: nucleus_std::sqlx::Decode<'r, R::Database> + nucleus_std::sqlx::Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, nucleus_std::sqlx::Error> {
         Ok(Self {
             id: row.try_get("id")?,
             email: row.try_get("email")?,
@@ -63,10 +68,14 @@ pub struct EmailTemplate {
     pub created_at: String,
 }
 
-impl<'r> nucleus_std::sqlx::FromRow<'r, nucleus_std::sqlx::sqlite::SqliteRow> for EmailTemplate {
-    fn from_row(
-        row: &'r nucleus_std::sqlx::sqlite::SqliteRow,
-    ) -> Result<Self, nucleus_std::sqlx::Error> {
+impl<'r, R> nucleus_std::sqlx::FromRow<'r, R> for EmailTemplate
+where
+    R: Row,
+    &'r str: nucleus_std::sqlx::ColumnIndex<R>,
+    i64: nucleus_std::sqlx::Decode<'r, R::Database> + nucleus_std::sqlx::Type<R::Database>,
+    String: nucleus_std::sqlx::Decode<'r, R::Database> + nucleus_std::sqlx::Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, nucleus_std::sqlx::Error> {
         Ok(Self {
             id: row.try_get("id")?,
             name: row.try_get("name")?,
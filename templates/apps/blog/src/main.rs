@@ -7,5 +7,7 @@ async fn main() {
 
     // Start Reactor with custom router
     let app_router = controllers::posts::router();
-    NucleusRuntime::start_with_router(None, None, Some(app_router)).await;
+    NucleusRuntime::start_with_router(None, None, Some(app_router))
+        .await
+        .expect("Atom Reactor failed");
 }
@@ -15,5 +15,7 @@ async fn main() {
     // We are relying on the declarative 'index.ncl' which the runtime will pick up.
 
     // We pass None for config to use defaults (which includes looking in src/views)
-    NucleusRuntime::start(None, None).await;
+    NucleusRuntime::start(None, None)
+        .await
+        .expect("Atom Reactor failed");
 }
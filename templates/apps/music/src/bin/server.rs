@@ -333,7 +333,8 @@ async fn main() {
         .route("/navbar", get(handle_navbar))
         .route("/LikeButton", get(handle_LikeButton))
         .nest_service("/pkg", ServeDir::new("static/pkg"))
-        .merge(services::api::routes());
+        .merge(services::api::routes())
+        .merge(services::uploads::routes());
 
     // Auto-Inject Middleware if `src/middleware.rs` exists
     let app = app;
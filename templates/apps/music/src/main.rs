@@ -54,7 +54,8 @@ async fn main() {
     let app = Router::new()
         .nest_service("/static", ServeDir::new("static"))
         .route_service("/", ServeFile::new("static/index.html"))
-        .merge(services::api::routes());
+        .merge(services::api::routes())
+        .merge(services::uploads::routes());
 
     // 5. Start Server
     let addr = SocketAddr::from(([0, 0, 0, 0], 3001));
@@ -1,3 +1,4 @@
 pub mod api;
 pub mod metadata;
 pub mod scanner;
+pub mod uploads;
@@ -0,0 +1,38 @@
+use crate::models::Video;
+use axum::Router;
+use nucleus_std::photon::Model;
+use nucleus_std::storage::LocalStorage;
+use nucleus_std::upload::{TusServer, UploadConfig};
+use std::sync::Arc;
+
+const VIDEO_DIR: &str = "static/music/videos";
+
+// Resumable video uploads (tus protocol), so large files survive dropped connections
+pub fn routes() -> Router {
+    let config = UploadConfig::new()
+        .backend(Arc::new(LocalStorage::new(VIDEO_DIR)))
+        .max_size(8 * 1024 * 1024 * 1024) // 8 GB
+        .allowed_types(vec!["video/*"]);
+
+    // Unfinished uploads stay out of the scanned library
+    let tus = TusServer::new(config, "/api/uploads/videos")
+        .with_staging(Arc::new(LocalStorage::new("uploads/tus")), "")
+        .on_complete(|file| async move {
+            let title = std::path::Path::new(&file.original_name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.original_name.clone());
+            println!("Uploaded Video: {}", title);
+
+            if let Err(e) = Video::create()
+                .value("title", title.as_str())
+                .value("path", format!("{}/{}", VIDEO_DIR, file.key))
+                .execute()
+                .await
+            {
+                eprintln!("❌ Failed to save video {} ({}): {}", title, file.key, e);
+            }
+        });
+
+    Router::new().nest("/api/uploads/videos", tus.router())
+}
@@ -8,5 +8,7 @@ async fn main() {
     println!("🛒 Nucleus Shop active on http://127.0.0.1:3000");
     // Initialize store to verify compilation/logic
     let _store = store::ShopStore::demo();
-    NucleusRuntime::start(None, None).await;
+    NucleusRuntime::start(None, None)
+        .await
+        .expect("Atom Reactor failed");
 }
@@ -5,5 +5,7 @@ async fn main() {
     println!("✨ Showcase running on http://127.0.0.1:3000");
 
     // Start Reactor with default config (scans src/views)
-    NucleusRuntime::start(None, None).await;
+    NucleusRuntime::start(None, None)
+        .await
+        .expect("Atom Reactor failed");
 }
@@ -10,22 +10,15 @@ async fn main() {
 
     // Load routes from views (simplified - normally ncc does this)
     let mut routes = HashMap::new();
-    routes.insert(
-        "home".to_string(),
-        include_str!("views/index.ncl").to_string(),
-    );
-    routes.insert(
-        "hello".to_string(),
-        include_str!("views/hello.ncl").to_string(),
-    );
+    routes.insert("home".to_string(), include_str!("views/index.ncl").into());
+    routes.insert("hello".to_string(), include_str!("views/hello.ncl").into());
     routes.insert(
         "counter".to_string(),
-        include_str!("views/counter.ncl").to_string(),
-    );
-    routes.insert(
-        "todo".to_string(),
-        include_str!("views/todo.ncl").to_string(),
+        include_str!("views/counter.ncl").into(),
     );
+    routes.insert("todo".to_string(), include_str!("views/todo.ncl").into());
 
-    atom::start_reactor(Some(routes), None).await;
+    atom::start_reactor(Some(routes), None)
+        .await
+        .expect("Atom Reactor failed");
 }